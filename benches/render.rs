@@ -0,0 +1,65 @@
+use ai_coder_interface_rs::App;
+use ai_coder_interface_rs::ui;
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use ratatui::{Terminal, backend::TestBackend};
+
+/// Output of alternating AI answers and bash listings with about `lines` lines
+fn synthetic_output(lines: usize) -> String {
+    let separator = "━".repeat(40);
+    let mut output = String::new();
+    let mut count = 0;
+    let mut exchange = 0;
+    while count < lines {
+        if exchange % 2 == 0 {
+            output.push_str(&format!("{}\n❯ explain step {}\n", separator, exchange));
+            for i in 0..20 {
+                output.push_str(&format!(
+                    "Line {} of the answer, long enough to wrap in a narrow terminal window once or twice.\n",
+                    i
+                ));
+            }
+        } else {
+            output.push_str(&format!("{}\n$ ls -la src/{}\n", separator, exchange));
+            for i in 0..30 {
+                output.push_str(&format!(
+                    "-rw-r--r--  1 user  staff  {:>6} file_{}.rs\n",
+                    i * 97,
+                    i
+                ));
+            }
+            output.push_str("[⏱️ 0.01s | ✓ | 📊 30]\n");
+        }
+        count += if exchange % 2 == 0 { 22 } else { 33 };
+        exchange += 1;
+    }
+    output
+}
+
+fn render_benchmark(c: &mut Criterion) {
+    let mut group = c.benchmark_group("render");
+    group.sample_size(10);
+
+    for lines in [10_000, 100_000, 1_000_000] {
+        let mut app = App::new();
+        app.add_output(synthetic_output(lines));
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("top", lines), &lines, |b, _| {
+            app.scroll_offset = 0;
+            b.iter(|| {
+                terminal.draw(|f| ui::render(f, &mut app)).unwrap();
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("bottom", lines), &lines, |b, _| {
+            app.scroll_offset = u16::MAX;
+            b.iter(|| {
+                terminal.draw(|f| ui::render(f, &mut app)).unwrap();
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, render_benchmark);
+criterion_main!(benches);
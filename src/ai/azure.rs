@@ -0,0 +1,132 @@
+//! Azure OpenAI Service
+//!
+//! Azure serves OpenAI models from deployments of a resource at
+//! `https://<resource>.openai.azure.com/openai/deployments/<deployment>`, with
+//! the API version as a query parameter and the key in an `api-key` header.
+//! Completions go through the OpenAI-compatible client; the deployments
+//! configured in `ai.azure.models` are the models.
+
+use crate::ai::extras::RequestExtras;
+use crate::ai::oauth::TokenSource;
+use crate::ai::types::{AIClient, AIError, AIResponse, ChunkSender, ModelCosts, ProgressCallback};
+use crate::ai::{OpenAICompatClient, Provider};
+use crate::config::{AzureOpenAIConfig, ModelConfig};
+use async_trait::async_trait;
+use reqwest::header::HeaderName;
+
+/// API version used unless one is configured
+pub const DEFAULT_API_VERSION: &str = "2024-10-21";
+
+/// Header Azure reads API keys from
+const API_KEY_HEADER: &str = "api-key";
+
+/// Base URL of the resource: the configured endpoint, or the one of the resource name
+pub fn endpoint(settings: &AzureOpenAIConfig) -> Result<String, AIError> {
+    let endpoint = settings.endpoint.trim().trim_end_matches('/');
+    if !endpoint.is_empty() {
+        return Ok(endpoint.to_string());
+    }
+    let resource = settings.resource.trim();
+    if resource.is_empty() {
+        return Err(AIError::ConfigError(
+            "Azure OpenAI has no resource; set it with /config azure resource <name>".to_string(),
+        ));
+    }
+    Ok(format!("https://{}.openai.azure.com", resource))
+}
+
+/// URL of a deployment, which the OpenAI paths such as `/chat/completions` follow
+pub fn deployment_url(settings: &AzureOpenAIConfig, deployment: &str) -> Result<String, AIError> {
+    if deployment.trim().is_empty() {
+        return Err(AIError::ConfigError(
+            "Azure OpenAI has no deployment; add one with /config azure deployment <name>"
+                .to_string(),
+        ));
+    }
+    Ok(format!(
+        "{}/openai/deployments/{}",
+        endpoint(settings)?,
+        deployment.trim()
+    ))
+}
+
+/// Client for the deployments of an Azure OpenAI resource
+pub struct AzureOpenAIClient {
+    inner: OpenAICompatClient,
+    deployments: Vec<String>,
+}
+
+impl AzureOpenAIClient {
+    /// Create a client for the deployment named by `model`
+    pub fn new(
+        settings: &AzureOpenAIConfig,
+        model: &ModelConfig,
+        extras: RequestExtras,
+    ) -> Result<Self, AIError> {
+        let api_version = match settings.api_version.trim() {
+            "" => DEFAULT_API_VERSION,
+            version => version,
+        };
+        let mut inner = OpenAICompatClient::new(
+            Provider::AzureOpenAI,
+            deployment_url(settings, &model.name)?,
+            settings.api_key.clone(),
+            model,
+        )
+        .with_extras(extras)?
+        .with_key_header(HeaderName::from_static(API_KEY_HEADER))
+        .with_query("api-version", api_version);
+        if let Some(oauth) = settings.oauth.clone() {
+            inner = inner.with_auth(TokenSource::new(Provider::AzureOpenAI, oauth));
+        }
+        Ok(Self {
+            inner,
+            deployments: settings.models.iter().map(|m| m.name.clone()).collect(),
+        })
+    }
+
+    /// Ask for reproducible sampling with `seed`
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.inner = self.inner.with_seed(seed);
+        self
+    }
+}
+
+#[async_trait]
+impl AIClient for AzureOpenAIClient {
+    async fn generate(&self, prompt: &str, context: Option<&str>) -> Result<AIResponse, AIError> {
+        self.inner.generate(prompt, context).await
+    }
+
+    async fn generate_with_progress(
+        &self,
+        prompt: &str,
+        context: Option<&str>,
+        on_progress: ProgressCallback,
+    ) -> Result<AIResponse, AIError> {
+        self.inner
+            .generate_with_progress(prompt, context, on_progress)
+            .await
+    }
+
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        context: Option<&str>,
+        chunks: ChunkSender,
+        on_progress: ProgressCallback,
+    ) -> Result<AIResponse, AIError> {
+        self.inner
+            .generate_stream(prompt, context, chunks, on_progress)
+            .await
+    }
+
+    /// The configured deployments; listing them needs the Azure management API
+    async fn models(&self) -> Result<Vec<String>, AIError> {
+        Ok(self.deployments.clone())
+    }
+
+    fn get_model_costs(&self, model: &str) -> ModelCosts {
+        self.inner.get_model_costs(model)
+    }
+}
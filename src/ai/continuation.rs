@@ -0,0 +1,139 @@
+//! Responses that came back empty or cut off
+//!
+//! A response the model stopped at `max_tokens` ends mid-sentence, often
+//! inside a code block, and an empty one leaves nothing to show. Either is
+//! recorded in the task of the prompt, and a banner offers to continue the
+//! cut-off answer or to send the prompt again with a higher `max_tokens`.
+//! A continuation is asked to pick up where the answer stops; text it
+//! repeats from the end of the answer is dropped before it is joined on.
+//! The usage of every part is added up, so that the joined answer reports
+//! what it cost as a whole.
+
+use super::{AIResponse, TokenUsage};
+use serde::{Deserialize, Serialize};
+
+/// Characters at the start of a continuation compared with the end of the answer
+const MAX_OVERLAP: usize = 400;
+
+/// Shorter repeats are more likely a coincidence than the model starting over
+const MIN_OVERLAP: usize = 8;
+
+/// Why a response needs the user's attention
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Incomplete {
+    /// Nothing but whitespace came back
+    Empty,
+    /// The model stopped at `max_tokens`
+    Truncated {
+        /// The answer up to where it stopped
+        partial: String,
+        /// Tokens generated before it stopped
+        tokens: usize,
+    },
+}
+
+/// Whether `response` came back empty or cut off
+pub fn detect(response: &AIResponse) -> Option<Incomplete> {
+    if response.content.trim().is_empty() {
+        // Reasoning models can spend the whole budget thinking
+        Some(Incomplete::Empty)
+    } else if response.truncated {
+        Some(Incomplete::Truncated {
+            partial: response.content.clone(),
+            tokens: response.usage.completion_tokens,
+        })
+    } else {
+        None
+    }
+}
+
+/// The `max_tokens` offered for a retry after `current` was not enough
+pub fn raised_max_tokens(current: usize) -> usize {
+    current.max(1).saturating_mul(2)
+}
+
+/// Prompt asking the model to continue `partial`, its cut-off answer to `prompt`
+pub fn continuation_prompt(prompt: &str, partial: &str) -> String {
+    format!(
+        "{}\n\n---\nYour answer to the request above was cut off at the token limit. \
+         This is what you wrote so far:\n\n{}\n\n---\nContinue exactly where the answer \
+         stops, mid-word or mid-line if needed. Do not repeat anything already written \
+         and do not add an introduction.",
+        prompt, partial
+    )
+}
+
+/// The part of `continuation` that is new after `partial`
+///
+/// Models often start a continuation by repeating the last words or line of
+/// the answer; the longest such repeat is left out.
+pub fn new_text<'a>(partial: &str, continuation: &'a str) -> &'a str {
+    let partial = partial.trim_end();
+    let start = continuation.trim_start();
+    start
+        .char_indices()
+        .map(|(idx, ch)| idx + ch.len_utf8())
+        .take(MAX_OVERLAP)
+        .filter(|&end| end >= MIN_OVERLAP && partial.ends_with(&start[..end]))
+        .last()
+        .map_or(continuation, |end| &start[end..])
+}
+
+/// `partial` with `continuation` joined on, without the text it repeats
+pub fn stitch(partial: &str, continuation: &str) -> String {
+    format!("{}{}", partial, new_text(partial, continuation))
+}
+
+/// `message` with `continuation` joined on where `partial` ends in it
+///
+/// The message may hold more than the answer, such as the output of code
+/// blocks that ran or notes after it, so the answer is found by its last
+/// line. `None` when the message does not contain it.
+pub fn join_into(message: &str, partial: &str, continuation: &str) -> Option<String> {
+    let answer = partial.trim_end();
+    let last_line = answer
+        .lines()
+        .last()
+        .filter(|line| !line.trim().is_empty())?;
+    let end = message.rfind(last_line)? + last_line.len();
+    Some(format!(
+        "{}{}{}{}",
+        &message[..end],
+        &partial[answer.len()..],
+        new_text(partial, continuation),
+        &message[end..]
+    ))
+}
+
+/// Usage of the parts of an answer that was continued
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnswerUsage {
+    /// Responses the answer is made of
+    pub parts: usize,
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    /// Estimated cost in dollars
+    pub cost: f64,
+}
+
+impl AnswerUsage {
+    /// Add the usage of one more part
+    pub fn add(&mut self, tokens: &TokenUsage, cost: f64) {
+        self.parts += 1;
+        self.prompt_tokens += tokens.prompt_tokens;
+        self.completion_tokens += tokens.completion_tokens;
+        self.cost += cost;
+    }
+
+    /// One line with the parts, tokens and cost of the answer
+    pub fn summary(&self) -> String {
+        format!(
+            "↪️ Answer joined from {} responses · {} tokens ({} prompt, {} completion) · ${:.4}",
+            self.parts,
+            self.prompt_tokens + self.completion_tokens,
+            self.prompt_tokens,
+            self.completion_tokens,
+            self.cost
+        )
+    }
+}
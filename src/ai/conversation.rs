@@ -0,0 +1,256 @@
+//! Conversation history for multi-turn chat
+//!
+//! Chat prompts and their answers are remembered so that follow-up prompts
+//! can refer to them. The earlier messages are sent to the model as context,
+//! cut down to the token budget of the `context` config by whole exchanges.
+//! `/context clear` forgets them.
+//!
+//! An exchange remembers the output message its prompt was typed in, so
+//! dropping that message from the context leaves the exchange out as well.
+
+use super::continuation;
+use crate::config::{ContextConfig, Truncation};
+use crate::utils::count_tokens;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Stable id of a message in the output pane
+///
+/// Ids are handed out in order as commands are entered and are never reused
+/// within a run, so they keep pointing at the same message as the output
+/// changes.
+pub type MessageId = u64;
+
+/// Who wrote a message
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Assistant,
+}
+
+impl Role {
+    fn label(self) -> &'static str {
+        match self {
+            Role::User => "User",
+            Role::Assistant => "Assistant",
+        }
+    }
+}
+
+/// One message of a conversation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Message {
+    pub role: Role,
+    pub content: String,
+    /// Output message the exchange was typed in, if known
+    #[serde(skip)]
+    pub source: Option<MessageId>,
+}
+
+/// The chat messages of a session, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct Conversation {
+    messages: Vec<Message>,
+    /// Output messages whose exchanges are left out of the context
+    excluded: HashSet<MessageId>,
+}
+
+impl Conversation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a message
+    pub fn push(&mut self, role: Role, content: &str) {
+        self.push_from(None, role, content);
+    }
+
+    /// Add a message of the exchange typed in output message `source`
+    fn push_from(&mut self, source: Option<MessageId>, role: Role, content: &str) {
+        let content = content.trim();
+        if !content.is_empty() {
+            self.messages.push(Message {
+                role,
+                content: content.to_string(),
+                source,
+            });
+        }
+    }
+
+    /// Remember a prompt and its answer
+    pub fn record(&mut self, prompt: &str, answer: &str) {
+        self.record_from(None, prompt, answer);
+    }
+
+    /// Remember a prompt typed in output message `source` and its answer
+    pub fn record_from(&mut self, source: Option<MessageId>, prompt: &str, answer: &str) {
+        self.push_from(source, Role::User, prompt);
+        self.push_from(source, Role::Assistant, answer);
+    }
+
+    /// Leave the exchanges typed in output message `source` out of the
+    /// context, or send them again
+    pub fn set_excluded(&mut self, source: MessageId, excluded: bool) {
+        if excluded {
+            self.excluded.insert(source);
+        } else {
+            self.excluded.remove(&source);
+        }
+    }
+
+    /// Whether an exchange was dropped from the context with its message
+    pub fn is_excluded(&self, exchange: &[Message]) -> bool {
+        exchange
+            .first()
+            .and_then(|message| message.source)
+            .is_some_and(|source| self.excluded.contains(&source))
+    }
+
+    pub fn messages(&self) -> &[Message] {
+        &self.messages
+    }
+
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// Forget all messages
+    pub fn clear(&mut self) {
+        self.messages.clear();
+        self.excluded.clear();
+    }
+
+    /// Approximate tokens of all messages
+    pub fn tokens(&self) -> usize {
+        self.messages
+            .iter()
+            .map(|message| count_tokens(&message.content))
+            .sum()
+    }
+
+    /// The messages that fit the budget, oldest first
+    ///
+    /// Exchanges, a prompt with its answer, are kept or left out together.
+    pub fn window(&self, config: &ContextConfig) -> Vec<&Message> {
+        self.exchanges()
+            .iter()
+            .zip(self.sent_exchanges(config))
+            .filter(|(_, sent)| *sent)
+            .flat_map(|(exchange, _)| exchange.iter())
+            .collect()
+    }
+
+    /// Whether each exchange fits the budget and is sent with the next prompt
+    ///
+    /// Dropped exchanges are never sent and take none of the budget.
+    pub fn sent_exchanges(&self, config: &ContextConfig) -> Vec<bool> {
+        let exchanges = self.exchanges();
+        if !config.enabled {
+            return vec![false; exchanges.len()];
+        }
+        let mut budget = config.max_tokens;
+        let mut kept = vec![false; exchanges.len()];
+        if config.truncation == Truncation::KeepFirst
+            && let Some(first) = exchanges.first()
+            && !self.is_excluded(first)
+            && exchange_tokens(first) <= budget
+        {
+            budget -= exchange_tokens(first);
+            kept[0] = true;
+        }
+        for (idx, exchange) in exchanges.iter().enumerate().rev() {
+            if kept[idx] || self.is_excluded(exchange) {
+                continue;
+            }
+            let tokens = exchange_tokens(exchange);
+            if tokens > budget {
+                break;
+            }
+            budget -= tokens;
+            kept[idx] = true;
+        }
+        kept
+    }
+
+    /// Context text carrying the earlier messages, none when none are sent
+    pub fn context(&self, config: &ContextConfig) -> Option<String> {
+        let window = self.window(config);
+        if window.is_empty() {
+            return None;
+        }
+        let messages: Vec<String> = window
+            .iter()
+            .map(|message| format!("{}: {}", message.role.label(), message.content))
+            .collect();
+        Some(format!(
+            "Earlier messages of this conversation:\n\n{}",
+            messages.join("\n\n")
+        ))
+    }
+
+    /// Join `continuation` onto the latest answer that ends like `partial`
+    pub fn continue_answer(&mut self, partial: &str, continuation: &str) -> bool {
+        let joined = self
+            .messages
+            .iter_mut()
+            .rev()
+            .filter(|message| message.role == Role::Assistant)
+            .find_map(|message| {
+                let joined = continuation::join_into(&message.content, partial, continuation)?;
+                Some((message, joined))
+            });
+        match joined {
+            Some((message, joined)) => {
+                message.content = joined.trim().to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Forget the exchange at `index`, as numbered by [`Conversation::exchanges`]
+    pub fn remove_exchange(&mut self, index: usize) -> bool {
+        let Some(range) = self.exchange_ranges().into_iter().nth(index) else {
+            return false;
+        };
+        self.messages.drain(range);
+        true
+    }
+
+    /// Messages grouped into exchanges, each starting at a user message
+    pub fn exchanges(&self) -> Vec<&[Message]> {
+        self.exchange_ranges()
+            .into_iter()
+            .map(|range| &self.messages[range])
+            .collect()
+    }
+
+    /// Index ranges of the exchanges
+    fn exchange_ranges(&self) -> Vec<std::ops::Range<usize>> {
+        let mut exchanges = Vec::new();
+        let mut start = 0;
+        for (idx, message) in self.messages.iter().enumerate() {
+            if message.role == Role::User && idx > start {
+                exchanges.push(start..idx);
+                start = idx;
+            }
+        }
+        if start < self.messages.len() {
+            exchanges.push(start..self.messages.len());
+        }
+        exchanges
+    }
+}
+
+/// Approximate tokens of an exchange
+pub fn exchange_tokens(exchange: &[Message]) -> usize {
+    exchange
+        .iter()
+        .map(|message| count_tokens(&message.content))
+        .sum()
+}
@@ -0,0 +1,75 @@
+//! Custom OpenAI-compatible endpoints
+//!
+//! vLLM, the llama.cpp server, text-generation-webui, LiteLLM and most
+//! gateways serve the OpenAI `/chat/completions` API. The `custom` provider
+//! talks to any of them with the endpoint, credentials, headers and models
+//! from `ai.custom`, so a new backend needs no code.
+
+use crate::ai::extras::RequestExtras;
+use crate::ai::oauth::TokenSource;
+use crate::ai::types::{AIClient, AIError};
+use crate::ai::{OpenAICompatClient, Provider};
+use crate::config::{CustomConfig, ModelConfig};
+use reqwest::header::HeaderName;
+
+/// Header the API key goes in, or none to send it as a bearer token
+pub fn key_header(settings: &CustomConfig) -> Result<Option<HeaderName>, AIError> {
+    let Some(name) = settings
+        .api_key_header
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    else {
+        return Ok(None);
+    };
+    HeaderName::from_bytes(name.as_bytes())
+        .map(Some)
+        .map_err(|_| AIError::ConfigError(format!("'{}' is not a valid header name", name)))
+}
+
+/// Client for `model` at the custom endpoint
+pub fn client(
+    settings: &CustomConfig,
+    model: &ModelConfig,
+    extras: RequestExtras,
+) -> Result<OpenAICompatClient, AIError> {
+    if settings.endpoint.trim().is_empty() {
+        return Err(AIError::ConfigError(
+            "The custom provider has no endpoint; set it with /config custom endpoint <url>"
+                .to_string(),
+        ));
+    }
+    let mut client = OpenAICompatClient::new(
+        Provider::Custom,
+        settings.endpoint.trim().to_string(),
+        settings.api_key.clone(),
+        model,
+    )
+    .with_extras(extras)?
+    .with_stream_usage(settings.stream_usage);
+    if let Some(header) = key_header(settings)? {
+        client = client.with_key_header(header);
+    }
+    if let Some(oauth) = settings.oauth.clone() {
+        client = client.with_auth(TokenSource::new(Provider::Custom, oauth));
+    }
+    Ok(client)
+}
+
+/// The configured models, followed by the ones the endpoint reports when
+/// `discover_models` is on
+pub async fn models(
+    settings: &CustomConfig,
+    extras: RequestExtras,
+) -> Result<Vec<String>, AIError> {
+    let mut models: Vec<String> = settings.models.iter().map(|m| m.name.clone()).collect();
+    if settings.discover_models {
+        let listing = client(settings, &ModelConfig::default(), extras)?;
+        for model in listing.models().await? {
+            if !models.contains(&model) {
+                models.push(model);
+            }
+        }
+    }
+    Ok(models)
+}
@@ -0,0 +1,147 @@
+//! Embeddings for the project index
+//!
+//! Ollama is asked through its own `/api/embed` endpoint; the other
+//! providers with an embeddings API speak the OpenAI `/embeddings` format.
+
+use super::{AIError, Provider};
+use crate::config::{AIConfig, IndexConfig};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// Longest time one batch of embeddings may take
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Embed `texts` with the configured model, one vector per text
+pub async fn embed(
+    config: &IndexConfig,
+    ai: &AIConfig,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, AIError> {
+    if texts.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut provider_config = ai.clone();
+    provider_config.active_provider = config.provider;
+    let endpoint = if config.endpoint.is_empty() {
+        provider_config.get_active_endpoint()
+    } else {
+        config.endpoint.clone()
+    };
+    let endpoint = endpoint.trim_end_matches('/');
+
+    let vectors = match config.provider {
+        Provider::Ollama => embed_ollama(endpoint, &config.model, texts).await?,
+        Provider::OpenAI
+        | Provider::LMStudio
+        | Provider::OpenRouter
+        | Provider::Groq
+        | Provider::Mistral
+        | Provider::Custom => {
+            let api_key = provider_config.get_active_api_key().unwrap_or_default();
+            embed_openai(endpoint, &api_key, &config.model, texts).await?
+        }
+        Provider::AzureOpenAI => {
+            return Err(AIError::ConfigError(
+                "Embeddings through Azure OpenAI are not supported yet; set index.provider to Ollama or an OpenAI-compatible provider"
+                    .to_string(),
+            ));
+        }
+        Provider::Anthropic | Provider::LlamaCpp => {
+            return Err(AIError::ConfigError(format!(
+                "{} has no embeddings API; set index.provider to Ollama or an OpenAI-compatible provider",
+                config.provider
+            )));
+        }
+    };
+    if vectors.len() != texts.len() {
+        return Err(AIError::InvalidResponse(format!(
+            "Asked for {} embeddings but got {}",
+            texts.len(),
+            vectors.len()
+        )));
+    }
+    Ok(vectors)
+}
+
+async fn embed_ollama(
+    endpoint: &str,
+    model: &str,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, AIError> {
+    #[derive(Deserialize)]
+    struct Embeddings {
+        embeddings: Vec<Vec<f32>>,
+    }
+
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/embed", endpoint))
+        .json(&serde_json::json!({ "model": model, "input": texts }))
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| {
+            AIError::NetworkError(format!("Cannot reach Ollama at {}: {}", endpoint, e))
+        })?;
+    let embeddings: Embeddings = read_json(response, "Ollama").await?;
+    Ok(embeddings.embeddings)
+}
+
+async fn embed_openai(
+    endpoint: &str,
+    api_key: &str,
+    model: &str,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, AIError> {
+    #[derive(Deserialize)]
+    struct Embeddings {
+        data: Vec<Embedding>,
+    }
+    #[derive(Deserialize)]
+    struct Embedding {
+        index: usize,
+        embedding: Vec<f32>,
+    }
+
+    let mut request = reqwest::Client::new()
+        .post(format!("{}/embeddings", endpoint))
+        .json(&serde_json::json!({ "model": model, "input": texts }))
+        .timeout(REQUEST_TIMEOUT);
+    if !api_key.is_empty() {
+        request = request.bearer_auth(api_key);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AIError::NetworkError(format!("Embeddings request failed: {}", e)))?;
+    let mut embeddings: Embeddings = read_json(response, "The embeddings API").await?;
+    embeddings.data.sort_by_key(|embedding| embedding.index);
+    Ok(embeddings
+        .data
+        .into_iter()
+        .map(|embedding| embedding.embedding)
+        .collect())
+}
+
+/// Check the status of a response and parse its body
+pub(crate) async fn read_json<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    service: &str,
+) -> Result<T, AIError> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(match status {
+            reqwest::StatusCode::UNAUTHORIZED => {
+                AIError::Authentication(format!("{} rejected the key", service))
+            }
+            reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                AIError::RateLimit(format!("{} returned {}", service, status))
+            }
+            _ => AIError::APIError(format!("{} returned {}: {}", service, status, body.trim())),
+        });
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| AIError::InvalidResponse(format!("Unexpected answer of {}: {}", service, e)))
+}
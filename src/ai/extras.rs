@@ -0,0 +1,70 @@
+//! Extra request headers and body fields
+//!
+//! Gateways in front of a provider may require custom headers (an
+//! organization id, routing tags) or additional JSON fields. Each provider
+//! config has `extra_headers` and `extra_body` maps that clients merge into
+//! every outgoing request.
+
+use crate::ai::AIError;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Headers and body fields added to the requests of one provider
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RequestExtras {
+    /// Headers sent with every request, replacing headers of the same name
+    pub headers: BTreeMap<String, String>,
+    /// Fields merged into the JSON body of generation requests
+    pub body: BTreeMap<String, Value>,
+}
+
+impl RequestExtras {
+    /// Create extras from the maps of a provider config
+    pub fn new(headers: BTreeMap<String, String>, body: BTreeMap<String, Value>) -> Self {
+        Self { headers, body }
+    }
+
+    /// The headers as a header map, failing on names or values HTTP does not allow
+    pub fn header_map(&self) -> Result<HeaderMap, AIError> {
+        let mut map = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let header = HeaderName::from_bytes(name.as_bytes()).map_err(|_| {
+                AIError::ConfigError(format!("Invalid header name in extra_headers: {}", name))
+            })?;
+            let value = HeaderValue::from_str(value).map_err(|_| {
+                AIError::ConfigError(format!(
+                    "Invalid value for header {} in extra_headers",
+                    name
+                ))
+            })?;
+            map.insert(header, value);
+        }
+        Ok(map)
+    }
+
+    /// Merge the extra fields into a request body
+    ///
+    /// Objects are merged key by key, so `options: {num_ctx: 8192}` adds to
+    /// the options the client sets instead of replacing them. Any other value,
+    /// including `null`, replaces the field.
+    pub fn merge_body(&self, body: &mut Value) {
+        let Some(fields) = body.as_object_mut() else {
+            return;
+        };
+        for (key, value) in &self.body {
+            merge_value(fields.entry(key.clone()).or_insert(Value::Null), value);
+        }
+    }
+}
+
+fn merge_value(target: &mut Value, extra: &Value) {
+    match (target, extra) {
+        (Value::Object(target), Value::Object(extra)) => {
+            for (key, value) in extra {
+                merge_value(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+        (target, extra) => *target = extra.clone(),
+    }
+}
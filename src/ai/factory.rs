@@ -2,7 +2,11 @@
 //!
 //! This module provides factory methods for creating AI clients based on configuration
 
-use crate::ai::{AIClient, AIError, OllamaClient, Provider};
+use crate::ai::oauth::TokenSource;
+use crate::ai::{
+    AIClient, AIError, OllamaClient, OpenAICompatClient, Provider, azure, custom, llamacpp,
+    openrouter,
+};
 use crate::config;
 
 /// Factory for creating AI clients
@@ -23,10 +27,17 @@ impl AIClientFactory {
             Provider::Ollama => {
                 let model_config = ai_config.get_active_model_config();
                 let endpoint = ai_config.get_active_endpoint();
-                Ok(Box::new(OllamaClient::with_base_url(
-                    endpoint,
-                    model_config.name,
-                )))
+                let mut client = OllamaClient::with_base_url(endpoint, model_config.name)
+                    .with_max_tokens(model_config.max_tokens)
+                    .with_extras(ai_config.extras(Provider::Ollama))?
+                    .with_sampling(
+                        ai_config.deterministic.then_some(0.0),
+                        ai_config.request_seed(),
+                    );
+                if let Some(oauth) = ai_config.oauth(Provider::Ollama) {
+                    client = client.with_auth(TokenSource::new(Provider::Ollama, oauth));
+                }
+                Ok(Box::new(client))
             }
             Provider::OpenAI => {
                 // We'll implement this later
@@ -40,6 +51,81 @@ impl AIClientFactory {
                     "Anthropic support is not implemented yet".to_string(),
                 ))
             }
+            Provider::OpenRouter => {
+                let settings = &ai_config.openrouter;
+                let mut client = OpenAICompatClient::new(
+                    Provider::OpenRouter,
+                    settings.endpoint.clone(),
+                    settings.api_key.clone(),
+                    &ai_config.request_model_config(),
+                )
+                .with_extras(openrouter::request_extras(settings))?
+                .with_seed(ai_config.request_seed());
+                if let Some(oauth) = settings.oauth.clone() {
+                    client = client.with_auth(TokenSource::new(Provider::OpenRouter, oauth));
+                }
+                Ok(Box::new(client))
+            }
+            Provider::Groq => {
+                let settings = &ai_config.groq;
+                let mut client = OpenAICompatClient::new(
+                    Provider::Groq,
+                    settings.endpoint.clone(),
+                    settings.api_key.clone(),
+                    &ai_config.request_model_config(),
+                )
+                .with_extras(ai_config.extras(Provider::Groq))?
+                .with_seed(ai_config.request_seed());
+                if let Some(oauth) = settings.oauth.clone() {
+                    client = client.with_auth(TokenSource::new(Provider::Groq, oauth));
+                }
+                Ok(Box::new(client))
+            }
+            Provider::Mistral => {
+                let settings = &ai_config.mistral;
+                let mut client = OpenAICompatClient::new(
+                    Provider::Mistral,
+                    settings.endpoint.clone(),
+                    settings.api_key.clone(),
+                    &ai_config.request_model_config(),
+                )
+                .with_extras(ai_config.extras(Provider::Mistral))?
+                .with_stream_usage(false)
+                .with_seed(ai_config.request_seed());
+                if let Some(oauth) = settings.oauth.clone() {
+                    client = client.with_auth(TokenSource::new(Provider::Mistral, oauth));
+                }
+                Ok(Box::new(client))
+            }
+            Provider::AzureOpenAI => Ok(Box::new(
+                azure::AzureOpenAIClient::new(
+                    &ai_config.azure,
+                    &ai_config.request_model_config(),
+                    ai_config.extras(Provider::AzureOpenAI),
+                )?
+                .with_seed(ai_config.request_seed()),
+            )),
+            Provider::Custom => Ok(Box::new(
+                custom::client(
+                    &ai_config.custom,
+                    &ai_config.request_model_config(),
+                    ai_config.extras(Provider::Custom),
+                )?
+                .with_seed(ai_config.request_seed()),
+            )),
+            #[cfg(feature = "llama")]
+            Provider::LlamaCpp => Ok(Box::new(
+                llamacpp::LlamaCppClient::new(
+                    &ai_config.llamacpp,
+                    &ai_config.request_model_config(),
+                )?
+                .with_seed(ai_config.request_seed()),
+            )),
+            #[cfg(not(feature = "llama"))]
+            Provider::LlamaCpp => Err(AIError::ConfigError(
+                "llama.cpp support is not built in; rebuild with `cargo build --features llama`"
+                    .to_string(),
+            )),
             Provider::LMStudio => {
                 // We'll implement this later
                 Err(AIError::ConfigError(
@@ -54,10 +140,14 @@ impl AIClientFactory {
         let config = config::get_config();
         match provider {
             Provider::Ollama => {
-                let client = OllamaClient::with_base_url(
+                let mut client = OllamaClient::with_base_url(
                     config.ai.ollama.endpoint.clone(),
                     "".to_string(), // Model name doesn't matter for listing
-                );
+                )
+                .with_extras(config.ai.extras(Provider::Ollama))?;
+                if let Some(oauth) = config.ai.oauth(Provider::Ollama) {
+                    client = client.with_auth(TokenSource::new(Provider::Ollama, oauth));
+                }
                 client.models().await
             }
             // For other providers, we'll return their configured models
@@ -82,6 +172,49 @@ impl AIClientFactory {
                 .iter()
                 .map(|m| m.name.clone())
                 .collect()),
+            Provider::Groq => Ok(config
+                .ai
+                .groq
+                .models
+                .iter()
+                .map(|m| m.name.clone())
+                .collect()),
+            Provider::Mistral => Ok(config
+                .ai
+                .mistral
+                .models
+                .iter()
+                .map(|m| m.name.clone())
+                .collect()),
+            // Deployments are listed by the Azure management API, not the resource
+            Provider::AzureOpenAI => Ok(config
+                .ai
+                .azure
+                .models
+                .iter()
+                .map(|m| m.name.clone())
+                .collect()),
+            Provider::Custom => {
+                custom::models(&config.ai.custom, config.ai.extras(Provider::Custom)).await
+            }
+            // The configured models and any other GGUF file in the model directory
+            Provider::LlamaCpp => {
+                let settings = &config.ai.llamacpp;
+                let mut models: Vec<String> =
+                    settings.models.iter().map(|m| m.name.clone()).collect();
+                for file in llamacpp::find_models(&llamacpp::model_dir(settings)) {
+                    if !models.contains(&file) {
+                        models.push(file);
+                    }
+                }
+                Ok(models)
+            }
+            // OpenRouter lists its whole catalog
+            Provider::OpenRouter => Ok(openrouter::fetch_catalog(&config.ai.openrouter.endpoint)
+                .await?
+                .into_iter()
+                .map(|m| m.id)
+                .collect()),
         }
     }
 }
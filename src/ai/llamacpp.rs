@@ -0,0 +1,381 @@
+//! In-process inference with llama.cpp
+//!
+//! With the `llama` feature the TUI loads GGUF models itself through the
+//! llama.cpp bindings and needs no model server. Models are named by file
+//! name, relative to the model directory, or by path. The loaded model is
+//! kept between requests and clients, so only the first prompt after
+//! switching models pays for loading it.
+
+use crate::ai::AIError;
+use crate::config::{LlamaCppConfig, ModelConfig, get_config_dir};
+use std::path::{Path, PathBuf};
+
+/// Directory searched for GGUF files
+pub fn model_dir(config: &LlamaCppConfig) -> PathBuf {
+    if config.model_dir.is_empty() {
+        get_config_dir().join("models")
+    } else {
+        expand_home(&config.model_dir)
+    }
+}
+
+/// Path of the GGUF file of a model
+///
+/// Absolute paths and paths starting with `~/` are used as they are, other
+/// names are looked up in the model directory.
+pub fn model_path(config: &LlamaCppConfig, name: &str) -> PathBuf {
+    let path = expand_home(name);
+    if path.is_absolute() {
+        path
+    } else {
+        model_dir(config).join(path)
+    }
+}
+
+/// File names of the GGUF models in a directory, sorted
+pub fn find_models(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut models: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.to_lowercase().ends_with(".gguf"))
+        .collect();
+    models.sort();
+    models
+}
+
+/// Threads to generate with, all cores when the config says 0
+pub fn thread_count(config: &LlamaCppConfig) -> usize {
+    if config.threads > 0 {
+        config.threads
+    } else {
+        std::thread::available_parallelism()
+            .map(|threads| threads.get())
+            .unwrap_or(4)
+    }
+}
+
+/// Check that this build runs llama.cpp and that the model file exists
+pub fn check_available(config: &LlamaCppConfig, model: &ModelConfig) -> Result<(), AIError> {
+    if !cfg!(feature = "llama") {
+        return Err(AIError::ConfigError(
+            "llama.cpp support is not built in; rebuild with `cargo build --features llama`"
+                .to_string(),
+        ));
+    }
+    let path = model_path(config, &model.name);
+    if !path.is_file() {
+        return Err(AIError::ConfigError(format!(
+            "GGUF model not found: {} (put it in {} or configure its path)",
+            path.display(),
+            model_dir(config).display()
+        )));
+    }
+    Ok(())
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(feature = "llama")]
+pub use client::LlamaCppClient;
+
+#[cfg(feature = "llama")]
+mod client {
+    use super::{check_available, model_path, thread_count};
+    use crate::ai::types::{
+        AIClient, AIError, AIResponse, ModelCosts, ProgressCallback, ProgressStats, TokenUsage,
+    };
+    use crate::config::{LlamaCppConfig, ModelConfig};
+    use async_trait::async_trait;
+    use llama_cpp_2::context::params::LlamaContextParams;
+    use llama_cpp_2::llama_backend::LlamaBackend;
+    use llama_cpp_2::llama_batch::LlamaBatch;
+    use llama_cpp_2::model::params::LlamaModelParams;
+    use llama_cpp_2::model::{LlamaChatMessage, LlamaModel};
+    use llama_cpp_2::sampling::LlamaSampler;
+    use once_cell::sync::OnceCell;
+    use std::num::NonZeroU32;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    /// Shortest time between two progress reports while generating
+    const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+    /// The backend can only be initialized once per process
+    static BACKEND: OnceCell<LlamaBackend> = OnceCell::new();
+
+    /// The most recently loaded model, reused while path and offloading match
+    static LOADED: Mutex<Option<LoadedModel>> = Mutex::new(None);
+
+    struct LoadedModel {
+        path: PathBuf,
+        gpu_layers: u32,
+        model: Arc<LlamaModel>,
+    }
+
+    fn backend() -> Result<&'static LlamaBackend, AIError> {
+        BACKEND.get_or_try_init(|| {
+            let mut backend = LlamaBackend::init()
+                .map_err(|e| AIError::ConfigError(format!("Cannot initialize llama.cpp: {}", e)))?;
+            // llama.cpp logs to stderr, which would draw over the TUI
+            backend.void_logs();
+            Ok(backend)
+        })
+    }
+
+    fn load_model(path: &Path, gpu_layers: u32) -> Result<Arc<LlamaModel>, AIError> {
+        let mut loaded = LOADED.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(current) = loaded
+            .as_ref()
+            .filter(|current| current.path == path && current.gpu_layers == gpu_layers)
+        {
+            return Ok(current.model.clone());
+        }
+        // Free the previous model before loading the next one
+        *loaded = None;
+
+        let params = LlamaModelParams::default().with_n_gpu_layers(gpu_layers);
+        let model = LlamaModel::load_from_file(backend()?, path, &params)
+            .map_err(|e| AIError::ConfigError(format!("Cannot load {}: {}", path.display(), e)))?;
+        let model = Arc::new(model);
+        *loaded = Some(LoadedModel {
+            path: path.to_path_buf(),
+            gpu_layers,
+            model: model.clone(),
+        });
+        Ok(model)
+    }
+
+    /// Sets the flag when the request future is dropped, stopping generation
+    struct CancelOnDrop(Arc<AtomicBool>);
+
+    impl Drop for CancelOnDrop {
+        fn drop(&mut self) {
+            self.0.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Client running a GGUF model in this process
+    #[derive(Clone)]
+    pub struct LlamaCppClient {
+        path: PathBuf,
+        model: ModelConfig,
+        threads: usize,
+        gpu_layers: u32,
+        context_size: u32,
+        seed: Option<u64>,
+    }
+
+    impl LlamaCppClient {
+        /// Create a client for `model`; the file is loaded by the first request
+        pub fn new(config: &LlamaCppConfig, model: &ModelConfig) -> Result<Self, AIError> {
+            check_available(config, model)?;
+            Ok(Self {
+                path: model_path(config, &model.name),
+                model: model.clone(),
+                threads: thread_count(config),
+                gpu_layers: config.gpu_layers,
+                context_size: config.context_size.max(512),
+                seed: None,
+            })
+        }
+
+        /// Sample with a fixed seed instead of a random one
+        pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+            self.seed = seed;
+            self
+        }
+
+        /// Load the model if needed and generate; blocks until done
+        fn run(
+            &self,
+            prompt: &str,
+            system: Option<&str>,
+            cancelled: &AtomicBool,
+            on_progress: &ProgressCallback,
+        ) -> Result<AIResponse, AIError> {
+            let model = load_model(&self.path, self.gpu_layers)?;
+            let failed = |what: &str, e: &dyn std::fmt::Display| {
+                AIError::APIError(format!("llama.cpp failed to {}: {}", what, e))
+            };
+
+            // Use the model's own chat template, plain text without one
+            let text = match model.chat_template(None) {
+                Ok(template) => {
+                    let mut chat = Vec::new();
+                    if let Some(system) = system {
+                        chat.push(LlamaChatMessage::new(
+                            "system".to_string(),
+                            system.to_string(),
+                        ));
+                    }
+                    chat.push(LlamaChatMessage::new(
+                        "user".to_string(),
+                        prompt.to_string(),
+                    ));
+                    let chat = chat
+                        .into_iter()
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|e| failed("read the prompt", &e))?;
+                    model
+                        .apply_chat_template(&template, &chat, true)
+                        .map_err(|e| failed("apply the chat template", &e))?
+                }
+                Err(_) => match system {
+                    Some(system) => format!("{}\n\n{}", system, prompt),
+                    None => prompt.to_string(),
+                },
+            };
+
+            let threads = i32::try_from(self.threads).unwrap_or(i32::MAX);
+            let params = LlamaContextParams::default()
+                .with_n_ctx(NonZeroU32::new(self.context_size))
+                .with_n_batch(self.context_size)
+                .with_n_threads(threads)
+                .with_n_threads_batch(threads);
+            let mut context = model
+                .new_context(backend()?, params)
+                .map_err(|e| failed("create a context", &e))?;
+
+            let vocab = model.vocab();
+            let tokens = vocab.tokenize(text.as_bytes(), true, true);
+            let context_size = self.context_size as usize;
+            if tokens.len() >= context_size {
+                return Err(AIError::InvalidResponse(format!(
+                    "The prompt has {} tokens but the context holds {}; raise context_size",
+                    tokens.len(),
+                    context_size
+                )));
+            }
+
+            let mut batch = LlamaBatch::new(context_size, 1);
+            let last = tokens.len() - 1;
+            for (i, token) in tokens.iter().enumerate() {
+                batch
+                    .add(*token, i as i32, &[0], i == last)
+                    .map_err(|e| failed("queue the prompt", &e))?;
+            }
+            context
+                .decode(&mut batch)
+                .map_err(|e| failed("read the prompt", &e))?;
+
+            let mut sampler = if self.model.temperature <= 0.0 {
+                LlamaSampler::greedy()
+            } else {
+                LlamaSampler::chain_simple([
+                    LlamaSampler::temp(self.model.temperature),
+                    // llama.cpp seeds are 32 bits
+                    LlamaSampler::dist(self.seed.map_or_else(rand::random, |seed| seed as u32)),
+                ])
+            };
+
+            let max_tokens = self.model.max_tokens.min(context_size - tokens.len());
+            let mut progress = ProgressStats::new();
+            progress.estimated_total_tokens = Some(max_tokens);
+            // The model is loaded and has read the prompt
+            progress.connected();
+            on_progress(&progress);
+            let mut last_report = Instant::now();
+            let mut output = Vec::new();
+            let mut generated = 0;
+            let mut position = tokens.len() as i32;
+            let mut stopped = false;
+
+            while generated < max_tokens {
+                if cancelled.load(Ordering::Relaxed) {
+                    return Err(AIError::Cancelled("Generation stopped".to_string()));
+                }
+                let token = sampler.sample(&context, batch.n_tokens() - 1);
+                if vocab.is_eog(token) {
+                    stopped = true;
+                    break;
+                }
+                output.extend(vocab.token_to_piece(token, false, None));
+                generated += 1;
+                progress.update(generated);
+                if last_report.elapsed() >= PROGRESS_INTERVAL {
+                    on_progress(&progress);
+                    last_report = Instant::now();
+                }
+
+                batch.clear();
+                batch
+                    .add(token, position, &[0], true)
+                    .map_err(|e| failed("queue a token", &e))?;
+                position += 1;
+                context
+                    .decode(&mut batch)
+                    .map_err(|e| failed("generate", &e))?;
+            }
+            progress.complete();
+            on_progress(&progress);
+
+            Ok(AIResponse {
+                // Pieces may split a character, so decode once at the end
+                content: String::from_utf8_lossy(&output).into_owned(),
+                model: self.model.name.clone(),
+                usage: TokenUsage {
+                    prompt_tokens: tokens.len(),
+                    completion_tokens: generated,
+                    total_tokens: tokens.len() + generated,
+                },
+                progress: Some(progress),
+                cost: None,
+                reasoning: None,
+                truncated: !stopped,
+            })
+        }
+    }
+
+    #[async_trait]
+    impl AIClient for LlamaCppClient {
+        async fn generate(
+            &self,
+            prompt: &str,
+            context: Option<&str>,
+        ) -> Result<AIResponse, AIError> {
+            self.generate_with_progress(prompt, context, Arc::new(|_| {}))
+                .await
+        }
+
+        async fn generate_with_progress(
+            &self,
+            prompt: &str,
+            context: Option<&str>,
+            on_progress: ProgressCallback,
+        ) -> Result<AIResponse, AIError> {
+            let client = self.clone();
+            let prompt = prompt.to_string();
+            let system = context
+                .filter(|system| !system.is_empty())
+                .map(str::to_string);
+            let cancelled = Arc::new(AtomicBool::new(false));
+            // Aborting the request drops this guard and ends the blocking loop
+            let _guard = CancelOnDrop(cancelled.clone());
+
+            tokio::task::spawn_blocking(move || {
+                client.run(&prompt, system.as_deref(), &cancelled, &on_progress)
+            })
+            .await
+            .map_err(|e| AIError::APIError(format!("llama.cpp stopped unexpectedly: {}", e)))?
+        }
+
+        async fn models(&self) -> Result<Vec<String>, AIError> {
+            Ok(vec![self.model.name.clone()])
+        }
+
+        fn get_model_costs(&self, model: &str) -> ModelCosts {
+            crate::ai::pricing::model_costs(crate::ai::Provider::LlamaCpp, model)
+        }
+    }
+}
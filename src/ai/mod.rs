@@ -1,7 +1,28 @@
+pub mod azure;
+pub mod continuation;
+pub mod conversation;
+pub mod custom;
+pub mod embeddings;
+pub mod extras;
 mod factory;
+pub mod llamacpp;
+pub mod moderation;
+pub mod oauth;
 mod ollama;
+pub mod openai_compat;
+pub mod openrouter;
+pub mod persona;
+pub mod pricing;
+pub mod quiet_hours;
+pub mod rate_limit;
+pub mod routing;
+pub mod sampling;
 pub mod types;
 
 pub use factory::AIClientFactory;
 pub use ollama::OllamaClient;
-pub use types::{AIClient, AIError, AIResponse, ModelCosts, Provider, SessionStats, TokenUsage};
+pub use openai_compat::OpenAICompatClient;
+pub use types::{
+    AIClient, AIError, AIResponse, ChunkSender, ModelCosts, ProgressCallback, Provider,
+    SessionStats, StreamChunk, TokenUsage,
+};
@@ -0,0 +1,209 @@
+//! Moderation of generated responses
+//!
+//! With `moderation.enabled`, every chat response is classified before it is
+//! shown, by OpenAI's moderation endpoint or by a local classifier command
+//! that reads the response on stdin and prints the categories it matches.
+//! Responses in the configured categories are flagged with a warning or, with
+//! `action: block`, withheld so their code blocks never run. Each verdict is
+//! recorded in the audit log.
+
+use super::embeddings::read_json;
+use super::{AIError, Provider};
+use crate::config::{AIConfig, ModerationAction, ModerationBackend, ModerationConfig};
+use crate::handlers::postprocess::filter;
+use std::time::Duration;
+
+/// Longest wait for the moderation endpoint
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// What moderation decided about a response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    /// Nothing in the configured categories
+    Passed,
+    /// Shown with a warning
+    Flagged(Vec<String>),
+    /// Withheld
+    Blocked(Vec<String>),
+    /// The classifier could not be reached or answered nonsense
+    Unavailable(String),
+}
+
+impl Verdict {
+    /// Name of the verdict in the audit log
+    pub fn label(&self) -> &'static str {
+        match self {
+            Verdict::Passed => "passed",
+            Verdict::Flagged(_) => "flagged",
+            Verdict::Blocked(_) => "blocked",
+            Verdict::Unavailable(_) => "error",
+        }
+    }
+
+    /// Categories the verdict is based on
+    pub fn categories(&self) -> &[String] {
+        match self {
+            Verdict::Flagged(categories) | Verdict::Blocked(categories) => categories,
+            Verdict::Passed | Verdict::Unavailable(_) => &[],
+        }
+    }
+
+    /// Whether the response must not be shown or run
+    pub fn withholds(&self, config: &ModerationConfig) -> bool {
+        match self {
+            Verdict::Blocked(_) => true,
+            Verdict::Unavailable(_) => config.fail_closed,
+            Verdict::Passed | Verdict::Flagged(_) => false,
+        }
+    }
+}
+
+/// Name of the classifier in the audit log
+pub fn backend_name(backend: ModerationBackend) -> &'static str {
+    match backend {
+        ModerationBackend::OpenAI => "openai",
+        ModerationBackend::Command => "command",
+    }
+}
+
+/// Classify `text`, returning every category the classifier flags
+pub async fn classify(
+    text: &str,
+    config: &ModerationConfig,
+    ai: &AIConfig,
+) -> Result<Vec<String>, AIError> {
+    match config.backend {
+        ModerationBackend::OpenAI => classify_openai(text, config, ai).await,
+        ModerationBackend::Command => {
+            if config.command.trim().is_empty() {
+                return Err(AIError::ConfigError(
+                    "moderation.command is empty; set the classifier to run".to_string(),
+                ));
+            }
+            let command = config.command.clone();
+            let text = text.to_string();
+            let output = tokio::task::spawn_blocking(move || filter(&command, &text))
+                .await
+                .map_err(|e| AIError::APIError(format!("Classifier failed: {}", e)))?
+                .map_err(|e| AIError::APIError(format!("Classifier {}", e)))?;
+            Ok(parse_command_output(&output))
+        }
+    }
+}
+
+async fn classify_openai(
+    text: &str,
+    config: &ModerationConfig,
+    ai: &AIConfig,
+) -> Result<Vec<String>, AIError> {
+    let mut openai = ai.clone();
+    openai.active_provider = Provider::OpenAI;
+    let endpoint = if config.endpoint.is_empty() {
+        openai.get_active_endpoint()
+    } else {
+        config.endpoint.clone()
+    };
+    let api_key = openai.get_active_api_key().unwrap_or_default();
+
+    let mut request = reqwest::Client::new()
+        .post(format!("{}/moderations", endpoint.trim_end_matches('/')))
+        .json(&serde_json::json!({ "model": config.model, "input": text }))
+        .timeout(REQUEST_TIMEOUT);
+    if !api_key.is_empty() {
+        request = request.bearer_auth(api_key);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AIError::NetworkError(format!("Moderation request failed: {}", e)))?;
+    let body: serde_json::Value = read_json(response, "The moderation API").await?;
+    parse_openai_response(&body)
+}
+
+/// The categories flagged in an answer of the OpenAI moderation endpoint
+pub fn parse_openai_response(body: &serde_json::Value) -> Result<Vec<String>, AIError> {
+    let categories = body["results"][0]["categories"]
+        .as_object()
+        .ok_or_else(|| {
+            AIError::InvalidResponse("The moderation API returned no categories".to_string())
+        })?;
+    Ok(categories
+        .iter()
+        .filter(|(_, flagged)| flagged.as_bool() == Some(true))
+        .map(|(category, _)| category.clone())
+        .collect())
+}
+
+/// The categories a classifier command printed, separated by commas or lines
+pub fn parse_command_output(output: &str) -> Vec<String> {
+    let mut categories: Vec<String> = Vec::new();
+    for category in output.split([',', '\n']).map(str::trim) {
+        if !category.is_empty() && !categories.iter().any(|known| known == category) {
+            categories.push(category.to_string());
+        }
+    }
+    categories
+}
+
+/// The flagged categories that are configured, or all of them without any
+///
+/// A configured category also covers its subcategories, so `violence`
+/// matches `violence/graphic`.
+pub fn matched(flagged: &[String], configured: &[String]) -> Vec<String> {
+    if configured.is_empty() {
+        return flagged.to_vec();
+    }
+    flagged
+        .iter()
+        .filter(|category| {
+            let category = category.to_lowercase();
+            configured.iter().any(|wanted| {
+                let wanted = wanted.trim().to_lowercase();
+                category == wanted || category.starts_with(&format!("{}/", wanted))
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// The verdict on a classification
+pub fn decide(classified: Result<Vec<String>, AIError>, config: &ModerationConfig) -> Verdict {
+    match classified {
+        Ok(flagged) => {
+            let categories = matched(&flagged, &config.categories);
+            if categories.is_empty() {
+                Verdict::Passed
+            } else if config.action == ModerationAction::Block {
+                Verdict::Blocked(categories)
+            } else {
+                Verdict::Flagged(categories)
+            }
+        }
+        Err(e) => Verdict::Unavailable(e.to_string()),
+    }
+}
+
+/// The response as it is shown after the verdict
+pub fn apply(content: &str, verdict: &Verdict, config: &ModerationConfig) -> String {
+    match verdict {
+        Verdict::Passed => content.to_string(),
+        Verdict::Flagged(categories) => format!(
+            "{}\n⚠️ Moderation flagged this response: {}",
+            content.trim_end(),
+            categories.join(", ")
+        ),
+        Verdict::Blocked(categories) => format!(
+            "🚫 Response withheld by moderation: {}",
+            categories.join(", ")
+        ),
+        Verdict::Unavailable(e) if config.fail_closed => format!(
+            "🚫 Response withheld because moderation is unavailable: {}",
+            e
+        ),
+        Verdict::Unavailable(e) => format!(
+            "{}\n⚠️ Moderation unavailable, response not checked: {}",
+            content.trim_end(),
+            e
+        ),
+    }
+}
@@ -0,0 +1,365 @@
+//! OAuth device flow login
+//!
+//! Providers and gateways that support the OAuth 2.0 device authorization
+//! grant (RFC 8628) can be logged in to with `/login <provider>` instead of
+//! pasting an API key: the user opens a verification page and enters a short
+//! code while the app polls for the tokens. Tokens are stored in
+//! `~/.ai-coder/tokens.json`, readable by the owner only, and clients refresh
+//! the access token shortly before it expires.
+
+use crate::ai::{AIError, Provider};
+use crate::config::{OAuthConfig, get_config_dir};
+use crate::platform;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+
+/// Grant type of the device flow token request
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Polling interval when the server does not name one
+const DEFAULT_INTERVAL_SECS: u64 = 5;
+
+/// Refresh access tokens this long before they expire
+const REFRESH_MARGIN_SECS: i64 = 60;
+
+/// Codes returned when a device flow login starts
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DeviceCode {
+    /// Code the app polls with
+    pub device_code: String,
+    /// Code the user enters on the verification page
+    pub user_code: String,
+    /// Page where the user enters the code
+    #[serde(alias = "verification_url")]
+    pub verification_uri: String,
+    /// Page with the code already filled in, if the server offers one
+    #[serde(default)]
+    pub verification_uri_complete: Option<String>,
+    /// Seconds until the codes expire
+    pub expires_in: u64,
+    /// Seconds to wait between polls
+    #[serde(default)]
+    pub interval: Option<u64>,
+}
+
+/// Tokens obtained by logging in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredToken {
+    /// Token sent with requests
+    pub access_token: String,
+    /// Token used to get a new access token
+    pub refresh_token: Option<String>,
+    /// When the access token expires, if it does
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl StoredToken {
+    /// Whether the access token expires within the refresh margin
+    pub fn needs_refresh(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| {
+            expires_at - ChronoDuration::seconds(REFRESH_MARGIN_SECS) <= now
+        })
+    }
+}
+
+/// Body of a token endpoint response, successful or not
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// What a poll of the token endpoint returned
+#[derive(Debug, Clone, PartialEq)]
+pub enum PollOutcome {
+    /// The user approved the login
+    Token(StoredToken),
+    /// The user has not entered the code yet
+    Pending,
+    /// Polling too fast; wait longer between polls
+    SlowDown,
+    /// The login was denied, expired or failed
+    Failed(String),
+}
+
+/// Interpret a token endpoint response body
+///
+/// Some servers (GitHub) answer pending polls with status 200 and an `error`
+/// field, others with status 400, so only the body is looked at.
+pub fn poll_outcome(body: &str, now: DateTime<Utc>) -> PollOutcome {
+    let response: TokenResponse = match serde_json::from_str(body) {
+        Ok(response) => response,
+        Err(_) => return PollOutcome::Failed(format!("Unexpected response: {}", body.trim())),
+    };
+    match (response.error.as_deref(), response.access_token) {
+        (Some("authorization_pending"), _) => PollOutcome::Pending,
+        (Some("slow_down"), _) => PollOutcome::SlowDown,
+        (Some("expired_token"), _) => {
+            PollOutcome::Failed("The code expired before it was entered".to_string())
+        }
+        (Some("access_denied"), _) => PollOutcome::Failed("The login was denied".to_string()),
+        (Some(error), _) => PollOutcome::Failed(match response.error_description {
+            Some(description) => format!("{}: {}", error, description),
+            None => error.to_string(),
+        }),
+        (None, Some(access_token)) => PollOutcome::Token(StoredToken {
+            access_token,
+            refresh_token: response.refresh_token,
+            expires_at: response
+                .expires_in
+                .map(|secs| now + ChronoDuration::seconds(secs)),
+        }),
+        (None, None) => PollOutcome::Failed("The response has no access token".to_string()),
+    }
+}
+
+fn http_client() -> Client {
+    Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .unwrap_or_default()
+}
+
+/// Send a form to an OAuth endpoint and return the response body
+async fn post_form(
+    url: &str,
+    form: &[(&str, &str)],
+) -> Result<(reqwest::StatusCode, String), AIError> {
+    let response = http_client()
+        .post(url)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .form(form)
+        .send()
+        .await
+        .map_err(|e| AIError::NetworkError(format!("Failed to reach {}: {}", url, e)))?;
+    let status = response.status();
+    let body = response
+        .text()
+        .await
+        .map_err(|e| AIError::NetworkError(format!("Failed to read the response: {}", e)))?;
+    Ok((status, body))
+}
+
+/// Start a login: ask for a device code and the code the user enters
+pub async fn request_device_code(config: &OAuthConfig) -> Result<DeviceCode, AIError> {
+    let mut form = vec![("client_id", config.client_id.as_str())];
+    if !config.scope.is_empty() {
+        form.push(("scope", config.scope.as_str()));
+    }
+    let (status, body) = post_form(&config.device_authorization_url, &form).await?;
+    if !status.is_success() {
+        return Err(AIError::APIError(format!(
+            "Device authorization failed: {} - {}",
+            status,
+            body.trim()
+        )));
+    }
+    serde_json::from_str(&body)
+        .map_err(|e| AIError::InvalidResponse(format!("Unexpected device code response: {}", e)))
+}
+
+/// Poll until the user approves or denies the login, or the code expires
+///
+/// `cancelled` is checked before every poll.
+pub async fn poll_for_token(
+    config: &OAuthConfig,
+    code: &DeviceCode,
+    cancelled: impl Fn() -> bool,
+) -> Result<StoredToken, AIError> {
+    let mut interval = code.interval.unwrap_or(DEFAULT_INTERVAL_SECS).max(1);
+    let deadline = Utc::now() + ChronoDuration::seconds(code.expires_in as i64);
+    let form = [
+        ("grant_type", DEVICE_CODE_GRANT),
+        ("device_code", code.device_code.as_str()),
+        ("client_id", config.client_id.as_str()),
+    ];
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+        if cancelled() {
+            return Err(AIError::Cancelled("Login cancelled".to_string()));
+        }
+        if Utc::now() > deadline {
+            return Err(AIError::APIError(
+                "The code expired before it was entered".to_string(),
+            ));
+        }
+
+        let (_, body) = post_form(&config.token_url, &form).await?;
+        match poll_outcome(&body, Utc::now()) {
+            PollOutcome::Token(token) => return Ok(token),
+            PollOutcome::Pending => {}
+            // RFC 8628 asks for five more seconds on every slow_down
+            PollOutcome::SlowDown => interval += 5,
+            PollOutcome::Failed(reason) => return Err(AIError::APIError(reason)),
+        }
+    }
+}
+
+/// Exchange a refresh token for a new access token
+pub async fn refresh(config: &OAuthConfig, token: &StoredToken) -> Result<StoredToken, AIError> {
+    let Some(refresh_token) = token.refresh_token.as_deref() else {
+        return Err(AIError::ConfigError(
+            "The access token expired and cannot be refreshed".to_string(),
+        ));
+    };
+    let form = [
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+        ("client_id", config.client_id.as_str()),
+    ];
+    let (_, body) = post_form(&config.token_url, &form).await?;
+    match poll_outcome(&body, Utc::now()) {
+        // Servers may keep the refresh token instead of rotating it
+        PollOutcome::Token(mut refreshed) => {
+            if refreshed.refresh_token.is_none() {
+                refreshed.refresh_token = token.refresh_token.clone();
+            }
+            Ok(refreshed)
+        }
+        PollOutcome::Failed(reason) => Err(AIError::APIError(format!(
+            "Refreshing the access token failed: {}",
+            reason
+        ))),
+        _ => Err(AIError::InvalidResponse(
+            "Unexpected response to a token refresh".to_string(),
+        )),
+    }
+}
+
+/// Tokens of every provider, kept in a file only the user can read
+#[derive(Debug, Clone)]
+pub struct TokenStore {
+    path: PathBuf,
+}
+
+impl Default for TokenStore {
+    fn default() -> Self {
+        Self::at(get_config_dir().join("tokens.json"))
+    }
+}
+
+impl TokenStore {
+    /// A store in the given file
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// File holding the tokens
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn read_all(&self) -> io::Result<BTreeMap<String, StoredToken>> {
+        match fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content).map_err(io::Error::other),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(BTreeMap::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn write_all(&self, tokens: &BTreeMap<String, StoredToken>) -> io::Result<()> {
+        if tokens.is_empty() {
+            return match fs::remove_file(&self.path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+        let content = serde_json::to_string_pretty(tokens).map_err(io::Error::other)?;
+        platform::write_private(&self.path, content.as_bytes())
+    }
+
+    /// The tokens of a provider, if logged in
+    pub fn load(&self, provider: Provider) -> io::Result<Option<StoredToken>> {
+        Ok(self.read_all()?.remove(&key(provider)))
+    }
+
+    /// Store the tokens of a provider, replacing earlier ones
+    pub fn save(&self, provider: Provider, token: &StoredToken) -> io::Result<()> {
+        let mut tokens = self.read_all()?;
+        tokens.insert(key(provider), token.clone());
+        self.write_all(&tokens)
+    }
+
+    /// Forget the tokens of a provider; returns whether there were any
+    pub fn remove(&self, provider: Provider) -> io::Result<bool> {
+        let mut tokens = self.read_all()?;
+        let removed = tokens.remove(&key(provider)).is_some();
+        if removed {
+            self.write_all(&tokens)?;
+        }
+        Ok(removed)
+    }
+}
+
+fn key(provider: Provider) -> String {
+    provider.to_string().to_lowercase()
+}
+
+/// Access tokens for a client, refreshed when they are about to expire
+#[derive(Debug, Clone)]
+pub struct TokenSource {
+    provider: Provider,
+    config: OAuthConfig,
+    store: TokenStore,
+    cached: Arc<Mutex<Option<StoredToken>>>,
+}
+
+impl TokenSource {
+    /// Tokens of `provider` from the default store
+    pub fn new(provider: Provider, config: OAuthConfig) -> Self {
+        Self::with_store(provider, config, TokenStore::default())
+    }
+
+    /// Tokens of `provider` from the given store
+    pub fn with_store(provider: Provider, config: OAuthConfig, store: TokenStore) -> Self {
+        Self {
+            provider,
+            config,
+            store,
+            cached: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// A valid access token, refreshing and storing it first if needed
+    pub async fn access_token(&self) -> Result<String, AIError> {
+        // Held across the refresh so concurrent requests refresh only once
+        let mut cached = self.cached.lock().await;
+        if cached.is_none() {
+            *cached = self
+                .store
+                .load(self.provider)
+                .map_err(|e| AIError::ConfigError(format!("Cannot read stored tokens: {}", e)))?;
+        }
+        let Some(token) = cached.as_ref() else {
+            return Err(AIError::ConfigError(format!(
+                "Not logged in to {} — run /login {}",
+                self.provider,
+                key(self.provider)
+            )));
+        };
+
+        if token.needs_refresh(Utc::now()) {
+            let refreshed = refresh(&self.config, token).await?;
+            self.store
+                .save(self.provider, &refreshed)
+                .map_err(|e| AIError::ConfigError(format!("Cannot store tokens: {}", e)))?;
+            *cached = Some(refreshed);
+        }
+        Ok(cached
+            .as_ref()
+            .map(|token| token.access_token.clone())
+            .unwrap_or_default())
+    }
+}
@@ -1,17 +1,35 @@
-use crate::ai::types::{AIClient, AIError, AIResponse, ModelCosts, ProgressStats, TokenUsage};
+use crate::ai::Provider;
+use crate::ai::extras::RequestExtras;
+use crate::ai::oauth::TokenSource;
+use crate::ai::pricing;
+use crate::ai::rate_limit::{self, RateLimitInfo};
+use crate::ai::types::{
+    AIClient, AIError, AIResponse, ChunkSender, ModelCosts, ProgressCallback, ProgressStats,
+    StreamChunk, TokenUsage,
+};
 use async_trait::async_trait;
 use futures_util::StreamExt;
 use reqwest::Client;
+use reqwest::header::HeaderMap;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 const OLLAMA_BASE_URL: &str = "http://localhost:11434";
 
+/// Shortest time between two progress reports while streaming
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Tokens an answer may take unless the model configuration says otherwise
+const DEFAULT_MAX_TOKENS: usize = 2048;
+
 #[derive(Debug, Serialize)]
 struct GenerateRequest {
     model: String,
     prompt: String,
     stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
     context: Option<Vec<i64>>,
     options: Option<GenerateOptions>,
 }
@@ -19,6 +37,10 @@ struct GenerateRequest {
 #[derive(Debug, Serialize)]
 struct GenerateOptions {
     num_predict: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -26,7 +48,13 @@ struct GenerateOptions {
 struct GenerateResponse {
     model: String,
     response: String,
+    /// Reasoning of thinking models, streamed before the answer
+    #[serde(default)]
+    thinking: String,
     done: bool,
+    /// Why generation stopped: `stop`, or `length` at `num_predict`
+    #[serde(default)]
+    done_reason: Option<String>,
     context: Option<Vec<i64>>,
     prompt_eval_count: Option<usize>,
     eval_count: Option<usize>,
@@ -43,6 +71,14 @@ pub struct OllamaClient {
     client: Client,
     model: String,
     base_url: String,
+    headers: HeaderMap,
+    extras: RequestExtras,
+    auth: Option<TokenSource>,
+    /// Temperature overriding the model's default
+    temperature: Option<f32>,
+    seed: Option<u64>,
+    /// Most tokens generated for an answer
+    max_tokens: usize,
 }
 
 impl OllamaClient {
@@ -54,6 +90,12 @@ impl OllamaClient {
                 .unwrap(),
             model,
             base_url: OLLAMA_BASE_URL.to_string(),
+            headers: HeaderMap::new(),
+            extras: RequestExtras::default(),
+            auth: None,
+            temperature: None,
+            seed: None,
+            max_tokens: DEFAULT_MAX_TOKENS,
         }
     }
 
@@ -65,7 +107,52 @@ impl OllamaClient {
                 .unwrap(),
             model,
             base_url,
+            headers: HeaderMap::new(),
+            extras: RequestExtras::default(),
+            auth: None,
+            temperature: None,
+            seed: None,
+            max_tokens: DEFAULT_MAX_TOKENS,
+        }
+    }
+
+    /// Send the extra headers and body fields with every request
+    pub fn with_extras(mut self, extras: RequestExtras) -> Result<Self, AIError> {
+        self.headers = extras.header_map()?;
+        self.extras = extras;
+        Ok(self)
+    }
+
+    /// Sample with this temperature and seed instead of the model's defaults
+    pub fn with_sampling(mut self, temperature: Option<f32>, seed: Option<u64>) -> Self {
+        self.temperature = temperature;
+        self.seed = seed;
+        self
+    }
+
+    /// Stop answers after `max_tokens` tokens
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// Authenticate with tokens from `/login`, e.g. for a gateway in front of Ollama
+    pub fn with_auth(mut self, auth: TokenSource) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Headers for a request, with a fresh access token when logged in
+    async fn request_headers(&self) -> Result<HeaderMap, AIError> {
+        let mut headers = self.headers.clone();
+        if let Some(auth) = &self.auth {
+            let token = auth.access_token().await?;
+            let value = format!("Bearer {}", token).parse().map_err(|_| {
+                AIError::ConfigError("The stored access token is not a valid header".to_string())
+            })?;
+            headers.insert(reqwest::header::AUTHORIZATION, value);
         }
+        Ok(headers)
     }
 
     fn count_tokens(&self, text: &str) -> usize {
@@ -74,11 +161,15 @@ impl OllamaClient {
         // This is a rough approximation that works reasonably well for English text
         text.split_whitespace().count()
     }
-}
 
-#[async_trait]
-impl AIClient for OllamaClient {
-    async fn generate(&self, prompt: &str, _context: Option<&str>) -> Result<AIResponse, AIError> {
+    /// Generate a completion, sending its text to `chunks` when given
+    async fn generate_streaming(
+        &self,
+        prompt: &str,
+        context: Option<&str>,
+        chunks: Option<&ChunkSender>,
+        on_progress: ProgressCallback,
+    ) -> Result<AIResponse, AIError> {
         // Use a properly configured client with appropriate timeouts
         let client = &self.client;
 
@@ -87,16 +178,27 @@ impl AIClient for OllamaClient {
             model: self.model.clone(),
             prompt: prompt.to_string(),
             stream: true, // Use streaming for better progress reporting
+            system: context
+                .filter(|system| !system.is_empty())
+                .map(str::to_string),
             context: None,
             options: Some(GenerateOptions {
-                num_predict: Some(2048), // Reasonable default token limit
+                num_predict: Some(self.max_tokens as i32),
+                temperature: self.temperature,
+                seed: self.seed,
             }),
         };
 
+        let mut body = serde_json::to_value(&request)
+            .map_err(|e| AIError::APIError(format!("Failed to build request: {}", e)))?;
+        self.extras.merge_body(&mut body);
+
         // Send the request with proper error handling
+        let mut progress_stats = ProgressStats::new();
         let response = client
             .post(format!("{}/api/generate", self.base_url))
-            .json(&request)
+            .headers(self.request_headers().await?)
+            .json(&body)
             .send()
             .await
             .map_err(|e| {
@@ -104,6 +206,18 @@ impl AIClient for OllamaClient {
                 AIError::APIError(format!("Failed to send request to Ollama: {}", e))
             })?;
 
+        // Remember the limits reported by the server (e.g. a proxy in front of Ollama)
+        rate_limit::record(
+            Provider::Ollama,
+            &RateLimitInfo::from_headers(response.headers()),
+        );
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(AIError::RateLimit(format!(
+                "Ollama returned {}",
+                response.status()
+            )));
+        }
+
         // Check HTTP status
         if !response.status().is_success() {
             let status = response.status();
@@ -118,68 +232,91 @@ impl AIClient for OllamaClient {
             )));
         }
 
-        // Process streamed response
+        progress_stats.connected();
+        on_progress(&progress_stats);
+
+        // Process streamed response: one JSON object per line, with chunks
+        // that may hold several lines or end in the middle of one
         let mut response_stream = response.bytes_stream();
+        let mut pending: Vec<u8> = Vec::new();
         let mut full_content = String::new();
+        let mut thinking = String::new();
         let mut model_name = self.model.clone();
-        let mut progress_stats = ProgressStats::new();
         let mut prompt_tokens = 0;
         let mut completion_tokens = 0;
+        let mut truncated = false;
+        let mut streamed_tokens = 0;
+        let mut last_report = Instant::now();
 
         // Estimated token count for progress estimation
-        progress_stats.estimated_total_tokens = Some(2048); // Initial estimate
-
-        while let Some(chunk_result) = response_stream.next().await {
-            let chunk = chunk_result
-                .map_err(|e| AIError::APIError(format!("Error reading stream chunk: {}", e)))?;
+        progress_stats.estimated_total_tokens = Some(self.max_tokens);
+
+        let mut finished = false;
+        while !finished {
+            match response_stream.next().await {
+                Some(chunk_result) => {
+                    let chunk = chunk_result.map_err(|e| {
+                        AIError::APIError(format!("Error reading stream chunk: {}", e))
+                    })?;
+                    pending.extend_from_slice(&chunk);
+                }
+                None => {
+                    // Flush a last line without a trailing newline
+                    finished = true;
+                    pending.push(b'\n');
+                }
+            }
 
-            // Parse the chunk as JSON
-            if let Ok(text) = std::str::from_utf8(&chunk) {
-                if let Ok(response) = serde_json::from_str::<GenerateResponse>(text) {
-                    // Add the new content
+            while let Some(end) = pending.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                let Ok(response) = serde_json::from_slice::<GenerateResponse>(&line) else {
+                    continue;
+                };
+
+                // Add the new content; each streamed line carries about one token
+                if !response.response.is_empty() || !response.thinking.is_empty() {
+                    if let Some(chunks) = chunks {
+                        if !response.thinking.is_empty() {
+                            let _ = chunks.send(StreamChunk::Reasoning(response.thinking.clone()));
+                        }
+                        if !response.response.is_empty() {
+                            let _ = chunks.send(StreamChunk::Content(response.response.clone()));
+                        }
+                    }
                     full_content.push_str(&response.response);
-
-                    // Update model name if present
-                    if !response.model.is_empty() {
-                        model_name = response.model;
+                    thinking.push_str(&response.thinking);
+                    streamed_tokens += 1;
+                    progress_stats.update(streamed_tokens);
+                    if last_report.elapsed() >= PROGRESS_INTERVAL {
+                        on_progress(&progress_stats);
+                        last_report = Instant::now();
                     }
+                }
 
-                    // Update prompt token count if provided
-                    if let Some(count) = response.prompt_eval_count {
-                        prompt_tokens = count;
-                    }
+                // Update model name if present
+                if !response.model.is_empty() {
+                    model_name = response.model;
+                }
 
-                    // Update completion token count
-                    if let Some(count) = response.eval_count {
-                        completion_tokens = count;
-
-                        // Update progress stats
-                        progress_stats.update(count);
-
-                        // Update estimated total if we have completion percentage
-                        if let Some(total_duration) = response.total_duration {
-                            if let Some(eval_duration) = response.eval_duration {
-                                if eval_duration > 0 && total_duration > 0 {
-                                    // Estimate total tokens based on how much time has been used
-                                    let progress_percent =
-                                        eval_duration as f64 / total_duration as f64;
-                                    if progress_percent > 0.0 {
-                                        let estimated_total =
-                                            (count as f64 / progress_percent) as usize;
-                                        progress_stats.estimated_total_tokens =
-                                            Some(estimated_total);
-                                    }
-                                }
-                            }
-                        }
-                    }
+                // Update prompt token count if provided
+                if let Some(count) = response.prompt_eval_count {
+                    prompt_tokens = count;
+                }
 
-                    // If done is true, we've reached the end
-                    if response.done {
-                        // Mark progress as complete
-                        progress_stats.complete();
-                        break;
-                    }
+                // The final line carries the real completion token count
+                if let Some(count) = response.eval_count {
+                    completion_tokens = count;
+                    progress_stats.update(count);
+                }
+
+                // If done is true, we've reached the end
+                if response.done {
+                    truncated = response.done_reason.as_deref() == Some("length");
+                    // Mark progress as complete
+                    progress_stats.complete();
+                    on_progress(&progress_stats);
+                    finished = true;
+                    break;
                 }
             }
         }
@@ -204,13 +341,46 @@ impl AIClient for OllamaClient {
             model: model_name,
             usage,
             progress: Some(progress_stats),
+            cost: None,
+            reasoning: (!thinking.trim().is_empty()).then_some(thinking),
+            truncated,
         })
     }
+}
+
+#[async_trait]
+impl AIClient for OllamaClient {
+    async fn generate(&self, prompt: &str, context: Option<&str>) -> Result<AIResponse, AIError> {
+        self.generate_with_progress(prompt, context, Arc::new(|_| {}))
+            .await
+    }
+
+    async fn generate_with_progress(
+        &self,
+        prompt: &str,
+        context: Option<&str>,
+        on_progress: ProgressCallback,
+    ) -> Result<AIResponse, AIError> {
+        self.generate_streaming(prompt, context, None, on_progress)
+            .await
+    }
+
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        context: Option<&str>,
+        chunks: ChunkSender,
+        on_progress: ProgressCallback,
+    ) -> Result<AIResponse, AIError> {
+        self.generate_streaming(prompt, context, Some(&chunks), on_progress)
+            .await
+    }
 
     async fn models(&self) -> Result<Vec<String>, AIError> {
         let response = self
             .client
             .get(format!("{}/api/tags", self.base_url))
+            .headers(self.request_headers().await?)
             .send()
             .await
             .map_err(|e| AIError::APIError(format!("Failed to send request: {}", e)))?;
@@ -241,26 +411,7 @@ impl AIClient for OllamaClient {
     }
 
     fn get_model_costs(&self, model: &str) -> ModelCosts {
-        // Define costs for different Ollama models
-        // These are placeholder values since Ollama is free and local
-        // You can adjust these based on your needs or computational costs
-        match model {
-            m if m.contains("llama2") => ModelCosts {
-                prompt_cost_per_1k: 0.0001,     // $0.0001 per 1K tokens
-                completion_cost_per_1k: 0.0002, // $0.0002 per 1K tokens
-            },
-            m if m.contains("codellama") => ModelCosts {
-                prompt_cost_per_1k: 0.0002,
-                completion_cost_per_1k: 0.0004,
-            },
-            m if m.contains("qwen") => ModelCosts {
-                prompt_cost_per_1k: 0.0002,
-                completion_cost_per_1k: 0.0004,
-            },
-            _ => ModelCosts {
-                prompt_cost_per_1k: 0.0001,
-                completion_cost_per_1k: 0.0002,
-            },
-        }
+        // Free unless the `pricing` config puts a price on the compute
+        pricing::model_costs(Provider::Ollama, model)
     }
 }
@@ -0,0 +1,518 @@
+//! Client for OpenAI-compatible chat completion APIs
+//!
+//! OpenRouter, Groq, Mistral, Azure OpenAI and many gateways expose the
+//! OpenAI `/chat/completions` and `/models` endpoints. This client streams
+//! completions over server-sent events and reads the usage, and the cost when
+//! the provider reports one, from the final chunk.
+
+use crate::ai::extras::RequestExtras;
+use crate::ai::oauth::TokenSource;
+use crate::ai::rate_limit::{self, RateLimitInfo};
+use crate::ai::types::{
+    AIClient, AIError, AIResponse, ChunkSender, ModelCosts, ProgressCallback, ProgressStats,
+    StreamChunk, TokenUsage,
+};
+use crate::ai::{Provider, pricing};
+use crate::config::ModelConfig;
+use crate::utils::count_tokens;
+use async_trait::async_trait;
+use futures_util::StreamExt;
+use reqwest::Client;
+use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderName, HeaderValue};
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Shortest time between two progress reports while streaming
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Usage reported at the end of a stream
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ReportedUsage {
+    /// Tokens of the prompt
+    #[serde(default)]
+    pub prompt_tokens: usize,
+    /// Tokens of the completion
+    #[serde(default)]
+    pub completion_tokens: usize,
+    /// Both together
+    #[serde(default)]
+    pub total_tokens: usize,
+    /// What the request cost in dollars (OpenRouter)
+    #[serde(default)]
+    pub cost: Option<f64>,
+}
+
+/// One `data:` line of a streamed completion
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StreamEvent {
+    /// Text added by this chunk
+    pub content: String,
+    /// Reasoning added by this chunk
+    pub reasoning: String,
+    /// Model that answered, when the chunk names it
+    pub model: Option<String>,
+    /// Usage, sent with the last chunk
+    pub usage: Option<ReportedUsage>,
+    /// Error reported in the middle of the stream
+    pub error: Option<String>,
+    /// Why the model stopped, sent with the last chunk of the answer
+    pub finish_reason: Option<String>,
+    /// The `[DONE]` marker
+    pub done: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Chunk {
+    #[serde(default)]
+    model: Option<String>,
+    #[serde(default)]
+    choices: Vec<ChunkChoice>,
+    #[serde(default)]
+    usage: Option<ReportedUsage>,
+    #[serde(default)]
+    error: Option<ChunkError>,
+    /// Groq reports the usage here instead of in `usage`
+    #[serde(default)]
+    x_groq: Option<GroqExtension>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GroqExtension {
+    #[serde(default)]
+    usage: Option<ReportedUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkChoice {
+    #[serde(default)]
+    delta: Option<ChunkDelta>,
+    #[serde(default)]
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkDelta {
+    #[serde(default)]
+    content: Option<String>,
+    /// Reasoning as DeepSeek sends it
+    #[serde(default)]
+    reasoning_content: Option<String>,
+    /// Reasoning as OpenRouter and Groq send it
+    #[serde(default)]
+    reasoning: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChunkError {
+    message: String,
+}
+
+/// Parse one line of a server-sent event stream
+///
+/// Returns `None` for blank lines, comments (`: OPENROUTER PROCESSING`) and
+/// data that is not a completion chunk.
+pub fn parse_stream_line(line: &str) -> Option<StreamEvent> {
+    let data = line.trim().strip_prefix("data:")?.trim();
+    if data == "[DONE]" {
+        return Some(StreamEvent {
+            done: true,
+            ..Default::default()
+        });
+    }
+    let chunk: Chunk = serde_json::from_str(data).ok()?;
+    let finish_reason = chunk
+        .choices
+        .iter()
+        .find_map(|choice| choice.finish_reason.clone());
+    let deltas: Vec<ChunkDelta> = chunk
+        .choices
+        .into_iter()
+        .filter_map(|choice| choice.delta)
+        .collect();
+    Some(StreamEvent {
+        content: deltas
+            .iter()
+            .filter_map(|delta| delta.content.as_deref())
+            .collect(),
+        reasoning: deltas
+            .iter()
+            .filter_map(|delta| {
+                delta
+                    .reasoning_content
+                    .as_deref()
+                    .or(delta.reasoning.as_deref())
+            })
+            .collect(),
+        model: chunk.model.filter(|model| !model.is_empty()),
+        usage: chunk
+            .usage
+            .or_else(|| chunk.x_groq.and_then(|extension| extension.usage)),
+        error: chunk.error.map(|error| error.message),
+        finish_reason,
+        done: false,
+    })
+}
+
+/// Client for a provider with an OpenAI-compatible API
+pub struct OpenAICompatClient {
+    provider: Provider,
+    client: Client,
+    base_url: String,
+    api_key: String,
+    model: String,
+    temperature: f32,
+    max_tokens: usize,
+    headers: HeaderMap,
+    extras: RequestExtras,
+    auth: Option<TokenSource>,
+    stream_usage: bool,
+    seed: Option<u64>,
+    key_header: Option<HeaderName>,
+    query: Vec<(String, String)>,
+}
+
+impl OpenAICompatClient {
+    /// Create a client for `model` at `base_url` (ending in `/v1` or similar)
+    pub fn new(provider: Provider, base_url: String, api_key: String, model: &ModelConfig) -> Self {
+        Self {
+            provider,
+            client: Client::builder()
+                .timeout(Duration::from_secs(120))
+                .build()
+                .unwrap_or_default(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key,
+            model: model.name.clone(),
+            temperature: model.temperature,
+            max_tokens: model.max_tokens,
+            headers: HeaderMap::new(),
+            extras: RequestExtras::default(),
+            auth: None,
+            stream_usage: true,
+            seed: None,
+            key_header: None,
+            query: Vec::new(),
+        }
+    }
+
+    /// Send the API key as is in `header` instead of as a bearer token
+    ///
+    /// Azure OpenAI reads keys from `api-key`; tokens from `/login` are
+    /// still sent as bearer tokens.
+    pub fn with_key_header(mut self, header: HeaderName) -> Self {
+        self.key_header = Some(header);
+        self
+    }
+
+    /// Add a query parameter to every request, e.g. Azure's `api-version`
+    pub fn with_query(mut self, name: &str, value: &str) -> Self {
+        self.query.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Ask for reproducible sampling with `seed`
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Whether to ask for usage with `stream_options`
+    ///
+    /// Some APIs (Mistral) reject unknown fields but send the usage anyway.
+    pub fn with_stream_usage(mut self, stream_usage: bool) -> Self {
+        self.stream_usage = stream_usage;
+        self
+    }
+
+    /// Send the extra headers and body fields with every request
+    pub fn with_extras(mut self, extras: RequestExtras) -> Result<Self, AIError> {
+        self.headers = extras.header_map()?;
+        self.extras = extras;
+        Ok(self)
+    }
+
+    /// Authenticate with tokens from `/login` instead of the API key
+    pub fn with_auth(mut self, auth: TokenSource) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Headers for a request, with the API key or a fresh access token
+    async fn request_headers(&self) -> Result<HeaderMap, AIError> {
+        let mut headers = self.headers.clone();
+        let invalid = |_| {
+            AIError::ConfigError(format!(
+                "The {} API key is not a valid header",
+                self.provider
+            ))
+        };
+        let token = match (&self.auth, &self.key_header) {
+            (Some(auth), _) => auth.access_token().await?,
+            (None, Some(header)) => {
+                if !self.api_key.is_empty() {
+                    let value = HeaderValue::from_str(&self.api_key).map_err(invalid)?;
+                    headers.insert(header.clone(), value);
+                }
+                return Ok(headers);
+            }
+            (None, None) => self.api_key.clone(),
+        };
+        if !token.is_empty() {
+            let value = format!("Bearer {}", token).parse().map_err(invalid)?;
+            headers.insert(AUTHORIZATION, value);
+        }
+        Ok(headers)
+    }
+
+    /// Turn an unsuccessful response into an error
+    async fn check_status(
+        &self,
+        response: reqwest::Response,
+    ) -> Result<reqwest::Response, AIError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "<could not read error body>".to_string());
+        Err(match status {
+            reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                AIError::RateLimit(format!("{} returned {}", self.provider, status))
+            }
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => {
+                AIError::Authentication(format!(
+                    "{} rejected the credentials: {}",
+                    self.provider,
+                    body.trim()
+                ))
+            }
+            _ => AIError::APIError(format!(
+                "{} API returned error status: {} - {}",
+                self.provider,
+                status,
+                body.trim()
+            )),
+        })
+    }
+
+    /// Generate a completion, sending its text to `chunks` when given
+    async fn generate_streaming(
+        &self,
+        prompt: &str,
+        context: Option<&str>,
+        chunks: Option<&ChunkSender>,
+        on_progress: ProgressCallback,
+    ) -> Result<AIResponse, AIError> {
+        let mut messages = Vec::new();
+        if let Some(system) = context.filter(|system| !system.is_empty()) {
+            messages.push(json!({ "role": "system", "content": system }));
+        }
+        messages.push(json!({ "role": "user", "content": prompt }));
+
+        let mut body = json!({
+            "model": self.model,
+            "messages": messages,
+            "temperature": self.temperature,
+            "max_tokens": self.max_tokens,
+            "stream": true,
+        });
+        if self.stream_usage {
+            body["stream_options"] = json!({ "include_usage": true });
+        }
+        if let Some(seed) = self.seed {
+            // Mistral calls the seed `random_seed`
+            let field = match self.provider {
+                Provider::Mistral => "random_seed",
+                _ => "seed",
+            };
+            body[field] = json!(seed);
+        }
+        self.extras.merge_body(&mut body);
+
+        let mut progress = ProgressStats::new();
+        progress.estimated_total_tokens = Some(self.max_tokens);
+        let response = self
+            .client
+            .post(format!("{}/chat/completions", self.base_url))
+            .query(&self.query)
+            .headers(self.request_headers().await?)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| {
+                AIError::NetworkError(format!(
+                    "Failed to send request to {}: {}",
+                    self.provider, e
+                ))
+            })?;
+        rate_limit::record(
+            self.provider,
+            &RateLimitInfo::from_headers(response.headers()),
+        );
+        let response = self.check_status(response).await?;
+        progress.connected();
+        on_progress(&progress);
+
+        // Server-sent events: one `data:` line per chunk, split across
+        // network chunks at arbitrary points
+        let mut stream = response.bytes_stream();
+        let mut pending: Vec<u8> = Vec::new();
+        let mut content = String::new();
+        let mut reasoning = String::new();
+        let mut model = self.model.clone();
+        let mut usage = None;
+        let mut truncated = false;
+        let mut streamed_tokens = 0;
+        let mut last_report = Instant::now();
+
+        let mut finished = false;
+        while !finished {
+            match stream.next().await {
+                Some(chunk) => {
+                    let chunk = chunk.map_err(|e| {
+                        AIError::APIError(format!("Error reading stream chunk: {}", e))
+                    })?;
+                    pending.extend_from_slice(&chunk);
+                }
+                None => {
+                    finished = true;
+                    pending.push(b'\n');
+                }
+            }
+
+            while let Some(end) = pending.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = pending.drain(..=end).collect();
+                let Some(event) = parse_stream_line(&String::from_utf8_lossy(&line)) else {
+                    continue;
+                };
+                if let Some(error) = event.error {
+                    return Err(AIError::APIError(error));
+                }
+                if event.done {
+                    finished = true;
+                    break;
+                }
+                if !event.content.is_empty() || !event.reasoning.is_empty() {
+                    if let Some(chunks) = chunks {
+                        if !event.reasoning.is_empty() {
+                            let _ = chunks.send(StreamChunk::Reasoning(event.reasoning.clone()));
+                        }
+                        if !event.content.is_empty() {
+                            let _ = chunks.send(StreamChunk::Content(event.content.clone()));
+                        }
+                    }
+                    content.push_str(&event.content);
+                    reasoning.push_str(&event.reasoning);
+                    streamed_tokens += 1;
+                    progress.update(streamed_tokens);
+                    if last_report.elapsed() >= PROGRESS_INTERVAL {
+                        on_progress(&progress);
+                        last_report = Instant::now();
+                    }
+                }
+                if let Some(name) = event.model {
+                    model = name;
+                }
+                if event.usage.is_some() {
+                    usage = event.usage;
+                }
+                // Anthropic's compatible endpoint names it after the parameter
+                if let Some("length" | "max_tokens") = event.finish_reason.as_deref() {
+                    truncated = true;
+                }
+            }
+        }
+        progress.complete();
+        on_progress(&progress);
+
+        // Estimate when the provider did not report usage
+        let reported = usage.unwrap_or(ReportedUsage {
+            prompt_tokens: count_tokens(prompt),
+            completion_tokens: count_tokens(&content),
+            total_tokens: 0,
+            cost: None,
+        });
+        let usage = TokenUsage {
+            prompt_tokens: reported.prompt_tokens,
+            completion_tokens: reported.completion_tokens,
+            total_tokens: reported
+                .total_tokens
+                .max(reported.prompt_tokens + reported.completion_tokens),
+        };
+
+        Ok(AIResponse {
+            content,
+            model,
+            usage,
+            progress: Some(progress),
+            cost: reported.cost,
+            reasoning: (!reasoning.trim().is_empty()).then_some(reasoning),
+            truncated,
+        })
+    }
+}
+
+#[async_trait]
+impl AIClient for OpenAICompatClient {
+    async fn generate(&self, prompt: &str, context: Option<&str>) -> Result<AIResponse, AIError> {
+        self.generate_with_progress(prompt, context, Arc::new(|_| {}))
+            .await
+    }
+
+    async fn generate_with_progress(
+        &self,
+        prompt: &str,
+        context: Option<&str>,
+        on_progress: ProgressCallback,
+    ) -> Result<AIResponse, AIError> {
+        self.generate_streaming(prompt, context, None, on_progress)
+            .await
+    }
+
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        context: Option<&str>,
+        chunks: ChunkSender,
+        on_progress: ProgressCallback,
+    ) -> Result<AIResponse, AIError> {
+        self.generate_streaming(prompt, context, Some(&chunks), on_progress)
+            .await
+    }
+
+    async fn models(&self) -> Result<Vec<String>, AIError> {
+        #[derive(Deserialize)]
+        struct ModelInfo {
+            id: String,
+        }
+
+        #[derive(Deserialize)]
+        struct ModelsResponse {
+            data: Vec<ModelInfo>,
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/models", self.base_url))
+            .query(&self.query)
+            .headers(self.request_headers().await?)
+            .send()
+            .await
+            .map_err(|e| AIError::NetworkError(format!("Failed to send request: {}", e)))?;
+        let models: ModelsResponse = self
+            .check_status(response)
+            .await?
+            .json()
+            .await
+            .map_err(|e| AIError::InvalidResponse(format!("Failed to parse response: {}", e)))?;
+        Ok(models.data.into_iter().map(|model| model.id).collect())
+    }
+
+    fn get_model_costs(&self, model: &str) -> ModelCosts {
+        pricing::model_costs(self.provider, model)
+    }
+}
@@ -0,0 +1,152 @@
+//! OpenRouter specifics
+//!
+//! OpenRouter speaks the OpenAI API (see [`crate::ai::openai_compat`]) and
+//! adds attribution headers, a catalog of several hundred models with their
+//! prices, and the cost of each request in the usage it reports.
+
+use crate::ai::extras::RequestExtras;
+use crate::ai::types::{AIError, ModelCosts};
+use crate::ai::{Provider, pricing};
+use crate::config::OpenRouterConfig;
+use serde::Deserialize;
+use serde_json::json;
+use std::time::Duration;
+
+/// A model of the OpenRouter catalog
+#[derive(Debug, Clone, PartialEq)]
+pub struct CatalogModel {
+    /// Id used in requests, e.g. `anthropic/claude-3.5-sonnet`
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// Longest context in tokens
+    pub context_length: Option<u64>,
+    /// Price per 1,000 tokens, unless it varies (auto routing)
+    pub costs: Option<ModelCosts>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CatalogResponse {
+    data: Vec<CatalogEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CatalogEntry {
+    id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    context_length: Option<u64>,
+    #[serde(default)]
+    pricing: Option<CatalogPricing>,
+}
+
+/// Prices per token, sent as decimal strings
+#[derive(Debug, Deserialize)]
+struct CatalogPricing {
+    prompt: String,
+    completion: String,
+}
+
+/// The attribution headers and usage reporting, under the configured extras
+///
+/// Values from `extra_headers` and `extra_body` take precedence.
+pub fn request_extras(config: &OpenRouterConfig) -> RequestExtras {
+    let mut extras = RequestExtras::new(config.extra_headers.clone(), config.extra_body.clone());
+    if !config.app_url.is_empty() {
+        extras
+            .headers
+            .entry("HTTP-Referer".to_string())
+            .or_insert_with(|| config.app_url.clone());
+    }
+    if !config.app_title.is_empty() {
+        extras
+            .headers
+            .entry("X-Title".to_string())
+            .or_insert_with(|| config.app_title.clone());
+    }
+    // Ask for the cost of each request in the usage
+    extras
+        .body
+        .entry("usage".to_string())
+        .or_insert_with(|| json!({ "include": true }));
+    extras
+}
+
+/// Parse the response of `GET /models`
+pub fn parse_catalog(body: &str) -> Result<Vec<CatalogModel>, AIError> {
+    let response: CatalogResponse = serde_json::from_str(body)
+        .map_err(|e| AIError::InvalidResponse(format!("Unexpected model catalog: {}", e)))?;
+    Ok(response
+        .data
+        .into_iter()
+        .map(|entry| {
+            let costs = entry.pricing.and_then(|pricing| {
+                let per_1k = |price: &str| {
+                    price
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|price| *price >= 0.0)
+                        .map(|price| price * 1000.0)
+                };
+                Some(ModelCosts {
+                    prompt_cost_per_1k: per_1k(&pricing.prompt)?,
+                    completion_cost_per_1k: per_1k(&pricing.completion)?,
+                })
+            });
+            CatalogModel {
+                name: if entry.name.is_empty() {
+                    entry.id.clone()
+                } else {
+                    entry.name
+                },
+                id: entry.id,
+                context_length: entry.context_length,
+                costs,
+            }
+        })
+        .collect())
+}
+
+/// Fetch the catalog and remember its prices for cost estimates
+pub async fn fetch_catalog(endpoint: &str) -> Result<Vec<CatalogModel>, AIError> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .unwrap_or_default();
+    let response = client
+        .get(format!("{}/models", endpoint.trim_end_matches('/')))
+        .send()
+        .await
+        .map_err(|e| AIError::NetworkError(format!("Failed to reach OpenRouter: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(AIError::APIError(format!(
+            "OpenRouter returned {} for the model catalog",
+            response.status()
+        )));
+    }
+    let body = response
+        .text()
+        .await
+        .map_err(|e| AIError::NetworkError(format!("Failed to read the model catalog: {}", e)))?;
+
+    let catalog = parse_catalog(&body)?;
+    for model in &catalog {
+        if let Some(costs) = &model.costs {
+            pricing::register(Provider::OpenRouter, &model.id, costs.clone());
+        }
+    }
+    Ok(catalog)
+}
+
+/// Catalog entries whose id or name contains every word of `filter`
+pub fn filter_catalog<'a>(catalog: &'a [CatalogModel], filter: &str) -> Vec<&'a CatalogModel> {
+    let words: Vec<String> = filter.split_whitespace().map(str::to_lowercase).collect();
+    catalog
+        .iter()
+        .filter(|model| {
+            let haystack = format!("{} {}", model.id, model.name).to_lowercase();
+            words.iter().all(|word| haystack.contains(word))
+        })
+        .collect()
+}
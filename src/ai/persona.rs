@@ -0,0 +1,82 @@
+//! Personas: named roles such as a reviewer or a tester
+//!
+//! A persona has its own system prompt and may use another provider, model
+//! or temperature. `/as <name>` makes one answer every chat prompt and an
+//! `@name:` prefix sends a single prompt to it. Personas share the output,
+//! pinned context and attachments; only the system prompt differs.
+
+use crate::config::{AIConfig, Persona};
+use std::collections::BTreeMap;
+
+/// Split an `@name:` prefix naming a configured persona off a prompt
+///
+/// Prompts starting with `@` that do not name a persona are left as they are.
+pub fn parse_prefix<'a>(
+    prompt: &'a str,
+    personas: &BTreeMap<String, Persona>,
+) -> (Option<String>, &'a str) {
+    let Some(rest) = prompt.strip_prefix('@') else {
+        return (None, prompt);
+    };
+    let Some((name, text)) = rest.split_once(':') else {
+        return (None, prompt);
+    };
+    match find(name.trim(), personas) {
+        Some(name) => (Some(name), text.trim_start()),
+        None => (None, prompt),
+    }
+}
+
+/// Name of the configured persona matching `name`, ignoring case
+pub fn find(name: &str, personas: &BTreeMap<String, Persona>) -> Option<String> {
+    personas
+        .keys()
+        .find(|key| key.eq_ignore_ascii_case(name))
+        .cloned()
+}
+
+/// Apply the provider, model and temperature of a persona to `ai`
+pub fn apply(persona: &Persona, ai: &mut AIConfig) {
+    if let Some(provider) = persona.provider {
+        ai.active_provider = provider;
+    }
+    if !persona.model.is_empty() {
+        ai.select_model(&persona.model);
+    }
+    if let Some(temperature) = persona.temperature {
+        ai.set_temperature(temperature);
+    }
+}
+
+/// Whether a persona picks its own provider or model, overriding routing
+pub fn overrides_model(persona: &Persona) -> bool {
+    persona.provider.is_some() || !persona.model.is_empty()
+}
+
+/// One-line description: model, temperature and the start of the system prompt
+pub fn describe(persona: &Persona, ai: &AIConfig) -> String {
+    let mut ai = ai.clone();
+    apply(persona, &mut ai);
+    let model = ai.get_active_model_config();
+    let first_line = persona
+        .system_prompt
+        .trim()
+        .lines()
+        .next()
+        .unwrap_or_default();
+    let mut prompt: String = first_line.chars().take(50).collect();
+    if prompt.len() < persona.system_prompt.trim().len() {
+        prompt.push('…');
+    }
+    format!(
+        "{} {}, temperature {} — {}",
+        ai.active_provider,
+        model.name,
+        model.temperature,
+        if prompt.is_empty() {
+            "no system prompt".to_string()
+        } else {
+            prompt
+        }
+    )
+}
@@ -0,0 +1,223 @@
+//! Model pricing
+//!
+//! Approximate list prices of the hosted models, used to estimate what a
+//! prompt will cost before it is sent. Local providers are free. Prices
+//! fetched from a provider's catalog (OpenRouter) are registered at runtime
+//! and take precedence over the table, and prices in the `pricing` config
+//! take precedence over both, also for local models.
+
+use crate::ai::{AIResponse, ModelCosts, Provider, TokenUsage};
+use crate::config::{ModelPrice, get_config};
+use crate::utils::{count_tokens, format_number};
+use once_cell::sync::Lazy;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// Prices read from provider catalogs
+static REGISTERED: Lazy<Mutex<HashMap<(Provider, String), ModelCosts>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Prices per 1,000 tokens (prompt, completion), matched by model name prefix
+const PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4.1-nano", 0.000_1, 0.000_4),
+    ("gpt-4.1-mini", 0.000_4, 0.001_6),
+    ("gpt-4.1", 0.002, 0.008),
+    ("gpt-4o-mini", 0.000_15, 0.000_6),
+    ("gpt-4o", 0.002_5, 0.01),
+    ("gpt-4-turbo", 0.01, 0.03),
+    ("gpt-4", 0.03, 0.06),
+    ("gpt-3.5-turbo", 0.000_5, 0.001_5),
+    ("o1-mini", 0.003, 0.012),
+    ("o1", 0.015, 0.06),
+    ("o3-mini", 0.001_1, 0.004_4),
+    ("claude-opus-4", 0.015, 0.075),
+    ("claude-sonnet-4", 0.003, 0.015),
+    ("claude-3-7-sonnet", 0.003, 0.015),
+    ("claude-3-5-sonnet", 0.003, 0.015),
+    ("claude-3-5-haiku", 0.000_8, 0.004),
+    ("claude-3-opus", 0.015, 0.075),
+    ("claude-3-sonnet", 0.003, 0.015),
+    ("claude-3-haiku", 0.000_25, 0.001_25),
+    // Groq
+    ("llama-3.1-8b", 0.000_05, 0.000_08),
+    ("llama-3.3-70b", 0.000_59, 0.000_79),
+    ("llama-3.1-70b", 0.000_59, 0.000_79),
+    ("mixtral-8x7b", 0.000_24, 0.000_24),
+    ("gemma2-9b", 0.000_2, 0.000_2),
+    // Mistral, whose `-latest` aliases match by prefix
+    ("mistral-large", 0.002, 0.006),
+    ("mistral-small", 0.000_2, 0.000_6),
+    ("codestral", 0.000_3, 0.000_9),
+    ("open-mistral-nemo", 0.000_15, 0.000_15),
+    ("ministral-8b", 0.000_1, 0.000_1),
+    ("ministral-3b", 0.000_04, 0.000_04),
+];
+
+/// Prices per image of the OpenAI image models (model prefix, size, dollars)
+const IMAGE_PRICES: &[(&str, &str, f64)] = &[
+    ("dall-e-3", "1024x1024", 0.04),
+    ("dall-e-3", "1024x1792", 0.08),
+    ("dall-e-3", "1792x1024", 0.08),
+    ("dall-e-2", "1024x1024", 0.02),
+    ("dall-e-2", "512x512", 0.018),
+    ("dall-e-2", "256x256", 0.016),
+];
+
+/// Prices per 1,000 tokens (text input, image output) of token-billed image models
+const IMAGE_TOKEN_PRICES: &[(&str, f64, f64)] = &[("gpt-image-1", 0.005, 0.04)];
+
+/// Where the price of a model comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceSource {
+    /// The `pricing` config
+    Config,
+    /// The provider's catalog
+    Catalog,
+    /// The built-in table
+    BuiltIn,
+    /// A local model, or a model without a known price
+    Free,
+}
+
+impl std::fmt::Display for PriceSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PriceSource::Config => "configured",
+            PriceSource::Catalog => "from the provider's catalog",
+            PriceSource::BuiltIn => "built-in",
+            PriceSource::Free => "free or unknown",
+        })
+    }
+}
+
+/// Costs of a model, or zero when it runs locally or its price is unknown
+pub fn model_costs(provider: Provider, model: &str) -> ModelCosts {
+    lookup(&get_config().pricing, provider, model).0
+}
+
+/// Costs of a model with `prices` taking precedence, and where they come from
+///
+/// Of the configured prices, the one naming the longest prefix of the model
+/// wins, so `gpt-4o-mini` is not priced as `gpt-4o`.
+pub fn lookup(prices: &[ModelPrice], provider: Provider, model: &str) -> (ModelCosts, PriceSource) {
+    let free = ModelCosts {
+        prompt_cost_per_1k: 0.0,
+        completion_cost_per_1k: 0.0,
+    };
+
+    // OpenRouter names models `vendor/model` and writes versions with dots
+    let bare = model.rsplit('/').next().unwrap_or(model);
+    let dashed = bare.replace('.', "-");
+
+    if let Some(price) = prices
+        .iter()
+        .filter(|price| price.provider.is_none_or(|priced| priced == provider))
+        .filter(|price| {
+            [model, bare, dashed.as_str()]
+                .iter()
+                .any(|name| name.starts_with(&price.model))
+        })
+        .max_by_key(|price| price.model.len())
+    {
+        let costs = ModelCosts {
+            prompt_cost_per_1k: price.prompt,
+            completion_cost_per_1k: price.completion,
+        };
+        return (costs, PriceSource::Config);
+    }
+    if provider.is_local() {
+        return (free, PriceSource::Free);
+    }
+    if let Some(costs) = REGISTERED
+        .lock()
+        .ok()
+        .and_then(|registered| registered.get(&(provider, model.to_string())).cloned())
+    {
+        return (costs, PriceSource::Catalog);
+    }
+
+    // Entries are ordered so that more specific prefixes come first
+    [bare, dashed.as_str()]
+        .iter()
+        .find_map(|name| {
+            PRICES
+                .iter()
+                .find(|(prefix, _, _)| name.starts_with(prefix))
+        })
+        .map(|&(_, prompt, completion)| {
+            let costs = ModelCosts {
+                prompt_cost_per_1k: prompt,
+                completion_cost_per_1k: completion,
+            };
+            (costs, PriceSource::BuiltIn)
+        })
+        .unwrap_or((free, PriceSource::Free))
+}
+
+/// What generating one image cost, or zero when the price is unknown
+///
+/// Token-billed models use the usage the API reported; the others have a
+/// price per image and size.
+pub fn image_cost(model: &str, size: &str, usage: &TokenUsage) -> f64 {
+    if let Some(&(_, input, output)) = IMAGE_TOKEN_PRICES
+        .iter()
+        .find(|(prefix, _, _)| model.starts_with(prefix))
+    {
+        return usage.prompt_tokens as f64 / 1000.0 * input
+            + usage.completion_tokens as f64 / 1000.0 * output;
+    }
+    IMAGE_PRICES
+        .iter()
+        .find(|(prefix, priced_size, _)| model.starts_with(prefix) && *priced_size == size)
+        .map_or(0.0, |&(_, _, price)| price)
+}
+
+/// Remember the price of a model, e.g. from a provider's catalog
+pub fn register(provider: Provider, model: &str, costs: ModelCosts) {
+    if let Ok(mut registered) = REGISTERED.lock() {
+        registered.insert((provider, model.to_string()), costs);
+    }
+}
+
+/// What a response cost: the cost the provider reported, or the list price
+pub fn response_cost(provider: Provider, response: &AIResponse) -> f64 {
+    response
+        .cost
+        .unwrap_or_else(|| model_costs(provider, &response.model).calculate_cost(&response.usage))
+}
+
+/// Estimated size and cost of a prompt
+#[derive(Debug, Clone, PartialEq)]
+pub struct PromptEstimate {
+    /// Approximate prompt tokens
+    pub tokens: usize,
+    /// Estimated cost of the prompt tokens in dollars
+    pub cost: f64,
+    /// Model the prompt goes to
+    pub model: String,
+}
+
+impl PromptEstimate {
+    /// Estimate the prompt tokens and their cost for a model
+    pub fn new(provider: Provider, model: &str, prompt: &str) -> Self {
+        let tokens = count_tokens(prompt);
+        let costs = model_costs(provider, model);
+        Self {
+            tokens,
+            cost: tokens as f64 / 1000.0 * costs.prompt_cost_per_1k,
+            model: model.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for PromptEstimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "~{} tokens, ≈${:.3} with {}",
+            format_number(self.tokens),
+            self.cost,
+            self.model
+        )
+    }
+}
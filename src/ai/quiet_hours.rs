@@ -0,0 +1,147 @@
+//! Scheduled maintenance windows of providers
+//!
+//! A provider's `quiet_hours` lists times it is known to be down, such as a
+//! self-hosted cluster that reboots every night. Each window is written
+//! `HH:MM-HH:MM`, optionally after the days it applies to (`mon-fri`,
+//! `sat,sun`); a window ending before it starts runs past midnight and
+//! belongs to the day it starts on, and equal times cover a whole day.
+//! During a window routing leaves the provider out and prompts for it go to
+//! another provider, while the status bar shows when the active provider is
+//! back.
+
+use super::types::Provider;
+use crate::config::AIConfig;
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Weekday};
+
+/// Days in the order they are written in ranges
+const DAYS: [(&str, Weekday); 7] = [
+    ("mon", Weekday::Mon),
+    ("tue", Weekday::Tue),
+    ("wed", Weekday::Wed),
+    ("thu", Weekday::Thu),
+    ("fri", Weekday::Fri),
+    ("sat", Weekday::Sat),
+    ("sun", Weekday::Sun),
+];
+
+/// One maintenance window
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuietWindow {
+    /// Days the window starts on; empty for every day
+    pub days: Vec<Weekday>,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl QuietWindow {
+    /// Parse `[days ]HH:MM-HH:MM`
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        let (days, times) = match spec.rsplit_once(char::is_whitespace) {
+            Some((days, times)) => (parse_days(days.trim())?, times),
+            None => (Vec::new(), spec),
+        };
+        let (start, end) = times
+            .split_once('-')
+            .ok_or_else(|| format!("'{}' is not HH:MM-HH:MM", spec))?;
+        let time = |text: &str| {
+            NaiveTime::parse_from_str(text.trim(), "%H:%M")
+                .map_err(|_| format!("invalid time '{}' in '{}'", text.trim(), spec))
+        };
+        Ok(Self {
+            days,
+            start: time(start)?,
+            end: time(end)?,
+        })
+    }
+
+    /// Whether the window applies to windows starting on `day`
+    fn starts_on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    /// When the window containing `at` ends, if it contains `at`
+    pub fn ends_after(&self, at: NaiveDateTime) -> Option<NaiveDateTime> {
+        let time = at.time();
+        let today = at.date();
+        if self.start < self.end {
+            return (self.starts_on(today.weekday()) && time >= self.start && time < self.end)
+                .then(|| today.and_time(self.end));
+        }
+        // Runs past midnight: started today or is left over from yesterday
+        if self.starts_on(today.weekday()) && time >= self.start {
+            return Some((today + Duration::days(1)).and_time(self.end));
+        }
+        let yesterday = today - Duration::days(1);
+        (self.starts_on(yesterday.weekday()) && time < self.end).then(|| today.and_time(self.end))
+    }
+}
+
+/// Days given as `mon,wed`, `mon-fri` or both
+fn parse_days(text: &str) -> Result<Vec<Weekday>, String> {
+    let index = |name: &str| {
+        DAYS.iter()
+            .position(|(day, _)| name.trim().to_lowercase().starts_with(day))
+            .ok_or_else(|| format!("unknown day '{}'", name.trim()))
+    };
+    let mut days = Vec::new();
+    for part in text.split(',') {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let (first, last) = (index(first)?, index(last)?);
+                let mut idx = first;
+                loop {
+                    days.push(DAYS[idx].1);
+                    if idx == last {
+                        break;
+                    }
+                    idx = (idx + 1) % DAYS.len();
+                }
+            }
+            None => days.push(DAYS[index(part)?].1),
+        }
+    }
+    Ok(days)
+}
+
+/// When the quiet window containing `at` ends, if any of `specs` contains it
+///
+/// Windows that do not parse are ignored; [`invalid`] lists them.
+pub fn quiet_until(specs: &[String], at: NaiveDateTime) -> Option<NaiveDateTime> {
+    specs
+        .iter()
+        .filter_map(|spec| QuietWindow::parse(spec).ok())
+        .filter_map(|window| window.ends_after(at))
+        .max()
+}
+
+/// Why each window of `specs` that does not parse is invalid
+pub fn invalid(specs: &[String]) -> Vec<String> {
+    specs
+        .iter()
+        .filter_map(|spec| QuietWindow::parse(spec).err())
+        .collect()
+}
+
+/// When the quiet hours `provider` is in at `at` end
+pub fn provider_quiet_until(
+    ai: &AIConfig,
+    provider: Provider,
+    at: NaiveDateTime,
+) -> Option<NaiveDateTime> {
+    quiet_until(ai.quiet_hours(provider), at)
+}
+
+/// The current local time, as quiet hours are written
+pub fn now() -> NaiveDateTime {
+    chrono::Local::now().naive_local()
+}
+
+/// When a window ends, as shown to the user: the time, with the day if it is not `at`'s
+pub fn format_until(until: NaiveDateTime, at: NaiveDateTime) -> String {
+    if until.date() == at.date() {
+        until.format("%H:%M").to_string()
+    } else {
+        until.format("%a %H:%M").to_string()
+    }
+}
@@ -0,0 +1,167 @@
+//! Provider rate limits
+//!
+//! Providers report their limits in response headers (remaining requests and
+//! tokens, and when they reset). The clients record those headers here so
+//! that new requests wait for the window to reset instead of running into
+//! HTTP 429 errors.
+
+use crate::ai::Provider;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Time each provider is blocked until
+static BLOCKED_UNTIL: Lazy<Mutex<HashMap<Provider, Instant>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Rate limit state reported by a provider
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimitInfo {
+    /// Requests left in the current window
+    pub remaining_requests: Option<u64>,
+    /// Tokens left in the current window
+    pub remaining_tokens: Option<u64>,
+    /// Time until the request window resets
+    pub reset_requests: Option<Duration>,
+    /// Time until the token window resets
+    pub reset_tokens: Option<Duration>,
+    /// Explicit wait requested with `Retry-After`
+    pub retry_after: Option<Duration>,
+}
+
+impl RateLimitInfo {
+    /// Read the OpenAI (`x-ratelimit-*`), Anthropic (`anthropic-ratelimit-*`)
+    /// and `Retry-After` headers
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        Self::from_headers_at(headers, Utc::now())
+    }
+
+    /// Like [`RateLimitInfo::from_headers`], resolving reset timestamps against `now`
+    pub fn from_headers_at(headers: &HeaderMap, now: DateTime<Utc>) -> Self {
+        let header = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| headers.get(*name)?.to_str().ok())
+                .map(str::trim)
+        };
+        let number = |names: &[&str]| header(names)?.parse::<u64>().ok();
+        let reset = |names: &[&str]| parse_reset(header(names)?, now);
+
+        Self {
+            remaining_requests: number(&[
+                "x-ratelimit-remaining-requests",
+                "anthropic-ratelimit-requests-remaining",
+            ]),
+            remaining_tokens: number(&[
+                "x-ratelimit-remaining-tokens",
+                "anthropic-ratelimit-tokens-remaining",
+            ]),
+            reset_requests: reset(&[
+                "x-ratelimit-reset-requests",
+                "anthropic-ratelimit-requests-reset",
+            ]),
+            reset_tokens: reset(&[
+                "x-ratelimit-reset-tokens",
+                "anthropic-ratelimit-tokens-reset",
+            ]),
+            retry_after: reset(&["retry-after"]),
+        }
+    }
+
+    /// How long the next request has to wait, if the limits are exhausted
+    pub fn wait_time(&self) -> Option<Duration> {
+        let exhausted = |remaining: Option<u64>, reset: Option<Duration>| {
+            reset.filter(|_| remaining == Some(0))
+        };
+        [
+            self.retry_after,
+            exhausted(self.remaining_requests, self.reset_requests),
+            exhausted(self.remaining_tokens, self.reset_tokens),
+        ]
+        .into_iter()
+        .flatten()
+        .max()
+    }
+}
+
+/// Parse a reset value: seconds (`12`, `0.5`), a Go-style duration
+/// (`6m0s`, `20ms`) or an RFC 3339 timestamp
+///
+/// The values come from the server, so anything that is not a finite,
+/// non-negative duration gives no reset hint.
+fn parse_reset(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    if let Ok(seconds) = value.parse::<f64>() {
+        return seconds_to_duration(seconds);
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(
+            (time.with_timezone(&Utc) - now)
+                .to_std()
+                .unwrap_or_default(),
+        );
+    }
+    parse_go_duration(value)
+}
+
+/// Parse durations such as `1h2m3s`, `6m0s`, `1.5s` or `20ms`
+fn parse_go_duration(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .filter(|&idx| idx > 0)?;
+        let amount: f64 = rest[..split].parse().ok()?;
+        rest = &rest[split..];
+
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let factor = match &rest[..unit_len] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        total += amount * factor;
+        rest = &rest[unit_len..];
+    }
+    seconds_to_duration(total)
+}
+
+/// `seconds` as a duration, `None` when negative, not finite or too large
+fn seconds_to_duration(seconds: f64) -> Option<Duration> {
+    if !seconds.is_finite() || seconds < 0.0 {
+        return None;
+    }
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Record the limits reported with a response from `provider`
+pub fn record(provider: Provider, info: &RateLimitInfo) {
+    // A wait too long to represent is no hint either
+    if let Some(until) = info
+        .wait_time()
+        .and_then(|wait| Instant::now().checked_add(wait))
+    {
+        let mut blocked = BLOCKED_UNTIL.lock().unwrap();
+        let entry = blocked.entry(provider).or_insert(until);
+        *entry = (*entry).max(until);
+    }
+}
+
+/// Time left before requests to `provider` may be sent again
+pub fn delay_for(provider: Provider) -> Option<Duration> {
+    let mut blocked = BLOCKED_UNTIL.lock().unwrap();
+    let until = *blocked.get(&provider)?;
+    let now = Instant::now();
+    if until <= now {
+        blocked.remove(&provider);
+        return None;
+    }
+    Some(until - now)
+}
@@ -0,0 +1,170 @@
+//! Prompt routing by kind of prompt
+//!
+//! With `routing.enabled`, chat prompts are classified as quick questions,
+//! code generation or vision prompts and sent to the model configured for
+//! that tier, e.g. a small local model for trivial questions and a large
+//! cloud model for code. A `%simple`, `%code` or `%vision` prefix picks the
+//! tier by hand and `%default` keeps the active model. Tiers whose provider
+//! is in its quiet hours are skipped.
+
+use super::quiet_hours;
+use super::types::Provider;
+use crate::config::{AIConfig, RoutingConfig};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Words that ask for code to be written or changed
+const CODE_WORDS: [&str; 20] = [
+    "implement",
+    "write",
+    "refactor",
+    "generate",
+    "create",
+    "build",
+    "debug",
+    "fix",
+    "function",
+    "class",
+    "struct",
+    "module",
+    "test",
+    "tests",
+    "script",
+    "code",
+    "program",
+    "convert",
+    "port",
+    "optimize",
+];
+
+/// Image files mentioned in a prompt
+static IMAGE_PATH: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)\S+\.(png|jpe?g|gif|webp|bmp)\b").unwrap());
+
+/// Kind of prompt, deciding its model tier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptClass {
+    /// Short question answered by a small model
+    Simple,
+    /// Code generation or a long prompt
+    Code,
+    /// Prompt with an image
+    Vision,
+}
+
+impl PromptClass {
+    /// Name used in the override prefix and the response footer
+    pub fn label(&self) -> &'static str {
+        match self {
+            PromptClass::Simple => "simple",
+            PromptClass::Code => "code",
+            PromptClass::Vision => "vision",
+        }
+    }
+}
+
+/// Where a routed prompt goes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Route {
+    pub class: PromptClass,
+    pub provider: Provider,
+    pub model: String,
+}
+
+impl std::fmt::Display for Route {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} → {} {}",
+            self.class.label(),
+            self.provider,
+            self.model
+        )
+    }
+}
+
+/// Tier chosen with a prefix: `Some(None)` for `%default`
+type Override = Option<Option<PromptClass>>;
+
+/// Split a `%tier` prefix off a prompt
+pub fn parse_override(prompt: &str) -> (Override, &str) {
+    let Some(rest) = prompt.strip_prefix('%') else {
+        return (None, prompt);
+    };
+    let (word, text) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let class = match word {
+        "simple" => Some(PromptClass::Simple),
+        "code" => Some(PromptClass::Code),
+        "vision" => Some(PromptClass::Vision),
+        "default" => None,
+        _ => return (None, prompt),
+    };
+    (Some(class), text.trim_start())
+}
+
+/// Classify a prompt; `has_attachments` counts files sent along with it
+pub fn classify(prompt: &str, has_attachments: bool, config: &RoutingConfig) -> PromptClass {
+    if IMAGE_PATH.is_match(prompt) {
+        return PromptClass::Vision;
+    }
+    let words: Vec<String> = prompt
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let asks_for_code = words.iter().any(|word| CODE_WORDS.contains(&word.as_str()));
+    if has_attachments
+        || asks_for_code
+        || prompt.contains("```")
+        || words.len() > config.simple_max_words
+    {
+        PromptClass::Code
+    } else {
+        PromptClass::Simple
+    }
+}
+
+/// Route a chat prompt, returning the route and the prompt without its prefix
+///
+/// Prompts are left on the active model when routing is off and there is no
+/// prefix, or when their tier has no model configured or its provider is in
+/// quiet hours.
+pub fn route<'a>(
+    prompt: &'a str,
+    has_attachments: bool,
+    routing: &RoutingConfig,
+    ai: &AIConfig,
+) -> (Option<Route>, &'a str) {
+    let (chosen, text) = parse_override(prompt);
+    let class = match chosen {
+        Some(class) => class,
+        None if routing.enabled => Some(classify(text, has_attachments, routing)),
+        None => None,
+    };
+    let target = match class {
+        Some(PromptClass::Simple) => routing.simple.as_ref(),
+        Some(PromptClass::Code) => routing.code.as_ref(),
+        Some(PromptClass::Vision) => routing.vision.as_ref(),
+        None => None,
+    }
+    .filter(|target| {
+        quiet_hours::provider_quiet_until(ai, target.provider, quiet_hours::now()).is_none()
+    });
+    let route = class.zip(target).map(|(class, target)| {
+        let model = if target.model.is_empty() {
+            let mut ai = ai.clone();
+            ai.active_provider = target.provider;
+            ai.get_active_model_config().name
+        } else {
+            target.model.clone()
+        };
+        Route {
+            class,
+            provider: target.provider,
+            model,
+        }
+    });
+    (route, text)
+}
@@ -0,0 +1,58 @@
+//! Sampling presets per command
+//!
+//! Commands whose answers should not vary, such as `/commitmsg`, can be
+//! bound to a low temperature and a fixed seed, while open-ended chat gets a
+//! higher one. `sampling.commands` names the preset of each command; the
+//! key of a prompt is the name of the template it came from, then
+//! `template`, then the kind of prompt (`chat`, `refactor`, `doc`,
+//! `commitmsg`, `rewrite`). A persona's own temperature wins over the preset.
+
+use crate::config::{AIConfig, SamplingConfig, SamplingPreset};
+
+/// Presets available without configuration
+const BUILT_IN: [(&str, f32, Option<u64>); 4] = [
+    ("deterministic", 0.0, Some(0)),
+    ("precise", 0.2, None),
+    ("balanced", 0.7, None),
+    ("creative", 1.0, None),
+];
+
+/// The preset called `name`: a configured one, or a built-in one
+pub fn preset(name: &str, config: &SamplingConfig) -> Option<SamplingPreset> {
+    config.presets.get(name).cloned().or_else(|| {
+        BUILT_IN
+            .iter()
+            .find(|(builtin, _, _)| *builtin == name)
+            .map(|&(_, temperature, seed)| SamplingPreset { temperature, seed })
+    })
+}
+
+/// The name and preset bound to the first of `keys` that has one
+///
+/// Fails when the bound preset does not exist.
+pub fn for_command(
+    keys: &[&str],
+    config: &SamplingConfig,
+) -> Result<Option<(String, SamplingPreset)>, String> {
+    let Some((key, name)) = keys
+        .iter()
+        .find_map(|key| config.commands.get(*key).map(|name| (key, name)))
+    else {
+        return Ok(None);
+    };
+    match preset(name, config) {
+        Some(preset) => Ok(Some((name.clone(), preset))),
+        None => Err(format!(
+            "no sampling preset named '{}' (bound to {})",
+            name, key
+        )),
+    }
+}
+
+/// Apply a preset to the selected model of `ai`
+pub fn apply(preset: &SamplingPreset, ai: &mut AIConfig) {
+    ai.set_temperature(preset.temperature);
+    if preset.seed.is_some() {
+        ai.seed = preset.seed;
+    }
+}
@@ -4,6 +4,7 @@
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use thiserror::Error;
 
 /// Supported AI provider types
@@ -17,6 +18,18 @@ pub enum Provider {
     Anthropic,
     /// Local models via LM Studio
     LMStudio,
+    /// Models of many vendors through OpenRouter's OpenAI-compatible API
+    OpenRouter,
+    /// Open models on Groq's LPU inference API
+    Groq,
+    /// Mistral AI's models, including Codestral
+    Mistral,
+    /// GGUF models run in-process by llama.cpp (needs the `llama` feature)
+    LlamaCpp,
+    /// OpenAI models deployed to an Azure OpenAI resource
+    AzureOpenAI,
+    /// Any OpenAI-compatible server, e.g. vLLM or a llama.cpp server
+    Custom,
 }
 
 impl std::fmt::Display for Provider {
@@ -26,6 +39,12 @@ impl std::fmt::Display for Provider {
             Provider::OpenAI => write!(f, "OpenAI"),
             Provider::Anthropic => write!(f, "Anthropic"),
             Provider::LMStudio => write!(f, "LMStudio"),
+            Provider::OpenRouter => write!(f, "OpenRouter"),
+            Provider::Groq => write!(f, "Groq"),
+            Provider::Mistral => write!(f, "Mistral"),
+            Provider::LlamaCpp => write!(f, "llama.cpp"),
+            Provider::AzureOpenAI => write!(f, "Azure OpenAI"),
+            Provider::Custom => write!(f, "Custom"),
         }
     }
 }
@@ -39,11 +58,41 @@ impl std::str::FromStr for Provider {
             "openai" => Ok(Provider::OpenAI),
             "anthropic" => Ok(Provider::Anthropic),
             "lmstudio" => Ok(Provider::LMStudio),
+            "openrouter" => Ok(Provider::OpenRouter),
+            "groq" => Ok(Provider::Groq),
+            "mistral" => Ok(Provider::Mistral),
+            "llamacpp" | "llama.cpp" => Ok(Provider::LlamaCpp),
+            "azure" | "azureopenai" | "azure_openai" => Ok(Provider::AzureOpenAI),
+            "custom" | "openai-compatible" | "openai_compatible" => Ok(Provider::Custom),
             _ => Err(format!("Unknown provider: {}", s)),
         }
     }
 }
 
+impl Provider {
+    /// Every provider, in the order of the config
+    pub const ALL: [Provider; 10] = [
+        Provider::Ollama,
+        Provider::OpenAI,
+        Provider::Anthropic,
+        Provider::LMStudio,
+        Provider::OpenRouter,
+        Provider::Groq,
+        Provider::Mistral,
+        Provider::LlamaCpp,
+        Provider::AzureOpenAI,
+        Provider::Custom,
+    ];
+
+    /// Whether the provider runs on this machine and works without internet access
+    pub fn is_local(&self) -> bool {
+        matches!(
+            self,
+            Provider::Ollama | Provider::LMStudio | Provider::LlamaCpp
+        )
+    }
+}
+
 impl Default for Provider {
     fn default() -> Self {
         Self::Ollama
@@ -106,6 +155,20 @@ pub struct AIResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub progress: Option<ProgressStats>,
+
+    /// Cost in dollars reported by the provider, preferred over list prices
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub cost: Option<f64>,
+
+    /// Reasoning the model produced before its answer, not part of `content`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub reasoning: Option<String>,
+
+    /// The model stopped because it reached `max_tokens`
+    #[serde(default)]
+    pub truncated: bool,
 }
 
 /// Token usage statistics
@@ -122,7 +185,7 @@ pub struct TokenUsage {
 }
 
 /// Model cost information
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ModelCosts {
     /// Cost per 1,000 prompt tokens
     pub prompt_cost_per_1k: f64,
@@ -195,6 +258,17 @@ impl std::fmt::Display for TaskStatus {
     }
 }
 
+/// How far a generation got, as reported by the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GenerationStage {
+    /// The request is sent, no answer from the server yet
+    Connecting,
+    /// The server accepted the request, no token generated yet
+    WaitingForFirstToken,
+    /// Tokens are arriving
+    Streaming,
+}
+
 /// Progress statistics for tracking task execution
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProgressStats {
@@ -215,6 +289,18 @@ pub struct ProgressStats {
 
     /// Estimated completion percentage (0-100)
     pub completion_percent: Option<f64>,
+
+    /// When the server accepted the request
+    #[serde(default)]
+    pub connected_at: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// When the first token arrived
+    #[serde(default)]
+    pub first_token_at: Option<chrono::DateTime<chrono::Utc>>,
+
+    /// When the generation completed
+    #[serde(default)]
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 impl Default for ProgressStats {
@@ -226,6 +312,9 @@ impl Default for ProgressStats {
             last_update: chrono::Utc::now(),
             tokens_per_second: 0.0,
             completion_percent: None,
+            connected_at: None,
+            first_token_at: None,
+            finished_at: None,
         }
     }
 }
@@ -239,6 +328,9 @@ impl ProgressStats {
     /// Update progress with new token count
     pub fn update(&mut self, tokens_generated: usize) {
         let now = chrono::Utc::now();
+        if tokens_generated > 0 && self.first_token_at.is_none() {
+            self.first_token_at = Some(now);
+        }
         let elapsed = (now - self.last_update).num_milliseconds() as f64 / 1000.0;
 
         // Only update rate if some time has passed
@@ -266,9 +358,37 @@ impl ProgressStats {
         }
     }
 
+    /// Note that the server accepted the request
+    pub fn connected(&mut self) {
+        self.connected_at.get_or_insert_with(chrono::Utc::now);
+    }
+
+    /// What the generation is doing
+    pub fn stage(&self) -> GenerationStage {
+        if self.tokens_generated > 0 || self.first_token_at.is_some() {
+            GenerationStage::Streaming
+        } else if self.connected_at.is_some() {
+            GenerationStage::WaitingForFirstToken
+        } else {
+            GenerationStage::Connecting
+        }
+    }
+
     /// Mark the task as completed
     pub fn complete(&mut self) {
         self.completion_percent = Some(100.0);
+        self.finished_at.get_or_insert_with(chrono::Utc::now);
+    }
+
+    /// Time from the start until the first token arrived
+    pub fn first_token_latency(&self) -> Option<std::time::Duration> {
+        (self.first_token_at? - self.start_time).to_std().ok()
+    }
+
+    /// Time from the start until the generation completed, or until the last update
+    pub fn elapsed(&self) -> std::time::Duration {
+        let end = self.finished_at.unwrap_or(self.last_update);
+        (end - self.start_time).to_std().unwrap_or_default()
     }
 
     /// Estimate time remaining in seconds
@@ -305,12 +425,57 @@ impl ProgressStats {
     }
 }
 
+/// Receives progress statistics while a response is generated
+pub type ProgressCallback = Arc<dyn Fn(&ProgressStats) + Send + Sync>;
+
+/// Text of a response as it streams in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamChunk {
+    /// Part of the answer
+    Content(String),
+    /// Part of the reasoning, for models that report it separately
+    Reasoning(String),
+}
+
+/// Channel the chunks of a streaming response are sent to
+pub type ChunkSender = tokio::sync::mpsc::UnboundedSender<StreamChunk>;
+
 /// Trait for AI clients
 #[async_trait]
 pub trait AIClient: Send + Sync {
     /// Generate a completion for the given prompt
     async fn generate(&self, prompt: &str, context: Option<&str>) -> Result<AIResponse, AIError>;
 
+    /// Generate a completion, reporting progress while the response streams
+    ///
+    /// Clients that do not stream report nothing until the response is complete.
+    async fn generate_with_progress(
+        &self,
+        prompt: &str,
+        context: Option<&str>,
+        _on_progress: ProgressCallback,
+    ) -> Result<AIResponse, AIError> {
+        self.generate(prompt, context).await
+    }
+
+    /// Generate a completion, sending its text to `chunks` as it streams in
+    ///
+    /// The complete response, with its usage, is returned once the stream
+    /// ends. Clients that do not stream send the whole answer as one chunk.
+    async fn generate_stream(
+        &self,
+        prompt: &str,
+        context: Option<&str>,
+        chunks: ChunkSender,
+        on_progress: ProgressCallback,
+    ) -> Result<AIResponse, AIError> {
+        let response = self
+            .generate_with_progress(prompt, context, on_progress)
+            .await?;
+        let _ = chunks.send(StreamChunk::Content(response.content.clone()));
+        Ok(response)
+    }
+
     /// List available models
     async fn models(&self) -> Result<Vec<String>, AIError>;
 
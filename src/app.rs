@@ -1,11 +1,13 @@
 //! Main application state and event handling
 
-use crate::handlers::{bash, command};
+use crate::handlers::files::{self, FileBlock};
+use crate::handlers::{command, edits};
 use anyhow::Result;
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, Utc};
 use clipboard::{ClipboardContext, ClipboardProvider};
+use serde::{Deserialize, Serialize};
 use crossterm::event::{KeyCode, KeyModifiers};
-use std::collections::VecDeque;
+use std::collections::{BTreeSet, HashMap, VecDeque};
 use std::env;
 use std::io;
 use std::path::PathBuf;
@@ -13,17 +15,124 @@ use std::sync::{mpsc, Arc};
 use std::sync::atomic::AtomicBool;
 use std::time::{Duration, Instant};
 
+use crate::ai::continuation::Incomplete;
+use crate::ai::conversation::{Conversation, MessageId};
+use crate::config::get_config;
 use crate::event::Event;
 use crate::handlers::CommandMode;
+use crate::handlers::attach::Attachment;
+use crate::handlers::doc::DocTarget;
+use crate::handlers::editor::FileLocation;
+use crate::handlers::refactor::RefactorRequest;
+use crate::handlers::rewrite::RewriteRequest;
 use crate::tui::Tui;
-use crate::ui;
-use crate::utils::{Colors, TaskManager};
+use crate::ui::progress::{self, Spinner};
+use crate::ui::status::StatusHits;
+use crate::ui::{self, FrameHistogram, FramePacer};
+use crate::utils::index::Indexer;
+use crate::utils::latency::{ExchangeLatency, Latency};
+use crate::utils::network::Connectivity;
+use crate::utils::speech::Recording;
+use crate::utils::tree::FileTree;
+use crate::utils::tts::Speaker;
+use crate::utils::undo::UndoStore;
+use crate::utils::{Colors, TaskId, TaskManager};
+use crate::ui::hints::HintContext;
 
 mod ai_handler;
+mod ask;
+mod attach;
+mod bandwidth;
+mod bash_queue;
+mod bash_run;
+mod bundle;
+mod catalog;
+mod chain;
+mod commands;
+mod confirm;
+mod connectivity;
+mod context;
+mod cost_preview;
+mod editor;
+mod eval;
+mod fetch;
+mod filter;
+mod image;
+mod import;
+mod incomplete;
+mod interactive;
+mod index;
+mod login;
+mod lsp;
+mod messages;
+mod navigation;
+mod pending;
+mod personas;
+mod prompts;
+mod redraw;
+mod rewrite;
+mod scroll;
+mod search;
+mod sections;
+mod sessions;
+mod share;
+mod speech;
+mod stats;
+mod status;
+mod storage;
+mod streaming;
+mod sync;
+mod title;
+mod toast;
+mod templates;
+mod tracked;
+mod tree;
+mod tts;
+mod update;
+mod usage;
+mod workdir;
 use ai_handler::AIHandler;
+pub use ask::SelectionPrompt;
+pub use scroll::ScrollAnchor;
+pub use search::OutputSearch;
+use attach::SummaryResult;
+use eval::EvalReport;
+use fetch::FetchResult;
+use image::ImageResult;
+use update::UpdateResult;
+use catalog::CatalogResult;
+pub use incomplete::IncompleteBanner;
+pub use interactive::InteractiveShell;
+use interactive::PtyEvent;
+use index::IndexSearchResult;
+pub use lsp::LspQuery;
+use lsp::{LspResult, LspServers};
+pub use bash_queue::{BashQueue, QueuedBlock};
+use bash_queue::{EditedBlock, QueuedOutput};
+use bash_run::BashEvent;
+pub use chain::split_chain;
+pub use confirm::{ConfirmAction, Confirmation};
+use confirm::WaitingWrite;
+use login::LoginEvent;
+pub use messages::{MessageFlags, MessageKey};
+pub use navigation::{anchor_jump, anchor_target, resized_input_height};
+pub use pending::PendingPrompt;
+pub use sessions::SessionBrowser;
+use sessions::TitleResult;
+use streaming::StreamingResponse;
+use sync::SyncResult;
+use title::TerminalStatus;
+pub use toast::Toast;
 
 pub type AppResult<T> = Result<T>;
 
+/// Team commands and the user's aliases, the user's taking precedence
+fn command_aliases() -> std::collections::BTreeMap<String, String> {
+    let mut aliases = crate::utils::templates::team_commands(&templates::templates_dir());
+    aliases.extend(get_config().aliases);
+    aliases
+}
+
 // Session statistics
 pub struct SessionStats {
     pub start_time: DateTime<Local>,
@@ -34,6 +143,8 @@ pub struct SessionStats {
     pub total_tokens: usize,
     pub prompt_tokens: usize,
     pub completion_tokens: usize,
+    pub models_used: Vec<String>, // Models that produced responses, in order of first use
+    pub latencies: Vec<ExchangeLatency>, // Timing of every answered prompt
 }
 
 impl Default for SessionStats {
@@ -47,10 +158,64 @@ impl Default for SessionStats {
             total_tokens: 0,
             prompt_tokens: 0,
             completion_tokens: 0,
+            models_used: Vec::new(),
+            latencies: Vec::new(),
+        }
+    }
+}
+
+/// What an AI generation was started for, deciding how its response is handled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum AIPurpose {
+    /// Regular prompt; code blocks in the response are executed
+    Chat,
+    /// Coordinated multi-file edits requested with /refactor
+    Refactor(RefactorRequest),
+    /// Documentation comments requested with /doc
+    Doc(DocTarget),
+    /// Commit message for the staged changes requested with /commitmsg
+    CommitMessage,
+    /// A response rewritten with /rewrite or a message quick action
+    Rewrite(RewriteRequest),
+}
+
+impl AIPurpose {
+    /// Short label used for the task name
+    pub fn label(&self) -> &'static str {
+        match self {
+            AIPurpose::Chat => "AI",
+            AIPurpose::Refactor(_) => "Refactor",
+            AIPurpose::Doc(_) => "Doc",
+            AIPurpose::CommitMessage => "Commit message",
+            AIPurpose::Rewrite(_) => "Rewrite",
+        }
+    }
+
+    /// Whether code blocks in the response should be executed
+    pub fn executes_code_blocks(&self) -> bool {
+        matches!(self, AIPurpose::Chat)
+    }
+
+    /// Name of the command kind in `sampling.commands`
+    pub fn command(&self) -> &'static str {
+        match self {
+            AIPurpose::Chat => "chat",
+            AIPurpose::Refactor(_) => "refactor",
+            AIPurpose::Doc(_) => "doc",
+            AIPurpose::CommitMessage => "commitmsg",
+            AIPurpose::Rewrite(_) => "rewrite",
         }
     }
 }
 
+/// Entries of the context menu: label and action passed to `handle_context_menu_action`
+pub const CONTEXT_MENU: [(&str, &str); 4] = [
+    ("Copy", "copy"),
+    ("Send to AI", "ask"),
+    ("Select All", "select_all"),
+    ("Paste", "paste"),
+];
+
 // Command history
 pub struct History {
     pub commands: VecDeque<String>,
@@ -89,12 +254,17 @@ pub struct App {
     pub cursor_visible: bool,        // Toggle for cursor blinking
     pub last_cursor_toggle: Instant, // Time of last cursor blink
     pub output: String,
+    pub output_generation: u64, // Bumped whenever the output text changes
     pub history: History,
     pub current_dir: PathBuf,
+    previous_dir: Option<PathBuf>, // Directory before the last /cd, for /cd -
     pub colors: Colors,
     pub stats: SessionStats,
     pub current_mode: CommandMode,
+    pub default_mode: CommandMode, // Where plain input goes: AI, or bash after a click on the mode
     pub scroll_offset: u16,
+    scroll_anchor: Option<ScrollAnchor>, // Section and row being read, kept on screen as the output changes
+    anchored_offset: u16, // scroll_offset when the anchor was taken, to tell scrolling apart
     pub is_selecting_text: bool,
     pub selection_start: usize,
     pub selection_end: usize,
@@ -102,27 +272,150 @@ pub struct App {
     pub show_context_menu: bool,
     pub context_menu_x: u16,
     pub context_menu_y: u16,
+    pub context_menu_selected: usize, // Highlighted entry of CONTEXT_MENU
+    pub selection_prompt: Option<SelectionPrompt>, // Mini prompt asking what to do with the selection
+    pub rewrite_target: Option<usize>, // Message the next /rewrite acts on, chosen with `t`
     pub mouse_drag_start_x: u16,
     pub mouse_drag_start_y: u16,
     pub mouse_drag_ongoing: bool,
     pub output_area_height: u16,    // To track output area dimensions
+    pub status_hits: StatusHits,    // Clickable status bar segments of the last frame
+    terminal_status: TerminalStatus, // Title and taskbar progress the terminal shows
     pub last_click_time: Instant,   // For double click detection
     pub last_click_pos: (u16, u16), // For double click detection
     pub native_selection_mode: bool,
     pub is_scrolling: bool, // Track when scrolling is in progress
     pub ai_handler: AIHandler,
     pub spinner_rx: Option<mpsc::Receiver<(String, usize)>>, // Receiver for spinner updates
+    pub spinner: Spinner, // Spinner style of the running prompt
+    spinner_task: Option<TaskId>, // Task whose progress the spinner line shows
     pub abort_requested: Arc<AtomicBool>, // Atomic flag to indicate if abort was requested
     pub global_abort: Option<Arc<AtomicBool>>, // Global atomic abort flag
     pub ui_notifier: Option<tokio::sync::mpsc::Sender<()>>, // Channel to request UI updates
     pub background_tasks: Vec<tokio::task::JoinHandle<()>>, // Track background tasks
     pub task_manager: TaskManager, // Manager for background tasks
     pub show_tasks_popup: bool, // Whether to show the tasks popup
+    pub show_debug_popup: bool, // Whether to show the debug panel
+    pub task_label_filter: Option<String>, // Only tasks with this label are listed in the tasks popup
     pub last_cleanup_time: Option<Instant>, // Last time task cleanup was performed
+    pub pending_confirmation: Option<Confirmation>, // Action awaiting user confirmation
+    pub file_blocks: Vec<FileBlock>, // Path-annotated code blocks from the last AI response
+    waiting_writes: VecDeque<WaitingWrite>, // Accepted file changes waiting for a task slot
+    pub in_flight: HashMap<TaskId, PendingPrompt>, // Prompt and purpose of each running AI task
+    streams: HashMap<TaskId, StreamingResponse>, // Chat responses shown while they are generated
+    pub undo: UndoStore, // Snapshots of files changed in this session
+    pub connectivity: Connectivity, // Shared online/offline state
+    pub was_online: bool, // Connectivity at the last check, to detect changes
+    pub offline_queue: VecDeque<PendingPrompt>, // Prompts waiting for the network
+    pub print_on_exit: Option<String>, // Plain text written to stdout after the TUI closes
+    pub collapsed_sections: HashMap<usize, bool>, // Sections the user expanded or collapsed
+    pub selected_section: Option<usize>, // Section selected with Alt+Up/Down
+    pub jump_back: Option<u16>, // Scroll position before the last jump, for ''
+    pub message_ids: Vec<MessageId>, // Stable id of each output section, in order
+    pub next_message_id: MessageId, // Id given to the next section
+    pub message_flags: HashMap<MessageId, MessageFlags>, // Pinned and excluded messages
+    pub marked_sections: BTreeSet<usize>, // Messages marked with Space for bulk actions
+    pub output_search: Option<OutputSearch>, // Term of /search and the match shown
+    pub output_filter: Vec<CommandMode>, // Kinds of section shown; empty shows all
+    pub section_times: Vec<DateTime<Local>>, // When each output section was entered
+    pub command_suggestion: Option<String>, // Correction of a mistyped command, accepted with Tab
+    pub chain: VecDeque<String>, // Commands of a chained input still to run
+    pub frame_pacer: FramePacer, // Redraw rate and low-bandwidth detection
+    pub needs_redraw: bool, // Something visible changed since the last frame
+    pub frame_times: FrameHistogram, // Render times shown in the debug panel
+    pub attachments: Vec<Attachment>, // Files and command output sent with every prompt
+    summarizing: usize, // Oversized attachments still being summarized
+    summary_tx: tokio::sync::mpsc::UnboundedSender<SummaryResult>, // Finished summaries from background tasks
+    summary_rx: tokio::sync::mpsc::UnboundedReceiver<SummaryResult>,
+    fetching: usize, // Pages still being fetched
+    fetch_tx: tokio::sync::mpsc::UnboundedSender<FetchResult>, // Fetched pages from background tasks
+    fetch_rx: tokio::sync::mpsc::UnboundedReceiver<FetchResult>,
+    image_tx: tokio::sync::mpsc::UnboundedSender<ImageResult>, // Images generated with /image
+    image_rx: tokio::sync::mpsc::UnboundedReceiver<ImageResult>,
+    update_tx: tokio::sync::mpsc::UnboundedSender<UpdateResult>, // Release checks and installs of /update
+    update_rx: tokio::sync::mpsc::UnboundedReceiver<UpdateResult>,
+    catalog_tx: tokio::sync::mpsc::UnboundedSender<CatalogResult>, // OpenRouter catalogs of /list models
+    catalog_rx: tokio::sync::mpsc::UnboundedReceiver<CatalogResult>,
+    update_task: Option<(TaskId, tokio::task::AbortHandle)>, // The running /update task, aborted with Esc
+    pub indexer: Indexer, // Project index kept up to date in the background
+    indexer_task: Option<tokio::task::JoinHandle<()>>, // The indexing loop while index.enabled is on
+    index_tx: tokio::sync::mpsc::UnboundedSender<IndexSearchResult>, // Results of /index search
+    index_rx: tokio::sync::mpsc::UnboundedReceiver<IndexSearchResult>,
+    lsp_servers: LspServers, // Language servers started for /definition, /references and /hover
+    lsp_tx: tokio::sync::mpsc::UnboundedSender<LspResult>, // Answers of language servers
+    lsp_rx: tokio::sync::mpsc::UnboundedReceiver<LspResult>,
+    open_locations: Vec<FileLocation>, // Locations last listed by /open, numbered from 1
+    login_tx: tokio::sync::mpsc::UnboundedSender<LoginEvent>, // Progress of /login device flows
+    login_rx: tokio::sync::mpsc::UnboundedReceiver<LoginEvent>,
+    recording: Option<Recording>, // Push-to-talk recording in progress
+    transcript_tx: tokio::sync::mpsc::UnboundedSender<Result<String, crate::ai::AIError>>, // Transcripts of finished recordings
+    transcript_rx: tokio::sync::mpsc::UnboundedReceiver<Result<String, crate::ai::AIError>>,
+    speaker: Speaker, // Reads responses aloud with /speak on
+    pub show_reasoning: bool, // Reasoning blocks shown instead of folded
+    pub diff_side_by_side: bool, // Diffs in the output shown in two columns
+    pub active_persona: Option<String>, // Persona chosen with /as that answers chat prompts
+    pub usage_tag: Option<String>, // Tag set with /tag that usage is recorded under
+    pub conversation: Conversation, // Earlier chat messages sent with every chat prompt
+    speech_error_tx: tokio::sync::mpsc::UnboundedSender<crate::ai::AIError>, // Responses that could not be read aloud
+    speech_error_rx: tokio::sync::mpsc::UnboundedReceiver<crate::ai::AIError>,
+    prompt_listing: Vec<String>, // Prompts numbered by the last /prompts listing
+    pub toast: Option<Toast>, // Notice shown in the corner for a few seconds
+    pub session_id: String, // Name of the stored session, the time it started
+    pub session_title: Option<String>, // Title of the conversation, set by the first prompt
+    session_created: DateTime<Local>, // When the stored session started
+    pub session_browser: Option<SessionBrowser>, // The /sessions popup
+    pub file_tree: Option<FileTree>, // The /tree popup
+    title_tx: tokio::sync::mpsc::UnboundedSender<TitleResult>, // Generated session titles
+    title_rx: tokio::sync::mpsc::UnboundedReceiver<TitleResult>,
+    syncing: bool, // A /sync push or pull is running
+    sync_tx: tokio::sync::mpsc::UnboundedSender<SyncResult>, // Finished pushes and pulls
+    sync_rx: tokio::sync::mpsc::UnboundedReceiver<SyncResult>,
+    templates_updating: bool, // A /template update is running
+    pub share: Option<crate::utils::share::ShareServer>, // Read-only viewer of this session, started with /share
+    share_published: Instant, // Last time the viewers of /share were updated
+    share_generation: u64, // Output generation the viewers of /share last got
+    template_tx: tokio::sync::mpsc::UnboundedSender<Result<String, String>>, // Finished team template updates
+    template_rx: tokio::sync::mpsc::UnboundedReceiver<Result<String, String>>,
+    eval_running: bool, // An /eval is running
+    eval_tx: tokio::sync::mpsc::UnboundedSender<EvalReport>, // Finished evaluations
+    eval_rx: tokio::sync::mpsc::UnboundedReceiver<EvalReport>,
+    pub bash_queue: Option<BashQueue>, // Bash blocks of a response to choose from
+    bash_queue_tx: tokio::sync::mpsc::UnboundedSender<QueuedOutput>, // Output of chosen bash blocks
+    bash_queue_rx: tokio::sync::mpsc::UnboundedReceiver<QueuedOutput>,
+    editing_block: Option<EditedBlock>, // Bash block loaded into the input with `e`
+    bash_runs: HashMap<TaskId, Arc<AtomicBool>>, // Running `!` commands and the flags that kill them
+    bash_tx: tokio::sync::mpsc::UnboundedSender<BashEvent>, // Output of running `!` commands
+    bash_rx: tokio::sync::mpsc::UnboundedReceiver<BashEvent>,
+    pub interactive: Option<InteractiveShell>, // `/pty` command in the pane, taking the keyboard
+    pty_tx: tokio::sync::mpsc::UnboundedSender<PtyEvent>, // Output of the `/pty` command, read on a thread
+    pty_rx: tokio::sync::mpsc::UnboundedReceiver<PtyEvent>,
+    pub incomplete_banner: Option<IncompleteBanner>, // Offer to continue or retry an empty or cut-off response
+    cut_off: Option<IncompleteBanner>, // Dismissed cut-off answer that /continue still picks up
+    drawn_second: i64, // Session clock second shown in the last frame
 }
 
 impl Default for App {
     fn default() -> Self {
+        let (summary_tx, summary_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (fetch_tx, fetch_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (pty_tx, pty_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (image_tx, image_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (update_tx, update_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (catalog_tx, catalog_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (index_tx, index_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (lsp_tx, lsp_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (login_tx, login_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (transcript_tx, transcript_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (speech_error_tx, speech_error_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (title_tx, title_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (sync_tx, sync_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (template_tx, template_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (eval_tx, eval_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (bash_queue_tx, bash_queue_rx) = tokio::sync::mpsc::unbounded_channel();
+        let (bash_tx, bash_rx) = tokio::sync::mpsc::unbounded_channel();
+        let session_id = Local::now().format("%Y%m%d-%H%M%S").to_string();
+        let task_manager = TaskManager::new();
+        task_manager.set_max_concurrent(get_config().tasks.max_concurrent);
         Self {
             running: true,
             input: String::new(),
@@ -130,12 +423,17 @@ impl Default for App {
             cursor_visible: true, // Start with visible cursor
             last_cursor_toggle: Instant::now(), // Initialize cursor blink timer
             output: String::new(),
+            output_generation: 0, // Nothing written yet
             history: History::default(),
             current_dir: env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+            previous_dir: None,
             colors: Colors::default(),
             stats: SessionStats::default(),
             current_mode: CommandMode::AI,
+            default_mode: CommandMode::AI,
             scroll_offset: 0,
+            scroll_anchor: None,
+            anchored_offset: 0,
             is_selecting_text: false,
             selection_start: 0,
             selection_end: 0,
@@ -143,23 +441,126 @@ impl Default for App {
             show_context_menu: false,
             context_menu_x: 0,
             context_menu_y: 0,
+            context_menu_selected: 0,
+            selection_prompt: None, // No mini prompt open
+            rewrite_target: None,
             mouse_drag_start_x: 0,
             mouse_drag_start_y: 0,
             mouse_drag_ongoing: false,
             output_area_height: 0,
+            status_hits: StatusHits::default(),
+            terminal_status: TerminalStatus::default(),
             last_click_time: Instant::now(),
             last_click_pos: (0, 0),
             native_selection_mode: true,
             is_scrolling: false, // Initialize scrolling state
             ai_handler: AIHandler::new(),
             spinner_rx: None, // Initialize spinner receiver as None
+            spinner: Spinner::new(&get_config().theme, false), // Configured spinner
+            spinner_task: None, // No prompt running yet
             abort_requested: Arc::new(AtomicBool::new(false)), // Initialize abort flag as false
             global_abort: None, // Initialize global abort flag as None,
             ui_notifier: None, // Will be set after construction
             background_tasks: Vec::new(), // Start with no background tasks
-            task_manager: TaskManager::new(), // Initialize task manager
+            task_manager, // Limited to tasks.max_concurrent
             show_tasks_popup: false, // Don't show tasks popup by default
+            show_debug_popup: false, // Debug panel starts hidden
+            task_label_filter: None, // List tasks with any label
             last_cleanup_time: None, // Initialize cleanup timer to None
+            pending_confirmation: None, // No confirmation pending
+            file_blocks: Vec::new(), // No file blocks offered yet
+            waiting_writes: VecDeque::new(), // No file changes waiting
+            in_flight: HashMap::new(), // No AI tasks started yet
+            streams: HashMap::new(),
+            undo: UndoStore::open(&crate::config::get_config_dir().join("undo"), &session_id), // One undo journal per session
+            connectivity: Connectivity::new(), // Assume online until probed
+            was_online: true,
+            offline_queue: VecDeque::new(), // Nothing queued
+            print_on_exit: None, // Nothing to print
+            collapsed_sections: HashMap::new(), // Default collapse state for all sections
+            selected_section: None, // No section selected
+            jump_back: None, // No jump made yet
+            message_ids: Vec::new(), // No commands yet
+            next_message_id: 0, // Ids start at zero
+            message_flags: HashMap::new(), // No message pinned or excluded
+            marked_sections: BTreeSet::new(), // No message marked
+            output_search: None, // Not searching
+            output_filter: Vec::new(), // Show all output
+            section_times: Vec::new(), // No commands yet
+            command_suggestion: None, // No mistyped command
+            chain: VecDeque::new(), // No chained commands
+            frame_pacer: FramePacer::new(get_config().ui.low_bandwidth), // Configured render mode
+            needs_redraw: true, // Draw the first frame right away
+            frame_times: FrameHistogram::new(), // No frames drawn yet
+            attachments: Vec::new(), // Nothing attached yet
+            summarizing: 0,
+            summary_tx,
+            summary_rx,
+            fetching: 0,
+            fetch_tx,
+            fetch_rx,
+            image_tx,
+            update_tx,
+            update_rx,
+            update_task: None, // No update running
+            catalog_tx,
+            catalog_rx,
+            image_rx,
+            indexer: Indexer::default(),
+            indexer_task: None,
+            index_tx,
+            index_rx,
+            lsp_servers: LspServers::default(),
+            lsp_tx,
+            lsp_rx,
+            open_locations: Vec::new(),
+            login_tx,
+            login_rx,
+            recording: None,
+            transcript_tx,
+            transcript_rx,
+            speaker: Speaker::default(),
+            show_reasoning: false, // Reasoning starts folded
+            diff_side_by_side: false,
+            active_persona: None,
+            usage_tag: None,
+            conversation: Conversation::new(),
+            speech_error_tx,
+            speech_error_rx,
+            prompt_listing: Vec::new(), // Nothing listed yet
+            toast: None,
+            session_id,
+            session_title: None, // Titled by the first prompt
+            session_created: Local::now(),
+            session_browser: None,
+            file_tree: None,
+            title_tx,
+            title_rx,
+            syncing: false,
+            sync_tx,
+            sync_rx,
+            templates_updating: false,
+            share: None,
+            share_published: Instant::now(),
+            share_generation: 0,
+            template_tx,
+            template_rx,
+            eval_running: false,
+            eval_tx,
+            eval_rx,
+            bash_queue: None,
+            bash_queue_tx,
+            bash_queue_rx,
+            editing_block: None,
+            bash_runs: HashMap::new(),
+            interactive: None,
+            pty_tx,
+            pty_rx,
+            incomplete_banner: None,
+            cut_off: None,
+            bash_tx,
+            bash_rx,
+            drawn_second: 0,
         }
     }
 }
@@ -169,10 +570,34 @@ impl App {
         Self::default()
     }
     
+    /// Apply `tasks.max_concurrent` after the configuration was replaced
+    fn apply_task_limit(&self) {
+        self.task_manager.set_max_concurrent(get_config().tasks.max_concurrent);
+    }
+
     pub fn set_global_abort(&mut self, abort_flag: Arc<AtomicBool>) {
         self.global_abort = Some(abort_flag);
     }
     
+    /// Whether an AI response is being generated
+    pub fn is_generating(&self) -> bool {
+        !self.in_flight.is_empty()
+    }
+
+    /// What the hint in the empty input depends on
+    pub fn hint_context(&self) -> HintContext {
+        HintContext {
+            generating: self.is_generating(),
+            running_bash: !self.bash_runs.is_empty(),
+            selecting_text: self.is_selecting_text,
+            message_selected: self.selected_section.is_some(),
+            messages_marked: !self.marked_sections.is_empty(),
+            searching: self.output_search.is_some(),
+            files_to_save: !self.file_blocks.is_empty(),
+            bash_default: self.default_mode == CommandMode::Bash,
+        }
+    }
+
     pub fn is_abort_requested(&self) -> bool {
         self.abort_requested.load(std::sync::atomic::Ordering::SeqCst) || 
         self.global_abort.as_ref().is_some_and(|flag| flag.load(std::sync::atomic::Ordering::SeqCst))
@@ -189,6 +614,8 @@ impl App {
         
         // Add the text to the output string
         self.output.push_str(&text);
+        self.output_generation += 1;
+        self.needs_redraw = true;
 
         // Update output_lines for text selection and copying
         // Split by newline to get individual lines
@@ -203,17 +630,175 @@ impl App {
         }
     }
 
+    /// Handle the response of a completed AI task according to its purpose
+    pub fn handle_ai_response(&mut self, task_id: TaskId, content: String) {
+        // Stop the spinner and clear its frames from the output
+        self.spinner_rx = None;
+        self.spinner_task = None;
+        for line in self.output_lines.iter_mut() {
+            if progress::is_indicator(line) {
+                line.clear();
+            }
+        }
+        self.output = progress::clear_indicators(&self.output);
+
+        // Replace any double newlines that might have been created
+        self.output = self.output.replace("\n\n\n", "\n\n");
+        self.output_generation += 1;
+        self.close_stream(task_id);
+
+        let usage = self.task_manager.get_task(task_id).and_then(|task| task.usage);
+        if let Some(usage) = &usage {
+            self.record_usage(usage);
+        }
+
+        let incomplete = self.task_manager.get_task(task_id).and_then(|task| task.incomplete);
+        let mut pending = self.in_flight.remove(&task_id);
+        if let (Some(pending), Some(usage)) = (pending.as_mut(), &usage) {
+            pending.answer_usage.add(&usage.tokens, usage.cost);
+        }
+        let untrusted = pending.as_ref().is_some_and(|pending| pending.untrusted);
+        if let Some(message) = pending.as_ref().and_then(|pending| pending.message.as_deref())
+            && incomplete != Some(Incomplete::Empty)
+        {
+            let source = pending.as_ref().and_then(|pending| pending.source);
+            self.remember_exchange(source, message, &content);
+        }
+        let purpose = pending.as_ref().map(|pending| pending.purpose.clone());
+        self.persist_pending();
+
+        let continues = pending.as_ref().and_then(|pending| pending.continues.clone());
+        match purpose.unwrap_or(AIPurpose::Chat) {
+            // A continuation goes on where the cut-off answer stopped
+            AIPurpose::Chat => match (continues, &pending) {
+                (Some(partial), Some(pending)) => {
+                    let usage = pending.answer_usage.clone();
+                    self.finish_continuation(&partial, content, untrusted, incomplete.is_none().then_some(usage));
+                }
+                _ => {
+                    self.cut_off = None;
+                    self.show_chat_response(content, untrusted);
+                }
+            },
+            AIPurpose::Refactor(request) => self.finish_refactor(request, content),
+            AIPurpose::Doc(target) => self.finish_doc(target, content),
+            AIPurpose::CommitMessage => self.finish_commit_message(content),
+            AIPurpose::Rewrite(request) => self.finish_rewrite(request, content),
+        }
+        if let (Some(incomplete), Some(pending)) = (incomplete, pending) {
+            self.offer_incomplete_retry(incomplete, pending);
+        }
+        self.save_session();
+    }
+
+    /// Add the usage of a finished generation to the session statistics
+    fn record_usage(&mut self, usage: &crate::utils::TaskUsage) {
+        self.stats.prompt_tokens += usage.tokens.prompt_tokens;
+        self.stats.completion_tokens += usage.tokens.completion_tokens;
+        self.stats.total_tokens += usage.tokens.total_tokens;
+        let level = get_config().usage.cost_level(self.stats.cost);
+        self.stats.cost += usage.cost;
+        self.record_in_ledger(usage);
+        self.alert_cost_level(level);
+        if !self.stats.models_used.contains(&usage.model) {
+            self.stats.models_used.push(usage.model.clone());
+        }
+        if let Some(latency) = usage.latency {
+            self.stats.latencies.push(ExchangeLatency {
+                provider: usage.provider,
+                model: usage.model.clone(),
+                latency,
+            });
+        }
+    }
+
+    /// Display a chat response and offer to save any file blocks it contains
+    ///
+    /// Unified diffs and search/replace blocks are applied to the files in
+    /// memory and offered like whole files. Code blocks of a prompt with suspicious untrusted context were not run
+    /// and are offered in the bash block popup instead.
+    fn show_chat_response(&mut self, content: String, untrusted: bool) {
+        self.add_output(content.clone());
+        let answer = crate::utils::transcript::strip_reasoning(&content);
+        self.speak_response(&answer);
+        self.fetch_requested_pages(&answer, untrusted);
+        self.offer_response_blocks(&answer, untrusted);
+    }
+
+    /// Queue the bash blocks of an answer, ask before running its interpreter
+    /// blocks and offer to save its file blocks
+    fn offer_response_blocks(&mut self, answer: &str, untrusted: bool) {
+        self.queue_bash_blocks(answer, untrusted);
+        self.offer_code_blocks(answer);
+        self.file_blocks = files::extract_file_blocks(answer);
+        let (edited, problems) = edits::resolve(&self.current_dir, &edits::extract_edits(answer));
+        for block in edited {
+            // A whole file in the same response wins over edits of it
+            if !self.file_blocks.iter().any(|b| b.path == block.path) {
+                self.file_blocks.push(block);
+            }
+        }
+        if !problems.is_empty() {
+            self.add_output(format!(
+                "⚠️ Edits that do not apply: {}",
+                problems.join("; ")
+            ));
+        }
+        if !self.file_blocks.is_empty() {
+            let paths: Vec<String> = self.file_blocks.iter().map(|b| b.display_path()).collect();
+            self.add_output(format!(
+                "💾 {} — press Ctrl+S to preview and save",
+                paths.join(", ")
+            ));
+        }
+    }
+
+    /// Show a diff preview for the file blocks of the last AI response
+    pub fn review_file_blocks(&mut self) {
+        if self.file_blocks.is_empty() {
+            self.add_output("⚠️ No file blocks to save".to_string());
+            return;
+        }
+
+        let preview: String = self
+            .file_blocks
+            .iter()
+            .map(|block| block.preview(&self.current_dir))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let title = match self.file_blocks.len() {
+            1 => format!("Save {}", self.file_blocks[0].display_path()),
+            n => format!("Save {} files", n),
+        };
+        self.request_confirmation(Confirmation::new(
+            title.clone(),
+            preview,
+            ConfirmAction::WriteFiles {
+                label: title,
+                blocks: self.file_blocks.clone(),
+            },
+        ));
+    }
+
     pub fn format_timestamp(&self) -> String {
         Local::now().format("%H:%M").to_string()
     }
 
     pub fn detect_mode(&self, command: &str) -> (CommandMode, String) {
-        let command = command.trim();
+        // Aliases expand before the mode is chosen, so `/gs` can run `!git status`
+        let expanded = command::CommandHandler::expand_alias(command, &command_aliases());
+        let command = expanded.as_deref().unwrap_or(command).trim();
 
         if let Some(stripped) = command.strip_prefix('!') {
             (CommandMode::Bash, stripped.trim().to_string())
         } else if let Some(stripped) = command.strip_prefix('/') {
             (CommandMode::Command, stripped.trim().to_string())
+        } else if self.default_mode == CommandMode::Bash {
+            // With bash as the default, `?` marks a prompt for the AI
+            match command.strip_prefix('?') {
+                Some(prompt) => (CommandMode::AI, prompt.trim().to_string()),
+                None => (CommandMode::Bash, command.to_string()),
+            }
         } else {
             (CommandMode::AI, command.to_string())
         }
@@ -227,18 +812,27 @@ impl App {
         
         // Add command to history
         self.history.add(command.clone());
+        self.command_suggestion = None;
 
         // Detect mode and get processed command
         let (mode, cmd) = self.detect_mode(&command);
 
+        // A bash block loaded for editing is dropped unless it is what runs
+        if !matches!(mode, CommandMode::Bash) {
+            self.finish_edited_block(None);
+        }
+
         // Add a separator between commands (more compact)
+        self.section_times.push(Local::now());
+        self.add_message_id();
         self.add_output("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n".to_string());
 
-        // Always format and display the command first, before any processing happens
+        // Always format and display the command first, before any processing happens.
+        // The echo shows the command after alias expansion.
         match mode {
-            CommandMode::Bash => self.add_output(format!("$ {}", command)),
-            CommandMode::Command => self.add_output(format!("/ {}", command)),
-            CommandMode::AI => self.add_output(format!("❯ {}", command)),
+            CommandMode::Bash => self.add_output(format!("$ !{}", cmd)),
+            CommandMode::Command => self.add_output(format!("/ /{}", cmd)),
+            CommandMode::AI => self.add_output(format!("❯ {}", cmd)),
         };
 
         // Force immediate UI refresh to show the command right away
@@ -253,11 +847,18 @@ impl App {
                 // Add a newline for better readability
                 self.add_output("\n".to_string());
 
-                // Now execute the command
-                let result =
-                    bash::handle_bash_command(&cmd).unwrap_or_else(|e| format!("Error: {}", e));
-                self.add_output(result);
+                // A shell would forget the new directory as soon as it exits
+                if let Some(args) = crate::handlers::workdir::cd_args(&cmd) {
+                    self.handle_cd(args);
+                    self.stats.bash_count += 1;
+                    self.finish_edited_block(Some(&cmd));
+                    return;
+                }
+
+                // Run the command in the background, showing its output as it is printed
+                self.start_bash(cmd.clone());
                 self.stats.bash_count += 1;
+                self.finish_edited_block(Some(&cmd));
             }
             CommandMode::Command => {
                 // Add a newline for better readability
@@ -266,7 +867,197 @@ impl App {
                 // Handle special cases
                 if &cmd == "clear" {
                     self.output = "🚀 Output cleared\n".to_string();
+                    self.output_generation += 1;
                     self.output_lines.clear();
+                    self.streams.clear();
+                    self.reset_sections();
+                    return;
+                } else if cmd == "refactor" || cmd.starts_with("refactor ") {
+                    self.start_refactor(cmd["refactor".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "doc" || cmd.starts_with("doc ") {
+                    self.start_doc(cmd["doc".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if &cmd == "commitmsg" {
+                    self.start_commit_message();
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "export" || cmd.starts_with("export ") {
+                    self.handle_export(cmd["export".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "search" || cmd.starts_with("search ") {
+                    self.handle_search(cmd["search".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "filter" || cmd.starts_with("filter ") {
+                    self.handle_filter(cmd["filter".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "linenumbers" || cmd.starts_with("linenumbers ") {
+                    self.handle_line_numbers(cmd["linenumbers".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "speak" || cmd.starts_with("speak ") {
+                    self.handle_speak(cmd["speak".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "prompts" || cmd.starts_with("prompts ") {
+                    self.handle_prompts(cmd["prompts".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "session" || cmd.starts_with("session ") {
+                    self.handle_session(cmd["session".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "diff-sessions" || cmd.starts_with("diff-sessions ") {
+                    self.handle_diff_sessions(cmd["diff-sessions".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "sessions" || cmd.starts_with("sessions ") {
+                    self.handle_sessions(cmd["sessions".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "encrypt" {
+                    self.handle_encrypt();
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "decrypt" || cmd.starts_with("decrypt ") {
+                    self.handle_decrypt(cmd["decrypt".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "sync" || cmd.starts_with("sync ") {
+                    self.handle_sync(cmd["sync".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "print" || cmd.starts_with("print ") {
+                    self.handle_print(cmd["print".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if (cmd == "list models" || cmd.starts_with("list models "))
+                    && get_config().ai.active_provider == crate::ai::Provider::OpenRouter
+                {
+                    self.list_openrouter_models(cmd["list models".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "attach" || cmd.starts_with("attach ") {
+                    self.handle_attach(cmd["attach".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "cd" || cmd.starts_with("cd ") {
+                    self.handle_cd(cmd["cd".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "continue" {
+                    self.handle_continue();
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "pty" || cmd.starts_with("pty ") {
+                    self.start_interactive(cmd["pty".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "fetch" || cmd.starts_with("fetch ") {
+                    self.handle_fetch(cmd["fetch".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "index" || cmd.starts_with("index ") {
+                    self.handle_index(cmd["index".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "image" || cmd.starts_with("image ") {
+                    self.handle_image(cmd["image".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if let Some((query, symbol)) = [
+                    ("definition", LspQuery::Definition),
+                    ("references", LspQuery::References),
+                    ("hover", LspQuery::Hover),
+                ]
+                .into_iter()
+                .find_map(|(name, query)| {
+                    let rest = cmd.strip_prefix(name)?;
+                    (rest.is_empty() || rest.starts_with(' ')).then(|| (query, rest.trim().to_string()))
+                }) {
+                    self.handle_lsp(query, &symbol);
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "open" || cmd.starts_with("open ") {
+                    self.handle_open(cmd["open".len()..].trim(), tui);
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "context" || cmd.starts_with("context ") {
+                    self.handle_context(cmd["context".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "as" || cmd.starts_with("as ") {
+                    self.handle_as(cmd["as".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "login" || cmd.starts_with("login ") {
+                    self.handle_login(cmd["login".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "logout" || cmd.starts_with("logout ") {
+                    self.handle_logout(cmd["logout".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "rewrite" || cmd.starts_with("rewrite ") {
+                    self.handle_rewrite(cmd["rewrite".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "debug" || cmd.starts_with("debug ") {
+                    self.handle_debug(cmd["debug".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "lowbandwidth" || cmd.starts_with("lowbandwidth ") {
+                    self.handle_low_bandwidth(cmd["lowbandwidth".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "undo" || cmd.starts_with("undo ") {
+                    self.handle_undo(cmd["undo".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "update" || cmd.starts_with("update ") {
+                    self.handle_update(cmd["update".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "tree" || cmd.starts_with("tree ") {
+                    self.open_file_tree(cmd["tree".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "stats" {
+                    self.handle_stats();
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd.starts_with("config export") || cmd.starts_with("config import") {
+                    self.handle_config_bundle(cmd["config".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "share" || cmd.starts_with("share ") {
+                    self.handle_share(cmd["share".len()..].trim()).await;
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "eval" || cmd.starts_with("eval ") {
+                    self.handle_eval(cmd["eval".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "template" || cmd.starts_with("template ") {
+                    self.handle_template(cmd["template".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "import" || cmd.starts_with("import ") {
+                    self.handle_import(cmd["import".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "tag" || cmd.starts_with("tag ") {
+                    self.handle_tag(cmd["tag".len()..].trim());
+                    self.stats.command_count += 1;
+                    return;
+                } else if cmd == "usage" || cmd.starts_with("usage ") {
+                    self.handle_usage(cmd["usage".len()..].trim());
+                    self.stats.command_count += 1;
                     return;
                 } else if &cmd == "cost" {
                     // Use our app's internal stats for cost reporting
@@ -292,198 +1083,274 @@ impl App {
                             } else {
                                 self.add_output("✅ AI client updated successfully\n".to_string());
                             }
+                        } else if cmd.starts_with("config seed") || cmd.starts_with("config deterministic") {
+                            self.add_output(result);
+                            // Sampling settings are fixed when the client is created
+                            let _ = self.ai_handler.update_client();
                         } else {
                             self.add_output(result);
                         }
+                        // `/config reset` may have changed the task limit
+                        self.apply_task_limit();
                     }
                     Err(e) => {
                         self.add_output(format!("Error: {}", e));
+                        // Offer the closest known command for a typo
+                        if let Some(corrected) = command::CommandHandler::correct(&cmd) {
+                            self.add_output(format!("💡 Press Tab to use /{}", corrected));
+                            self.command_suggestion = Some(format!("/{}", corrected));
+                        }
                     }
                 }
                 self.stats.command_count += 1;
             }
             CommandMode::AI => {
-                // Add a minimal spinner indicator with no extra space
-                self.add_output("".to_string());
+                self.remember_prompt(&cmd);
+                self.send_prompt(cmd, AIPurpose::Chat);
+            }
+        }
 
-                // Immediately refresh UI
-                if let Err(e) = tui.immediate_refresh(|f| {
-                    ui::render(f, self);
-                }) {
-                    eprintln!("Failed to refresh UI: {}", e);
+        // Update current mode
+        self.current_mode = mode;
+    }
+
+    /// Start an AI generation in the background with a spinner and a tracked task
+    ///
+    /// The response is delivered through the task manager and handled by
+    /// [`App::handle_ai_response`] according to `purpose`.
+    pub fn start_ai_generation(&mut self, prompt: String, purpose: AIPurpose) {
+        self.start_prompt(PendingPrompt::new(prompt, purpose));
+    }
+
+    /// Start the generation of a prompt, on the model it was routed to if any
+    pub fn start_prompt(&mut self, pending: PendingPrompt) {
+        // Titling sends the message too, so only once it passed the guardrails
+        if let (AIPurpose::Chat, Some(message)) = (&pending.purpose, &pending.message) {
+            let message = message.clone();
+            self.title_session(&message);
+        }
+        let mut handler = self.ai_handler.clone();
+        let persona = pending
+            .persona
+            .as_ref()
+            .and_then(|name| Some((name, get_config().personas.get(name)?.clone())));
+        let sampling = crate::ai::sampling::for_command(&pending.sampling_keys(), &get_config().sampling);
+        let sampling = match sampling {
+            Ok(sampling) => sampling,
+            Err(e) => {
+                self.add_output(format!("⚠️ {} — using the model's temperature", e));
+                None
+            }
+        };
+        if persona.is_some() || pending.route.is_some() || sampling.is_some() || pending.max_tokens.is_some() {
+            let chosen = persona.as_ref().map(|(name, persona)| (name.as_str(), persona));
+            let preset = sampling.as_ref().map(|(name, preset)| (name.as_str(), preset));
+            match AIHandler::for_prompt(pending.route.as_ref(), chosen, preset, pending.max_tokens) {
+                Ok(prompt_handler) => handler = prompt_handler,
+                Err(e) => {
+                    let what = match (&persona, &pending.route, &sampling) {
+                        (Some((name, _)), _, _) => format!("answer as {}", name),
+                        (None, Some(route), _) => format!("route to {}", route),
+                        (None, None, Some(_)) => "apply the sampling preset".to_string(),
+                        (None, None, None) => "raise max_tokens".to_string(),
+                    };
+                    self.add_output(format!("⚠️ Cannot {}: {} — using the active model", what, e));
                 }
+            }
+        }
 
-                // Create a new channel for spinner animation
-                let (tx, rx) = mpsc::channel();
-                self.spinner_rx = Some(rx);
-                
-                // Determine the line index for the spinner (the last line in output_lines)
-                let spinner_line_index = self.output_lines.len() - 1;
-                
-                // Save a reference to our global abort flag for the spinner task
-                let global_abort_clone = self.global_abort.clone();
-                
-                // Spawn spinner task with proper line index and abort checking
-                let spinner_task = tokio::spawn(async move {
-                    let spinner_frames = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
-                    let mut frame = 0;
-                    
-                    loop {
-                        // Check if we should abort
-                        let should_abort = global_abort_clone
-                            .as_ref()
-                            .is_some_and(|flag| flag.load(std::sync::atomic::Ordering::SeqCst));
-                            
-                        if should_abort {
-                            break;
-                        }
-                        
-                        // Send both the spinner frame and its line index
-                        if tx.send((spinner_frames[frame].to_string(), spinner_line_index)).is_err() {
-                            break;
-                        }
-                        
-                        frame = (frame + 1) % spinner_frames.len();
-                        tokio::time::sleep(Duration::from_millis(80)).await;
-                    }
-                });
+        // Providers in their maintenance window are passed over
+        handler = self.avoid_quiet_hours(handler);
 
-                // Reset abort flags before starting
-                self.abort_requested.store(false, std::sync::atomic::Ordering::SeqCst);
-                if let Some(global_abort) = &self.global_abort {
-                    global_abort.store(false, std::sync::atomic::Ordering::SeqCst);
+        // Cloud providers are unreachable while offline: go local or queue the prompt
+        if !self.connectivity.is_online() && !handler.provider().is_local() {
+            match self.local_fallback_handler() {
+                Some((provider, local)) => {
+                    self.add_output(format!("⚡ Offline — using local provider {}", provider));
+                    handler = local;
+                }
+                None => {
+                    self.queue_offline_prompt(pending);
+                    return;
                 }
+            }
+        }
+        let prompt = pending.request();
+        let purpose = pending.purpose.clone();
+        let hold_code_blocks = pending.untrusted;
+
+        // Add a minimal spinner indicator with no extra space
+        self.add_output("".to_string());
+
+        // Animate the spinner on the last line, the one just added
+        let spinner_line_index = self.output_lines.len().saturating_sub(1);
+        self.spinner = Spinner::new(&get_config().theme, self.frame_pacer.is_low_bandwidth());
+        let (rx, spinner_task) = self.spinner.spawn(spinner_line_index, self.global_abort.clone());
+        self.spinner_rx = Some(rx);
+
+        // Reset abort flags before starting
+        self.abort_requested.store(false, std::sync::atomic::Ordering::SeqCst);
+        if let Some(global_abort) = &self.global_abort {
+            global_abort.store(false, std::sync::atomic::Ordering::SeqCst);
+        }
 
-                // Get shared references to what we need for the task 
-                let abort_flag = self.abort_requested.clone();
-                let global_abort_clone = self.global_abort.clone();
-                let cmd_clone = cmd.clone();
-                let ai_handler_clone = self.ai_handler.clone();
-                let ui_tx = self.ui_notifier.clone();
-                
-                // Create a task in the task manager
-                let task_id = self.task_manager.create_task(
-                    format!("AI: {}", cmd.chars().take(30).collect::<String>()),
-                    crate::utils::tasks::TaskType::AIGeneration
-                );
-                
-                // Mark task as running
-                self.task_manager.update_task_status(task_id, crate::ai::types::TaskStatus::Running);
-                
-                // Create a task progress update channel
-                let task_manager = self.task_manager.clone();
-                
-                // Use a truly concurrent approach by spawning the AI generation in a separate task
-                let ai_task = tokio::spawn(async move {
-                    // We'll use the atomic abort flag for thread-safe cancellation
-                    
-                    // Run the AI generation with a timeout to prevent hanging
-                    let result = tokio::time::timeout(
-                        std::time::Duration::from_secs(120), // Increase timeout for larger models
-                        ai_handler_clone.generate(&cmd_clone, abort_flag, global_abort_clone)
-                    ).await;
-                    
-                    // Update task status based on result
-                    match &result {
-                        Ok(Ok(response)) => {
-                            // If the response has progress stats, update the task
-                            if let Some(progress) = &response.progress {
-                                task_manager
-                                    .update_task_progress(task_id, progress.tokens_generated);
-                            }
-                            task_manager.update_task_status(
-                                task_id,
-                                crate::ai::types::TaskStatus::Completed,
-                            );
-                        }
-                        Ok(Err(e)) => {
-                            if let crate::ai::AIError::Cancelled(_) = e {
-                                task_manager.update_task_status(
-                                    task_id,
-                                    crate::ai::types::TaskStatus::Cancelled,
-                                );
-                            } else {
-                                task_manager.update_task_status(
-                                    task_id,
-                                    crate::ai::types::TaskStatus::Failed,
-                                );
-                            }
-                        }
-                        Err(_) => {
-                            task_manager
-                                .update_task_status(task_id, crate::ai::types::TaskStatus::Failed);
+        // Get shared references to what we need for the task 
+        let abort_flag = self.abort_requested.clone();
+        let global_abort_clone = self.global_abort.clone();
+        let cmd_clone = prompt.clone();
+        let executes_code = purpose.executes_code_blocks();
+        let ui_tx = self.ui_notifier.clone();
+        
+        // Create a task in the task manager; it starts once the scheduler has a slot
+        let task_id = self.task_manager.create_task(
+            format!("{}: {}", purpose.label(), prompt.chars().take(30).collect::<String>()),
+            crate::utils::tasks::TaskType::AIGeneration
+        );
+        self.spinner_task = Some(task_id);
+        let mut ai_handler_clone = handler
+            .with_history(pending.history.clone())
+            .holding_code_blocks(hold_code_blocks)
+            .in_dir(self.current_dir.clone())
+            .in_session(self.session_id.clone())
+            .tracking(self.task_manager.clone(), task_id);
+        if matches!(purpose, AIPurpose::Chat)
+            && let Some(chunks) = self.open_stream(task_id)
+        {
+            ai_handler_clone = ai_handler_clone.streaming(chunks);
+        }
+        self.stats.ai_count += 1;
+        self.in_flight.insert(task_id, pending);
+        self.persist_pending();
+        
+        // Create a task progress update channel
+        let task_manager = self.task_manager.clone();
+        
+        // Use a truly concurrent approach by spawning the AI generation in a separate task
+        let ai_task = tokio::spawn(async move {
+            // We'll use the atomic abort flag for thread-safe cancellation
+            
+            // Wait for a scheduler slot and provider rate limits before the timeout starts counting
+            let waited = match ai_handler_clone.wait_for_turn(&abort_flag, &global_abort_clone).await {
+                Ok(()) => ai_handler_clone
+                    .wait_for_rate_limit(&abort_flag, &global_abort_clone)
+                    .await,
+                Err(e) => Err(e),
+            };
+
+            // Run the AI generation with a timeout to prevent hanging
+            let result = match waited {
+                Err(e) => Ok(Err(e)),
+                Ok(()) => tokio::time::timeout(
+                    std::time::Duration::from_secs(120), // Increase timeout for larger models
+                    async {
+                        if executes_code {
+                            ai_handler_clone.generate(&cmd_clone, abort_flag, global_abort_clone).await
+                        } else {
+                            ai_handler_clone.generate_plain(&cmd_clone, abort_flag, global_abort_clone).await
                         }
                     }
-
-                    // Notify the UI thread that an update is needed
-                    if let Some(tx) = ui_tx {
-                        let _ = tx.send(()).await;
+                ).await,
+            };
+            
+            // Update task status based on result
+            match &result {
+                Ok(Ok(response)) => {
+                    // If the response has progress stats, update the task
+                    if let Some(progress) = &response.progress {
+                        task_manager.update_task_progress(
+                            task_id,
+                            progress.tokens_generated,
+                            progress.estimated_total_tokens,
+                        );
                     }
-
-                    result
-                });
-
-                // Create a channel to send the response back to the main thread
-                let (response_tx, response_rx) = tokio::sync::mpsc::channel::<Option<String>>(1);
-                
-                // Store the receiver for later use
-                self.task_manager.set_response_channel(task_id, response_rx);
-                
-                // We'll save the result handling in a separate task to avoid blocking
-                let ui_tx_clone = self.ui_notifier.clone();
-                let result_handler = tokio::spawn(async move {
-                    // Await the AI task result
-                    let result = ai_task.await;
-                    
-                    // Process the result to get the AI response content
-                    let response_content = match result {
-                        Ok(Ok(response)) => {
-                            // If we have a successful response, extract the content
-                            if let Ok(ai_response) = response {
-                                // Quietly return the content without debug messages
-                                Some(ai_response.content)
-                            } else {
-                                None
-                            }
-                        }
-                        _ => {
-                            None
-                        }
-                    };
-                    
-                    // Send the response content back to the main thread
-                    let _ = response_tx.send(response_content).await;
-                    
-                    // Notify the UI thread that we have a result
-                    if let Some(tx) = ui_tx_clone {
-                        let _ = tx.send(()).await;
+                    task_manager.set_task_usage(task_id, crate::utils::TaskUsage {
+                        provider: ai_handler_clone.provider(),
+                        model: response.model.clone(),
+                        tokens: response.usage.clone(),
+                        cost: crate::ai::pricing::response_cost(ai_handler_clone.provider(), response),
+                        latency: response.progress.as_ref().map(Latency::of),
+                    });
+                    task_manager.update_task_status(
+                        task_id,
+                        crate::ai::types::TaskStatus::Completed,
+                    );
+                }
+                Ok(Err(e)) => {
+                    if let crate::ai::AIError::Cancelled(_) = e {
+                        task_manager.update_task_status(
+                            task_id,
+                            crate::ai::types::TaskStatus::Cancelled,
+                        );
+                    } else {
+                        task_manager.update_task_status(
+                            task_id,
+                            crate::ai::types::TaskStatus::Failed,
+                        );
                     }
-                });
+                }
+                Err(_) => {
+                    task_manager
+                        .update_task_status(task_id, crate::ai::types::TaskStatus::Failed);
+                }
+            }
 
-                // Store the task in our background tasks
-                self.background_tasks.push(result_handler);
+            // Notify the UI thread that an update is needed
+            if let Some(tx) = ui_tx {
+                let _ = tx.send(()).await;
+            }
 
-                // No processing indicator, keep output minimal
+            result
+        });
 
-                // Set up spinner cleanup when AI task completes
-                let ui_tx_clone = self.ui_notifier.clone();
-                tokio::spawn(async move {
-                    // Give the task some time to run
-                    tokio::time::sleep(Duration::from_secs(120)).await;
-                    
-                    // Abort the spinner task
-                    spinner_task.abort();
-                    
-                    // Notify UI thread that we should refresh
-                    if let Some(tx) = ui_tx_clone {
-                        let _ = tx.send(()).await;
-                    }
-                });
+        // Create a channel to send the response back to the main thread
+        let (response_tx, response_rx) = tokio::sync::mpsc::channel::<Option<String>>(1);
+        
+        // Store the receiver for later use
+        self.task_manager.set_response_channel(task_id, response_rx);
+        
+        // We'll save the result handling in a separate task to avoid blocking
+        let ui_tx_clone = self.ui_notifier.clone();
+        let result_handler = tokio::spawn(async move {
+            // Await the AI task result
+            let result = ai_task.await;
+            
+            // Process the result to get the AI response content
+            let response_content = match result {
+                // Quietly return the content of a successful response without debug messages
+                Ok(Ok(Ok(ai_response))) => Some(ai_response.content),
+                _ => {
+                    None
+                }
+            };
+            
+            // Send the response content back to the main thread
+            let _ = response_tx.send(response_content).await;
+            
+            // Notify the UI thread that we have a result
+            if let Some(tx) = ui_tx_clone {
+                let _ = tx.send(()).await;
             }
-        }
-
-        // Update current mode
-        self.current_mode = mode;
+        });
+
+        // Store the task in our background tasks
+        self.background_tasks.push(result_handler);
+
+        // No processing indicator, keep output minimal
+
+        // Set up spinner cleanup when AI task completes
+        let ui_tx_clone = self.ui_notifier.clone();
+        tokio::spawn(async move {
+            // Give the task some time to run
+            tokio::time::sleep(Duration::from_secs(120)).await;
+            
+            // Abort the spinner task
+            spinner_task.abort();
+            
+            // Notify UI thread that we should refresh
+            if let Some(tx) = ui_tx_clone {
+                let _ = tx.send(()).await;
+            }
+        });
     }
 
     pub fn navigate_history_up(&mut self) {
@@ -669,6 +1536,7 @@ impl App {
         self.show_context_menu = true;
         self.context_menu_x = x;
         self.context_menu_y = y;
+        self.context_menu_selected = 0;
     }
 
     pub fn hide_context_menu(&mut self) {
@@ -698,6 +1566,10 @@ impl App {
                     }
                 }
             }
+            "ask" => {
+                let row = self.context_menu_y;
+                self.open_selection_prompt(Some(row));
+            }
             "select_all" => {
                 if !self.output_lines.is_empty() {
                     self.is_selecting_text = true;
@@ -719,15 +1591,42 @@ impl App {
     pub fn toggle_tasks_popup(&mut self) {
         self.show_tasks_popup = !self.show_tasks_popup;
     }
+
+    /// Handle `/debug [reset]`: toggle the debug panel or clear its frame times
+    pub fn handle_debug(&mut self, args: &str) {
+        match args {
+            "" => self.show_debug_popup = !self.show_debug_popup,
+            "reset" => {
+                self.frame_times.reset();
+                self.add_output("✅ Frame times cleared".to_string());
+            }
+            _ => self.add_output("Error: Usage: /debug [reset]".to_string()),
+        }
+    }
     
-    /// Get active tasks for display
+    /// Get active tasks for display, highest priority first
     pub fn get_active_tasks(&self) -> Vec<crate::utils::tasks::Task> {
-        self.task_manager.active_tasks()
+        let mut tasks = self.task_manager.active_tasks();
+        tasks.retain(|task| self.task_label_filter.as_ref().is_none_or(|label| task.has_label(label)));
+        tasks.sort_by(|a, b| b.priority.cmp(&a.priority).then(a.created_at.cmp(&b.created_at)));
+        tasks
     }
     
     /// Get recent completed tasks
     pub fn get_recent_tasks(&self) -> Vec<crate::utils::tasks::Task> {
-        self.task_manager.recent_tasks()
+        let mut tasks = self.task_manager.recent_tasks();
+        tasks.retain(|task| self.task_label_filter.as_ref().is_none_or(|label| task.has_label(label)));
+        tasks
+    }
+    
+    /// Show only tasks with the next label in the tasks popup, then all tasks again
+    pub fn cycle_task_label_filter(&mut self) {
+        let labels = self.task_manager.labels();
+        let next = match &self.task_label_filter {
+            None => 0,
+            Some(current) => labels.iter().position(|label| label == current).map_or(0, |idx| idx + 1),
+        };
+        self.task_label_filter = labels.get(next).cloned();
     }
     
     /// Check if the cleanup timer has been initialized
@@ -777,6 +1676,9 @@ impl App {
                     // Add a message to the output indicating cancellation was requested
                     self.add_output(format!("\n[Task {}] Cancellation requested.\n", id.short()));
                 }
+                if let Some(abort) = self.bash_runs.get(&id) {
+                    abort.store(true, std::sync::atomic::Ordering::SeqCst);
+                }
                 
                 // Mark the task as cancelled in the task manager
                 return self.task_manager.cancel_task(id);
@@ -827,9 +1729,13 @@ impl App {
         let now = Instant::now();
         let elapsed = now.duration_since(self.last_cursor_toggle).as_millis();
 
-        if elapsed >= CURSOR_BLINK_RATE_MS {
+        // A steady cursor saves a repaint every blink on slow links
+        if self.frame_pacer.is_low_bandwidth() {
+            self.cursor_visible = true;
+        } else if elapsed >= CURSOR_BLINK_RATE_MS {
             self.cursor_visible = !self.cursor_visible;
             self.last_cursor_toggle = now;
+            self.needs_redraw = true;
         }
 
         // Check if we received any spinner update from the background thread
@@ -843,10 +1749,17 @@ impl App {
 
             // If we got any updates, apply the latest one
             if let Some((frame, line_index)) = latest_update {
-                // Update the spinner in the output area
-                if line_index < self.output_lines.len() {
-                    // Update the line with the new spinner frame
-                    self.output_lines[line_index] = frame;
+                // Update the spinner in the output area, unless streamed text took its line
+                if self.output_lines.get(line_index).is_some_and(|line| line.trim().is_empty() || progress::is_indicator(line)) {
+                    // Update the line with the new spinner frame and the stage of the
+                    // generation, with a bar once the ETA is known
+                    let task = self.spinner_task.and_then(|id| self.task_manager.get_task(id));
+                    let waited = task
+                        .as_ref()
+                        .and_then(|task| (Utc::now() - task.started_at.unwrap_or(task.created_at)).to_std().ok())
+                        .unwrap_or_default();
+                    let progress = task.and_then(|task| task.progress);
+                    self.output_lines[line_index] = self.spinner.status_line(&frame, progress.as_ref(), waited);
 
                     // Rebuild the output string to reflect the spinner update
                     // Make sure we use the entire output_lines vector
@@ -858,6 +1771,7 @@ impl App {
                         }
                     }
                     self.output = rebuilt_output;
+                    self.output_generation += 1;
                     
                     updated = true;
                 }
@@ -866,268 +1780,465 @@ impl App {
 
         // If we made changes, trigger a redraw
         if updated {
-            // The redraw will happen on the next tick naturally
+            self.needs_redraw = true;
         }
     }
 
+    /// Abort the running generation and every other operation (Esc)
+    fn abort_operations(&mut self, tui: &mut Tui) {
+        // Set both the local and global abort flags immediately
+        self.abort_requested.store(true, std::sync::atomic::Ordering::SeqCst);
+        if let Some(global_abort) = &self.global_abort {
+            // Use SeqCst ordering to ensure all threads see this change immediately
+            global_abort.store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+        
+        // Cancel any ongoing operations
+        self.stop_chain("aborted");
+        self.kill_bash_runs();
+        self.cancel_update();
+        // Always show abort message in output area (but avoid duplicates)
+        if !self.output.contains("[Operation Aborted]") {
+            self.add_output("\n[Operation Aborted] ❌ Cancellation requested. Processing should stop momentarily.\n".to_string());
+        }
+        
+        // Cancel spinner if it exists - this is critical for releasing resources
+        if self.spinner_rx.is_some() {
+            if let Some(handle) = self.spinner_rx.take() {
+                // Explicitly drop the channel to ensure the spinner task terminates
+                drop(handle);
+            }
+        }
+        
+        // Reset state that may be affected
+        self.is_scrolling = false;
+        
+        // Force immediate UI refresh to show abort message
+        tui.immediate_refresh(|f| {
+            ui::render(f, self);
+        }).ok();
+    }
+
     pub async fn handle_events(&mut self, tui: &mut Tui) -> Result<()> {
-        if let Ok(event) = tui.events().next() {
-            match event {
-                Event::Abort => {
-                    // Set both the local and global abort flags immediately
-                    self.abort_requested.store(true, std::sync::atomic::Ordering::SeqCst);
-                    if let Some(global_abort) = &self.global_abort {
-                        // Use SeqCst ordering to ensure all threads see this change immediately
-                        global_abort.store(true, std::sync::atomic::Ordering::SeqCst);
+        let event = tui.next_event().await?;
+        self.handle_event(event, tui).await
+    }
+
+    /// Handle one event received by the main loop
+    pub async fn handle_event(&mut self, event: Event, tui: &mut Tui) -> Result<()> {
+        match event {
+            Event::Abort if self.pending_confirmation.is_some() => {
+                // Esc dismisses the confirmation popup instead of aborting
+                self.cancel_confirmation();
+            }
+            Event::Abort if self.bash_queue.is_some() => {
+                // Likewise for the bash block popup
+                self.close_bash_queue();
+            }
+            Event::Abort if self.is_generating() => {
+                // A running generation is aborted before anything else is dismissed
+                self.abort_operations(tui);
+            }
+            Event::Abort if self.incomplete_banner.is_some() => {
+                // And the banner of an empty or cut-off response
+                self.dismiss_incomplete_banner();
+            }
+            Event::Abort if !self.marked_sections.is_empty() => {
+                // And the marks of messages
+                self.marked_sections.clear();
+            }
+            Event::Abort if self.output_search.is_some() => {
+                // And the search highlights
+                self.end_search();
+            }
+            Event::Abort => self.abort_operations(tui),
+            Event::Tick => {
+                // Advance the cursor blink and the session clock
+                self.tick();
+            }
+            Event::Key(key_event) => {
+                // Only handle key events if we're not scrolling
+                if !self.is_scrolling {
+                    // A `/pty` command gets every key while its pane is open
+                    if self.interactive.is_some() {
+                        self.handle_interactive_key(key_event);
+                        return Ok(());
                     }
-                    
-                    // Cancel any ongoing operations
-                    // Always show abort message in output area (but avoid duplicates)
-                    if !self.output.contains("[Operation Aborted]") {
-                        self.add_output("\n[Operation Aborted] ❌ Cancellation requested. Processing should stop momentarily.\n".to_string());
+
+                    // A pending confirmation captures all keys until resolved
+                    if self.pending_confirmation.is_some() {
+                        self.handle_confirmation_key(key_event, tui);
+                        return Ok(());
                     }
-                    
-                    // Cancel spinner if it exists - this is critical for releasing resources
-                    if self.spinner_rx.is_some() {
-                        if let Some(handle) = self.spinner_rx.take() {
-                            // Explicitly drop the channel to ensure the spinner task terminates
-                            drop(handle);
+
+                    // The session browser takes all keys while open
+                    if self.session_browser.is_some() {
+                        self.handle_session_browser_key(key_event);
+                        return Ok(());
+                    }
+
+                    // As does the file tree
+                    if self.file_tree.is_some() {
+                        self.handle_file_tree_key(key_event, tui);
+                        return Ok(());
+                    }
+
+                    // As does the bash block popup
+                    if self.bash_queue.is_some() {
+                        self.handle_bash_queue_key(key_event);
+                        return Ok(());
+                    }
+
+                    // And the banner of an empty or cut-off response
+                    if self.incomplete_banner.is_some() {
+                        self.handle_incomplete_key(key_event);
+                        return Ok(());
+                    }
+
+                    // So does the mini prompt of "Send to AI"
+                    if self.selection_prompt.is_some() {
+                        if let Some(prompt) = self.handle_selection_prompt_key(key_event) {
+                            self.submit_input(prompt, tui).await;
                         }
+                        return Ok(());
                     }
-                    
-                    // Reset state that may be affected
-                    self.is_scrolling = false;
-                    
-                    // Force immediate UI refresh to show abort message
-                    tui.immediate_refresh(|f| {
-                        ui::render(f, self);
-                    }).ok();
-                }
-                Event::Tick => {
-                    // Update cursor blink state
-                    self.update_cursor_blink();
-                }
-                Event::Key(key_event) => {
-                    // Only handle key events if we're not scrolling
-                    if !self.is_scrolling {
-                        // Hide context menu on any key press
-                        if self.show_context_menu {
-                            // Handle menu selection
-                            if key_event.code == KeyCode::Enter {
-                                let menu_options = ["copy", "paste", "select_all"];
-                                if let Some(selected) = menu_options.first() {
-                                    // In the future, track selected item
-                                    self.handle_context_menu_action(selected);
-                                }
+
+                    // Keys of the selected output section and message
+                    if self.selected_section.is_some() {
+                        match self.handle_section_key(key_event) {
+                            MessageKey::Ignored => {}
+                            MessageKey::Handled => return Ok(()),
+                            MessageKey::Execute(command) => {
+                                self.execute_command(command, tui).await;
                                 return Ok(());
                             }
-                            self.hide_context_menu();
-                            return Ok(());
                         }
+                    }
 
+                    // Hide context menu on any key press
+                    if self.show_context_menu {
+                        // Move through the entries and run the highlighted one
                         match key_event.code {
-                            KeyCode::Enter => {
-                                // Check if Shift is held - if so, insert newline instead of submitting
-                                if key_event.modifiers.contains(KeyModifiers::SHIFT) {
-                                    // Insert a newline at cursor position
-                                    self.input.insert(self.cursor_position, '\n');
-                                    self.cursor_position += 1;
-                                    // Reset cursor blink
-                                    self.cursor_visible = true;
-                                    self.last_cursor_toggle = Instant::now();
-                                } else {
-                                    // Submit the command
-                                    let command = self.input.trim().to_string();
-                                    if !command.is_empty() {
-                                        self.input.clear();
-                                        self.cursor_position = 0;
-                                        self.execute_command(command, tui).await;
-                                    }
-                                }
-                            }
-                            KeyCode::Char('c')
-                                if key_event.modifiers.contains(KeyModifiers::CONTROL) =>
-                            {
-                                // Handle task cancellation in tasks popup view
-                                if self.show_tasks_popup {
-                                    // Get the first active task and cancel it
-                                    let active_tasks = self.get_active_tasks();
-                                    if !active_tasks.is_empty() {
-                                        // Cancel the most recent active task
-                                        let task_id = active_tasks[0].id;
-                                        if self.cancel_task(task_id) {
-                                            self.add_output(format!("\nCancelling task {}...\n", task_id.short()));
-                                        }
-                                    }
-                                }
-                                // Handle text selection copy
-                                else if self.is_selecting_text {
-                                    self.copy_selected_text();
-                                }
-                                // Otherwise abort is handled in Event::Abort handler
-                            }
-                            // Context menu key
-                            KeyCode::Char('k') if key_event.modifiers == KeyModifiers::CONTROL => {
-                                self.show_context_menu(10, 10); // Show context menu at center
-                            }
-                            // Show tasks popup with Ctrl+T
-                            KeyCode::Char('t') if key_event.modifiers == KeyModifiers::CONTROL => {
-                                self.toggle_tasks_popup();
-                            }
-                            // Start text selection with Shift+Up/Down
-                            KeyCode::Up if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
-                                self.start_text_selection();
-                                self.move_selection_up();
-                            }
-                            KeyCode::Down if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
-                                self.start_text_selection();
-                                self.move_selection_down();
-                            }
-                            // Normal navigation
                             KeyCode::Up => {
-                                self.navigate_history_up();
+                                self.context_menu_selected = self.context_menu_selected.saturating_sub(1);
                             }
                             KeyCode::Down => {
-                                self.navigate_history_down();
-                            }
-                            // Scrolling with page up/down
-                            KeyCode::PageUp => {
-                                self.scroll_up(10);
-                            }
-                            KeyCode::PageDown => {
-                                self.scroll_down(10);
-                            }
-                            KeyCode::Char('v') if key_event.modifiers == KeyModifiers::CONTROL => {
-                                // Paste from clipboard
-                                self.handle_context_menu_action("paste");
+                                self.context_menu_selected =
+                                    (self.context_menu_selected + 1).min(CONTEXT_MENU.len() - 1);
                             }
-                            KeyCode::Char('a') if key_event.modifiers == KeyModifiers::CONTROL => {
-                                // Select all
-                                self.handle_context_menu_action("select_all");
+                            KeyCode::Enter => {
+                                let (_, action) = CONTEXT_MENU[self.context_menu_selected];
+                                self.handle_context_menu_action(action);
                             }
-                            // Input editing with cursor support
-                            KeyCode::Char(c) => {
-                                // Insert character at cursor position
-                                self.input.insert(self.cursor_position, c);
+                            _ => self.hide_context_menu(),
+                        }
+                        return Ok(());
+                    }
+
+                    match key_event.code {
+                        KeyCode::Enter => {
+                            // Check if Shift is held - if so, insert newline instead of submitting
+                            if key_event.modifiers.contains(KeyModifiers::SHIFT) {
+                                // Insert a newline at cursor position
+                                self.input.insert(self.cursor_position, '\n');
                                 self.cursor_position += 1;
-                                // Reset blink timer and make cursor visible when typing
+                                // Reset cursor blink
                                 self.cursor_visible = true;
                                 self.last_cursor_toggle = Instant::now();
-                            }
-                            KeyCode::Backspace => {
-                                // Delete character before cursor
-                                if self.cursor_position > 0 {
-                                    self.cursor_position -= 1;
-                                    self.input.remove(self.cursor_position);
-                                    // Reset blink timer
-                                    self.cursor_visible = true;
-                                    self.last_cursor_toggle = Instant::now();
+                            } else {
+                                // Submit the command
+                                let command = self.input.trim().to_string();
+                                if !command.is_empty() {
+                                    self.input.clear();
+                                    self.cursor_position = 0;
+                                    // `:<n>` and `''` move around the output instead of running
+                                    if !self.handle_jump_input(&command) {
+                                        self.submit_input(command, tui).await;
+                                    }
                                 }
                             }
-                            KeyCode::Delete => {
-                                // Delete character at cursor
-                                if self.cursor_position < self.input.len() {
-                                    self.input.remove(self.cursor_position);
-                                    // Reset blink timer
-                                    self.cursor_visible = true;
-                                    self.last_cursor_toggle = Instant::now();
+                        }
+                        KeyCode::Char('c')
+                            if key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+                        {
+                            // Handle task cancellation in tasks popup view
+                            if self.show_tasks_popup {
+                                // Get the first active task and cancel it
+                                let active_tasks = self.get_active_tasks();
+                                if !active_tasks.is_empty() {
+                                    // Cancel the most recent active task
+                                    let task_id = active_tasks[0].id;
+                                    if self.cancel_task(task_id) {
+                                        self.add_output(format!("\nCancelling task {}...\n", task_id.short()));
+                                    }
                                 }
                             }
-                            // Cursor movement
-                            KeyCode::Left => {
-                                if self.cursor_position > 0 {
-                                    self.cursor_position -= 1;
-                                    // Reset blink timer and make cursor visible when moving
-                                    self.cursor_visible = true;
-                                    self.last_cursor_toggle = Instant::now();
-                                }
+                            // Handle text selection copy
+                            else if self.is_selecting_text {
+                                self.copy_selected_text();
                             }
-                            KeyCode::Right => {
-                                if self.cursor_position < self.input.len() {
-                                    self.cursor_position += 1;
-                                    // Reset blink timer and make cursor visible when moving
-                                    self.cursor_visible = true;
-                                    self.last_cursor_toggle = Instant::now();
-                                }
+                            // Otherwise abort is handled in Event::Abort handler
+                        }
+                        // Context menu key
+                        KeyCode::Char('k') if key_event.modifiers == KeyModifiers::CONTROL => {
+                            self.show_context_menu(10, 10); // Show context menu at center
+                        }
+                        // Ask the model about the selected text or message
+                        KeyCode::Char('e') if key_event.modifiers == KeyModifiers::CONTROL => {
+                            self.open_selection_prompt(None);
+                        }
+                        // Search the output with Ctrl+F
+                        KeyCode::Char('f') if key_event.modifiers == KeyModifiers::CONTROL => {
+                            self.start_search_input();
+                        }
+                        // Filter the tasks popup by label with Tab
+                        KeyCode::Tab if self.show_tasks_popup => {
+                            self.cycle_task_label_filter();
+                        }
+                        // Accept the suggested command for a typo with Tab
+                        KeyCode::Tab if self.command_suggestion.is_some() => {
+                            if let Some(suggestion) = self.command_suggestion.take() {
+                                self.input = suggestion;
+                                self.cursor_position = self.input.len();
                             }
-                            KeyCode::Home => {
-                                self.cursor_position = 0;
+                        }
+                        // Quote the selected text or message into the input with Ctrl+Q
+                        KeyCode::Char('q') if key_event.modifiers == KeyModifiers::CONTROL => {
+                            self.quote_selection();
+                        }
+                        // Show tasks popup with Ctrl+T
+                        KeyCode::Char('t') if key_event.modifiers == KeyModifiers::CONTROL => {
+                            self.toggle_tasks_popup();
+                        }
+                        // Generate a commit message for staged changes with Ctrl+G
+                        KeyCode::Char('g') if key_event.modifiers == KeyModifiers::CONTROL => {
+                            self.execute_command("/commitmsg".to_string(), tui).await;
+                        }
+                        // Start or stop push-to-talk recording with Ctrl+R
+                        KeyCode::Char('r') if key_event.modifiers == KeyModifiers::CONTROL => {
+                            self.toggle_recording();
+                        }
+                        // Show or hide the reasoning of responses with Ctrl+O
+                        KeyCode::Char('o') if key_event.modifiers == KeyModifiers::CONTROL => {
+                            self.toggle_reasoning();
+                        }
+                        // Switch diffs between unified and side by side with Alt+D
+                        KeyCode::Char('d') if key_event.modifiers == KeyModifiers::ALT => {
+                            self.toggle_diff_view();
+                        }
+                        // Stop reading a response aloud with Ctrl+X
+                        KeyCode::Char('x') if key_event.modifiers == KeyModifiers::CONTROL => {
+                            self.stop_speaking();
+                        }
+                        // Preview and save file blocks with Ctrl+S
+                        KeyCode::Char('s') if key_event.modifiers == KeyModifiers::CONTROL => {
+                            self.review_file_blocks();
+                        }
+                        // Resize the input box with Ctrl+Up/Down
+                        KeyCode::Up if key_event.modifiers == KeyModifiers::CONTROL => {
+                            self.resize_input(1);
+                        }
+                        KeyCode::Down if key_event.modifiers == KeyModifiers::CONTROL => {
+                            self.resize_input(-1);
+                        }
+                        // Start text selection with Shift+Up/Down
+                        KeyCode::Up if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
+                            self.start_text_selection();
+                            self.move_selection_up();
+                        }
+                        KeyCode::Down if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
+                            self.start_text_selection();
+                            self.move_selection_down();
+                        }
+                        // Select output sections with Alt+Up/Down
+                        KeyCode::Up if key_event.modifiers == KeyModifiers::ALT => {
+                            self.select_section(-1);
+                        }
+                        KeyCode::Down if key_event.modifiers == KeyModifiers::ALT => {
+                            self.select_section(1);
+                        }
+                        // Normal navigation
+                        KeyCode::Up => {
+                            self.navigate_history_up();
+                        }
+                        KeyCode::Down => {
+                            self.navigate_history_down();
+                        }
+                        // Scrolling with page up/down
+                        KeyCode::PageUp => {
+                            self.scroll_up(10);
+                        }
+                        KeyCode::PageDown => {
+                            self.scroll_down(10);
+                        }
+                        KeyCode::Char('v') if key_event.modifiers == KeyModifiers::CONTROL => {
+                            // Paste from clipboard
+                            self.handle_context_menu_action("paste");
+                        }
+                        KeyCode::Char('a') if key_event.modifiers == KeyModifiers::CONTROL => {
+                            // Select all
+                            self.handle_context_menu_action("select_all");
+                        }
+                        // Hop between prompt boundaries, leaving plain braces to the input
+                        KeyCode::Char('{' | '}') if key_event.modifiers.contains(KeyModifiers::ALT) => {
+                            if let Some(delta) = anchor_jump(&key_event) {
+                                self.jump_to_anchor(delta);
+                            }
+                        }
+                        // Move between search matches while the input is empty, or with Alt+N/Alt+P
+                        KeyCode::Char('n') if self.input.is_empty() && self.output_search.is_some() => {
+                            self.jump_to_match(true);
+                        }
+                        KeyCode::Char('N') if self.input.is_empty() && self.output_search.is_some() => {
+                            self.jump_to_match(false);
+                        }
+                        KeyCode::Char('n') if key_event.modifiers == KeyModifiers::ALT && self.output_search.is_some() => {
+                            self.jump_to_match(true);
+                        }
+                        KeyCode::Char('p') if key_event.modifiers == KeyModifiers::ALT && self.output_search.is_some() => {
+                            self.jump_to_match(false);
+                        }
+                        // Input editing with cursor support
+                        KeyCode::Char(c) => {
+                            // Insert character at cursor position
+                            self.input.insert(self.cursor_position, c);
+                            self.cursor_position += 1;
+                            // Reset blink timer and make cursor visible when typing
+                            self.cursor_visible = true;
+                            self.last_cursor_toggle = Instant::now();
+                        }
+                        KeyCode::Backspace => {
+                            // Delete character before cursor
+                            if self.cursor_position > 0 {
+                                self.cursor_position -= 1;
+                                self.input.remove(self.cursor_position);
+                                // Reset blink timer
+                                self.cursor_visible = true;
+                                self.last_cursor_toggle = Instant::now();
+                            }
+                        }
+                        KeyCode::Delete => {
+                            // Delete character at cursor
+                            if self.cursor_position < self.input.len() {
+                                self.input.remove(self.cursor_position);
+                                // Reset blink timer
+                                self.cursor_visible = true;
+                                self.last_cursor_toggle = Instant::now();
+                            }
+                        }
+                        // Cursor movement
+                        KeyCode::Left => {
+                            if self.cursor_position > 0 {
+                                self.cursor_position -= 1;
                                 // Reset blink timer and make cursor visible when moving
                                 self.cursor_visible = true;
                                 self.last_cursor_toggle = Instant::now();
                             }
-                            KeyCode::End => {
-                                self.cursor_position = self.input.len();
+                        }
+                        KeyCode::Right => {
+                            if self.cursor_position < self.input.len() {
+                                self.cursor_position += 1;
                                 // Reset blink timer and make cursor visible when moving
                                 self.cursor_visible = true;
                                 self.last_cursor_toggle = Instant::now();
                             }
-                            KeyCode::Esc => {
-                                // Handle local functions only, the abort is handled at the Event::Abort level
-                                if self.show_tasks_popup {
-                                    self.show_tasks_popup = false;
-                                } else if self.is_selecting_text {
-                                    self.cancel_text_selection();
-                                } else {
-                                    self.input.clear();
-                                    self.cursor_position = 0;
-                                }
+                        }
+                        KeyCode::Home => {
+                            self.cursor_position = 0;
+                            // Reset blink timer and make cursor visible when moving
+                            self.cursor_visible = true;
+                            self.last_cursor_toggle = Instant::now();
+                        }
+                        KeyCode::End => {
+                            self.cursor_position = self.input.len();
+                            // Reset blink timer and make cursor visible when moving
+                            self.cursor_visible = true;
+                            self.last_cursor_toggle = Instant::now();
+                        }
+                        KeyCode::Esc => {
+                            // Handle local functions only, the abort is handled at the Event::Abort level
+                            if self.show_tasks_popup {
+                                self.show_tasks_popup = false;
+                            } else if self.show_debug_popup {
+                                self.show_debug_popup = false;
+                            } else if self.is_selecting_text {
+                                self.cancel_text_selection();
+                            } else {
+                                self.input.clear();
+                                self.cursor_position = 0;
                             }
-                            _ => {}
                         }
+                        _ => {}
                     }
                 }
-                Event::Mouse(mouse_event) => {
-                    // Only process mouse events in vim-like selection mode
-                    if !self.native_selection_mode {
-                        // Only process mouse events in the output area (y < output_area_height)
-                        if mouse_event.row < self.output_area_height {
-                            match mouse_event.kind {
-                                crossterm::event::MouseEventKind::Down(
-                                    crossterm::event::MouseButton::Right,
-                                ) => {
-                                    self.show_context_menu(mouse_event.column, mouse_event.row);
-                                }
-                                crossterm::event::MouseEventKind::Down(
-                                    crossterm::event::MouseButton::Left,
-                                ) => {
-                                    self.start_mouse_selection(mouse_event.column, mouse_event.row);
-                                }
-                                crossterm::event::MouseEventKind::Drag(
-                                    crossterm::event::MouseButton::Left,
-                                ) => {
-                                    self.update_mouse_selection(
-                                        mouse_event.column,
-                                        mouse_event.row,
-                                    );
-                                }
-                                crossterm::event::MouseEventKind::Up(
-                                    crossterm::event::MouseButton::Left,
-                                ) => {
-                                    self.end_mouse_selection();
-                                }
-                                _ => {}
+            }
+            Event::Mouse(mouse_event) => {
+                // Only process mouse events in vim-like selection mode
+                if !self.native_selection_mode {
+                    // Clicks on the mode, directory and cost in the status bar
+                    if mouse_event.kind
+                        == crossterm::event::MouseEventKind::Down(
+                            crossterm::event::MouseButton::Left,
+                        )
+                        && let Some(action) =
+                            self.status_hits.action_at(mouse_event.column, mouse_event.row)
+                    {
+                        self.run_status_action(action, tui).await;
+                        return Ok(());
+                    }
+                    // Only process mouse events in the output area (y < output_area_height)
+                    if mouse_event.row < self.output_area_height {
+                        match mouse_event.kind {
+                            crossterm::event::MouseEventKind::Down(
+                                crossterm::event::MouseButton::Right,
+                            ) => {
+                                self.show_context_menu(mouse_event.column, mouse_event.row);
+                            }
+                            crossterm::event::MouseEventKind::Down(
+                                crossterm::event::MouseButton::Left,
+                            ) => {
+                                self.start_mouse_selection(mouse_event.column, mouse_event.row);
+                            }
+                            crossterm::event::MouseEventKind::Drag(
+                                crossterm::event::MouseButton::Left,
+                            ) => {
+                                self.update_mouse_selection(
+                                    mouse_event.column,
+                                    mouse_event.row,
+                                );
+                            }
+                            crossterm::event::MouseEventKind::Up(
+                                crossterm::event::MouseButton::Left,
+                            ) => {
+                                self.end_mouse_selection();
                             }
+                            _ => {}
                         }
                     }
                 }
-                Event::Resize(_, _) => {}
-                Event::Copy => {
-                    if !self.native_selection_mode {
-                        self.copy_selected_text();
-                    }
-                }
-                Event::ScrollUp => {
-                    self.is_scrolling = true;
-                    self.scroll_up(3); // Scroll 3 lines at a time for better UX
-                    self.is_scrolling = false;
-                }
-                Event::ScrollDown => {
-                    self.is_scrolling = true;
-                    self.scroll_down(3); // Scroll 3 lines at a time for better UX
-                    self.is_scrolling = false;
+            }
+            Event::Resize(_, _) => {}
+            Event::Copy => {
+                if !self.native_selection_mode {
+                    self.copy_selected_text();
                 }
             }
+            Event::ScrollUp if self.pending_confirmation.is_some() => {
+                self.scroll_confirmation(-3);
+            }
+            Event::ScrollDown if self.pending_confirmation.is_some() => {
+                self.scroll_confirmation(3);
+            }
+            Event::ScrollUp => {
+                self.is_scrolling = true;
+                self.scroll_up(3); // Scroll 3 lines at a time for better UX
+                self.is_scrolling = false;
+            }
+            Event::ScrollDown => {
+                self.is_scrolling = true;
+                self.scroll_down(3); // Scroll 3 lines at a time for better UX
+                self.is_scrolling = false;
+            }
         }
         Ok(())
     }
@@ -1,11 +1,42 @@
-use crate::ai::{AIClient, AIClientFactory, AIError, AIResponse, ModelCosts};
+use crate::ai::routing::Route;
+use crate::ai::types::{ProgressStats, TaskStatus};
+use crate::ai::{
+    AIClient, AIClientFactory, AIError, AIResponse, ChunkSender, ModelCosts, ProgressCallback,
+    Provider,
+};
+use crate::ai::{continuation, moderation, persona, rate_limit, sampling};
 use crate::config;
+use crate::config::{Persona, PostProcessor, SamplingPreset};
 use crate::handlers::HandlerResult;
-use regex::Regex;
+use crate::handlers::code::CODE_BLOCK;
+use crate::handlers::postprocess::{postprocess, split_thinking};
+use crate::utils::log_error;
+use crate::utils::{TaskId, TaskManager};
+use crate::utils::{audit, transcript};
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::AtomicBool;
+use std::time::Duration;
 use tokio::sync::Mutex;
 
+/// Attempts made for a request rejected with a rate limit error
+const MAX_RATE_LIMIT_ATTEMPTS: usize = 3;
+
+/// Move `<think>` blocks out of the content into the reasoning
+///
+/// Models served without a reasoning field (DeepSeek-R1 on older Ollama
+/// versions, llama.cpp) write their reasoning into the text itself.
+fn separate_reasoning(mut response: AIResponse) -> AIResponse {
+    if let (Some(reasoning), content) = split_thinking(&response.content) {
+        response.content = content;
+        response.reasoning = Some(match response.reasoning.take() {
+            Some(reported) => format!("{}\n\n{}", reported, reasoning),
+            None => reasoning,
+        });
+    }
+    response
+}
+
 /// AIHandler handles all AI operations in a thread-safe manner
 ///
 /// This struct provides methods for generating AI responses, managing models,
@@ -17,6 +48,26 @@ use tokio::sync::Mutex;
 #[derive(Clone)]
 pub struct AIHandler {
     client: Arc<Mutex<Box<dyn AIClient>>>,
+    /// Provider used instead of the configured one, if any
+    provider: Option<Provider>,
+    /// Task whose entry shows rate limit waits
+    task: Option<(TaskManager, TaskId)>,
+    /// Routing decision shown below chat responses
+    route: Option<Route>,
+    /// Leave code blocks for the user instead of running them
+    hold_code_blocks: bool,
+    /// Directory code blocks run in, the process's working directory if unset
+    working_dir: Option<PathBuf>,
+    /// Persona answering, shown below chat responses
+    persona: Option<String>,
+    /// System prompt sent with every request
+    system_prompt: Option<String>,
+    /// Channel the text of responses is streamed to as it arrives
+    stream: Option<ChunkSender>,
+    /// Stored session, recorded with moderation verdicts
+    session: Option<String>,
+    /// Sampling preset applied, shown below chat responses
+    sampling: Option<String>,
 }
 
 impl Default for AIHandler {
@@ -39,9 +90,238 @@ impl AIHandler {
 
         Self {
             client: Arc::new(Mutex::new(client)),
+            provider: None,
+            task: None,
+            route: None,
+            hold_code_blocks: false,
+            working_dir: None,
+            persona: None,
+            system_prompt: None,
+            stream: None,
+            session: None,
+            sampling: None,
+        }
+    }
+
+    /// Create a handler for a specific provider, regardless of the active one
+    pub fn for_provider(provider: Provider) -> Result<Self, AIError> {
+        let mut ai_config = config::get_config().ai;
+        ai_config.active_provider = provider;
+        let client = AIClientFactory::create_client_from_config(&ai_config)?;
+
+        Ok(Self {
+            client: Arc::new(Mutex::new(client)),
+            provider: Some(provider),
+            task: None,
+            route: None,
+            hold_code_blocks: false,
+            working_dir: None,
+            persona: None,
+            system_prompt: None,
+            stream: None,
+            session: None,
+            sampling: None,
+        })
+    }
+
+    /// Create a handler for a specific provider and model, regardless of the active ones
+    pub fn for_model(provider: Provider, model: &str) -> Result<Self, AIError> {
+        let mut ai_config = config::get_config().ai;
+        ai_config.active_provider = provider;
+        ai_config.select_model(model);
+        let client = AIClientFactory::create_client_from_config(&ai_config)?;
+
+        Ok(Self {
+            client: Arc::new(Mutex::new(client)),
+            provider: Some(provider),
+            task: None,
+            route: None,
+            hold_code_blocks: false,
+            working_dir: None,
+            persona: None,
+            system_prompt: None,
+            stream: None,
+            session: None,
+            sampling: None,
+        })
+    }
+
+    /// Create a handler for the provider and model a prompt was routed to
+    pub fn for_route(route: &Route) -> Result<Self, AIError> {
+        Ok(Self {
+            route: Some(route.clone()),
+            ..Self::for_model(route.provider, &route.model)?
+        })
+    }
+
+    /// Create a handler for a prompt's route, persona, sampling preset and token limit
+    ///
+    /// The persona's provider and model win over the route; without them the
+    /// prompt goes to the routed or active model with the persona's system
+    /// prompt. The persona's temperature wins over the preset of the command.
+    pub fn for_prompt(
+        route: Option<&Route>,
+        persona: Option<(&str, &Persona)>,
+        sampling: Option<(&str, &SamplingPreset)>,
+        max_tokens: Option<usize>,
+    ) -> Result<Self, AIError> {
+        let mut ai_config = config::get_config().ai;
+        let persona_model = persona.is_some_and(|(_, persona)| persona::overrides_model(persona));
+        let route = route.filter(|_| !persona_model);
+        if let Some(route) = route {
+            ai_config.active_provider = route.provider;
+            ai_config.select_model(&route.model);
+        }
+        if let Some((_, persona)) = persona {
+            persona::apply(persona, &mut ai_config);
+        }
+        let persona_temperature = persona.is_some_and(|(_, persona)| persona.temperature.is_some());
+        let sampling = sampling.filter(|_| !persona_temperature);
+        if let Some((_, preset)) = sampling {
+            sampling::apply(preset, &mut ai_config);
+        }
+        if let Some(max_tokens) = max_tokens {
+            ai_config.set_max_tokens(max_tokens);
+        }
+        let client = AIClientFactory::create_client_from_config(&ai_config)?;
+
+        Ok(Self {
+            client: Arc::new(Mutex::new(client)),
+            provider: Some(ai_config.active_provider),
+            task: None,
+            route: route.cloned(),
+            hold_code_blocks: false,
+            working_dir: None,
+            persona: persona.map(|(name, _)| name.to_string()),
+            system_prompt: persona
+                .map(|(_, persona)| persona.system_prompt.clone())
+                .filter(|prompt| !prompt.is_empty()),
+            stream: None,
+            session: None,
+            sampling: sampling.map(|(name, _)| name.to_string()),
+        })
+    }
+
+    /// Report rate limit waits in the entry of the given task
+    pub fn tracking(mut self, task_manager: TaskManager, task_id: TaskId) -> Self {
+        self.task = Some((task_manager, task_id));
+        self
+    }
+
+    /// Leave the code blocks of responses for the user, e.g. when the prompt
+    /// carried instruction-like untrusted content
+    pub fn holding_code_blocks(mut self, hold: bool) -> Self {
+        self.hold_code_blocks = hold;
+        self
+    }
+
+    /// Run the code blocks of responses in `dir`
+    pub fn in_dir(mut self, dir: PathBuf) -> Self {
+        self.working_dir = Some(dir);
+        self
+    }
+
+    /// Send earlier messages of the conversation along with the system prompt
+    pub fn with_history(mut self, history: Option<String>) -> Self {
+        if let Some(history) = history {
+            self.system_prompt = Some(match self.system_prompt.take() {
+                Some(system) => format!("{}\n\n{}", system, history),
+                None => history,
+            });
+        }
+        self
+    }
+
+    /// Send the text of responses to `chunks` while they are generated
+    pub fn streaming(mut self, chunks: ChunkSender) -> Self {
+        self.stream = Some(chunks);
+        self
+    }
+
+    /// Record the stored session the responses belong to
+    pub fn in_session(mut self, session_id: String) -> Self {
+        self.session = Some(session_id);
+        self
+    }
+
+    /// Provider the requests of this handler go to
+    pub fn provider(&self) -> Provider {
+        self.provider
+            .unwrap_or_else(|| config::get_config().ai.active_provider)
+    }
+
+    /// Show or clear the note of the tracked task
+    fn set_task_note(&self, note: Option<String>) {
+        if let Some((task_manager, task_id)) = &self.task {
+            task_manager.set_task_note(*task_id, note);
         }
     }
 
+    /// Wait until the task scheduler lets the tracked task run
+    ///
+    /// Tasks with a higher priority and tasks over the concurrency limit are
+    /// shown in the task entry. Returns an error if the user aborts while waiting.
+    pub async fn wait_for_turn(
+        &self,
+        abort_flag: &Arc<AtomicBool>,
+        global_abort: &Option<Arc<AtomicBool>>,
+    ) -> Result<(), AIError> {
+        let Some((task_manager, task_id)) = &self.task else {
+            return Ok(());
+        };
+        while !task_manager.try_start(*task_id) {
+            let aborted = abort_flag.load(std::sync::atomic::Ordering::SeqCst)
+                || global_abort
+                    .as_ref()
+                    .is_some_and(|flag| flag.load(std::sync::atomic::Ordering::SeqCst))
+                || task_manager
+                    .get_task(*task_id)
+                    .is_none_or(|task| task.status == TaskStatus::Cancelled);
+            if aborted {
+                self.set_task_note(None);
+                return Err(AIError::Cancelled("Operation aborted by user".to_string()));
+            }
+
+            self.set_task_note(Some(format!(
+                "queued — {} task(s) running",
+                task_manager.running_count()
+            )));
+            task_manager.wait_for_slot().await;
+        }
+        self.set_task_note(None);
+        Ok(())
+    }
+
+    /// Wait until the provider's rate limit window has reset
+    ///
+    /// The remaining time is shown in the task entry and counts down every
+    /// second. Returns an error if the user aborts while waiting.
+    pub async fn wait_for_rate_limit(
+        &self,
+        abort_flag: &Arc<AtomicBool>,
+        global_abort: &Option<Arc<AtomicBool>>,
+    ) -> Result<(), AIError> {
+        let provider = self.provider();
+        while let Some(delay) = rate_limit::delay_for(provider) {
+            let aborted = abort_flag.load(std::sync::atomic::Ordering::SeqCst)
+                || global_abort
+                    .as_ref()
+                    .is_some_and(|flag| flag.load(std::sync::atomic::Ordering::SeqCst));
+            if aborted {
+                self.set_task_note(None);
+                return Err(AIError::Cancelled("Operation aborted by user".to_string()));
+            }
+
+            self.set_task_note(Some(format!(
+                "rate limited — resuming in {}s",
+                delay.as_secs_f64().ceil() as u64
+            )));
+            tokio::time::sleep(delay.min(Duration::from_secs(1))).await;
+        }
+        self.set_task_note(None);
+        Ok(())
+    }
+
     /// Update the client based on new configuration
     pub fn update_client(&self) -> Result<(), AIError> {
         match AIClientFactory::create_client() {
@@ -67,15 +347,168 @@ impl AIHandler {
         }
     }
 
+    /// Generate a response and execute the code blocks it contains
     pub async fn generate(
         &self,
         prompt: &str,
         abort_flag: Arc<AtomicBool>,
         global_abort: Option<Arc<AtomicBool>>,
+    ) -> Result<AIResponse, AIError> {
+        let response = self
+            .generate_plain(prompt, abort_flag.clone(), global_abort)
+            .await?;
+
+        // Apply the configured post-processors before anything is shown or run
+        let steps = config::get_config().postprocess;
+        let content = postprocess(&steps, &response.content);
+
+        // Moderate the response before its code blocks can run
+        let moderation_config = config::get_config().moderation;
+        let verdict = self.moderate(&content, &response).await;
+        if let Some(verdict) = &verdict
+            && verdict.withholds(&moderation_config)
+        {
+            return Ok(AIResponse {
+                content: moderation::apply(&content, verdict, &moderation_config),
+                reasoning: None,
+                ..response
+            });
+        }
+
+        // Process code blocks with abort capability
+        let processed_content = self
+            .process_llm_output(&content, abort_flag)
+            .await
+            .map_err(|e| {
+                AIError::InvalidResponse(format!("Failed to process code blocks: {}", e))
+            })?;
+
+        // Record the sampling settings so the answer can be reproduced
+        let ai_config = config::get_config().ai;
+        let processed_content = match ai_config.request_seed() {
+            Some(seed) if ai_config.deterministic => {
+                format!(
+                    "{}\n🎲 seed {}, temperature 0",
+                    processed_content.trim_end(),
+                    seed
+                )
+            }
+            Some(seed) => format!("{}\n🎲 seed {}", processed_content.trim_end(), seed),
+            None => processed_content,
+        };
+        let processed_content = match &verdict {
+            Some(verdict) => moderation::apply(&processed_content, verdict, &moderation_config),
+            None => processed_content,
+        };
+        let processed_content = match &self.route {
+            Some(route) => format!("{}\n🧭 {}", processed_content.trim_end(), route),
+            None => processed_content,
+        };
+        let processed_content = match &self.persona {
+            Some(persona) => format!("{}\n🎭 {}", processed_content.trim_end(), persona),
+            None => processed_content,
+        };
+        let processed_content = match &self.sampling {
+            Some(preset) => format!("{}\n🎛️ {}", processed_content.trim_end(), preset),
+            None => processed_content,
+        };
+
+        // Reasoning goes above the answer, folded until expanded with Ctrl+O
+        let content = match &response.reasoning {
+            Some(reasoning) if !steps.contains(&PostProcessor::StripThinking) => format!(
+                "{}\n\n{}",
+                transcript::format_reasoning(reasoning),
+                processed_content
+            ),
+            _ => processed_content,
+        };
+
+        Ok(AIResponse {
+            content,
+            ..response
+        })
+    }
+
+    /// Classify a response with the configured moderation and record the verdict
+    ///
+    /// Returns `None` when moderation is off.
+    async fn moderate(&self, content: &str, response: &AIResponse) -> Option<moderation::Verdict> {
+        let app_config = config::get_config();
+        let moderation_config = &app_config.moderation;
+        if !moderation_config.enabled {
+            return None;
+        }
+        self.set_task_note(Some("moderating".to_string()));
+        let classified = moderation::classify(content, moderation_config, &app_config.ai).await;
+        self.set_task_note(None);
+        let verdict = moderation::decide(classified, moderation_config);
+
+        let entry = audit::ModerationEntry {
+            kind: "moderation".to_string(),
+            timestamp: chrono::Local::now(),
+            session_id: self.session.clone().unwrap_or_default(),
+            model: format!("{}/{}", self.provider(), response.model),
+            backend: moderation::backend_name(moderation_config.backend).to_string(),
+            verdict: verdict.label().to_string(),
+            categories: verdict.categories().to_vec(),
+            error: match &verdict {
+                moderation::Verdict::Unavailable(e) => Some(e.clone()),
+                _ => None,
+            },
+        };
+        if let Err(e) = audit::append(&audit::default_path(), &entry) {
+            log_error(&format!("Failed to record the moderation verdict: {}", e)).ok();
+        }
+        Some(verdict)
+    }
+
+    /// Generate a response without executing any code blocks
+    ///
+    /// Used when the response is consumed by the application (e.g. file
+    /// edits) rather than shown to the user as a chat answer.
+    pub async fn generate_plain(
+        &self,
+        prompt: &str,
+        abort_flag: Arc<AtomicBool>,
+        global_abort: Option<Arc<AtomicBool>>,
     ) -> Result<AIResponse, AIError> {
         // First, check if Ollama is running
         self.check_service_availability().await?;
 
+        // Requests rejected for exceeding the rate limit are retried once the window resets
+        let mut attempt = 1;
+        loop {
+            self.wait_for_rate_limit(&abort_flag, &global_abort).await?;
+            match self
+                .generate_once(prompt, abort_flag.clone(), global_abort.clone())
+                .await
+            {
+                Err(AIError::RateLimit(_))
+                    if attempt < MAX_RATE_LIMIT_ATTEMPTS
+                        && rate_limit::delay_for(self.provider()).is_some() =>
+                {
+                    attempt += 1;
+                }
+                result => {
+                    let response = separate_reasoning(result?);
+                    if let (Some((task_manager, task_id)), Some(incomplete)) =
+                        (&self.task, continuation::detect(&response))
+                    {
+                        task_manager.set_task_incomplete(*task_id, incomplete);
+                    }
+                    return Ok(response);
+                }
+            }
+        }
+    }
+
+    /// Send a single generation request, racing it against the abort flags
+    async fn generate_once(
+        &self,
+        prompt: &str,
+        abort_flag: Arc<AtomicBool>,
+        global_abort: Option<Arc<AtomicBool>>,
+    ) -> Result<AIResponse, AIError> {
         // If we get here, service is available
         if abort_flag.load(std::sync::atomic::Ordering::SeqCst) {
             return Err(AIError::Cancelled("Operation aborted by user".to_string()));
@@ -84,8 +517,41 @@ impl AIHandler {
         // Get the client and generate
         let client = self.client.lock().await;
 
+        // Stream progress into the tracked task so the tasks popup stays live
+        let on_progress: ProgressCallback = match &self.task {
+            Some((task_manager, task_id)) => {
+                let (task_manager, task_id) = (task_manager.clone(), *task_id);
+                Arc::new(move |stats: &ProgressStats| {
+                    if stats.tokens_generated == 0 && stats.connected_at.is_some() {
+                        task_manager.mark_task_connected(task_id);
+                    }
+                    task_manager.update_task_progress(
+                        task_id,
+                        stats.tokens_generated,
+                        stats.estimated_total_tokens,
+                    );
+                })
+            }
+            None => Arc::new(|_| {}),
+        };
+
         // Set up a future for generation
-        let generation_future = client.generate(prompt, None);
+        let started = chrono::Utc::now();
+        let system_prompt = self.system_prompt.as_deref();
+        let generation_future = async {
+            match &self.stream {
+                Some(chunks) => {
+                    client
+                        .generate_stream(prompt, system_prompt, chunks.clone(), on_progress)
+                        .await
+                }
+                None => {
+                    client
+                        .generate_with_progress(prompt, system_prompt, on_progress)
+                        .await
+                }
+            }
+        };
 
         // Set up a better abort check that uses both the local and global flags
         // and checks more frequently for better responsiveness
@@ -121,43 +587,30 @@ impl AIHandler {
         };
 
         // Process the result
-        match result {
-            Ok(response) => {
-                // Successfully generated response
-                // Check if process_llm_output should be called based on the abort flag
-                if abort_flag.load(std::sync::atomic::Ordering::SeqCst) {
-                    return Err(AIError::Cancelled(
-                        "Operation aborted after generation completed".to_string(),
-                    ));
-                }
-
-                // Process bash blocks with abort capability
-                let processed_content = self
-                    .process_llm_output(&response.content, abort_flag)
-                    .await
-                    .map_err(|e| {
-                        AIError::InvalidResponse(format!("Failed to process bash blocks: {}", e))
-                    })?;
-
-                Ok(AIResponse {
-                    content: processed_content,
-                    ..response
-                })
-            }
-            Err(e) => Err(e),
+        let mut response = result?;
+        if abort_flag.load(std::sync::atomic::Ordering::SeqCst) {
+            return Err(AIError::Cancelled(
+                "Operation aborted after generation completed".to_string(),
+            ));
         }
+
+        // Latency counts from the request, not from the first byte of the answer
+        let progress = response.progress.get_or_insert_with(ProgressStats::new);
+        progress.start_time = started;
+        progress.finished_at.get_or_insert_with(chrono::Utc::now);
+
+        Ok(response)
     }
 
     // Helper method to check if the AI service is available
     async fn check_service_availability(&self) -> Result<(), AIError> {
-        use crate::ai::Provider;
         use crate::config;
         use reqwest::Client;
         use std::time::Duration;
 
-        // Get current provider from config
+        // Get current provider from config unless this handler targets a specific one
         let app_config = config::get_config();
-        let provider = app_config.ai.active_provider;
+        let provider = self.provider.unwrap_or(app_config.ai.active_provider);
 
         // Create a client with a short timeout for just checking availability
         let client = Client::builder()
@@ -197,6 +650,73 @@ impl AIHandler {
                 }
                 Ok(())
             }
+            Provider::OpenRouter => {
+                // For OpenRouter we check for an API key or a device flow login
+                if app_config.ai.openrouter.api_key.is_empty()
+                    && app_config.ai.openrouter.oauth.is_none()
+                {
+                    return Err(AIError::Authentication(
+                        "OpenRouter API key is not set. Please update your configuration."
+                            .to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            Provider::Groq => {
+                // For Groq we check for an API key or a device flow login
+                if app_config.ai.groq.api_key.is_empty() && app_config.ai.groq.oauth.is_none() {
+                    return Err(AIError::Authentication(
+                        "Groq API key is not set. Please update your configuration.".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            Provider::Mistral => {
+                // For Mistral we check for an API key or a device flow login
+                if app_config.ai.mistral.api_key.is_empty() && app_config.ai.mistral.oauth.is_none()
+                {
+                    return Err(AIError::Authentication(
+                        "Mistral API key is not set. Please update your configuration.".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            Provider::AzureOpenAI => {
+                // For Azure we check for a resource and an API key or a login
+                crate::ai::azure::endpoint(&app_config.ai.azure)?;
+                if app_config.ai.azure.api_key.is_empty() && app_config.ai.azure.oauth.is_none() {
+                    return Err(AIError::Authentication(
+                        "Azure OpenAI API key is not set. Please update your configuration."
+                            .to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            Provider::LlamaCpp => {
+                // Runs in-process, so only the build and the model file matter
+                let mut llama_config = app_config.ai.clone();
+                llama_config.active_provider = Provider::LlamaCpp;
+                crate::ai::llamacpp::check_available(
+                    &app_config.ai.llamacpp,
+                    &llama_config.get_active_model_config(),
+                )
+            }
+            Provider::Custom => {
+                // Any answer from the server means it is up; credentials are checked by requests
+                let endpoint = app_config
+                    .ai
+                    .custom
+                    .endpoint
+                    .trim_end_matches('/')
+                    .to_string();
+                match client.get(format!("{}/models", endpoint)).send().await {
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(AIError::NetworkError(format!(
+                        "The custom endpoint {} is not reachable: {}",
+                        endpoint, e
+                    ))),
+                }
+            }
             Provider::LMStudio => {
                 // Check if LM Studio is running
                 let endpoint = app_config.ai.lmstudio.endpoint.clone();
@@ -235,49 +755,67 @@ impl AIHandler {
         output: &str,
         abort_flag: Arc<AtomicBool>,
     ) -> HandlerResult<String> {
-        // Regular expression to match bash code blocks with flexible whitespace
-        let bash_block_re = Regex::new(r"```bash\n([\s\S]*?)\n```").unwrap();
+        if self.hold_code_blocks {
+            return Ok(output.to_string());
+        }
 
-        // Check if there are any bash blocks to process
-        let captures: Vec<_> = bash_block_re.captures_iter(output).collect();
+        // Check if there are any code blocks to process
+        let captures: Vec<_> = CODE_BLOCK.captures_iter(output).collect();
         if captures.is_empty() {
-            // No bash blocks found, return original content
+            // No code blocks found, return original content
             return Ok(output.to_string());
         }
 
+        let dir = self
+            .working_dir
+            .clone()
+            .unwrap_or_else(crate::utils::get_current_dir);
+
+        // Many bash blocks are left for the user to choose from in a popup
+        let queue_threshold = config::get_config().code_execution.queue_threshold;
+        let queue_bash = queue_threshold > 0
+            && crate::handlers::bash::extract_bash_blocks(output).len() >= queue_threshold;
+
         // Store the original text with proper line breaks
         let mut result = String::new();
         let mut last_end = 0;
 
-        // Process each bash block
+        // Process each code block
         for cap in captures {
             // Check if abort was requested using atomic operations
             if abort_flag.load(std::sync::atomic::Ordering::SeqCst) {
                 // Add text until the current point and then terminate early
                 result.push_str(&output[last_end..]);
-                result.push_str("\n\n[Remaining bash commands aborted by user]\n");
+                result.push_str("\n\n[Remaining code blocks aborted by user]\n");
                 return Ok(result);
             }
 
             let full_match = cap.get(0).unwrap();
-            let command = cap.get(1).unwrap();
-            let cmd_str = command.as_str().trim();
+            let language = cap.get(1).unwrap().as_str();
+            let code = cap.get(2).unwrap();
+            let code_str = code.as_str().trim();
+
+            // Skip empty blocks
+            if code_str.is_empty() {
+                continue;
+            }
 
-            // Skip empty commands
-            if cmd_str.is_empty() {
+            // Only bash runs inline; blocks for enabled interpreters are
+            // confirmed after the response and anything else stays untouched
+            if language != "bash" || queue_bash {
                 continue;
             }
 
             // Add text before this match
             result.push_str(&output[last_end..full_match.start()]);
 
-            // Add the original bash block
-            result.push_str("```bash\n");
-            result.push_str(cmd_str);
+            // Add the original code block
+            result.push_str(&format!("```{}\n", language));
+            result.push_str(code_str);
             result.push_str("\n```\n");
 
-            // Execute the command and add its output right after the code block
-            match crate::handlers::bash::handle_bash_command(cmd_str) {
+            // Execute the block and add its output right after it
+            match crate::handlers::bash::handle_bash_command(code_str, &dir) {
                 Ok(cmd_output) => {
                     result.push_str(&cmd_output);
                 }
@@ -0,0 +1,105 @@
+//! Asking the model about selected output
+//!
+//! "Send to AI" (Ctrl+E or the context menu) takes the selected text, the
+//! selected or marked messages or the line under the pointer and opens a one-line
+//! prompt asking what to do with it. The answer and the selection are sent
+//! together as a regular AI prompt, with the selection quoted as `> ` lines.
+
+use super::App;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Question used when the prompt is sent empty
+const DEFAULT_QUESTION: &str = "Explain this";
+
+/// The mini prompt shown over the output while asking about a selection
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectionPrompt {
+    /// Text the question is about
+    pub selection: String,
+    /// What the user typed so far
+    pub question: String,
+}
+
+impl SelectionPrompt {
+    /// Start asking about `selection`
+    pub fn new(selection: impl Into<String>) -> Self {
+        Self {
+            selection: selection.into(),
+            question: String::new(),
+        }
+    }
+
+    /// The prompt sent to the model: the question followed by the quoted selection
+    pub fn compose(&self) -> String {
+        let question = match self.question.trim() {
+            "" => DEFAULT_QUESTION,
+            question => question,
+        };
+        let quoted: Vec<String> = self
+            .selection
+            .lines()
+            .map(|line| format!("> {}", line))
+            .collect();
+        format!("{}\n\n{}", question, quoted.join("\n"))
+    }
+}
+
+impl App {
+    /// The text "Send to AI" acts on: the selection, the selected message or the line at `row`
+    fn text_to_ask_about(&self, row: Option<u16>) -> Option<String> {
+        if self.is_selecting_text {
+            let start = self.selection_start.min(self.selection_end);
+            let end = self.selection_start.max(self.selection_end);
+            return self
+                .output_lines
+                .get(start..=end.min(self.output_lines.len().saturating_sub(1)))
+                .map(|lines| lines.join("\n"));
+        }
+        if self.selected_section.is_some() {
+            return self.target_text();
+        }
+        let line = self.scroll_offset as usize + row? as usize;
+        self.output_lines.get(line).cloned()
+    }
+
+    /// Open the mini prompt for the current selection
+    ///
+    /// `row` is the output row the context menu was opened on, used when
+    /// nothing is selected.
+    pub fn open_selection_prompt(&mut self, row: Option<u16>) {
+        match self.text_to_ask_about(row) {
+            Some(text) if !text.trim().is_empty() => {
+                self.cancel_text_selection();
+                self.clear_message_selection();
+                self.selection_prompt = Some(SelectionPrompt::new(text));
+            }
+            _ => self.add_output(
+                "📋 Select text (Shift+Up/Down) or a message (Alt+Up/Down) to send to the AI"
+                    .to_string(),
+            ),
+        }
+    }
+
+    /// Handle a key while the mini prompt is open
+    ///
+    /// Returns the prompt to run once the question is sent.
+    pub fn handle_selection_prompt_key(&mut self, key: KeyEvent) -> Option<String> {
+        let prompt = self.selection_prompt.as_mut()?;
+        match key.code {
+            KeyCode::Enter => {
+                let composed = prompt.compose();
+                self.selection_prompt = None;
+                return Some(composed);
+            }
+            KeyCode::Esc => self.selection_prompt = None,
+            KeyCode::Backspace => {
+                prompt.question.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                prompt.question.push(c);
+            }
+            _ => {}
+        }
+        None
+    }
+}
@@ -0,0 +1,372 @@
+//! Files and command output attached to prompts
+//!
+//! `/attach` adds content that is sent as context with every following
+//! prompt. Content over the context budget is summarized in a background
+//! task, chunk by chunk, and the merged summary is attached once it is
+//! ready; `/attach --raw` attaches the chunks themselves instead.
+//!
+//! Files named with `@path` in a chat prompt are sent with that prompt only.
+
+use super::App;
+use super::ai_handler::AIHandler;
+use super::tracked::is_cancelled;
+use crate::ai::types::{TaskStatus, TokenUsage};
+use crate::ai::{AIError, pricing};
+use crate::config::get_config;
+use crate::handlers::attach::{self, Attachment};
+use crate::handlers::guardrails;
+use crate::handlers::injection;
+use crate::utils::tasks::TaskType;
+use crate::utils::{TaskId, TaskManager, TaskUsage, count_tokens};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+/// Longest time a single summarizing request may take
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Outcome of summarizing an oversized attachment in the background
+#[derive(Debug)]
+pub struct SummaryResult {
+    /// Task that tracked the summary
+    pub task_id: TaskId,
+    /// Name of the attached file or command
+    pub name: String,
+    /// The merged summary, or why it could not be made
+    pub result: Result<String, AIError>,
+}
+
+/// Requests and token usage of one summary
+struct Summarizer {
+    handler: AIHandler,
+    abort_flag: Arc<AtomicBool>,
+    global_abort: Option<Arc<AtomicBool>>,
+    task_manager: TaskManager,
+    task_id: TaskId,
+    usage: TokenUsage,
+    cost: f64,
+    model: String,
+}
+
+impl Summarizer {
+    /// Send one prompt and add its usage to the total
+    async fn ask(&mut self, prompt: &str, note: String) -> Result<String, AIError> {
+        // Cancelling the task in the task list stops before the next request
+        if is_cancelled(&self.task_manager, self.task_id) {
+            return Err(AIError::Cancelled("Operation aborted by user".to_string()));
+        }
+        self.task_manager.set_task_note(self.task_id, Some(note));
+        let response = tokio::time::timeout(
+            REQUEST_TIMEOUT,
+            self.handler
+                .generate_plain(prompt, self.abort_flag.clone(), self.global_abort.clone()),
+        )
+        .await
+        .map_err(|_| AIError::NetworkError("Summarizing request timed out".to_string()))??;
+
+        self.cost += pricing::response_cost(self.handler.provider(), &response);
+        self.usage.prompt_tokens += response.usage.prompt_tokens;
+        self.usage.completion_tokens += response.usage.completion_tokens;
+        self.usage.total_tokens += response.usage.total_tokens;
+        self.model = response.model;
+        Ok(response.content.trim().to_string())
+    }
+
+    /// Summarize every chunk, then merge the summaries in rounds until one is left
+    async fn summarize(
+        &mut self,
+        name: &str,
+        chunks: Vec<String>,
+        chunk_tokens: usize,
+    ) -> Result<String, AIError> {
+        let total = chunks.len();
+        let mut summaries = Vec::with_capacity(total);
+        for (index, chunk) in chunks.iter().enumerate() {
+            let prompt = attach::chunk_prompt(name, index, total, chunk);
+            let note = format!("part {}/{}", index + 1, total);
+            summaries.push(self.ask(&prompt, note).await?);
+        }
+
+        while summaries.len() > 1 {
+            let mut merged = Vec::new();
+            for group in attach::merge_groups(summaries, chunk_tokens) {
+                if group.len() == 1 {
+                    merged.extend(group);
+                    continue;
+                }
+                let note = format!("merging {} summaries", group.len());
+                merged.push(self.ask(&attach::merge_prompt(name, &group), note).await?);
+            }
+            summaries = merged;
+        }
+        Ok(summaries.pop().unwrap_or_default())
+    }
+}
+
+impl App {
+    /// Handle `/attach [--raw] <file|!command>`, `/attach list` and `/attach clear`
+    pub fn handle_attach(&mut self, args: &str) {
+        match args {
+            "list" => return self.list_attachments(),
+            "clear" => {
+                let count = self.attachments.len();
+                self.attachments.clear();
+                self.add_output(format!("✅ Removed {} attachment(s)", count));
+                return;
+            }
+            _ => {}
+        }
+
+        let loaded = attach::parse_args(args).and_then(|request| {
+            attach::load(&self.current_dir, &request.source).map(|loaded| (request, loaded))
+        });
+        let (request, (name, content)) = match loaded {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                self.add_output(format!("Error: {}", e));
+                return;
+            }
+        };
+        if content.trim().is_empty() {
+            self.add_output(format!("⚠️ {} is empty — nothing attached", name));
+            return;
+        }
+
+        let config = get_config().attachments;
+        let tokens = count_tokens(&content);
+        if tokens <= config.context_budget {
+            self.attachments.push(Attachment::new(&name, content));
+            self.add_output(format!("📎 Attached {} (~{} tokens)", name, tokens));
+            return;
+        }
+
+        let chunks = attach::split_chunks(&content, config.chunk_tokens);
+        if request.raw {
+            let total = chunks.len();
+            for (index, chunk) in chunks.into_iter().enumerate() {
+                self.attachments.push(Attachment::new(
+                    format!("{} (part {}/{})", name, index + 1, total),
+                    chunk,
+                ));
+            }
+            self.add_output(format!(
+                "📎 Attached {} as {} chunks (~{} tokens, above the budget of {})",
+                name, total, tokens, config.context_budget
+            ));
+            return;
+        }
+
+        // Summarizing sends the file, so it has to pass the guardrails first
+        let findings = guardrails::check(
+            &content,
+            &get_config().guardrails,
+            self.ai_handler.provider().is_local(),
+        );
+        if !findings.is_empty() {
+            let found: Vec<String> = findings.iter().map(ToString::to_string).collect();
+            self.add_output(format!(
+                "🚫 Not summarizing {}: the guardrails found {} — /attach --raw {} attaches it to be checked with the prompt",
+                name,
+                found.join("; "),
+                args
+            ));
+            return;
+        }
+
+        self.add_output(format!(
+            "📎 {} has ~{} tokens, above the budget of {} — summarizing {} chunks in the background (Ctrl+T to follow, /attach --raw {} to attach it as is)",
+            name,
+            tokens,
+            config.context_budget,
+            chunks.len(),
+            args
+        ));
+        self.start_summary(name, chunks, config.chunk_tokens);
+    }
+
+    /// Summarize the chunks of an attachment in a tracked background task
+    fn start_summary(&mut self, name: String, chunks: Vec<String>, chunk_tokens: usize) {
+        let task_id = self
+            .task_manager
+            .create_task(format!("Summarize: {}", name), TaskType::AIGeneration);
+        self.summarizing += 1;
+
+        let mut summarizer = Summarizer {
+            handler: self.ai_handler.clone(),
+            abort_flag: self.abort_requested.clone(),
+            global_abort: self.global_abort.clone(),
+            task_manager: self.task_manager.clone(),
+            task_id,
+            usage: TokenUsage {
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                total_tokens: 0,
+            },
+            cost: 0.0,
+            model: String::new(),
+        };
+        let scheduler = self
+            .ai_handler
+            .clone()
+            .tracking(self.task_manager.clone(), task_id);
+        let results = self.summary_tx.clone();
+
+        let job = async move {
+            let result = match scheduler
+                .wait_for_turn(&summarizer.abort_flag, &summarizer.global_abort)
+                .await
+            {
+                Ok(()) => summarizer.summarize(&name, chunks, chunk_tokens).await,
+                Err(e) => Err(e),
+            };
+
+            let task_manager = summarizer.task_manager.clone();
+            task_manager.set_task_note(task_id, None);
+            task_manager.set_task_usage(
+                task_id,
+                TaskUsage {
+                    provider: summarizer.handler.provider(),
+                    model: summarizer.model,
+                    tokens: summarizer.usage,
+                    cost: summarizer.cost,
+                    latency: None,
+                },
+            );
+            SummaryResult {
+                task_id,
+                name,
+                result,
+            }
+        };
+        self.spawn_tracked(
+            task_id,
+            results,
+            job,
+            |summary| match summary.result {
+                Ok(_) => TaskStatus::Completed,
+                Err(AIError::Cancelled(_)) => TaskStatus::Cancelled,
+                Err(_) => TaskStatus::Failed,
+            },
+            // Still delivered, so that the count of running summaries drops
+            |summary| {
+                Some(SummaryResult {
+                    result: Err(AIError::Cancelled("Operation aborted by user".to_string())),
+                    ..summary
+                })
+            },
+        );
+    }
+
+    /// Attach summaries finished in the background
+    pub fn collect_summaries(&mut self) {
+        while let Ok(summary) = self.summary_rx.try_recv() {
+            self.summarizing = self.summarizing.saturating_sub(1);
+            if let Some(usage) = self
+                .task_manager
+                .get_task(summary.task_id)
+                .and_then(|task| task.usage)
+            {
+                self.record_usage(&usage);
+            }
+
+            match summary.result {
+                Ok(text) if !text.is_empty() => {
+                    let attachment = Attachment::new(format!("{} (summary)", summary.name), text);
+                    self.add_output(format!(
+                        "📎 Attached a summary of {} (~{} tokens)",
+                        summary.name,
+                        attachment.tokens()
+                    ));
+                    self.attachments.push(attachment);
+                }
+                Ok(_) => {
+                    self.add_output(format!(
+                        "⚠️ The model returned an empty summary of {}",
+                        summary.name
+                    ));
+                    self.stop_chain("the attachment could not be summarized");
+                }
+                Err(e) => {
+                    self.add_output(format!(
+                        "⚠️ Could not summarize {}: {} — /attach --raw attaches it in chunks instead",
+                        summary.name, e
+                    ));
+                    self.stop_chain("the attachment could not be summarized");
+                }
+            }
+        }
+    }
+
+    /// Show the current attachments
+    fn list_attachments(&mut self) {
+        if self.attachments.is_empty() {
+            self.add_output("📎 No attachments".to_string());
+            return;
+        }
+        let mut listing = String::from("📎 Attached to every prompt:\n");
+        for (idx, attachment) in self.attachments.iter().enumerate() {
+            listing.push_str(&format!(
+                "  {}. {} (~{} tokens)\n",
+                idx + 1,
+                attachment.name,
+                attachment.tokens()
+            ));
+        }
+        listing.push_str("Use /attach clear to remove them");
+        self.add_output(listing);
+    }
+
+    /// Read the files named with `@path` in a chat prompt and say which were attached
+    pub fn load_references(&mut self, prompt: &str) -> Vec<Attachment> {
+        let config = get_config().attachments;
+        let references = attach::load_references(
+            &self.current_dir,
+            prompt,
+            config.reference_budget,
+            config.max_file_bytes,
+        );
+        if references.is_empty() {
+            return Vec::new();
+        }
+        let lines: Vec<String> = references
+            .iter()
+            .map(|reference| format!("  {}", reference.describe()))
+            .collect();
+        self.add_output(format!("📎 Referenced files:\n{}", lines.join("\n")));
+        references
+            .iter()
+            .filter_map(|reference| reference.attachment().cloned())
+            .collect()
+    }
+
+    /// Instruction-like passages in the attachments, the referenced files and pinned output
+    ///
+    /// Always empty while `injection_guard.scan` is off.
+    pub fn scan_untrusted_context(&self, references: &[Attachment]) -> Vec<String> {
+        if !get_config().injection_guard.scan {
+            return Vec::new();
+        }
+        let mut content: Vec<String> = self
+            .attachments
+            .iter()
+            .chain(references)
+            .map(|attachment| attachment.content.clone())
+            .collect();
+        content.extend(
+            self.pinned_messages()
+                .into_iter()
+                .map(|message| message.output),
+        );
+        injection::scan(&content.join("\n"))
+    }
+
+    /// Prepend the attachments and the files referenced in the prompt to it
+    pub fn with_attachments(&self, prompt: String, references: &[Attachment]) -> String {
+        let mut attachments = self.attachments.clone();
+        attachments.extend_from_slice(references);
+        match attach::context(&attachments) {
+            Some(context) => format!("{}\n\n---\n\n{}", context, prompt),
+            None => prompt,
+        }
+    }
+}
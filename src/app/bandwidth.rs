@@ -0,0 +1,53 @@
+//! Low-bandwidth rendering for slow terminals
+
+use super::App;
+use crate::config::{LowBandwidth, update_field};
+use std::time::{Duration, Instant};
+
+impl App {
+    /// Record a drawn frame that started at `started`, announcing automatic low-bandwidth mode
+    ///
+    /// Only the time spent writing to the terminal, the frame without the
+    /// `rendering` of the widgets, counts as slow: a large output renders
+    /// slowly on any terminal.
+    pub fn record_frame(&mut self, started: Instant, rendering: Duration) {
+        let took = started.elapsed();
+        self.frame_times.record(took);
+        if self
+            .frame_pacer
+            .record(Instant::now(), took.saturating_sub(rendering))
+        {
+            self.add_output(
+                "🐢 Slow terminal detected — switched to low-bandwidth mode (/lowbandwidth off to disable)"
+                    .to_string(),
+            );
+        }
+    }
+
+    /// Handle `/lowbandwidth [on|off|auto]`
+    pub fn handle_low_bandwidth(&mut self, args: &str) {
+        let mode = match args {
+            "" if self.frame_pacer.is_low_bandwidth() => LowBandwidth::Off,
+            "" | "on" => LowBandwidth::On,
+            "off" => LowBandwidth::Off,
+            "auto" => LowBandwidth::Auto,
+            _ => {
+                self.add_output("Error: Usage: /lowbandwidth [on|off|auto]".to_string());
+                return;
+            }
+        };
+
+        self.frame_pacer.set_mode(mode);
+        match update_field(|config| config.ui.low_bandwidth = mode) {
+            Ok(()) => self.add_output(format!(
+                "✅ Low-bandwidth mode {}",
+                match mode {
+                    LowBandwidth::On => "on",
+                    LowBandwidth::Off => "off",
+                    LowBandwidth::Auto => "automatic",
+                }
+            )),
+            Err(e) => self.add_output(format!("⚠️ Failed to save configuration: {}", e)),
+        }
+    }
+}
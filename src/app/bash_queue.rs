@@ -0,0 +1,441 @@
+//! Choosing and ordering the bash blocks of a response
+//!
+//! A response with at least `code_execution.queue_threshold` bash blocks does
+//! not run them inline. Instead a popup lists them with checkboxes: Space
+//! checks a block, `a` checks or clears all, Shift+↑/↓ moves the highlighted
+//! block and Enter runs the checked ones in the listed order. Each block is a
+//! tracked task; they run one after another with a pause of
+//! `code_execution.queue_delay_ms` between them. Each block depends on the
+//! one before it, so the rest are skipped once a block fails, and the first
+//! waits for accepted file changes not written yet.
+//!
+//! `e` loads the highlighted block into the input as a `!` command to edit
+//! before running it. Once it ran, the block and the command are added to
+//! the audit log along with the response they came from, and the popup
+//! returns with the remaining blocks.
+//!
+//! Blocks for enabled interpreters (python, node, sql, ...) run the same way
+//! once their confirmation is accepted.
+
+use super::{App, ConfirmAction, Confirmation};
+use crate::ai::types::TaskStatus;
+use crate::config::get_config;
+use crate::handlers::bash;
+use crate::handlers::code::{self, CodeBlock};
+use crate::utils::audit::{self, AuditEntry};
+use crate::utils::log_error;
+use crate::utils::tasks::TaskType;
+use chrono::Local;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// A bash block listed in the popup
+#[derive(Debug, Clone)]
+pub struct QueuedBlock {
+    pub command: String,
+    /// Whether the block runs when the queue is started
+    pub checked: bool,
+}
+
+/// The popup listing the bash blocks of a response
+#[derive(Debug, Clone)]
+pub struct BashQueue {
+    /// Blocks in the order they run
+    pub blocks: Vec<QueuedBlock>,
+    /// Index of the highlighted block
+    pub selected: usize,
+    /// Index of the response among the messages
+    pub response: usize,
+    /// Prompt the response answered
+    pub prompt: String,
+}
+
+impl BashQueue {
+    /// List blocks in the order of the response, all checked
+    pub fn new(commands: Vec<String>) -> Self {
+        Self {
+            blocks: commands
+                .into_iter()
+                .map(|command| QueuedBlock {
+                    command,
+                    checked: true,
+                })
+                .collect(),
+            selected: 0,
+            response: 0,
+            prompt: String::new(),
+        }
+    }
+
+    /// Link the blocks to the response they came from
+    pub fn with_source(mut self, response: usize, prompt: impl Into<String>) -> Self {
+        self.response = response;
+        self.prompt = prompt.into();
+        self
+    }
+
+    /// Check or clear the highlighted block
+    pub fn toggle(&mut self) {
+        if let Some(block) = self.blocks.get_mut(self.selected) {
+            block.checked = !block.checked;
+        }
+    }
+
+    /// Clear all blocks if all are checked, otherwise check all
+    pub fn toggle_all(&mut self) {
+        let checked = !self.blocks.iter().all(|block| block.checked);
+        for block in &mut self.blocks {
+            block.checked = checked;
+        }
+    }
+
+    /// Move the highlighted block one place earlier
+    pub fn move_up(&mut self) {
+        if self.selected > 0 {
+            self.blocks.swap(self.selected, self.selected - 1);
+            self.selected -= 1;
+        }
+    }
+
+    /// Move the highlighted block one place later
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.blocks.len() {
+            self.blocks.swap(self.selected, self.selected + 1);
+            self.selected += 1;
+        }
+    }
+
+    /// Remove the highlighted block from the list
+    pub fn take_selected(&mut self) -> Option<QueuedBlock> {
+        if self.selected >= self.blocks.len() {
+            return None;
+        }
+        let block = self.blocks.remove(self.selected);
+        self.selected = self.selected.min(self.blocks.len().saturating_sub(1));
+        Some(block)
+    }
+
+    /// The checked commands in the order they run
+    pub fn checked(&self) -> Vec<String> {
+        self.blocks
+            .iter()
+            .filter(|block| block.checked)
+            .map(|block| block.command.clone())
+            .collect()
+    }
+
+    /// One line per block: checkbox, position and first line of the command
+    pub fn lines(&self) -> Vec<String> {
+        self.blocks
+            .iter()
+            .enumerate()
+            .map(|(i, block)| {
+                let mut lines = block.command.lines();
+                let first = lines.next().unwrap_or_default();
+                let more = lines.count();
+                format!(
+                    "[{}] {}. {}{}",
+                    if block.checked { "x" } else { " " },
+                    i + 1,
+                    first,
+                    if more > 0 {
+                        format!(" (+{} line(s))", more)
+                    } else {
+                        String::new()
+                    }
+                )
+            })
+            .collect()
+    }
+}
+
+/// A block loaded into the input with `e`, waiting to be run
+#[derive(Debug, Clone)]
+pub(super) struct EditedBlock {
+    original: String,
+    /// The other blocks of the response, shown again afterwards
+    rest: BashQueue,
+}
+
+/// Output of a queued block once it ran
+pub(super) struct QueuedOutput {
+    position: usize,
+    total: usize,
+    block: CodeBlock,
+    /// Formatted output, or why the block did not run
+    result: Result<String, String>,
+}
+
+impl App {
+    /// Offer the bash blocks of a chat response in the popup if there are
+    /// many, or any at all when they were held because of untrusted context
+    pub(super) fn queue_bash_blocks(&mut self, answer: &str, held: bool) {
+        let threshold = get_config().code_execution.queue_threshold;
+        let commands = bash::extract_bash_blocks(answer);
+        let queued = if held {
+            !commands.is_empty()
+        } else {
+            threshold > 0 && commands.len() >= threshold
+        };
+        if !queued {
+            return;
+        }
+        self.add_output(format!(
+            "🧾 {} bash block(s) were not run{} — Space checks, Shift+↑/↓ reorders, Enter runs the checked ones",
+            commands.len(),
+            if held { " because of the untrusted context" } else { "" }
+        ));
+        let messages = self.messages();
+        let prompt = messages
+            .last()
+            .map(|message| message.input.clone())
+            .unwrap_or_default();
+        self.bash_queue =
+            Some(BashQueue::new(commands).with_source(messages.len().saturating_sub(1), prompt));
+    }
+
+    /// Ask before running the blocks of an answer that have an enabled
+    /// interpreter; unlike bash they are not screened at all
+    pub(super) fn offer_code_blocks(&mut self, answer: &str) {
+        let blocks = code::extract_code_blocks(answer);
+        if blocks.is_empty() {
+            return;
+        }
+        let preview = blocks
+            .iter()
+            .map(|block| format!("```{}\n{}\n```", block.language, block.code))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let body = format!(
+            "⚠️ No safety check applies to interpreter code — read it before running it.\n\n{}",
+            preview
+        );
+        self.request_confirmation(Confirmation::new(
+            format!("Run {} code block(s)", blocks.len()),
+            body,
+            ConfirmAction::RunCodeBlocks(blocks),
+        ));
+    }
+
+    /// Handle a key while the bash block popup is open
+    pub fn handle_bash_queue_key(&mut self, key: KeyEvent) {
+        let Some(queue) = self.bash_queue.as_mut() else {
+            return;
+        };
+        let last = queue.blocks.len().saturating_sub(1);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        match key.code {
+            KeyCode::Up if shift => queue.move_up(),
+            KeyCode::Down if shift => queue.move_down(),
+            KeyCode::Up => queue.selected = queue.selected.saturating_sub(1),
+            KeyCode::Down => queue.selected = (queue.selected + 1).min(last),
+            KeyCode::Home => queue.selected = 0,
+            KeyCode::End => queue.selected = last,
+            KeyCode::Char(' ') => queue.toggle(),
+            KeyCode::Char('a') => queue.toggle_all(),
+            KeyCode::Char('e') => self.edit_queued_block(),
+            KeyCode::Enter => {
+                let commands = queue.checked();
+                self.bash_queue = None;
+                self.run_bash_queue(commands);
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.close_bash_queue(),
+            _ => {}
+        }
+        self.needs_redraw = true;
+    }
+
+    /// Load the highlighted block into the input to edit it before it runs
+    fn edit_queued_block(&mut self) {
+        let Some(mut rest) = self.bash_queue.take() else {
+            return;
+        };
+        let Some(block) = rest.take_selected() else {
+            return;
+        };
+        self.input = format!("!{}", block.command);
+        self.cursor_position = self.input.len();
+        self.editing_block = Some(EditedBlock {
+            original: block.command,
+            rest,
+        });
+    }
+
+    /// Record a block that ran after editing and show the remaining blocks
+    ///
+    /// Called with the command that ran; anything other than a `!` command
+    /// sent instead drops the edit.
+    pub(super) fn finish_edited_block(&mut self, command: Option<&str>) {
+        let Some(edited) = self.editing_block.take() else {
+            return;
+        };
+        let Some(command) = command else {
+            return;
+        };
+        let entry = AuditEntry {
+            timestamp: Local::now(),
+            session_id: self.session_id.clone(),
+            response: edited.rest.response,
+            prompt: edited.rest.prompt.clone(),
+            original: edited.original,
+            command: command.to_string(),
+        };
+        if let Err(e) = audit::append(&audit::default_path(), &entry) {
+            log_error(&format!("Failed to update the audit log: {}", e)).ok();
+        }
+        if !edited.rest.blocks.is_empty() {
+            self.bash_queue = Some(edited.rest);
+        }
+    }
+
+    /// Close the popup without running anything
+    pub fn close_bash_queue(&mut self) {
+        self.bash_queue = None;
+        self.add_output("🧾 Bash blocks not run".to_string());
+    }
+
+    /// Run the checked bash blocks
+    fn run_bash_queue(&mut self, commands: Vec<String>) {
+        if commands.is_empty() {
+            self.add_output("🧾 No bash block checked — nothing run".to_string());
+            return;
+        }
+        let blocks = commands
+            .into_iter()
+            .map(|code| CodeBlock {
+                language: "bash".to_string(),
+                code,
+            })
+            .collect();
+        self.run_code_blocks(blocks);
+    }
+
+    /// Run blocks one after another, each as its own task
+    pub(super) fn run_code_blocks(&mut self, blocks: Vec<CodeBlock>) {
+        let total = blocks.len();
+        let kind = if blocks.iter().all(|block| block.language == "bash") {
+            "bash"
+        } else {
+            "code"
+        };
+        let tasks: Vec<_> = blocks
+            .into_iter()
+            .enumerate()
+            .map(|(i, block)| {
+                let first = block.code.lines().next().unwrap_or_default();
+                let language = if block.language == "bash" {
+                    "Bash"
+                } else {
+                    block.language.as_str()
+                };
+                let name = format!(
+                    "{} {}/{}: {}",
+                    language,
+                    i + 1,
+                    total,
+                    first.chars().take(30).collect::<String>()
+                );
+                let task_id = self.task_manager.create_task(name, TaskType::BashCommand);
+                (task_id, block)
+            })
+            .collect();
+        let mut previous = self.waiting_write_tasks();
+        for (task_id, _) in &tasks {
+            for dependency in previous {
+                self.task_manager.add_dependency(*task_id, dependency);
+            }
+            previous = vec![*task_id];
+        }
+        self.stats.bash_count += total;
+        self.add_output(format!("🧾 Running {} {} block(s)…", total, kind));
+
+        let delay = Duration::from_millis(get_config().code_execution.queue_delay_ms);
+        let task_manager = self.task_manager.clone();
+        let outputs = self.bash_queue_tx.clone();
+        let dir = self.current_dir.clone();
+        let abort = self
+            .global_abort
+            .clone()
+            .unwrap_or_else(|| Arc::new(AtomicBool::new(false)));
+
+        let task = tokio::spawn(async move {
+            for (i, (task_id, block)) in tasks.into_iter().enumerate() {
+                if i > 0 {
+                    tokio::time::sleep(delay).await;
+                }
+                // Blocks cancelled from the task list, after an abort or after
+                // a failed block are skipped
+                let mut skipped = None;
+                while !task_manager.try_start(task_id) {
+                    let task = task_manager.get_task(task_id);
+                    if task
+                        .as_ref()
+                        .is_none_or(|task| task.status == TaskStatus::Cancelled)
+                        || abort.load(Ordering::SeqCst)
+                    {
+                        skipped = Some(task.and_then(|task| task.note));
+                        break;
+                    }
+                    task_manager.wait_for_slot().await;
+                }
+                if let Some(note) = skipped {
+                    task_manager.update_task_status(task_id, TaskStatus::Cancelled);
+                    if let Some(note) = note {
+                        let _ = outputs.send(QueuedOutput {
+                            position: i + 1,
+                            total,
+                            block,
+                            result: Err(format!("skipped, {}", note)),
+                        });
+                    }
+                    continue;
+                }
+
+                let run = block.clone();
+                let dir = dir.clone();
+                let result = match tokio::task::spawn_blocking(move || {
+                    if run.language == "bash" {
+                        bash::handle_bash_command(&run.code, &dir)
+                    } else {
+                        code::handle_code_block(&run.language, &run.code, &dir)
+                    }
+                })
+                .await
+                {
+                    Ok(result) => result.map_err(|e| e.to_string()),
+                    Err(e) => Err(e.to_string()),
+                };
+                let status = if result.is_ok() {
+                    TaskStatus::Completed
+                } else {
+                    TaskStatus::Failed
+                };
+                // Deliver the output before the status update wakes the main loop
+                let _ = outputs.send(QueuedOutput {
+                    position: i + 1,
+                    total,
+                    block,
+                    result,
+                });
+                task_manager.update_task_status(task_id, status);
+            }
+        });
+        self.background_tasks.push(task);
+    }
+
+    /// Show the output of queued blocks as they finish
+    pub fn collect_bash_queue(&mut self) {
+        while let Ok(output) = self.bash_queue_rx.try_recv() {
+            let result = match output.result {
+                Ok(result) => result,
+                Err(e) => format!("⚠️ Error: {}\n", e),
+            };
+            self.add_output(format!(
+                "🧾 Block {}/{}:\n```{}\n{}\n```\n{}",
+                output.position, output.total, output.block.language, output.block.code, result
+            ));
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! `!` commands running in the background
+//!
+//! A command typed with `!` runs as a tracked task, so long builds or test
+//! runs do not freeze the interface. Its output is shown line by line as it
+//! is printed, followed by the run time and exit code once it exits. Esc, or
+//! cancelling the task in the task list, kills it. Chained commands wait for
+//! it and are skipped when it fails. A command waits for a free task slot and
+//! for accepted file changes not written yet, e.g. tests typed while a patch
+//! waits to be applied.
+
+use super::App;
+use crate::ai::types::TaskStatus;
+use crate::handlers::bash::{self, Exit};
+use crate::utils::TaskId;
+use crate::utils::tasks::TaskType;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Characters of a command shown in its task name
+const TASK_NAME_CHARS: usize = 30;
+
+/// Something a running `!` command reported
+#[derive(Debug)]
+pub enum BashEvent {
+    /// A line of output
+    Line(String),
+    /// The command exited, was killed or could not start
+    Finished(TaskId, Result<Exit, String>),
+}
+
+impl App {
+    /// Run a `!` command in the background
+    ///
+    /// Returns the task running it.
+    pub fn start_bash(&mut self, command: String) -> TaskId {
+        let first = command.lines().next().unwrap_or_default();
+        let name = format!(
+            "Bash: {}",
+            first.chars().take(TASK_NAME_CHARS).collect::<String>()
+        );
+        let task_id = self.task_manager.create_task(name, TaskType::BashCommand);
+        for write in self.waiting_write_tasks() {
+            self.task_manager.add_dependency(task_id, write);
+        }
+        let abort = Arc::new(AtomicBool::new(false));
+        self.bash_runs.insert(task_id, abort.clone());
+
+        let task_manager = self.task_manager.clone();
+        let events = self.bash_tx.clone();
+        let dir = self.current_dir.clone();
+        let task = tokio::spawn(async move {
+            while !task_manager.try_start(task_id) {
+                let cancelled = task_manager
+                    .get_task(task_id)
+                    .is_none_or(|task| task.status == TaskStatus::Cancelled);
+                if cancelled || abort.load(Ordering::SeqCst) {
+                    let reason = task_manager
+                        .get_task(task_id)
+                        .and_then(|task| task.note)
+                        .unwrap_or_else(|| "cancelled before it started".to_string());
+                    let _ = events.send(BashEvent::Finished(task_id, Err(reason)));
+                    task_manager.update_task_status(task_id, TaskStatus::Cancelled);
+                    return;
+                }
+                task_manager.wait_for_slot().await;
+            }
+            let lines = events.clone();
+            let result = bash::run_streaming(&command, &dir, abort.clone(), |line| {
+                let _ = lines.send(BashEvent::Line(line));
+            })
+            .await
+            .map_err(|e| e.to_string());
+            let status = match &result {
+                Ok(exit) if exit.code == 0 => TaskStatus::Completed,
+                Err(_) if abort.load(Ordering::SeqCst) => TaskStatus::Cancelled,
+                _ => TaskStatus::Failed,
+            };
+            // Deliver the output before the status update wakes the main loop
+            let _ = events.send(BashEvent::Finished(task_id, result));
+            task_manager.update_task_status(task_id, status);
+        });
+        self.background_tasks.push(task);
+        task_id
+    }
+
+    /// Kill every running `!` command
+    pub(super) fn kill_bash_runs(&mut self) {
+        for abort in self.bash_runs.values() {
+            abort.store(true, Ordering::SeqCst);
+        }
+    }
+
+    /// Show the output of running `!` commands and how they ended
+    pub fn collect_bash_output(&mut self) {
+        let mut lines = Vec::new();
+        while let Ok(event) = self.bash_rx.try_recv() {
+            match event {
+                BashEvent::Line(line) => lines.push(line),
+                BashEvent::Finished(task_id, result) => {
+                    self.show_bash_lines(&mut lines);
+                    self.bash_runs.remove(&task_id);
+                    match result {
+                        Ok(exit) => {
+                            self.add_output(exit.summary());
+                            if exit.code != 0 {
+                                self.stop_chain("the previous command failed");
+                            }
+                        }
+                        Err(e) => {
+                            self.add_output(format!("Error: {}", e));
+                            self.stop_chain("the previous command failed");
+                        }
+                    }
+                }
+            }
+        }
+        self.show_bash_lines(&mut lines);
+    }
+
+    /// Add output lines received in one pass at once
+    fn show_bash_lines(&mut self, lines: &mut Vec<String>) {
+        if !lines.is_empty() {
+            self.add_output(lines.join("\n"));
+            lines.clear();
+        }
+    }
+}
@@ -0,0 +1,190 @@
+//! `/config export <path>` and `/config import <path>`: configuration bundles
+//!
+//! With `--keys`, export keeps the API keys in the bundle encrypted with the
+//! passphrase in `AI_CODER_BUNDLE_PASSPHRASE`; importing opens them with the
+//! same passphrase.
+
+use super::App;
+use super::prompts::library_file;
+use super::storage::storage_cipher;
+use crate::config::{get_config, update_config};
+use crate::handlers::safe;
+use crate::utils::bundle;
+use crate::utils::crypto::Cipher;
+use crate::utils::prompts::PromptLibrary;
+use chrono::Local;
+use std::fs;
+use std::io;
+
+/// Environment variable holding the passphrase of bundled keys
+const PASSPHRASE_VAR: &str = "AI_CODER_BUNDLE_PASSPHRASE";
+
+/// Cipher of bundled keys, `None` when no passphrase is set
+fn bundle_cipher() -> Result<Option<Cipher>, String> {
+    match std::env::var(PASSPHRASE_VAR) {
+        Ok(passphrase) if !passphrase.is_empty() => Cipher::new(&passphrase)
+            .map(Some)
+            .map_err(|e| e.to_string()),
+        _ => Ok(None),
+    }
+}
+
+/// The local prompt library
+fn load_prompts() -> io::Result<PromptLibrary> {
+    let cipher = storage_cipher()?;
+    PromptLibrary::load_with(&library_file(), cipher.as_deref())
+}
+
+/// Add the prompts of a bundle to the local library
+fn merge_prompts(prompts: PromptLibrary) -> io::Result<()> {
+    let path = library_file();
+    let cipher = storage_cipher()?;
+    let mut library = PromptLibrary::load_with(&path, cipher.as_deref())?;
+    library.merge(prompts, Local::now());
+    library.save_with(&path, cipher.as_deref())
+}
+
+impl App {
+    /// Handle `/config export <path> [--keys]` and `/config import <path>`
+    pub fn handle_config_bundle(&mut self, args: &str) {
+        const USAGE: &str = "Usage: /config export <path> [--keys] or /config import <path>";
+        let words: Vec<&str> = args.split_whitespace().collect();
+        match words.as_slice() {
+            ["export", path] => self.export_bundle(path, false),
+            ["export", path, "--keys"] | ["export", "--keys", path] => {
+                self.export_bundle(path, true)
+            }
+            ["import", path] => self.import_bundle(path),
+            _ => self.add_output(format!("Error: {}", USAGE)),
+        }
+    }
+
+    fn export_bundle(&mut self, path: &str, with_keys: bool) {
+        if let Err(e) = safe::check("Writing files") {
+            self.add_output(format!("Error: {}", e));
+            return;
+        }
+        let cipher = if with_keys {
+            match bundle_cipher() {
+                Ok(Some(cipher)) => Some(cipher),
+                Ok(None) => {
+                    self.add_output(format!(
+                        "Error: Set {} to export the API keys encrypted",
+                        PASSPHRASE_VAR
+                    ));
+                    return;
+                }
+                Err(e) => {
+                    self.add_output(format!("Error: {}", e));
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+
+        let mut notes = Vec::new();
+        let prompts = match load_prompts() {
+            Ok(prompts) => Some(prompts),
+            Err(e) => {
+                notes.push(format!("⚠️ Left out the prompt library: {}", e));
+                None
+            }
+        };
+        let bundle = match bundle::export(&get_config(), prompts, cipher.as_ref(), Local::now()) {
+            Ok(bundle) => bundle,
+            Err(e) => {
+                self.add_output(format!("Error: {}", e));
+                return;
+            }
+        };
+        let path = self.current_dir.join(path);
+        let written = serde_json::to_string_pretty(&bundle)
+            .map_err(io::Error::other)
+            .and_then(|json| fs::write(&path, json));
+        match written {
+            Ok(()) => {
+                let keys = if bundle.keys.is_some() {
+                    format!("API keys encrypted with {}", PASSPHRASE_VAR)
+                } else {
+                    "without API keys".to_string()
+                };
+                notes.insert(
+                    0,
+                    format!(
+                        "📦 Exported the configuration to {} ({})",
+                        path.display(),
+                        keys
+                    ),
+                );
+                self.add_output(notes.join("\n"));
+            }
+            Err(e) => self.add_output(format!("Error: Cannot write {}: {}", path.display(), e)),
+        }
+    }
+
+    fn import_bundle(&mut self, path: &str) {
+        if let Err(e) = safe::check("Importing") {
+            self.add_output(format!("Error: {}", e));
+            return;
+        }
+        let path = self.current_dir.join(path);
+        let bundle = match fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|data| bundle::parse(&data).map_err(|e| e.to_string()))
+        {
+            Ok(bundle) => bundle,
+            Err(e) => {
+                self.add_output(format!("Error: Cannot read {}: {}", path.display(), e));
+                return;
+            }
+        };
+        let cipher = match bundle.keys.as_ref().map(|_| bundle_cipher()) {
+            Some(Err(e)) => {
+                self.add_output(format!("Error: {}", e));
+                return;
+            }
+            Some(Ok(cipher)) => cipher,
+            None => None,
+        };
+        let applied = match bundle::apply(&bundle, &get_config(), cipher.as_ref()) {
+            Ok(applied) => applied,
+            Err(e) => {
+                self.add_output(format!("Error: {}", e));
+                return;
+            }
+        };
+        if let Err(e) = update_config(applied.config) {
+            self.add_output(format!("Error: Cannot save the configuration: {}", e));
+            return;
+        }
+        let _ = self.ai_handler.update_client();
+        self.apply_task_limit();
+
+        let mut lines = vec![format!(
+            "📦 Imported the configuration from {} (created {})",
+            path.display(),
+            bundle.created.format("%Y-%m-%d %H:%M")
+        )];
+        if applied.imported_keys > 0 {
+            lines.push(format!("  Imported {} API key(s)", applied.imported_keys));
+        }
+        if applied.locked_keys {
+            lines.push(format!(
+                "  Kept the local API keys; set {} to import the bundled ones",
+                PASSPHRASE_VAR
+            ));
+        }
+        if let Some(prompts) = bundle.prompts {
+            let count = prompts.prompts.len();
+            match merge_prompts(prompts) {
+                Ok(()) if count > 0 => {
+                    lines.push(format!("  Merged {} prompt(s) into /prompts", count))
+                }
+                Ok(()) => {}
+                Err(e) => lines.push(format!("⚠️ Skipped the prompt library: {}", e)),
+            }
+        }
+        self.add_output(lines.join("\n"));
+    }
+}
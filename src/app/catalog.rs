@@ -0,0 +1,122 @@
+//! Model catalogs fetched from the provider
+//!
+//! `/list models` shows the configured models of most providers. OpenRouter
+//! offers hundreds, so its catalog is fetched with prices and context sizes
+//! in a tracked background task; `/list models <words>` narrows it down.
+
+use super::App;
+use crate::ai::openrouter::{self, CatalogModel};
+use crate::ai::types::TaskStatus;
+use crate::config::get_config;
+use crate::utils::format_number;
+use crate::utils::tasks::TaskType;
+
+/// Outcome of fetching the OpenRouter catalog in the background
+#[derive(Debug)]
+pub struct CatalogResult {
+    /// Words the listing is narrowed down to
+    pub filter: String,
+    /// The catalog, or why it could not be fetched
+    pub result: Result<Vec<CatalogModel>, String>,
+}
+
+/// Most catalog entries shown at once
+const MAX_LISTED: usize = 60;
+
+/// One line of the catalog listing
+fn catalog_line(model: &CatalogModel, active: &str) -> String {
+    let marker = if model.id == active { " (active)" } else { "" };
+    let context = model
+        .context_length
+        .map(|tokens| format!(", {} context", format_number(tokens as usize)))
+        .unwrap_or_default();
+    let price = match &model.costs {
+        Some(costs) if costs.prompt_cost_per_1k == 0.0 && costs.completion_cost_per_1k == 0.0 => {
+            "free".to_string()
+        }
+        Some(costs) => format!(
+            "${:.4} / ${:.4} per 1K tokens",
+            costs.prompt_cost_per_1k, costs.completion_cost_per_1k
+        ),
+        None => "price varies".to_string(),
+    };
+    format!("* {}{} — {}{}", model.id, marker, price, context)
+}
+
+impl App {
+    /// Handle `/list models [words]` while OpenRouter is the active provider
+    pub fn list_openrouter_models(&mut self, filter: &str) {
+        let task_id = self
+            .task_manager
+            .create_task("OpenRouter catalog".to_string(), TaskType::NetworkRequest);
+        self.task_manager
+            .update_task_status(task_id, TaskStatus::Running);
+        let task_manager = self.task_manager.clone();
+        let results = self.catalog_tx.clone();
+        let endpoint = get_config().ai.openrouter.endpoint;
+        let filter = filter.to_string();
+
+        let task = tokio::spawn(async move {
+            let result = openrouter::fetch_catalog(&endpoint)
+                .await
+                .map_err(|e| e.to_string());
+            let cancelled = task_manager
+                .get_task(task_id)
+                .is_none_or(|task| task.status == TaskStatus::Cancelled);
+            let status = match &result {
+                _ if cancelled => TaskStatus::Cancelled,
+                Ok(_) => TaskStatus::Completed,
+                Err(_) => TaskStatus::Failed,
+            };
+            // A cancelled fetch is not listed
+            if !cancelled {
+                let _ = results.send(CatalogResult { filter, result });
+            }
+            task_manager.update_task_status(task_id, status);
+        });
+        self.background_tasks.push(task);
+        self.add_output("📋 Fetching the OpenRouter catalog…".to_string());
+    }
+
+    /// List the OpenRouter catalogs fetched in the background
+    pub fn collect_catalogs(&mut self) {
+        while let Ok(fetched) = self.catalog_rx.try_recv() {
+            match fetched.result {
+                Ok(catalog) => self.show_catalog(&catalog, &fetched.filter),
+                Err(e) => {
+                    self.add_output(format!("⚠️ Could not fetch the OpenRouter catalog: {}", e))
+                }
+            }
+        }
+    }
+
+    /// List the models of `catalog` matching `filter`
+    fn show_catalog(&mut self, catalog: &[CatalogModel], filter: &str) {
+        let active = get_config().ai.get_active_model_config().name;
+        let matching = openrouter::filter_catalog(catalog, filter);
+        let mut listing = if filter.is_empty() {
+            format!("📋 OpenRouter models ({}):\n", matching.len())
+        } else {
+            format!(
+                "📋 OpenRouter models matching \"{}\" ({} of {}):\n",
+                filter,
+                matching.len(),
+                catalog.len()
+            )
+        };
+        for model in matching.iter().take(MAX_LISTED) {
+            listing.push_str(&catalog_line(model, &active));
+            listing.push('\n');
+        }
+        if matching.len() > MAX_LISTED {
+            listing.push_str(&format!(
+                "… and {} more; narrow the list with /list models <words>\n",
+                matching.len() - MAX_LISTED
+            ));
+        }
+        listing.push_str(
+            "\nPrices are prompt / completion. Use /config model <id> to change the active model.",
+        );
+        self.add_output(listing);
+    }
+}
@@ -0,0 +1,178 @@
+//! Several commands in one input
+//!
+//! A submitted input is split into commands at every line starting with a
+//! known slash command or alias and at `&&` before a command, e.g.
+//! `/config provider openai && explain this error`. Other lines form AI
+//! prompts, so multi-line prompts, pasted code and pasted paths such as
+//! `/usr/bin/ld: cannot find -lxcb` stay intact. The commands run one after
+//! another: each waits until the AI prompts before it have answered, and
+//! the rest of the chain is skipped when a command fails.
+
+use super::App;
+use crate::handlers::CommandMode;
+use crate::handlers::command::COMMANDS;
+use crate::tui::Tui;
+use crate::utils::transcript::Exchange;
+use std::collections::BTreeMap;
+
+/// Whether a line is a built-in slash command or an alias
+fn is_known_command(line: &str, aliases: &BTreeMap<String, String>) -> bool {
+    let Some(command) = line.strip_prefix('/') else {
+        return false;
+    };
+    let name = command
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    COMMANDS.contains(&name.as_str()) || aliases.contains_key(&name)
+}
+
+/// Whether a line starts a command: a known `/name` or `!command`
+fn starts_command(line: &str, aliases: &BTreeMap<String, String>) -> bool {
+    let mut chars = line.chars();
+    match (chars.next(), chars.next()) {
+        (Some('!'), Some(c)) => !c.is_whitespace(),
+        _ => is_known_command(line, aliases),
+    }
+}
+
+/// Split a one-line command at `&&`
+///
+/// `&&` separates commands when the text after it starts a command, or when
+/// the text before it is a slash command. Shell `&&` inside `!a && b` stays
+/// part of the bash command.
+fn split_and(line: &str, aliases: &BTreeMap<String, String>) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut current = String::new();
+    for part in line.split("&&") {
+        let part = part.trim();
+        // `&&` inside a quoted argument is never a separator
+        let quoted = current.matches('"').count() % 2 == 1;
+        if current.is_empty() {
+            current = part.to_string();
+        } else if !quoted && (starts_command(part, aliases) || is_known_command(&current, aliases))
+        {
+            commands.push(std::mem::take(&mut current));
+            current = part.to_string();
+        } else {
+            current = format!("{} && {}", current, part);
+        }
+    }
+    commands.push(current);
+    commands.retain(|command| !command.is_empty());
+    commands
+}
+
+/// Split an input into the commands to run, in order
+///
+/// `aliases` are the user and team aliases, which start a command like the
+/// built-in ones. A `!command` line only runs as bash when it opens the
+/// input or follows another command.
+pub fn split_chain(input: &str, aliases: &BTreeMap<String, String>) -> Vec<String> {
+    let mut blocks: Vec<Vec<&str>> = Vec::new();
+    let mut in_fence = false;
+    for line in input.lines() {
+        // Commands are one line; prompt lines after one start a new prompt
+        let after_command = blocks
+            .last()
+            .and_then(|block| block.first())
+            .is_some_and(|first| starts_command(first, aliases));
+        if (!in_fence && is_known_command(line, aliases)) || after_command || blocks.is_empty() {
+            blocks.push(Vec::new());
+        }
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if let Some(block) = blocks.last_mut() {
+            block.push(line);
+        }
+    }
+
+    let mut commands = Vec::new();
+    for block in blocks {
+        let text = block.join("\n").trim().to_string();
+        if text.is_empty() {
+            continue;
+        }
+        if text.contains('\n') {
+            commands.push(text);
+        } else {
+            commands.extend(split_and(&text, aliases));
+        }
+    }
+    commands
+}
+
+/// Whether the output of a finished command reports a failure
+fn failed(message: &Exchange) -> bool {
+    match message.mode {
+        CommandMode::Bash => message
+            .output
+            .lines()
+            .any(|line| line.starts_with("[⏱") && line.contains('✗')),
+        CommandMode::Command => message
+            .output
+            .lines()
+            .any(|line| line.starts_with("Error:")),
+        CommandMode::AI => false,
+    }
+}
+
+impl App {
+    /// Run a submitted input, which may hold several commands
+    pub async fn submit_input(&mut self, input: String, tui: &mut Tui) {
+        // A message chosen with `t` only applies to the /rewrite it started
+        if !input.starts_with("/rewrite") {
+            self.rewrite_target = None;
+        }
+        let mut commands = split_chain(&input, &super::command_aliases());
+        if commands.len() <= 1 {
+            self.execute_command(commands.pop().unwrap_or(input), tui)
+                .await;
+            return;
+        }
+        self.chain.extend(commands);
+        self.run_chain(tui).await;
+    }
+
+    /// Run queued chain commands until one has to wait for an AI response,
+    /// an attachment summary or a fetched page
+    ///
+    /// Called again from the main loop, so the chain resumes once the
+    /// response has been handled.
+    pub async fn run_chain(&mut self, tui: &mut Tui) {
+        while !self.chain.is_empty()
+            && self.in_flight.is_empty()
+            && self.bash_runs.is_empty()
+            && self.waiting_writes.is_empty()
+            && self.interactive.is_none()
+            && self.summarizing == 0
+            && self.fetching == 0
+            && self.pending_confirmation.is_none()
+        {
+            let Some(command) = self.chain.pop_front() else {
+                return;
+            };
+            self.execute_command(command, tui).await;
+
+            let last = self.messages().pop();
+            if last.as_ref().is_some_and(failed) {
+                self.stop_chain("the previous command failed");
+            }
+        }
+    }
+
+    /// Drop the rest of the chain, saying why
+    pub fn stop_chain(&mut self, reason: &str) {
+        if self.chain.is_empty() {
+            return;
+        }
+        let skipped = self.chain.len();
+        self.chain.clear();
+        self.add_output(format!(
+            "⛔ Stopped: {} — skipped {} chained command(s)",
+            reason, skipped
+        ));
+    }
+}
@@ -0,0 +1,381 @@
+//! Application commands that need access to the app state
+//!
+//! Most slash commands are handled by `CommandHandler`; the ones here start
+//! AI tasks or stage confirmations and are therefore implemented on [`App`].
+
+use super::storage::write_export;
+use super::{AIPurpose, App, ConfirmAction, Confirmation};
+use crate::ai::types::TaskStatus;
+use crate::config::get_config;
+use crate::handlers::doc::{self, DocTarget};
+use crate::handlers::export::{self, ExportMeta};
+use crate::handlers::files::{self, FileBlock};
+use crate::handlers::git;
+use crate::handlers::refactor::{self, RefactorRequest};
+use crate::handlers::safe;
+use crate::handlers::workspace;
+use crate::utils::diff::{diff_lines, diff_stats};
+use crate::utils::tasks::TaskType;
+use crate::utils::transcript::{self, Transcript};
+use chrono::Local;
+use clipboard::{ClipboardContext, ClipboardProvider};
+use std::fs;
+
+impl App {
+    /// Handle `/refactor "<instruction>" <glob>`
+    pub fn start_refactor(&mut self, args: &str) {
+        let prepared = refactor::parse_args(args).and_then(|(instruction, pattern)| {
+            refactor::prepare(&self.current_dir, &instruction, &pattern)
+        });
+
+        match prepared {
+            Ok((request, prompt)) => {
+                let mut summary = format!(
+                    "🔧 Refactoring {} file(s): {}",
+                    request.files.len(),
+                    request
+                        .files
+                        .iter()
+                        .map(|p| p.to_string_lossy().to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                if !request.skipped.is_empty() {
+                    summary.push_str(&format!("\n⚠️ Skipped: {}", request.skipped.join(", ")));
+                }
+                self.add_output(summary);
+                self.send_prompt(prompt, AIPurpose::Refactor(request));
+            }
+            Err(e) => self.add_output(format!("Error: {}", e)),
+        }
+    }
+
+    /// Turn the model's refactoring response into a confirmable patch set
+    pub(super) fn finish_refactor(&mut self, request: RefactorRequest, content: String) {
+        let blocks = files::extract_file_blocks(&content);
+        let (edits, ignored) = refactor::select_edits(&request, blocks);
+        // Nothing outside the workspace is read for the preview or written
+        let (edits, outside): (Vec<_>, Vec<_>) = edits
+            .into_iter()
+            .partition(|block| workspace::check(&self.current_dir, &block.path).is_ok());
+
+        if !ignored.is_empty() {
+            self.add_output(format!(
+                "⚠️ Ignored edits to files outside the request: {}",
+                ignored.join(", ")
+            ));
+        }
+        if !outside.is_empty() {
+            let paths: Vec<String> = outside.iter().map(FileBlock::display_path).collect();
+            self.add_output(format!(
+                "⚠️ Ignored edits to files outside the workspace: {}",
+                paths.join(", ")
+            ));
+        }
+
+        // Drop blocks that would leave the file as it is
+        let (mut added, mut removed) = (0, 0);
+        let edits: Vec<_> = edits
+            .into_iter()
+            .filter(|block| {
+                let current =
+                    fs::read_to_string(self.current_dir.join(&block.path)).unwrap_or_default();
+                let (block_added, block_removed) =
+                    diff_stats(&diff_lines(&current, &block.content));
+                added += block_added;
+                removed += block_removed;
+                block_added + block_removed > 0
+            })
+            .collect();
+
+        if edits.is_empty() {
+            self.add_output("⚠️ The model did not propose any changes:".to_string());
+            self.add_output(content);
+            return;
+        }
+
+        self.add_output(format!(
+            "🔧 {} file(s) changed (+{} -{}) — review the patch and press Enter to apply",
+            edits.len(),
+            added,
+            removed
+        ));
+
+        let preview = edits
+            .iter()
+            .map(|block| block.preview(&self.current_dir))
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.request_confirmation(Confirmation::new(
+            format!("Refactor: {}", request.instruction),
+            preview,
+            ConfirmAction::WriteFiles {
+                label: format!("Refactor: {}", request.instruction),
+                blocks: edits,
+            },
+        ));
+    }
+
+    /// Handle `/undo [n]` and `/undo list`
+    pub fn handle_undo(&mut self, args: &str) {
+        if args == "list" {
+            let points = self.undo.points();
+            if points.is_empty() {
+                self.add_output("📋 Nothing to undo".to_string());
+                return;
+            }
+
+            let mut listing = String::from("📋 Undo history (most recent first):\n");
+            for (idx, point) in points.iter().rev().enumerate() {
+                listing.push_str(&format!(
+                    "  {}. {} {} ({} file{})\n",
+                    idx + 1,
+                    point.timestamp.format("%H:%M:%S"),
+                    point.label,
+                    point.files.len(),
+                    if point.files.len() == 1 { "" } else { "s" }
+                ));
+            }
+            listing.push_str("Use /undo <n> to revert the n most recent changes");
+            self.add_output(listing);
+            return;
+        }
+        if safe::is_enabled() {
+            self.add_output(safe::blocked_message("Undoing file changes"));
+            return;
+        }
+
+        let count = if args.is_empty() {
+            1
+        } else {
+            match args.parse::<usize>() {
+                Ok(n) if n > 0 => n,
+                _ => {
+                    self.add_output("Error: Usage: /undo [n] or /undo list".to_string());
+                    return;
+                }
+            }
+        };
+
+        let task_id = self
+            .task_manager
+            .create_task("Undo file changes".to_string(), TaskType::FileOperation);
+        self.task_manager
+            .update_task_status(task_id, TaskStatus::Running);
+        match self.undo.undo(count) {
+            Ok(reverted) if reverted.is_empty() => {
+                self.task_manager
+                    .update_task_status(task_id, TaskStatus::Cancelled);
+                self.add_output("📋 Nothing to undo".to_string());
+            }
+            Ok(reverted) => {
+                // The tasks that applied the changes now read as reverted
+                for task in reverted.iter().filter_map(|point| point.task) {
+                    self.task_manager
+                        .set_task_note(task, Some("reverted".to_string()));
+                }
+                let labels: Vec<&str> = reverted.iter().map(|point| point.label.as_str()).collect();
+                self.task_manager
+                    .set_task_note(task_id, Some(labels.join(", ")));
+                self.task_manager
+                    .update_task_status(task_id, TaskStatus::Completed);
+                let lines: Vec<String> = labels
+                    .iter()
+                    .map(|label| format!("↩️ Reverted: {}", label))
+                    .collect();
+                self.add_output(lines.join("\n"));
+            }
+            Err(e) => {
+                self.task_manager
+                    .update_task_status(task_id, TaskStatus::Failed);
+                self.add_output(format!("⚠️ Undo failed: {}", e));
+            }
+        }
+    }
+
+    /// Handle `/doc <path|symbol>`
+    pub fn start_doc(&mut self, args: &str) {
+        let prepared = doc::resolve_target(&self.current_dir, args).and_then(|target| {
+            let content = doc::read_source(&self.current_dir.join(&target.path))?;
+            let prompt = doc::build_prompt(&target, &content);
+            Ok((target, prompt))
+        });
+
+        match prepared {
+            Ok((target, prompt)) => {
+                self.add_output(format!("📝 Documenting {}", target.describe()));
+                self.send_prompt(prompt, AIPurpose::Doc(target));
+            }
+            Err(e) => self.add_output(format!("Error: {}", e)),
+        }
+    }
+
+    /// Insert the generated documentation and ask for confirmation
+    pub(super) fn finish_doc(&mut self, target: DocTarget, content: String) {
+        let path = self.current_dir.join(&target.path);
+        let source = match doc::read_source(&path) {
+            Ok(source) => source,
+            Err(e) => {
+                self.add_output(format!("Error: {}", e));
+                return;
+            }
+        };
+
+        let entries = doc::parse_doc_response(&content);
+        let (documented, count) = doc::insert_docs(&source, target.style, &entries, target.line);
+        if count == 0 {
+            self.add_output(format!(
+                "⚠️ No documentation to add to {} (items may already be documented)",
+                target.describe()
+            ));
+            return;
+        }
+
+        let block = FileBlock {
+            path: target.path.clone(),
+            language: None,
+            content: documented,
+        };
+        self.add_output(format!(
+            "📝 Documented {} item(s) in {} — review the diff and press Enter to apply",
+            count,
+            block.display_path()
+        ));
+        self.request_confirmation(Confirmation::new(
+            format!("Doc: {}", target.describe()),
+            block.preview(&self.current_dir),
+            ConfirmAction::WriteFiles {
+                label: format!("Doc: {}", target.describe()),
+                blocks: vec![block],
+            },
+        ));
+    }
+
+    /// Handle `/export html [file]`
+    pub fn handle_export(&mut self, args: &str) {
+        let mut words = args.split_whitespace();
+        if words.next() != Some("html") {
+            self.add_output("Error: Usage: /export html [file]".to_string());
+            return;
+        }
+
+        let exported = Local::now();
+        let path = match words.next() {
+            Some(file) => self.current_dir.join(file),
+            None => self.current_dir.join(format!(
+                "ai-coder-session-{}.html",
+                exported.format("%Y%m%d-%H%M%S")
+            )),
+        };
+
+        let config = get_config();
+        let meta = ExportMeta {
+            started: self.stats.start_time,
+            exported,
+            directory: self.current_dir.display().to_string(),
+            provider: config.ai.active_provider.to_string(),
+            models: self.stats.models_used.clone(),
+            prompt_tokens: self.stats.prompt_tokens,
+            completion_tokens: self.stats.completion_tokens,
+            cost: self.stats.cost,
+            times: self.section_times.clone(),
+        };
+        let html = export::render_html(&Transcript::parse(&self.output), &meta, &config.theme);
+
+        match write_export(&path, html.as_bytes()) {
+            Ok(path) => self.add_output(format!("✅ Exported session to {}", path.display())),
+            Err(e) => self.add_output(format!("⚠️ Failed to write {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Handle `/print [n] [file]`: plain text of the last n exchanges
+    ///
+    /// Without a file the text is written to stdout when the app exits.
+    pub fn handle_print(&mut self, args: &str) {
+        let mut words = args.split_whitespace().peekable();
+        let count = match words.peek().map(|word| word.parse::<usize>()) {
+            Some(Ok(n)) if n > 0 => {
+                words.next();
+                n
+            }
+            Some(Ok(_)) => {
+                self.add_output("Error: Usage: /print [n] [file]".to_string());
+                return;
+            }
+            _ => 1,
+        };
+        let file = words.next();
+
+        // The /print command itself is already in the output; leave it out
+        let parsed = Transcript::parse(&self.output);
+        let exchanges = &parsed.exchanges[..parsed.exchanges.len().saturating_sub(1)];
+        let exchanges = &exchanges[exchanges.len().saturating_sub(count)..];
+        if exchanges.is_empty() {
+            self.add_output("📋 Nothing to print yet".to_string());
+            return;
+        }
+        let text = transcript::to_plain_text(exchanges);
+
+        match file {
+            Some(file) => {
+                let path = self.current_dir.join(file);
+                match write_export(&path, text.as_bytes()) {
+                    Ok(path) => self.add_output(format!(
+                        "✅ Wrote {} exchange(s) to {}",
+                        exchanges.len(),
+                        path.display()
+                    )),
+                    Err(e) => {
+                        self.add_output(format!("⚠️ Failed to write {}: {}", path.display(), e))
+                    }
+                }
+            }
+            None => {
+                self.print_on_exit = Some(text);
+                self.add_output(format!(
+                    "📄 {} exchange(s) will be printed to stdout when you exit",
+                    exchanges.len()
+                ));
+            }
+        }
+    }
+
+    /// Handle `/commitmsg`: generate a commit message for the staged changes
+    pub fn start_commit_message(&mut self) {
+        match git::commit_message_prompt(&self.current_dir) {
+            Ok(prompt) => {
+                self.add_output("📝 Generating commit message for staged changes".to_string());
+                self.send_prompt(prompt, AIPurpose::CommitMessage);
+            }
+            Err(e) => self.add_output(format!("Error: {}", e)),
+        }
+    }
+
+    /// Show the generated commit message, copy it and offer to commit with it
+    pub(super) fn finish_commit_message(&mut self, content: String) {
+        let message = git::clean_commit_message(&content);
+        if message.is_empty() {
+            self.add_output("⚠️ The model returned an empty commit message".to_string());
+            return;
+        }
+
+        self.add_output(message.clone());
+        let copied = ClipboardContext::new()
+            .and_then(|mut ctx| ctx.set_contents(message.clone()))
+            .is_ok();
+        if copied {
+            self.add_output("✅ Commit message copied to clipboard".to_string());
+        } else {
+            self.add_output("⚠️ Failed to copy commit message to clipboard".to_string());
+        }
+
+        self.request_confirmation(Confirmation::new(
+            "Commit with this message?",
+            format!(
+                "{}\n\nEnter runs `git commit -e` so you can finish the message in your editor.",
+                message
+            ),
+            ConfirmAction::GitCommit(message),
+        ));
+    }
+}
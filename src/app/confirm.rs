@@ -0,0 +1,256 @@
+//! Confirmation prompts for actions that modify the project
+//!
+//! Actions such as writing files are staged as a [`Confirmation`] and shown
+//! in a popup with a scrollable preview until the user accepts or cancels.
+//! Accepted file changes run as tasks: when every task slot is taken they
+//! wait, and commands started meanwhile wait for them.
+
+use crossterm::event::{KeyCode, KeyEvent};
+use std::path::PathBuf;
+
+use super::{App, PendingPrompt};
+use crate::ai::types::TaskStatus;
+use crate::handlers::code::CodeBlock;
+use crate::handlers::files::{self, FileBlock};
+use crate::handlers::git;
+use crate::handlers::safe;
+use crate::tui::Tui;
+use crate::utils::TaskId;
+use crate::utils::tasks::TaskType;
+
+/// Action performed when a confirmation is accepted
+#[derive(Debug, Clone)]
+pub enum ConfirmAction {
+    /// Write code blocks to their annotated paths as one atomic change,
+    /// recorded as a single undo point with the given label
+    WriteFiles {
+        label: String,
+        blocks: Vec<FileBlock>,
+    },
+    /// Run code blocks with their interpreters, which no safety check screens
+    RunCodeBlocks(Vec<CodeBlock>),
+    /// Run `git commit -e` with the message so the user can finish it
+    GitCommit(String),
+    /// Send prompts left unfinished by the previous session again
+    ResumePrompts(Vec<PendingPrompt>),
+    /// Send a prompt that is above the cost preview threshold
+    SendPrompt(Box<PendingPrompt>),
+}
+
+/// A pending confirmation with a preview of what will change
+#[derive(Debug, Clone)]
+pub struct Confirmation {
+    /// Popup title
+    pub title: String,
+    /// Preview text, usually a unified diff
+    pub body: String,
+    /// Action to run on confirm
+    pub action: ConfirmAction,
+    /// Scroll offset within the preview
+    pub scroll: u16,
+}
+
+/// An accepted file change waiting for a free task slot
+#[derive(Debug)]
+pub(super) struct WaitingWrite {
+    task_id: TaskId,
+    label: String,
+    blocks: Vec<FileBlock>,
+}
+
+impl Confirmation {
+    /// Create a new confirmation
+    pub fn new(title: impl Into<String>, body: impl Into<String>, action: ConfirmAction) -> Self {
+        Self {
+            title: title.into(),
+            body: body.into(),
+            action,
+            scroll: 0,
+        }
+    }
+}
+
+impl App {
+    /// Show a confirmation popup, replacing any pending one
+    pub fn request_confirmation(&mut self, confirmation: Confirmation) {
+        self.pending_confirmation = Some(confirmation);
+    }
+
+    /// Handle a key press while a confirmation is shown
+    pub fn handle_confirmation_key(&mut self, key: KeyEvent, tui: &mut Tui) {
+        match key.code {
+            KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('Y') => {
+                self.accept_confirmation(tui)
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => self.cancel_confirmation(),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_confirmation(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_confirmation(1),
+            _ => {}
+        }
+    }
+
+    /// Scroll the confirmation preview by the given number of lines
+    pub fn scroll_confirmation(&mut self, delta: i32) {
+        if let Some(confirmation) = &mut self.pending_confirmation {
+            let max = confirmation.body.lines().count().saturating_sub(1) as i32;
+            confirmation.scroll = (confirmation.scroll as i32 + delta).clamp(0, max) as u16;
+        }
+    }
+
+    /// Dismiss the pending confirmation without running its action
+    pub fn cancel_confirmation(&mut self) {
+        if let Some(confirmation) = self.pending_confirmation.take() {
+            if let ConfirmAction::ResumePrompts(_) = confirmation.action {
+                // Rewrite the pending file so the discarded prompts are not offered again
+                self.persist_pending();
+            }
+            self.add_output("⚠️ Cancelled".to_string());
+            self.stop_chain("cancelled");
+        }
+    }
+
+    /// Run the action of the pending confirmation
+    pub fn accept_confirmation(&mut self, tui: &mut Tui) {
+        let Some(confirmation) = self.pending_confirmation.take() else {
+            return;
+        };
+
+        match confirmation.action {
+            ConfirmAction::WriteFiles { .. } if safe::is_enabled() => {
+                self.add_output(safe::blocked_message("Writing files"))
+            }
+            ConfirmAction::RunCodeBlocks(_) if safe::is_enabled() => {
+                self.add_output(safe::blocked_message("Running code blocks"))
+            }
+            ConfirmAction::GitCommit(_) if safe::is_enabled() => {
+                self.add_output(safe::blocked_message("Committing"))
+            }
+            ConfirmAction::WriteFiles { label, blocks } => {
+                let task_id = self.queue_file_write(label, blocks);
+                let waiting = self
+                    .task_manager
+                    .get_task(task_id)
+                    .is_some_and(|task| task.status == TaskStatus::Pending);
+                if waiting {
+                    self.add_output(
+                        "⏳ Files will be written once a task slot is free".to_string(),
+                    );
+                }
+            }
+            ConfirmAction::RunCodeBlocks(blocks) => self.run_code_blocks(blocks),
+            ConfirmAction::GitCommit(message) => {
+                // The editor needs the terminal while git waits for it
+                if let Err(e) = tui.suspend() {
+                    self.add_output(format!("⚠️ Could not release the terminal: {}", e));
+                    return;
+                }
+                let result = git::commit_with_editor(&self.current_dir, &message);
+                if let Err(e) = tui.resume() {
+                    self.add_output(format!("⚠️ Could not restore the terminal: {}", e));
+                }
+
+                match result {
+                    Ok(status) if status.success() => {
+                        self.add_output("✅ Changes committed".to_string())
+                    }
+                    Ok(_) => self.add_output("⚠️ git commit was aborted".to_string()),
+                    Err(e) => self.add_output(format!("⚠️ {}", e)),
+                }
+            }
+            ConfirmAction::ResumePrompts(prompts) => self.resume_prompts(prompts),
+            ConfirmAction::SendPrompt(pending) => self.start_prompt(*pending),
+        }
+    }
+
+    /// Write an accepted file change as a task, now or once a slot is free
+    ///
+    /// Changes are written in the order they were accepted. Returns the
+    /// task, so commands can be made to wait for it.
+    pub fn queue_file_write(&mut self, label: String, blocks: Vec<FileBlock>) -> TaskId {
+        // Every applied change set shows up in the tasks popup
+        let task_id = self
+            .task_manager
+            .create_task(label.clone(), TaskType::FileOperation);
+        self.waiting_writes.push_back(WaitingWrite {
+            task_id,
+            label,
+            blocks,
+        });
+        self.start_waiting_writes();
+        task_id
+    }
+
+    /// Write the waiting file changes whose turn has come
+    pub fn start_waiting_writes(&mut self) {
+        while let Some(write) = self.waiting_writes.front() {
+            let cancelled = self
+                .task_manager
+                .get_task(write.task_id)
+                .is_none_or(|task| task.status == TaskStatus::Cancelled);
+            if !cancelled && !self.task_manager.try_start(write.task_id) {
+                return;
+            }
+            let Some(write) = self.waiting_writes.pop_front() else {
+                return;
+            };
+            if cancelled {
+                self.add_output(format!("⚠️ Cancelled: {}", write.label));
+            } else {
+                self.write_files(write);
+            }
+        }
+    }
+
+    /// Ids of the accepted file changes not written yet
+    pub(super) fn waiting_write_tasks(&self) -> Vec<TaskId> {
+        self.waiting_writes
+            .iter()
+            .map(|write| write.task_id)
+            .collect()
+    }
+
+    /// Write a file change whose task is running
+    fn write_files(&mut self, write: WaitingWrite) {
+        let WaitingWrite {
+            task_id,
+            label,
+            blocks,
+        } = write;
+        // Snapshot the originals first so the whole change can be undone
+        let paths: Vec<PathBuf> = blocks
+            .iter()
+            .map(|block| self.current_dir.join(&block.path))
+            .collect();
+        let point = match self.undo.record(&label, &paths) {
+            Ok(id) => id,
+            Err(e) => {
+                self.task_manager
+                    .update_task_status(task_id, TaskStatus::Failed);
+                self.add_output(format!(
+                    "⚠️ Could not save undo snapshot: {}. No files were changed.",
+                    e
+                ));
+                return;
+            }
+        };
+
+        match files::write_all(&self.current_dir, &blocks) {
+            Ok(messages) => {
+                self.undo.attach_task(task_id);
+                self.task_manager
+                    .set_task_note(task_id, Some(format!("undo point {}", point)));
+                self.task_manager
+                    .update_task_status(task_id, TaskStatus::Completed);
+                self.add_output(messages.join("\n"));
+                self.add_output("↩️ Revert with /undo".to_string());
+            }
+            Err(e) => {
+                self.undo.discard_last();
+                self.task_manager
+                    .update_task_status(task_id, TaskStatus::Failed);
+                self.add_output(format!("⚠️ {}", e));
+            }
+        }
+        self.file_blocks.clear();
+    }
+}
@@ -0,0 +1,135 @@
+//! Offline handling for AI requests
+//!
+//! While the network is down, prompts for cloud providers are sent to a
+//! local provider when possible and queued otherwise. Queued prompts are
+//! dispatched once connectivity returns. Providers in their configured
+//! quiet hours are passed over the same way.
+
+use super::ai_handler::AIHandler;
+use super::{App, PendingPrompt};
+use crate::ai::{Provider, quiet_hours};
+use crate::config::get_config;
+use crate::utils::network::{endpoint_address, offline_candidates};
+use std::time::Duration;
+
+impl App {
+    /// Start probing the network in the background
+    pub fn start_connectivity_monitor(&mut self) {
+        let (network, ai) = (get_config().network, get_config().ai);
+        let local = [
+            (Provider::Ollama, ai.ollama.endpoint),
+            (Provider::LMStudio, ai.lmstudio.endpoint),
+        ]
+        .into_iter()
+        .filter_map(|(provider, endpoint)| Some((provider, endpoint_address(&endpoint)?)))
+        .collect();
+        let handle = self.connectivity.spawn_monitor(
+            network.probe_host,
+            local,
+            Duration::from_secs(network.probe_interval_secs.max(1)),
+            Duration::from_millis(network.probe_timeout_ms),
+            self.ui_notifier.clone(),
+        );
+        self.background_tasks.push(handle);
+    }
+
+    /// Handler for a local provider to use while offline, if enabled and
+    /// its server answered the last probe
+    pub(super) fn local_fallback_handler(&self) -> Option<(Provider, AIHandler)> {
+        let (ai, now) = (get_config().ai, quiet_hours::now());
+        let reachable = self.connectivity.reachable_local();
+        offline_candidates(get_config().network.prefer_local_when_offline, &reachable)
+            .into_iter()
+            .filter(|&provider| quiet_hours::provider_quiet_until(&ai, provider, now).is_none())
+            .find_map(|provider| {
+                AIHandler::for_provider(provider)
+                    .ok()
+                    .map(|handler| (provider, handler))
+            })
+    }
+
+    /// The handler to send a prompt with instead of `handler` while its
+    /// provider is in quiet hours: the active provider or a local one
+    pub(super) fn avoid_quiet_hours(&mut self, handler: AIHandler) -> AIHandler {
+        let (ai, now) = (get_config().ai, quiet_hours::now());
+        let quiet = handler.provider();
+        let Some(until) = quiet_hours::provider_quiet_until(&ai, quiet, now) else {
+            return handler;
+        };
+        let until = quiet_hours::format_until(until, now);
+        let fallback = [
+            ai.active_provider,
+            Provider::Ollama,
+            Provider::LMStudio,
+            Provider::LlamaCpp,
+        ]
+        .into_iter()
+        .filter(|&provider| {
+            provider != quiet && quiet_hours::provider_quiet_until(&ai, provider, now).is_none()
+        })
+        .find_map(|provider| {
+            AIHandler::for_provider(provider)
+                .ok()
+                .map(|handler| (provider, handler))
+        });
+        match fallback {
+            Some((provider, other)) => {
+                self.add_output(format!(
+                    "🌙 {} is in quiet hours until {} — using {}",
+                    quiet, until, provider
+                ));
+                other
+            }
+            None => {
+                self.add_output(format!(
+                    "🌙 {} is in quiet hours until {} and no other provider is available — sending anyway",
+                    quiet, until
+                ));
+                handler
+            }
+        }
+    }
+
+    /// Warn about quiet hours that do not parse and are therefore ignored
+    pub fn check_quiet_hours(&mut self) {
+        let ai = get_config().ai;
+        for provider in Provider::ALL {
+            for error in quiet_hours::invalid(ai.quiet_hours(provider)) {
+                self.add_output(format!(
+                    "⚠️ Ignoring quiet hours of {}: {}",
+                    provider, error
+                ));
+            }
+        }
+    }
+
+    /// Queue a prompt until the network is back
+    pub(super) fn queue_offline_prompt(&mut self, pending: PendingPrompt) {
+        self.offline_queue.push_back(pending);
+        self.persist_pending();
+        self.add_output(format!(
+            "📥 Offline — prompt queued ({} pending) and will be sent when the connection returns",
+            self.offline_queue.len()
+        ));
+    }
+
+    /// React to connectivity changes, sending queued prompts once back online
+    pub fn check_connectivity(&mut self) {
+        let online = self.connectivity.is_online();
+        if online == self.was_online {
+            return;
+        }
+        self.was_online = online;
+
+        if !online || self.offline_queue.is_empty() {
+            return;
+        }
+        self.add_output(format!(
+            "🌐 Back online — sending {} queued prompt(s)",
+            self.offline_queue.len()
+        ));
+        while let Some(pending) = self.offline_queue.pop_front() {
+            self.start_prompt(pending);
+        }
+    }
+}
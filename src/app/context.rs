@@ -0,0 +1,277 @@
+//! The conversation sent with chat prompts and `/context`
+//!
+//! Answered chat prompts are remembered in [`App::conversation`]; the lines
+//! the app adds below an answer (estimate, seed, route and persona) are left
+//! out. Opening a stored session rebuilds the conversation from its output.
+//!
+//! `/context` breaks the next chat prompt down into its parts — the system
+//! prompt of the active persona, pinned messages, attachments and the earlier
+//! exchanges within the budget — with their tokens as bars. `/context drop`
+//! leaves parts out before the prompt is sent.
+
+use super::App;
+use crate::ai::conversation::{MessageId, Role, exchange_tokens};
+use crate::config::{Truncation, get_config};
+use crate::handlers::CommandMode;
+use crate::utils::transcript::strip_reasoning;
+use crate::utils::{bar, count_tokens, format_number, truncate_string};
+
+/// Width of the token bars
+const BAR_WIDTH: usize = 20;
+
+/// Width of the part labels
+const LABEL_WIDTH: usize = 36;
+
+/// Lines the app adds around answers, which are not part of them
+const APP_LINE_PREFIXES: [&str; 4] = ["🧮 ", "🎲 ", "🧭 ", "🎭 "];
+
+/// The answer as the model gave it, without reasoning and the lines of the app
+fn answer_text(output: &str) -> String {
+    strip_reasoning(output)
+        .lines()
+        .filter(|line| {
+            !APP_LINE_PREFIXES
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Where a part of the next prompt comes from
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ContextSource {
+    /// System prompt of the active persona
+    SystemPrompt,
+    /// Pinned message, by message id
+    Pinned(MessageId),
+    /// Attachment, by index
+    Attachment(usize),
+    /// Earlier exchange of the conversation, by index
+    Exchange(usize),
+}
+
+/// A part of the next chat prompt
+struct ContextPart {
+    source: ContextSource,
+    label: String,
+    tokens: usize,
+}
+
+impl App {
+    /// The parts of the next chat prompt, in the order they are sent
+    fn context_parts(&self) -> Vec<ContextPart> {
+        let config = get_config();
+        let mut parts = Vec::new();
+        if let Some(name) = &self.active_persona
+            && let Some(persona) = config.personas.get(name)
+            && !persona.system_prompt.is_empty()
+        {
+            parts.push(ContextPart {
+                source: ContextSource::SystemPrompt,
+                label: format!("System prompt of {}", name),
+                tokens: count_tokens(&persona.system_prompt),
+            });
+        }
+        for (idx, message) in self.messages().iter().enumerate() {
+            let flags = self.message_flags(idx);
+            if let Some(&id) = self.message_ids.get(idx)
+                && flags.pinned
+                && !flags.excluded
+            {
+                parts.push(ContextPart {
+                    source: ContextSource::Pinned(id),
+                    label: format!("📌 {}", message.display_input()),
+                    tokens: count_tokens(&message.input) + count_tokens(&message.output),
+                });
+            }
+        }
+        for (idx, attachment) in self.attachments.iter().enumerate() {
+            parts.push(ContextPart {
+                source: ContextSource::Attachment(idx),
+                label: format!("📎 {}", attachment.name),
+                tokens: attachment.tokens(),
+            });
+        }
+        let exchanges = self.conversation.exchanges();
+        for (idx, (exchange, sent)) in exchanges
+            .iter()
+            .zip(self.conversation.sent_exchanges(&config.context))
+            .enumerate()
+        {
+            if !sent {
+                continue;
+            }
+            let prompt = exchange
+                .iter()
+                .find(|message| message.role == Role::User)
+                .map(|message| message.content.lines().next().unwrap_or_default())
+                .unwrap_or_default();
+            parts.push(ContextPart {
+                source: ContextSource::Exchange(idx),
+                label: format!("💬 {}", prompt),
+                tokens: exchange_tokens(exchange),
+            });
+        }
+        parts
+    }
+
+    /// Show the parts of the next chat prompt with their tokens
+    fn show_context(&mut self) {
+        let config = get_config().context;
+        let parts = self.context_parts();
+        let total: usize = parts.iter().map(|part| part.tokens).sum();
+        let largest = parts.iter().map(|part| part.tokens).max().unwrap_or(0);
+        let mut lines = vec![format!(
+            "💬 The next chat prompt carries ~{} tokens of context",
+            format_number(total)
+        )];
+        for (idx, part) in parts.iter().enumerate() {
+            lines.push(format!(
+                "  {:>2}. {:<label$}  {}  {:>7}",
+                idx + 1,
+                truncate_string(&part.label, LABEL_WIDTH),
+                bar(part.tokens, largest, BAR_WIDTH),
+                format_number(part.tokens),
+                label = LABEL_WIDTH
+            ));
+        }
+        if parts.is_empty() {
+            lines.push("  Nothing besides the prompt itself".to_string());
+        }
+
+        if !config.enabled {
+            lines.push(
+                "Conversation context is off (context.enabled) — earlier exchanges are not sent"
+                    .to_string(),
+            );
+        } else {
+            let left_out = self
+                .conversation
+                .sent_exchanges(&config)
+                .iter()
+                .filter(|sent| !**sent)
+                .count();
+            if left_out > 0 {
+                let truncation = match config.truncation {
+                    Truncation::DropOldest => "drop_oldest",
+                    Truncation::KeepFirst => "keep_first",
+                };
+                lines.push(format!(
+                    "{} earlier exchanges do not fit within {} tokens ({}) and are left out",
+                    left_out, config.max_tokens, truncation
+                ));
+            }
+        }
+        if !parts.is_empty() {
+            lines.push("Leave parts out with /context drop <n> [n…]".to_string());
+        }
+        self.add_output(lines.join("\n"));
+    }
+
+    /// Leave the numbered parts out of the next prompts
+    fn drop_context(&mut self, args: &str) {
+        let parts = self.context_parts();
+        let mut chosen = Vec::new();
+        for arg in args.split_whitespace() {
+            match arg
+                .parse::<usize>()
+                .ok()
+                .and_then(|number| number.checked_sub(1))
+                .and_then(|idx| parts.get(idx))
+            {
+                Some(part) => {
+                    if !chosen
+                        .iter()
+                        .any(|known: &&ContextPart| known.source == part.source)
+                    {
+                        chosen.push(part);
+                    }
+                }
+                None => {
+                    self.add_output(format!(
+                        "Error: No context part {} (1-{})",
+                        arg,
+                        parts.len()
+                    ));
+                    return;
+                }
+            }
+        }
+        if chosen.is_empty() {
+            self.add_output("Error: Usage: /context drop <n> [n…]".to_string());
+            return;
+        }
+
+        // Attachments and exchanges shift when one is removed, so the later
+        // ones go first
+        chosen.sort_by_key(|part| match part.source {
+            ContextSource::Attachment(idx) | ContextSource::Exchange(idx) => std::cmp::Reverse(idx),
+            _ => std::cmp::Reverse(0),
+        });
+        for part in &chosen {
+            match part.source {
+                ContextSource::SystemPrompt => self.active_persona = None,
+                ContextSource::Pinned(id) => {
+                    self.message_flags.entry(id).or_default().pinned = false;
+                }
+                ContextSource::Attachment(idx) => {
+                    self.attachments.remove(idx);
+                }
+                ContextSource::Exchange(idx) => {
+                    self.conversation.remove_exchange(idx);
+                }
+            }
+        }
+        let tokens: usize = chosen.iter().map(|part| part.tokens).sum();
+        let labels: Vec<&str> = chosen.iter().map(|part| part.label.as_str()).collect();
+        self.add_output(format!(
+            "💬 Left out ~{} tokens: {}",
+            format_number(tokens),
+            labels.join(", ")
+        ));
+    }
+
+    /// Remember an answered chat message typed in output message `source`
+    pub(super) fn remember_exchange(
+        &mut self,
+        source: Option<MessageId>,
+        message: &str,
+        response: &str,
+    ) {
+        self.conversation
+            .record_from(source, message, &answer_text(response));
+    }
+
+    /// Rebuild the conversation from the chat exchanges in the output
+    pub(super) fn restore_conversation(&mut self) {
+        self.conversation.clear();
+        for (idx, exchange) in self.messages().into_iter().enumerate() {
+            if exchange.mode == CommandMode::AI {
+                self.conversation.record_from(
+                    self.message_ids.get(idx).copied(),
+                    &exchange.input,
+                    &answer_text(&exchange.output),
+                );
+            }
+        }
+    }
+
+    /// Handle `/context [clear|drop <n> [n…]]`
+    pub fn handle_context(&mut self, args: &str) {
+        match args {
+            "" => self.show_context(),
+            "drop" => self.drop_context(""),
+            _ if args.starts_with("drop ") => self.drop_context(&args["drop ".len()..]),
+            "clear" => {
+                let count = self.conversation.len();
+                self.conversation.clear();
+                self.add_output(format!(
+                    "💬 Forgot {} messages — the next prompt starts a new conversation",
+                    count
+                ));
+            }
+            _ => self.add_output("Usage: /context [clear|drop <n> [n…]]".to_string()),
+        }
+    }
+}
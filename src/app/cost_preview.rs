@@ -0,0 +1,202 @@
+//! Token and cost preview for outgoing prompts
+//!
+//! Prompts typed by the user or built by commands go through
+//! [`App::send_prompt`], which runs the guardrails on them, shows an
+//! estimate of their size and cost and asks for confirmation when a
+//! guardrail warns or the estimate exceeds the configured thresholds.
+//! Prompts restored from the previous session are checked again before
+//! they are resumed, since the guardrails may have been tightened since.
+
+use super::{AIPurpose, App, ConfirmAction, Confirmation, PendingPrompt};
+use crate::ai::pricing::PromptEstimate;
+use crate::ai::routing::Route;
+use crate::ai::types::Provider;
+use crate::ai::{persona, routing};
+use crate::config::{AppConfig, GuardrailAction, get_config};
+use crate::handlers::guardrails::{self, Finding};
+
+/// Lines of the prompt shown in the confirmation popup
+const PREVIEW_LINES: usize = 40;
+
+impl App {
+    /// Show the prompt estimate, then send the prompt or ask to confirm it
+    pub fn send_prompt(&mut self, prompt: String, purpose: AIPurpose) {
+        self.send_prompt_from(prompt, purpose, None);
+    }
+
+    /// Send a prompt built from `template`, whose sampling preset it gets
+    pub fn send_prompt_from(
+        &mut self,
+        prompt: String,
+        purpose: AIPurpose,
+        template: Option<String>,
+    ) {
+        let config = get_config();
+
+        // Commands build prompts with a fixed response format; only chat gets
+        // routed, answered by personas, continues the conversation and gets
+        // pinned context, attachments and `@path` files
+        let (route, prompt, untrusted, persona, chat) = match purpose {
+            AIPurpose::Chat => {
+                let (chosen, text) = persona::parse_prefix(&prompt, &config.personas);
+                let persona = chosen.or_else(|| self.active_persona.clone());
+                let references = self.load_references(text);
+                let (route, text) = routing::route(
+                    text,
+                    !self.attachments.is_empty() || !references.is_empty(),
+                    &config.routing,
+                    &config.ai,
+                );
+                let text = text.to_string();
+                let history = self.conversation.context(&config.context);
+                let findings = self.scan_untrusted_context(&references);
+                if !findings.is_empty() {
+                    self.add_output(format!(
+                        "🛡️ Attached, referenced or pinned content reads like instructions to the model (\"{}\") — code blocks of the response will not run automatically",
+                        findings.join("\", \"")
+                    ));
+                }
+                let prompt =
+                    self.with_attachments(self.with_pinned_context(text.clone()), &references);
+                (
+                    route,
+                    prompt,
+                    !findings.is_empty(),
+                    persona,
+                    Some((history, text)),
+                )
+            }
+            _ => (None, prompt, false, None, None),
+        };
+        let sent = match &chat {
+            Some((Some(history), _)) => format!("{}\n\n{}", history, prompt),
+            _ => prompt.clone(),
+        };
+
+        let preview = &config.cost_preview;
+        let (provider, model) = prompt_target(&config, persona.as_deref(), route.as_ref());
+
+        let findings = guardrails::check(&prompt, &config.guardrails, provider.is_local());
+        if guardrails::is_blocked(&findings) {
+            self.add_output(format!(
+                "🚫 Prompt not sent — the guardrails do not allow it to leave this machine:\n{}",
+                finding_lines(&findings)
+            ));
+            return;
+        }
+
+        let estimate = PromptEstimate::new(provider, &model, &sent);
+        let mut pending = PendingPrompt::new(prompt.clone(), purpose.clone())
+            .with_route(route)
+            .with_untrusted(untrusted)
+            .with_persona(persona)
+            .with_template(template);
+        if let Some((history, message)) = chat {
+            pending = pending.with_history(history, &message, self.message_ids.last().copied());
+        }
+
+        if preview.show_estimate {
+            self.add_output(format!("🧮 {}", estimate));
+        }
+
+        let too_many_tokens =
+            preview.confirm_above_tokens > 0 && estimate.tokens > preview.confirm_above_tokens;
+        let too_expensive =
+            preview.confirm_above_cost > 0.0 && estimate.cost > preview.confirm_above_cost;
+        if !too_many_tokens && !too_expensive && findings.is_empty() {
+            self.start_prompt(pending);
+            return;
+        }
+
+        let mut body = String::new();
+        if !findings.is_empty() {
+            body.push_str(&format!(
+                "The guardrails found possibly sensitive content:\n{}\n\n",
+                finding_lines(&findings)
+            ));
+        }
+        if too_many_tokens || too_expensive {
+            body.push_str(&format!(
+                "{}\n\nThis is above the confirmation threshold of {} tokens or ${:.2}.\n\n",
+                estimate, preview.confirm_above_tokens, preview.confirm_above_cost
+            ));
+        }
+        let lines: Vec<&str> = prompt.lines().collect();
+        body.push_str(&lines[..lines.len().min(PREVIEW_LINES)].join("\n"));
+        if lines.len() > PREVIEW_LINES {
+            body.push_str(&format!("\n… {} more lines", lines.len() - PREVIEW_LINES));
+        }
+
+        let reason = if findings.is_empty() {
+            "Large prompt"
+        } else {
+            "Possibly sensitive prompt"
+        };
+        self.add_output(format!(
+            "⚠️ {} — press Enter to send it or Esc to cancel",
+            reason
+        ));
+        self.request_confirmation(Confirmation::new(
+            format!("Send {} prompt?", purpose.label()),
+            body,
+            ConfirmAction::SendPrompt(Box::new(pending)),
+        ));
+    }
+
+    /// Findings of the guardrails for a prompt restored from the previous session
+    pub(super) fn resume_findings(&self, pending: &PendingPrompt) -> Vec<Finding> {
+        let config = get_config();
+        let (provider, _) =
+            prompt_target(&config, pending.persona.as_deref(), pending.route.as_ref());
+        guardrails::check(&pending.prompt, &config.guardrails, provider.is_local())
+    }
+
+    /// Report a restored prompt the guardrails no longer let leave this machine
+    pub(super) fn report_blocked_resume(&mut self, pending: &PendingPrompt, findings: &[Finding]) {
+        self.add_output(format!(
+            "🚫 {} not resumed — the guardrails do not allow it to leave this machine:\n{}",
+            pending.summary(),
+            finding_lines(findings)
+        ));
+    }
+}
+
+/// Provider and model answering a prompt: the persona's if it picks a
+/// model, else the routed one, else the active one
+fn prompt_target(
+    config: &AppConfig,
+    persona: Option<&str>,
+    route: Option<&Route>,
+) -> (Provider, String) {
+    let persona_model = persona
+        .and_then(|name| config.personas.get(name))
+        .filter(|persona| persona::overrides_model(persona))
+        .map(|persona| {
+            let mut ai = config.ai.clone();
+            persona::apply(persona, &mut ai);
+            (ai.active_provider, ai.get_active_model_config().name)
+        });
+    match (persona_model, route) {
+        (Some(target), _) => target,
+        (None, Some(route)) => (route.provider, route.model.clone()),
+        (None, None) => (
+            config.ai.active_provider,
+            config.ai.get_active_model_config().name,
+        ),
+    }
+}
+
+/// One line per finding, marked by whether it blocks
+pub(super) fn finding_lines(findings: &[Finding]) -> String {
+    findings
+        .iter()
+        .map(|finding| {
+            let marker = match finding.action {
+                GuardrailAction::Block => "🚫",
+                _ => "⚠️",
+            };
+            format!("  {} {}", marker, finding)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
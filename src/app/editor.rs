@@ -0,0 +1,147 @@
+//! `/open` and the `o` message key
+//!
+//! File locations found in the output are listed with numbers and opened
+//! in the configured editor. A terminal editor gets the terminal until it
+//! exits; the interface is restored afterwards.
+
+use super::{App, MessageKey};
+use crate::config::get_config;
+use crate::handlers::editor::{self, FileLocation};
+use crate::handlers::safe;
+use crate::tui::Tui;
+
+impl App {
+    /// Locations in `text` that name existing files
+    fn existing_locations(&self, text: &str) -> Vec<FileLocation> {
+        editor::find_locations(text)
+            .into_iter()
+            .filter(|location| self.current_dir.join(&location.path).is_file())
+            .collect()
+    }
+
+    /// Remember locations for `/open <n>` and list them
+    fn list_locations(&mut self, locations: Vec<FileLocation>) {
+        let lines: Vec<String> = locations
+            .iter()
+            .enumerate()
+            .map(|(idx, location)| format!("  {}. {}", idx + 1, location))
+            .collect();
+        self.add_output(format!(
+            "📂 Locations:\n{}\nOpen one with /open <n>",
+            lines.join("\n")
+        ));
+        self.open_locations = locations;
+    }
+
+    /// Open a location in the editor
+    pub(super) fn open_location(&mut self, location: &FileLocation, tui: &mut Tui) {
+        if safe::is_enabled() {
+            self.add_output(safe::blocked_message("Opening an editor"));
+            return;
+        }
+        let path = self.current_dir.join(&location.path);
+        if !path.is_file() {
+            self.add_output(format!("Error: No such file: {}", location.path));
+            return;
+        }
+        let config = get_config().editor;
+        let env_editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .ok();
+        let (template, terminal) = editor::resolve(&config, env_editor);
+        let args = match editor::command_line(&template, &path, location) {
+            Ok(args) => args,
+            Err(e) => {
+                self.add_output(format!("⚠️ {}", e));
+                return;
+            }
+        };
+
+        if terminal && let Err(e) = tui.suspend() {
+            self.add_output(format!("⚠️ Could not release the terminal: {}", e));
+            return;
+        }
+        let result = editor::open(&args, terminal, &self.current_dir);
+        if terminal {
+            if let Err(e) = tui.resume() {
+                self.add_output(format!("⚠️ Could not restore the terminal: {}", e));
+            }
+            self.needs_redraw = true;
+        }
+        match result {
+            Ok(()) => self.add_output(format!("📂 Opened {} in {}", location, args[0])),
+            Err(e) => self.add_output(format!("⚠️ {}", e)),
+        }
+    }
+
+    /// Handle `/open [n|file[:line[:column]]]`
+    ///
+    /// Without an argument the locations of the latest output that has any
+    /// are listed; a number opens one of the last list shown.
+    pub fn handle_open(&mut self, args: &str, tui: &mut Tui) {
+        if args.is_empty() || args.parse::<usize>().is_ok() {
+            if args.is_empty() || self.open_locations.is_empty() {
+                let latest = self
+                    .messages()
+                    .iter()
+                    .rev()
+                    .map(|message| self.existing_locations(&message.output))
+                    .find(|locations| !locations.is_empty());
+                match latest {
+                    Some(locations) => self.open_locations = locations,
+                    None => {
+                        self.add_output("📂 No file:line locations in the output".to_string());
+                        return;
+                    }
+                }
+            }
+            if args.is_empty() {
+                let locations = std::mem::take(&mut self.open_locations);
+                self.list_locations(locations);
+                return;
+            }
+            let number: usize = args.parse().unwrap_or_default();
+            match number
+                .checked_sub(1)
+                .and_then(|idx| self.open_locations.get(idx))
+            {
+                Some(location) => {
+                    let location = location.clone();
+                    self.open_location(&location, tui);
+                }
+                None => self.add_output(format!(
+                    "Error: No location {} (1-{})",
+                    number,
+                    self.open_locations.len()
+                )),
+            }
+            return;
+        }
+        match FileLocation::parse(args) {
+            Some(location) => self.open_location(&location, tui),
+            None => self.add_output("Usage: /open [n|file[:line[:column]]]".to_string()),
+        }
+    }
+
+    /// Open the location of the selected message, or list them when there are several
+    pub(super) fn open_selected_message(&mut self) -> MessageKey {
+        let Some((_, message)) = self.selected_message() else {
+            return MessageKey::Handled;
+        };
+        let mut locations = self.existing_locations(&message.output);
+        match locations.len() {
+            0 => self.add_output("📂 No file:line locations in this message".to_string()),
+            1 => {
+                self.selected_section = None;
+                return MessageKey::Execute(format!("/open {}", locations.remove(0)));
+            }
+            _ => {
+                self.selected_section = None;
+                self.list_locations(locations);
+                self.input = "/open ".to_string();
+                self.cursor_position = self.input.len();
+            }
+        }
+        MessageKey::Handled
+    }
+}
@@ -0,0 +1,315 @@
+//! `/eval`: the same prompts on several models
+//!
+//! The models answer in parallel in one tracked background task, each
+//! working through the cases in order; the judge grades every answer right
+//! after it arrives. Their usage counts toward the session like any other
+//! request once the report comes back.
+
+use super::App;
+use super::ai_handler::AIHandler;
+use super::templates::templates_dir;
+use crate::ai::pricing;
+use crate::ai::types::TaskStatus;
+use crate::config::get_config;
+use crate::handlers::guardrails::{self, Finding};
+use crate::handlers::workspace;
+use crate::utils::eval::{self, CaseResult, EvalCase, EvalTarget, ModelResult};
+use crate::utils::tasks::{TaskType, TaskUsage};
+use crate::utils::templates;
+use futures_util::future::join_all;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::time::{Duration, Instant};
+
+/// A finished evaluation
+pub(super) struct EvalReport {
+    source: String,
+    cases: Vec<EvalCase>,
+    results: Vec<ModelResult>,
+    judge: Option<EvalTarget>,
+    usage: Vec<TaskUsage>,
+}
+
+/// Longest failure list shown per model
+const MAX_FAILURES_SHOWN: usize = 5;
+
+/// The Esc and Ctrl+C flags that cancel an evaluation
+type Aborts = (Arc<AtomicBool>, Option<Arc<AtomicBool>>);
+
+/// One answer of a model
+struct Answer {
+    text: Result<String, String>,
+    latency: Duration,
+    usage: Option<TaskUsage>,
+}
+
+/// Ask `target` for `prompt`, timing the answer
+async fn ask(target: &EvalTarget, prompt: &str, aborts: &Aborts) -> Answer {
+    let started = Instant::now();
+    let result = match AIHandler::for_model(target.provider, &target.model) {
+        Ok(handler) => {
+            handler
+                .generate_plain(prompt, aborts.0.clone(), aborts.1.clone())
+                .await
+        }
+        Err(e) => Err(e),
+    };
+    let latency = started.elapsed();
+    match result {
+        Ok(response) => Answer {
+            usage: Some(TaskUsage {
+                provider: target.provider,
+                model: response.model.clone(),
+                tokens: response.usage.clone(),
+                cost: pricing::response_cost(target.provider, &response),
+                latency: None,
+            }),
+            text: Ok(response.content),
+            latency,
+        },
+        Err(e) => Answer {
+            text: Err(e.to_string()),
+            latency,
+            usage: None,
+        },
+    }
+}
+
+/// Run every case on `target`, grading the answers with `judge`
+async fn evaluate(
+    target: EvalTarget,
+    cases: &[EvalCase],
+    judge: Option<&EvalTarget>,
+    aborts: Aborts,
+) -> (ModelResult, Vec<TaskUsage>) {
+    let mut results = Vec::with_capacity(cases.len());
+    let mut usage = Vec::new();
+    for case in cases {
+        let answer = ask(&target, &case.prompt, &aborts).await;
+        let cost = answer.usage.as_ref().map_or(0.0, |usage| usage.cost);
+        usage.extend(answer.usage);
+
+        let mut failures = Vec::new();
+        let mut score = None;
+        if let Ok(text) = &answer.text {
+            failures = case.failures(text);
+            if let Some(judge) = judge {
+                let grade = ask(judge, &eval::judge_prompt(&case.prompt, text), &aborts).await;
+                usage.extend(grade.usage);
+                score = grade.text.ok().and_then(|reply| eval::parse_score(&reply));
+            }
+        }
+        results.push(CaseResult {
+            response: answer.text,
+            latency: answer.latency,
+            cost,
+            failures,
+            score,
+        });
+    }
+    (
+        ModelResult {
+            target,
+            cases: results,
+        },
+        usage,
+    )
+}
+
+impl App {
+    /// Handle `/eval <template|file> [text] --models m1,m2 [--expect k1,k2] [--judge model]`
+    pub fn handle_eval(&mut self, args: &str) {
+        let args = match eval::parse_args(args) {
+            Ok(args) => args,
+            Err(e) => {
+                self.add_output(format!(
+                    "Error: {}\nUsage: /eval <template|cases.yaml> [text] --models m1,m2 [--expect k1,k2] [--judge model]",
+                    e
+                ));
+                return;
+            }
+        };
+        if self.eval_running {
+            self.add_output("⚠️ An evaluation is already running".to_string());
+            return;
+        }
+
+        let cases = match self.eval_cases(&args) {
+            Ok(cases) => cases,
+            Err(e) => {
+                self.add_output(format!("Error: {}", e));
+                return;
+            }
+        };
+        let active = get_config().ai.active_provider;
+        let targets: Result<Vec<EvalTarget>, String> = args
+            .models
+            .iter()
+            .map(|spec| eval::parse_target(spec, active))
+            .collect();
+        let judge = args
+            .judge
+            .as_deref()
+            .map(|spec| eval::parse_target(spec, active))
+            .transpose();
+        let (targets, judge) = match (targets, judge) {
+            (Ok(targets), Ok(judge)) => (targets, judge),
+            (Err(e), _) | (_, Err(e)) => {
+                self.add_output(format!("Error: {}", e));
+                return;
+            }
+        };
+
+        // The prompts go to every model, so they are checked as for the least local one
+        let local = targets.iter().all(|target| target.provider.is_local());
+        let findings: Vec<Finding> = cases
+            .iter()
+            .flat_map(|case| guardrails::check(&case.prompt, &get_config().guardrails, local))
+            .collect();
+        let found: Vec<String> = findings.iter().map(ToString::to_string).collect();
+        if guardrails::is_blocked(&findings) {
+            self.add_output(format!(
+                "🚫 Not evaluating — the guardrails do not allow the prompts to leave this machine: {}",
+                found.join("; ")
+            ));
+            return;
+        }
+        if !findings.is_empty() {
+            self.add_output(format!(
+                "⚠️ The guardrails found possibly sensitive content: {}",
+                found.join("; ")
+            ));
+        }
+
+        let task_id = self.task_manager.create_task(
+            format!("Evaluate {} on {} model(s)", args.source, targets.len()),
+            TaskType::AIGeneration,
+        );
+        self.task_manager
+            .update_task_status(task_id, TaskStatus::Running);
+        let reports = self.eval_tx.clone();
+        let aborts = (self.abort_requested.clone(), self.global_abort.clone());
+        self.eval_running = true;
+        self.add_output(format!(
+            "📏 Evaluating {} prompt(s) on {}{}…",
+            cases.len(),
+            targets
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", "),
+            judge
+                .as_ref()
+                .map(|judge| format!(", judged by {}", judge))
+                .unwrap_or_default()
+        ));
+
+        let source = args.source.clone();
+        let job = async move {
+            let runs = targets
+                .into_iter()
+                .map(|target| evaluate(target, &cases, judge.as_ref(), aborts.clone()));
+            let (results, usage): (Vec<ModelResult>, Vec<Vec<TaskUsage>>) =
+                join_all(runs).await.into_iter().unzip();
+            EvalReport {
+                source,
+                cases,
+                results,
+                judge,
+                usage: usage.into_iter().flatten().collect(),
+            }
+        };
+        self.spawn_tracked(
+            task_id,
+            reports,
+            job,
+            |report| {
+                let cases = report.cases.len();
+                if report.results.iter().all(|result| result.errors() == cases) {
+                    TaskStatus::Failed
+                } else {
+                    TaskStatus::Completed
+                }
+            },
+            // Still reported, with the usage of what ran
+            Some,
+        );
+    }
+
+    /// The cases of a file, or the one prompt a template gives
+    fn eval_cases(&self, args: &eval::EvalArgs) -> Result<Vec<EvalCase>, String> {
+        if eval::is_cases_file(&args.source) {
+            // Cases are sent to every model, so the file must lie in the workspace
+            let path = workspace::check(&self.current_dir, Path::new(&args.source))
+                .map_err(|e| e.to_string())?;
+            let content = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+            let mut cases = eval::parse_cases(&content)?;
+            for case in &mut cases {
+                case.expect.extend(args.expect.iter().cloned());
+            }
+            return Ok(cases);
+        }
+        let all = templates::load(&templates_dir());
+        let template = templates::find(&all, &args.source)
+            .ok_or_else(|| format!("No template named '{}'; /template lists them", args.source))?;
+        Ok(vec![EvalCase {
+            prompt: template.render(&args.text),
+            expect: args.expect.clone(),
+            forbid: Vec::new(),
+        }])
+    }
+
+    /// Show finished evaluations
+    pub fn collect_evals(&mut self) {
+        while let Ok(report) = self.eval_rx.try_recv() {
+            self.eval_running = false;
+            for usage in &report.usage {
+                self.record_usage(usage);
+            }
+
+            let mut lines = vec![format!(
+                "📏 Evaluation of {} — {} prompt(s){}",
+                report.source,
+                report.cases.len(),
+                report
+                    .judge
+                    .as_ref()
+                    .map(|judge| format!(", judged by {} (0–{})", judge, eval::MAX_SCORE))
+                    .unwrap_or_default()
+            )];
+            lines.push(eval::render_table(
+                &report.cases,
+                &report.results,
+                report.judge.is_some(),
+            ));
+            for result in &report.results {
+                let problems: Vec<String> = result
+                    .cases
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(idx, case)| {
+                        let problems = match &case.response {
+                            Ok(_) => case.failures.clone(),
+                            Err(e) => vec![format!("error: {}", e)],
+                        };
+                        problems
+                            .into_iter()
+                            .map(move |problem| format!("#{} {}", idx + 1, problem))
+                    })
+                    .collect();
+                if problems.is_empty() {
+                    continue;
+                }
+                let mut shown = problems[..problems.len().min(MAX_FAILURES_SHOWN)].join("; ");
+                if problems.len() > MAX_FAILURES_SHOWN {
+                    shown.push_str(&format!("; … {} more", problems.len() - MAX_FAILURES_SHOWN));
+                }
+                lines.push(format!("⚠️ {}: {}", result.target, shown));
+            }
+            self.add_output(lines.join("\n"));
+            self.needs_redraw = true;
+        }
+    }
+}
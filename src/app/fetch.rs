@@ -0,0 +1,130 @@
+//! Web pages attached with `/fetch`
+//!
+//! Pages are downloaded in a tracked background task and attached as
+//! context once their readable text is ready. Responses may ask for pages
+//! with ```` ```fetch ```` blocks, which are fetched the same way unless the
+//! prompt carried untrusted content.
+
+use super::App;
+use crate::ai::types::TaskStatus;
+use crate::config::get_config;
+use crate::handlers::attach::Attachment;
+use crate::handlers::fetch::{self, Page};
+use crate::utils::tasks::TaskType;
+
+/// Outcome of fetching a page in the background
+#[derive(Debug)]
+pub struct FetchResult {
+    /// Address as requested
+    pub url: String,
+    /// The page, or why it could not be fetched
+    pub result: Result<Page, String>,
+}
+
+impl App {
+    /// Handle `/fetch <url>`
+    pub fn handle_fetch(&mut self, args: &str) {
+        let url = match fetch::parse_url(args) {
+            Ok(url) => url,
+            Err(e) => {
+                self.add_output(format!("Error: {}", e));
+                return;
+            }
+        };
+        if !self.connectivity.is_online() {
+            self.add_output(format!("⚡ Offline — cannot fetch {}", url));
+            return;
+        }
+
+        let task_id = self
+            .task_manager
+            .create_task(format!("Fetch: {}", url), TaskType::NetworkRequest);
+        self.task_manager
+            .update_task_status(task_id, TaskStatus::Running);
+        self.fetching += 1;
+        let results = self.fetch_tx.clone();
+        let config = get_config().fetch;
+        let requested = url.to_string();
+
+        let job = async move {
+            let result = fetch::fetch(&url, &config).await.map_err(|e| e.to_string());
+            FetchResult {
+                url: requested,
+                result,
+            }
+        };
+        self.spawn_tracked(
+            task_id,
+            results,
+            job,
+            |fetched| match fetched.result {
+                Ok(_) => TaskStatus::Completed,
+                Err(_) => TaskStatus::Failed,
+            },
+            // Still delivered, so that the count of running fetches drops
+            |fetched| {
+                Some(FetchResult {
+                    result: Err("cancelled".to_string()),
+                    ..fetched
+                })
+            },
+        );
+        self.add_output(format!("🌐 Fetching {}…", args.trim()));
+    }
+
+    /// Attach pages fetched in the background
+    pub fn collect_fetches(&mut self) {
+        while let Ok(fetched) = self.fetch_rx.try_recv() {
+            self.fetching = self.fetching.saturating_sub(1);
+            match fetched.result {
+                Ok(page) if !page.text.trim().is_empty() => {
+                    let attachment = Attachment::new(page.name(), page.text.clone());
+                    let cut = if page.truncated {
+                        ", cut to fetch.token_budget"
+                    } else {
+                        ""
+                    };
+                    self.add_output(format!(
+                        "🌐 Attached {} (~{} tokens{})",
+                        attachment.name,
+                        attachment.tokens(),
+                        cut
+                    ));
+                    self.attachments.push(attachment);
+                }
+                Ok(_) => {
+                    self.add_output(format!(
+                        "⚠️ {} has no readable text — nothing attached",
+                        fetched.url
+                    ));
+                    self.stop_chain("the page could not be fetched");
+                }
+                Err(e) => {
+                    self.add_output(format!("⚠️ Could not fetch {}: {}", fetched.url, e));
+                    self.stop_chain("the page could not be fetched");
+                }
+            }
+        }
+    }
+
+    /// Fetch the pages a response asked for in ```` ```fetch ```` blocks
+    ///
+    /// Nothing is downloaded when the prompt carried untrusted content, since
+    /// the request may come from instructions hidden in it.
+    pub(super) fn fetch_requested_pages(&mut self, answer: &str, held: bool) {
+        let urls = fetch::extract_fetch_requests(answer);
+        if urls.is_empty() {
+            return;
+        }
+        if held {
+            self.add_output(format!(
+                "🛡️ The response asks for {} — not fetched because the prompt carried untrusted content; use /fetch <url> to fetch a page yourself",
+                urls.join(", ")
+            ));
+            return;
+        }
+        for url in urls {
+            self.handle_fetch(&url);
+        }
+    }
+}
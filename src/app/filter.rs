@@ -0,0 +1,76 @@
+//! Output filtering by source
+//!
+//! `/filter ai|bash|cmd|all` hides whole sections of the output pane by the
+//! kind of command that produced them. Nothing is removed from the buffer, so
+//! exports and `/print` still see the full session. The active filter is
+//! confirmed in a toast rather than in a command section, which `/filter ai`
+//! would hide right away.
+
+use super::App;
+use crate::handlers::CommandMode;
+use crate::utils::transcript::SectionSpan;
+
+/// Name of a section kind in `/filter`
+fn mode_name(mode: &CommandMode) -> &'static str {
+    match mode {
+        CommandMode::AI => "ai",
+        CommandMode::Bash => "bash",
+        CommandMode::Command => "cmd",
+    }
+}
+
+impl App {
+    /// Whether a section passes the output filter
+    pub fn is_section_visible(&self, span: &SectionSpan) -> bool {
+        self.output_filter.is_empty() || self.output_filter.contains(&span.mode)
+    }
+
+    /// Label of the active filter for the status bar, e.g. `ai+bash`
+    pub fn filter_label(&self) -> Option<String> {
+        if self.output_filter.is_empty() {
+            return None;
+        }
+        let names: Vec<&str> = self.output_filter.iter().map(mode_name).collect();
+        Some(names.join("+"))
+    }
+
+    /// Handle `/filter [ai|bash|cmd|all]...`
+    pub fn handle_filter(&mut self, args: &str) {
+        if args.is_empty() {
+            let showing = self.filter_label().unwrap_or_else(|| "all".to_string());
+            self.show_toast(format!(
+                "🔍 Showing {} output. Usage: /filter ai|bash|cmd|all",
+                showing
+            ));
+            return;
+        }
+
+        let mut modes = Vec::new();
+        for word in args.split_whitespace() {
+            let mode = match word {
+                "all" => {
+                    modes.clear();
+                    break;
+                }
+                "ai" => CommandMode::AI,
+                "bash" => CommandMode::Bash,
+                "cmd" => CommandMode::Command,
+                _ => {
+                    self.add_output("Error: Usage: /filter ai|bash|cmd|all".to_string());
+                    return;
+                }
+            };
+            if !modes.contains(&mode) {
+                modes.push(mode);
+            }
+        }
+
+        self.output_filter = modes;
+        self.selected_section = None;
+        self.scroll_offset = 0;
+        match self.filter_label() {
+            Some(label) => self.show_toast(format!("🔍 Showing only {} output", label)),
+            None => self.show_toast("🔍 Showing all output".to_string()),
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Images generated with `/image`
+//!
+//! The image is generated in a tracked background task, saved as a PNG file
+//! and shown on the normal screen when the terminal supports kitty graphics
+//! or sixel. What the image cost is added to the session and the usage
+//! ledger like the cost of text responses.
+
+use super::App;
+use crate::ai::types::TaskStatus;
+use crate::ai::{AIError, Provider, pricing};
+use crate::config::{ImageBackend, get_config};
+use crate::handlers::safe;
+use crate::tui::Tui;
+use crate::utils::image::{self, GeneratedImage, Graphics};
+use crate::utils::tasks::TaskType;
+use crate::utils::{TaskId, TaskUsage};
+use chrono::Local;
+use std::io;
+
+/// Outcome of generating an image in the background
+#[derive(Debug)]
+pub struct ImageResult {
+    /// Task that tracked the generation
+    pub task_id: TaskId,
+    /// The prompt as the user wrote it
+    pub prompt: String,
+    /// The image, or why it could not be made
+    pub result: Result<GeneratedImage, AIError>,
+}
+
+impl App {
+    /// Handle `/image <prompt>`
+    pub fn handle_image(&mut self, args: &str) {
+        let prompt = args.trim().to_string();
+        if prompt.is_empty() {
+            self.add_output("Error: Usage: /image <prompt>".to_string());
+            return;
+        }
+        if safe::is_enabled() {
+            self.add_output(safe::blocked_message("Saving images"));
+            return;
+        }
+
+        let app_config = get_config();
+        let mut config = app_config.image;
+        if config.backend == ImageBackend::OpenAI {
+            if config.api_key.is_empty() {
+                config.api_key = app_config.ai.openai.api_key;
+            }
+            if config.api_key.is_empty() {
+                self.add_output(
+                    "⚠️ No OpenAI API key — set image.api_key or use image.backend: stable_diffusion"
+                        .to_string(),
+                );
+                return;
+            }
+            if !self.connectivity.is_online() {
+                self.add_output("⚡ Offline — cannot reach the images API".to_string());
+                return;
+            }
+        }
+
+        let task_id = self.task_manager.create_task(
+            format!("Image: {}", crate::utils::truncate_string(&prompt, 40)),
+            TaskType::NetworkRequest,
+        );
+        self.task_manager
+            .update_task_status(task_id, TaskStatus::Running);
+        let task_manager = self.task_manager.clone();
+        let results = self.image_tx.clone();
+        let generator = match config.backend {
+            ImageBackend::OpenAI => config.model.clone(),
+            ImageBackend::StableDiffusion => format!("Stable Diffusion at {}", config.endpoint),
+        };
+
+        let job = async move {
+            let result = image::generate(&config, &prompt).await;
+            if let (Ok(generated), ImageBackend::OpenAI) = (&result, config.backend) {
+                task_manager.set_task_usage(
+                    task_id,
+                    TaskUsage {
+                        provider: Provider::OpenAI,
+                        model: generated.model.clone(),
+                        tokens: generated.usage.clone(),
+                        cost: pricing::image_cost(&generated.model, &config.size, &generated.usage),
+                        latency: None,
+                    },
+                );
+            }
+            ImageResult {
+                task_id,
+                prompt,
+                result,
+            }
+        };
+        self.spawn_tracked(
+            task_id,
+            results,
+            job,
+            |generated| match generated.result {
+                Ok(_) => TaskStatus::Completed,
+                Err(_) => TaskStatus::Failed,
+            },
+            |_| None,
+        );
+        self.add_output(format!(
+            "🖼️ Generating an image with {}… (Ctrl+T to follow)",
+            generator
+        ));
+    }
+
+    /// Save images generated in the background and show them
+    pub fn collect_images(&mut self, tui: &mut Tui) {
+        while let Ok(generated) = self.image_rx.try_recv() {
+            let image = match generated.result {
+                Ok(image) => image,
+                Err(e) => {
+                    self.add_output(format!("⚠️ Image generation failed: {}", e));
+                    self.stop_chain("the image could not be generated");
+                    continue;
+                }
+            };
+
+            let mut cost = String::new();
+            if let Some(usage) = self
+                .task_manager
+                .get_task(generated.task_id)
+                .and_then(|task| task.usage)
+            {
+                cost = format!(", ${:.4}", usage.cost);
+                self.record_usage(&usage);
+            }
+
+            let config = get_config().image;
+            let name = image::file_name(&generated.prompt, Local::now());
+            let path = match image::save(&image::output_dir(&config), &name, &image.png) {
+                Ok(path) => path,
+                Err(e) => {
+                    self.add_output(format!("⚠️ Could not save the image: {}", e));
+                    self.stop_chain("the image could not be saved");
+                    continue;
+                }
+            };
+            let mut message = format!("🖼️ Saved {} ({}{})", path.display(), image.model, cost);
+            if let Some(revised) = image.revised_prompt.filter(|revised| !revised.is_empty()) {
+                message.push_str(&format!("\nPrompt as revised by the model: {}", revised));
+            }
+            self.add_output(message);
+
+            let graphics = image::detect_graphics(config.display);
+            if graphics != Graphics::None {
+                if let Err(e) = show_on_normal_screen(tui, graphics, &path) {
+                    self.add_output(format!("⚠️ Could not show the image: {}", e));
+                }
+                self.needs_redraw = true;
+            }
+        }
+    }
+}
+
+/// Leave the TUI, draw the image and wait for Enter
+fn show_on_normal_screen(
+    tui: &mut Tui,
+    graphics: Graphics,
+    path: &std::path::Path,
+) -> anyhow::Result<()> {
+    tui.suspend()?;
+    let shown = image::show(graphics, path).and_then(|()| {
+        println!("{}\nPress Enter to return", path.display());
+        io::stdin().read_line(&mut String::new()).map(|_| ())
+    });
+    tui.resume()?;
+    Ok(shown?)
+}
@@ -0,0 +1,88 @@
+//! `/import aider|llm|sgpt`: conversations and settings of other AI CLIs
+//!
+//! The conversations are stored as sessions, so `/sessions` lists them and
+//! opening one continues it with its messages as context. Importing again
+//! replaces the sessions made by the earlier import.
+
+use super::App;
+use super::sessions::store;
+use crate::config::{get_config, update_config};
+use crate::handlers::import::{self, Tool};
+use crate::handlers::safe;
+use std::path::PathBuf;
+
+impl App {
+    /// Handle `/import aider|llm|sgpt`
+    pub fn handle_import(&mut self, args: &str) {
+        let tool: Tool = match args.trim().parse() {
+            Ok(tool) => tool,
+            Err(e) => {
+                self.add_output(format!("Error: {}", e));
+                return;
+            }
+        };
+        if let Err(e) = safe::check("Importing") {
+            self.add_output(format!("Error: {}", e));
+            return;
+        }
+
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        let imported = import::load(tool, &self.current_dir, &home);
+        let mut warnings = imported.warnings.clone();
+        if imported.is_empty() {
+            let mut lines = vec![format!(
+                "📥 Found no {} history or settings to import",
+                tool
+            )];
+            lines.extend(warnings.iter().map(|warning| format!("⚠️ {}", warning)));
+            self.add_output(lines.join("\n"));
+            return;
+        }
+
+        let mut saved = 0;
+        if !imported.conversations.is_empty() {
+            match store() {
+                Ok(store) => {
+                    let ids = import::session_ids(tool, &imported.conversations);
+                    for (conversation, id) in imported.conversations.iter().zip(ids) {
+                        match store.save(&import::to_session(tool, conversation, id)) {
+                            Ok(()) => saved += 1,
+                            Err(e) => warnings.push(format!("Cannot save a session: {}", e)),
+                        }
+                    }
+                }
+                Err(e) => warnings.push(format!("Cannot open the session store: {}", e)),
+            }
+        }
+
+        let mut notes = Vec::new();
+        if !imported.settings.is_empty() {
+            let mut config = get_config();
+            notes = import::apply_settings(&mut config.ai, &imported.settings);
+            match update_config(config) {
+                Ok(()) => {
+                    let _ = self.ai_handler.update_client();
+                }
+                Err(e) => {
+                    notes.clear();
+                    warnings.push(format!("Cannot save the settings: {}", e));
+                }
+            }
+        }
+
+        let mut lines = vec![format!(
+            "📥 Imported {} conversation(s) from {} as sessions{}",
+            saved,
+            tool,
+            if saved > 0 {
+                " — open them with /sessions"
+            } else {
+                ""
+            }
+        )];
+        lines.extend(notes.iter().map(|note| format!("  {}", note)));
+        lines.push(format!("Read: {}", imported.sources.join(", ")));
+        lines.extend(warnings.iter().map(|warning| format!("⚠️ {}", warning)));
+        self.add_output(lines.join("\n"));
+    }
+}
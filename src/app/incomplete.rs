@@ -0,0 +1,232 @@
+//! Offering to continue or retry empty and cut-off responses
+//!
+//! When a response comes back empty, or stopped at `max_tokens`, a banner
+//! says so instead of leaving a cut-off answer to look finished. `c` asks
+//! the model to continue a cut-off chat answer and joins the continuation
+//! onto it; `r` sends the prompt again with twice the token limit. Esc keeps
+//! the response as it is.
+//!
+//! `/continue` does what `c` does, also after the banner was dismissed, and
+//! otherwise asks the model to go on with the last chat answer. The parts
+//! are joined into a single message in the output and the conversation, and
+//! a line after it adds up their tokens and cost.
+
+use super::{AIPurpose, App, PendingPrompt};
+use crate::ai::continuation::{self, AnswerUsage, Incomplete};
+use crate::ai::conversation::Role;
+use crate::config::get_config;
+use crate::handlers::CommandMode;
+use crate::utils::transcript::strip_reasoning;
+use crossterm::event::{KeyCode, KeyEvent};
+
+/// The banner shown below an empty or cut-off response
+#[derive(Debug, Clone)]
+pub struct IncompleteBanner {
+    /// What is wrong with the response
+    pub incomplete: Incomplete,
+    /// The prompt that was answered
+    pub pending: PendingPrompt,
+    /// Token limit offered for a retry
+    pub max_tokens: usize,
+}
+
+impl IncompleteBanner {
+    /// Whether the answer can be continued: cut off, and shown as a chat answer
+    pub fn can_continue(&self) -> bool {
+        matches!(self.incomplete, Incomplete::Truncated { .. })
+            && matches!(self.pending.purpose, AIPurpose::Chat)
+    }
+
+    /// What happened to the response
+    pub fn title(&self) -> String {
+        match &self.incomplete {
+            Incomplete::Empty => "∅ The response came back empty".to_string(),
+            Incomplete::Truncated { tokens, .. } => {
+                format!(
+                    "✂️ The response was cut off at max_tokens ({} tokens)",
+                    tokens
+                )
+            }
+        }
+    }
+
+    /// The keys of the banner
+    pub fn keys(&self) -> String {
+        let retry = format!("r retries with {} max_tokens", self.max_tokens);
+        if self.can_continue() {
+            format!("c continues · {} · Esc keeps it", retry)
+        } else {
+            format!("{} · Esc keeps it", retry)
+        }
+    }
+}
+
+impl App {
+    /// Show the banner for a response that came back empty or cut off
+    pub(super) fn offer_incomplete_retry(
+        &mut self,
+        incomplete: Incomplete,
+        pending: PendingPrompt,
+    ) {
+        // A continuation that is cut off again continues the whole answer
+        let incomplete = match (incomplete, &pending.continues) {
+            (Incomplete::Truncated { partial, tokens }, Some(previous)) => Incomplete::Truncated {
+                partial: continuation::stitch(previous, &partial),
+                tokens,
+            },
+            (incomplete, _) => incomplete,
+        };
+        let used = match &incomplete {
+            Incomplete::Truncated { tokens, .. } => *tokens,
+            Incomplete::Empty => 0,
+        };
+        let current = pending.max_tokens.unwrap_or_else(|| {
+            get_config()
+                .ai
+                .get_active_model_config()
+                .max_tokens
+                .max(used)
+        });
+
+        self.stop_chain("the previous response was incomplete");
+        self.cut_off = None;
+        self.incomplete_banner = Some(IncompleteBanner {
+            incomplete,
+            pending,
+            max_tokens: continuation::raised_max_tokens(current),
+        });
+    }
+
+    /// Handle a key press while the banner is shown
+    pub fn handle_incomplete_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('c') | KeyCode::Char('C') => self.continue_response(),
+            KeyCode::Char('r') | KeyCode::Char('R') => self.retry_with_more_tokens(),
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.dismiss_incomplete_banner();
+            }
+            _ => {}
+        }
+    }
+
+    /// Hide the banner, keeping a cut-off answer for `/continue`
+    pub(super) fn dismiss_incomplete_banner(&mut self) {
+        self.cut_off = self
+            .incomplete_banner
+            .take()
+            .filter(|banner| banner.can_continue());
+    }
+
+    /// Handle `/continue`
+    pub fn handle_continue(&mut self) {
+        if let Some(banner) = self
+            .incomplete_banner
+            .take_if(|banner| banner.can_continue())
+            .or_else(|| self.cut_off.take())
+        {
+            self.incomplete_banner = Some(banner);
+            self.continue_response();
+            return;
+        }
+
+        // Without a cut-off answer, the model goes on with the last one
+        let last = self.conversation.exchanges().last().and_then(|exchange| {
+            let prompt = exchange.iter().find(|message| message.role == Role::User)?;
+            let answer = exchange
+                .iter()
+                .find(|message| message.role == Role::Assistant)?;
+            Some((prompt.content.clone(), answer.content.clone()))
+        });
+        let Some((prompt, answer)) = last else {
+            self.add_output("⚠️ No chat answer to continue".to_string());
+            return;
+        };
+        self.add_output("↪️ Continuing the last answer".to_string());
+        self.start_prompt(PendingPrompt::new(prompt, AIPurpose::Chat).continuing(answer));
+    }
+
+    /// Join a continuation onto the answer it continues
+    ///
+    /// `usage` adds up the parts once the answer is complete.
+    pub(super) fn finish_continuation(
+        &mut self,
+        partial: &str,
+        continuation: String,
+        untrusted: bool,
+        usage: Option<AnswerUsage>,
+    ) {
+        let added = continuation::new_text(partial, &continuation).to_string();
+        // Line numbers of later output move when the message changes length,
+        // so only join it while no other response is on its way
+        let joined = self.in_flight.is_empty() && self.join_into_message(partial, &continuation);
+        if !joined {
+            self.add_output(added.clone());
+        }
+        self.conversation.continue_answer(partial, &continuation);
+
+        self.speak_response(&strip_reasoning(&added));
+        self.fetch_requested_pages(&strip_reasoning(&added), untrusted);
+        // Blocks cut in two are only whole in the joined answer
+        let answer = strip_reasoning(&continuation::stitch(partial, &continuation));
+        self.offer_response_blocks(&answer, untrusted);
+        if let Some(usage) = usage.filter(|usage| usage.parts > 1) {
+            self.add_output(usage.summary());
+        }
+    }
+
+    /// Join a continuation onto the latest chat message that holds `partial`
+    fn join_into_message(&mut self, partial: &str, continuation: &str) -> bool {
+        let messages = self.messages();
+        let found = messages
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, message)| message.mode == CommandMode::AI)
+            .find_map(|(index, message)| {
+                let joined = continuation::join_into(&message.output, partial, continuation)?;
+                Some((index, message.output.clone(), joined))
+            });
+        match found {
+            Some((index, original, joined)) => self.replace_message(index, &original, &joined),
+            None => false,
+        }
+    }
+
+    /// Ask the model to continue the cut-off answer of the banner
+    fn continue_response(&mut self) {
+        let Some(banner) = self
+            .incomplete_banner
+            .take_if(|banner| banner.can_continue())
+        else {
+            return;
+        };
+        if let Incomplete::Truncated { partial, .. } = banner.incomplete {
+            self.add_output("↪️ Continuing the response".to_string());
+            self.start_prompt(banner.pending.continuing(partial));
+        }
+    }
+
+    /// Send the prompt of the banner again with a higher token limit
+    fn retry_with_more_tokens(&mut self) {
+        let Some(banner) = self.incomplete_banner.take() else {
+            return;
+        };
+        // The cut-off answer makes way for the new one in the conversation
+        if banner.pending.message.is_some()
+            && banner.pending.continues.is_none()
+            && matches!(banner.incomplete, Incomplete::Truncated { .. })
+        {
+            let last = self.conversation.exchanges().len();
+            if last > 0 {
+                self.conversation.remove_exchange(last - 1);
+            }
+        }
+        self.add_output(format!("🔁 Retrying with {} max_tokens", banner.max_tokens));
+        let pending = PendingPrompt {
+            created: chrono::Local::now(),
+            answer_usage: AnswerUsage::default(),
+            ..banner.pending.with_max_tokens(banner.max_tokens)
+        };
+        self.start_prompt(pending);
+    }
+}
@@ -0,0 +1,444 @@
+//! Background project indexing and `/index`
+//!
+//! While `index.enabled` is on, a background loop checks the workspace for
+//! changed files every `index.poll_secs` and embeds only those, in a
+//! low-priority task. A pass stops as soon as an AI generation is queued or
+//! running and resumes with the files left once it is done. `/index search`
+//! attaches the chunks closest to a query as context.
+
+use super::App;
+use crate::ai::embeddings;
+use crate::ai::types::TaskStatus;
+use crate::config::{IndexConfig, get_config, update_field};
+use crate::handlers::attach::Attachment;
+use crate::handlers::workspace;
+use crate::utils::index::{
+    self, Chunk, FileEntry, FileStamp, IndexActivity, Indexer, Match, ProjectIndex,
+};
+use crate::utils::network::Connectivity;
+use crate::utils::tasks::TaskType;
+use crate::utils::{TaskId, TaskManager, TaskPriority};
+use chrono::Local;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc::Sender;
+
+/// Wait after a failed pass before trying again
+const RETRY_AFTER_FAILURE: Duration = Duration::from_secs(300);
+
+/// Files embedded between saves of the index
+const SAVE_EVERY: usize = 20;
+
+/// Outcome of `/index search` in the background
+#[derive(Debug)]
+pub struct IndexSearchResult {
+    pub query: String,
+    /// Best chunks, or why the query could not be embedded
+    pub result: Result<Vec<Match>, String>,
+}
+
+/// Whether an AI generation is queued or running
+fn generation_active(task_manager: &TaskManager) -> bool {
+    task_manager.active_tasks().iter().any(|task| {
+        task.task_type == TaskType::AIGeneration
+            && matches!(task.status, TaskStatus::Pending | TaskStatus::Running)
+    })
+}
+
+/// Index the workspace at `root` until the task is aborted
+async fn run_indexer(
+    indexer: Indexer,
+    root: PathBuf,
+    task_manager: TaskManager,
+    connectivity: Connectivity,
+    ui_tx: Option<Sender<()>>,
+) {
+    let path = index::index_file(&root);
+    let model = get_config().index.model;
+    let stored = ProjectIndex::load(&path).ok().flatten();
+    indexer.with_index(|index| {
+        *index = stored
+            .filter(|stored| stored.model == model)
+            .unwrap_or_else(|| ProjectIndex::new(&model))
+    });
+    indexer.publish();
+    let notify = || {
+        if let Some(tx) = &ui_tx {
+            let _ = tx.try_send(());
+        }
+    };
+
+    loop {
+        let app_config = get_config();
+        let config = app_config.index.clone();
+        let rebuild = indexer.take_rebuild();
+        indexer.with_index(|index| {
+            if rebuild || index.model != config.model {
+                *index = ProjectIndex::new(&config.model);
+            }
+        });
+
+        let reachable = config.provider.is_local() || connectivity.is_online();
+        if generation_active(&task_manager) || !reachable {
+            indexer.set_activity(IndexActivity::Paused);
+            notify();
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            continue;
+        }
+
+        let scanned = {
+            let (root, config, workspace) =
+                (root.clone(), config.clone(), app_config.workspace.clone());
+            tokio::task::spawn_blocking(move || index::scan(&root, &config, &workspace))
+                .await
+                .unwrap_or_default()
+        };
+        let changes = indexer.with_index(|index| index.changes(&scanned));
+        let mut wait = Duration::from_secs(config.poll_secs.max(1));
+        let mut failed = false;
+        if !changes.is_empty() {
+            indexer.with_index(|index| {
+                for removed in &changes.removed {
+                    index.files.remove(removed);
+                }
+            });
+            let finished = match index_pass(
+                &indexer,
+                &root,
+                &changes.changed,
+                &scanned,
+                &task_manager,
+                &config,
+                &path,
+            )
+            .await
+            {
+                Ok(finished) => finished,
+                Err(e) => {
+                    indexer.set_activity(IndexActivity::Failed(e));
+                    wait = RETRY_AFTER_FAILURE;
+                    failed = true;
+                    false
+                }
+            };
+            if finished {
+                indexer.with_index(|index| index.updated = Some(Local::now()));
+            }
+            if let Err(e) = indexer.with_index(|index| index.save(&path)) {
+                failed = true;
+                indexer.set_activity(IndexActivity::Failed(format!(
+                    "cannot save {}: {}",
+                    path.display(),
+                    e
+                )));
+            }
+            indexer.publish();
+            notify();
+        } else if indexer.with_index(|index| index.updated.is_none()) {
+            // Nothing to index: the index is as fresh as it gets
+            indexer.with_index(|index| index.updated = Some(Local::now()));
+            indexer.publish();
+        }
+        if !failed && !generation_active(&task_manager) {
+            indexer.set_activity(IndexActivity::Idle);
+        }
+
+        // Sleep in short steps so a rebuild starts right away
+        let mut slept = Duration::ZERO;
+        while slept < wait && !indexer.is_rebuild_requested() {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+            slept += Duration::from_millis(500);
+        }
+    }
+}
+
+/// Embed changed files in a tracked low-priority task
+///
+/// Returns whether every file was indexed; the pass stops early when an AI
+/// generation starts or the task is cancelled.
+async fn index_pass(
+    indexer: &Indexer,
+    root: &Path,
+    changed: &[PathBuf],
+    scanned: &BTreeMap<PathBuf, FileStamp>,
+    task_manager: &TaskManager,
+    config: &IndexConfig,
+    path: &Path,
+) -> Result<bool, String> {
+    let task_id = task_manager.create_task_with(
+        format!("Index: {} changed file(s)", changed.len()),
+        TaskType::FileOperation,
+        TaskPriority::Low,
+        &["index"],
+    );
+    let cancelled = |task_id: TaskId| {
+        task_manager
+            .get_task(task_id)
+            .is_none_or(|task| task.status == TaskStatus::Cancelled)
+    };
+    while !task_manager.try_start(task_id) {
+        if cancelled(task_id) {
+            return Ok(false);
+        }
+        if generation_active(task_manager) {
+            task_manager.update_task_status(task_id, TaskStatus::Cancelled);
+            return Ok(false);
+        }
+        task_manager.wait_for_slot().await;
+    }
+
+    let ai = get_config().ai;
+    for (done, file) in changed.iter().enumerate() {
+        if cancelled(task_id) {
+            return Ok(false);
+        }
+        if generation_active(task_manager) {
+            task_manager.set_task_note(task_id, Some("paused for an AI generation".to_string()));
+            task_manager.update_task_status(task_id, TaskStatus::Completed);
+            indexer.set_activity(IndexActivity::Paused);
+            return Ok(false);
+        }
+        indexer.set_activity(IndexActivity::Indexing {
+            done,
+            total: changed.len(),
+        });
+        task_manager.set_task_note(task_id, Some(format!("{}/{} files", done, changed.len())));
+
+        let Some(stamp) = scanned.get(file).copied() else {
+            continue;
+        };
+        // Files that are not UTF-8 are remembered without chunks so they are not retried
+        let text = std::fs::read_to_string(root.join(file)).unwrap_or_default();
+        let pieces = index::chunk_text(&text, config.chunk_lines);
+        let texts: Vec<String> = pieces
+            .iter()
+            .map(|(start, end, text)| format!("{}:{}-{}\n{}", file.display(), start, end, text))
+            .collect();
+        let vectors = match embeddings::embed(config, &ai, &texts).await {
+            Ok(vectors) => vectors,
+            Err(e) => {
+                task_manager.set_task_note(task_id, Some(e.to_string()));
+                task_manager.update_task_status(task_id, TaskStatus::Failed);
+                return Err(e.to_string());
+            }
+        };
+        let chunks = pieces
+            .into_iter()
+            .zip(vectors)
+            .map(|((start_line, end_line, text), vector)| Chunk {
+                start_line,
+                end_line,
+                text,
+                vector,
+            })
+            .collect();
+        indexer.with_index(|index| {
+            index
+                .files
+                .insert(file.clone(), FileEntry { stamp, chunks })
+        });
+        if (done + 1) % SAVE_EVERY == 0 {
+            let _ = indexer.with_index(|index| index.save(path));
+            indexer.publish();
+        }
+    }
+    task_manager.set_task_note(task_id, None);
+    task_manager.update_task_status(task_id, TaskStatus::Completed);
+    Ok(true)
+}
+
+impl App {
+    /// Start indexing the workspace in the background if `index.enabled` is on
+    pub fn start_indexer(&mut self) {
+        if !get_config().index.enabled || self.indexer_task.is_some() {
+            return;
+        }
+        let base = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        let root = workspace::root(&get_config().workspace, &base);
+        self.indexer_task = Some(tokio::spawn(run_indexer(
+            self.indexer.clone(),
+            root,
+            self.task_manager.clone(),
+            self.connectivity.clone(),
+            self.ui_notifier.clone(),
+        )));
+    }
+
+    /// Freshness of the index for the status bar, while indexing is on
+    pub fn index_label(&self) -> Option<String> {
+        self.indexer_task
+            .as_ref()
+            .map(|_| self.indexer.status().label(Local::now()))
+    }
+
+    /// Stop the background indexer
+    fn stop_indexer(&mut self) {
+        if let Some(task) = self.indexer_task.take() {
+            task.abort();
+        }
+        self.indexer.set_activity(IndexActivity::Idle);
+    }
+
+    /// Handle `/index [on|off|rebuild|search <query>]`
+    pub fn handle_index(&mut self, args: &str) {
+        let (action, rest) = args.split_once(' ').unwrap_or((args, ""));
+        match action {
+            "" | "status" => self.show_index_status(),
+            "on" | "off" => {
+                let enabled = action == "on";
+                if let Err(e) = update_field(|config| config.index.enabled = enabled) {
+                    self.add_output(format!("Error: Could not save index.enabled: {}", e));
+                    return;
+                }
+                if enabled {
+                    self.start_indexer();
+                    self.add_output(
+                        "📇 Indexing the workspace in the background (index.enabled)".to_string(),
+                    );
+                } else {
+                    self.stop_indexer();
+                    self.add_output("📇 Background indexing is off".to_string());
+                }
+            }
+            "rebuild" => {
+                if self.indexer_task.is_none() {
+                    self.add_output(
+                        "Error: Indexing is off — turn it on with /index on".to_string(),
+                    );
+                    return;
+                }
+                self.indexer.request_rebuild();
+                self.add_output("📇 Embedding every file again".to_string());
+            }
+            "search" => self.search_index(rest.trim()),
+            _ => {
+                self.add_output("Error: Usage: /index [on|off|rebuild|search <query>]".to_string())
+            }
+        }
+    }
+
+    fn show_index_status(&mut self) {
+        let config = get_config().index;
+        if self.indexer_task.is_none() {
+            self.add_output(format!(
+                "📇 Indexing is off — /index on embeds the workspace with {} {}",
+                config.provider, config.model
+            ));
+            return;
+        }
+        let status = self.indexer.status();
+        let state = match &status.activity {
+            IndexActivity::Idle => "up to date".to_string(),
+            IndexActivity::Indexing { done, total } => {
+                format!("indexing, {}/{} files", done, total)
+            }
+            IndexActivity::Paused => "paused while the AI is busy".to_string(),
+            IndexActivity::Failed(e) => format!("failed: {}", e),
+        };
+        let updated = status
+            .updated
+            .map(|updated| updated.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_else(|| "never".to_string());
+        self.add_output(format!(
+            "📇 Index ({} {}): {} files, {} chunks, {}; last caught up {}",
+            config.provider, config.model, status.files, status.chunks, state, updated
+        ));
+    }
+
+    /// Embed a query and attach the closest chunks in the background
+    fn search_index(&mut self, query: &str) {
+        if query.is_empty() {
+            self.add_output("Error: Usage: /index search <query>".to_string());
+            return;
+        }
+        if self.indexer_task.is_none() {
+            self.add_output("Error: Indexing is off — turn it on with /index on".to_string());
+            return;
+        }
+        let app_config = get_config();
+        let config = app_config.index;
+        if !config.provider.is_local() && !self.connectivity.is_online() {
+            self.add_output(format!("⚡ Offline — cannot reach {}", config.provider));
+            return;
+        }
+
+        let task_id = self.task_manager.create_task(
+            format!("Index search: {}", crate::utils::truncate_string(query, 40)),
+            TaskType::NetworkRequest,
+        );
+        self.task_manager
+            .update_task_status(task_id, TaskStatus::Running);
+        let results = self.index_tx.clone();
+        let indexer = self.indexer.clone();
+        let query = query.to_string();
+
+        let job = async move {
+            let result = embeddings::embed(&config, &app_config.ai, std::slice::from_ref(&query))
+                .await
+                .map_err(|e| e.to_string())
+                .map(|vectors| {
+                    let vector = vectors.into_iter().next().unwrap_or_default();
+                    indexer.with_index(|index| index.search(&vector, config.top_k))
+                });
+            IndexSearchResult { query, result }
+        };
+        self.spawn_tracked(
+            task_id,
+            results,
+            job,
+            |search| match search.result {
+                Ok(_) => TaskStatus::Completed,
+                Err(_) => TaskStatus::Failed,
+            },
+            |_| None,
+        );
+        self.add_output("📇 Searching the index…".to_string());
+    }
+
+    /// Attach the chunks found by index searches
+    pub fn collect_index_searches(&mut self) {
+        while let Ok(search) = self.index_rx.try_recv() {
+            match search.result {
+                Ok(matches) if !matches.is_empty() => {
+                    let found = matches
+                        .iter()
+                        .map(|found| {
+                            format!(
+                                "  {}:{}-{} ({:.2})",
+                                found.path.display(),
+                                found.start_line,
+                                found.end_line,
+                                found.score
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    let attachment = Attachment::new(
+                        format!("index: {}", search.query),
+                        index::format_matches(&matches),
+                    );
+                    self.add_output(format!(
+                        "📇 Attached {} chunk(s) for \"{}\" (~{} tokens):\n{}",
+                        matches.len(),
+                        search.query,
+                        attachment.tokens(),
+                        found
+                    ));
+                    self.attachments.push(attachment);
+                }
+                Ok(_) => {
+                    self.add_output(format!(
+                        "📇 Nothing indexed yet matches \"{}\"",
+                        search.query
+                    ));
+                    self.stop_chain("the index search found nothing");
+                }
+                Err(e) => {
+                    self.add_output(format!("⚠️ Index search failed: {}", e));
+                    self.stop_chain("the index search failed");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! `/pty` commands in a pane that takes the keyboard
+//!
+//! `/pty <command>` runs a command that reads from its terminal, such as a
+//! REPL or `ssh`, in a pseudo-terminal shown in a pane over the output.
+//! Every key goes to the command, Ctrl+C included, until it exits; Ctrl+D
+//! ends its input and closes the pane, hanging the command up if it keeps
+//! running. Its output is then added to the output like that of a `!`
+//! command, with the run time and exit code.
+
+use super::App;
+use crate::ai::types::TaskStatus;
+use crate::handlers::bash::Exit;
+use crate::handlers::pty::{self, END_OF_INPUT, PANE_PERCENT, Screen};
+use crate::utils::TaskId;
+use crate::utils::tasks::TaskType;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use portable_pty::{Child, MasterPty};
+use std::io::Write;
+use std::sync::mpsc::{self, Sender};
+use std::time::{Duration, Instant};
+
+/// Characters of a command shown in its task name
+const TASK_NAME_CHARS: usize = 30;
+
+/// Time a command gets to exit after Ctrl+D before it is hung up
+const HANGUP_GRACE: Duration = Duration::from_millis(500);
+
+/// Bytes read from the pty at once
+const READ_CHUNK: usize = 4096;
+
+/// Something the pty of a `/pty` command reported
+#[derive(Debug)]
+pub enum PtyEvent {
+    /// Output of the command
+    Output(TaskId, Vec<u8>),
+    /// The command exited and the pty closed
+    Closed(TaskId),
+}
+
+/// A command running in the pane
+pub struct InteractiveShell {
+    pub command: String,
+    /// What the command printed
+    pub screen: Screen,
+    task_id: TaskId,
+    child: Box<dyn Child + Send + Sync>,
+    /// Keys for the thread writing them to the pty
+    keys: Sender<Vec<u8>>,
+    /// Controlling side of the pty, kept open while the pane is
+    _master: Box<dyn MasterPty + Send>,
+    started: Instant,
+    /// When Ctrl+D closed the pane
+    closing: Option<Instant>,
+}
+
+impl InteractiveShell {
+    /// Whether the pane is waiting for the command to exit after Ctrl+D
+    pub fn is_closing(&self) -> bool {
+        self.closing.is_some()
+    }
+}
+
+impl App {
+    /// Run `command` in a pseudo-terminal in the pane
+    pub(super) fn start_interactive(&mut self, command: &str) {
+        if command.is_empty() {
+            self.add_output("Usage: /pty <command>, e.g. /pty python3".to_string());
+            return;
+        }
+        if self.interactive.is_some() {
+            self.add_output("⚠️ An interactive command is already running".to_string());
+            return;
+        }
+
+        // The pane covers part of the output area, inside its border
+        let (cols, rows) = crossterm::terminal::size().unwrap_or((80, 24));
+        let cols = cols.saturating_sub(2).max(20);
+        let rows = (rows * PANE_PERCENT / 100).saturating_sub(2).max(5);
+        let process = match pty::spawn(command, &self.current_dir, cols, rows) {
+            Ok(process) => process,
+            Err(e) => {
+                self.add_output(format!("Error: {}", e));
+                self.stop_chain("the previous command failed");
+                return;
+            }
+        };
+        let mut output = process.output;
+        let mut input = process.input;
+
+        let name = format!(
+            "Pty: {}",
+            command.chars().take(TASK_NAME_CHARS).collect::<String>()
+        );
+        let task_id = self.task_manager.create_task(name, TaskType::BashCommand);
+        self.task_manager
+            .update_task_status(task_id, TaskStatus::Running);
+
+        // Reading blocks, so it gets a thread of its own
+        let events = self.pty_tx.clone();
+        std::thread::spawn(move || {
+            let mut buffer = [0; READ_CHUNK];
+            loop {
+                match output.read(&mut buffer) {
+                    Ok(0) | Err(_) => break,
+                    Ok(read) => {
+                        let bytes = buffer[..read].to_vec();
+                        if events.send(PtyEvent::Output(task_id, bytes)).is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            let _ = events.send(PtyEvent::Closed(task_id));
+        });
+
+        // So does writing while the command is not reading; the thread ends
+        // when the pane closes and drops the sender
+        let (keys, typed) = mpsc::channel::<Vec<u8>>();
+        std::thread::spawn(move || {
+            for bytes in typed {
+                if input.write_all(&bytes).and_then(|_| input.flush()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        self.add_output(format!(
+            "⌨️ {} — keys go to the command, Ctrl+D closes it",
+            command
+        ));
+        self.interactive = Some(InteractiveShell {
+            command: command.to_string(),
+            screen: Screen::new(),
+            task_id,
+            child: process.child,
+            keys,
+            _master: process.master,
+            started: Instant::now(),
+            closing: None,
+        });
+    }
+
+    /// Pass a key to the command in the pane; Ctrl+D closes it
+    pub fn handle_interactive_key(&mut self, key: KeyEvent) {
+        let Some(shell) = &mut self.interactive else {
+            return;
+        };
+        if shell.closing.is_some() {
+            return;
+        }
+        let bytes =
+            if key.code == KeyCode::Char('d') && key.modifiers.contains(KeyModifiers::CONTROL) {
+                shell.closing = Some(Instant::now());
+                vec![END_OF_INPUT]
+            } else {
+                match pty::key_bytes(&key) {
+                    Some(bytes) => bytes,
+                    None => return,
+                }
+            };
+        // A command that already exited is picked up by collect_pty_output
+        let _ = shell.keys.send(bytes);
+    }
+
+    /// Show the output of the command in the pane and finish it once it exits
+    pub fn collect_pty_output(&mut self) {
+        let mut closed = false;
+        while let Ok(event) = self.pty_rx.try_recv() {
+            // Events of a command that was hung up before its pty closed are stale
+            let Some(shell) = self.interactive.as_mut().filter(|shell| match &event {
+                PtyEvent::Output(id, _) | PtyEvent::Closed(id) => *id == shell.task_id,
+            }) else {
+                continue;
+            };
+            match event {
+                PtyEvent::Output(_, bytes) => shell.screen.feed(&bytes),
+                PtyEvent::Closed(_) => closed = true,
+            }
+            self.request_redraw();
+        }
+
+        let Some(shell) = &mut self.interactive else {
+            return;
+        };
+        let hung_up = shell
+            .closing
+            .is_some_and(|closing| closing.elapsed() >= HANGUP_GRACE);
+        let exited = match shell.child.try_wait() {
+            Ok(None) if hung_up => {
+                // Ignored the end of its input: hang up as a closed terminal would
+                shell.child.kill().ok();
+                false
+            }
+            Ok(status) => status.is_some(),
+            Err(_) => true,
+        };
+        // Programs it started may keep the pty open after it exited
+        if closed || (hung_up && exited) {
+            self.finish_interactive();
+        }
+    }
+
+    /// Close the pane and add what the command printed to the output
+    fn finish_interactive(&mut self) {
+        let Some(mut shell) = self.interactive.take() else {
+            return;
+        };
+        if let Ok(None) = shell.child.try_wait() {
+            shell.child.kill().ok();
+        }
+        let code = shell
+            .child
+            .wait()
+            .map_or(-1, |status| status.exit_code() as i32);
+        let exit = Exit {
+            code,
+            elapsed: shell.started.elapsed().as_secs_f64(),
+            printed: !shell.screen.is_empty(),
+        };
+
+        if exit.printed {
+            self.add_output(shell.screen.text());
+        }
+        self.add_output(exit.summary());
+        let status = if code == 0 {
+            TaskStatus::Completed
+        } else if shell.closing.is_some() {
+            TaskStatus::Cancelled
+        } else {
+            TaskStatus::Failed
+        };
+        self.task_manager.update_task_status(shell.task_id, status);
+        if code != 0 {
+            self.stop_chain("the previous command failed");
+        }
+    }
+}
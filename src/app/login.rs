@@ -0,0 +1,166 @@
+//! `/login <provider>` and `/logout <provider>`
+//!
+//! Logging in runs the OAuth device flow in a tracked background task: the
+//! verification page and user code are shown as soon as they arrive, and
+//! the task polls until the login is approved, denied or expires. Cancelling
+//! the task in the task list stops the polling.
+
+use super::App;
+use super::tracked::is_cancelled;
+use crate::ai::oauth::{self, DeviceCode, TokenStore};
+use crate::ai::types::TaskStatus;
+use crate::ai::{AIError, Provider};
+use crate::config::get_config;
+use crate::utils::tasks::TaskType;
+
+/// Progress of a login running in the background
+#[derive(Debug)]
+pub enum LoginEvent {
+    /// The code the user has to enter arrived
+    Code {
+        provider: Provider,
+        code: DeviceCode,
+    },
+    /// The login finished, failed or was cancelled
+    Finished {
+        provider: Provider,
+        result: Result<(), AIError>,
+    },
+}
+
+/// Parse the provider argument of `/login` and `/logout`
+fn parse_provider(command: &str, args: &str) -> Result<Provider, String> {
+    if args.is_empty() {
+        return Err(format!(
+            "Usage: /{} ollama|openai|anthropic|lmstudio|openrouter|groq|mistral|azure|custom",
+            command
+        ));
+    }
+    args.parse()
+}
+
+impl App {
+    /// Handle `/login <provider>`
+    pub fn handle_login(&mut self, args: &str) {
+        let provider = match parse_provider("login", args) {
+            Ok(provider) => provider,
+            Err(e) => {
+                self.add_output(format!("Error: {}", e));
+                return;
+            }
+        };
+        let Some(config) = get_config().ai.oauth(provider) else {
+            self.add_output(format!(
+                "⚠️ {} has no oauth settings — add client_id, device_authorization_url and token_url under its `oauth` key in the config",
+                provider
+            ));
+            return;
+        };
+
+        let task_id = self
+            .task_manager
+            .create_task(format!("Login: {}", provider), TaskType::NetworkRequest);
+        self.task_manager
+            .update_task_status(task_id, TaskStatus::Running);
+        let task_manager = self.task_manager.clone();
+        let events = self.login_tx.clone();
+
+        let results = events.clone();
+        let job = async move {
+            let result = async {
+                let code = oauth::request_device_code(&config).await?;
+                let note = format!("enter {} at {}", code.user_code, code.verification_uri);
+                let _ = events.send(LoginEvent::Code {
+                    provider,
+                    code: code.clone(),
+                });
+                task_manager.set_task_note(task_id, Some(note));
+
+                let cancelled = || is_cancelled(&task_manager, task_id);
+                let token = oauth::poll_for_token(&config, &code, cancelled).await?;
+                TokenStore::default()
+                    .save(provider, &token)
+                    .map_err(|e| AIError::ConfigError(format!("Cannot store tokens: {}", e)))
+            }
+            .await;
+
+            task_manager.set_task_note(task_id, None);
+            LoginEvent::Finished { provider, result }
+        };
+        self.spawn_tracked(
+            task_id,
+            results,
+            job,
+            |event| match event {
+                LoginEvent::Finished { result: Ok(()), .. } => TaskStatus::Completed,
+                LoginEvent::Finished {
+                    result: Err(AIError::Cancelled(_)),
+                    ..
+                } => TaskStatus::Cancelled,
+                _ => TaskStatus::Failed,
+            },
+            // Reported, so the login does not look like it is still waiting
+            Some,
+        );
+        self.add_output(format!("🔑 Starting the login to {}…", provider));
+    }
+
+    /// Handle `/logout <provider>`
+    pub fn handle_logout(&mut self, args: &str) {
+        let provider = match parse_provider("logout", args) {
+            Ok(provider) => provider,
+            Err(e) => {
+                self.add_output(format!("Error: {}", e));
+                return;
+            }
+        };
+        match TokenStore::default().remove(provider) {
+            Ok(true) => {
+                self.reload_client_for(provider);
+                self.add_output(format!("✅ Logged out of {}", provider));
+            }
+            Ok(false) => self.add_output(format!("ℹ️ Not logged in to {}", provider)),
+            Err(e) => self.add_output(format!("⚠️ Could not remove the stored tokens: {}", e)),
+        }
+    }
+
+    /// Show login progress from background tasks
+    pub fn collect_logins(&mut self) {
+        while let Ok(event) = self.login_rx.try_recv() {
+            match event {
+                LoginEvent::Code { provider, code } => {
+                    let page = code
+                        .verification_uri_complete
+                        .unwrap_or(code.verification_uri);
+                    self.add_output(format!(
+                        "🔑 To log in to {}, open {} and enter the code {}\n   The code expires in {} minutes; cancel the login from the task list (Ctrl+T)",
+                        provider,
+                        page,
+                        code.user_code,
+                        code.expires_in.div_ceil(60)
+                    ));
+                }
+                LoginEvent::Finished { provider, result } => match result {
+                    Ok(()) => {
+                        self.reload_client_for(provider);
+                        self.add_output(format!(
+                            "✅ Logged in to {}; the access token is refreshed automatically",
+                            provider
+                        ));
+                    }
+                    Err(AIError::Cancelled(_)) => {
+                        self.add_output(format!("Login to {} cancelled", provider))
+                    }
+                    Err(e) => self.add_output(format!("⚠️ Login to {} failed: {}", provider, e)),
+                },
+            }
+        }
+    }
+
+    /// Pick up new tokens when `provider` is the active one
+    fn reload_client_for(&mut self, provider: Provider) {
+        if get_config().ai.active_provider == provider {
+            let _ = self.ai_handler.update_client();
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! `/definition`, `/references` and `/hover` through language servers
+//!
+//! The servers under `lsp.servers` are started on first use, when the
+//! workspace has files they handle, and kept running. A query looks the
+//! symbol up with `workspace/symbol` in each of them and attaches its
+//! definition, its references or its hover documentation as context.
+//! Files denied by the workspace globs are never attached.
+
+use super::App;
+use crate::ai::types::TaskStatus;
+use crate::config::{LspConfig, WorkspaceConfig, get_config};
+use crate::handlers::attach::Attachment;
+use crate::handlers::workspace;
+use crate::utils::glob::{Glob, find_files};
+use crate::utils::lsp::{self, Location, LspClient, Symbol};
+use crate::utils::tasks::TaskType;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Definitions attached when several symbols share a name
+const MAX_DEFINITIONS: usize = 5;
+
+/// Longest excerpt attached for one definition
+const MAX_DEFINITION_LINES: usize = 80;
+
+/// Time between symbol lookups while a new server is still indexing
+const INDEXING_RETRY: Duration = Duration::from_secs(1);
+
+/// Running language servers by language id
+pub type LspServers = Arc<tokio::sync::Mutex<HashMap<String, Arc<LspClient>>>>;
+
+/// What to look up about a symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LspQuery {
+    Definition,
+    References,
+    Hover,
+}
+
+impl LspQuery {
+    fn name(self) -> &'static str {
+        match self {
+            LspQuery::Definition => "definition",
+            LspQuery::References => "references",
+            LspQuery::Hover => "hover",
+        }
+    }
+}
+
+/// Outcome of a query in the background
+#[derive(Debug)]
+pub struct LspResult {
+    query: LspQuery,
+    symbol: String,
+    /// Attachment text and one line per place found, or why the query failed
+    result: Result<(String, Vec<String>), String>,
+}
+
+/// Read a file of a location if the workspace allows it
+fn read_allowed(
+    workspace_config: &WorkspaceConfig,
+    root: &Path,
+    location: &Location,
+) -> Option<(PathBuf, String)> {
+    let path = workspace::check_with(workspace_config, root, &location.path).ok()?;
+    let text = std::fs::read_to_string(&path).ok()?;
+    let shown = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+    Some((shown, text))
+}
+
+/// Start the servers for the files of the workspace, or reuse running ones
+async fn servers_for(
+    servers: &LspServers,
+    root: &Path,
+    config: &LspConfig,
+) -> Result<Vec<Arc<LspClient>>, String> {
+    let mut running = servers.lock().await;
+    let mut clients = Vec::new();
+    let mut errors = Vec::new();
+    for (language, server) in &config.servers {
+        if let Some(client) = running.get(language).filter(|client| client.is_running()) {
+            clients.push(client.clone());
+            continue;
+        }
+        let handles_files = !server.extensions.is_empty()
+            && Glob::new(&format!("*.{{{}}}", server.extensions.join(",")))
+                .is_ok_and(|glob| !find_files(root, &glob).is_empty());
+        if !handles_files {
+            continue;
+        }
+        let timeout = Duration::from_secs(config.startup_timeout_secs);
+        match LspClient::start(language, server, root, timeout).await {
+            Ok(client) => {
+                let client = Arc::new(client);
+                running.insert(language.clone(), client.clone());
+                clients.push(client);
+            }
+            Err(e) => errors.push(e),
+        }
+    }
+    if clients.is_empty() {
+        return Err(if errors.is_empty() {
+            "no language server in lsp.servers handles the files of the workspace".to_string()
+        } else {
+            errors.join("; ")
+        });
+    }
+    Ok(clients)
+}
+
+/// Symbols named like `symbol`, waiting while a new server is still indexing
+async fn find_symbol(
+    client: &LspClient,
+    symbol: &str,
+    startup: Duration,
+) -> Result<Vec<Symbol>, String> {
+    let (_, name) = lsp::split_symbol(symbol);
+    loop {
+        let found = match client.symbols(name).await {
+            Ok(symbols) => lsp::matching_symbols(symbols, symbol),
+            // Servers may refuse queries until their first indexing is done
+            Err(_) if client.started.elapsed() < startup => Vec::new(),
+            Err(e) => return Err(e),
+        };
+        if !found.is_empty() || client.started.elapsed() >= startup {
+            return Ok(found);
+        }
+        tokio::time::sleep(INDEXING_RETRY).await;
+    }
+}
+
+/// Look a symbol up in the servers of the workspace
+async fn run_query(
+    servers: LspServers,
+    root: PathBuf,
+    config: LspConfig,
+    workspace_config: WorkspaceConfig,
+    query: LspQuery,
+    symbol: String,
+) -> Result<(String, Vec<String>), String> {
+    let startup = Duration::from_secs(config.startup_timeout_secs);
+    let (_, name) = lsp::split_symbol(&symbol);
+    let mut sections = Vec::new();
+    let mut places = Vec::new();
+
+    for client in servers_for(&servers, &root, &config).await? {
+        let symbols = find_symbol(&client, &symbol, startup).await?;
+        match query {
+            LspQuery::Definition => {
+                for found in symbols.iter().take(MAX_DEFINITIONS) {
+                    let Some((path, text)) =
+                        read_allowed(&workspace_config, &root, &found.location)
+                    else {
+                        continue;
+                    };
+                    let location = &found.location;
+                    let first = location.start_line.saturating_sub(config.context_lines);
+                    let last = (location.end_line + config.context_lines)
+                        .min(location.start_line + MAX_DEFINITION_LINES);
+                    places.push(format!("{}:{}", path.display(), location.start_line + 1));
+                    sections.push(lsp::format_excerpt(&path, &text, first, last));
+                }
+            }
+            LspQuery::References | LspQuery::Hover => {
+                let Some(found) = symbols.first() else {
+                    continue;
+                };
+                let Some((shown, text)) = read_allowed(&workspace_config, &root, &found.location)
+                else {
+                    continue;
+                };
+                let path = found.location.path.as_path();
+                let position = lsp::name_position(&text, &found.location, name);
+                if query == LspQuery::Hover {
+                    if let Some(hover) = client.hover(path, &text, position).await? {
+                        places.push(format!("{}:{}", shown.display(), position.0 + 1));
+                        sections.push(format!("{}:{}\n{}", shown.display(), position.0 + 1, hover));
+                    }
+                    continue;
+                }
+                for reference in client
+                    .references(path, &text, position)
+                    .await?
+                    .iter()
+                    .take(config.max_references)
+                {
+                    let Some((shown, text)) = read_allowed(&workspace_config, &root, reference)
+                    else {
+                        continue;
+                    };
+                    places.push(format!("{}:{}", shown.display(), reference.start_line + 1));
+                    sections.push(lsp::format_excerpt(
+                        &shown,
+                        &text,
+                        reference.start_line.saturating_sub(config.context_lines),
+                        reference.end_line + config.context_lines,
+                    ));
+                }
+            }
+        }
+    }
+    Ok((sections.join("\n\n"), places))
+}
+
+impl App {
+    /// Handle `/definition`, `/references` and `/hover` with a symbol
+    pub fn handle_lsp(&mut self, query: LspQuery, symbol: &str) {
+        if symbol.is_empty() {
+            self.add_output(format!("Error: Usage: /{} <symbol>", query.name()));
+            return;
+        }
+        let app_config = get_config();
+        let base = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        let root = workspace::root(&app_config.workspace, &base);
+
+        let task_id = self
+            .task_manager
+            .create_task(format!("LSP {}: {}", query.name(), symbol), TaskType::Other);
+        self.task_manager
+            .update_task_status(task_id, TaskStatus::Running);
+        let results = self.lsp_tx.clone();
+        let servers = self.lsp_servers.clone();
+        let asked = symbol.to_string();
+
+        let job = async move {
+            let symbol = asked;
+            let result = run_query(
+                servers,
+                root,
+                app_config.lsp,
+                app_config.workspace,
+                query,
+                symbol.clone(),
+            )
+            .await;
+            LspResult {
+                query,
+                symbol,
+                result,
+            }
+        };
+        self.spawn_tracked(
+            task_id,
+            results,
+            job,
+            |found| match found.result {
+                Ok(_) => TaskStatus::Completed,
+                Err(_) => TaskStatus::Failed,
+            },
+            |_| None,
+        );
+        self.add_output(format!(
+            "🔎 Asking the language servers for the {} of {}…",
+            query.name(),
+            symbol
+        ));
+    }
+
+    /// Attach the answers of language servers
+    pub fn collect_lsp_results(&mut self) {
+        while let Ok(found) = self.lsp_rx.try_recv() {
+            let name = found.query.name();
+            match found.result {
+                Ok((content, places)) if !places.is_empty() => {
+                    let attachment =
+                        Attachment::new(format!("{}: {}", name, found.symbol), content);
+                    self.add_output(format!(
+                        "🔎 Attached the {} of {} (~{} tokens):\n{}",
+                        name,
+                        found.symbol,
+                        attachment.tokens(),
+                        places
+                            .iter()
+                            .map(|place| format!("  {}", place))
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    ));
+                    self.attachments.push(attachment);
+                }
+                Ok(_) => {
+                    self.add_output(format!("🔎 No {} found for {}", name, found.symbol));
+                    self.stop_chain("the language server found nothing");
+                }
+                Err(e) => {
+                    self.add_output(format!("⚠️ Language server query failed: {}", e));
+                    self.stop_chain("the language server query failed");
+                }
+            }
+        }
+    }
+}
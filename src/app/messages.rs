@@ -0,0 +1,345 @@
+//! Message-level navigation and actions
+//!
+//! Every command in the output pane and the output printed for it form a
+//! message. Once a message is selected with Alt+Up/Down, j/k move between
+//! messages and single keys act on the selected one:
+//!
+//! - `y` copies the output to the clipboard
+//! - `r` runs the input again
+//! - `q` or Ctrl+Q quotes the output into the input
+//! - `d` drops the message from the context sent with prompts, along with
+//!   the chat exchange typed in it
+//! - `p` pins the message so it is sent as context with every prompt
+//! - `s`, `b` and `t` rewrite it shorter, as bullet points or translated
+//! - `o` opens the `file:line` location it mentions in the editor
+//! - `e` exports it to a text file
+//! - `a` or Ctrl+E asks the AI about it
+//!
+//! Space marks messages for a bulk action: while any are marked, `y`, `q`,
+//! `d`, `e` and `a` act on all of them, as plain text with their inputs.
+//!
+//! Pins and drops are kept by message id rather than by position, so they
+//! stay on their message as the output changes.
+
+use super::App;
+use super::storage::write_export;
+use crate::ai::conversation::MessageId;
+use crate::handlers::injection;
+use crate::handlers::rewrite::RewriteAction;
+use crate::utils::transcript::{self, Exchange};
+use chrono::Local;
+use clipboard::{ClipboardContext, ClipboardProvider};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// State the user attached to a message
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MessageFlags {
+    /// Sent as context with every following prompt
+    pub pinned: bool,
+    /// Never sent as context
+    pub excluded: bool,
+}
+
+/// What the event loop should do after a key in message navigation
+#[derive(Debug, Clone, PartialEq)]
+pub enum MessageKey {
+    /// Not a message key; the selection was cleared
+    Ignored,
+    /// The key was handled
+    Handled,
+    /// Run the command as if it had been typed
+    Execute(String),
+}
+
+impl App {
+    /// The messages in the output pane, oldest first
+    pub fn messages(&self) -> Vec<Exchange> {
+        let lines: Vec<&str> = self.output.lines().collect();
+        self.output_sections()
+            .iter()
+            .map(|span| span.exchange(&lines))
+            .collect()
+    }
+
+    /// Give the section being added an id
+    pub(super) fn add_message_id(&mut self) {
+        self.message_ids.push(self.next_message_id);
+        self.next_message_id += 1;
+    }
+
+    /// Number the sections of the output afresh, e.g. after it was replaced
+    pub(super) fn renumber_messages(&mut self) {
+        self.message_ids.clear();
+        for _ in self.output_sections() {
+            self.add_message_id();
+        }
+    }
+
+    /// Flags of the message at `index`
+    pub fn message_flags(&self, index: usize) -> MessageFlags {
+        self.message_ids
+            .get(index)
+            .and_then(|id| self.message_flags.get(id))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// The selected message and its index
+    pub(super) fn selected_message(&self) -> Option<(usize, Exchange)> {
+        let index = self.selected_section?;
+        self.messages()
+            .into_iter()
+            .nth(index)
+            .map(|message| (index, message))
+    }
+
+    /// Messages pinned as context and not excluded
+    pub fn pinned_messages(&self) -> Vec<Exchange> {
+        self.messages()
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, _)| {
+                let flags = self.message_flags(*idx);
+                flags.pinned && !flags.excluded
+            })
+            .map(|(_, message)| message)
+            .collect()
+    }
+
+    /// Pinned messages formatted as context for a prompt
+    ///
+    /// The output is marked as untrusted; only the input was typed by the user.
+    pub fn pinned_context(&self) -> Option<String> {
+        let pinned: Vec<String> = self
+            .pinned_messages()
+            .iter()
+            .map(|message| {
+                let input = message.display_input();
+                let output = injection::mark(&format!("the output of {}", input), &message.output);
+                format!("{}\n{}", input, output)
+            })
+            .collect();
+        if pinned.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "Pinned context from earlier in this session:\n\n{}",
+            pinned.join("\n\n")
+        ))
+    }
+
+    /// Prepend the pinned messages to a prompt
+    pub fn with_pinned_context(&self, prompt: String) -> String {
+        match self.pinned_context() {
+            Some(context) => format!("{}\n\n---\n\n{}", context, prompt),
+            None => prompt,
+        }
+    }
+
+    /// Mark or unmark the selected message for a bulk action (Space)
+    pub fn toggle_marked_message(&mut self) {
+        if let Some(index) = self.selected_section
+            && !self.marked_sections.remove(&index)
+        {
+            self.marked_sections.insert(index);
+        }
+    }
+
+    /// The messages keys act on: the marked ones, or else the selected one
+    fn target_messages(&self) -> Vec<(usize, Exchange)> {
+        let messages = self.messages();
+        let indices: Vec<usize> = if self.marked_sections.is_empty() {
+            self.selected_section.into_iter().collect()
+        } else {
+            self.marked_sections.iter().copied().collect()
+        };
+        indices
+            .into_iter()
+            .filter_map(|index| Some((index, messages.get(index)?.clone())))
+            .collect()
+    }
+
+    /// Text of the messages keys act on
+    ///
+    /// A single message is its output; several are joined with their inputs.
+    pub(super) fn target_text(&self) -> Option<String> {
+        match self.target_messages().as_slice() {
+            [] => None,
+            [(_, message)] => Some(message.output.clone()),
+            targets => {
+                let exchanges: Vec<Exchange> =
+                    targets.iter().map(|(_, message)| message.clone()).collect();
+                Some(transcript::to_plain_text(&exchanges).trim_end().to_string())
+            }
+        }
+    }
+
+    /// Leave message navigation, dropping the marks
+    pub(super) fn clear_message_selection(&mut self) {
+        self.selected_section = None;
+        self.marked_sections.clear();
+    }
+
+    /// Copy the output of the selected message, or the marked ones, to the clipboard
+    fn copy_selected_message(&mut self) {
+        let count = self.target_messages().len();
+        let Some(text) = self.target_text() else {
+            return;
+        };
+        let copied = ClipboardContext::new()
+            .and_then(|mut ctx| ctx.set_contents(text))
+            .is_ok();
+        if !copied {
+            self.add_output("⚠️ Failed to copy message to clipboard".to_string());
+        } else if count > 1 {
+            self.marked_sections.clear();
+            self.add_output(format!("✅ {} messages copied to clipboard", count));
+        } else {
+            self.add_output("✅ Message copied to clipboard".to_string());
+        }
+    }
+
+    /// Write the selected message, or the marked ones, to a text file
+    fn export_selected_messages(&mut self) {
+        let targets = self.target_messages();
+        if targets.is_empty() {
+            return;
+        }
+        let exchanges: Vec<Exchange> = targets.into_iter().map(|(_, message)| message).collect();
+        let path = self.current_dir.join(format!(
+            "ai-coder-messages-{}.txt",
+            Local::now().format("%Y%m%d-%H%M%S")
+        ));
+        match write_export(&path, transcript::to_plain_text(&exchanges).as_bytes()) {
+            Ok(path) => {
+                self.marked_sections.clear();
+                self.add_output(format!(
+                    "✅ Wrote {} message(s) to {}",
+                    exchanges.len(),
+                    path.display()
+                ));
+            }
+            Err(e) => self.add_output(format!("⚠️ Failed to write {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Drop the selected message, or the marked ones, from the context, or
+    /// bring them back once all of them are dropped
+    ///
+    /// The chat exchanges typed in them are left out of the conversation
+    /// history sent to the model as well.
+    fn toggle_excluded_messages(&mut self) {
+        let ids: Vec<MessageId> = self
+            .target_messages()
+            .into_iter()
+            .filter_map(|(index, _)| self.message_ids.get(index).copied())
+            .collect();
+        let exclude = ids.iter().any(|id| {
+            !self
+                .message_flags
+                .get(id)
+                .is_some_and(|flags| flags.excluded)
+        });
+        for id in ids {
+            let flags = self.message_flags.entry(id).or_default();
+            flags.excluded = exclude;
+            flags.pinned = false;
+            self.conversation.set_excluded(id, exclude);
+        }
+        self.marked_sections.clear();
+    }
+
+    /// Insert text into the input as a block quoted with `> `
+    fn quote_into_input(&mut self, text: &str) {
+        let mut quoted: String = text.lines().map(|line| format!("> {}\n", line)).collect();
+        // Start the quote on its own line
+        if !self.input[..self.cursor_position].is_empty()
+            && !self.input[..self.cursor_position].ends_with('\n')
+        {
+            quoted.insert(0, '\n');
+        }
+        self.input.insert_str(self.cursor_position, &quoted);
+        self.cursor_position += quoted.len();
+    }
+
+    /// Quote the selected text, or the output of the selected message, into the input (Ctrl+Q)
+    pub fn quote_selection(&mut self) {
+        if self.is_selecting_text {
+            let start = self.selection_start.min(self.selection_end);
+            let end = self
+                .selection_start
+                .max(self.selection_end)
+                .min(self.output_lines.len().saturating_sub(1));
+            let text = self
+                .output_lines
+                .get(start..=end)
+                .unwrap_or_default()
+                .join("\n");
+            self.cancel_text_selection();
+            self.quote_into_input(&text);
+        } else if let Some(text) = self.target_text() {
+            self.clear_message_selection();
+            self.quote_into_input(&text);
+        } else {
+            self.add_output(
+                "📋 Select text (Shift+Up/Down) or a message (Alt+Up/Down) to quote".to_string(),
+            );
+        }
+    }
+
+    /// Flip a flag of the selected message
+    fn toggle_message_flag(&mut self, update: impl FnOnce(&mut MessageFlags)) {
+        if let Some(&id) = self
+            .selected_section
+            .and_then(|index| self.message_ids.get(index))
+        {
+            let flags = self.message_flags.entry(id).or_default();
+            update(flags);
+            let excluded = flags.excluded;
+            self.conversation.set_excluded(id, excluded);
+        }
+    }
+
+    /// Handle a message action key while a message is selected
+    pub fn handle_message_key(&mut self, key: KeyEvent) -> MessageKey {
+        if key.code == KeyCode::Char('q') && key.modifiers == KeyModifiers::CONTROL {
+            self.quote_selection();
+            return MessageKey::Handled;
+        }
+        if key.code == KeyCode::Char('e') && key.modifiers == KeyModifiers::CONTROL {
+            self.open_selection_prompt(None);
+            return MessageKey::Handled;
+        }
+        if !key.modifiers.is_empty() {
+            return MessageKey::Ignored;
+        }
+        match key.code {
+            KeyCode::Char('y') => self.copy_selected_message(),
+            KeyCode::Char('r') => {
+                if let Some((_, message)) = self.selected_message() {
+                    self.selected_section = None;
+                    return MessageKey::Execute(message.command());
+                }
+            }
+            KeyCode::Char('q') => self.quote_selection(),
+            KeyCode::Char('d') => self.toggle_excluded_messages(),
+            KeyCode::Char('e') => self.export_selected_messages(),
+            KeyCode::Char('a') => self.open_selection_prompt(None),
+            KeyCode::Char('p') => self.toggle_message_flag(|flags| {
+                flags.pinned = !flags.pinned;
+                flags.excluded = false;
+            }),
+            KeyCode::Char('o') => return self.open_selected_message(),
+            KeyCode::Char('s') => self.start_rewrite(RewriteAction::Shorter, false, None),
+            KeyCode::Char('b') => self.start_rewrite(RewriteAction::Bullets, false, None),
+            KeyCode::Char('t') => {
+                // The language is typed into the input
+                self.rewrite_target = self.selected_section.take();
+                self.input = "/rewrite translate ".to_string();
+                self.cursor_position = self.input.len();
+            }
+            _ => return MessageKey::Ignored,
+        }
+        MessageKey::Handled
+    }
+}
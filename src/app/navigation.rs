@@ -0,0 +1,124 @@
+//! Output navigation and input box sizing
+//!
+//! `:<n>` typed in the input jumps to line n and `''` jumps back to where
+//! the last jump started. Alt+{ and Alt+} move between the anchors set at
+//! each prompt boundary, leaving plain braces to the input. Ctrl+Up/Down
+//! resize the input box.
+
+use super::App;
+use crate::config::{get_config, update_field};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Largest input box height in lines
+const MAX_INPUT_HEIGHT: u16 = 40;
+
+impl App {
+    /// Handle `:<n>` and `''` typed in the input
+    ///
+    /// Returns false if the input is not a jump and should be executed.
+    pub fn handle_jump_input(&mut self, input: &str) -> bool {
+        if input == "''" {
+            match self.jump_back {
+                Some(row) => self.jump_to_row(row),
+                None => self.add_output("📍 No previous position to jump back to".to_string()),
+            }
+            return true;
+        }
+
+        let Some(line) = input
+            .strip_prefix(':')
+            .and_then(|n| n.trim().parse::<usize>().ok())
+        else {
+            return false;
+        };
+        self.jump_to_row(line.saturating_sub(1) as u16);
+        true
+    }
+
+    /// Scroll to a row, remembering the current position for `''`
+    pub fn jump_to_row(&mut self, row: u16) {
+        self.jump_back = Some(self.scroll_offset);
+        self.scroll_offset = row;
+    }
+
+    /// Jump to the previous (`delta` < 0) or next prompt boundary
+    pub fn jump_to_anchor(&mut self, delta: isize) {
+        let target = anchor_target(&self.section_rows(), self.scroll_offset as usize, delta);
+        if let Some(row) = target {
+            self.jump_to_row(row.min(u16::MAX as usize) as u16);
+        }
+    }
+
+    /// Grow (`delta` > 0) or shrink the input box and remember its height
+    ///
+    /// Shrinking to the automatic height switches back to sizing the box to
+    /// its content.
+    pub fn resize_input(&mut self, delta: i32) {
+        let height = resized_input_height(get_config().ui.input_height, &self.input, delta);
+        if let Err(e) = update_field(|config| config.ui.input_height = height) {
+            self.add_output(format!("⚠️ Failed to save configuration: {}", e));
+        }
+    }
+
+    /// Handle `/linenumbers [on|off]`
+    pub fn handle_line_numbers(&mut self, args: &str) {
+        let enabled = match args {
+            "" => !get_config().ui.line_numbers,
+            "on" => true,
+            "off" => false,
+            _ => {
+                self.add_output("Error: Usage: /linenumbers [on|off]".to_string());
+                return;
+            }
+        };
+
+        match update_field(|config| config.ui.line_numbers = enabled) {
+            Ok(()) => self.add_output(format!(
+                "✅ Line numbers {}",
+                if enabled { "on" } else { "off" }
+            )),
+            Err(e) => self.add_output(format!("⚠️ Failed to save configuration: {}", e)),
+        }
+    }
+}
+
+/// Direction of the anchor jump `key` asks for: Alt+{ for the previous
+/// prompt boundary and Alt+} for the next one
+///
+/// Terminals differ in whether they report Shift with the brace, so only
+/// Alt is required.
+pub fn anchor_jump(key: &KeyEvent) -> Option<isize> {
+    if !key.modifiers.contains(KeyModifiers::ALT) {
+        return None;
+    }
+    match key.code {
+        KeyCode::Char('{') => Some(-1),
+        KeyCode::Char('}') => Some(1),
+        _ => None,
+    }
+}
+
+/// Row of the prompt boundary before (`delta` < 0) or after the `current`
+/// row, if any
+pub fn anchor_target(rows: &[usize], current: usize, delta: isize) -> Option<usize> {
+    if delta < 0 {
+        rows.iter().rev().find(|&&row| row < current).copied()
+    } else {
+        rows.iter().find(|&&row| row > current).copied()
+    }
+}
+
+/// Input box height after resizing it by `delta` lines from the saved
+/// `height` (0 when sized to the `input`)
+///
+/// The result is at most 40 lines; shrinking to a single line gives 0, the
+/// automatic height.
+pub fn resized_input_height(height: u16, input: &str, delta: i32) -> u16 {
+    let current = if height > 0 {
+        height
+    } else {
+        input.lines().count().clamp(1, 10) as u16
+    };
+    let height = (current as i32 + delta).clamp(0, MAX_INPUT_HEIGHT as i32) as u16;
+    if delta < 0 && height <= 1 { 0 } else { height }
+}
@@ -0,0 +1,304 @@
+//! Persistence of unfinished AI requests
+//!
+//! Prompts that are queued or still generating are written to
+//! `~/.ai-coder/pending.json` whenever the set changes, so that they survive
+//! the app exiting or being killed (e.g. when a laptop goes to sleep). On the
+//! next launch the user is offered to resume them. With `encryption.enabled`
+//! the file is encrypted like the stored sessions.
+
+use super::cost_preview::finding_lines;
+use super::storage::storage_cipher;
+use super::{AIPurpose, App, ConfirmAction, Confirmation};
+use crate::ai::continuation::{self, AnswerUsage};
+use crate::ai::conversation::MessageId;
+use crate::ai::routing::Route;
+use crate::config::get_config_dir;
+use crate::handlers::guardrails;
+use crate::utils::TaskId;
+use crate::utils::crypto::{self, Cipher};
+use crate::utils::log_error;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A prompt that has not produced a response yet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingPrompt {
+    /// Prompt sent to the model
+    pub prompt: String,
+    /// How the response will be handled
+    pub purpose: AIPurpose,
+    /// When the prompt was first submitted
+    pub created: DateTime<Local>,
+    /// Model tier the prompt was routed to, if any
+    #[serde(default)]
+    pub route: Option<Route>,
+    /// The context carried instruction-like untrusted content, so code
+    /// blocks of the response wait for the user
+    #[serde(default)]
+    pub untrusted: bool,
+    /// Persona answering the prompt, if any
+    #[serde(default)]
+    pub persona: Option<String>,
+    /// Earlier messages of the conversation sent along with the prompt
+    #[serde(default)]
+    pub history: Option<String>,
+    /// The chat message as typed, remembered in the conversation once answered
+    #[serde(default)]
+    pub message: Option<String>,
+    /// Output message the chat message was typed in; ids do not outlive a run
+    #[serde(skip)]
+    pub source: Option<MessageId>,
+    /// Template the prompt was built from, choosing its sampling preset
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Token limit raised for a retry, instead of the model's `max_tokens`
+    #[serde(default)]
+    pub max_tokens: Option<usize>,
+    /// The cut-off answer this prompt asks the model to continue
+    #[serde(default)]
+    pub continues: Option<String>,
+    /// Usage of the parts of the answer so far, while it is being continued
+    #[serde(default)]
+    pub answer_usage: AnswerUsage,
+}
+
+impl PendingPrompt {
+    /// Create a pending prompt submitted now
+    pub fn new(prompt: String, purpose: AIPurpose) -> Self {
+        Self {
+            prompt,
+            purpose,
+            created: Local::now(),
+            route: None,
+            untrusted: false,
+            persona: None,
+            history: None,
+            message: None,
+            source: None,
+            template: None,
+            max_tokens: None,
+            continues: None,
+            answer_usage: AnswerUsage::default(),
+        }
+    }
+
+    /// Send the prompt to the model of `route` instead of the active one
+    pub fn with_route(mut self, route: Option<Route>) -> Self {
+        self.route = route;
+        self
+    }
+
+    /// Answer the prompt as the named persona
+    pub fn with_persona(mut self, persona: Option<String>) -> Self {
+        self.persona = persona;
+        self
+    }
+
+    /// Send earlier messages of the conversation and remember `message`,
+    /// typed in output message `source`, with the answer
+    pub fn with_history(
+        mut self,
+        history: Option<String>,
+        message: &str,
+        source: Option<MessageId>,
+    ) -> Self {
+        self.history = history;
+        self.message = Some(message.to_string());
+        self.source = source;
+        self
+    }
+
+    /// Remember the template the prompt was built from
+    pub fn with_template(mut self, template: Option<String>) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Allow the answer up to `max_tokens` tokens
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Ask the model to continue `partial`, its cut-off answer to this prompt
+    ///
+    /// The continuation is shown after the answer rather than as a new message.
+    pub fn continuing(mut self, partial: String) -> Self {
+        self.message = None;
+        self.continues = Some(partial);
+        self
+    }
+
+    /// Prompt sent to the model: the prompt itself, or the request to continue its answer
+    pub fn request(&self) -> String {
+        match &self.continues {
+            Some(partial) => continuation::continuation_prompt(&self.prompt, partial),
+            None => self.prompt.clone(),
+        }
+    }
+
+    /// Keys of `sampling.commands` that may choose the prompt's preset, most specific first
+    pub fn sampling_keys(&self) -> Vec<&str> {
+        let mut keys = Vec::new();
+        if let Some(template) = &self.template {
+            keys.extend([template.as_str(), "template"]);
+        }
+        keys.push(self.purpose.command());
+        keys
+    }
+
+    /// Mark the prompt as carrying suspicious untrusted content
+    pub fn with_untrusted(mut self, untrusted: bool) -> Self {
+        self.untrusted = untrusted;
+        self
+    }
+
+    /// One-line description: purpose label and the start of the prompt
+    pub fn summary(&self) -> String {
+        let first_line = self.prompt.trim().lines().next().unwrap_or_default();
+        let mut text: String = first_line.chars().take(60).collect();
+        if text.len() < self.prompt.trim().len() {
+            text.push('…');
+        }
+        format!("[{}] {}", self.purpose.label(), text)
+    }
+
+    /// Read pending prompts from `path`, returning none if it does not exist
+    pub fn load_all(path: &Path) -> io::Result<Vec<PendingPrompt>> {
+        Self::load_all_with(path, None)
+    }
+
+    /// Read pending prompts from `path`, decrypting them if they were saved encrypted
+    pub fn load_all_with(path: &Path, cipher: Option<&Cipher>) -> io::Result<Vec<PendingPrompt>> {
+        match crypto::read_file(path, cipher) {
+            Ok(content) => serde_json::from_slice(&content).map_err(io::Error::other),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write pending prompts to `path`, removing the file when there are none
+    pub fn save_all(path: &Path, prompts: &[PendingPrompt]) -> io::Result<()> {
+        Self::save_all_with(path, prompts, None)
+    }
+
+    /// Write pending prompts to `path`, encrypted when a cipher is given
+    pub fn save_all_with(
+        path: &Path,
+        prompts: &[PendingPrompt],
+        cipher: Option<&Cipher>,
+    ) -> io::Result<()> {
+        if prompts.is_empty() {
+            return match fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            };
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_vec_pretty(prompts).map_err(io::Error::other)?;
+        let tmp = path.with_extension("json.tmp");
+        crypto::write_file(&tmp, &content, cipher)?;
+        fs::rename(&tmp, path)
+    }
+}
+
+/// File holding the pending prompts
+pub(super) fn pending_file() -> PathBuf {
+    get_config_dir().join("pending.json")
+}
+
+impl App {
+    /// Save the prompts that are still generating or queued
+    pub fn persist_pending(&self) {
+        let mut in_flight: Vec<&PendingPrompt> = self.in_flight.values().collect();
+        in_flight.sort_by_key(|pending| pending.created);
+
+        let prompts: Vec<PendingPrompt> = in_flight
+            .into_iter()
+            .chain(self.offline_queue.iter())
+            .cloned()
+            .collect();
+        let result = storage_cipher().and_then(|cipher| {
+            PendingPrompt::save_all_with(&pending_file(), &prompts, cipher.as_deref())
+        });
+        if let Err(e) = result {
+            log_error(&format!("Failed to save pending prompts: {}", e)).ok();
+        }
+    }
+
+    /// Drop a task that ended without a response to handle
+    pub fn forget_task(&mut self, task_id: TaskId) {
+        self.abandon_stream(task_id);
+        if self.in_flight.remove(&task_id).is_some() {
+            self.persist_pending();
+        }
+    }
+
+    /// Offer to resume prompts left over from the previous session
+    pub fn offer_pending_resume(&mut self) {
+        let loaded = match storage_cipher()
+            .and_then(|cipher| PendingPrompt::load_all_with(&pending_file(), cipher.as_deref()))
+        {
+            Ok(prompts) => prompts,
+            Err(e) => {
+                self.add_output(format!("⚠️ Could not read unfinished prompts: {}", e));
+                return;
+            }
+        };
+
+        // The guardrails may have been tightened since the prompts were queued
+        let mut prompts = Vec::new();
+        let mut lines = Vec::new();
+        for pending in loaded {
+            let findings = self.resume_findings(&pending);
+            if guardrails::is_blocked(&findings) {
+                self.report_blocked_resume(&pending, &findings);
+                continue;
+            }
+            lines.push(format!(
+                "{}. {} ({})",
+                prompts.len() + 1,
+                pending.summary(),
+                pending.created.format("%Y-%m-%d %H:%M")
+            ));
+            if !findings.is_empty() {
+                lines.push(finding_lines(&findings));
+            }
+            prompts.push(pending);
+        }
+        if prompts.is_empty() {
+            // Forget the blocked prompts, if any
+            self.persist_pending();
+            return;
+        }
+
+        let body = lines.join("\n");
+        self.request_confirmation(Confirmation::new(
+            format!("Resume {} unfinished prompt(s)?", prompts.len()),
+            format!("{}\n\nEnter sends them again; n discards them.", body),
+            ConfirmAction::ResumePrompts(prompts),
+        ));
+    }
+
+    /// Send prompts restored from the previous session
+    pub(super) fn resume_prompts(&mut self, prompts: Vec<PendingPrompt>) {
+        self.add_output(format!("▶️ Resuming {} prompt(s)", prompts.len()));
+        for pending in prompts {
+            let findings = self.resume_findings(&pending);
+            if guardrails::is_blocked(&findings) {
+                self.report_blocked_resume(&pending, &findings);
+                continue;
+            }
+            self.add_output(format!("❯ {}", pending.summary()));
+            self.start_prompt(pending);
+        }
+        // Forget the prompts that were not resumed
+        self.persist_pending();
+    }
+}
@@ -0,0 +1,69 @@
+//! Switching personas with `/as`
+//!
+//! The active persona answers every chat prompt until `/as off`. Prompts
+//! starting with `@name:` go to that persona once, whichever is active.
+
+use super::App;
+use crate::ai::persona;
+use crate::config::get_config;
+
+impl App {
+    /// Handle `/as [persona|off]`
+    pub fn handle_as(&mut self, args: &str) {
+        let config = get_config();
+        match args {
+            "" => {
+                if config.personas.is_empty() {
+                    self.add_output(
+                        "No personas configured — add them under personas: in ~/.ai-coder/config.yaml"
+                            .to_string(),
+                    );
+                    return;
+                }
+                let mut text = String::from("🎭 Personas:\n");
+                for (name, persona) in &config.personas {
+                    let marker = if self.active_persona.as_ref() == Some(name) {
+                        "▶"
+                    } else {
+                        " "
+                    };
+                    text.push_str(&format!(
+                        "{} {}: {}\n",
+                        marker,
+                        name,
+                        persona::describe(persona, &config.ai)
+                    ));
+                }
+                text.push_str(
+                    "\nUse /as <name> to switch, /as off to go back, or start a prompt with @name:",
+                );
+                self.add_output(text);
+            }
+            "off" | "none" => match self.active_persona.take() {
+                Some(name) => self.add_output(format!(
+                    "🎭 {} is off — prompts go to the active model",
+                    name
+                )),
+                None => self.add_output("No persona is active".to_string()),
+            },
+            name => match persona::find(name, &config.personas) {
+                Some(name) => {
+                    self.add_output(format!("🎭 Answering as {} — /as off to go back", name));
+                    self.active_persona = Some(name);
+                }
+                None => {
+                    let known: Vec<&str> = config.personas.keys().map(String::as_str).collect();
+                    self.add_output(format!(
+                        "Error: Unknown persona {} (configured: {})",
+                        name,
+                        if known.is_empty() {
+                            "none".to_string()
+                        } else {
+                            known.join(", ")
+                        }
+                    ));
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Prompt library
+//!
+//! `/prompts [words]` lists the chat prompts of this and earlier sessions,
+//! the often used ones first, filtered with a fuzzy search. A listed prompt
+//! is sent again with `/prompts run <n>` or put in the input box to change
+//! it first with `/prompts edit <n>`.
+
+use super::App;
+use super::storage::storage_cipher;
+use crate::config::get_config_dir;
+use crate::utils::log_error;
+use crate::utils::prompts::{PromptEntry, PromptLibrary};
+use chrono::Local;
+use std::path::PathBuf;
+
+/// Most prompts listed at once
+const MAX_LISTED: usize = 20;
+
+/// File holding the prompt library
+pub(super) fn library_file() -> PathBuf {
+    get_config_dir().join("prompts.json")
+}
+
+/// One line of the listing: number, first line of the prompt and its use
+fn prompt_line(number: usize, entry: &PromptEntry) -> String {
+    let first_line = entry.text.lines().next().unwrap_or_default();
+    let mut text: String = first_line.chars().take(70).collect();
+    if text.len() < entry.text.len() {
+        text.push('…');
+    }
+    format!(
+        "{:>3}. {} ({}×, {})",
+        number,
+        text,
+        entry.count,
+        entry.last_used.format("%Y-%m-%d %H:%M")
+    )
+}
+
+impl App {
+    /// Add a sent chat prompt to the library
+    pub(super) fn remember_prompt(&mut self, prompt: &str) {
+        let path = library_file();
+        let cipher = match storage_cipher() {
+            Ok(cipher) => cipher,
+            Err(e) => {
+                log_error(&format!("Failed to save the prompt: {}", e)).ok();
+                return;
+            }
+        };
+        let mut library = match PromptLibrary::load_with(&path, cipher.as_deref()) {
+            Ok(library) => library,
+            Err(e) => {
+                log_error(&format!("Failed to read the prompt library: {}", e)).ok();
+                return;
+            }
+        };
+        library.record(prompt, Local::now());
+        if let Err(e) = library.save_with(&path, cipher.as_deref()) {
+            log_error(&format!("Failed to save the prompt library: {}", e)).ok();
+        }
+    }
+
+    /// Handle `/prompts [words]`, `/prompts run <n>` and `/prompts edit <n>`
+    pub fn handle_prompts(&mut self, args: &str) {
+        let (action, rest) = args.split_once(' ').unwrap_or((args, ""));
+        let number = rest.trim().parse::<usize>();
+        if !matches!(action, "run" | "edit") || (number.is_err() && !rest.trim().is_empty()) {
+            // Anything else, including "run tests", is a search
+            self.list_prompts(args);
+            return;
+        }
+
+        let Some(prompt) = number
+            .ok()
+            .and_then(|n| n.checked_sub(1))
+            .and_then(|idx| self.prompt_listing.get(idx).cloned())
+        else {
+            self.add_output(format!(
+                "Error: Usage: /prompts {} <n> — n is a number from the last /prompts listing",
+                action
+            ));
+            return;
+        };
+        if action == "run" {
+            // Runs as its own chat prompt once this command is done
+            self.chain.push_front(prompt);
+        } else {
+            self.cursor_position = prompt.len();
+            self.input = prompt;
+            self.add_output("✏️ Prompt copied to the input — edit it and press Enter".to_string());
+        }
+    }
+
+    fn list_prompts(&mut self, query: &str) {
+        let library = match storage_cipher()
+            .and_then(|cipher| PromptLibrary::load_with(&library_file(), cipher.as_deref()))
+        {
+            Ok(library) => library,
+            Err(e) => {
+                self.add_output(format!("⚠️ Could not read the prompt library: {}", e));
+                return;
+            }
+        };
+        let found = library.search(query, Local::now());
+        if found.is_empty() {
+            self.prompt_listing.clear();
+            self.add_output(if library.prompts.is_empty() {
+                "📚 No prompts yet — every chat prompt you send is added to the library".to_string()
+            } else {
+                format!("📚 No prompt matches \"{}\"", query)
+            });
+            return;
+        }
+
+        let shown: Vec<&PromptEntry> = found.iter().take(MAX_LISTED).copied().collect();
+        let (often, once): (Vec<&PromptEntry>, Vec<&PromptEntry>) =
+            shown.into_iter().partition(|entry| entry.count > 1);
+        self.prompt_listing = often
+            .iter()
+            .chain(once.iter())
+            .map(|entry| entry.text.clone())
+            .collect();
+
+        let mut lines = vec![format!(
+            "📚 {} of {} prompt(s){}",
+            self.prompt_listing.len(),
+            found.len(),
+            if query.is_empty() {
+                String::new()
+            } else {
+                format!(" matching \"{}\"", query)
+            }
+        )];
+        let groups = [("Often used", &often), ("Recent", &once)];
+        let mut number = 0;
+        for (title, entries) in groups {
+            if entries.is_empty() {
+                continue;
+            }
+            lines.push(format!("\n{}:", title));
+            for entry in entries.iter() {
+                number += 1;
+                lines.push(prompt_line(number, entry));
+            }
+        }
+        lines.push(
+            "\n/prompts run <n> sends a prompt again, /prompts edit <n> puts it in the input"
+                .to_string(),
+        );
+        self.add_output(lines.join("\n"));
+    }
+}
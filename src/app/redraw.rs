@@ -0,0 +1,59 @@
+//! Redraw requests for the main loop
+//!
+//! The main loop sleeps until an event, a task update or the next animation
+//! step and only draws when something visible changed. Anything that changes
+//! what is on screen outside of an event sets `needs_redraw`; the loop asks
+//! [`App::next_wakeup`] how long it may sleep.
+
+use super::App;
+use chrono::Local;
+use std::time::{Duration, Instant};
+
+/// How often streamed response text is picked up
+const STREAM_POLL: Duration = Duration::from_millis(50);
+
+/// Time between cursor blinks
+const CURSOR_BLINK: Duration = Duration::from_millis(500);
+
+impl App {
+    /// Ask for a new frame on the next pass of the main loop
+    pub fn request_redraw(&mut self) {
+        self.needs_redraw = true;
+    }
+
+    /// Advance animations and the session clock, requesting a redraw if any changed
+    pub fn tick(&mut self) {
+        self.update_cursor_blink();
+        self.expire_toast();
+
+        let second = (Local::now() - self.stats.start_time).num_seconds();
+        if second != self.drawn_second {
+            self.drawn_second = second;
+            self.request_redraw();
+        }
+    }
+
+    /// How long the main loop may sleep before something needs repainting
+    pub fn next_wakeup(&self, now: Instant) -> Duration {
+        // The status bar shows the session time in seconds
+        let millis = (Local::now() - self.stats.start_time).num_milliseconds();
+        let mut wait = Duration::from_millis(1000 - millis.rem_euclid(1000) as u64);
+
+        // A redraw held back by frame pacing
+        if self.needs_redraw {
+            wait = wait.min(self.frame_pacer.next_draw(now));
+        }
+        // Spinner frames are picked up as often as they change
+        if self.spinner_rx.is_some() {
+            wait = wait.min(self.spinner.interval);
+        }
+        if !self.streams.is_empty() || !self.bash_runs.is_empty() || self.interactive.is_some() {
+            wait = wait.min(STREAM_POLL);
+        }
+        if !self.frame_pacer.is_low_bandwidth() {
+            let blink = self.last_cursor_toggle + CURSOR_BLINK;
+            wait = wait.min(blink.saturating_duration_since(now));
+        }
+        wait
+    }
+}
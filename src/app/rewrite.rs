@@ -0,0 +1,130 @@
+//! Quick rewrite actions on responses
+//!
+//! With a message selected, `s` makes it shorter, `b` turns it into bullet
+//! points and `t` starts `/rewrite translate <language>` in the input. The
+//! result replaces the message in the output; `/rewrite … --append` adds it
+//! below instead, as does any rewrite whose message changed in the meantime.
+
+use super::{AIPurpose, App};
+use crate::handlers::CommandMode;
+use crate::handlers::rewrite::{self, RewriteAction, RewriteRequest};
+use crate::utils::transcript::Exchange;
+
+impl App {
+    /// Handle `/rewrite shorter|bullets|translate <language> [--append]`
+    pub fn handle_rewrite(&mut self, args: &str) {
+        let target = self.rewrite_target.take();
+        match rewrite::parse_args(args) {
+            Ok((action, append)) => self.start_rewrite(action, append, target),
+            Err(e) => self.add_output(format!("Error: {}", e)),
+        }
+    }
+
+    /// The message to rewrite: the given one, the selected one or the last AI response
+    fn rewrite_message(&self, index: Option<usize>) -> Option<(usize, Exchange)> {
+        let messages = self.messages();
+        match index.or(self.selected_section) {
+            Some(index) => messages
+                .into_iter()
+                .nth(index)
+                .map(|message| (index, message)),
+            None => {
+                messages.into_iter().enumerate().rev().find(|(_, message)| {
+                    message.mode == CommandMode::AI && !message.output.is_empty()
+                })
+            }
+        }
+    }
+
+    /// Send a message back to the model with the instruction of `action`
+    pub fn start_rewrite(&mut self, action: RewriteAction, append: bool, index: Option<usize>) {
+        let Some((message, exchange)) = self
+            .rewrite_message(index)
+            .filter(|(_, exchange)| !exchange.output.trim().is_empty())
+        else {
+            self.add_output(
+                "📋 Nothing to rewrite — select a response with Alt+Up/Down".to_string(),
+            );
+            return;
+        };
+
+        self.selected_section = None;
+        let prompt = rewrite::build_prompt(&action, &exchange.output);
+        self.send_prompt(
+            prompt,
+            AIPurpose::Rewrite(RewriteRequest {
+                action,
+                message,
+                original: exchange.output,
+                append,
+            }),
+        );
+    }
+
+    /// Put the rewritten text in place of the message, or below it
+    pub(super) fn finish_rewrite(&mut self, request: RewriteRequest, content: String) {
+        let text = content.trim();
+        if text.is_empty() {
+            self.add_output("⚠️ The model returned an empty rewrite".to_string());
+            return;
+        }
+        let note = format!("✏️ Rewritten {}", request.action.describe());
+
+        // Line numbers of later output move when the message changes length,
+        // so only replace it while no other response is on its way
+        let replaced = !request.append
+            && self.in_flight.is_empty()
+            && self.replace_message(
+                request.message,
+                &request.original,
+                &format!("{}\n{}", text, note),
+            );
+        if replaced {
+            return;
+        }
+        if !request.append {
+            self.add_output(
+                "⚠️ The message changed in the meantime — adding the rewrite below".to_string(),
+            );
+        }
+        self.add_output(format!("{}:\n{}", note, text));
+    }
+
+    /// Replace the output of message `index` if it is still `original`
+    pub(super) fn replace_message(&mut self, index: usize, original: &str, text: &str) -> bool {
+        let mut lines: Vec<String> = self.output.lines().map(String::from).collect();
+        let Some(span) = self.output_sections().into_iter().nth(index) else {
+            return false;
+        };
+        if span.exchange(&lines).output != original {
+            return false;
+        }
+
+        // Keep the blank lines around the message
+        let body = &lines[span.body.clone()];
+        let leading = body
+            .iter()
+            .take_while(|line| line.trim().is_empty())
+            .count();
+        let trailing = if leading == body.len() {
+            0
+        } else {
+            body.iter()
+                .rev()
+                .take_while(|line| line.trim().is_empty())
+                .count()
+        };
+        let replacement: Vec<String> = std::iter::repeat_n(String::new(), leading)
+            .chain(text.lines().map(String::from))
+            .chain(std::iter::repeat_n(String::new(), trailing))
+            .collect();
+
+        lines.splice(span.body, replacement);
+        self.output = lines.join("\n");
+        self.output.push('\n');
+        self.output_generation += 1;
+        self.output_lines = lines;
+        self.request_redraw();
+        true
+    }
+}
@@ -0,0 +1,71 @@
+//! Keeping the reading position while the output changes
+//!
+//! `scroll_offset` counts rows from the top of the output, so anything that
+//! grows, shrinks or collapses above the visible part (a long command output
+//! folding into its summary, a rewritten or continued response, reasoning
+//! shown with Ctrl+O) would move the text being read. The position is kept
+//! as a section and a row within it instead, and the offset is derived from
+//! it again before every frame. Scrolling moves the anchor along.
+
+use super::App;
+
+/// A reading position that survives changes to the output
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrollAnchor {
+    /// Section holding the top row, `None` for the lines before the first one
+    pub section: Option<usize>,
+    /// Rows from the start of the section, or of the output
+    pub offset: usize,
+}
+
+impl ScrollAnchor {
+    /// The anchor of `row`, given the first row of every section
+    pub fn at(section_rows: &[usize], row: usize) -> Self {
+        match section_rows.iter().rposition(|&start| start <= row) {
+            Some(section) => Self {
+                section: Some(section),
+                offset: row - section_rows[section],
+            },
+            None => Self {
+                section: None,
+                offset: row,
+            },
+        }
+    }
+
+    /// The row of the anchor now, `None` once its section is gone
+    ///
+    /// A section that got shorter keeps the anchor on its last row.
+    pub fn row(&self, section_rows: &[usize]) -> Option<usize> {
+        let (start, end) = match self.section {
+            Some(section) => (
+                *section_rows.get(section)?,
+                section_rows.get(section + 1).copied(),
+            ),
+            None => (0, section_rows.first().copied()),
+        };
+        Some(match end {
+            Some(end) => (start + self.offset).min(end.saturating_sub(1).max(start)),
+            None => start + self.offset,
+        })
+    }
+}
+
+impl App {
+    /// Keep the rows being read on screen as the output changes
+    pub fn follow_scroll_anchor(&mut self) {
+        // A selection is shown line by line, without sections
+        if self.is_selecting_text && !self.native_selection_mode {
+            return;
+        }
+        let rows = self.section_rows();
+        // An offset that changed since the last frame was scrolled on purpose
+        if self.scroll_offset == self.anchored_offset
+            && let Some(row) = self.scroll_anchor.and_then(|anchor| anchor.row(&rows))
+        {
+            self.scroll_offset = row.min(u16::MAX as usize) as u16;
+        }
+        self.scroll_anchor = Some(ScrollAnchor::at(&rows, self.scroll_offset as usize));
+        self.anchored_offset = self.scroll_offset;
+    }
+}
@@ -0,0 +1,155 @@
+//! Searching the output
+//!
+//! `/search <term>` (or Ctrl+F, which starts the command in the input)
+//! highlights the matches in the output and scrolls to the first one after
+//! the reading position. With an empty input `n` and `N` move to the next
+//! and previous match, wrapping around; Alt+N and Alt+P do the same while
+//! something is typed. Esc ends the search once no generation is running. A
+//! match in a collapsed section or in folded reasoning is revealed when
+//! jumped to. The `/search` commands themselves are left out.
+
+use super::App;
+use crate::handlers::CommandMode;
+use crate::utils::search::{self, next_match};
+use crate::utils::transcript;
+
+/// An active search of the output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputSearch {
+    /// Text searched for, smart-case
+    pub term: String,
+    /// Output line of the match shown last
+    pub line: Option<usize>,
+}
+
+impl App {
+    /// Handle `/search [term]`; without a term the search ends
+    pub fn handle_search(&mut self, term: &str) {
+        if term.is_empty() {
+            if self.output_search.is_some() {
+                self.end_search();
+            } else {
+                self.add_output("Error: Usage: /search <term>".to_string());
+            }
+            return;
+        }
+
+        self.output_search = Some(OutputSearch {
+            term: term.to_string(),
+            line: None,
+        });
+        let matches = self.match_lines();
+        let Some(&first) = matches.first() else {
+            self.show_toast(format!("No matches for \"{}\"", term));
+            return;
+        };
+
+        // Start from the reading position rather than the top
+        let top = self.scroll_offset as usize;
+        let line = matches
+            .iter()
+            .copied()
+            .find(|&line| self.line_row(line).is_some_and(|row| row >= top))
+            .unwrap_or(first);
+        self.show_match(line);
+        self.show_toast(format!(
+            "{} line(s) match \"{}\" · n/N move, Esc ends the search",
+            matches.len(),
+            term
+        ));
+    }
+
+    /// Put `/search ` in the input, with the current term to edit (Ctrl+F)
+    pub fn start_search_input(&mut self) {
+        let term = self
+            .output_search
+            .as_ref()
+            .map_or("", |search| search.term.as_str());
+        self.input = format!("/search {}", term);
+        self.cursor_position = self.input.len();
+    }
+
+    /// Remove the search highlights
+    pub fn end_search(&mut self) {
+        self.output_search = None;
+        self.needs_redraw = true;
+    }
+
+    /// Scroll to the next (`forward`) or previous matching line (n/N)
+    pub fn jump_to_match(&mut self, forward: bool) {
+        let Some(current) = self.output_search.as_ref().map(|search| search.line) else {
+            return;
+        };
+        match next_match(&self.match_lines(), current, forward) {
+            Some(line) => self.show_match(line),
+            None => self.show_toast("No matches".to_string()),
+        }
+    }
+
+    /// Reveal a matching line and scroll to it
+    fn show_match(&mut self, line: usize) {
+        self.reveal_line(line);
+        if let Some(search) = &mut self.output_search {
+            search.line = Some(line);
+        }
+        if let Some(row) = self.line_row(line) {
+            self.jump_to_row(row.min(u16::MAX as usize) as u16);
+        }
+        self.needs_redraw = true;
+    }
+
+    /// Row of the match shown last, for drawing it apart from the others
+    pub fn current_match_row(&self) -> Option<usize> {
+        self.line_row(self.output_search.as_ref()?.line?)
+    }
+
+    /// Output lines holding the term, outside `/search` commands and
+    /// sections hidden by the filter
+    fn match_lines(&self) -> Vec<usize> {
+        let Some(search) = &self.output_search else {
+            return Vec::new();
+        };
+        let lines: Vec<&str> = self.output.lines().collect();
+        let sections = self.output_sections();
+        let skipped: Vec<_> = sections
+            .iter()
+            .filter(|span| {
+                let exchange = span.exchange(&lines);
+                !self.is_section_visible(span)
+                    || (exchange.mode == CommandMode::Command
+                        && (exchange.input == "search" || exchange.input.starts_with("search ")))
+            })
+            .map(|span| span.header..span.body.end)
+            .collect();
+        lines
+            .iter()
+            .enumerate()
+            .filter(|(idx, line)| {
+                search::contains(line, &search.term)
+                    && !skipped.iter().any(|range| range.contains(idx))
+            })
+            .map(|(idx, _)| idx)
+            .collect()
+    }
+
+    /// Expand the section of an output line and show its reasoning if folded
+    fn reveal_line(&mut self, line: usize) {
+        let lines: Vec<&str> = self.output.lines().collect();
+        let sections = self.output_sections();
+        let Some(index) = sections.iter().rposition(|span| span.header <= line) else {
+            return;
+        };
+        let span = &sections[index];
+        if self.is_section_collapsed(index, span) {
+            self.collapsed_sections.insert(index, false);
+        }
+        let body = &lines[span.body.clone()];
+        let in_reasoning = transcript::reasoning_blocks(body).iter().any(|block| {
+            line.checked_sub(span.body.start)
+                .is_some_and(|offset| block.contains(&offset))
+        });
+        if in_reasoning {
+            self.show_reasoning = true;
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Collapsible output sections
+//!
+//! Every command in the output pane forms a section. Long outputs of bash
+//! and app commands are shown as a one-line summary until expanded.
+//! Alt+Up/Down selects a section, Alt+Up/Down or j/k move the selection,
+//! Enter expands or collapses it and Esc clears the selection. Space marks
+//! it for a bulk action. Other keys act on the selected section, or the
+//! marked ones, as messages.
+//!
+//! Reasoning blocks inside AI responses are folded to one line; Ctrl+O
+//! shows or hides all of them.
+
+use super::{App, MessageKey};
+use crate::config::get_config;
+use crate::handlers::CommandMode;
+use crate::utils::diff;
+use crate::utils::transcript::{self, SectionSpan};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+impl App {
+    /// Sections of the current output
+    pub fn output_sections(&self) -> Vec<SectionSpan> {
+        let lines: Vec<&str> = self.output.lines().collect();
+        transcript::section_spans(&lines)
+    }
+
+    /// Whether the section at `index` is shown as a summary
+    ///
+    /// Short sections are never collapsed. Long bash and command outputs
+    /// start collapsed and AI responses start expanded.
+    pub fn is_section_collapsed(&self, index: usize, span: &SectionSpan) -> bool {
+        if span.body.len() <= get_config().ui.collapse_after_lines {
+            return false;
+        }
+        self.collapsed_sections
+            .get(&index)
+            .copied()
+            .unwrap_or(span.mode != CommandMode::AI)
+    }
+
+    /// Move the section selection by `delta`, starting from the last section
+    ///
+    /// Sections hidden by the output filter are skipped.
+    pub fn select_section(&mut self, delta: isize) {
+        let sections = self.output_sections();
+        let visible: Vec<usize> = (0..sections.len())
+            .filter(|&idx| self.is_section_visible(&sections[idx]))
+            .collect();
+        if visible.is_empty() {
+            self.selected_section = None;
+            return;
+        }
+
+        let last = visible.len() - 1;
+        let position = self
+            .selected_section
+            .and_then(|current| visible.iter().position(|&idx| idx == current));
+        let index = match position {
+            Some(position) => visible[position.saturating_add_signed(delta).min(last)],
+            None => visible[last],
+        };
+        self.selected_section = Some(index);
+
+        // Bring the selected section into view
+        if let Some(&row) = self.section_rows().get(index) {
+            self.scroll_offset = row.min(u16::MAX as usize) as u16;
+        }
+    }
+
+    /// Row of each section's separator line in the output pane
+    pub fn section_rows(&self) -> Vec<usize> {
+        let lines: Vec<&str> = self.output.lines().collect();
+        let sections = self.output_sections();
+        let mut row = sections.first().map_or(0, |span| span.header);
+        sections
+            .iter()
+            .enumerate()
+            .map(|(idx, span)| {
+                let start = row;
+                row += self.section_height(idx, span, &lines);
+                start
+            })
+            .collect()
+    }
+
+    /// Row of an output line in the output pane, `None` while its section
+    /// is hidden by the filter
+    ///
+    /// A line of a collapsed section is on its summary row.
+    pub(super) fn line_row(&self, line: usize) -> Option<usize> {
+        let sections = self.output_sections();
+        let Some(index) = sections.iter().rposition(|span| span.header <= line) else {
+            return Some(line);
+        };
+        let span = &sections[index];
+        if !self.is_section_visible(span) {
+            return None;
+        }
+        let start = *self.section_rows().get(index)?;
+        if line <= span.header + 1 {
+            return Some(start + line - span.header);
+        }
+        if self.is_section_collapsed(index, span) {
+            return Some(start + 2);
+        }
+        let lines: Vec<&str> = self.output.lines().collect();
+        let body = &lines[span.body.start..line.min(span.body.end)];
+        Some(start + 2 + self.body_height(body))
+    }
+
+    /// Number of lines a section takes in the output pane
+    fn section_height(&self, index: usize, span: &SectionSpan, lines: &[&str]) -> usize {
+        if !self.is_section_visible(span) {
+            0
+        } else if self.is_section_collapsed(index, span) {
+            3
+        } else {
+            2 + self.body_height(&lines[span.body.clone()])
+        }
+    }
+
+    /// Number of lines a section body takes, with reasoning folded or shown
+    fn body_height(&self, body: &[&str]) -> usize {
+        let mut height = 0;
+        let mut next = 0;
+        for block in transcript::reasoning_blocks(body) {
+            height += self.text_height(&body[next..block.start]);
+            // A block becomes its summary line, or gets a header line
+            height += if self.show_reasoning {
+                block.len() + 1
+            } else {
+                1
+            };
+            next = block.end;
+        }
+        height + self.text_height(&body[next..])
+    }
+
+    /// Number of lines text takes, with its diffs unified or side by side
+    fn text_height(&self, lines: &[&str]) -> usize {
+        if !self.diff_side_by_side {
+            return lines.len();
+        }
+        diff::find_unified_diffs(lines)
+            .into_iter()
+            .fold(lines.len(), |height, range| {
+                height - range.len() + diff::side_by_side(&lines[range]).len()
+            })
+    }
+
+    /// Show or hide the reasoning of all responses (Ctrl+O)
+    pub fn toggle_reasoning(&mut self) {
+        self.show_reasoning = !self.show_reasoning;
+        self.needs_redraw = true;
+    }
+
+    /// Show diffs in the output unified or side by side
+    pub fn toggle_diff_view(&mut self) {
+        self.diff_side_by_side = !self.diff_side_by_side;
+        let view = if self.diff_side_by_side {
+            "side by side"
+        } else {
+            "unified"
+        };
+        self.show_toast(format!("Diffs shown {} (Alt+D)", view));
+        self.needs_redraw = true;
+    }
+
+    /// Expand or collapse the selected section
+    pub fn toggle_selected_section(&mut self) {
+        let Some(index) = self.selected_section else {
+            return;
+        };
+        if let Some(span) = self.output_sections().get(index) {
+            let collapsed = self.is_section_collapsed(index, span);
+            self.collapsed_sections.insert(index, !collapsed);
+        }
+    }
+
+    /// Forget section state, e.g. when the output is cleared
+    pub fn reset_sections(&mut self) {
+        self.collapsed_sections.clear();
+        self.message_flags.clear();
+        self.marked_sections.clear();
+        self.section_times.clear();
+        self.selected_section = None;
+        self.renumber_messages();
+    }
+
+    /// Handle a key while a section is selected
+    ///
+    /// Returns [`MessageKey::Ignored`] when the key is not a section or
+    /// message key; the selection is cleared and the key should be handled
+    /// normally.
+    pub fn handle_section_key(&mut self, key: KeyEvent) -> MessageKey {
+        let plain = key.modifiers.is_empty();
+        match key.code {
+            KeyCode::Enter if plain => self.toggle_selected_section(),
+            KeyCode::Char(' ') if plain => self.toggle_marked_message(),
+            KeyCode::Up if key.modifiers == KeyModifiers::ALT => self.select_section(-1),
+            KeyCode::Char('k') if plain => self.select_section(-1),
+            KeyCode::Down if key.modifiers == KeyModifiers::ALT => self.select_section(1),
+            KeyCode::Char('j') if plain => self.select_section(1),
+            KeyCode::Esc => self.clear_message_selection(),
+            _ => {
+                let result = self.handle_message_key(key);
+                if result == MessageKey::Ignored {
+                    self.clear_message_selection();
+                }
+                return result;
+            }
+        }
+        MessageKey::Handled
+    }
+}
@@ -0,0 +1,490 @@
+//! Conversation titles and the session browser
+//!
+//! After the first chat prompt of a session, the model is asked for a short
+//! title in a tracked background task; until it arrives the first words of
+//! the prompt serve as the title. The session is saved after every response
+//! and on exit. `/sessions [words]` lists the stored sessions with their
+//! title, date, message count and cost; Enter opens the highlighted one in
+//! place of the current output.
+//!
+//! `/session save <name>` keeps the session under a name of its own, and
+//! `/session load <name>` brings back its output, input history, statistics
+//! and conversation context. `/diff-sessions <a> <b>` compares the prompts
+//! and responses of two stored sessions.
+
+use super::App;
+use super::storage::storage_cipher;
+use crate::ai::routing;
+use crate::ai::types::{Provider, TaskStatus};
+use crate::ai::{AIError, AIResponse};
+use crate::config::{get_config, get_config_dir};
+use crate::utils::session_diff;
+use crate::utils::sessions::{self, SessionStore, StoredSession};
+use crate::utils::tasks::TaskType;
+use crate::utils::undo::UndoStore;
+use crate::utils::{TaskUsage, format_money, log_error};
+use chrono::Local;
+use crossterm::event::{KeyCode, KeyEvent};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+
+/// A generated title for a session
+pub(super) struct TitleResult {
+    session_id: String,
+    provider: Provider,
+    result: Result<AIResponse, AIError>,
+}
+
+/// The `/sessions` popup
+#[derive(Debug, Clone)]
+pub struct SessionBrowser {
+    /// Stored sessions, most recent first
+    pub sessions: Vec<StoredSession>,
+    /// Index of the highlighted session
+    pub selected: usize,
+}
+
+impl SessionBrowser {
+    /// One line per session: title, date, message count and cost
+    pub fn lines(&self, current: &str) -> Vec<String> {
+        self.sessions
+            .iter()
+            .map(|session| {
+                let marker = if session.id == current { "● " } else { "  " };
+                format!(
+                    "{}{} — {} · {} message(s) · {}",
+                    marker,
+                    session.title,
+                    session.updated.format("%Y-%m-%d %H:%M"),
+                    session.messages,
+                    format_money(session.cost)
+                )
+            })
+            .collect()
+    }
+}
+
+/// Stored sessions in the config directory, encrypted when that is on
+pub(super) fn store() -> std::io::Result<SessionStore> {
+    Ok(SessionStore::new(&get_config_dir().join("sessions")).with_cipher(storage_cipher()?))
+}
+
+impl App {
+    /// Title the session by its first prompt, asking the model for a better one
+    pub(super) fn title_session(&mut self, prompt: &str) {
+        if self.session_title.is_some() {
+            return;
+        }
+        let (_, prompt) = routing::parse_override(prompt);
+        self.session_title = Some(sessions::draft_title(prompt));
+        if !get_config().sessions.generate_titles {
+            return;
+        }
+
+        let task_id = self
+            .task_manager
+            .create_task("Title conversation".to_string(), TaskType::Other);
+        self.task_manager
+            .update_task_status(task_id, TaskStatus::Running);
+        let handler = self.ai_handler.clone();
+        let titles = self.title_tx.clone();
+        let session_id = self.session_id.clone();
+        let title_prompt = sessions::title_prompt(prompt);
+
+        let job = async move {
+            let result = handler
+                .generate_plain(&title_prompt, Arc::new(AtomicBool::new(false)), None)
+                .await;
+            TitleResult {
+                session_id,
+                provider: handler.provider(),
+                result,
+            }
+        };
+        self.spawn_tracked(
+            task_id,
+            titles,
+            job,
+            |title| match title.result {
+                Ok(_) => TaskStatus::Completed,
+                Err(_) => TaskStatus::Failed,
+            },
+            |_| None,
+        );
+    }
+
+    /// Use generated titles once they arrive
+    pub fn collect_titles(&mut self) {
+        while let Ok(title) = self.title_rx.try_recv() {
+            let response = match title.result {
+                Ok(response) => response,
+                Err(e) => {
+                    log_error(&format!("Failed to generate a session title: {}", e)).ok();
+                    continue;
+                }
+            };
+            self.record_usage(&TaskUsage {
+                provider: title.provider,
+                model: response.model.clone(),
+                tokens: response.usage.clone(),
+                cost: crate::ai::pricing::response_cost(title.provider, &response),
+                latency: None,
+            });
+            // A session opened meanwhile keeps its own title
+            if title.session_id != self.session_id {
+                continue;
+            }
+            if let Some(text) = sessions::clean_title(&response.content) {
+                self.session_title = Some(text);
+                self.save_session();
+                self.needs_redraw = true;
+            }
+        }
+    }
+
+    /// The session as it is stored, once it has a title
+    fn stored_session(&self) -> Option<StoredSession> {
+        Some(StoredSession {
+            id: self.session_id.clone(),
+            title: self.session_title.clone()?,
+            created: self.session_created,
+            updated: Local::now(),
+            messages: self.messages().len(),
+            cost: self.stats.cost,
+            prompt_tokens: self.stats.prompt_tokens,
+            completion_tokens: self.stats.completion_tokens,
+            output: self.output.clone(),
+            history: self.history.commands.iter().cloned().collect(),
+            conversation: Some(self.conversation.messages().to_vec()),
+            command_count: self.stats.command_count,
+            ai_count: self.stats.ai_count,
+            bash_count: self.stats.bash_count,
+            models_used: self.stats.models_used.clone(),
+        })
+    }
+
+    /// Save the session if it has a title, i.e. once a prompt was sent
+    pub fn save_session(&mut self) {
+        let Some(session) = self.stored_session() else {
+            return;
+        };
+        if let Err(e) = store().and_then(|store| store.save(&session)) {
+            log_error(&format!("Failed to save the session: {}", e)).ok();
+        }
+    }
+
+    /// Handle `/sessions [words]`: open the browser, filtered by title
+    pub fn handle_sessions(&mut self, filter: &str) {
+        // The current session is listed as it is now
+        self.save_session();
+        let mut stored = match store().and_then(|store| store.list()) {
+            Ok(stored) => stored,
+            Err(e) => {
+                self.add_output(format!("⚠️ Could not read the stored sessions: {}", e));
+                return;
+            }
+        };
+        let words: Vec<String> = filter.split_whitespace().map(str::to_lowercase).collect();
+        stored.retain(|session| {
+            let title = session.title.to_lowercase();
+            words.iter().all(|word| title.contains(word))
+        });
+        if stored.is_empty() {
+            self.add_output(if filter.is_empty() {
+                "🗂️ No stored sessions yet — sessions are saved once a prompt was sent".to_string()
+            } else {
+                format!("🗂️ No session title matches \"{}\"", filter)
+            });
+            return;
+        }
+
+        self.add_output(format!(
+            "🗂️ {} session(s) — ↑/↓ to choose, Enter opens, Esc closes",
+            stored.len()
+        ));
+        self.session_browser = Some(SessionBrowser {
+            sessions: stored,
+            selected: 0,
+        });
+    }
+
+    /// Handle a key while the session browser is open
+    pub fn handle_session_browser_key(&mut self, key: KeyEvent) {
+        let Some(browser) = self.session_browser.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Up => browser.selected = browser.selected.saturating_sub(1),
+            KeyCode::Down => {
+                browser.selected = (browser.selected + 1).min(browser.sessions.len() - 1)
+            }
+            KeyCode::Home => browser.selected = 0,
+            KeyCode::End => browser.selected = browser.sessions.len() - 1,
+            KeyCode::Enter => {
+                let session = browser.sessions[browser.selected].clone();
+                self.session_browser = None;
+                self.open_session(session);
+            }
+            KeyCode::Esc | KeyCode::Char('q') => self.session_browser = None,
+            _ => {}
+        }
+        self.needs_redraw = true;
+    }
+
+    /// Replace the current session with a stored one
+    fn open_session(&mut self, session: StoredSession) {
+        if session.id == self.session_id {
+            return;
+        }
+        self.save_session();
+
+        self.output_lines = session.output.lines().map(str::to_string).collect();
+        self.output = session.output;
+        self.output_generation += 1;
+        self.reset_sections();
+        self.file_blocks.clear();
+        self.undo = UndoStore::open(&get_config_dir().join("undo"), &session.id);
+        self.session_id = session.id;
+        self.session_title = Some(session.title.clone());
+        self.session_created = session.created;
+        self.stats.cost = session.cost;
+        self.stats.prompt_tokens = session.prompt_tokens;
+        self.stats.completion_tokens = session.completion_tokens;
+        self.stats.total_tokens = session.prompt_tokens + session.completion_tokens;
+        self.stats.command_count = session.command_count;
+        self.stats.ai_count = session.ai_count;
+        self.stats.bash_count = session.bash_count;
+        self.stats.models_used = session.models_used;
+        let skip = session.history.len().saturating_sub(self.history.max_size);
+        self.history.commands = session
+            .history
+            .into_iter()
+            .skip(skip)
+            .collect::<VecDeque<_>>();
+        self.history.position = self.history.commands.len();
+        self.scroll_offset = 0;
+        match session.conversation {
+            Some(messages) => {
+                self.conversation.clear();
+                for message in messages {
+                    self.conversation.push(message.role, &message.content);
+                }
+            }
+            None => self.restore_conversation(),
+        }
+        self.add_output(format!("🗂️ Opened \"{}\"", session.title));
+    }
+
+    /// Handle `/session [save [name]|load <name>|list]`
+    pub fn handle_session(&mut self, args: &str) {
+        let (action, name) = args.split_once(' ').unwrap_or((args, ""));
+        let name = name.trim();
+        match action {
+            "" => {
+                let title = self.session_title.as_deref().unwrap_or("untitled");
+                self.add_output(format!(
+                    "🗂️ Session {} (\"{}\") — /session save <name> keeps it under a name, /session load <name> resumes one",
+                    self.session_id, title
+                ));
+            }
+            "save" => self.save_named_session(name),
+            "load" => {
+                if !sessions::is_valid_name(name) {
+                    self.add_output("Usage: /session load <name>".to_string());
+                    return;
+                }
+                if name == self.session_id {
+                    self.add_output(format!("🗂️ Session {} is already open", name));
+                    return;
+                }
+                match store().and_then(|store| store.load(name)) {
+                    Ok(session) => self.open_session(session),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => self.add_output(format!(
+                        "Error: No session named {} (see /session list)",
+                        name
+                    )),
+                    Err(e) => self.add_output(format!("⚠️ Could not load {}: {}", name, e)),
+                }
+            }
+            "list" => self.list_stored_sessions(),
+            _ => self.add_output("Usage: /session [save [name]|load <name>|list]".to_string()),
+        }
+    }
+
+    /// Save the session now, under a new name if one is given
+    fn save_named_session(&mut self, name: &str) {
+        if !name.is_empty() && !sessions::is_valid_name(name) {
+            self.add_output(
+                "Error: Session names use letters, digits, '-', '_' and '.'".to_string(),
+            );
+            return;
+        }
+        let store = match store() {
+            Ok(store) => store,
+            Err(e) => {
+                self.add_output(format!("⚠️ Could not open the session store: {}", e));
+                return;
+            }
+        };
+        let previous = self.session_id.clone();
+        if !name.is_empty() && name != previous {
+            if store.load(name).is_ok() {
+                self.add_output(format!(
+                    "Error: A session named {} exists; choose another name or /session load {}",
+                    name, name
+                ));
+                return;
+            }
+            self.session_id = name.to_string();
+        }
+        if self.session_title.is_none() {
+            self.session_title = Some(self.session_id.clone());
+        }
+
+        let Some(session) = self.stored_session() else {
+            return;
+        };
+        if let Err(e) = store.save(&session) {
+            self.session_id = previous;
+            self.add_output(format!("⚠️ Could not save the session: {}", e));
+            return;
+        }
+        // The session now lives under its name only
+        if self.session_id != previous {
+            if let Err(e) = store.remove(&previous) {
+                log_error(&format!("Failed to remove the session {}: {}", previous, e)).ok();
+            }
+            if let Err(e) = self.undo.rename(&self.session_id) {
+                log_error(&format!("Failed to move the undo journal: {}", e)).ok();
+            }
+        }
+        self.add_output(format!(
+            "💾 Saved session {} to {}",
+            self.session_id,
+            get_config_dir()
+                .join("sessions")
+                .join(format!("{}.json", self.session_id))
+                .display()
+        ));
+    }
+
+    /// Handle `/diff-sessions <a> <b>`
+    pub fn handle_diff_sessions(&mut self, args: &str) {
+        let names: Vec<&str> = args.split_whitespace().collect();
+        let [old_name, new_name] = names.as_slice() else {
+            self.add_output("Usage: /diff-sessions <a> <b> (see /session list)".to_string());
+            return;
+        };
+        // The current session is compared as it is now
+        self.save_session();
+        let store = match store() {
+            Ok(store) => store,
+            Err(e) => {
+                self.add_output(format!("⚠️ Could not open the session store: {}", e));
+                return;
+            }
+        };
+        let mut loaded = Vec::with_capacity(2);
+        for name in [old_name, new_name] {
+            match store.load(name) {
+                Ok(session) => loaded.push(session),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    self.add_output(format!(
+                        "Error: No session named {} (see /session list)",
+                        name
+                    ));
+                    return;
+                }
+                Err(e) => {
+                    self.add_output(format!("⚠️ Could not load {}: {}", name, e));
+                    return;
+                }
+            }
+        }
+        let (old, new) = (&loaded[0], &loaded[1]);
+
+        let describe = |session: &StoredSession| {
+            let models = if session.models_used.is_empty() {
+                "no responses".to_string()
+            } else {
+                session.models_used.join(", ")
+            };
+            format!(
+                "  {} \"{}\" — {} · {}",
+                session.id,
+                session.title,
+                models,
+                format_money(session.cost)
+            )
+        };
+        let diffs = session_diff::compare(
+            &session_diff::conversation(&old.output),
+            &session_diff::conversation(&new.output),
+        );
+        let mut lines = vec![
+            format!(
+                "🔀 Comparing sessions:\n{}\n{}",
+                describe(old),
+                describe(new)
+            ),
+            session_diff::summary(&diffs, &old.id, &new.id),
+        ];
+        for diff in diffs.iter().filter(|diff| !diff.is_same()) {
+            lines.push(String::new());
+            lines.push(diff.render(&old.id, &new.id));
+        }
+        if diffs.iter().all(|diff| diff.is_same()) {
+            lines.push("✅ Both sessions have the same prompts and responses".to_string());
+        }
+        self.add_output(lines.join("\n"));
+    }
+
+    /// List the stored sessions by name
+    fn list_stored_sessions(&mut self) {
+        // The current session is listed as it is now
+        self.save_session();
+        let stored = match store().and_then(|store| store.list()) {
+            Ok(stored) => stored,
+            Err(e) => {
+                self.add_output(format!("⚠️ Could not read the stored sessions: {}", e));
+                return;
+            }
+        };
+        if stored.is_empty() {
+            self.add_output(
+                "🗂️ No stored sessions yet — /session save <name> stores this one".to_string(),
+            );
+            return;
+        }
+        let lines: Vec<String> = stored
+            .iter()
+            .map(|session| {
+                let marker = if session.id == self.session_id {
+                    "●"
+                } else {
+                    " "
+                };
+                let title = if session.title == session.id {
+                    String::new()
+                } else {
+                    format!(" \"{}\"", session.title)
+                };
+                format!(
+                    "{} {}{} — {} · {} message(s) · {}",
+                    marker,
+                    session.id,
+                    title,
+                    session.updated.format("%Y-%m-%d %H:%M"),
+                    session.messages,
+                    format_money(session.cost)
+                )
+            })
+            .collect();
+        self.add_output(format!(
+            "🗂️ {} stored session(s) — /session load <name> resumes one:\n{}",
+            stored.len(),
+            lines.join("\n")
+        ));
+    }
+}
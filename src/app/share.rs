@@ -0,0 +1,96 @@
+//! `/share`: a read-only view of the live session in a browser
+//!
+//! The output pane is published to the share server at most a few times a
+//! second, so streaming responses stay live without sending every token. It
+//! is only copied when its generation shows it changed since.
+
+use super::App;
+use crate::config::get_config;
+use crate::utils::share::{ShareServer, Snapshot};
+use std::time::{Duration, Instant};
+
+/// Shortest time between two published snapshots
+const PUBLISH_INTERVAL: Duration = Duration::from_millis(250);
+
+impl App {
+    /// Handle `/share [stop]`
+    pub async fn handle_share(&mut self, args: &str) {
+        match args {
+            "" | "start" => self.start_share().await,
+            "stop" => match self.share.take() {
+                Some(_) => self.add_output("👀 Stopped sharing the session".to_string()),
+                None => self.add_output("👀 The session is not shared".to_string()),
+            },
+            _ => self.add_output("Error: Usage: /share [stop]".to_string()),
+        }
+    }
+
+    /// Serve the session on the configured address, or show where it is served
+    pub async fn start_share(&mut self) {
+        if let Some(server) = &self.share {
+            let message = format!(
+                "👀 Sharing read-only at {} ({} viewer(s)); /share stop ends it",
+                server.url(),
+                server.viewers()
+            );
+            self.add_output(message);
+            return;
+        }
+        let address = get_config().share.address;
+        match ShareServer::start(&address, self.share_snapshot()).await {
+            Ok(server) => {
+                self.share_generation = self.output_generation;
+                let mut message = format!(
+                    "👀 Sharing this session read-only at {}\n   Anyone with the link can watch; /share stop ends it",
+                    server.url()
+                );
+                if server.local_addr().ip().is_loopback() {
+                    message.push_str(
+                        "\n   Only this machine can connect; set share.address to 0.0.0.0:<port> for teammates",
+                    );
+                }
+                self.share = Some(server);
+                self.add_output(message);
+            }
+            Err(e) => self.add_output(format!("⚠️ Cannot share on {}: {}", address, e)),
+        }
+    }
+
+    /// What viewers see right now
+    fn share_snapshot(&self) -> Snapshot {
+        Snapshot {
+            title: self
+                .session_title
+                .clone()
+                .unwrap_or_else(|| self.session_id.clone()),
+            output: self.output.clone(),
+            busy: self.task_manager.running_count() > 0,
+        }
+    }
+
+    /// Send what changed to the viewers of a shared session
+    pub fn publish_share(&mut self) {
+        let Some(server) = &self.share else {
+            return;
+        };
+        if self.share_published.elapsed() < PUBLISH_INTERVAL {
+            return;
+        }
+        let output = (self.share_generation != self.output_generation).then(|| self.output.clone());
+        let title = self.session_title.as_ref().unwrap_or(&self.session_id);
+        let busy = self.task_manager.running_count() > 0;
+        server.update(|snapshot| {
+            let changed = output.is_some() || snapshot.busy != busy || snapshot.title != *title;
+            if let Some(output) = output {
+                snapshot.output = output;
+            }
+            if snapshot.title != *title {
+                snapshot.title = title.clone();
+            }
+            snapshot.busy = busy;
+            changed
+        });
+        self.share_published = Instant::now();
+        self.share_generation = self.output_generation;
+    }
+}
@@ -0,0 +1,123 @@
+//! Push-to-talk input
+//!
+//! Ctrl+R starts recording from the microphone and pressing it again stops
+//! it. The recording is transcribed in a tracked background task and the
+//! transcript is inserted into the input box at the cursor, ready to edit
+//! and send.
+
+use super::App;
+use crate::ai::AIError;
+use crate::ai::types::TaskStatus;
+use crate::config::{SpeechBackend, get_config};
+use crate::utils::speech::{self, Recording};
+use crate::utils::tasks::TaskType;
+use std::time::Duration;
+
+impl App {
+    /// Start recording, or stop and transcribe the running recording
+    pub fn toggle_recording(&mut self) {
+        match self.recording.take() {
+            Some(recording) => self.transcribe_recording(recording),
+            None => self.start_recording(),
+        }
+    }
+
+    /// How long the current recording has been running, if one is
+    pub fn recording_elapsed(&self) -> Option<Duration> {
+        self.recording.as_ref().map(Recording::elapsed)
+    }
+
+    fn start_recording(&mut self) {
+        let config = get_config().speech;
+        let path = std::env::temp_dir().join(format!("ai-coder-{}.wav", uuid::Uuid::new_v4()));
+        match Recording::start(&config, path) {
+            Ok(recording) => self.recording = Some(recording),
+            Err(e) => self.add_output(format!(
+                "⚠️ {} — set speech.record_command in the config",
+                e
+            )),
+        }
+        self.needs_redraw = true;
+    }
+
+    fn transcribe_recording(&mut self, recording: Recording) {
+        let app_config = get_config();
+        let mut config = app_config.speech;
+        if config.api_key.is_empty() {
+            config.api_key = app_config.ai.openai.api_key;
+        }
+        let task_type = match config.backend {
+            SpeechBackend::Whisper => TaskType::Other,
+            SpeechBackend::OpenAI => TaskType::NetworkRequest,
+        };
+
+        let task_id = self
+            .task_manager
+            .create_task("Transcribe recording".to_string(), task_type);
+        self.task_manager
+            .update_task_status(task_id, TaskStatus::Running);
+        let transcripts = self.transcript_tx.clone();
+
+        let job = async move {
+            // Waiting for the recorder to finish its file may take a moment
+            match tokio::task::spawn_blocking(move || recording.stop()).await {
+                Ok(Ok(path)) => {
+                    let result = speech::transcribe(&config, &path).await;
+                    let _ = std::fs::remove_file(&path);
+                    result
+                }
+                Ok(Err(e)) => Err(AIError::ConfigError(e)),
+                Err(e) => Err(AIError::ConfigError(format!("Recording failed: {}", e))),
+            }
+        };
+        self.spawn_tracked(
+            task_id,
+            transcripts,
+            job,
+            |result| match result {
+                Ok(_) => TaskStatus::Completed,
+                Err(_) => TaskStatus::Failed,
+            },
+            |_| None,
+        );
+        self.needs_redraw = true;
+    }
+
+    /// Insert finished transcripts into the input box
+    pub fn collect_transcripts(&mut self) {
+        while let Ok(result) = self.transcript_rx.try_recv() {
+            match result {
+                Ok(text) if text.is_empty() => {
+                    self.add_output("🎙️ No speech was recognized".to_string())
+                }
+                Ok(text) => self.insert_transcript(&text),
+                Err(e) => self.add_output(format!("⚠️ Transcription failed: {}", e)),
+            }
+        }
+    }
+
+    fn insert_transcript(&mut self, text: &str) {
+        let mut at = self.cursor_position.min(self.input.len());
+        if !self.input.is_char_boundary(at) {
+            at = self.input.len();
+        }
+        let mut insert = text.to_string();
+        if self.input[..at]
+            .chars()
+            .next_back()
+            .is_some_and(|c| !c.is_whitespace())
+        {
+            insert.insert(0, ' ');
+        }
+        if self.input[at..]
+            .chars()
+            .next()
+            .is_some_and(|c| !c.is_whitespace())
+        {
+            insert.push(' ');
+        }
+        self.input.insert_str(at, &insert);
+        self.cursor_position = at + insert.len();
+        self.needs_redraw = true;
+    }
+}
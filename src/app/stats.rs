@@ -0,0 +1,98 @@
+//! `/stats`: counts, usage and latency of the session
+//!
+//! Latency is shown per provider and model as the median and 95th
+//! percentile of the time to the first token and of the whole generation.
+
+use super::App;
+use crate::utils::latency::{self, Percentiles, format_latency};
+use crate::utils::{format_duration, format_money};
+use chrono::Local;
+
+/// `p50 / p95`, or a dash when nothing was measured
+fn percentiles(value: Option<Percentiles>) -> String {
+    match value {
+        Some(value) => format!(
+            "{} / {}",
+            format_latency(value.p50),
+            format_latency(value.p95)
+        ),
+        None => "—".to_string(),
+    }
+}
+
+impl App {
+    /// Handle `/stats`
+    pub fn handle_stats(&mut self) {
+        let stats = &self.stats;
+        let running = (Local::now() - stats.start_time).num_seconds().max(0) as u64;
+        let mut lines = vec![
+            format!(
+                "📊 Session started {} ({} ago)",
+                stats.start_time.format("%H:%M"),
+                format_duration(running)
+            ),
+            format!(
+                "Commands: {} · AI prompts: {} · Bash: {}",
+                stats.command_count, stats.ai_count, stats.bash_count
+            ),
+            format!(
+                "Tokens: {} in, {} out · Cost: {}",
+                stats.prompt_tokens,
+                stats.completion_tokens,
+                format_money(stats.cost)
+            ),
+        ];
+        if !stats.models_used.is_empty() {
+            lines.push(format!("Models: {}", stats.models_used.join(", ")));
+        }
+
+        let summaries = latency::summarize(&stats.latencies);
+        if summaries.is_empty() {
+            lines.push("\n⏱️ No answered prompts yet to measure latency".to_string());
+            self.add_output(lines.join("\n"));
+            return;
+        }
+        let names: Vec<String> = summaries
+            .iter()
+            .map(|summary| format!("{}/{}", summary.provider, summary.model))
+            .collect();
+        let width = names
+            .iter()
+            .map(|name| name.chars().count())
+            .max()
+            .unwrap_or(0);
+        lines.push(format!(
+            "\n⏱️ Latency (p50 / p95)\n  {:<width$}  {:>4}  {:<17}  {}",
+            "",
+            "n",
+            "first token",
+            "total",
+            width = width
+        ));
+        for (summary, name) in summaries.iter().zip(&names) {
+            lines.push(format!(
+                "  {:<width$}  {:>4}  {:<17}  {}",
+                name,
+                summary.exchanges,
+                percentiles(summary.first_token),
+                percentiles(Some(summary.total)),
+                width = width
+            ));
+        }
+        if let Some(last) = stats.latencies.last() {
+            let first_token = last
+                .latency
+                .first_token
+                .map(|duration| format!("{} to the first token, ", format_latency(duration)))
+                .unwrap_or_default();
+            lines.push(format!(
+                "Last prompt: {}{} in total ({}/{})",
+                first_token,
+                format_latency(last.latency.total),
+                last.provider,
+                last.model
+            ));
+        }
+        self.add_output(lines.join("\n"));
+    }
+}
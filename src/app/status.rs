@@ -0,0 +1,38 @@
+//! Clicks on the status bar
+//!
+//! The mode segment switches what plain input does: it is sent to the AI
+//! by default, or run as a bash command after the switch, when a `?` prefix
+//! sends a prompt to the AI instead. The 💰 segment runs `/cost` and the 📁
+//! segment opens the file tree of the current directory.
+
+use super::App;
+use crate::handlers::CommandMode;
+use crate::tui::Tui;
+use crate::ui::status::StatusAction;
+
+impl App {
+    /// Run the action of a clicked status bar segment
+    pub(super) async fn run_status_action(&mut self, action: StatusAction, tui: &mut Tui) {
+        match action {
+            StatusAction::ToggleMode => self.toggle_default_mode(),
+            StatusAction::Cost => self.execute_command("/cost".to_string(), tui).await,
+            StatusAction::FileTree => self.open_file_tree(""),
+        }
+        self.needs_redraw = true;
+    }
+
+    /// Switch plain input between AI prompts and bash commands
+    pub fn toggle_default_mode(&mut self) {
+        self.default_mode = match self.default_mode {
+            CommandMode::Bash => CommandMode::AI,
+            _ => CommandMode::Bash,
+        };
+        self.current_mode = self.default_mode.clone();
+        self.show_toast(match self.default_mode {
+            CommandMode::Bash => {
+                "⌨️ Plain input now runs as bash — prefix ? to ask the AI".to_string()
+            }
+            _ => "⌨️ Plain input is sent to the AI again".to_string(),
+        });
+    }
+}
@@ -0,0 +1,188 @@
+//! Encryption at rest
+//!
+//! With `encryption.enabled`, stored sessions, the prompt library, unsent
+//! prompts and files written by `/export` and `/print` are encrypted with a
+//! key kept in the OS keyring or derived from `AI_CODER_PASSPHRASE`. Files
+//! written before stay readable; `/encrypt` rewrites them encrypted and
+//! `/decrypt <file> [out]` opens an encrypted export.
+//!
+//! Everything else under `~/.ai-coder` stays in plain text, among it the undo
+//! journal, the workspace index, templates, the sync clone, crash reports and
+//! logs; the README lists every such file and why.
+
+use super::App;
+use super::pending::{PendingPrompt, pending_file};
+use super::prompts::library_file;
+use super::sessions::store;
+use crate::config::{KeySource, get_config, get_config_dir};
+use crate::handlers::safe;
+use crate::utils::crypto::{self, Cipher, SALT_LEN};
+use crate::utils::prompts::PromptLibrary;
+use once_cell::sync::Lazy;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Environment variable holding the passphrase
+const PASSPHRASE_VAR: &str = "AI_CODER_PASSPHRASE";
+
+/// The key of this run and where it came from
+type CachedCipher = Option<(KeySource, Arc<Cipher>)>;
+
+/// The key of this run, read or derived once
+static CIPHER: Lazy<Mutex<CachedCipher>> = Lazy::new(|| Mutex::new(None));
+
+/// The cipher for stored data, `None` while encryption is off
+///
+/// Fails rather than falling back to plain text when the key is unavailable.
+pub(super) fn storage_cipher() -> io::Result<Option<Arc<Cipher>>> {
+    let config = get_config().encryption;
+    if !config.enabled {
+        return Ok(None);
+    }
+    load_cipher(config.key_source).map(Some)
+}
+
+fn load_cipher(source: KeySource) -> io::Result<Arc<Cipher>> {
+    let mut cached = CIPHER.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((_, cipher)) = cached.as_ref().filter(|(cached, _)| *cached == source) {
+        return Ok(cipher.clone());
+    }
+    let cipher = match source {
+        KeySource::Keyring => Cipher::from_key(crypto::keyring_key().map_err(io::Error::other)?),
+        KeySource::Passphrase => {
+            let passphrase = std::env::var(PASSPHRASE_VAR).unwrap_or_default();
+            Cipher::with_salt(&passphrase, passphrase_salt()?)
+                .map_err(|e| io::Error::other(format!("{} (set {})", e, PASSPHRASE_VAR)))?
+        }
+    };
+    let cipher = Arc::new(cipher);
+    *cached = Some((source, cipher.clone()));
+    Ok(cipher)
+}
+
+/// Salt of the passphrase key, shared by all stored files so the key is
+/// derived once per run
+fn passphrase_salt() -> io::Result<[u8; SALT_LEN]> {
+    let path = get_config_dir().join("encryption.salt");
+    if let Ok(Ok(salt)) = fs::read(&path).map(<[u8; SALT_LEN]>::try_from) {
+        return Ok(salt);
+    }
+    let salt = crypto::new_salt();
+    fs::create_dir_all(get_config_dir())?;
+    fs::write(&path, salt)?;
+    Ok(salt)
+}
+
+/// Write an export, encrypted with a `.enc` suffix while encryption is on
+///
+/// Returns the path written.
+pub(super) fn write_export(path: &Path, data: &[u8]) -> io::Result<PathBuf> {
+    safe::check("Writing files").map_err(io::Error::other)?;
+    let cipher = storage_cipher()?;
+    let path = match cipher {
+        Some(_) => PathBuf::from(format!("{}.enc", path.display())),
+        None => path.to_path_buf(),
+    };
+    crypto::write_file(&path, data, cipher.as_deref())?;
+    Ok(path)
+}
+
+impl App {
+    /// Warn on startup when encryption is on but its key cannot be had
+    pub fn check_encryption(&mut self) {
+        if let Err(e) = storage_cipher() {
+            self.add_output(format!(
+                "⚠️ Encryption is on but its key is unavailable: {}\n   Sessions and prompts are not saved until this is fixed.",
+                e
+            ));
+        }
+    }
+
+    /// Handle `/encrypt`: rewrite the stored sessions, prompt library and
+    /// unsent prompts encrypted
+    pub fn handle_encrypt(&mut self) {
+        let config = get_config().encryption;
+        if !config.enabled {
+            self.add_output(
+                "🔓 Encryption is off — set encryption.enabled: true in the config first"
+                    .to_string(),
+            );
+            return;
+        }
+        match encrypt_stored() {
+            Ok(sessions) => self.add_output(format!(
+                "🔒 {} session(s), the prompt library and unsent prompts are stored encrypted (key from {})",
+                sessions,
+                match config.key_source {
+                    KeySource::Keyring => "the OS keyring",
+                    KeySource::Passphrase => PASSPHRASE_VAR,
+                }
+            )),
+            Err(e) => self.add_output(format!("⚠️ Could not encrypt the stored data: {}", e)),
+        }
+    }
+
+    /// Handle `/decrypt <file> [out]`: write the plain text of an encrypted export
+    pub fn handle_decrypt(&mut self, args: &str) {
+        let mut words = args.split_whitespace();
+        let Some(file) = words.next() else {
+            self.add_output("Error: Usage: /decrypt <file> [out]".to_string());
+            return;
+        };
+        let input = self.current_dir.join(file);
+        let output = match words.next() {
+            Some(out) => self.current_dir.join(out),
+            None => match file.strip_suffix(".enc") {
+                Some(plain) => self.current_dir.join(plain),
+                None => {
+                    self.add_output(format!(
+                        "Error: {} has no .enc suffix; name the output: /decrypt <file> <out>",
+                        file
+                    ));
+                    return;
+                }
+            },
+        };
+        if safe::is_enabled() {
+            self.add_output(safe::blocked_message("Writing files"));
+            return;
+        }
+        if output == input {
+            self.add_output("Error: the output must differ from the encrypted file".to_string());
+            return;
+        }
+
+        let result = load_cipher(get_config().encryption.key_source)
+            .and_then(|cipher| crypto::read_file(&input, Some(&cipher)))
+            .and_then(|data| fs::write(&output, data));
+        match result {
+            Ok(()) => self.add_output(format!(
+                "🔓 Decrypted {} to {}",
+                input.display(),
+                output.display()
+            )),
+            Err(e) => self.add_output(format!("⚠️ Could not decrypt {}: {}", input.display(), e)),
+        }
+    }
+}
+
+/// Save every stored session, the prompt library and the unsent prompts
+/// again, now encrypted
+fn encrypt_stored() -> io::Result<usize> {
+    let store = store()?;
+    let sessions = store.list()?;
+    for session in &sessions {
+        store.save(session)?;
+    }
+    let cipher = storage_cipher()?;
+    let path = library_file();
+    if path.exists() {
+        PromptLibrary::load_with(&path, cipher.as_deref())?.save_with(&path, cipher.as_deref())?;
+    }
+    let path = pending_file();
+    let prompts = PendingPrompt::load_all_with(&path, cipher.as_deref())?;
+    PendingPrompt::save_all_with(&path, &prompts, cipher.as_deref())?;
+    Ok(sessions.len())
+}
@@ -0,0 +1,182 @@
+//! Live preview of chat responses while they stream in
+//!
+//! The clients send the text of a response as it is generated. Until the
+//! response is complete its text replaces the spinner line of the prompt, with
+//! a cursor at the end. The preview is removed again once the complete
+//! response is shown, since post-processing and code block execution may
+//! change it. Reasoning is not shown while it streams.
+
+use super::App;
+use crate::ai::{ChunkSender, StreamChunk};
+use crate::config::get_config;
+use crate::handlers::postprocess::streaming_preview;
+use crate::utils::TaskId;
+use tokio::sync::mpsc::{UnboundedReceiver, unbounded_channel};
+
+/// Cursor marking the end of a response that is still streaming
+const STREAM_CURSOR: &str = "▌";
+
+/// Shown while a model is only reasoning
+const REASONING_NOTE: &str = "💭 Reasoning…";
+
+/// A response shown in the output pane while it is generated
+#[derive(Debug)]
+pub struct StreamingResponse {
+    rx: UnboundedReceiver<StreamChunk>,
+    /// Output line the preview starts at, the spinner line of the prompt
+    first_line: usize,
+    /// Lines last written to the output, to notice when they were replaced
+    written: Vec<String>,
+    /// Answer received so far
+    text: String,
+    /// Whether any reasoning arrived
+    reasoning: bool,
+    /// Whether the spinner still animates the first line
+    has_spinner: bool,
+}
+
+impl StreamingResponse {
+    /// Lines showing the response received so far
+    fn preview(&self) -> Vec<String> {
+        let steps = get_config().postprocess;
+        let visible = streaming_preview(&steps, &self.text);
+        if visible.is_empty() {
+            let thinking = self.reasoning || !self.text.trim().is_empty();
+            return if thinking {
+                vec![REASONING_NOTE.to_string()]
+            } else {
+                Vec::new()
+            };
+        }
+        let mut lines: Vec<String> = visible.lines().map(String::from).collect();
+        if let Some(last) = lines.last_mut() {
+            last.push_str(STREAM_CURSOR);
+        }
+        lines
+    }
+}
+
+impl App {
+    /// Open a preview for the chat prompt starting now, if streaming is enabled
+    ///
+    /// Must be called right after the spinner line of the prompt was added.
+    pub(super) fn open_stream(&mut self, task_id: TaskId) -> Option<ChunkSender> {
+        let config = get_config();
+        // Responses moderation may withhold are only shown once checked
+        if !config.ui.stream_responses || config.moderation.holds_responses() {
+            return None;
+        }
+        // The new prompt takes over the spinner
+        for stream in self.streams.values_mut() {
+            stream.has_spinner = false;
+        }
+        let (tx, rx) = unbounded_channel();
+        self.streams.insert(
+            task_id,
+            StreamingResponse {
+                rx,
+                first_line: self.output_lines.len().saturating_sub(1),
+                written: Vec::new(),
+                text: String::new(),
+                reasoning: false,
+                has_spinner: true,
+            },
+        );
+        Some(tx)
+    }
+
+    /// Add the chunks that arrived to the previews of streaming responses
+    pub fn collect_stream_chunks(&mut self) {
+        let task_ids: Vec<TaskId> = self.streams.keys().copied().collect();
+        for task_id in task_ids {
+            let Some(stream) = self.streams.get_mut(&task_id) else {
+                continue;
+            };
+            let mut received = false;
+            while let Ok(chunk) = stream.rx.try_recv() {
+                match chunk {
+                    StreamChunk::Content(text) => stream.text.push_str(&text),
+                    StreamChunk::Reasoning(_) => stream.reasoning = true,
+                }
+                received = true;
+            }
+            if !received {
+                continue;
+            }
+            if stream.has_spinner {
+                stream.has_spinner = false;
+                self.spinner_rx = None;
+            }
+            let lines = stream.preview();
+            if !self.write_stream_lines(task_id, lines) {
+                // The output was replaced, e.g. by /clear: stop previewing
+                self.streams.remove(&task_id);
+            }
+        }
+    }
+
+    /// Remove the preview of a response before the complete one is shown
+    pub(super) fn close_stream(&mut self, task_id: TaskId) {
+        if self.streams.contains_key(&task_id) {
+            self.write_stream_lines(task_id, vec![String::new()]);
+            self.streams.remove(&task_id);
+        }
+    }
+
+    /// Keep the text of a response that will not complete, without its cursor
+    pub(super) fn abandon_stream(&mut self, task_id: TaskId) {
+        let Some(stream) = self.streams.get(&task_id) else {
+            return;
+        };
+        let mut lines = stream.written.clone();
+        if let Some(last) = lines.last_mut()
+            && let Some(stripped) = last.strip_suffix(STREAM_CURSOR)
+        {
+            *last = stripped.to_string();
+        }
+        if !lines.is_empty() {
+            self.write_stream_lines(task_id, lines);
+        }
+        self.streams.remove(&task_id);
+    }
+
+    /// Replace the lines of a preview, returning false if they are gone
+    fn write_stream_lines(&mut self, task_id: TaskId, lines: Vec<String>) -> bool {
+        let Some(stream) = self.streams.get_mut(&task_id) else {
+            return false;
+        };
+        let start = stream.first_line;
+        // Before the first chunk the preview is the spinner line
+        let end = start + stream.written.len().max(1);
+        let intact = match self.output_lines.get(start..end) {
+            Some([spinner]) if stream.written.is_empty() => spinner.chars().count() <= 1,
+            Some(current) => current == stream.written.as_slice(),
+            None => false,
+        };
+        if !intact {
+            return false;
+        }
+
+        // Keep one line so that the range stays valid for the next chunk
+        let lines = if lines.is_empty() {
+            vec![String::new()]
+        } else {
+            lines
+        };
+        stream.written = lines.clone();
+        let added = lines.len() as isize - (end - start) as isize;
+        self.output_lines.splice(start..end, lines);
+
+        // Previews further down move with the lines
+        for other in self.streams.values_mut() {
+            if other.first_line > start {
+                other.first_line = other.first_line.saturating_add_signed(added);
+            }
+        }
+        self.output = self.output_lines.join("\n");
+        self.output.push('\n');
+        self.output_generation += 1;
+        self.request_redraw();
+        true
+    }
+}
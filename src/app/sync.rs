@@ -0,0 +1,295 @@
+//! `/sync`: encrypted sync of sessions between machines
+//!
+//! `/sync push` uploads the stored sessions, the prompt library and, with
+//! `sync.include_config`, the configuration; `/sync pull` on another machine
+//! downloads what changed and opens the session browser so a conversation
+//! can be continued there. Both run as tracked background tasks.
+
+use super::App;
+use super::prompts::library_file;
+use super::sessions::store;
+use super::storage::storage_cipher;
+use crate::ai::types::TaskStatus;
+use crate::config::{AppConfig, SyncConfig, get_config, get_config_dir, update_config};
+use crate::handlers::safe;
+use crate::utils::crypto::{self, Cipher};
+use crate::utils::log_error;
+use crate::utils::prompts::PromptLibrary;
+use crate::utils::sessions::StoredSession;
+use crate::utils::sync::{self, Remote, SyncError, SyncFile};
+use crate::utils::tasks::TaskType;
+use chrono::Local;
+use std::io;
+
+/// Name of the prompt library among the synced files
+const PROMPTS_FILE: &str = "prompts.json";
+
+/// Name of the configuration among the synced files
+const CONFIG_FILE: &str = "config.yaml";
+
+/// Outcome of a push or pull
+pub(super) enum SyncResult {
+    /// Number of files uploaded
+    Pushed(Result<usize, SyncError>),
+    /// Files that differ from the local ones
+    Pulled(Result<Vec<SyncFile>, SyncError>),
+}
+
+impl SyncResult {
+    fn failed(&self) -> bool {
+        matches!(
+            self,
+            SyncResult::Pushed(Err(_)) | SyncResult::Pulled(Err(_))
+        )
+    }
+}
+
+/// Name of a stored session among the synced files
+fn session_file(id: &str) -> String {
+    format!("session-{}.json", id)
+}
+
+/// The files synced from this machine
+fn local_files(include_config: bool) -> io::Result<Vec<SyncFile>> {
+    let mut files = Vec::new();
+    for session in store()?.list()? {
+        let data = serde_json::to_vec(&session).map_err(io::Error::other)?;
+        files.push(SyncFile::new(session_file(&session.id), data));
+    }
+    match crypto::read_file(&library_file(), storage_cipher()?.as_deref()) {
+        Ok(data) => files.push(SyncFile::new(PROMPTS_FILE, data)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    if include_config {
+        // Where and how to sync is decided per machine
+        let mut config = get_config();
+        config.sync = SyncConfig::default();
+        let data = serde_yaml::to_string(&config).map_err(io::Error::other)?;
+        files.push(SyncFile::new(CONFIG_FILE, data.into_bytes()));
+    }
+    Ok(files)
+}
+
+/// Add a pulled prompt library to the local one
+fn merge_prompts(data: &[u8]) -> io::Result<()> {
+    let pulled: PromptLibrary = serde_json::from_slice(data).map_err(io::Error::other)?;
+    let path = library_file();
+    let cipher = storage_cipher()?;
+    let mut library = PromptLibrary::load_with(&path, cipher.as_deref())?;
+    library.merge(pulled, Local::now());
+    library.save_with(&path, cipher.as_deref())
+}
+
+impl App {
+    /// Handle `/sync [status|push|pull]`
+    pub fn handle_sync(&mut self, args: &str) {
+        let pull = match args {
+            "" | "status" => {
+                self.show_sync_status();
+                return;
+            }
+            "push" => false,
+            "pull" => true,
+            _ => {
+                self.add_output("Error: Usage: /sync [status|push|pull]".to_string());
+                return;
+            }
+        };
+        if safe::is_enabled() {
+            self.add_output(safe::blocked_message("Syncing"));
+            return;
+        }
+        if self.syncing {
+            self.add_output("🔄 A sync is already running".to_string());
+            return;
+        }
+
+        let config = get_config().sync;
+        let remote = match Remote::from_config(&config, &get_config_dir().join("sync").join("git"))
+        {
+            Ok(remote) => remote,
+            Err(e) => {
+                self.add_output(format!("⚠️ Sync is not set up: {}", e));
+                return;
+            }
+        };
+        let Some(passphrase) = config.passphrase() else {
+            self.add_output(
+                "⚠️ Set sync.passphrase or AI_CODER_SYNC_PASSPHRASE to encrypt synced data"
+                    .to_string(),
+            );
+            return;
+        };
+        // The current session goes along with a push and is compared on a pull
+        self.save_session();
+        let files = match local_files(config.include_config) {
+            Ok(files) => files,
+            Err(e) => {
+                self.add_output(format!("⚠️ Could not read the data to sync: {}", e));
+                return;
+            }
+        };
+
+        let name = if pull { "Sync pull" } else { "Sync push" };
+        let task_id = self
+            .task_manager
+            .create_task(name.to_string(), TaskType::Other);
+        self.task_manager
+            .update_task_status(task_id, TaskStatus::Running);
+        let results = self.sync_tx.clone();
+        self.syncing = true;
+        self.add_output(format!(
+            "🔄 {} {}…",
+            if pull { "Pulling from" } else { "Pushing to" },
+            remote.describe()
+        ));
+
+        let job = async move {
+            match (Cipher::new(&passphrase), pull) {
+                (Ok(cipher), true) => {
+                    let local = sync::local_index(&files);
+                    SyncResult::Pulled(sync::pull(&remote, &cipher, &local).await)
+                }
+                (Ok(cipher), false) => {
+                    SyncResult::Pushed(sync::push(&remote, &cipher, &files).await)
+                }
+                (Err(e), true) => SyncResult::Pulled(Err(e.into())),
+                (Err(e), false) => SyncResult::Pushed(Err(e.into())),
+            }
+        };
+        self.spawn_tracked(
+            task_id,
+            results,
+            job,
+            |result| {
+                if result.failed() {
+                    TaskStatus::Failed
+                } else {
+                    TaskStatus::Completed
+                }
+            },
+            // Pulled files are not applied; what was pushed is still reported
+            |result| match result {
+                SyncResult::Pulled(_) => Some(SyncResult::Pulled(Err(SyncError::Cancelled))),
+                pushed => Some(pushed),
+            },
+        );
+    }
+
+    /// Report finished pushes and apply pulled files
+    pub fn collect_sync(&mut self) {
+        while let Ok(result) = self.sync_rx.try_recv() {
+            self.syncing = false;
+            match result {
+                SyncResult::Pushed(Ok(0)) => {
+                    self.add_output("✅ Sync: the remote is up to date".to_string())
+                }
+                SyncResult::Pushed(Ok(pushed)) => {
+                    self.add_output(format!("✅ Sync: pushed {} file(s)", pushed))
+                }
+                SyncResult::Pulled(Ok(files)) => self.apply_pulled(files),
+                SyncResult::Pushed(Err(e)) | SyncResult::Pulled(Err(e)) => {
+                    self.add_output(format!("⚠️ Sync failed: {}", e))
+                }
+            }
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Merge pulled files into the local data
+    fn apply_pulled(&mut self, files: Vec<SyncFile>) {
+        let store = match store() {
+            Ok(store) => store,
+            Err(e) => {
+                self.add_output(format!("⚠️ Sync: could not store the pulled data: {}", e));
+                return;
+            }
+        };
+        let mut sessions = 0;
+        let mut notes = Vec::new();
+        for file in files {
+            if file.name == PROMPTS_FILE {
+                match merge_prompts(&file.data) {
+                    Ok(()) => notes.push("merged the prompt library".to_string()),
+                    Err(e) => notes.push(format!("skipped the prompt library ({})", e)),
+                }
+            } else if file.name == CONFIG_FILE {
+                if !get_config().sync.include_config {
+                    continue;
+                }
+                match self.replace_config(&file.data) {
+                    Ok(()) => notes.push("updated the configuration".to_string()),
+                    Err(e) => notes.push(format!("skipped the configuration ({})", e)),
+                }
+            } else if let Ok(session) = serde_json::from_slice::<StoredSession>(&file.data) {
+                if file.name != session_file(&session.id) {
+                    continue;
+                }
+                // The copy changed last wins
+                let newer = store
+                    .load(&session.id)
+                    .map_or(true, |local| session.updated > local.updated);
+                if !newer {
+                    continue;
+                }
+                if let Err(e) = store.save(&session) {
+                    log_error(&format!("Failed to save a synced session: {}", e)).ok();
+                    continue;
+                }
+                sessions += 1;
+            }
+        }
+
+        if sessions == 0 && notes.is_empty() {
+            self.add_output("✅ Sync: nothing new to pull".to_string());
+            return;
+        }
+        if sessions > 0 {
+            notes.insert(0, format!("pulled {} session(s)", sessions));
+        }
+        self.add_output(format!("✅ Sync: {}", notes.join(", ")));
+        if sessions > 0 {
+            self.handle_sessions("");
+        }
+    }
+
+    fn replace_config(&mut self, data: &[u8]) -> io::Result<()> {
+        let mut config: AppConfig = serde_yaml::from_slice(data).map_err(io::Error::other)?;
+        config.sync = get_config().sync;
+        update_config(config)?;
+        let _ = self.ai_handler.update_client();
+        self.apply_task_limit();
+        Ok(())
+    }
+
+    /// Show where data is synced to and whether it can be encrypted
+    fn show_sync_status(&mut self) {
+        let config = get_config().sync;
+        let remote = match Remote::from_config(&config, &get_config_dir().join("sync").join("git"))
+        {
+            Ok(remote) => remote,
+            Err(e) => {
+                self.add_output(format!("🔄 Sync is not set up: {}", e));
+                return;
+            }
+        };
+        let passphrase = if !config.passphrase.is_empty() {
+            "from sync.passphrase"
+        } else if config.passphrase().is_some() {
+            "from AI_CODER_SYNC_PASSPHRASE"
+        } else {
+            "not set"
+        };
+        self.add_output(format!(
+            "🔄 Sync to {}\n   Passphrase: {}\n   Synced: sessions, prompt library{}",
+            remote.describe(),
+            passphrase,
+            if config.include_config {
+                ", configuration"
+            } else {
+                ""
+            }
+        ));
+    }
+}
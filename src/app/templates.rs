@@ -0,0 +1,182 @@
+//! `/template`: prompt templates and the team repository
+//!
+//! `/template update` clones or pulls the repository in `templates.team_url`
+//! in the background; the other subcommands read the templates from disk
+//! each time, so edited files apply without a restart.
+
+use super::{AIPurpose, App};
+use crate::ai::types::TaskStatus;
+use crate::config::{get_config, get_config_dir, update_field};
+use crate::handlers::safe;
+use crate::utils::tasks::TaskType;
+use crate::utils::templates::{self, Template};
+use std::path::PathBuf;
+
+/// Directory of the templates
+pub(super) fn templates_dir() -> PathBuf {
+    get_config_dir().join("templates")
+}
+
+/// One line of the listing
+fn template_line(template: &Template) -> String {
+    match &template.description {
+        Some(description) => format!("  {} — {}", template.name, description),
+        None => format!("  {}", template.name),
+    }
+}
+
+impl App {
+    /// Handle `/template [list|show <name>|update|source <url> [branch]|<name> [text]]`
+    pub fn handle_template(&mut self, args: &str) {
+        let (sub, rest) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+        let rest = rest.trim();
+        match sub {
+            "" | "list" => self.list_templates(),
+            "update" => self.update_team_templates(),
+            "source" => self.set_team_source(rest),
+            "show" => match templates::find(&templates::load(&templates_dir()), rest) {
+                Some(template) => {
+                    self.add_output(format!("📄 {}\n{}", template.name, template.body))
+                }
+                None => self.add_output(format!("Error: No template named '{}'", rest)),
+            },
+            name => match templates::find(&templates::load(&templates_dir()), name) {
+                Some(template) => {
+                    let prompt = template.render(rest);
+                    self.remember_prompt(&prompt);
+                    self.send_prompt_from(prompt, AIPurpose::Chat, Some(template.name.clone()));
+                }
+                None => self.add_output(format!(
+                    "Error: No template named '{}'; /template lists them",
+                    name
+                )),
+            },
+        }
+    }
+
+    fn list_templates(&mut self) {
+        let dir = templates_dir();
+        let all = templates::load(&dir);
+        let commands = templates::team_commands(&dir);
+        let mut lines = Vec::new();
+        if all.is_empty() {
+            lines.push(format!(
+                "📄 No templates yet. Add .md files to {}",
+                dir.display()
+            ));
+        } else {
+            lines.push("📄 Templates (use with /template <name> [text]):".to_string());
+            lines.extend(all.iter().map(template_line));
+        }
+        if !commands.is_empty() {
+            lines.push("👥 Team commands:".to_string());
+            lines.extend(
+                commands
+                    .iter()
+                    .map(|(name, command)| format!("  /{} → {}", name, command)),
+            );
+        }
+        let config = get_config().templates;
+        if config.team_url.is_empty() {
+            lines.push("Share templates with a team: /template source <git-url>".to_string());
+        } else {
+            lines.push(format!(
+                "Team repository: {} — /template update pulls it",
+                config.team_url
+            ));
+        }
+        self.add_output(lines.join("\n"));
+    }
+
+    fn set_team_source(&mut self, args: &str) {
+        let words: Vec<&str> = args.split_whitespace().collect();
+        let (url, branch) = match words.as_slice() {
+            [url] => (url.to_string(), String::new()),
+            [url, branch] => (url.to_string(), branch.to_string()),
+            _ => {
+                self.add_output("Error: Usage: /template source <git-url> [branch]".to_string());
+                return;
+            }
+        };
+        let result = update_field(|c| {
+            c.templates.team_url = url.clone();
+            c.templates.team_branch = branch.clone();
+        });
+        match result {
+            Ok(()) => self.add_output(format!(
+                "✅ Team templates come from {}; /template update fetches them",
+                url
+            )),
+            Err(e) => self.add_output(format!("Error: Failed to update config: {}", e)),
+        }
+    }
+
+    fn update_team_templates(&mut self) {
+        let config = get_config().templates;
+        if config.team_url.is_empty() {
+            self.add_output(
+                "⚠️ No team repository; set one with /template source <git-url>".to_string(),
+            );
+            return;
+        }
+        if safe::is_enabled() {
+            self.add_output(safe::blocked_message("Updating templates"));
+            return;
+        }
+        if self.templates_updating {
+            self.add_output("👥 The team templates are already being updated".to_string());
+            return;
+        }
+
+        let task_id = self
+            .task_manager
+            .create_task("Team templates".to_string(), TaskType::Other);
+        self.task_manager
+            .update_task_status(task_id, TaskStatus::Running);
+        let results = self.template_tx.clone();
+        self.templates_updating = true;
+        self.add_output(format!(
+            "👥 Updating team templates from {}…",
+            config.team_url
+        ));
+
+        let dest = templates::team_dir(&templates_dir());
+        self.spawn_tracked(
+            task_id,
+            results,
+            async move { templates::update_team(&config.team_url, &config.team_branch, &dest).await },
+            |result| {
+                if result.is_ok() {
+                    TaskStatus::Completed
+                } else {
+                    TaskStatus::Failed
+                }
+            },
+            // Still delivered, so that another update can start
+            |_| Some(Err("cancelled".to_string())),
+        );
+    }
+
+    /// Report finished team template updates
+    pub fn collect_template_updates(&mut self) {
+        while let Ok(result) = self.template_rx.try_recv() {
+            self.templates_updating = false;
+            match result {
+                Ok(commit) => {
+                    let dir = templates_dir();
+                    let count = templates::load(&dir)
+                        .iter()
+                        .filter(|template| template.is_team())
+                        .count();
+                    let commands = templates::team_commands(&dir).len();
+                    self.add_output(format!(
+                        "✅ Team templates at {}: {} template(s), {} command(s)",
+                        commit, count, commands
+                    ));
+                }
+                Err(e) => self.add_output(format!("⚠️ Team templates not updated: {}", e)),
+            }
+            self.needs_redraw = true;
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Keeping the terminal title and taskbar progress up to date
+//!
+//! The title is saved on the first change and brought back on exit, when
+//! the progress is cleared. Sequences are only written when what they show
+//! changes.
+
+use super::App;
+use crate::config::get_config;
+use crate::ui::title::{self, POP_TITLE, PUSH_TITLE, TaskbarState};
+use crate::utils::log_error;
+use std::io::{self, Write};
+
+/// What the terminal was last told to show
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct TerminalStatus {
+    title: Option<String>,
+    progress: Option<TaskbarState>,
+}
+
+/// Write escape sequences straight to the terminal
+fn emit(sequences: &str) {
+    let mut stdout = io::stdout();
+    if let Err(e) = stdout
+        .write_all(sequences.as_bytes())
+        .and_then(|_| stdout.flush())
+    {
+        log_error(&format!("Failed to update the terminal title: {}", e)).ok();
+    }
+}
+
+impl App {
+    /// Show the directory and busy state in the title and the progress of
+    /// generations in the taskbar
+    pub fn update_terminal_status(&mut self) {
+        let config = get_config().ui;
+        let state = title::taskbar_state(&self.task_manager.all_tasks());
+        let busy = matches!(
+            state,
+            TaskbarState::Indeterminate | TaskbarState::Progress(_)
+        );
+        let progress =
+            title::progress_enabled(config.taskbar_progress, |name| std::env::var(name).ok())
+                .then_some(state);
+        let wanted = TerminalStatus {
+            title: config
+                .terminal_title
+                .then(|| title::title(&self.current_dir, busy)),
+            progress,
+        };
+
+        let mut sequences = String::new();
+        if wanted.title != self.terminal_status.title {
+            match &wanted.title {
+                Some(title) => {
+                    if self.terminal_status.title.is_none() {
+                        sequences.push_str(PUSH_TITLE);
+                    }
+                    sequences.push_str(&title::title_sequence(title));
+                }
+                None => sequences.push_str(POP_TITLE),
+            }
+        }
+        if wanted.progress != self.terminal_status.progress {
+            let state = wanted.progress.unwrap_or(TaskbarState::Hidden);
+            sequences.push_str(&state.sequence());
+        }
+        if !sequences.is_empty() {
+            emit(&sequences);
+        }
+        self.terminal_status = wanted;
+    }
+
+    /// Clear the progress and bring back the title the terminal had before
+    pub fn restore_terminal_status(&mut self) {
+        let mut sequences = String::new();
+        if self.terminal_status.progress.is_some() {
+            sequences.push_str(&TaskbarState::Hidden.sequence());
+        }
+        if self.terminal_status.title.is_some() {
+            sequences.push_str(POP_TITLE);
+        }
+        if !sequences.is_empty() {
+            emit(&sequences);
+        }
+        self.terminal_status = TerminalStatus::default();
+    }
+}
@@ -0,0 +1,41 @@
+//! Short-lived notices
+//!
+//! A toast is a one-line message shown in the top-right corner of the output
+//! for a few seconds, for events worth noticing without scrolling, such as
+//! the session cost crossing a threshold.
+
+use super::App;
+use std::time::{Duration, Instant};
+
+/// How long a toast stays on screen
+const TOAST_DURATION: Duration = Duration::from_secs(6);
+
+/// A notice shown over the output for a few seconds
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub message: String,
+    pub shown_at: Instant,
+}
+
+impl App {
+    /// Show a toast, replacing any that is still visible
+    pub fn show_toast(&mut self, message: String) {
+        self.toast = Some(Toast {
+            message,
+            shown_at: Instant::now(),
+        });
+        self.needs_redraw = true;
+    }
+
+    /// Remove the toast once it has been shown long enough
+    pub(super) fn expire_toast(&mut self) {
+        if self
+            .toast
+            .as_ref()
+            .is_some_and(|toast| toast.shown_at.elapsed() >= TOAST_DURATION)
+        {
+            self.toast = None;
+            self.needs_redraw = true;
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Background jobs tracked in the task list
+//!
+//! Commands that work in the background create a task, run the job with
+//! [`App::spawn_tracked`] and pick its result up from a channel in the main
+//! loop. How the job ended is recorded in one place, so a task cancelled
+//! from the tasks popup stays cancelled whatever the job returns.
+
+use super::App;
+use crate::ai::types::TaskStatus;
+use crate::utils::{TaskId, TaskManager};
+use std::future::Future;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::task::AbortHandle;
+
+impl App {
+    /// Run `job` for task `task_id` and deliver its result on `results`
+    ///
+    /// `status` says how the job ended. When the task was cancelled while the
+    /// job ran, its status is left alone and `on_cancel` turns the result
+    /// into the one to deliver, or `None` to drop it.
+    pub fn spawn_tracked<T, Fut>(
+        &mut self,
+        task_id: TaskId,
+        results: UnboundedSender<T>,
+        job: Fut,
+        status: fn(&T) -> TaskStatus,
+        on_cancel: fn(T) -> Option<T>,
+    ) -> AbortHandle
+    where
+        T: Send + 'static,
+        Fut: Future<Output = T> + Send + 'static,
+    {
+        let task_manager = self.task_manager.clone();
+        let task = tokio::spawn(async move {
+            let result = job.await;
+            if is_cancelled(&task_manager, task_id) {
+                if let Some(result) = on_cancel(result) {
+                    let _ = results.send(result);
+                }
+                return;
+            }
+            let status = status(&result);
+            // Deliver the result before the status update wakes the main loop
+            let _ = results.send(result);
+            task_manager.update_task_status(task_id, status);
+        });
+        let abort = task.abort_handle();
+        self.background_tasks.push(task);
+        abort
+    }
+}
+
+/// Whether the task was cancelled from the tasks popup or with Esc
+pub(super) fn is_cancelled(task_manager: &TaskManager, task_id: TaskId) -> bool {
+    task_manager
+        .get_task(task_id)
+        .is_none_or(|task| task.status == TaskStatus::Cancelled)
+}
@@ -0,0 +1,64 @@
+//! `/tree`: the file tree popup
+//!
+//! Lists a directory, the current one by default, with its subdirectories
+//! first. **Up**/**Down** choose, **Enter** or **Right** expands a
+//! directory and opens a file in the editor, **Left** collapses and **Esc**
+//! closes the tree.
+
+use super::App;
+use crate::handlers::editor::FileLocation;
+use crate::tui::Tui;
+use crate::utils::tree::FileTree;
+use crossterm::event::{KeyCode, KeyEvent};
+
+impl App {
+    /// Handle `/tree [dir]`
+    pub fn open_file_tree(&mut self, args: &str) {
+        let root = self.current_dir.join(args);
+        if !root.is_dir() {
+            self.add_output(format!("Error: No such directory: {}", args));
+            return;
+        }
+        let tree = FileTree::new(&root);
+        if tree.entries.is_empty() {
+            self.add_output(format!("🌳 {} is empty", root.display()));
+            return;
+        }
+        self.file_tree = Some(tree);
+        self.needs_redraw = true;
+    }
+
+    /// Handle a key while the file tree is open
+    pub fn handle_file_tree_key(&mut self, key: KeyEvent, tui: &mut Tui) {
+        let Some(tree) = self.file_tree.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Up => tree.move_by(-1),
+            KeyCode::Down => tree.move_by(1),
+            KeyCode::PageUp => tree.move_by(-10),
+            KeyCode::PageDown => tree.move_by(10),
+            KeyCode::Home => tree.selected = 0,
+            KeyCode::End => tree.move_by(isize::MAX),
+            KeyCode::Left => tree.collapse(),
+            KeyCode::Enter | KeyCode::Right => match tree.selected_entry() {
+                Some(entry) if entry.is_dir => tree.toggle(),
+                Some(entry) => {
+                    let path = tree.root.join(&entry.path);
+                    let path = path.strip_prefix(&self.current_dir).unwrap_or(&path);
+                    let location = FileLocation {
+                        path: path.to_string_lossy().to_string(),
+                        line: 1,
+                        column: None,
+                    };
+                    self.file_tree = None;
+                    self.open_location(&location, tui);
+                }
+                None => {}
+            },
+            KeyCode::Esc | KeyCode::Char('q') => self.file_tree = None,
+            _ => {}
+        }
+        self.needs_redraw = true;
+    }
+}
@@ -0,0 +1,87 @@
+//! Reading responses aloud
+//!
+//! With `/speak on` every chat response is read aloud once it has arrived,
+//! without its code blocks. A new response cuts off the one being read;
+//! Ctrl+X or `/speak stop` silences it.
+
+use super::App;
+use crate::config::{get_config, update_field};
+use crate::utils::tts;
+
+impl App {
+    /// Handle `/speak [on|off|stop]`
+    pub fn handle_speak(&mut self, args: &str) {
+        let enabled = match args {
+            "" => !get_config().tts.enabled,
+            "on" => true,
+            "off" => false,
+            "stop" => {
+                self.stop_speaking();
+                return;
+            }
+            _ => {
+                self.add_output("Error: Usage: /speak [on|off|stop]".to_string());
+                return;
+            }
+        };
+
+        if !enabled {
+            self.speaker.stop();
+        }
+        match update_field(|config| config.tts.enabled = enabled) {
+            Ok(()) if enabled => {
+                self.add_output("🔊 Responses are read aloud — Ctrl+X stops reading".to_string())
+            }
+            Ok(()) => self.add_output("✅ Reading responses aloud is off".to_string()),
+            Err(e) => self.add_output(format!("⚠️ Failed to save configuration: {}", e)),
+        }
+    }
+
+    /// Stop reading the current response aloud
+    pub fn stop_speaking(&mut self) {
+        if self.speaker.stop() {
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Whether a response is being read aloud
+    pub fn is_speaking(&self) -> bool {
+        self.speaker.is_speaking()
+    }
+
+    /// Read a chat response aloud when `/speak` is on
+    pub(super) fn speak_response(&mut self, content: &str) {
+        let app_config = get_config();
+        let mut config = app_config.tts;
+        if !config.enabled {
+            return;
+        }
+        let text = tts::speakable_text(content);
+        if text.is_empty() {
+            return;
+        }
+        if config.api_key.is_empty() {
+            config.api_key = app_config.ai.openai.api_key;
+        }
+
+        let speaker = self.speaker.clone();
+        let errors = self.speech_error_tx.clone();
+        let task = tokio::spawn(async move {
+            if let Err(e) = speaker.speak(&config, &text).await {
+                let _ = errors.send(e);
+            }
+        });
+        self.background_tasks.push(task);
+        self.needs_redraw = true;
+    }
+
+    /// Report responses that could not be read aloud
+    pub fn collect_speech_errors(&mut self) {
+        while let Ok(e) = self.speech_error_rx.try_recv() {
+            self.add_output(format!(
+                "⚠️ Cannot read the response aloud: {} — check the tts settings",
+                e
+            ));
+        }
+    }
+}
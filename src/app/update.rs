@@ -0,0 +1,226 @@
+//! `/update`: check for a newer release and optionally install it
+//!
+//! The release check and the download run in a tracked background task, so
+//! the UI stays responsive; Esc or cancelling the task stops them before the
+//! binary is replaced.
+
+use super::App;
+use super::tracked::is_cancelled;
+use crate::ai::types::TaskStatus;
+use crate::config::get_config;
+use crate::handlers::safe;
+use crate::handlers::update::{self, Release};
+use crate::utils::TaskManager;
+use crate::utils::tasks::{TaskId, TaskType};
+use std::future::Future;
+use std::path::PathBuf;
+
+/// Version of the running binary
+const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Outcome of an update check or install in the background
+#[derive(Debug)]
+pub enum UpdateResult {
+    /// Releases newer than the running version, newest first
+    Checked {
+        /// Releases found
+        newer: Vec<Release>,
+        /// `/update install` asked to install the newest one
+        install: bool,
+    },
+    /// The new binary replaced the running one
+    Installed {
+        /// Release installed
+        release: Release,
+        /// Where the previous binary is kept
+        backup: PathBuf,
+    },
+    /// The check or the install failed
+    Failed(String),
+}
+
+impl App {
+    /// Handle `/update` and `/update install`
+    pub fn handle_update(&mut self, args: &str) {
+        let install = match args {
+            "" | "check" => false,
+            "install" => true,
+            _ => {
+                self.add_output("Error: Usage: /update [check|install]".to_string());
+                return;
+            }
+        };
+        if self.update_task.is_some() {
+            self.add_output("⚠️ An update check is already running".to_string());
+            return;
+        }
+
+        let repository = get_config().update.repository;
+        self.spawn_update("Update check".to_string(), move |_, _| async move {
+            match update::fetch_releases(&repository).await {
+                Ok(releases) => UpdateResult::Checked {
+                    newer: update::newer_releases(&releases, CURRENT_VERSION),
+                    install,
+                },
+                Err(e) => UpdateResult::Failed(format!("Update check failed: {}", e)),
+            }
+        });
+        self.add_output("🔎 Checking for updates…".to_string());
+    }
+
+    /// Report update checks and installs finished in the background
+    pub fn collect_updates(&mut self) {
+        while let Ok(result) = self.update_rx.try_recv() {
+            self.update_task = None;
+            match result {
+                UpdateResult::Checked { newer, install } => self.show_releases(newer, install),
+                UpdateResult::Installed { release, backup } => self.add_output(format!(
+                    "✅ Updated to {} — restart to use it (previous binary kept at {})",
+                    release.tag_name,
+                    backup.display()
+                )),
+                UpdateResult::Failed(e) => self.add_output(format!("⚠️ {}", e)),
+            }
+        }
+    }
+
+    /// Stop a running update check or download (Esc)
+    pub(super) fn cancel_update(&mut self) {
+        if let Some((task_id, task)) = self.update_task.take() {
+            task.abort();
+            self.task_manager.cancel_task(task_id);
+        }
+    }
+
+    /// Show the releases found by a check, and install the newest if asked
+    fn show_releases(&mut self, newer: Vec<Release>, install: bool) {
+        let config = get_config().update;
+        let Some(latest) = newer.first().cloned() else {
+            self.add_output(format!("✅ AI Coder v{} is up to date", CURRENT_VERSION));
+            return;
+        };
+
+        if !install {
+            self.add_output(format!(
+                "⬆️ {} is available (you have v{})\n\n{}",
+                latest.tag_name,
+                CURRENT_VERSION,
+                update::changelog(&newer)
+            ));
+            self.add_output(self.install_hint(&latest, config.allow_self_update));
+            return;
+        }
+
+        if safe::is_enabled() {
+            self.add_output(safe::blocked_message("Installing updates"));
+            return;
+        }
+        if !config.allow_self_update {
+            self.add_output(format!(
+                "⚠️ Self-update is disabled (update.allow_self_update); install {} from {}",
+                latest.tag_name, latest.html_url
+            ));
+            return;
+        }
+        self.install_release(latest);
+    }
+
+    /// How the user can install `release`
+    fn install_hint(&self, release: &Release, allowed: bool) -> String {
+        if !allowed {
+            format!(
+                "ℹ️ Self-update is disabled; download it from {}",
+                release.html_url
+            )
+        } else if release
+            .asset_for(std::env::consts::OS, std::env::consts::ARCH)
+            .is_none()
+        {
+            format!(
+                "ℹ️ No prebuilt binary for {} {}; download it from {}",
+                std::env::consts::OS,
+                std::env::consts::ARCH,
+                release.html_url
+            )
+        } else {
+            "💡 Run /update install to download it and replace this binary".to_string()
+        }
+    }
+
+    /// Download the binary of `release` and replace the running executable
+    fn install_release(&mut self, release: Release) {
+        let Some(asset) = release
+            .asset_for(std::env::consts::OS, std::env::consts::ARCH)
+            .cloned()
+        else {
+            self.add_output(format!(
+                "⚠️ {} has no prebuilt binary for {} {}; download it from {}",
+                release.tag_name,
+                std::env::consts::OS,
+                std::env::consts::ARCH,
+                release.html_url
+            ));
+            return;
+        };
+        let exe = match std::env::current_exe() {
+            Ok(exe) => exe,
+            Err(e) => {
+                self.add_output(format!("⚠️ Cannot locate the running binary: {}", e));
+                return;
+            }
+        };
+
+        self.add_output(format!("⬇️ Downloading {}… (Esc to cancel)", asset.name));
+        self.spawn_update(
+            format!("Update to {}", release.tag_name),
+            move |task_manager, task_id| async move {
+                let binary = match update::download_verified(&release, &asset).await {
+                    Ok(binary) => binary,
+                    Err(e) => return UpdateResult::Failed(format!("Update failed: {}", e)),
+                };
+                // Leave the binary alone once the task was cancelled
+                if is_cancelled(&task_manager, task_id) {
+                    return UpdateResult::Failed("Update cancelled".to_string());
+                }
+                match update::replace_binary(&exe, &binary) {
+                    Ok(backup) => UpdateResult::Installed { release, backup },
+                    Err(e) => UpdateResult::Failed(format!("Update failed: {}", e)),
+                }
+            },
+        );
+    }
+
+    /// Run `job` as a tracked task and deliver its result to [`Self::collect_updates`]
+    fn spawn_update<F, Fut>(&mut self, label: String, job: F)
+    where
+        F: FnOnce(TaskManager, TaskId) -> Fut + Send + 'static,
+        Fut: Future<Output = UpdateResult> + Send + 'static,
+    {
+        let task_id = self
+            .task_manager
+            .create_task(label, TaskType::NetworkRequest);
+        self.task_manager
+            .update_task_status(task_id, TaskStatus::Running);
+        let results = self.update_tx.clone();
+
+        let abort = self.spawn_tracked(
+            task_id,
+            results,
+            job(self.task_manager.clone(), task_id),
+            |result| match result {
+                UpdateResult::Failed(_) => TaskStatus::Failed,
+                UpdateResult::Checked { .. } | UpdateResult::Installed { .. } => {
+                    TaskStatus::Completed
+                }
+            },
+            // An installed binary stays installed, cancelled or not
+            |result| match result {
+                UpdateResult::Checked { .. } => {
+                    Some(UpdateResult::Failed("Update cancelled".to_string()))
+                }
+                result => Some(result),
+            },
+        );
+        self.update_task = Some((task_id, abort));
+    }
+}
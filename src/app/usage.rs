@@ -0,0 +1,151 @@
+//! Usage across sessions
+//!
+//! The usage of every finished generation is appended to the ledger, and on
+//! startup a compact summary of yesterday's and this week's tokens and spend
+//! per provider is shown, unless `usage.startup_summary` is off. When the
+//! session cost crosses `usage.cost_warning` or `usage.cost_alert`, the 💰
+//! status bar segment changes color and a toast says so.
+//!
+//! `/tag <name>` records the following usage under a tag, such as a client
+//! or ticket, and `/usage by-tag` totals the spend per tag.
+
+use super::App;
+use crate::config::{CostLevel, get_config, get_config_dir};
+use crate::utils::ledger::{self, LedgerEntry};
+use crate::utils::{TaskUsage, log_error};
+use chrono::Local;
+use std::path::PathBuf;
+
+/// File holding the usage ledger
+fn ledger_file() -> PathBuf {
+    get_config_dir().join("usage.jsonl")
+}
+
+impl App {
+    /// Append the usage of a finished generation to the ledger
+    pub(super) fn record_in_ledger(&self, usage: &TaskUsage) {
+        let entry = LedgerEntry {
+            timestamp: Local::now(),
+            provider: usage.provider,
+            model: usage.model.clone(),
+            prompt_tokens: usage.tokens.prompt_tokens,
+            completion_tokens: usage.tokens.completion_tokens,
+            cost: usage.cost,
+            tag: self.usage_tag.clone(),
+        };
+        if let Err(e) = ledger::append(&ledger_file(), &entry) {
+            log_error(&format!("Failed to update the usage ledger: {}", e)).ok();
+        }
+    }
+
+    /// Show a toast when the session cost has risen above a threshold
+    pub(super) fn alert_cost_level(&mut self, before: CostLevel) {
+        let config = get_config().usage;
+        let threshold = match config.cost_level(self.stats.cost) {
+            level if level <= before => return,
+            CostLevel::Warning => config.cost_warning,
+            CostLevel::Alert => config.cost_alert,
+            CostLevel::Normal => return,
+        };
+        self.show_toast(format!(
+            "💰 Session cost passed ${:.2} (now ${:.4})",
+            threshold, self.stats.cost
+        ));
+    }
+
+    /// Show yesterday's and this week's usage per provider
+    pub fn show_usage_summary(&mut self) {
+        if !get_config().usage.startup_summary {
+            return;
+        }
+        let entries = match ledger::load(&ledger_file()) {
+            Ok(entries) => entries,
+            Err(e) => {
+                log_error(&format!("Failed to read the usage ledger: {}", e)).ok();
+                return;
+            }
+        };
+        if let Some(summary) = ledger::usage_summary(&entries, Local::now()) {
+            self.add_output(summary);
+        }
+    }
+
+    /// Handle `/tag [name|off]`
+    pub fn handle_tag(&mut self, args: &str) {
+        match args {
+            "" => match &self.usage_tag {
+                Some(tag) => self.add_output(format!(
+                    "🏷️ Usage is recorded under {} (/tag off to stop)",
+                    tag
+                )),
+                None => self.add_output("🏷️ No tag; usage is recorded untagged".to_string()),
+            },
+            "off" | "clear" => match self.usage_tag.take() {
+                Some(tag) => self.add_output(format!("🏷️ Stopped tagging usage as {}", tag)),
+                None => self.add_output("🏷️ No tag was set".to_string()),
+            },
+            tag if tag.contains(char::is_whitespace) => self.add_output(
+                "Error: Tags are a single word, e.g. /tag acme or /tag JIRA-123".to_string(),
+            ),
+            tag => {
+                self.usage_tag = Some(tag.to_string());
+                self.add_output(format!(
+                    "🏷️ Usage from now on is recorded under {} — /usage by-tag totals it",
+                    tag
+                ));
+            }
+        }
+    }
+
+    /// Handle `/usage` and `/usage by-tag [today|week|month]`
+    pub fn handle_usage(&mut self, args: &str) {
+        let entries = match ledger::load(&ledger_file()) {
+            Ok(entries) => entries,
+            Err(e) => {
+                self.add_output(format!("Error: Cannot read the usage ledger: {}", e));
+                return;
+            }
+        };
+        let now = Local::now();
+        let mut words = args.split_whitespace();
+        match (words.next(), words.next(), words.next()) {
+            (None, ..) => {
+                let summary = ledger::usage_summary(&entries, now)
+                    .unwrap_or_else(|| "📊 No usage recorded yesterday or this week".to_string());
+                self.add_output(summary);
+            }
+            (Some("by-tag"), period, None) => {
+                let (label, range) = match period {
+                    None | Some("all") => ("all time", None),
+                    Some(name) => match ledger::named_period(name, now) {
+                        Some(range) if name == "today" => ("today", Some(range)),
+                        Some(range) => (
+                            if name == "week" {
+                                "this week"
+                            } else {
+                                "this month"
+                            },
+                            Some(range),
+                        ),
+                        None => return self.add_output(usage()),
+                    },
+                };
+                let totals = ledger::tag_totals(&entries, range.as_ref());
+                if totals.is_empty() {
+                    self.add_output(format!("🏷️ No usage recorded ({})", label));
+                    return;
+                }
+                self.add_output(format!(
+                    "🏷️ Usage by tag ({})\n{}",
+                    label,
+                    ledger::tag_report(&totals)
+                ));
+            }
+            _ => self.add_output(usage()),
+        }
+    }
+}
+
+fn usage() -> String {
+    "Usage: /usage | /usage by-tag [today|week|month|all]".to_string()
+}
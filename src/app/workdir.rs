@@ -0,0 +1,35 @@
+//! `/cd` and `!cd`
+//!
+//! The app keeps its own working directory, shown in the status bar, for
+//! `!` commands, code blocks, `/pty` and the paths of commands such as
+//! `/doc` or `@file`. `/cd` alone goes to the home directory and `/cd -`
+//! back to the previous one.
+
+use super::App;
+use crate::handlers::workdir;
+
+impl App {
+    /// Handle `/cd [path|-]` and a plain `!cd [path|-]`
+    pub fn handle_cd(&mut self, args: &str) {
+        let target = match args {
+            "-" => match &self.previous_dir {
+                Some(dir) => Ok(dir.clone()),
+                None => {
+                    self.add_output("⚠️ No previous directory to go back to".to_string());
+                    return;
+                }
+            },
+            _ => workdir::resolve(&self.current_dir, args),
+        };
+        match target {
+            Ok(dir) => {
+                self.previous_dir = Some(std::mem::replace(&mut self.current_dir, dir));
+                self.add_output(format!("📁 {}", self.current_dir.display()));
+            }
+            Err(e) => {
+                self.add_output(format!("Error: {}", e));
+                self.stop_chain("the previous command failed");
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+//! Command-line options
+
+/// Usage shown by `--help`
+pub const USAGE: &str = "\
+Usage: ai-coder [share] [options]
+
+Commands:
+  share          Serve the live session read-only over HTTP for teammates to watch
+                 in a browser (address from share.address, see also /share)
+
+Options:
+  --safe         Block side effects: shell commands, code execution, file writes,
+                 patches, commits, updates and sync; chat and read-only commands still work
+  -h, --help     Show this help
+  -V, --version  Show the version";
+
+/// Options given on the command line
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options {
+    /// Start in safe mode
+    pub safe: bool,
+    /// Share the session read-only from the start
+    pub share: bool,
+    /// Print the usage and exit
+    pub help: bool,
+    /// Print the version and exit
+    pub version: bool,
+}
+
+/// Parse the arguments after the program name
+pub fn parse_args<I>(args: I) -> Result<Options, String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut options = Options::default();
+    for arg in args {
+        match arg.as_str() {
+            "--safe" => options.safe = true,
+            "share" => options.share = true,
+            "-h" | "--help" => options.help = true,
+            "-V" | "--version" => options.version = true,
+            _ => return Err(format!("Unknown option {} (see --help)", arg)),
+        }
+    }
+    Ok(options)
+}
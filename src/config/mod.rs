@@ -6,12 +6,15 @@
 //! - User preferences
 //! - Theme settings
 
+use crate::ai::extras::RequestExtras;
+use chrono::format::{Item, StrftimeItems};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::io;
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::{Mutex, TryLockError};
 
 /// Theme configuration for the application UI
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +29,23 @@ pub struct ThemeConfig {
     pub background: String,
     /// Foreground/text color (or "default" for terminal default)
     pub foreground: String,
+    /// Spinner shown while a prompt is answered
+    #[serde(default)]
+    pub spinner: SpinnerStyle,
+    /// Show a progress bar instead of only the spinner once the remaining time is known
+    #[serde(default = "default_progress_bar")]
+    pub progress_bar: bool,
+    /// Background of the matches of `/search`; the one jumped to uses the accent color
+    #[serde(default = "default_search_match")]
+    pub search_match: String,
+}
+
+fn default_progress_bar() -> bool {
+    true
+}
+
+fn default_search_match() -> String {
+    "#D7D75F".to_string() // Yellow
 }
 
 impl Default for ThemeConfig {
@@ -36,6 +56,53 @@ impl Default for ThemeConfig {
             accent: "#AF8700".to_string(),     // Gold
             background: "default".to_string(), // Terminal default
             foreground: "default".to_string(), // Terminal default
+            spinner: SpinnerStyle::default(),
+            progress_bar: true,
+            search_match: default_search_match(),
+        }
+    }
+}
+
+/// Animation of the spinner
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpinnerStyle {
+    /// ⠋⠙⠹⠸ braille dots
+    #[default]
+    Braille,
+    /// ∙∙∙ dots running across
+    Dots,
+    /// ─╲│╱ a turning line
+    Line,
+    /// No spinner; the progress bar is still shown when enabled
+    None,
+}
+
+impl std::fmt::Display for SpinnerStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Braille => "braille",
+            Self::Dots => "dots",
+            Self::Line => "line",
+            Self::None => "none",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl std::str::FromStr for SpinnerStyle {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "braille" => Ok(Self::Braille),
+            "dots" => Ok(Self::Dots),
+            "line" => Ok(Self::Line),
+            "none" => Ok(Self::None),
+            _ => Err(format!(
+                "Unknown spinner style: {} (braille, dots, line or none)",
+                value
+            )),
         }
     }
 }
@@ -64,6 +131,20 @@ impl Default for ModelConfig {
     }
 }
 
+/// OAuth device flow settings of a provider or the gateway in front of it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OAuthConfig {
+    /// Client id registered with the identity provider
+    pub client_id: String,
+    /// Endpoint that issues the device and user codes
+    pub device_authorization_url: String,
+    /// Endpoint that exchanges the device code or a refresh token for tokens
+    pub token_url: String,
+    /// Scopes to request, separated by spaces
+    #[serde(default)]
+    pub scope: String,
+}
+
 /// Ollama provider configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OllamaConfig {
@@ -73,6 +154,18 @@ pub struct OllamaConfig {
     pub models: Vec<ModelConfig>,
     /// Currently selected model (index into models)
     pub current_model_index: usize,
+    /// Headers added to every request, e.g. an organization id for a gateway
+    #[serde(default)]
+    pub extra_headers: BTreeMap<String, String>,
+    /// Fields merged into the JSON body of every generation request
+    #[serde(default)]
+    pub extra_body: BTreeMap<String, serde_json::Value>,
+    /// Device flow login with `/login` instead of an API key
+    #[serde(default)]
+    pub oauth: Option<OAuthConfig>,
+    /// Maintenance windows in which the provider is skipped, e.g. `sat,sun 01:00-05:00`
+    #[serde(default)]
+    pub quiet_hours: Vec<String>,
 }
 
 impl Default for OllamaConfig {
@@ -89,6 +182,10 @@ impl Default for OllamaConfig {
                 },
             ],
             current_model_index: 0,
+            extra_headers: BTreeMap::new(),
+            extra_body: BTreeMap::new(),
+            oauth: None,
+            quiet_hours: Vec::new(),
         }
     }
 }
@@ -104,6 +201,18 @@ pub struct OpenAIConfig {
     pub models: Vec<ModelConfig>,
     /// Currently selected model (index into models)
     pub current_model_index: usize,
+    /// Headers added to every request, e.g. an organization id for a gateway
+    #[serde(default)]
+    pub extra_headers: BTreeMap<String, String>,
+    /// Fields merged into the JSON body of every generation request
+    #[serde(default)]
+    pub extra_body: BTreeMap<String, serde_json::Value>,
+    /// Device flow login with `/login` instead of an API key
+    #[serde(default)]
+    pub oauth: Option<OAuthConfig>,
+    /// Maintenance windows in which the provider is skipped, e.g. `sat,sun 01:00-05:00`
+    #[serde(default)]
+    pub quiet_hours: Vec<String>,
 }
 
 impl Default for OpenAIConfig {
@@ -126,6 +235,10 @@ impl Default for OpenAIConfig {
                 },
             ],
             current_model_index: 0,
+            extra_headers: BTreeMap::new(),
+            extra_body: BTreeMap::new(),
+            oauth: None,
+            quiet_hours: Vec::new(),
         }
     }
 }
@@ -141,6 +254,18 @@ pub struct AnthropicConfig {
     pub models: Vec<ModelConfig>,
     /// Currently selected model (index into models)
     pub current_model_index: usize,
+    /// Headers added to every request, e.g. an organization id for a gateway
+    #[serde(default)]
+    pub extra_headers: BTreeMap<String, String>,
+    /// Fields merged into the JSON body of every generation request
+    #[serde(default)]
+    pub extra_body: BTreeMap<String, serde_json::Value>,
+    /// Device flow login with `/login` instead of an API key
+    #[serde(default)]
+    pub oauth: Option<OAuthConfig>,
+    /// Maintenance windows in which the provider is skipped, e.g. `sat,sun 01:00-05:00`
+    #[serde(default)]
+    pub quiet_hours: Vec<String>,
 }
 
 impl Default for AnthropicConfig {
@@ -163,6 +288,10 @@ impl Default for AnthropicConfig {
                 },
             ],
             current_model_index: 0,
+            extra_headers: BTreeMap::new(),
+            extra_body: BTreeMap::new(),
+            oauth: None,
+            quiet_hours: Vec::new(),
         }
     }
 }
@@ -176,6 +305,18 @@ pub struct LMStudioConfig {
     pub models: Vec<ModelConfig>,
     /// Currently selected model (index into models)
     pub current_model_index: usize,
+    /// Headers added to every request, e.g. an organization id for a gateway
+    #[serde(default)]
+    pub extra_headers: BTreeMap<String, String>,
+    /// Fields merged into the JSON body of every generation request
+    #[serde(default)]
+    pub extra_body: BTreeMap<String, serde_json::Value>,
+    /// Device flow login with `/login` instead of an API key
+    #[serde(default)]
+    pub oauth: Option<OAuthConfig>,
+    /// Maintenance windows in which the provider is skipped, e.g. `sat,sun 01:00-05:00`
+    #[serde(default)]
+    pub quiet_hours: Vec<String>,
 }
 
 impl Default for LMStudioConfig {
@@ -189,6 +330,328 @@ impl Default for LMStudioConfig {
                 system_prompt: None,
             }],
             current_model_index: 0,
+            extra_headers: BTreeMap::new(),
+            extra_body: BTreeMap::new(),
+            oauth: None,
+            quiet_hours: Vec::new(),
+        }
+    }
+}
+
+/// OpenRouter provider configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenRouterConfig {
+    /// API endpoint URL
+    pub endpoint: String,
+    /// API key
+    pub api_key: String,
+    /// Available models, named `vendor/model`
+    pub models: Vec<ModelConfig>,
+    /// Currently selected model (index into models)
+    pub current_model_index: usize,
+    /// Site sent as `HTTP-Referer` so OpenRouter can attribute the requests
+    pub app_url: String,
+    /// Name sent as `X-Title` and shown in OpenRouter's rankings
+    pub app_title: String,
+    /// Headers added to every request, e.g. an organization id for a gateway
+    #[serde(default)]
+    pub extra_headers: BTreeMap<String, String>,
+    /// Fields merged into the JSON body of every generation request
+    #[serde(default)]
+    pub extra_body: BTreeMap<String, serde_json::Value>,
+    /// Device flow login with `/login` instead of an API key
+    #[serde(default)]
+    pub oauth: Option<OAuthConfig>,
+    /// Maintenance windows in which the provider is skipped, e.g. `sat,sun 01:00-05:00`
+    #[serde(default)]
+    pub quiet_hours: Vec<String>,
+}
+
+impl Default for OpenRouterConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "https://openrouter.ai/api/v1".to_string(),
+            api_key: "".to_string(),
+            models: vec![
+                ModelConfig {
+                    name: "anthropic/claude-3.5-sonnet".to_string(),
+                    temperature: 0.1,
+                    max_tokens: 4096,
+                    system_prompt: None,
+                },
+                ModelConfig {
+                    name: "openai/gpt-4o-mini".to_string(),
+                    temperature: 0.2,
+                    max_tokens: 4096,
+                    system_prompt: None,
+                },
+            ],
+            current_model_index: 0,
+            app_url: "https://github.com/suyashbhawsar/ai-coder".to_string(),
+            app_title: "AI Coder".to_string(),
+            extra_headers: BTreeMap::new(),
+            extra_body: BTreeMap::new(),
+            oauth: None,
+            quiet_hours: Vec::new(),
+        }
+    }
+}
+
+/// Groq provider configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroqConfig {
+    /// API endpoint URL
+    pub endpoint: String,
+    /// API key
+    pub api_key: String,
+    /// Available models
+    pub models: Vec<ModelConfig>,
+    /// Currently selected model (index into models)
+    pub current_model_index: usize,
+    /// Headers added to every request, e.g. an organization id for a gateway
+    #[serde(default)]
+    pub extra_headers: BTreeMap<String, String>,
+    /// Fields merged into the JSON body of every generation request
+    #[serde(default)]
+    pub extra_body: BTreeMap<String, serde_json::Value>,
+    /// Device flow login with `/login` instead of an API key
+    #[serde(default)]
+    pub oauth: Option<OAuthConfig>,
+    /// Maintenance windows in which the provider is skipped, e.g. `sat,sun 01:00-05:00`
+    #[serde(default)]
+    pub quiet_hours: Vec<String>,
+}
+
+impl Default for GroqConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "https://api.groq.com/openai/v1".to_string(),
+            api_key: "".to_string(),
+            models: vec![
+                ModelConfig {
+                    name: "llama-3.3-70b-versatile".to_string(),
+                    temperature: 0.1,
+                    max_tokens: 4096,
+                    system_prompt: None,
+                },
+                ModelConfig {
+                    name: "llama-3.1-8b-instant".to_string(),
+                    temperature: 0.2,
+                    max_tokens: 4096,
+                    system_prompt: None,
+                },
+            ],
+            current_model_index: 0,
+            extra_headers: BTreeMap::new(),
+            extra_body: BTreeMap::new(),
+            oauth: None,
+            quiet_hours: Vec::new(),
+        }
+    }
+}
+
+/// Mistral provider configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MistralConfig {
+    /// API endpoint URL
+    pub endpoint: String,
+    /// API key
+    pub api_key: String,
+    /// Available models
+    pub models: Vec<ModelConfig>,
+    /// Currently selected model (index into models)
+    pub current_model_index: usize,
+    /// Headers added to every request, e.g. an organization id for a gateway
+    #[serde(default)]
+    pub extra_headers: BTreeMap<String, String>,
+    /// Fields merged into the JSON body of every generation request
+    #[serde(default)]
+    pub extra_body: BTreeMap<String, serde_json::Value>,
+    /// Device flow login with `/login` instead of an API key
+    #[serde(default)]
+    pub oauth: Option<OAuthConfig>,
+    /// Maintenance windows in which the provider is skipped, e.g. `sat,sun 01:00-05:00`
+    #[serde(default)]
+    pub quiet_hours: Vec<String>,
+}
+
+impl Default for MistralConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "https://api.mistral.ai/v1".to_string(),
+            api_key: "".to_string(),
+            models: vec![
+                ModelConfig {
+                    name: "codestral-latest".to_string(),
+                    temperature: 0.1,
+                    max_tokens: 4096,
+                    system_prompt: None,
+                },
+                ModelConfig {
+                    name: "mistral-large-latest".to_string(),
+                    temperature: 0.2,
+                    max_tokens: 4096,
+                    system_prompt: None,
+                },
+            ],
+            current_model_index: 0,
+            extra_headers: BTreeMap::new(),
+            extra_body: BTreeMap::new(),
+            oauth: None,
+            quiet_hours: Vec::new(),
+        }
+    }
+}
+
+/// In-process llama.cpp configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LlamaCppConfig {
+    /// Directory of the GGUF files, `~/.ai-coder/models` when empty
+    #[serde(default)]
+    pub model_dir: String,
+    /// Available models, named by GGUF file name or path
+    pub models: Vec<ModelConfig>,
+    /// Currently selected model (index into models)
+    pub current_model_index: usize,
+    /// CPU threads used for generation, 0 for all cores
+    #[serde(default)]
+    pub threads: usize,
+    /// Layers offloaded to the GPU (`-ngl`), 0 to run on the CPU only
+    #[serde(default)]
+    pub gpu_layers: u32,
+    /// Context window in tokens, shared by the prompt and the response
+    pub context_size: u32,
+}
+
+impl Default for LlamaCppConfig {
+    fn default() -> Self {
+        Self {
+            model_dir: "".to_string(),
+            models: vec![ModelConfig {
+                name: "qwen2.5-coder-7b-instruct-q4_k_m.gguf".to_string(),
+                temperature: 0.1,
+                max_tokens: 2048,
+                system_prompt: None,
+            }],
+            current_model_index: 0,
+            threads: 0,
+            gpu_layers: 0,
+            context_size: 8192,
+        }
+    }
+}
+
+/// Azure OpenAI provider configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AzureOpenAIConfig {
+    /// Name of the Azure OpenAI resource, served at `https://<resource>.openai.azure.com`
+    pub resource: String,
+    /// Endpoint URL used instead of the resource's, e.g. for a sovereign cloud or a proxy
+    #[serde(default)]
+    pub endpoint: String,
+    /// API key of the resource, sent in the `api-key` header
+    pub api_key: String,
+    /// API version sent with every request
+    pub api_version: String,
+    /// Deployments of the resource; each model is named after its deployment
+    pub models: Vec<ModelConfig>,
+    /// Currently selected deployment (index into models)
+    pub current_model_index: usize,
+    /// Headers added to every request
+    #[serde(default)]
+    pub extra_headers: BTreeMap<String, String>,
+    /// Fields merged into the JSON body of every generation request
+    #[serde(default)]
+    pub extra_body: BTreeMap<String, serde_json::Value>,
+    /// Microsoft Entra ID login with `/login` instead of an API key
+    #[serde(default)]
+    pub oauth: Option<OAuthConfig>,
+    /// Maintenance windows in which the provider is skipped, e.g. `sat,sun 01:00-05:00`
+    #[serde(default)]
+    pub quiet_hours: Vec<String>,
+}
+
+impl Default for AzureOpenAIConfig {
+    fn default() -> Self {
+        Self {
+            resource: "".to_string(),
+            endpoint: "".to_string(),
+            api_key: "".to_string(),
+            api_version: crate::ai::azure::DEFAULT_API_VERSION.to_string(),
+            models: vec![ModelConfig {
+                name: "gpt-4o".to_string(),
+                temperature: 0.1,
+                max_tokens: 4096,
+                system_prompt: None,
+            }],
+            current_model_index: 0,
+            extra_headers: BTreeMap::new(),
+            extra_body: BTreeMap::new(),
+            oauth: None,
+            quiet_hours: Vec::new(),
+        }
+    }
+}
+
+/// Configuration of any OpenAI-compatible server, e.g. vLLM, a llama.cpp
+/// server or a gateway
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomConfig {
+    /// Base URL of the API, the part before `/chat/completions`
+    pub endpoint: String,
+    /// API key, sent as a bearer token unless `api_key_header` names another header
+    #[serde(default)]
+    pub api_key: String,
+    /// Header the API key is sent in as is, e.g. `x-api-key`
+    #[serde(default)]
+    pub api_key_header: Option<String>,
+    /// Models served by the endpoint
+    pub models: Vec<ModelConfig>,
+    /// Currently selected model (index into models)
+    pub current_model_index: usize,
+    /// Also list the models the endpoint reports at `/models` in `/list models`
+    #[serde(default)]
+    pub discover_models: bool,
+    /// Ask for the token usage at the end of streamed responses; some servers reject it
+    #[serde(default = "default_stream_usage")]
+    pub stream_usage: bool,
+    /// Headers added to every request
+    #[serde(default)]
+    pub extra_headers: BTreeMap<String, String>,
+    /// Fields merged into the JSON body of every generation request
+    #[serde(default)]
+    pub extra_body: BTreeMap<String, serde_json::Value>,
+    /// Device flow login with `/login` instead of an API key
+    #[serde(default)]
+    pub oauth: Option<OAuthConfig>,
+    /// Maintenance windows in which the provider is skipped, e.g. `sat,sun 01:00-05:00`
+    #[serde(default)]
+    pub quiet_hours: Vec<String>,
+}
+
+fn default_stream_usage() -> bool {
+    true
+}
+
+impl Default for CustomConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "http://localhost:8000/v1".to_string(),
+            api_key: "".to_string(),
+            api_key_header: None,
+            models: vec![ModelConfig {
+                name: "default".to_string(),
+                temperature: 0.1,
+                max_tokens: 2048,
+                system_prompt: None,
+            }],
+            current_model_index: 0,
+            discover_models: false,
+            stream_usage: true,
+            extra_headers: BTreeMap::new(),
+            extra_body: BTreeMap::new(),
+            oauth: None,
+            quiet_hours: Vec::new(),
         }
     }
 }
@@ -206,6 +669,30 @@ pub struct AIConfig {
     pub anthropic: AnthropicConfig,
     /// LM Studio configuration
     pub lmstudio: LMStudioConfig,
+    /// OpenRouter configuration
+    #[serde(default)]
+    pub openrouter: OpenRouterConfig,
+    /// Groq configuration
+    #[serde(default)]
+    pub groq: GroqConfig,
+    /// Mistral configuration
+    #[serde(default)]
+    pub mistral: MistralConfig,
+    /// In-process llama.cpp configuration
+    #[serde(default)]
+    pub llamacpp: LlamaCppConfig,
+    /// Azure OpenAI configuration
+    #[serde(default)]
+    pub azure: AzureOpenAIConfig,
+    /// Custom OpenAI-compatible endpoint configuration
+    #[serde(default)]
+    pub custom: CustomConfig,
+    /// Seed sent with every request so sampling can be reproduced (`/config seed`)
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Temperature 0 and a fixed seed for reproducible responses (`/config deterministic`)
+    #[serde(default)]
+    pub deterministic: bool,
 }
 
 impl Default for AIConfig {
@@ -216,11 +703,111 @@ impl Default for AIConfig {
             openai: OpenAIConfig::default(),
             anthropic: AnthropicConfig::default(),
             lmstudio: LMStudioConfig::default(),
+            openrouter: OpenRouterConfig::default(),
+            groq: GroqConfig::default(),
+            mistral: MistralConfig::default(),
+            llamacpp: LlamaCppConfig::default(),
+            azure: AzureOpenAIConfig::default(),
+            custom: CustomConfig::default(),
+            seed: None,
+            deterministic: false,
         }
     }
 }
 
 impl AIConfig {
+    /// Seed to send with requests: the configured one, 0 in deterministic mode without one
+    pub fn request_seed(&self) -> Option<u64> {
+        self.seed.or(self.deterministic.then_some(0))
+    }
+
+    /// Models of the active provider and the index of the selected one
+    fn active_models_mut(&mut self) -> (&mut Vec<ModelConfig>, &mut usize) {
+        use crate::ai::types::Provider;
+        match self.active_provider {
+            Provider::Ollama => (
+                &mut self.ollama.models,
+                &mut self.ollama.current_model_index,
+            ),
+            Provider::OpenAI => (
+                &mut self.openai.models,
+                &mut self.openai.current_model_index,
+            ),
+            Provider::Anthropic => (
+                &mut self.anthropic.models,
+                &mut self.anthropic.current_model_index,
+            ),
+            Provider::LMStudio => (
+                &mut self.lmstudio.models,
+                &mut self.lmstudio.current_model_index,
+            ),
+            Provider::OpenRouter => (
+                &mut self.openrouter.models,
+                &mut self.openrouter.current_model_index,
+            ),
+            Provider::Groq => (&mut self.groq.models, &mut self.groq.current_model_index),
+            Provider::Mistral => (
+                &mut self.mistral.models,
+                &mut self.mistral.current_model_index,
+            ),
+            Provider::LlamaCpp => (
+                &mut self.llamacpp.models,
+                &mut self.llamacpp.current_model_index,
+            ),
+            Provider::AzureOpenAI => (&mut self.azure.models, &mut self.azure.current_model_index),
+            Provider::Custom => (
+                &mut self.custom.models,
+                &mut self.custom.current_model_index,
+            ),
+        }
+    }
+
+    /// Select a model of the active provider by name, adding it if it is not listed
+    pub fn select_model(&mut self, name: &str) {
+        let (models, current) = self.active_models_mut();
+        match models.iter().position(|model| model.name == name) {
+            Some(idx) => *current = idx,
+            None => {
+                // Settings of the selected model apply to the new one
+                let mut model = models
+                    .get(*current)
+                    .or(models.first())
+                    .cloned()
+                    .unwrap_or_default();
+                model.name = name.to_string();
+                models.push(model);
+                *current = models.len() - 1;
+            }
+        }
+    }
+
+    /// Change the temperature of the selected model of the active provider
+    pub fn set_temperature(&mut self, temperature: f32) {
+        let (models, current) = self.active_models_mut();
+        let idx = (*current).min(models.len().saturating_sub(1));
+        if let Some(model) = models.get_mut(idx) {
+            model.temperature = temperature;
+        }
+    }
+
+    /// Change the most tokens an answer of the selected model of the active provider may take
+    pub fn set_max_tokens(&mut self, max_tokens: usize) {
+        let (models, current) = self.active_models_mut();
+        let idx = (*current).min(models.len().saturating_sub(1));
+        if let Some(model) = models.get_mut(idx) {
+            model.max_tokens = max_tokens;
+        }
+    }
+
+    /// The active model configuration as requests use it, with temperature 0 in deterministic mode
+    pub fn request_model_config(&self) -> ModelConfig {
+        let mut model = self.get_active_model_config();
+        if self.deterministic {
+            model.temperature = 0.0;
+        }
+        model
+    }
+
     /// Get the currently active model configuration
     pub fn get_active_model_config(&self) -> ModelConfig {
         match self.active_provider {
@@ -252,6 +839,48 @@ impl AIConfig {
                     .min(self.lmstudio.models.len().saturating_sub(1));
                 self.lmstudio.models[idx].clone()
             }
+            crate::ai::types::Provider::OpenRouter => {
+                let idx = self
+                    .openrouter
+                    .current_model_index
+                    .min(self.openrouter.models.len().saturating_sub(1));
+                self.openrouter.models[idx].clone()
+            }
+            crate::ai::types::Provider::Groq => {
+                let idx = self
+                    .groq
+                    .current_model_index
+                    .min(self.groq.models.len().saturating_sub(1));
+                self.groq.models[idx].clone()
+            }
+            crate::ai::types::Provider::Mistral => {
+                let idx = self
+                    .mistral
+                    .current_model_index
+                    .min(self.mistral.models.len().saturating_sub(1));
+                self.mistral.models[idx].clone()
+            }
+            crate::ai::types::Provider::LlamaCpp => {
+                let idx = self
+                    .llamacpp
+                    .current_model_index
+                    .min(self.llamacpp.models.len().saturating_sub(1));
+                self.llamacpp.models[idx].clone()
+            }
+            crate::ai::types::Provider::AzureOpenAI => {
+                let idx = self
+                    .azure
+                    .current_model_index
+                    .min(self.azure.models.len().saturating_sub(1));
+                self.azure.models[idx].clone()
+            }
+            crate::ai::types::Provider::Custom => {
+                let idx = self
+                    .custom
+                    .current_model_index
+                    .min(self.custom.models.len().saturating_sub(1));
+                self.custom.models[idx].clone()
+            }
         }
     }
 
@@ -262,6 +891,84 @@ impl AIConfig {
             crate::ai::types::Provider::OpenAI => self.openai.endpoint.clone(),
             crate::ai::types::Provider::Anthropic => self.anthropic.endpoint.clone(),
             crate::ai::types::Provider::LMStudio => self.lmstudio.endpoint.clone(),
+            crate::ai::types::Provider::OpenRouter => self.openrouter.endpoint.clone(),
+            crate::ai::types::Provider::Groq => self.groq.endpoint.clone(),
+            crate::ai::types::Provider::Mistral => self.mistral.endpoint.clone(),
+            // There is no server; the models are read from this directory
+            crate::ai::types::Provider::LlamaCpp => crate::ai::llamacpp::model_dir(&self.llamacpp)
+                .display()
+                .to_string(),
+            crate::ai::types::Provider::AzureOpenAI => {
+                crate::ai::azure::endpoint(&self.azure).unwrap_or_default()
+            }
+            crate::ai::types::Provider::Custom => self.custom.endpoint.clone(),
+        }
+    }
+
+    /// Get the extra headers and body fields configured for a provider
+    pub fn extras(&self, provider: crate::ai::types::Provider) -> RequestExtras {
+        let (headers, body) = match provider {
+            crate::ai::types::Provider::Ollama => {
+                (&self.ollama.extra_headers, &self.ollama.extra_body)
+            }
+            crate::ai::types::Provider::OpenAI => {
+                (&self.openai.extra_headers, &self.openai.extra_body)
+            }
+            crate::ai::types::Provider::Anthropic => {
+                (&self.anthropic.extra_headers, &self.anthropic.extra_body)
+            }
+            crate::ai::types::Provider::LMStudio => {
+                (&self.lmstudio.extra_headers, &self.lmstudio.extra_body)
+            }
+            crate::ai::types::Provider::OpenRouter => {
+                (&self.openrouter.extra_headers, &self.openrouter.extra_body)
+            }
+            crate::ai::types::Provider::Groq => (&self.groq.extra_headers, &self.groq.extra_body),
+            crate::ai::types::Provider::Mistral => {
+                (&self.mistral.extra_headers, &self.mistral.extra_body)
+            }
+            crate::ai::types::Provider::AzureOpenAI => {
+                (&self.azure.extra_headers, &self.azure.extra_body)
+            }
+            crate::ai::types::Provider::Custom => {
+                (&self.custom.extra_headers, &self.custom.extra_body)
+            }
+            // Nothing is sent over HTTP
+            crate::ai::types::Provider::LlamaCpp => return RequestExtras::default(),
+        };
+        RequestExtras::new(headers.clone(), body.clone())
+    }
+
+    /// Get the device flow login settings of a provider, if it has any
+    pub fn oauth(&self, provider: crate::ai::types::Provider) -> Option<OAuthConfig> {
+        match provider {
+            crate::ai::types::Provider::Ollama => self.ollama.oauth.clone(),
+            crate::ai::types::Provider::OpenAI => self.openai.oauth.clone(),
+            crate::ai::types::Provider::Anthropic => self.anthropic.oauth.clone(),
+            crate::ai::types::Provider::LMStudio => self.lmstudio.oauth.clone(),
+            crate::ai::types::Provider::OpenRouter => self.openrouter.oauth.clone(),
+            crate::ai::types::Provider::Groq => self.groq.oauth.clone(),
+            crate::ai::types::Provider::Mistral => self.mistral.oauth.clone(),
+            crate::ai::types::Provider::LlamaCpp => None,
+            crate::ai::types::Provider::AzureOpenAI => self.azure.oauth.clone(),
+            crate::ai::types::Provider::Custom => self.custom.oauth.clone(),
+        }
+    }
+
+    /// Get the maintenance windows of a provider
+    pub fn quiet_hours(&self, provider: crate::ai::types::Provider) -> &[String] {
+        match provider {
+            crate::ai::types::Provider::Ollama => &self.ollama.quiet_hours,
+            crate::ai::types::Provider::OpenAI => &self.openai.quiet_hours,
+            crate::ai::types::Provider::Anthropic => &self.anthropic.quiet_hours,
+            crate::ai::types::Provider::LMStudio => &self.lmstudio.quiet_hours,
+            crate::ai::types::Provider::OpenRouter => &self.openrouter.quiet_hours,
+            crate::ai::types::Provider::Groq => &self.groq.quiet_hours,
+            crate::ai::types::Provider::Mistral => &self.mistral.quiet_hours,
+            // Runs in-process, so there is nothing to maintain
+            crate::ai::types::Provider::LlamaCpp => &[],
+            crate::ai::types::Provider::AzureOpenAI => &self.azure.quiet_hours,
+            crate::ai::types::Provider::Custom => &self.custom.quiet_hours,
         }
     }
 
@@ -272,51 +979,1293 @@ impl AIConfig {
             crate::ai::types::Provider::OpenAI => Some(self.openai.api_key.clone()),
             crate::ai::types::Provider::Anthropic => Some(self.anthropic.api_key.clone()),
             crate::ai::types::Provider::LMStudio => None,
+            crate::ai::types::Provider::OpenRouter => Some(self.openrouter.api_key.clone()),
+            crate::ai::types::Provider::Groq => Some(self.groq.api_key.clone()),
+            crate::ai::types::Provider::Mistral => Some(self.mistral.api_key.clone()),
+            crate::ai::types::Provider::LlamaCpp => None,
+            crate::ai::types::Provider::AzureOpenAI => Some(self.azure.api_key.clone()),
+            crate::ai::types::Provider::Custom => Some(self.custom.api_key.clone()),
         }
     }
 }
 
-/// Main application configuration
+/// Interpreter used to run fenced code blocks of one language
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AppConfig {
-    /// Theme settings
-    pub theme: ThemeConfig,
-    /// AI provider settings
-    pub ai: AIConfig,
-    /// History size
-    pub history_size: usize,
-    /// Enable mouse support
-    pub mouse_enabled: bool,
-    /// Enable logging
-    pub logging_enabled: bool,
-    /// Log file path (relative to config directory)
-    pub log_file: Option<String>,
+pub struct InterpreterConfig {
+    /// Whether blocks in this language are offered to run
+    pub enabled: bool,
+    /// Interpreter executable (looked up in PATH)
+    pub command: String,
+    /// Arguments passed to the interpreter; the block is fed on stdin
+    #[serde(default)]
+    pub args: Vec<String>,
 }
 
-impl Default for AppConfig {
+/// Code block execution configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeExecutionConfig {
+    /// Interpreters keyed by language name (python, node, sql, ...)
+    pub interpreters: BTreeMap<String, InterpreterConfig>,
+    /// Responses with at least this many bash blocks list them in a popup to
+    /// choose and order instead of running them inline (0 never does)
+    #[serde(default = "default_queue_threshold")]
+    pub queue_threshold: usize,
+    /// Pause in milliseconds between the bash blocks run from that popup
+    #[serde(default = "default_queue_delay_ms")]
+    pub queue_delay_ms: u64,
+}
+
+fn default_queue_threshold() -> usize {
+    3
+}
+
+fn default_queue_delay_ms() -> u64 {
+    500
+}
+
+impl Default for CodeExecutionConfig {
     fn default() -> Self {
+        let mut interpreters = BTreeMap::new();
+        interpreters.insert(
+            "python".to_string(),
+            InterpreterConfig {
+                enabled: false,
+                command: crate::platform::python_command().to_string(),
+                args: vec!["-".to_string()],
+            },
+        );
+        interpreters.insert(
+            "node".to_string(),
+            InterpreterConfig {
+                enabled: false,
+                command: "node".to_string(),
+                args: vec!["-".to_string()],
+            },
+        );
+        interpreters.insert(
+            "sql".to_string(),
+            InterpreterConfig {
+                enabled: false,
+                command: "sqlite3".to_string(),
+                args: vec![":memory:".to_string()],
+            },
+        );
         Self {
-            theme: ThemeConfig::default(),
-            ai: AIConfig::default(),
-            history_size: 100,
-            mouse_enabled: true,
-            logging_enabled: false,
-            log_file: Some("ai-coder.log".to_string()),
+            interpreters,
+            queue_threshold: default_queue_threshold(),
+            queue_delay_ms: default_queue_delay_ms(),
         }
     }
 }
 
-// Global configuration instance
-static CONFIG: Lazy<Mutex<AppConfig>> = Lazy::new(|| {
-    let config = load_config().unwrap_or_default();
-    Mutex::new(config)
-});
+impl CodeExecutionConfig {
+    /// Map a code fence language tag to its configured interpreter key
+    pub fn canonical_language(language: &str) -> String {
+        match language.to_lowercase().as_str() {
+            "py" | "python3" => "python".to_string(),
+            "js" | "javascript" => "node".to_string(),
+            "sqlite" | "sqlite3" => "sql".to_string(),
+            other => other.to_string(),
+        }
+    }
+
+    /// Get the interpreter for a language if execution is enabled for it
+    pub fn enabled_interpreter(&self, language: &str) -> Option<&InterpreterConfig> {
+        self.interpreters
+            .get(&Self::canonical_language(language))
+            .filter(|interpreter| interpreter.enabled)
+    }
+}
+
+/// Limits for the files sent to the model by `/refactor`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefactorConfig {
+    /// Maximum number of files included in one request
+    pub max_files: usize,
+    /// Files larger than this many bytes are skipped
+    pub max_file_bytes: u64,
+    /// Approximate token budget for all included file contents
+    pub token_budget: usize,
+}
+
+impl Default for RefactorConfig {
+    fn default() -> Self {
+        Self {
+            max_files: 20,
+            max_file_bytes: 64 * 1024,
+            token_budget: 16_000,
+        }
+    }
+}
+
+/// Provider and model a routed prompt is sent to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RouteTarget {
+    pub provider: crate::ai::types::Provider,
+    /// Model name; empty uses the provider's selected model
+    #[serde(default)]
+    pub model: String,
+}
+
+/// Routing of chat prompts to a model tier by the kind of prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingConfig {
+    /// Classify every chat prompt; the `%tier` prefix works either way
+    pub enabled: bool,
+    /// Longest prompt, in words, that still counts as a quick question
+    pub simple_max_words: usize,
+    /// Model for short questions, e.g. a small local one
+    #[serde(default)]
+    pub simple: Option<RouteTarget>,
+    /// Model for code generation and long prompts
+    #[serde(default)]
+    pub code: Option<RouteTarget>,
+    /// Model for prompts with images
+    #[serde(default)]
+    pub vision: Option<RouteTarget>,
+}
+
+impl Default for RoutingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            simple_max_words: 30,
+            simple: None,
+            code: None,
+            vision: None,
+        }
+    }
+}
+
+/// A named role with its own system prompt, chosen with `/as` or `@name:`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Persona {
+    /// System prompt sent with every prompt of the persona
+    #[serde(default)]
+    pub system_prompt: String,
+    /// Provider to use instead of the active one
+    #[serde(default)]
+    pub provider: Option<crate::ai::types::Provider>,
+    /// Model name; empty uses the provider's selected model
+    #[serde(default)]
+    pub model: String,
+    /// Temperature to use instead of the model's
+    #[serde(default)]
+    pub temperature: Option<f32>,
+}
+
+/// Temperature and seed used for the prompts of a command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SamplingPreset {
+    pub temperature: f32,
+    /// Seed sent with the requests, for reproducible answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+/// Sampling presets bound to the commands that send prompts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SamplingConfig {
+    /// Presets by name, besides the built-in deterministic, precise, balanced and creative
+    #[serde(default)]
+    pub presets: BTreeMap<String, SamplingPreset>,
+    /// Preset per command: chat, refactor, doc, commitmsg, rewrite, template or a template's name
+    #[serde(default)]
+    pub commands: BTreeMap<String, String>,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            presets: BTreeMap::new(),
+            commands: BTreeMap::from([
+                ("commitmsg".to_string(), "deterministic".to_string()),
+                ("doc".to_string(), "precise".to_string()),
+                ("refactor".to_string(), "deterministic".to_string()),
+            ]),
+        }
+    }
+}
+
+/// Earlier chat messages sent along with every chat prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContextConfig {
+    /// Send the conversation so far; off makes every prompt stand alone
+    pub enabled: bool,
+    /// Tokens the earlier messages may take up
+    pub max_tokens: usize,
+    /// Which messages to leave out once they exceed `max_tokens`
+    #[serde(default)]
+    pub truncation: Truncation,
+}
+
+impl Default for ContextConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_tokens: 4000,
+            truncation: Truncation::DropOldest,
+        }
+    }
+}
+
+/// How a conversation is cut down to the context budget
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Truncation {
+    /// Leave out the oldest exchanges first
+    #[default]
+    DropOldest,
+    /// Keep the first exchange, which usually sets the task, and leave out
+    /// the ones after it
+    KeepFirst,
+}
+
+/// Connectivity detection settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Host and port probed to detect internet access
+    pub probe_host: String,
+    /// Seconds between probes
+    pub probe_interval_secs: u64,
+    /// Milliseconds before a probe counts as failed
+    pub probe_timeout_ms: u64,
+    /// Send prompts to a local provider instead of queueing them while offline
+    pub prefer_local_when_offline: bool,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            probe_host: "cloudflare.com:443".to_string(),
+            probe_interval_secs: 30,
+            probe_timeout_ms: 1500,
+            prefer_local_when_offline: true,
+        }
+    }
+}
+
+/// Token and cost estimate shown before a prompt is sent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostPreviewConfig {
+    /// Print the estimate for every prompt
+    pub show_estimate: bool,
+    /// Ask for confirmation above this many prompt tokens (0 disables)
+    pub confirm_above_tokens: usize,
+    /// Ask for confirmation above this estimated cost in dollars (0 disables)
+    pub confirm_above_cost: f64,
+}
+
+impl Default for CostPreviewConfig {
+    fn default() -> Self {
+        Self {
+            show_estimate: true,
+            confirm_above_tokens: 8_000,
+            confirm_above_cost: 0.05,
+        }
+    }
+}
+
+/// Usage recorded across sessions in the usage ledger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageConfig {
+    /// Show yesterday's and this week's usage per provider on startup
+    pub startup_summary: bool,
+    /// Session cost in dollars above which 💰 turns yellow (0 disables)
+    #[serde(default = "default_cost_warning")]
+    pub cost_warning: f64,
+    /// Session cost in dollars above which 💰 turns red (0 disables)
+    #[serde(default = "default_cost_alert")]
+    pub cost_alert: f64,
+}
+
+fn default_cost_warning() -> f64 {
+    1.0
+}
+
+fn default_cost_alert() -> f64 {
+    5.0
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self {
+            startup_summary: true,
+            cost_warning: default_cost_warning(),
+            cost_alert: default_cost_alert(),
+        }
+    }
+}
+
+/// Stored conversations listed by /sessions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionsConfig {
+    /// Ask the model for a short title after the first prompt
+    pub generate_titles: bool,
+}
+
+impl Default for SessionsConfig {
+    fn default() -> Self {
+        Self {
+            generate_titles: true,
+        }
+    }
+}
+
+/// Encrypted sync of sessions between machines with /sync
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncConfig {
+    /// Where synced data is kept
+    pub backend: SyncBackend,
+    /// Git remote, WebDAV folder URL, or S3 endpoint such as `https://s3.eu-west-1.amazonaws.com`
+    pub url: String,
+    /// WebDAV user name or S3 access key id
+    pub username: String,
+    /// WebDAV password or S3 secret access key
+    pub password: String,
+    /// S3 bucket, optionally with a folder: `bucket/ai-coder`
+    pub bucket: String,
+    /// S3 region, `us-east-1` when empty
+    pub region: String,
+    /// Passphrase the data is encrypted with, `AI_CODER_SYNC_PASSPHRASE` when empty
+    pub passphrase: String,
+    /// Also sync this configuration, API keys included; the sync section stays local
+    pub include_config: bool,
+}
+
+/// Stores for synced data
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncBackend {
+    /// Sync is not set up
+    #[default]
+    Off,
+    /// A git repository the user can push to
+    Git,
+    /// A WebDAV folder
+    WebDav,
+    /// An S3 bucket or S3-compatible object store
+    S3,
+}
+
+impl SyncConfig {
+    /// The configured passphrase or the one in the environment
+    pub fn passphrase(&self) -> Option<String> {
+        if !self.passphrase.is_empty() {
+            return Some(self.passphrase.clone());
+        }
+        std::env::var("AI_CODER_SYNC_PASSPHRASE")
+            .ok()
+            .filter(|passphrase| !passphrase.is_empty())
+    }
+}
+
+/// Encryption at rest of stored sessions, the prompt library, unsent prompts and exports
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EncryptionConfig {
+    /// Encrypt these files whenever they are written
+    pub enabled: bool,
+    /// Where the key comes from
+    pub key_source: KeySource,
+}
+
+/// Where files may be read and written on behalf of commands and responses
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceConfig {
+    /// Directory files must lie under; empty for the working directory
+    pub root: String,
+    /// Globs of files that are never read or written, even inside the root;
+    /// `~/` expands to the home directory
+    pub deny: Vec<String>,
+}
+
+impl Default for WorkspaceConfig {
+    fn default() -> Self {
+        Self {
+            root: String::new(),
+            deny: [
+                "~/.ssh/**",
+                "~/.gnupg/**",
+                "~/.aws/**",
+                "~/.ai-coder/**",
+                ".env",
+                "*.pem",
+            ]
+            .map(String::from)
+            .to_vec(),
+        }
+    }
+}
+
+/// Guarding prompts against instructions hidden in untrusted content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InjectionGuardConfig {
+    /// Send attachments, pinned output and fetched pages between
+    /// untrusted-content markers
+    pub mark_untrusted: bool,
+    /// Look for instruction-like text in that content and, when found, do
+    /// not run code blocks of the response automatically
+    pub scan: bool,
+}
+
+impl Default for InjectionGuardConfig {
+    fn default() -> Self {
+        Self {
+            mark_untrusted: true,
+            scan: true,
+        }
+    }
+}
+
+/// What happens when a guardrail finds something in an outgoing prompt
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GuardrailAction {
+    /// Do not send the prompt
+    #[default]
+    Block,
+    /// Show what was found and ask before sending
+    Warn,
+    /// Do not check
+    Off,
+}
+
+/// A check of outgoing prompts: a regex, or a command reading the prompt on stdin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GuardrailRule {
+    /// Shown when the rule matches
+    pub name: String,
+    /// Regex searched for in the prompt
+    #[serde(default)]
+    pub pattern: Option<String>,
+    /// Shell command given the prompt on stdin; a non-zero exit is a match
+    /// and its output says why
+    #[serde(default)]
+    pub command: Option<String>,
+    #[serde(default)]
+    pub action: GuardrailAction,
+}
+
+/// Checks of prompts before they leave the machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardrailsConfig {
+    /// API keys, access tokens and private keys in prompts
+    pub secrets: GuardrailAction,
+    /// Also check prompts for models on this machine (Ollama, LM Studio, llama.cpp)
+    pub check_local: bool,
+    /// Organization-specific checks, such as internal hostnames or customer ids
+    pub rules: Vec<GuardrailRule>,
+}
+
+impl Default for GuardrailsConfig {
+    fn default() -> Self {
+        Self {
+            secrets: GuardrailAction::Warn,
+            check_local: false,
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Price of a model, used instead of the built-in table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// Provider the price applies to; every provider when left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<crate::ai::types::Provider>,
+    /// Model name, or a prefix such as `gpt-4o` for all of its snapshots
+    pub model: String,
+    /// Dollars per 1,000 prompt tokens
+    pub prompt: f64,
+    /// Dollars per 1,000 completion tokens
+    pub completion: f64,
+}
+
+/// Classifiers that moderate responses
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationBackend {
+    /// OpenAI's moderation endpoint
+    #[default]
+    OpenAI,
+    /// A local classifier command
+    Command,
+}
+
+/// What happens to a response in a moderated category
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationAction {
+    /// Show the response with a warning
+    #[default]
+    Flag,
+    /// Withhold the response and do not run its code blocks
+    Block,
+}
+
+/// Moderation of chat responses after they are generated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationConfig {
+    pub enabled: bool,
+    pub backend: ModerationBackend,
+    /// Model of the OpenAI moderation endpoint
+    pub model: String,
+    /// Endpoint of the moderation API; empty uses the OpenAI endpoint
+    pub endpoint: String,
+    /// Classifier for the `command` backend: gets the response on stdin and
+    /// prints the categories it matches, separated by commas or lines
+    pub command: String,
+    /// Categories acted on; empty for every category the classifier flags
+    pub categories: Vec<String>,
+    pub action: ModerationAction,
+    /// Withhold responses when the classifier cannot be reached
+    pub fail_closed: bool,
+}
+
+impl Default for ModerationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            backend: ModerationBackend::OpenAI,
+            model: "omni-moderation-latest".to_string(),
+            endpoint: String::new(),
+            command: String::new(),
+            categories: Vec::new(),
+            action: ModerationAction::Flag,
+            fail_closed: false,
+        }
+    }
+}
+
+impl ModerationConfig {
+    /// Whether responses are held back until moderated instead of streamed
+    pub fn holds_responses(&self) -> bool {
+        self.enabled && (self.action == ModerationAction::Block || self.fail_closed)
+    }
+}
+
+/// Downloading web pages with /fetch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FetchConfig {
+    /// Tokens of page text attached at most; longer pages are cut
+    pub token_budget: usize,
+    /// Seconds to wait for a page
+    pub timeout_secs: u64,
+    /// Bytes downloaded at most
+    pub max_bytes: usize,
+}
+
+impl Default for FetchConfig {
+    fn default() -> Self {
+        Self {
+            token_budget: 4000,
+            timeout_secs: 20,
+            max_bytes: 2_000_000,
+        }
+    }
+}
+
+/// Background indexing of the project for `/index search`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexConfig {
+    /// Index the workspace in the background while the app runs
+    pub enabled: bool,
+    /// Provider computing the embeddings: Ollama or an OpenAI-compatible one
+    pub provider: crate::ai::types::Provider,
+    /// Embedding model
+    pub model: String,
+    /// Endpoint of the embeddings API; empty uses the provider's endpoint
+    pub endpoint: String,
+    /// Lines per embedded chunk
+    pub chunk_lines: usize,
+    /// Larger files are not indexed
+    pub max_file_bytes: u64,
+    /// Seconds between checks for changed files
+    pub poll_secs: u64,
+    /// Chunks attached by a search
+    pub top_k: usize,
+    /// File extensions indexed; empty indexes every text file
+    pub extensions: Vec<String>,
+}
+
+impl Default for IndexConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            provider: crate::ai::types::Provider::Ollama,
+            model: "nomic-embed-text".to_string(),
+            endpoint: String::new(),
+            chunk_lines: 40,
+            max_file_bytes: 200_000,
+            poll_secs: 10,
+            top_k: 5,
+            extensions: [
+                "rs", "py", "js", "ts", "tsx", "jsx", "go", "java", "kt", "c", "h", "cpp", "hpp",
+                "cs", "rb", "php", "swift", "sh", "sql", "md", "toml", "yaml", "yml", "json",
+            ]
+            .map(String::from)
+            .to_vec(),
+        }
+    }
+}
+
+/// A language server started for `/definition`, `/references` and `/hover`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LanguageServer {
+    /// Program speaking LSP over stdin and stdout
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Extensions of the files the server handles; the server is only
+    /// started when the workspace has such files
+    #[serde(default)]
+    pub extensions: Vec<String>,
+}
+
+/// Language servers asked about symbols of the workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LspConfig {
+    /// Servers by LSP language id
+    pub servers: BTreeMap<String, LanguageServer>,
+    /// Seconds a server may take to start and index before a query gives up
+    pub startup_timeout_secs: u64,
+    /// Lines shown around each definition and reference
+    pub context_lines: usize,
+    /// References attached by `/references`
+    pub max_references: usize,
+}
+
+impl Default for LspConfig {
+    fn default() -> Self {
+        let server = |command: &str, args: &[&str], extensions: &[&str]| LanguageServer {
+            command: command.to_string(),
+            args: args.iter().map(|arg| arg.to_string()).collect(),
+            extensions: extensions.iter().map(|ext| ext.to_string()).collect(),
+        };
+        let mut servers = BTreeMap::new();
+        servers.insert("rust".to_string(), server("rust-analyzer", &[], &["rs"]));
+        servers.insert(
+            "python".to_string(),
+            server("pyright-langserver", &["--stdio"], &["py"]),
+        );
+        servers.insert(
+            "typescript".to_string(),
+            server(
+                "typescript-language-server",
+                &["--stdio"],
+                &["ts", "tsx", "js", "jsx"],
+            ),
+        );
+        servers.insert("go".to_string(), server("gopls", &[], &["go"]));
+        Self {
+            servers,
+            startup_timeout_secs: 60,
+            context_lines: 3,
+            max_references: 20,
+        }
+    }
+}
+
+/// Editor that file locations in the output are opened in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EditorConfig {
+    /// Command with `{file}`, `{line}` and `{column}`, e.g. `code -g {file}:{line}`
+    /// or `vim +{line} {file}`; empty uses `$VISUAL` or `$EDITOR`
+    pub command: String,
+    /// The editor runs in the terminal, so the interface is suspended until it exits
+    pub terminal: bool,
+}
+
+impl Default for EditorConfig {
+    fn default() -> Self {
+        Self {
+            command: String::new(),
+            terminal: true,
+        }
+    }
+}
+
+/// Sources of the encryption key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeySource {
+    /// A random key kept in the OS keyring (Keychain, Credential Manager, Secret Service)
+    #[default]
+    Keyring,
+    /// A key derived from the `AI_CODER_PASSPHRASE` environment variable
+    Passphrase,
+}
+
+/// How a session's cost compares to the configured thresholds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CostLevel {
+    /// Below both thresholds
+    Normal,
+    /// Above `cost_warning`
+    Warning,
+    /// Above `cost_alert`
+    Alert,
+}
+
+impl UsageConfig {
+    /// Level of a session cost; thresholds set to 0 are ignored
+    pub fn cost_level(&self, cost: f64) -> CostLevel {
+        if self.cost_alert > 0.0 && cost >= self.cost_alert {
+            CostLevel::Alert
+        } else if self.cost_warning > 0.0 && cost >= self.cost_warning {
+            CostLevel::Warning
+        } else {
+            CostLevel::Normal
+        }
+    }
+}
+
+/// Files and command output attached with /attach or `@path`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttachmentsConfig {
+    /// Attachments above this many tokens are summarized or split into chunks
+    pub context_budget: usize,
+    /// Tokens per chunk sent to the model for summarizing
+    pub chunk_tokens: usize,
+    /// Tokens of all files named with `@path` in one prompt; files past it are cut or skipped
+    #[serde(default = "default_reference_budget")]
+    pub reference_budget: usize,
+    /// Files named with `@path` above this size are not read
+    #[serde(default = "default_max_file_bytes")]
+    pub max_file_bytes: u64,
+}
+
+fn default_reference_budget() -> usize {
+    8_000
+}
+
+fn default_max_file_bytes() -> u64 {
+    256 * 1024
+}
+
+impl Default for AttachmentsConfig {
+    fn default() -> Self {
+        Self {
+            context_budget: 6_000,
+            chunk_tokens: 3_000,
+            reference_budget: default_reference_budget(),
+            max_file_bytes: default_max_file_bytes(),
+        }
+    }
+}
+
+/// Background task scheduling
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TasksConfig {
+    /// Most tasks running at once (0 for no limit); waiting tasks start by priority
+    pub max_concurrent: usize,
+}
+
+impl Default for TasksConfig {
+    fn default() -> Self {
+        Self { max_concurrent: 4 }
+    }
+}
+
+/// Release checks with `/update`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateConfig {
+    /// GitHub repository (`owner/name`) whose releases are checked
+    pub repository: String,
+    /// Let `/update install` replace the binary; turn off in managed environments
+    pub allow_self_update: bool,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            repository: "suyashbhawsar/ai-coder".to_string(),
+            allow_self_update: true,
+        }
+    }
+}
+
+/// Prompt templates and the team repository that distributes them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TemplatesConfig {
+    /// Git URL of the team repository, cloned into `~/.ai-coder/templates/team`
+    pub team_url: String,
+    /// Branch to follow, the remote's default branch when empty
+    #[serde(default)]
+    pub team_branch: String,
+}
+
+/// Read-only session sharing with `ai-coder share` and `/share`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShareConfig {
+    /// Address the viewer is served on; `0.0.0.0:<port>` lets other machines connect
+    pub address: String,
+}
+
+impl Default for ShareConfig {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:8765".to_string(),
+        }
+    }
+}
+
+/// Output pane settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UiConfig {
+    /// Command outputs longer than this many lines can be collapsed
+    pub collapse_after_lines: usize,
+    /// Show line numbers in the output pane
+    #[serde(default)]
+    pub line_numbers: bool,
+    /// Prefix every command in the output pane with the time it was entered
+    #[serde(default)]
+    pub timestamps: bool,
+    /// strftime format of the timestamps
+    #[serde(default = "default_timestamp_format")]
+    pub timestamp_format: String,
+    /// Lines of the input box set with Ctrl+Up/Down; 0 sizes it to the input
+    #[serde(default)]
+    pub input_height: u16,
+    /// Fewer redraws and no animations for slow links; `auto` switches on when frames are slow
+    #[serde(default)]
+    pub low_bandwidth: LowBandwidth,
+    /// Show chat responses in the output pane while they are generated
+    #[serde(default = "default_stream_responses")]
+    pub stream_responses: bool,
+    /// Set the terminal title to the directory, marked while the AI is busy
+    #[serde(default = "default_terminal_title")]
+    pub terminal_title: bool,
+    /// Report task progress to the terminal (OSC 9;4); `auto` only in terminals known to show it
+    #[serde(default)]
+    pub taskbar_progress: TaskbarProgress,
+    /// Show a placeholder and hints for the current state in the empty input
+    #[serde(default = "default_input_hints")]
+    pub input_hints: bool,
+}
+
+/// When to send progress sequences to the terminal
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskbarProgress {
+    /// In Windows Terminal and ConEmu, which show it in the tab and taskbar
+    #[default]
+    Auto,
+    /// Always, for other terminals that understand OSC 9;4
+    On,
+    /// Never
+    Off,
+}
+
+/// When to use low-bandwidth rendering
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LowBandwidth {
+    /// Switch on when frames take long to flush
+    #[default]
+    Auto,
+    /// Always on
+    On,
+    /// Never on
+    Off,
+}
+
+fn default_timestamp_format() -> String {
+    "%H:%M:%S".to_string()
+}
+
+fn default_stream_responses() -> bool {
+    true
+}
+
+fn default_terminal_title() -> bool {
+    true
+}
+
+fn default_input_hints() -> bool {
+    true
+}
+
+impl UiConfig {
+    /// The timestamp format, or the default if it is not a valid strftime format
+    pub fn valid_timestamp_format(&self) -> String {
+        let invalid = StrftimeItems::new(&self.timestamp_format).any(|item| item == Item::Error);
+        if invalid {
+            default_timestamp_format()
+        } else {
+            self.timestamp_format.clone()
+        }
+    }
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            collapse_after_lines: 15,
+            line_numbers: false,
+            timestamps: false,
+            timestamp_format: default_timestamp_format(),
+            input_height: 0,
+            low_bandwidth: LowBandwidth::Auto,
+            stream_responses: true,
+            terminal_title: true,
+            taskbar_progress: TaskbarProgress::Auto,
+            input_hints: true,
+        }
+    }
+}
+
+/// Speech-to-text input
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeechConfig {
+    /// Where recordings are transcribed
+    pub backend: SpeechBackend,
+    /// Command recording from the microphone until interrupted; `{file}` is the WAV path
+    pub record_command: String,
+    /// whisper.cpp program
+    pub whisper_command: String,
+    /// whisper.cpp model, `~/.ai-coder/models/ggml-base.en.bin` when empty
+    pub whisper_model: String,
+    /// Spoken language such as `en`, empty to detect it
+    pub language: String,
+    /// Base URL of the OpenAI-compatible transcription API
+    pub endpoint: String,
+    /// Transcription model of the API
+    pub model: String,
+    /// API key, the OpenAI provider's key when empty
+    pub api_key: String,
+}
+
+/// Speech-to-text backends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpeechBackend {
+    /// whisper.cpp on this machine
+    #[default]
+    Whisper,
+    /// The OpenAI audio transcription API
+    OpenAI,
+}
+
+impl Default for SpeechConfig {
+    fn default() -> Self {
+        Self {
+            backend: SpeechBackend::Whisper,
+            record_command: crate::platform::record_command().to_string(),
+            whisper_command: "whisper-cli".to_string(),
+            whisper_model: "".to_string(),
+            language: "".to_string(),
+            endpoint: "https://api.openai.com/v1".to_string(),
+            model: "whisper-1".to_string(),
+            api_key: "".to_string(),
+        }
+    }
+}
+
+/// Reading AI responses aloud
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TtsConfig {
+    /// Read chat responses aloud, toggled with /speak
+    pub enabled: bool,
+    /// What turns text into speech
+    pub engine: TtsEngine,
+    /// Speech engine command reading the text from stdin
+    pub command: String,
+    /// Base URL of the OpenAI-compatible speech API
+    pub endpoint: String,
+    /// Speech model of the API
+    pub model: String,
+    /// Voice of the API
+    pub voice: String,
+    /// API key, the OpenAI provider's key when empty
+    pub api_key: String,
+    /// Command playing synthesized speech; `{file}` is the WAV path
+    pub player_command: String,
+}
+
+/// Text-to-speech engines
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TtsEngine {
+    /// A speech engine on this machine such as `say` or `espeak`
+    #[default]
+    System,
+    /// The OpenAI audio speech API
+    OpenAI,
+}
+
+impl Default for TtsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            engine: TtsEngine::System,
+            command: crate::platform::speak_command().to_string(),
+            endpoint: "https://api.openai.com/v1".to_string(),
+            model: "tts-1".to_string(),
+            voice: "alloy".to_string(),
+            api_key: "".to_string(),
+            player_command: crate::platform::play_command().to_string(),
+        }
+    }
+}
+
+/// Image generation with /image
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageConfig {
+    /// What generates images
+    pub backend: ImageBackend,
+    /// Base URL of the OpenAI images API or the Stable Diffusion web UI
+    pub endpoint: String,
+    /// Image model of the OpenAI API
+    pub model: String,
+    /// Width and height such as `1024x1024`
+    pub size: String,
+    /// Sampling steps of Stable Diffusion
+    pub steps: u32,
+    /// API key, the OpenAI provider's key when empty
+    pub api_key: String,
+    /// Directory images are saved to, `~/.ai-coder/images` when empty
+    pub output_dir: String,
+    /// How images are shown in the terminal
+    pub display: ImageDisplay,
+}
+
+/// Image generation backends
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImageBackend {
+    /// The OpenAI images API
+    #[default]
+    OpenAI,
+    /// The txt2img API of a local Stable Diffusion web UI (AUTOMATIC1111, Forge)
+    StableDiffusion,
+}
+
+/// Terminal graphics used to show generated images
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageDisplay {
+    /// Kitty graphics or sixel when the terminal supports them
+    #[default]
+    Auto,
+    /// The kitty graphics protocol
+    Kitty,
+    /// Sixel through `img2sixel`
+    Sixel,
+    /// Only print the path
+    Off,
+}
+
+impl Default for ImageConfig {
+    fn default() -> Self {
+        Self {
+            backend: ImageBackend::OpenAI,
+            endpoint: "https://api.openai.com/v1".to_string(),
+            model: "dall-e-3".to_string(),
+            size: "1024x1024".to_string(),
+            steps: 25,
+            api_key: "".to_string(),
+            output_dir: "".to_string(),
+            display: ImageDisplay::Auto,
+        }
+    }
+}
+
+/// A step applied to chat responses before they are displayed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PostProcessor {
+    /// Drop the reasoning of responses instead of showing it folded
+    StripThinking,
+    /// Mask API keys and bearer tokens
+    RedactSecrets,
+    /// Pipe code blocks in `language` through a formatter reading stdin
+    FormatCode { language: String, command: String },
+    /// Pipe the whole response through a command reading stdin
+    Command { command: String },
+}
+
+/// Main application configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppConfig {
+    /// Theme settings
+    pub theme: ThemeConfig,
+    /// AI provider settings
+    pub ai: AIConfig,
+    /// History size
+    pub history_size: usize,
+    /// Enable mouse support
+    pub mouse_enabled: bool,
+    /// Enable logging
+    pub logging_enabled: bool,
+    /// Log file path (relative to config directory)
+    pub log_file: Option<String>,
+    /// Interpreters for executing code blocks in AI responses
+    #[serde(default)]
+    pub code_execution: CodeExecutionConfig,
+    /// Limits for multi-file refactoring requests
+    #[serde(default)]
+    pub refactor: RefactorConfig,
+    /// Offline detection settings
+    #[serde(default)]
+    pub network: NetworkConfig,
+    /// Prompt size and cost preview
+    #[serde(default)]
+    pub cost_preview: CostPreviewConfig,
+    /// Usage ledger and summaries
+    #[serde(default)]
+    pub usage: UsageConfig,
+    /// Model tiers chosen by the kind of prompt
+    #[serde(default)]
+    pub routing: RoutingConfig,
+    /// Roles with their own system prompt, model and temperature
+    #[serde(default)]
+    pub personas: BTreeMap<String, Persona>,
+    /// Temperature presets of the commands that send prompts
+    #[serde(default)]
+    pub sampling: SamplingConfig,
+    /// Conversation history sent with chat prompts
+    #[serde(default)]
+    pub context: ContextConfig,
+    /// Stored conversations
+    #[serde(default)]
+    pub sessions: SessionsConfig,
+    /// Encrypted sync between machines
+    #[serde(default)]
+    pub sync: SyncConfig,
+    /// Encryption of stored data
+    #[serde(default)]
+    pub encryption: EncryptionConfig,
+    /// Workspace root and denied files
+    #[serde(default)]
+    pub workspace: WorkspaceConfig,
+    /// Untrusted-content markers and scanning
+    #[serde(default)]
+    pub injection_guard: InjectionGuardConfig,
+    /// Checks of outgoing prompts
+    #[serde(default)]
+    pub guardrails: GuardrailsConfig,
+    /// Checks of generated responses
+    #[serde(default)]
+    pub moderation: ModerationConfig,
+    /// Model prices replacing the built-in ones (`/config pricing`)
+    #[serde(default)]
+    pub pricing: Vec<ModelPrice>,
+    /// Limits of /fetch
+    #[serde(default)]
+    pub fetch: FetchConfig,
+    /// Background project index
+    #[serde(default)]
+    pub index: IndexConfig,
+    /// Language servers for `/definition`, `/references` and `/hover`
+    #[serde(default)]
+    pub lsp: LspConfig,
+    /// Editor for `/open` and the `o` message key
+    #[serde(default)]
+    pub editor: EditorConfig,
+    /// Budget and chunking of attachments
+    #[serde(default)]
+    pub attachments: AttachmentsConfig,
+    /// Output pane settings
+    #[serde(default)]
+    pub ui: UiConfig,
+    /// Background task scheduling
+    #[serde(default)]
+    pub tasks: TasksConfig,
+    /// Release checks and self-update
+    #[serde(default)]
+    pub update: UpdateConfig,
+    /// Prompt templates and the team repository
+    #[serde(default)]
+    pub templates: TemplatesConfig,
+    /// Read-only session sharing
+    #[serde(default)]
+    pub share: ShareConfig,
+    /// Shortcuts defined with /alias: `/name` expands to the command
+    #[serde(default)]
+    pub aliases: BTreeMap<String, String>,
+    /// Push-to-talk recording and transcription
+    #[serde(default)]
+    pub speech: SpeechConfig,
+    /// Reading responses aloud
+    #[serde(default)]
+    pub tts: TtsConfig,
+    /// Image generation
+    #[serde(default)]
+    pub image: ImageConfig,
+    /// Steps applied to chat responses before display, in order
+    #[serde(default)]
+    pub postprocess: Vec<PostProcessor>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            theme: ThemeConfig::default(),
+            ai: AIConfig::default(),
+            history_size: 100,
+            mouse_enabled: true,
+            logging_enabled: false,
+            log_file: Some("ai-coder.log".to_string()),
+            code_execution: CodeExecutionConfig::default(),
+            refactor: RefactorConfig::default(),
+            network: NetworkConfig::default(),
+            cost_preview: CostPreviewConfig::default(),
+            usage: UsageConfig::default(),
+            routing: RoutingConfig::default(),
+            personas: BTreeMap::new(),
+            sampling: SamplingConfig::default(),
+            context: ContextConfig::default(),
+            sessions: SessionsConfig::default(),
+            sync: SyncConfig::default(),
+            encryption: EncryptionConfig::default(),
+            workspace: WorkspaceConfig::default(),
+            injection_guard: InjectionGuardConfig::default(),
+            guardrails: GuardrailsConfig::default(),
+            moderation: ModerationConfig::default(),
+            pricing: Vec::new(),
+            fetch: FetchConfig::default(),
+            index: IndexConfig::default(),
+            lsp: LspConfig::default(),
+            editor: EditorConfig::default(),
+            attachments: AttachmentsConfig::default(),
+            ui: UiConfig::default(),
+            tasks: TasksConfig::default(),
+            update: UpdateConfig::default(),
+            templates: TemplatesConfig::default(),
+            share: ShareConfig::default(),
+            aliases: BTreeMap::new(),
+            speech: SpeechConfig::default(),
+            tts: TtsConfig::default(),
+            image: ImageConfig::default(),
+            postprocess: Vec::new(),
+        }
+    }
+}
+
+// Global configuration instance
+static CONFIG: Lazy<Mutex<AppConfig>> = Lazy::new(|| {
+    let config = load_config().unwrap_or_default();
+    Mutex::new(config)
+});
 
 /// Get a reference to the application configuration
 pub fn get_config() -> AppConfig {
     CONFIG.lock().unwrap().clone()
 }
 
+/// The configuration if it can be read without waiting, e.g. from a panic hook
+///
+/// `None` while another caller holds it, including a thread that panicked
+/// while holding it; a lock poisoned by a panic still gives the configuration.
+pub fn try_get_config() -> Option<AppConfig> {
+    match CONFIG.try_lock() {
+        Ok(config) => Some(config.clone()),
+        Err(TryLockError::Poisoned(e)) => Some(e.into_inner().clone()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
 /// Update the application configuration
 pub fn update_config(config: AppConfig) -> Result<(), io::Error> {
     let mut current = CONFIG.lock().unwrap();
@@ -1,3 +1,4 @@
+use crate::platform;
 use anyhow::Result;
 use crossterm::{
     ExecutableCommand,
@@ -7,9 +8,11 @@ use crossterm::{
     terminal::{EnterAlternateScreen, enable_raw_mode},
 };
 use std::io;
-use std::sync::mpsc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::thread;
 use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
 
 pub use crossterm::event::KeyEvent;
 
@@ -27,18 +30,28 @@ pub enum Event {
 
 pub struct EventHandler {
     #[allow(dead_code)]
-    sender: mpsc::Sender<Event>,
-    receiver: mpsc::Receiver<Event>,
+    sender: mpsc::UnboundedSender<Event>,
+    receiver: mpsc::UnboundedReceiver<Event>,
     #[allow(dead_code)]
     handler: thread::JoinHandle<()>,
+    paused: Arc<AtomicBool>,
+    parked: Arc<AtomicBool>,
+    /// Send every key as it is, for a command running in a pty
+    passthrough: Arc<AtomicBool>,
 }
 
 impl EventHandler {
     pub fn new(tick_rate: u64) -> Self {
         let tick_rate = Duration::from_millis(tick_rate);
-        let (sender, receiver) = mpsc::channel();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let paused = Arc::new(AtomicBool::new(false));
+        let parked = Arc::new(AtomicBool::new(false));
+        let passthrough = Arc::new(AtomicBool::new(false));
         let handler = {
             let sender = sender.clone();
+            let paused = paused.clone();
+            let parked = parked.clone();
+            let passthrough = passthrough.clone();
             thread::spawn(move || {
                 let mut stdout = io::stdout();
                 enable_raw_mode().expect("Failed to enable raw mode");
@@ -51,12 +64,29 @@ impl EventHandler {
 
                 let mut last_tick = Instant::now();
                 loop {
+                    // Stop reading the terminal while another program owns it
+                    if paused.load(Ordering::SeqCst) {
+                        parked.store(true, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(20));
+                        continue;
+                    }
+                    parked.store(false, Ordering::SeqCst);
+
                     let timeout = tick_rate
                         .checked_sub(last_tick.elapsed())
                         .unwrap_or_else(|| Duration::from_secs(0));
 
                     if event::poll(timeout).expect("Failed to poll new events") {
                         match event::read().expect("Unable to read event") {
+                            // Windows also reports key releases; only presses are input
+                            CrosstermEvent::Key(e) if !platform::is_key_press(&e) => {}
+                            // Esc, Ctrl+C and Ctrl+D belong to the command in the pty
+                            CrosstermEvent::Key(e) if passthrough.load(Ordering::SeqCst) => {
+                                if let Err(err) = sender.send(Event::Key(e)) {
+                                    eprintln!("Error sending key event: {}", err);
+                                    break;
+                                }
+                            }
                             CrosstermEvent::Key(e) => {
                                 // Handle scroll keys and abort keys
                                 match e.code {
@@ -162,10 +192,36 @@ impl EventHandler {
             sender,
             receiver,
             handler,
+            paused,
+            parked,
+            passthrough,
+        }
+    }
+
+    /// Stop reading terminal events, waiting until the event thread is idle
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while !self.parked.load(Ordering::SeqCst) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(5));
         }
     }
 
-    pub fn next(&self) -> Result<Event> {
-        Ok(self.receiver.recv()?)
+    /// Send Esc, Ctrl+C, Ctrl+D and PageUp/Down as keys instead of handling them
+    pub fn pass_keys_through(&self, on: bool) {
+        self.passthrough.store(on, Ordering::SeqCst);
+    }
+
+    /// Resume reading terminal events
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    /// Wait for the next event without blocking the runtime
+    pub async fn next(&mut self) -> Result<Event> {
+        self.receiver
+            .recv()
+            .await
+            .ok_or_else(|| anyhow::anyhow!("Event thread stopped"))
     }
 }
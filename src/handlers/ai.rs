@@ -123,6 +123,63 @@ impl AIHandler {
                 }
                 Ok(())
             }
+            Provider::OpenRouter => {
+                // For OpenRouter we check for an API key or a device flow login
+                if config.ai.openrouter.api_key.is_empty() && config.ai.openrouter.oauth.is_none() {
+                    return Err(AIError::Authentication(
+                        "OpenRouter API key is not set. Please update your configuration."
+                            .to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            Provider::Groq => {
+                // For Groq we check for an API key or a device flow login
+                if config.ai.groq.api_key.is_empty() && config.ai.groq.oauth.is_none() {
+                    return Err(AIError::Authentication(
+                        "Groq API key is not set. Please update your configuration.".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            Provider::Mistral => {
+                // For Mistral we check for an API key or a device flow login
+                if config.ai.mistral.api_key.is_empty() && config.ai.mistral.oauth.is_none() {
+                    return Err(AIError::Authentication(
+                        "Mistral API key is not set. Please update your configuration.".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            Provider::AzureOpenAI => {
+                // For Azure we check for a resource and an API key or a login
+                crate::ai::azure::endpoint(&config.ai.azure)?;
+                if config.ai.azure.api_key.is_empty() && config.ai.azure.oauth.is_none() {
+                    return Err(AIError::Authentication(
+                        "Azure OpenAI API key is not set. Please update your configuration."
+                            .to_string(),
+                    ));
+                }
+                Ok(())
+            }
+            Provider::LlamaCpp => {
+                // Runs in-process, so only the build and the model file matter
+                crate::ai::llamacpp::check_available(
+                    &config.ai.llamacpp,
+                    &config.ai.get_active_model_config(),
+                )
+            }
+            Provider::Custom => {
+                // Any answer from the server means it is up; credentials are checked by requests
+                let endpoint = config.ai.custom.endpoint.trim_end_matches('/').to_string();
+                match client.get(format!("{}/models", endpoint)).send().await {
+                    Ok(_) => Ok(()),
+                    Err(e) => Err(AIError::NetworkError(format!(
+                        "The custom endpoint {} is not reachable: {}",
+                        endpoint, e
+                    ))),
+                }
+            }
             Provider::LMStudio => {
                 // Check if LM Studio is running
                 let endpoint = config.ai.lmstudio.endpoint.clone();
@@ -167,7 +224,7 @@ impl AIHandler {
             Ok(p) => p,
             Err(_) => {
                 return Ok(format!(
-                    "⚠️ Unsupported provider: {}. Valid providers are: ollama, openai, anthropic, lmstudio",
+                    "⚠️ Unsupported provider: {}. Valid providers are: ollama, openai, anthropic, lmstudio, openrouter, groq, mistral, llamacpp, azure, custom",
                     provider_str
                 ));
             }
@@ -271,6 +328,93 @@ impl AIHandler {
                             config.ai.lmstudio.models.len() - 1;
                     }
                 }
+                Provider::OpenRouter => {
+                    // Similar logic for OpenRouter
+                    let mut found = false;
+                    for (i, m) in config.ai.openrouter.models.iter().enumerate() {
+                        if m.name == model {
+                            config.ai.openrouter.current_model_index = i;
+                            found = true;
+                            break;
+                        }
+                    }
+
+                    if !found {
+                        config
+                            .ai
+                            .openrouter
+                            .models
+                            .push(crate::config::ModelConfig {
+                                name: model.to_string(),
+                                ..Default::default()
+                            });
+                        config.ai.openrouter.current_model_index =
+                            config.ai.openrouter.models.len() - 1;
+                    }
+                }
+                Provider::Groq => {
+                    // Similar logic for Groq
+                    let mut found = false;
+                    for (i, m) in config.ai.groq.models.iter().enumerate() {
+                        if m.name == model {
+                            config.ai.groq.current_model_index = i;
+                            found = true;
+                            break;
+                        }
+                    }
+
+                    if !found {
+                        config.ai.groq.models.push(crate::config::ModelConfig {
+                            name: model.to_string(),
+                            ..Default::default()
+                        });
+                        config.ai.groq.current_model_index = config.ai.groq.models.len() - 1;
+                    }
+                }
+                Provider::Mistral => {
+                    // Similar logic for Mistral
+                    let mut found = false;
+                    for (i, m) in config.ai.mistral.models.iter().enumerate() {
+                        if m.name == model {
+                            config.ai.mistral.current_model_index = i;
+                            found = true;
+                            break;
+                        }
+                    }
+
+                    if !found {
+                        config.ai.mistral.models.push(crate::config::ModelConfig {
+                            name: model.to_string(),
+                            ..Default::default()
+                        });
+                        config.ai.mistral.current_model_index = config.ai.mistral.models.len() - 1;
+                    }
+                }
+                Provider::LlamaCpp => {
+                    // Similar logic for LlamaCpp
+                    let mut found = false;
+                    for (i, m) in config.ai.llamacpp.models.iter().enumerate() {
+                        if m.name == model {
+                            config.ai.llamacpp.current_model_index = i;
+                            found = true;
+                            break;
+                        }
+                    }
+
+                    if !found {
+                        config.ai.llamacpp.models.push(crate::config::ModelConfig {
+                            name: model.to_string(),
+                            ..Default::default()
+                        });
+                        config.ai.llamacpp.current_model_index =
+                            config.ai.llamacpp.models.len() - 1;
+                    }
+                }
+                Provider::AzureOpenAI => {
+                    // Models are deployments, selected or added by name
+                    config.ai.select_model(model);
+                }
+                Provider::Custom => config.ai.select_model(model),
             }
         })
         .map_err(|e| AIError::ConfigError(format!("Failed to update config: {}", e)))?;
@@ -323,7 +467,10 @@ impl AIHandler {
             result.push_str("Output:\n");
 
             // Execute the bash block and capture output
-            match crate::handlers::bash::handle_bash_command(block) {
+            match crate::handlers::bash::handle_bash_command(
+                block,
+                &crate::utils::get_current_dir(),
+            ) {
                 Ok(output) => {
                     result.push_str(&output);
                 }
@@ -0,0 +1,361 @@
+//! Attachment handler
+//!
+//! This module loads files and command output for `/attach` and prepares
+//! content that exceeds the context budget: it is split into chunks at line
+//! boundaries, each chunk is summarized by the model, and the summaries are
+//! merged in groups until a single summary is left (map-reduce).
+//!
+//! Chat prompts can also name files inline, as in `explain @src/app.rs`.
+//! Those are read for that prompt only, within a token budget shared by all
+//! the files of the prompt.
+
+use crate::handlers::{HandlerError, HandlerResult, injection, safe, workspace};
+use crate::platform;
+use crate::utils::count_tokens;
+use std::fs;
+use std::path::Path;
+
+/// Where the content of an attachment comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum Source {
+    /// A file, relative to the working directory
+    File(String),
+    /// Output of a shell command (`/attach !cargo test`)
+    Command(String),
+}
+
+/// Parsed `/attach` arguments
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttachRequest {
+    /// What to attach
+    pub source: Source,
+    /// Attach oversized content as raw chunks instead of a summary
+    pub raw: bool,
+}
+
+/// Content sent as context with every prompt until cleared
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attachment {
+    /// File path or command, with the part or "summary" for prepared content
+    pub name: String,
+    /// Text sent to the model
+    pub content: String,
+}
+
+impl Attachment {
+    /// Create an attachment
+    pub fn new(name: impl Into<String>, content: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            content: content.into(),
+        }
+    }
+
+    /// Estimated tokens of the content
+    pub fn tokens(&self) -> usize {
+        count_tokens(&self.content)
+    }
+}
+
+/// Parse `[--raw] <path>` or `[--raw] !<command>` arguments
+pub fn parse_args(args: &str) -> HandlerResult<AttachRequest> {
+    let (raw, rest) = match args.strip_prefix("--raw") {
+        Some(rest) if rest.is_empty() || rest.starts_with(char::is_whitespace) => {
+            (true, rest.trim())
+        }
+        _ => (false, args.trim()),
+    };
+
+    let source = match rest.strip_prefix('!') {
+        Some(command) if !command.trim().is_empty() => Source::Command(command.trim().to_string()),
+        Some(_) => return Err(usage()),
+        None if rest.is_empty() => return Err(usage()),
+        None => Source::File(rest.to_string()),
+    };
+    Ok(AttachRequest { source, raw })
+}
+
+fn usage() -> HandlerError {
+    HandlerError::Parse(
+        "Usage: /attach [--raw] <file> | /attach [--raw] !<command> | /attach list | /attach clear"
+            .to_string(),
+    )
+}
+
+/// Read the file or run the command, returning a display name and the content
+pub fn load(root: &Path, source: &Source) -> HandlerResult<(String, String)> {
+    match source {
+        Source::File(path) => {
+            let full_path = workspace::check(root, Path::new(path))?;
+            let content = fs::read_to_string(full_path)
+                .map_err(|e| HandlerError::Other(format!("Cannot read {}: {}", path, e)))?;
+            Ok((path.clone(), content))
+        }
+        Source::Command(command) => {
+            safe::check("Attaching command output")?;
+            let output = platform::shell_command(command)
+                .current_dir(root)
+                .output()
+                .map_err(|e| HandlerError::Bash(format!("Cannot run {}: {}", command, e)))?;
+            let mut content = String::from_utf8_lossy(&output.stdout).to_string();
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if !stderr.trim().is_empty() {
+                if !content.is_empty() && !content.ends_with('\n') {
+                    content.push('\n');
+                }
+                content.push_str(&stderr);
+            }
+            Ok((format!("$ {}", command), content))
+        }
+    }
+}
+
+/// Split text into chunks of at most `chunk_tokens` tokens at line boundaries
+///
+/// A single line longer than a chunk is split between words.
+pub fn split_chunks(text: &str, chunk_tokens: usize) -> Vec<String> {
+    let chunk_tokens = chunk_tokens.max(1);
+    let pieces = text.split_inclusive('\n').flat_map(|line| {
+        if count_tokens(line) <= chunk_tokens {
+            vec![line]
+        } else {
+            line.split_inclusive(' ').collect()
+        }
+    });
+
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    let mut current_tokens = 0;
+    for piece in pieces {
+        let tokens = count_tokens(piece);
+        if current_tokens + tokens > chunk_tokens && !current.is_empty() {
+            chunks.push(std::mem::take(&mut current));
+            current_tokens = 0;
+        }
+        current.push_str(piece);
+        current_tokens += tokens;
+    }
+    if !current.trim().is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+/// Group summaries for the next merge round
+///
+/// Groups stay within `budget` tokens but always hold at least two
+/// summaries, so every round shrinks the list.
+pub fn merge_groups(summaries: Vec<String>, budget: usize) -> Vec<Vec<String>> {
+    let mut groups: Vec<Vec<String>> = Vec::new();
+    let mut tokens = 0;
+    for summary in summaries {
+        let summary_tokens = count_tokens(&summary);
+        match groups.last_mut() {
+            Some(group) if group.len() < 2 || tokens + summary_tokens <= budget => {
+                tokens += summary_tokens;
+                group.push(summary);
+            }
+            _ => {
+                tokens = summary_tokens;
+                groups.push(vec![summary]);
+            }
+        }
+    }
+
+    // A trailing single summary joins the group before it
+    if groups.len() > 1 && groups.last().is_some_and(|group| group.len() == 1) {
+        let last = groups.pop().unwrap_or_default();
+        if let Some(group) = groups.last_mut() {
+            group.extend(last);
+        }
+    }
+    groups
+}
+
+/// A file named with `@path` in a chat prompt
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reference {
+    /// The whole file is sent with the prompt
+    Attached(Attachment),
+    /// The file is cut to the remaining budget; holds the tokens of the whole file
+    Truncated(Attachment, usize),
+    /// The file is left out, with the reason
+    Skipped(String, String),
+}
+
+impl Reference {
+    /// The content sent with the prompt, if any
+    pub fn attachment(&self) -> Option<&Attachment> {
+        match self {
+            Reference::Attached(attachment) | Reference::Truncated(attachment, _) => {
+                Some(attachment)
+            }
+            Reference::Skipped(..) => None,
+        }
+    }
+
+    /// One line describing what was attached
+    pub fn describe(&self) -> String {
+        match self {
+            Reference::Attached(attachment) => {
+                format!("{} (~{} tokens)", attachment.name, attachment.tokens())
+            }
+            Reference::Truncated(attachment, total) => format!(
+                "{} (first ~{} of ~{} tokens)",
+                attachment.name,
+                attachment.tokens(),
+                total
+            ),
+            Reference::Skipped(path, reason) => format!("{} skipped: {}", path, reason),
+        }
+    }
+}
+
+/// Paths named with `@` at the start of a word, without trailing punctuation
+///
+/// Repeated paths are returned once. `me@example.com` is not a reference.
+pub fn reference_paths(prompt: &str) -> Vec<String> {
+    let mut paths: Vec<String> = Vec::new();
+    for word in prompt.split_whitespace() {
+        let word = word.trim_start_matches(['(', '[', '"', '\'', '`']);
+        let Some(path) = word.strip_prefix('@') else {
+            continue;
+        };
+        let path = path.trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '"', '\'', '`']);
+        if !path.is_empty() && !paths.iter().any(|known| known == path) {
+            paths.push(path.to_string());
+        }
+    }
+    paths
+}
+
+/// Read a referenced file
+///
+/// `None` when there is no such file, so `@name` in prose stays text; an
+/// error for files that exist but cannot be sent.
+pub fn read_reference(root: &Path, path: &str, max_bytes: u64) -> Option<Result<String, String>> {
+    let joined = root.join(path);
+    if !joined.exists() {
+        return None;
+    }
+    let read = || {
+        let full_path = workspace::check(root, Path::new(path)).map_err(|e| e.to_string())?;
+        let metadata = fs::metadata(&full_path).map_err(|e| e.to_string())?;
+        if metadata.is_dir() {
+            return Err("it is a directory".to_string());
+        }
+        if metadata.len() > max_bytes {
+            return Err(format!(
+                "{} KB is above attachments.max_file_bytes ({} KB)",
+                metadata.len() / 1024,
+                max_bytes / 1024
+            ));
+        }
+        fs::read_to_string(&full_path).map_err(|_| "not a text file".to_string())
+    };
+    Some(read())
+}
+
+/// Fit the files read for a prompt into `budget` tokens, in order
+///
+/// The first file above the remaining budget is cut at a line boundary to
+/// fill it, and the files after it are skipped.
+pub fn fit_references(
+    files: Vec<(String, Result<String, String>)>,
+    budget: usize,
+) -> Vec<Reference> {
+    let mut remaining = budget;
+    files
+        .into_iter()
+        .map(|(path, content)| {
+            let content = match content {
+                Ok(content) => content,
+                Err(reason) => return Reference::Skipped(path, reason),
+            };
+            let tokens = count_tokens(&content);
+            if tokens <= remaining {
+                remaining -= tokens;
+                return Reference::Attached(Attachment::new(path, content));
+            }
+            if remaining == 0 {
+                return Reference::Skipped(
+                    path,
+                    "the reference budget of the prompt is spent".to_string(),
+                );
+            }
+            let head = split_chunks(&content, remaining)
+                .into_iter()
+                .next()
+                .unwrap_or_default();
+            remaining = 0;
+            if head.trim().is_empty() {
+                return Reference::Skipped(
+                    path,
+                    "the reference budget of the prompt is spent".to_string(),
+                );
+            }
+            Reference::Truncated(Attachment::new(path, head), tokens)
+        })
+        .collect()
+}
+
+/// Read the files referenced in a prompt within the budget
+pub fn load_references(root: &Path, prompt: &str, budget: usize, max_bytes: u64) -> Vec<Reference> {
+    let files = reference_paths(prompt)
+        .into_iter()
+        .filter_map(|path| {
+            let content = read_reference(root, &path, max_bytes)?;
+            Some((path, content))
+        })
+        .collect();
+    fit_references(files, budget)
+}
+
+/// Prompt asking for a summary of one chunk
+pub fn chunk_prompt(name: &str, index: usize, total: usize, chunk: &str) -> String {
+    format!(
+        "Summarize part {} of {} of `{}` so it can stand in for the original as context for \
+         later questions. Keep names, signatures, numbers, errors and anything else a reader \
+         would need to refer to; drop repetition. Reply with the summary only.\n\n```\n{}\n```",
+        index + 1,
+        total,
+        name,
+        chunk.trim_end()
+    )
+}
+
+/// Prompt asking to merge summaries of consecutive parts into one
+pub fn merge_prompt(name: &str, summaries: &[String]) -> String {
+    let parts = summaries
+        .iter()
+        .enumerate()
+        .map(|(idx, summary)| format!("### Part {}\n{}", idx + 1, summary.trim()))
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    format!(
+        "These are summaries of consecutive parts of `{}`. Merge them into one summary that \
+         keeps every detail needed to answer questions about it, without repeating anything. \
+         Reply with the summary only.\n\n{}",
+        name, parts
+    )
+}
+
+/// Attachments formatted as context for a prompt
+pub fn context(attachments: &[Attachment]) -> Option<String> {
+    if attachments.is_empty() {
+        return None;
+    }
+    let sections = attachments
+        .iter()
+        .map(|attachment| {
+            let section = format!(
+                "### {}\n```\n{}\n```",
+                attachment.name,
+                attachment.content.trim_end()
+            );
+            injection::mark(&attachment.name, &section)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    Some(format!("Attached for reference:\n\n{}", sections))
+}
@@ -3,10 +3,19 @@
 //! This module handles execution of bash commands and provides
 //! security controls and formatting of outputs.
 
-use crate::handlers::{HandlerError, HandlerResult};
+use crate::handlers::code::CODE_BLOCK;
+use crate::handlers::{HandlerError, HandlerResult, safe};
+use crate::platform;
 use regex::Regex;
+use std::path::Path;
 use std::process::{Command, Stdio};
-use std::time::Instant;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, BufReader};
+
+/// How often a streamed command checks whether it was aborted
+const ABORT_POLL: Duration = Duration::from_millis(100);
 
 /// List of commands that are completely restricted for security
 const RESTRICTED_COMMANDS: [&str; 12] = [
@@ -37,7 +46,7 @@ const DANGEROUS_PATTERNS: [&str; 8] = [
 ];
 
 /// Checks if a command is safe to execute
-fn is_command_safe(command: &str) -> bool {
+pub(crate) fn is_command_safe(command: &str) -> bool {
     // Check for exact matches to restricted commands
     for restricted in RESTRICTED_COMMANDS.iter() {
         if command.contains(restricted) {
@@ -65,63 +74,152 @@ fn is_command_safe(command: &str) -> bool {
     true
 }
 
-/// Handle execution of a bash command
-pub fn handle_bash_command(command: &str) -> HandlerResult<String> {
-    // At the beginning of this function, we could add an abort check
-    // But since it's not running in an async context, we'll handle abort
-    // in the calling functions
-    let command = command.trim();
+/// The non-empty ```bash blocks of a response, in order
+pub fn extract_bash_blocks(text: &str) -> Vec<String> {
+    CODE_BLOCK
+        .captures_iter(text)
+        .filter(|cap| &cap[1] == "bash")
+        .map(|cap| cap[2].trim().to_string())
+        .filter(|block| !block.is_empty())
+        .collect()
+}
 
-    if command.is_empty() {
-        return Err(HandlerError::Bash("Empty command".to_string()));
-    }
+/// Handle execution of a bash command in `dir`
+pub fn handle_bash_command(command: &str, dir: &Path) -> HandlerResult<String> {
+    safe::check("Running shell commands")?;
+    execute(command, dir)
+}
 
-    // Security checks
-    if !is_command_safe(command) {
-        return Err(HandlerError::Bash(
-            "This command is restricted for security reasons.".to_string(),
-        ));
-    }
+/// Run a read-only command the app issues itself, such as `ollama list`,
+/// which is allowed in safe mode
+pub(crate) fn handle_internal_command(command: &str) -> HandlerResult<String> {
+    execute(command, &crate::utils::get_current_dir())
+}
 
-    // Execute and time the command
-    let start_time = Instant::now();
+/// How a streamed command ended
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Exit {
+    pub code: i32,
+    /// Seconds the command ran
+    pub elapsed: f64,
+    /// Whether it printed anything
+    pub printed: bool,
+}
 
-    // For commands that use shell patterns, use the shell to interpret them
-    if command.contains('*') || command.contains('?') || command.contains('[') {
-        let result = Command::new("sh")
-            .arg("-c")
-            .arg(command)
-            .current_dir(std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from(".")))
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .map_err(|e| HandlerError::Bash(format!("Failed to execute command: {}", e)))?;
-
-        let elapsed = start_time.elapsed();
-        let exit_code = result.status.code().unwrap_or(-1);
-        let stdout = String::from_utf8_lossy(&result.stdout).to_string();
-        let stderr = String::from_utf8_lossy(&result.stderr).to_string();
-
-        return Ok(format_command_output(
-            command,
-            exit_code,
-            &stdout,
-            &stderr,
-            elapsed.as_secs_f64(),
-        ));
+impl Exit {
+    /// The line shown after the output, in the format of [`format_command_output`]
+    pub fn summary(&self) -> String {
+        let mut summary = format_header(self.code, self.elapsed);
+        if !self.printed {
+            summary.push_str("(no output)\n");
+        }
+        summary
     }
+}
 
-    // For other commands, use direct execution
-    let cmd_parts: Vec<String> = shell_words::split(command)
-        .map_err(|e| HandlerError::Parse(format!("Failed to parse command: {}", e)))?;
+/// Run a command typed with `!` in `dir` without blocking, passing each
+/// line of its output to `on_line` as it is printed
+///
+/// Lines on stderr start with ⚠️; output that is not UTF-8 is shown with
+/// replacement characters. The command and everything it started are
+/// killed once `abort` is set, which returns an error like failing to start
+/// does.
+pub async fn run_streaming(
+    command: &str,
+    dir: &Path,
+    abort: Arc<AtomicBool>,
+    mut on_line: impl FnMut(String),
+) -> HandlerResult<Exit> {
+    safe::check("Running shell commands")?;
+    let start_time = Instant::now();
+    let mut command = prepare(command, dir)?;
+    platform::own_process_group(&mut command);
+    let mut child = tokio::process::Command::from(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| HandlerError::Bash(format!("Failed to execute command: {}", e)))?;
+    let mut stdout = child.stdout.take().map(BufReader::new);
+    let mut stderr = child.stderr.take().map(BufReader::new);
+    // Partly read lines survive the other branches of the select
+    let mut stdout_line = Vec::new();
+    let mut stderr_line = Vec::new();
 
-    if cmd_parts.is_empty() {
-        return Err(HandlerError::Parse("Invalid command format".to_string()));
+    let mut printed = false;
+    let mut poll = tokio::time::interval(ABORT_POLL);
+    let status = loop {
+        tokio::select! {
+            line = read_line(stdout.as_mut(), &mut stdout_line), if stdout.is_some() => {
+                match line {
+                    Some(line) => {
+                        on_line(line);
+                        printed = true;
+                    }
+                    None => stdout = None,
+                }
+            }
+            line = read_line(stderr.as_mut(), &mut stderr_line), if stderr.is_some() => {
+                match line {
+                    Some(line) => {
+                        on_line(format!("⚠️ {}", line));
+                        printed = true;
+                    }
+                    None => stderr = None,
+                }
+            }
+            status = child.wait(), if stdout.is_none() && stderr.is_none() => {
+                break status
+                    .map_err(|e| HandlerError::Bash(format!("Failed to wait for command: {}", e)))?;
+            }
+            _ = poll.tick() => {
+                if abort.load(Ordering::SeqCst) {
+                    if let Some(pid) = child.id() {
+                        platform::kill_tree(pid).ok();
+                    }
+                    child.kill().await.ok();
+                    return Err(HandlerError::Bash(format!(
+                        "Killed after {:.2}s",
+                        start_time.elapsed().as_secs_f64()
+                    )));
+                }
+            }
+        }
+    };
+    Ok(Exit {
+        code: status.code().unwrap_or(-1),
+        elapsed: start_time.elapsed().as_secs_f64(),
+        printed,
+    })
+}
+
+/// Read the next line into `buffer` and take it out without its line ending,
+/// decoded lossily; `None` at the end of the output or on a read error
+///
+/// `buffer` keeps a partly read line when the read is cancelled.
+async fn read_line<R: AsyncBufRead + Unpin>(
+    reader: Option<&mut R>,
+    buffer: &mut Vec<u8>,
+) -> Option<String> {
+    let read = reader?.read_until(b'\n', buffer).await.ok()?;
+    if read == 0 && buffer.is_empty() {
+        return None;
+    }
+    if buffer.ends_with(b"\n") {
+        buffer.pop();
+        if buffer.ends_with(b"\r") {
+            buffer.pop();
+        }
     }
+    let line = String::from_utf8_lossy(buffer).into_owned();
+    buffer.clear();
+    Some(line)
+}
 
-    let result = Command::new(&cmd_parts[0])
-        .args(&cmd_parts[1..])
-        .current_dir(std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from(".")))
+fn execute(command: &str, dir: &Path) -> HandlerResult<String> {
+    let start_time = Instant::now();
+    let result = prepare(command, dir)?
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .output()
@@ -141,8 +239,49 @@ pub fn handle_bash_command(command: &str) -> HandlerResult<String> {
     ))
 }
 
+/// Check a command and build the process running it in `dir`
+pub(crate) fn prepare(command: &str, dir: &Path) -> HandlerResult<Command> {
+    let command = command.trim();
+
+    if command.is_empty() {
+        return Err(HandlerError::Bash("Empty command".to_string()));
+    }
+
+    // Security checks
+    if !is_command_safe(command) {
+        let hint = if command.contains("curl") || command.contains("wget") {
+            " Use /fetch <url> to attach a web page instead."
+        } else {
+            ""
+        };
+        return Err(HandlerError::Bash(format!(
+            "This command is restricted for security reasons.{}",
+            hint
+        )));
+    }
+
+    // Let the shell interpret patterns (and, on Windows, PowerShell aliases)
+    if platform::needs_shell(command) {
+        let mut shell = platform::shell_command(command);
+        shell.current_dir(dir);
+        return Ok(shell);
+    }
+
+    // For other commands, use direct execution
+    let cmd_parts: Vec<String> = shell_words::split(command)
+        .map_err(|e| HandlerError::Parse(format!("Failed to parse command: {}", e)))?;
+
+    if cmd_parts.is_empty() {
+        return Err(HandlerError::Parse("Invalid command format".to_string()));
+    }
+
+    let mut direct = Command::new(&cmd_parts[0]);
+    direct.args(&cmd_parts[1..]).current_dir(dir);
+    Ok(direct)
+}
+
 /// Format command output with proper style and information
-fn format_command_output(
+pub(crate) fn format_command_output(
     _command: &str, // Not used in the new format but kept for backwards compatibility
     return_code: i32,
     stdout: &str,
@@ -150,12 +289,7 @@ fn format_command_output(
     execution_time: f64,
 ) -> String {
     // Compact header with metadata
-    let mut result = format!(
-        "[⏱️ {:.2}s | {} | 📊 {}]\n",
-        execution_time,
-        if return_code == 0 { "✓" } else { "✗" },
-        return_code
-    );
+    let mut result = format_header(return_code, execution_time);
 
     // Format output with cleaner headers
     if !stdout.is_empty() {
@@ -179,3 +313,13 @@ fn format_command_output(
 
     result
 }
+
+/// The line with the run time and exit code of a command
+fn format_header(return_code: i32, execution_time: f64) -> String {
+    format!(
+        "[⏱️ {:.2}s | {} | 📊 {}]\n",
+        execution_time,
+        if return_code == 0 { "✓" } else { "✗" },
+        return_code
+    )
+}
@@ -0,0 +1,114 @@
+//! Code block handler
+//!
+//! This module executes fenced code blocks in languages other than bash
+//! (python, node, sql, ...) through the interpreters configured in
+//! `code_execution`. Unlike bash commands they are not screened: a
+//! blocklist of shell commands says nothing about interpreter code, so
+//! such blocks only run once the user has confirmed them.
+
+use crate::config::{CodeExecutionConfig, InterpreterConfig, get_config};
+use crate::handlers::bash::format_command_output;
+use crate::handlers::{HandlerError, HandlerResult, safe};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::Instant;
+
+/// Fenced code blocks with their language tag, as responses are scanned
+/// for blocks to run or to format
+pub static CODE_BLOCK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"```(\w+)\n([\s\S]*?)\n```").unwrap());
+
+/// A fenced code block to run with an interpreter
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodeBlock {
+    /// Language tag of the fence, e.g. `python`
+    pub language: String,
+    /// Code of the block, trimmed
+    pub code: String,
+}
+
+/// Blocks of `text` in languages whose interpreter is enabled
+pub fn extract_code_blocks(text: &str) -> Vec<CodeBlock> {
+    extract_code_blocks_with(&get_config().code_execution, text)
+}
+
+/// Blocks of `text` whose interpreter is enabled in `config`
+pub fn extract_code_blocks_with(config: &CodeExecutionConfig, text: &str) -> Vec<CodeBlock> {
+    CODE_BLOCK
+        .captures_iter(text)
+        .filter(|cap| &cap[1] != "bash" && config.enabled_interpreter(&cap[1]).is_some())
+        .map(|cap| CodeBlock {
+            language: cap[1].to_string(),
+            code: cap[2].trim().to_string(),
+        })
+        .filter(|block| !block.code.is_empty())
+        .collect()
+}
+
+/// Execute a code block in `dir` with the interpreter configured for its language
+pub fn handle_code_block(language: &str, code: &str, dir: &Path) -> HandlerResult<String> {
+    safe::check("Running code blocks")?;
+    let config = get_config();
+    let interpreter = config
+        .code_execution
+        .enabled_interpreter(language)
+        .ok_or_else(|| {
+            HandlerError::Other(format!(
+                "Execution of {} blocks is disabled. Enable it with /config exec {} on",
+                language, language
+            ))
+        })?;
+    run_with(interpreter, code, dir)
+}
+
+/// Execute a code block in `dir` with the given interpreter
+pub fn run_with(interpreter: &InterpreterConfig, code: &str, dir: &Path) -> HandlerResult<String> {
+    let code = code.trim();
+    if code.is_empty() {
+        return Err(HandlerError::Bash("Empty code block".to_string()));
+    }
+
+    let start_time = Instant::now();
+
+    let mut child = Command::new(&interpreter.command)
+        .args(&interpreter.args)
+        .current_dir(dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            HandlerError::Bash(format!(
+                "Failed to start interpreter '{}': {}",
+                interpreter.command, e
+            ))
+        })?;
+
+    // Feed the block on stdin; dropping the handle closes it so the interpreter runs
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(code.as_bytes())
+            .and_then(|_| stdin.write_all(b"\n"))
+            .map_err(|e| HandlerError::Bash(format!("Failed to send code block: {}", e)))?;
+    }
+
+    let result = child
+        .wait_with_output()
+        .map_err(|e| HandlerError::Bash(format!("Failed to execute code block: {}", e)))?;
+
+    let elapsed = start_time.elapsed();
+    let exit_code = result.status.code().unwrap_or(-1);
+    let stdout = String::from_utf8_lossy(&result.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&result.stderr).to_string();
+
+    Ok(format_command_output(
+        code,
+        exit_code,
+        &stdout,
+        &stderr,
+        elapsed.as_secs_f64(),
+    ))
+}
@@ -6,13 +6,198 @@
 use crate::config::{AppConfig, get_config, update_field};
 use crate::handlers::{HandlerError, HandlerResult};
 use chrono::Local;
+use std::collections::BTreeMap;
 use std::env;
 use std::process;
 
+/// Every slash command, including the ones handled by the app itself
+pub const COMMANDS: &[&str] = &[
+    "alias",
+    "as",
+    "attach",
+    "cd",
+    "clear",
+    "commitmsg",
+    "config",
+    "context",
+    "continue",
+    "cost",
+    "debug",
+    "decrypt",
+    "definition",
+    "diff-sessions",
+    "doc",
+    "echo",
+    "encrypt",
+    "eval",
+    "exit",
+    "export",
+    "fetch",
+    "filter",
+    "help",
+    "hover",
+    "image",
+    "import",
+    "index",
+    "linenumbers",
+    "list",
+    "login",
+    "logout",
+    "lowbandwidth",
+    "open",
+    "print",
+    "prompts",
+    "pty",
+    "quit",
+    "refactor",
+    "references",
+    "rewrite",
+    "search",
+    "session",
+    "sessions",
+    "share",
+    "speak",
+    "stats",
+    "sync",
+    "system",
+    "tag",
+    "template",
+    "theme",
+    "tree",
+    "undo",
+    "update",
+    "usage",
+    "version",
+];
+
+/// Number of single-character edits that turn `a` into `b`, counting
+/// swapped neighbours (`hlep`) as one edit
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
 /// Command handler for application commands
 pub struct CommandHandler;
 
 impl CommandHandler {
+    /// Known commands closest to an unknown one, best match first
+    pub fn suggest(name: &str) -> Vec<&'static str> {
+        let name = name.to_lowercase();
+        // Allow one typo in short names and two in longer ones
+        let max_distance = if name.chars().count() <= 4 { 1 } else { 2 };
+        let mut matches: Vec<(usize, &'static str)> = COMMANDS
+            .iter()
+            .map(|command| (edit_distance(&name, command), *command))
+            .filter(|(distance, _)| *distance <= max_distance)
+            .collect();
+        matches.sort();
+        matches.into_iter().map(|(_, command)| command).collect()
+    }
+
+    /// Expand `/name args` when `name` is an alias, appending the arguments
+    pub fn expand_alias(command: &str, aliases: &BTreeMap<String, String>) -> Option<String> {
+        let command = command.trim().strip_prefix('/')?;
+        let (name, args) = command
+            .split_once(char::is_whitespace)
+            .unwrap_or((command, ""));
+        let expansion = aliases.get(name)?;
+        if args.trim().is_empty() {
+            Some(expansion.clone())
+        } else {
+            Some(format!("{} {}", expansion, args.trim()))
+        }
+    }
+
+    /// Handle `/alias add <name> "<command>"`, `/alias list` and `/alias rm <name>`
+    fn handle_alias(args: &str) -> HandlerResult<String> {
+        const USAGE: &str =
+            "Usage: /alias add <name> \"<command>\", /alias list or /alias rm <name>";
+        let words = shell_words::split(args)
+            .map_err(|e| HandlerError::Parse(format!("Invalid alias: {}", e)))?;
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+
+        match words.as_slice() {
+            [] | ["list"] => {
+                let aliases = get_config().aliases;
+                if aliases.is_empty() {
+                    return Ok(format!("📋 No aliases defined. {}", USAGE));
+                }
+                let mut listing = String::from("📋 Aliases:\n");
+                for (name, command) in &aliases {
+                    listing.push_str(&format!("  /{} → {}\n", name, command));
+                }
+                Ok(listing.trim_end().to_string())
+            }
+            ["add", name, command @ ..] if !command.is_empty() => {
+                let name = name.trim_start_matches('/').to_lowercase();
+                if name.is_empty() || name.contains(char::is_whitespace) {
+                    return Err(HandlerError::Parse(USAGE.to_string()));
+                }
+                if COMMANDS.contains(&name.as_str()) {
+                    return Err(HandlerError::Parse(format!(
+                        "/{} is a built-in command and cannot be an alias",
+                        name
+                    )));
+                }
+                let command = command.join(" ");
+                update_field(|c: &mut AppConfig| {
+                    c.aliases.insert(name.clone(), command.clone());
+                })
+                .map_err(|e| HandlerError::Other(format!("Failed to save alias: {}", e)))?;
+                Ok(format!("✅ /{} → {}", name, command))
+            }
+            ["rm", name] => {
+                let name = name.trim_start_matches('/').to_lowercase();
+                if !get_config().aliases.contains_key(&name) {
+                    return Err(HandlerError::Parse(format!("No alias named /{}", name)));
+                }
+                update_field(|c: &mut AppConfig| {
+                    c.aliases.remove(&name);
+                })
+                .map_err(|e| HandlerError::Other(format!("Failed to save aliases: {}", e)))?;
+                Ok(format!("✅ Removed alias /{}", name))
+            }
+            _ => Err(HandlerError::Parse(USAGE.to_string())),
+        }
+    }
+
+    /// The command with its unknown name replaced by the closest known one
+    pub fn correct(command: &str) -> Option<String> {
+        let command = command.trim();
+        let (name, args) = command
+            .split_once(char::is_whitespace)
+            .unwrap_or((command, ""));
+        if COMMANDS.contains(&name.to_lowercase().as_str()) {
+            return None;
+        }
+        let best = *Self::suggest(name).first()?;
+        if args.is_empty() {
+            Some(best.to_string())
+        } else {
+            Some(format!("{} {}", best, args.trim()))
+        }
+    }
+
     /// Handle list commands to show available resources
     fn handle_list_command(args: &[&str]) -> HandlerResult<String> {
         if args.is_empty() {
@@ -32,6 +217,12 @@ impl CommandHandler {
                     * OpenAI{} - GPT models via API
                     * Anthropic{} - Claude models via API
                     * LMStudio{} - Local models via LM Studio
+                    * OpenRouter{} - Models of many vendors through one API
+                    * Groq{} - Fast open models via API
+                    * Mistral{} - Mistral and Codestral models via API
+                    * llama.cpp{} - GGUF models run in-process (built with --features llama)
+                    * Azure OpenAI{} - OpenAI models deployed to an Azure resource
+                    * Custom{} - Any OpenAI-compatible endpoint (vLLM, llama.cpp server, gateways)
 
                     Use /config provider <name> to change the active provider.",
                     if active_provider == crate::ai::Provider::Ollama {
@@ -53,6 +244,36 @@ impl CommandHandler {
                         " (active)"
                     } else {
                         ""
+                    },
+                    if active_provider == crate::ai::Provider::OpenRouter {
+                        " (active)"
+                    } else {
+                        ""
+                    },
+                    if active_provider == crate::ai::Provider::Groq {
+                        " (active)"
+                    } else {
+                        ""
+                    },
+                    if active_provider == crate::ai::Provider::Mistral {
+                        " (active)"
+                    } else {
+                        ""
+                    },
+                    if active_provider == crate::ai::Provider::LlamaCpp {
+                        " (active)"
+                    } else {
+                        ""
+                    },
+                    if active_provider == crate::ai::Provider::AzureOpenAI {
+                        " (active)"
+                    } else {
+                        ""
+                    },
+                    if active_provider == crate::ai::Provider::Custom {
+                        " (active)"
+                    } else {
+                        ""
                     }
                 );
 
@@ -77,7 +298,7 @@ impl CommandHandler {
                         // Use a safer approach to get models from the bash command
                         // This won't crash if the command fails
                         let models_output =
-                            match crate::handlers::bash::handle_bash_command("ollama list") {
+                            match crate::handlers::bash::handle_internal_command("ollama list") {
                                 Ok(output) => output,
                                 Err(_) => "Error: Could not run 'ollama list'".to_string(),
                             };
@@ -157,6 +378,83 @@ impl CommandHandler {
                             result.push_str(&format!("* {}{}\n", model.name, active));
                         }
                     }
+                    crate::ai::Provider::OpenRouter => {
+                        // The catalog is fetched by the app; this lists the configured models
+                        for (i, model) in config.ai.openrouter.models.iter().enumerate() {
+                            let active = if i == config.ai.openrouter.current_model_index {
+                                " (active)"
+                            } else {
+                                ""
+                            };
+                            result.push_str(&format!("* {}{}\n", model.name, active));
+                        }
+                    }
+                    crate::ai::Provider::Groq => {
+                        for (i, model) in config.ai.groq.models.iter().enumerate() {
+                            let active = if i == config.ai.groq.current_model_index {
+                                " (active)"
+                            } else {
+                                ""
+                            };
+                            result.push_str(&format!("* {}{}\n", model.name, active));
+                        }
+                    }
+                    crate::ai::Provider::Mistral => {
+                        for (i, model) in config.ai.mistral.models.iter().enumerate() {
+                            let active = if i == config.ai.mistral.current_model_index {
+                                " (active)"
+                            } else {
+                                ""
+                            };
+                            result.push_str(&format!("* {}{}\n", model.name, active));
+                        }
+                    }
+                    crate::ai::Provider::LlamaCpp => {
+                        for (i, model) in config.ai.llamacpp.models.iter().enumerate() {
+                            let active = if i == config.ai.llamacpp.current_model_index {
+                                " (active)"
+                            } else {
+                                ""
+                            };
+                            result.push_str(&format!("* {}{}\n", model.name, active));
+                        }
+                        // GGUF files that can be selected by name
+                        let dir = crate::ai::llamacpp::model_dir(&config.ai.llamacpp);
+                        let found: Vec<String> = crate::ai::llamacpp::find_models(&dir)
+                            .into_iter()
+                            .filter(|file| {
+                                !config.ai.llamacpp.models.iter().any(|m| &m.name == file)
+                            })
+                            .collect();
+                        if !found.is_empty() {
+                            result.push_str(&format!("\n🤖 Other models in {}:\n", dir.display()));
+                            for file in found {
+                                result.push_str(&format!("* {}\n", file));
+                            }
+                        }
+                    }
+                    crate::ai::Provider::AzureOpenAI => {
+                        result.push_str("🤖 Deployments:\n");
+                        for (i, model) in config.ai.azure.models.iter().enumerate() {
+                            let active = if i == config.ai.azure.current_model_index {
+                                " (active)"
+                            } else {
+                                ""
+                            };
+                            result.push_str(&format!("* {}{}\n", model.name, active));
+                        }
+                    }
+                    crate::ai::Provider::Custom => {
+                        result.push_str(&format!("🤖 Models at {}:\n", config.ai.custom.endpoint));
+                        for (i, model) in config.ai.custom.models.iter().enumerate() {
+                            let active = if i == config.ai.custom.current_model_index {
+                                " (active)"
+                            } else {
+                                ""
+                            };
+                            result.push_str(&format!("* {}{}\n", model.name, active));
+                        }
+                    }
                 }
 
                 result.push_str("\nUse /config model <name> to change the active model.");
@@ -186,6 +484,7 @@ impl CommandHandler {
 
         // Command mapping
         match cmd.as_str() {
+            "alias" => Self::handle_alias(command.trim_start()[parts[0].len()..].trim()),
             "help" => Ok(Self::show_help(args)),
             "clear" => Ok("/clear".to_string()), // Special return value handled by app
             "exit" | "quit" => {
@@ -197,10 +496,26 @@ impl CommandHandler {
             "system" => Ok(Self::show_system_info()),
             "theme" => Self::handle_theme(args),
             "list" => Self::handle_list_command(args),
-            _ => Err(HandlerError::Parse(format!(
-                "Unknown command '{}'. Type '/help' for commands.",
-                cmd
-            ))),
+            _ => {
+                let suggestions = Self::suggest(&cmd);
+                if suggestions.is_empty() {
+                    Err(HandlerError::Parse(format!(
+                        "Unknown command '{}'. Type '/help' for commands.",
+                        cmd
+                    )))
+                } else {
+                    let names: Vec<String> = suggestions
+                        .iter()
+                        .take(3)
+                        .map(|name| format!("/{}", name))
+                        .collect();
+                    Err(HandlerError::Parse(format!(
+                        "Unknown command /{} — did you mean {}?",
+                        cmd,
+                        names.join(" or ")
+                    )))
+                }
+            }
         }
     }
 
@@ -234,11 +549,16 @@ impl CommandHandler {
                     Configure settings using /config [key] [value]
                     Example keys:
                     - model - Set AI model (e.g. qwen2.5-coder, gpt-4o)
-                    - provider - Set AI provider (ollama, openai, anthropic, lmstudio)
+                    - provider - Set AI provider (ollama, openai, anthropic, lmstudio, openrouter, groq, mistral, llamacpp, azure, custom)
                     - temperature - Set temperature (0.0-1.0)
                     - endpoint - Set API endpoint URL
                     - api_key - Set API key (for OpenAI/Anthropic)
-                    - system_prompt - Set system prompt",
+                    - azure - Set the Azure OpenAI resource, endpoint, API version and deployments
+                    - system_prompt - Set system prompt
+                    - exec - Offer to run python/node/sql blocks from responses (e.g. exec python on)
+                    Bundles:
+                    - /config export <path> [--keys] - Write the configuration and prompt library to a file
+                    - /config import <path> - Use a bundle, keeping the local API keys",
                 ),
                 (
                     "list",
@@ -262,7 +582,10 @@ impl CommandHandler {
                     - accent - Accent color for highlights
                     - background - Background color
                     - foreground - Text color
-                    Values can be hex colors like #FF0000 or named colors",
+                    - search_match - Background of /search matches
+                    Values can be hex colors like #FF0000 or named colors
+                    - spinner - braille, dots, line or none
+                    - progress_bar - on or off: a bar with the remaining time once it is known",
                 ),
                 (
                     "system",
@@ -297,27 +620,91 @@ impl CommandHandler {
 
         Available commands:
           /help [topic]   - Show help (optional: ai, bash, config, theme, system, list)
+          /cd [path|-]    - Change the directory commands run in (also !cd path)
           /clear          - Clear terminal output
           /config         - View or set configuration
-          /theme          - Customize UI colors
+          /theme          - Customize UI colors, the spinner and the progress bar
           /system         - Display system information
+          /stats          - Session counts, usage and p50/p95 latency per provider and model
+          /tag [name|off] - Record the following usage under a tag, e.g. a client or ticket
+          /usage [by-tag [today|week|month]] - Usage across sessions, or spend per tag
+          /template [name [text]|show <name>|update|source <git-url>] - Prompt templates and team repository
+          /eval <template|cases.yaml> [text] --models m1,m2 [--expect k1,k2] [--judge model] - Compare models on the same prompts
           /version        - Show version information
+          /update [install] - Check for a newer release (install replaces this binary)
           /list           - List available providers, models, etc.
+          /login <provider> - Log in with a device code instead of an API key (/logout <provider>)
+          /refactor \"<instruction>\" <glob> - Edit matching files together
+          /doc <path|symbol> - Generate doc comments and review them as a diff
+          /rewrite shorter|bullets|translate <lang> [--append] - Rewrite the selected or last response
+          /commitmsg      - Generate a commit message for staged changes (Ctrl+G)
+          /undo [n]       - Revert the last n file changes (/undo list to show them)
+          /export html [file] - Save the session as a standalone HTML page
+          /print [n] [file] - Plain text of the last n exchanges, to a file or stdout on exit
+          /prompts [words] - Search past prompts (/prompts run <n>, /prompts edit <n>)
+          /pty <command>  - Run a command that reads input (python, ssh) in a pane; Ctrl+D closes it
+          /session [save [name]|load <name>|list] - Keep this session under a name and resume it later
+          /sessions [words] - Browse stored conversations and open one with Enter
+          /diff-sessions <a> <b> - Compare the prompts and responses of two stored sessions
+          /share [stop]   - Let teammates watch this session read-only in a browser
+          /import aider|llm|sgpt - Turn the history and settings of another AI CLI into sessions and config
+          /sync [status|push|pull] - Sync encrypted sessions with another machine
+          /encrypt        - Encrypt the stored sessions and prompts (needs encryption.enabled)
+          /decrypt <file> [out] - Write the plain text of an encrypted export
+          /linenumbers [on|off] - Show line numbers in the output area
+          /lowbandwidth [on|off|auto] - Fewer redraws and no animations for slow links
+          /speak [on|off|stop] - Read responses aloud, skipping code blocks (Ctrl+X stops)
+          /debug [reset]  - Show render statistics and the frame-time histogram
+          /attach <file|!cmd> - Send a file or command output with every prompt (/attach list, /attach clear)
+          /fetch <url>    - Attach the readable text of a web page
+          /index [on|off|rebuild|search <query>] - Embed the workspace in the background and search it
+          /definition <symbol> - Attach where a symbol is defined, from the language server
+          /references <symbol> - Attach the places a symbol is used
+          /hover <symbol> - Attach the type and documentation of a symbol
+          /tree [dir]     - Browse the files of a directory and open one in the editor
+          /open [n|file:line] - List the file:line locations of the last output or open one in the editor
+          /image <prompt> - Generate an image, save it and show it on kitty/sixel terminals
+          /as [persona|off] - Answer chat prompts as a configured persona (@name: for one prompt)
+          /context [clear|drop <n>] - Show what the next chat prompt carries by tokens, forget or leave out parts
+          /continue       - Continue the last cut-off answer, joining the parts into one message
+          /alias add <name> \"<command>\" - Define /name as a shortcut (/alias list, /alias rm <name>)
+          /filter ai|bash|cmd|all - Show only some kinds of output (e.g. /filter ai)
+          /search [term]  - Highlight a term in the output and jump to it (Ctrl+F; n/N move, Esc ends)
+          :<n>            - Jump to output line n ('' jumps back)
           /exit or /quit  - Exit application
 
         AI configuration:
-          /config provider <name>  - Set AI provider (ollama, openai, anthropic, lmstudio)
+          /config provider <name>  - Set AI provider (ollama, openai, anthropic, lmstudio, openrouter, groq, mistral, llamacpp, azure, custom)
           /config model <name>     - Set AI model for current provider
           /config endpoint <url>   - Set API endpoint URL
           /config api_key <key>    - Set API key (for OpenAI/Anthropic)
+          /config azure [key] [v]  - Azure OpenAI resource, endpoint, api_version, deployment, remove
+          /config custom [key] [v] - Custom endpoint, model, remove, key_header, header, discover
+          /config pricing [model prompt completion] - Show or set model prices per 1K tokens
+          /config exec <lang> on   - Offer to run python/node/sql blocks
+          /config seed <n>|off     - Send a fixed seed with requests
+          /config deterministic on - Temperature 0 and a fixed seed for reproducible answers
           /list providers          - Show available providers
           /list models             - Show available models for current provider
 
         Keyboard shortcuts:
           - Up/Down arrow: Navigate command history
           - Shift+Up/Down: Select text in output area
+          - Ctrl+Up/Down: Grow or shrink the input box (remembered)
           - Ctrl+C: Copy selected text (when in selection mode) or exit
           - PageUp/Down: Scroll output
+          - Ctrl+S: Preview and save file blocks from the last response
+          - Ctrl+G: Generate a commit message for staged changes
+          - Ctrl+R: Start or stop voice input; the transcript is inserted into the input
+          - Ctrl+O: Show or fold the reasoning of responses
+          - Ctrl+X: Stop reading a response aloud
+          - Ctrl+Q: Quote the selected text or message into the input
+          - Ctrl+E: Ask the AI about the selected text or message
+          - Ctrl+K: Context menu (Copy, Send to AI, Select All, Paste)
+          - Alt+Up/Down: Select an output section; Enter/Space expands or collapses it
+          - With a section selected: j/k move, y copy, r re-ask, q quote, d drop from context, p pin,
+            s shorter, b bullet points, t translate
+          - { / }: Jump to the previous/next prompt (when the input is empty)
           - Esc: Cancel text selection or clear input"
             .to_string()
     }
@@ -387,6 +774,20 @@ impl CommandHandler {
                 _ => "not set".to_string(),
             };
 
+            // Bash blocks always run; list the interpreters that are switched on
+            let enabled_languages: Vec<&str> = config
+                .code_execution
+                .interpreters
+                .iter()
+                .filter(|(_, interpreter)| interpreter.enabled)
+                .map(|(language, _)| language.as_str())
+                .collect();
+            let exec_display = if enabled_languages.is_empty() {
+                "bash".to_string()
+            } else {
+                format!("bash, {}", enabled_languages.join(", "))
+            };
+
             return Ok(format!(
                 "📝 Current Configuration:
                 AI Provider: {}
@@ -394,29 +795,50 @@ impl CommandHandler {
                 API Key: {}
                 Model: {}
                 Temperature: {}
+                Seed: {}
                 Max Tokens: {}
                 System Prompt: {}
                 History Size: {}
                 Mouse Enabled: {}
                 Logging Enabled: {}
+                Code Execution: {}
 
                 Use /config [key] [value] to change settings.",
                 config.ai.active_provider,
                 config.ai.get_active_endpoint(),
                 api_key_display,
                 active_model.name,
-                active_model.temperature,
+                if config.ai.deterministic {
+                    "0 (deterministic)".to_string()
+                } else {
+                    active_model.temperature.to_string()
+                },
+                config
+                    .ai
+                    .request_seed()
+                    .map_or_else(|| "not set".to_string(), |seed| seed.to_string()),
                 active_model.max_tokens,
                 active_model.system_prompt.as_deref().unwrap_or("not set"),
                 config.history_size,
                 config.mouse_enabled,
-                config.logging_enabled
+                config.logging_enabled,
+                exec_display
             ));
         }
 
         let key = args[0].to_lowercase();
         let value = if args.len() > 1 { args[1] } else { "" };
 
+        if key == "azure" {
+            return Self::handle_azure_config(&args[1..]);
+        }
+        if key == "custom" {
+            return Self::handle_custom_config(&args[1..]);
+        }
+        if key == "pricing" {
+            return Self::handle_pricing_config(&args[1..]);
+        }
+
         if value.is_empty() && key != "reset" {
             return Err(HandlerError::Parse(format!(
                 "Value required for key: {}",
@@ -529,6 +951,106 @@ impl CommandHandler {
                                 c.ai.lmstudio.current_model_index = c.ai.lmstudio.models.len() - 1;
                             }
                         }
+                        crate::ai::Provider::OpenRouter => {
+                            // Check if model exists in the list (case insensitive)
+                            let mut found = false;
+                            for (i, model) in c.ai.openrouter.models.iter().enumerate() {
+                                if model.name.to_lowercase() == value.to_lowercase() {
+                                    c.ai.openrouter.current_model_index = i;
+                                    found = true;
+                                    break;
+                                }
+                            }
+
+                            // If not found, add it (any model of the catalog works)
+                            if !found {
+                                c.ai.openrouter.models.push(crate::config::ModelConfig {
+                                    name: value.to_string(),
+                                    temperature: 0.1,
+                                    system_prompt: Some(
+                                        "You are a helpful AI coding assistant.".to_string(),
+                                    ),
+                                    ..Default::default()
+                                });
+                                c.ai.openrouter.current_model_index =
+                                    c.ai.openrouter.models.len() - 1;
+                            }
+                        }
+                        crate::ai::Provider::Groq => {
+                            // Check if model exists in the list (case insensitive)
+                            let mut found = false;
+                            for (i, model) in c.ai.groq.models.iter().enumerate() {
+                                if model.name.to_lowercase() == value.to_lowercase() {
+                                    c.ai.groq.current_model_index = i;
+                                    found = true;
+                                    break;
+                                }
+                            }
+
+                            // If not found, add it
+                            if !found {
+                                c.ai.groq.models.push(crate::config::ModelConfig {
+                                    name: value.to_string(),
+                                    temperature: 0.1,
+                                    system_prompt: Some(
+                                        "You are a helpful AI coding assistant.".to_string(),
+                                    ),
+                                    ..Default::default()
+                                });
+                                c.ai.groq.current_model_index = c.ai.groq.models.len() - 1;
+                            }
+                        }
+                        crate::ai::Provider::Mistral => {
+                            // Check if model exists in the list (case insensitive)
+                            let mut found = false;
+                            for (i, model) in c.ai.mistral.models.iter().enumerate() {
+                                if model.name.to_lowercase() == value.to_lowercase() {
+                                    c.ai.mistral.current_model_index = i;
+                                    found = true;
+                                    break;
+                                }
+                            }
+
+                            // If not found, add it
+                            if !found {
+                                c.ai.mistral.models.push(crate::config::ModelConfig {
+                                    name: value.to_string(),
+                                    temperature: 0.1,
+                                    system_prompt: Some(
+                                        "You are a helpful AI coding assistant.".to_string(),
+                                    ),
+                                    ..Default::default()
+                                });
+                                c.ai.mistral.current_model_index = c.ai.mistral.models.len() - 1;
+                            }
+                        }
+                        crate::ai::Provider::LlamaCpp => {
+                            // Check if model exists in the list (case insensitive)
+                            let mut found = false;
+                            for (i, model) in c.ai.llamacpp.models.iter().enumerate() {
+                                if model.name.to_lowercase() == value.to_lowercase() {
+                                    c.ai.llamacpp.current_model_index = i;
+                                    found = true;
+                                    break;
+                                }
+                            }
+
+                            // If not found, add it
+                            if !found {
+                                c.ai.llamacpp.models.push(crate::config::ModelConfig {
+                                    name: value.to_string(),
+                                    temperature: 0.1,
+                                    system_prompt: Some(
+                                        "You are a helpful AI coding assistant.".to_string(),
+                                    ),
+                                    ..Default::default()
+                                });
+                                c.ai.llamacpp.current_model_index = c.ai.llamacpp.models.len() - 1;
+                            }
+                        }
+                        // Models are the deployments of the resource
+                        crate::ai::Provider::AzureOpenAI => c.ai.select_model(value),
+                        crate::ai::Provider::Custom => c.ai.select_model(value),
                     }
                 })
                 .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
@@ -548,9 +1070,17 @@ impl CommandHandler {
                     "openai" => crate::ai::Provider::OpenAI,
                     "anthropic" => crate::ai::Provider::Anthropic,
                     "lmstudio" => crate::ai::Provider::LMStudio,
+                    "openrouter" => crate::ai::Provider::OpenRouter,
+                    "groq" => crate::ai::Provider::Groq,
+                    "mistral" => crate::ai::Provider::Mistral,
+                    "llamacpp" | "llama.cpp" => crate::ai::Provider::LlamaCpp,
+                    "azure" | "azureopenai" | "azure_openai" => crate::ai::Provider::AzureOpenAI,
+                    "custom" | "openai-compatible" | "openai_compatible" => {
+                        crate::ai::Provider::Custom
+                    }
                     _ => {
                         return Err(HandlerError::Parse(format!(
-                            "⚠️ Unknown provider: {}. Available: ollama, openai, anthropic, lmstudio",
+                            "⚠️ Unknown provider: {}. Available: ollama, openai, anthropic, lmstudio, openrouter, groq, mistral, llamacpp, azure, custom",
                             value
                         )));
                     }
@@ -598,6 +1128,42 @@ impl CommandHandler {
                                     c.ai.lmstudio.models[idx].temperature = temp;
                                 }
                             }
+                            crate::ai::Provider::OpenRouter => {
+                                let idx = c.ai.openrouter.current_model_index;
+                                if idx < c.ai.openrouter.models.len() {
+                                    c.ai.openrouter.models[idx].temperature = temp;
+                                }
+                            }
+                            crate::ai::Provider::Groq => {
+                                let idx = c.ai.groq.current_model_index;
+                                if idx < c.ai.groq.models.len() {
+                                    c.ai.groq.models[idx].temperature = temp;
+                                }
+                            }
+                            crate::ai::Provider::Mistral => {
+                                let idx = c.ai.mistral.current_model_index;
+                                if idx < c.ai.mistral.models.len() {
+                                    c.ai.mistral.models[idx].temperature = temp;
+                                }
+                            }
+                            crate::ai::Provider::LlamaCpp => {
+                                let idx = c.ai.llamacpp.current_model_index;
+                                if idx < c.ai.llamacpp.models.len() {
+                                    c.ai.llamacpp.models[idx].temperature = temp;
+                                }
+                            }
+                            crate::ai::Provider::AzureOpenAI => {
+                                let idx = c.ai.azure.current_model_index;
+                                if idx < c.ai.azure.models.len() {
+                                    c.ai.azure.models[idx].temperature = temp;
+                                }
+                            }
+                            crate::ai::Provider::Custom => {
+                                let idx = c.ai.custom.current_model_index;
+                                if idx < c.ai.custom.models.len() {
+                                    c.ai.custom.models[idx].temperature = temp;
+                                }
+                            }
                         })
                         .map_err(|e| {
                             HandlerError::Other(format!("Failed to update config: {}", e))
@@ -639,6 +1205,42 @@ impl CommandHandler {
                                     c.ai.lmstudio.models[idx].max_tokens = tokens;
                                 }
                             }
+                            crate::ai::Provider::OpenRouter => {
+                                let idx = c.ai.openrouter.current_model_index;
+                                if idx < c.ai.openrouter.models.len() {
+                                    c.ai.openrouter.models[idx].max_tokens = tokens;
+                                }
+                            }
+                            crate::ai::Provider::Groq => {
+                                let idx = c.ai.groq.current_model_index;
+                                if idx < c.ai.groq.models.len() {
+                                    c.ai.groq.models[idx].max_tokens = tokens;
+                                }
+                            }
+                            crate::ai::Provider::Mistral => {
+                                let idx = c.ai.mistral.current_model_index;
+                                if idx < c.ai.mistral.models.len() {
+                                    c.ai.mistral.models[idx].max_tokens = tokens;
+                                }
+                            }
+                            crate::ai::Provider::LlamaCpp => {
+                                let idx = c.ai.llamacpp.current_model_index;
+                                if idx < c.ai.llamacpp.models.len() {
+                                    c.ai.llamacpp.models[idx].max_tokens = tokens;
+                                }
+                            }
+                            crate::ai::Provider::AzureOpenAI => {
+                                let idx = c.ai.azure.current_model_index;
+                                if idx < c.ai.azure.models.len() {
+                                    c.ai.azure.models[idx].max_tokens = tokens;
+                                }
+                            }
+                            crate::ai::Provider::Custom => {
+                                let idx = c.ai.custom.current_model_index;
+                                if idx < c.ai.custom.models.len() {
+                                    c.ai.custom.models[idx].max_tokens = tokens;
+                                }
+                            }
                         })
                         .map_err(|e| {
                             HandlerError::Other(format!("Failed to update config: {}", e))
@@ -673,6 +1275,26 @@ impl CommandHandler {
                     crate::ai::Provider::LMStudio => {
                         c.ai.lmstudio.endpoint = value.to_string();
                     }
+                    crate::ai::Provider::OpenRouter => {
+                        c.ai.openrouter.endpoint = value.to_string();
+                    }
+                    crate::ai::Provider::Groq => {
+                        c.ai.groq.endpoint = value.to_string();
+                    }
+                    crate::ai::Provider::Mistral => {
+                        c.ai.mistral.endpoint = value.to_string();
+                    }
+                    // llama.cpp has no server; this sets where the models are
+                    crate::ai::Provider::LlamaCpp => {
+                        c.ai.llamacpp.model_dir = value.to_string();
+                    }
+                    // Used instead of the endpoint of the resource
+                    crate::ai::Provider::AzureOpenAI => {
+                        c.ai.azure.endpoint = value.to_string();
+                    }
+                    crate::ai::Provider::Custom => {
+                        c.ai.custom.endpoint = value.to_string();
+                    }
                 })
                 .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
 
@@ -687,7 +1309,9 @@ impl CommandHandler {
             "api_key" => {
                 // Validate that provider requires API key
                 match config.ai.active_provider {
-                    crate::ai::Provider::Ollama | crate::ai::Provider::LMStudio => {
+                    crate::ai::Provider::Ollama
+                    | crate::ai::Provider::LMStudio
+                    | crate::ai::Provider::LlamaCpp => {
                         return Err(HandlerError::Parse(format!(
                             "⚠️ {} does not require an API key",
                             config.ai.active_provider
@@ -705,6 +1329,21 @@ impl CommandHandler {
                         crate::ai::Provider::Anthropic => {
                             c.ai.anthropic.api_key = value.to_string();
                         }
+                        crate::ai::Provider::OpenRouter => {
+                            c.ai.openrouter.api_key = value.to_string();
+                        }
+                        crate::ai::Provider::Groq => {
+                            c.ai.groq.api_key = value.to_string();
+                        }
+                        crate::ai::Provider::Mistral => {
+                            c.ai.mistral.api_key = value.to_string();
+                        }
+                        crate::ai::Provider::AzureOpenAI => {
+                            c.ai.azure.api_key = value.to_string();
+                        }
+                        crate::ai::Provider::Custom => {
+                            c.ai.custom.api_key = value.to_string();
+                        }
                         _ => {} // Already handled above
                     }
                 })
@@ -752,6 +1391,42 @@ impl CommandHandler {
                                 c.ai.lmstudio.models[idx].system_prompt = prompt;
                             }
                         }
+                        crate::ai::Provider::OpenRouter => {
+                            let idx = c.ai.openrouter.current_model_index;
+                            if idx < c.ai.openrouter.models.len() {
+                                c.ai.openrouter.models[idx].system_prompt = prompt;
+                            }
+                        }
+                        crate::ai::Provider::Groq => {
+                            let idx = c.ai.groq.current_model_index;
+                            if idx < c.ai.groq.models.len() {
+                                c.ai.groq.models[idx].system_prompt = prompt;
+                            }
+                        }
+                        crate::ai::Provider::Mistral => {
+                            let idx = c.ai.mistral.current_model_index;
+                            if idx < c.ai.mistral.models.len() {
+                                c.ai.mistral.models[idx].system_prompt = prompt;
+                            }
+                        }
+                        crate::ai::Provider::LlamaCpp => {
+                            let idx = c.ai.llamacpp.current_model_index;
+                            if idx < c.ai.llamacpp.models.len() {
+                                c.ai.llamacpp.models[idx].system_prompt = prompt;
+                            }
+                        }
+                        crate::ai::Provider::AzureOpenAI => {
+                            let idx = c.ai.azure.current_model_index;
+                            if idx < c.ai.azure.models.len() {
+                                c.ai.azure.models[idx].system_prompt = prompt;
+                            }
+                        }
+                        crate::ai::Provider::Custom => {
+                            let idx = c.ai.custom.current_model_index;
+                            if idx < c.ai.custom.models.len() {
+                                c.ai.custom.models[idx].system_prompt = prompt;
+                            }
+                        }
                     }
                 })
                 .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
@@ -793,6 +1468,86 @@ impl CommandHandler {
                     "⚠️ Value must be true/false, yes/no, on/off, or 1/0".to_string(),
                 )),
             },
+            "exec" => {
+                let language = crate::config::CodeExecutionConfig::canonical_language(value);
+                if !config.code_execution.interpreters.contains_key(&language) {
+                    let known: Vec<&str> = config
+                        .code_execution
+                        .interpreters
+                        .keys()
+                        .map(|k| k.as_str())
+                        .collect();
+                    return Err(HandlerError::Parse(format!(
+                        "⚠️ No interpreter configured for '{}'. Configured: {}",
+                        value,
+                        known.join(", ")
+                    )));
+                }
+
+                let enabled = match args.get(2).map(|v| v.to_lowercase()) {
+                    Some(v) if matches!(v.as_str(), "true" | "yes" | "on" | "1") => true,
+                    Some(v) if matches!(v.as_str(), "false" | "no" | "off" | "0") => false,
+                    _ => {
+                        return Err(HandlerError::Parse(
+                            "⚠️ Usage: /config exec <language> on|off".to_string(),
+                        ));
+                    }
+                };
+
+                update_field(|c: &mut AppConfig| {
+                    if let Some(interpreter) = c.code_execution.interpreters.get_mut(&language) {
+                        interpreter.enabled = enabled;
+                    }
+                })
+                .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+
+                Ok(format!(
+                    "✅ Execution of {} blocks {}",
+                    language,
+                    if enabled { "enabled" } else { "disabled" }
+                ))
+            }
+            "seed" => {
+                let seed = match value.to_lowercase().as_str() {
+                    "off" | "none" => None,
+                    _ => Some(value.parse::<u64>().map_err(|_| {
+                        HandlerError::Parse("⚠️ Usage: /config seed <number>|off".to_string())
+                    })?),
+                };
+                update_field(|c: &mut AppConfig| {
+                    c.ai.seed = seed;
+                })
+                .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+
+                Ok(match seed {
+                    Some(seed) => format!("✅ Requests are sent with seed {}", seed),
+                    None => "✅ Seed cleared".to_string(),
+                })
+            }
+            "deterministic" => {
+                let enabled = match value.to_lowercase().as_str() {
+                    "true" | "yes" | "on" | "1" => true,
+                    "false" | "no" | "off" | "0" => false,
+                    _ => {
+                        return Err(HandlerError::Parse(
+                            "⚠️ Usage: /config deterministic on|off".to_string(),
+                        ));
+                    }
+                };
+                update_field(|c: &mut AppConfig| {
+                    c.ai.deterministic = enabled;
+                })
+                .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+
+                if enabled {
+                    Ok(format!(
+                        "✅ Deterministic mode on: temperature 0 and seed {}",
+                        config.ai.seed.unwrap_or(0)
+                    ))
+                } else {
+                    Ok("✅ Deterministic mode off".to_string())
+                }
+            }
             "reset" => {
                 update_field(|c: &mut AppConfig| {
                     *c = AppConfig::default();
@@ -807,6 +1562,503 @@ impl CommandHandler {
         }
     }
 
+    /// Handle `/config azure [resource|endpoint|api_version|deployment|remove <value>]`
+    fn handle_azure_config(args: &[&str]) -> HandlerResult<String> {
+        let config = get_config();
+        let azure = &config.ai.azure;
+
+        if args.is_empty() {
+            let endpoint = crate::ai::azure::endpoint(azure)
+                .unwrap_or_else(|_| "not set (set a resource)".to_string());
+            let deployments: Vec<String> = azure
+                .models
+                .iter()
+                .enumerate()
+                .map(|(i, model)| {
+                    if i == azure.current_model_index {
+                        format!("{} (active)", model.name)
+                    } else {
+                        model.name.clone()
+                    }
+                })
+                .collect();
+            let auth = if azure.oauth.is_some() {
+                "Entra ID login"
+            } else if azure.api_key.is_empty() {
+                "not set"
+            } else {
+                "API key"
+            };
+            return Ok(format!(
+                "☁️ Azure OpenAI:
+                Resource: {}
+                Endpoint: {}
+                API Version: {}
+                Deployments: {}
+                Credentials: {}
+
+                Use /config azure resource|endpoint|api_version <value>, /config azure deployment <name>
+                to add or select a deployment and /config azure remove <name> to remove one.",
+                if azure.resource.is_empty() {
+                    "not set"
+                } else {
+                    &azure.resource
+                },
+                endpoint,
+                azure.api_version,
+                deployments.join(", "),
+                auth
+            ));
+        }
+
+        let key = args[0].to_lowercase();
+        let value = args.get(1).copied().unwrap_or("");
+        if value.is_empty() {
+            return Err(HandlerError::Parse(
+                "⚠️ Usage: /config azure resource|endpoint|api_version|deployment|remove <value>"
+                    .to_string(),
+            ));
+        }
+
+        let message = match key.as_str() {
+            "resource" => {
+                if !value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+                    return Err(HandlerError::Parse(
+                        "⚠️ A resource name has only letters, digits and hyphens, e.g. my-team-openai"
+                            .to_string(),
+                    ));
+                }
+                update_field(|c: &mut AppConfig| c.ai.azure.resource = value.to_string())
+                    .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+                format!(
+                    "✅ Azure resource set to: {} (https://{}.openai.azure.com)",
+                    value, value
+                )
+            }
+            "endpoint" => {
+                let endpoint = match value {
+                    "default" | "off" => "",
+                    _ if value.starts_with("http://") || value.starts_with("https://") => value,
+                    _ => {
+                        return Err(HandlerError::Parse(
+                            "⚠️ Endpoint URL must start with http:// or https://, or be `default`"
+                                .to_string(),
+                        ));
+                    }
+                };
+                update_field(|c: &mut AppConfig| c.ai.azure.endpoint = endpoint.to_string())
+                    .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+                if endpoint.is_empty() {
+                    "✅ Azure requests go to the endpoint of the resource".to_string()
+                } else {
+                    format!("✅ Azure endpoint set to: {}", endpoint)
+                }
+            }
+            "api_version" => {
+                let version = Regex::new(r"^\d{4}-\d{2}-\d{2}(-preview)?$").unwrap();
+                if !version.is_match(value) {
+                    return Err(HandlerError::Parse(format!(
+                        "⚠️ API versions look like {} or 2025-01-01-preview",
+                        crate::ai::azure::DEFAULT_API_VERSION
+                    )));
+                }
+                update_field(|c: &mut AppConfig| c.ai.azure.api_version = value.to_string())
+                    .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+                format!("✅ Azure API version set to: {}", value)
+            }
+            "deployment" => {
+                update_field(|c: &mut AppConfig| {
+                    let azure = &mut c.ai.azure;
+                    match azure.models.iter().position(|model| model.name == value) {
+                        Some(idx) => azure.current_model_index = idx,
+                        None => {
+                            // Settings of the selected deployment apply to the new one
+                            let mut model = azure
+                                .models
+                                .get(azure.current_model_index)
+                                .cloned()
+                                .unwrap_or_default();
+                            model.name = value.to_string();
+                            azure.models.push(model);
+                            azure.current_model_index = azure.models.len() - 1;
+                        }
+                    }
+                })
+                .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+                format!("✅ Azure deployment set to: {}", value)
+            }
+            "remove" => {
+                let Some(idx) = azure.models.iter().position(|model| model.name == value) else {
+                    return Err(HandlerError::Parse(format!(
+                        "⚠️ No Azure deployment named {}",
+                        value
+                    )));
+                };
+                if azure.models.len() == 1 {
+                    return Err(HandlerError::Parse(
+                        "⚠️ The last deployment cannot be removed; add another one first"
+                            .to_string(),
+                    ));
+                }
+                update_field(|c: &mut AppConfig| {
+                    let azure = &mut c.ai.azure;
+                    azure.models.remove(idx);
+                    if azure.current_model_index > idx
+                        || azure.current_model_index >= azure.models.len()
+                    {
+                        azure.current_model_index = azure.current_model_index.saturating_sub(1);
+                    }
+                })
+                .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+                format!("✅ Azure deployment {} removed", value)
+            }
+            _ => {
+                return Err(HandlerError::Parse(format!(
+                    "⚠️ Unknown Azure setting: {}. Use resource, endpoint, api_version, deployment or remove",
+                    key
+                )));
+            }
+        };
+
+        // Requests of the active client go to the new deployment or endpoint
+        if config.ai.active_provider == crate::ai::Provider::AzureOpenAI {
+            let app = crate::app::App::new();
+            app.ai_handler
+                .update_client()
+                .map_err(|e| HandlerError::Other(format!("Failed to update AI client: {}", e)))?;
+        }
+        Ok(message)
+    }
+
+    /// Handle `/config custom [endpoint|model|remove|key_header|header|discover|stream_usage <value>]`
+    fn handle_custom_config(args: &[&str]) -> HandlerResult<String> {
+        let config = get_config();
+        let custom = &config.ai.custom;
+
+        if args.is_empty() {
+            let models: Vec<String> = custom
+                .models
+                .iter()
+                .enumerate()
+                .map(|(i, model)| {
+                    if i == custom.current_model_index {
+                        format!("{} (active)", model.name)
+                    } else {
+                        model.name.clone()
+                    }
+                })
+                .collect();
+            let auth = match (
+                &custom.oauth,
+                custom.api_key.is_empty(),
+                &custom.api_key_header,
+            ) {
+                (Some(_), _, _) => "device flow login".to_string(),
+                (None, true, _) => "none".to_string(),
+                (None, false, Some(header)) => format!("API key in the {} header", header),
+                (None, false, None) => "API key as a bearer token".to_string(),
+            };
+            let headers: Vec<&str> = custom.extra_headers.keys().map(String::as_str).collect();
+            return Ok(format!(
+                "🔌 Custom OpenAI-compatible endpoint:
+                Endpoint: {}
+                Models: {}
+                Credentials: {}
+                Extra headers: {}
+                Discover models: {}
+                Stream usage: {}
+
+                Use /config custom endpoint <url>, /config custom model <name> to add or select a
+                model, /config custom remove <name>, /config custom key_header <name|off>,
+                /config custom header <name> <value|off> and /config custom discover|stream_usage on|off.",
+                custom.endpoint,
+                models.join(", "),
+                auth,
+                if headers.is_empty() {
+                    "none".to_string()
+                } else {
+                    headers.join(", ")
+                },
+                if custom.discover_models { "on" } else { "off" },
+                if custom.stream_usage { "on" } else { "off" }
+            ));
+        }
+
+        let key = args[0].to_lowercase();
+        let value = args.get(1).copied().unwrap_or("");
+        if value.is_empty() {
+            return Err(HandlerError::Parse(
+                "⚠️ Usage: /config custom endpoint|model|remove|key_header|header|discover|stream_usage <value>"
+                    .to_string(),
+            ));
+        }
+        let switch = |value: &str| match value.to_lowercase().as_str() {
+            "on" | "true" | "yes" | "1" => Ok(true),
+            "off" | "false" | "no" | "0" => Ok(false),
+            _ => Err(HandlerError::Parse(format!(
+                "⚠️ Usage: /config custom {} on|off",
+                key
+            ))),
+        };
+
+        let message = match key.as_str() {
+            "endpoint" => {
+                if !value.starts_with("http://") && !value.starts_with("https://") {
+                    return Err(HandlerError::Parse(
+                        "⚠️ Endpoint URL must start with http:// or https://".to_string(),
+                    ));
+                }
+                update_field(|c: &mut AppConfig| c.ai.custom.endpoint = value.to_string())
+                    .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+                format!("✅ Custom endpoint set to: {}", value)
+            }
+            "model" => {
+                update_field(|c: &mut AppConfig| {
+                    let custom = &mut c.ai.custom;
+                    match custom.models.iter().position(|model| model.name == value) {
+                        Some(idx) => custom.current_model_index = idx,
+                        None => {
+                            // Settings of the selected model apply to the new one
+                            let mut model = custom
+                                .models
+                                .get(custom.current_model_index)
+                                .cloned()
+                                .unwrap_or_default();
+                            model.name = value.to_string();
+                            custom.models.push(model);
+                            custom.current_model_index = custom.models.len() - 1;
+                        }
+                    }
+                })
+                .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+                format!("✅ Custom model set to: {}", value)
+            }
+            "remove" => {
+                let Some(idx) = custom.models.iter().position(|model| model.name == value) else {
+                    return Err(HandlerError::Parse(format!(
+                        "⚠️ No custom model named {}",
+                        value
+                    )));
+                };
+                if custom.models.len() == 1 {
+                    return Err(HandlerError::Parse(
+                        "⚠️ The last model cannot be removed; add another one first".to_string(),
+                    ));
+                }
+                update_field(|c: &mut AppConfig| {
+                    let custom = &mut c.ai.custom;
+                    custom.models.remove(idx);
+                    if custom.current_model_index > idx
+                        || custom.current_model_index >= custom.models.len()
+                    {
+                        custom.current_model_index = custom.current_model_index.saturating_sub(1);
+                    }
+                })
+                .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+                format!("✅ Custom model {} removed", value)
+            }
+            "key_header" => {
+                let header = match value {
+                    "off" | "bearer" => None,
+                    _ => Some(value.to_string()),
+                };
+                let mut checked = custom.clone();
+                checked.api_key_header = header.clone();
+                crate::ai::custom::key_header(&checked)
+                    .map_err(|e| HandlerError::Parse(format!("⚠️ {}", e)))?;
+                update_field(|c: &mut AppConfig| c.ai.custom.api_key_header = header.clone())
+                    .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+                match header {
+                    Some(header) => format!("✅ The API key is sent in the {} header", header),
+                    None => "✅ The API key is sent as a bearer token".to_string(),
+                }
+            }
+            "header" => {
+                let header_value = args[2..].join(" ");
+                if header_value.is_empty() {
+                    return Err(HandlerError::Parse(
+                        "⚠️ Usage: /config custom header <name> <value|off>".to_string(),
+                    ));
+                }
+                if reqwest::header::HeaderName::from_bytes(value.as_bytes()).is_err() {
+                    return Err(HandlerError::Parse(format!(
+                        "⚠️ '{}' is not a valid header name",
+                        value
+                    )));
+                }
+                let remove = header_value == "off";
+                update_field(|c: &mut AppConfig| {
+                    if remove {
+                        c.ai.custom.extra_headers.remove(value);
+                    } else {
+                        c.ai.custom
+                            .extra_headers
+                            .insert(value.to_string(), header_value.clone());
+                    }
+                })
+                .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+                if remove {
+                    format!("✅ Header {} removed", value)
+                } else {
+                    format!("✅ Header {} is sent with every request", value)
+                }
+            }
+            "discover" => {
+                let enabled = switch(value)?;
+                update_field(|c: &mut AppConfig| c.ai.custom.discover_models = enabled)
+                    .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+                if enabled {
+                    "✅ /list models also shows the models the endpoint reports".to_string()
+                } else {
+                    "✅ /list models shows the configured models only".to_string()
+                }
+            }
+            "stream_usage" => {
+                let enabled = switch(value)?;
+                update_field(|c: &mut AppConfig| c.ai.custom.stream_usage = enabled)
+                    .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+                format!(
+                    "✅ Usage is {}asked for at the end of streamed responses",
+                    if enabled { "" } else { "no longer " }
+                )
+            }
+            _ => {
+                return Err(HandlerError::Parse(format!(
+                    "⚠️ Unknown custom setting: {}. Use endpoint, model, remove, key_header, header, discover or stream_usage",
+                    key
+                )));
+            }
+        };
+
+        // Requests of the active client use the new settings
+        if config.ai.active_provider == crate::ai::Provider::Custom {
+            let app = crate::app::App::new();
+            app.ai_handler
+                .update_client()
+                .map_err(|e| HandlerError::Other(format!("Failed to update AI client: {}", e)))?;
+        }
+        Ok(message)
+    }
+
+    /// Handle `/config pricing [[provider/]model <prompt> <completion> | remove [provider/]model]`
+    fn handle_pricing_config(args: &[&str]) -> HandlerResult<String> {
+        let config = get_config();
+        // `provider/model` prices one provider's model, a bare name every provider's
+        let parse_model = |spec: &str| match spec.split_once('/') {
+            Some((prefix, model)) => match prefix.parse::<crate::ai::Provider>() {
+                Ok(provider) => (Some(provider), model.to_string()),
+                Err(_) => (None, spec.to_string()),
+            },
+            None => (None, spec.to_string()),
+        };
+        let describe = |provider: Option<crate::ai::Provider>, model: &str| match provider {
+            Some(provider) => format!("{}/{}", provider, model),
+            None => model.to_string(),
+        };
+
+        match args {
+            [] => {
+                let provider = config.ai.active_provider;
+                let model = config.ai.get_active_model_config().name;
+                let (costs, source) = crate::ai::pricing::lookup(&config.pricing, provider, &model);
+                let configured: Vec<String> = config
+                    .pricing
+                    .iter()
+                    .map(|price| {
+                        format!(
+                            "  {}: ${} prompt, ${} completion",
+                            describe(price.provider, &price.model),
+                            price.prompt,
+                            price.completion
+                        )
+                    })
+                    .collect();
+                Ok(format!(
+                    "💲 {} {}: ${} per 1K prompt tokens, ${} per 1K completion tokens ({})\n{}\n\nUse /config pricing [provider/]model <prompt> <completion> to set a price in dollars per 1K tokens and /config pricing remove [provider/]model to go back to the built-in one.",
+                    provider,
+                    model,
+                    costs.prompt_cost_per_1k,
+                    costs.completion_cost_per_1k,
+                    source,
+                    if configured.is_empty() {
+                        "No prices configured; the built-in table applies.".to_string()
+                    } else {
+                        format!("Configured prices:\n{}", configured.join("\n"))
+                    }
+                ))
+            }
+            ["remove", spec] => {
+                let (provider, model) = parse_model(spec);
+                if !config
+                    .pricing
+                    .iter()
+                    .any(|price| price.provider == provider && price.model == model)
+                {
+                    return Err(HandlerError::Parse(format!(
+                        "⚠️ No price configured for {}",
+                        describe(provider, &model)
+                    )));
+                }
+                update_field(|c: &mut AppConfig| {
+                    c.pricing
+                        .retain(|price| price.provider != provider || price.model != model)
+                })
+                .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+                Ok(format!(
+                    "✅ Price of {} removed",
+                    describe(provider, &model)
+                ))
+            }
+            [spec, prompt, completion] => {
+                let price = |value: &str| {
+                    value
+                        .trim_start_matches('$')
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|price| price.is_finite() && *price >= 0.0)
+                        .ok_or_else(|| {
+                            HandlerError::Parse(format!(
+                                "⚠️ '{}' is not a price in dollars per 1K tokens",
+                                value
+                            ))
+                        })
+                };
+                let (prompt, completion) = (price(prompt)?, price(completion)?);
+                let (provider, model) = parse_model(spec);
+                if model.is_empty() {
+                    return Err(HandlerError::Parse(format!("⚠️ No model in '{}'", spec)));
+                }
+                let entry = crate::config::ModelPrice {
+                    provider,
+                    model: model.clone(),
+                    prompt,
+                    completion,
+                };
+                update_field(|c: &mut AppConfig| {
+                    match c
+                        .pricing
+                        .iter_mut()
+                        .find(|price| price.provider == provider && price.model == model)
+                    {
+                        Some(existing) => *existing = entry.clone(),
+                        None => c.pricing.push(entry.clone()),
+                    }
+                })
+                .map_err(|e| HandlerError::Other(format!("Failed to update config: {}", e)))?;
+                Ok(format!(
+                    "✅ {} costs ${} per 1K prompt tokens and ${} per 1K completion tokens",
+                    describe(provider, &model),
+                    prompt,
+                    completion
+                ))
+            }
+            _ => Err(HandlerError::Parse(
+                "⚠️ Usage: /config pricing [[provider/]model <prompt> <completion> | remove [provider/]model]"
+                    .to_string(),
+            )),
+        }
+    }
+
     /// Handle theme customization
     fn handle_theme(args: &[&str]) -> HandlerResult<String> {
         let config = get_config();
@@ -820,19 +2072,58 @@ impl CommandHandler {
                 Accent: {}
                 Background: {}
                 Foreground: {}
+                Search matches: {}
+                Spinner: {}
+                Progress bar: {}
 
-                Use /theme [key] [value] to change colors.",
+                Use /theme [key] [value] to change colors, /theme spinner <braille|dots|line|none>
+                and /theme progress_bar <on|off> to change how prompts in progress are shown.",
                 config.theme.primary,
                 config.theme.secondary,
                 config.theme.accent,
                 config.theme.background,
-                config.theme.foreground
+                config.theme.foreground,
+                config.theme.search_match,
+                config.theme.spinner,
+                if config.theme.progress_bar {
+                    "on"
+                } else {
+                    "off"
+                }
             ));
         }
 
         let key = args[0].to_lowercase();
         let value = if args.len() > 1 { args[1] } else { "" };
 
+        match key.as_str() {
+            "spinner" => {
+                let style: crate::config::SpinnerStyle =
+                    value.to_lowercase().parse().map_err(HandlerError::Parse)?;
+                update_field(|c: &mut AppConfig| c.theme.spinner = style)
+                    .map_err(|e| HandlerError::Other(format!("Failed to update theme: {}", e)))?;
+                return Ok(format!("✅ Spinner set to: {}", style));
+            }
+            "progress_bar" => {
+                let enabled = match value {
+                    "on" => true,
+                    "off" => false,
+                    _ => {
+                        return Err(HandlerError::Parse(
+                            "Usage: /theme progress_bar <on|off>".to_string(),
+                        ));
+                    }
+                };
+                update_field(|c: &mut AppConfig| c.theme.progress_bar = enabled)
+                    .map_err(|e| HandlerError::Other(format!("Failed to update theme: {}", e)))?;
+                return Ok(format!(
+                    "✅ Progress bar {}",
+                    if enabled { "on" } else { "off" }
+                ));
+            }
+            _ => {}
+        }
+
         if value.is_empty() {
             return Err(HandlerError::Parse(format!(
                 "Color value required for: {}",
@@ -908,6 +2199,13 @@ impl CommandHandler {
                 .map_err(|e| HandlerError::Other(format!("Failed to update theme: {}", e)))?;
                 Ok(format!("✅ Foreground color set to: {}", color_value))
             }
+            "search_match" => {
+                update_field(|c: &mut AppConfig| {
+                    c.theme.search_match = color_value.clone();
+                })
+                .map_err(|e| HandlerError::Other(format!("Failed to update theme: {}", e)))?;
+                Ok(format!("✅ Search match color set to: {}", color_value))
+            }
             "reset" => {
                 update_field(|c: &mut AppConfig| {
                     c.theme = crate::config::ThemeConfig::default();
@@ -0,0 +1,439 @@
+//! Documentation handler
+//!
+//! This module prepares `/doc` requests. The target file is sent to the
+//! model with line numbers and the model answers with plain documentation
+//! text for each item (`@@ <line>` followed by the text). The text is then
+//! turned into doc comments of the file's language and inserted at the
+//! right positions, skipping items that are already documented.
+
+use crate::handlers::{HandlerError, HandlerResult, workspace};
+use crate::utils::glob::{Glob, find_files};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Largest file sent to the model for documentation
+const MAX_DOC_FILE_BYTES: u64 = 128 * 1024;
+
+/// Header introducing the documentation for one line in the response
+static DOC_HEADER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\s*@@\s*(\d+)\s*@*\s*$").unwrap());
+
+/// Doc comment conventions supported by `/doc`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DocStyle {
+    /// `///` comments above the item (Rust)
+    Rust,
+    /// Docstrings inside the definition (Python)
+    Python,
+    /// `/** ... */` blocks above the item (JavaScript, TypeScript, Java, C, ...)
+    Block,
+    /// `//` comments above the item (Go)
+    Go,
+}
+
+impl DocStyle {
+    /// Pick the style for a file extension
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(DocStyle::Rust),
+            "py" => Some(DocStyle::Python),
+            "go" => Some(DocStyle::Go),
+            "js" | "jsx" | "mjs" | "ts" | "tsx" | "java" | "kt" | "c" | "h" | "cpp" | "hpp"
+            | "cs" | "php" | "swift" => Some(DocStyle::Block),
+            _ => None,
+        }
+    }
+
+    /// Name used in the prompt
+    fn comment_name(&self) -> &'static str {
+        match self {
+            DocStyle::Rust => "rustdoc",
+            DocStyle::Python => "docstring",
+            DocStyle::Block => "doc comment",
+            DocStyle::Go => "godoc",
+        }
+    }
+
+    /// Pattern matching lines that start a documentable item
+    fn item_regex(&self) -> &'static Regex {
+        static RUST: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(
+                r#"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:(?:async|const|unsafe|extern\s+"[^"]*")\s+)*(?:fn|struct|enum|trait|type|mod|const|static|union|impl|macro_rules!)\b"#,
+            )
+            .unwrap()
+        });
+        static PYTHON: Lazy<Regex> =
+            Lazy::new(|| Regex::new(r"^\s*(?:async\s+)?(?:def|class)\s+\w+").unwrap());
+        static BLOCK: Lazy<Regex> = Lazy::new(|| {
+            Regex::new(
+                r"^\s*(?:export\s+)?(?:default\s+)?(?:(?:public|private|protected|static|abstract|final|async)\s+)*(?:function\*?|class|interface|enum|type|const|let|struct|[\w<>\[\],]+\s+\w+\s*\()",
+            )
+            .unwrap()
+        });
+        static GO: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?:func|type|var|const)\b").unwrap());
+
+        match self {
+            DocStyle::Rust => &RUST,
+            DocStyle::Python => &PYTHON,
+            DocStyle::Block => &BLOCK,
+            DocStyle::Go => &GO,
+        }
+    }
+}
+
+/// The file, and optionally the single item, to document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocTarget {
+    /// File relative to the working directory
+    pub path: PathBuf,
+    /// Comment convention of the file
+    pub style: DocStyle,
+    /// 1-based line of the item when documenting a single symbol
+    pub line: Option<usize>,
+}
+
+impl DocTarget {
+    /// Description shown to the user
+    pub fn describe(&self) -> String {
+        let path = self.path.to_string_lossy().replace('\\', "/");
+        match self.line {
+            Some(line) => format!("{}:{}", path, line),
+            None => path,
+        }
+    }
+}
+
+/// Resolve `<path>`, `<path>:<symbol>` or `<symbol>` to a documentation target
+pub fn resolve_target(root: &Path, arg: &str) -> HandlerResult<DocTarget> {
+    let arg = arg.trim();
+    if arg.is_empty() {
+        return Err(HandlerError::Parse(
+            "Usage: /doc <path|symbol> or /doc <path>:<symbol>".to_string(),
+        ));
+    }
+
+    // Whole file
+    if root.join(arg).is_file() {
+        workspace::check(root, Path::new(arg))?;
+        let path = PathBuf::from(arg);
+        let style = style_for(&path)?;
+        return Ok(DocTarget {
+            path,
+            style,
+            line: None,
+        });
+    }
+
+    // Symbol within a given file
+    if let Some((file, symbol)) = arg.rsplit_once(':')
+        && root.join(file).is_file()
+    {
+        workspace::check(root, Path::new(file))?;
+        let path = PathBuf::from(file);
+        let style = style_for(&path)?;
+        let content = read_source(&root.join(&path))?;
+        let line = find_symbol(&content, style, symbol).ok_or_else(|| {
+            HandlerError::Other(format!("No definition of '{}' in {}", symbol, file))
+        })?;
+        return Ok(DocTarget {
+            path,
+            style,
+            line: Some(line),
+        });
+    }
+
+    // Symbol anywhere in the project
+    let all_files = Glob::new("*").map_err(|e| HandlerError::Other(e.to_string()))?;
+    for path in find_files(root, &all_files) {
+        let Some(style) = DocStyle::from_path(&path) else {
+            continue;
+        };
+        if workspace::check(root, &path).is_err() {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(root.join(&path)) else {
+            continue;
+        };
+        if let Some(line) = find_symbol(&content, style, arg) {
+            return Ok(DocTarget {
+                path,
+                style,
+                line: Some(line),
+            });
+        }
+    }
+
+    Err(HandlerError::Other(format!(
+        "'{}' is neither a file nor a symbol defined in the project",
+        arg
+    )))
+}
+
+/// Read the target file, enforcing the size limit
+pub fn read_source(path: &Path) -> HandlerResult<String> {
+    let size = fs::metadata(path)?.len();
+    if size > MAX_DOC_FILE_BYTES {
+        return Err(HandlerError::Other(format!(
+            "{} is too large to document ({} bytes)",
+            path.display(),
+            size
+        )));
+    }
+    fs::read_to_string(path)
+        .map_err(|e| HandlerError::Other(format!("Failed to read {}: {}", path.display(), e)))
+}
+
+/// Build the prompt asking for documentation of the target
+pub fn build_prompt(target: &DocTarget, content: &str) -> String {
+    let numbered: String = content
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| format!("{:>5} | {}\n", idx + 1, line))
+        .collect();
+    let scope = match target.line {
+        Some(line) => format!("only the item defined on line {}", line),
+        None => "every public or non-trivial item that has no documentation yet".to_string(),
+    };
+
+    format!(
+        "Write {} documentation for {} in {}.\n\n\
+         Answer only with entries of this form, one per item, using the line number \
+         where the item's definition starts:\n\
+         @@ <line>\n<documentation text>\n\n\
+         Write plain text without comment markers; they are added automatically. \
+         Describe what the item does, its parameters, return value and errors where \
+         relevant, following the usual conventions of the language. Keep it concise.\n\n{}",
+        target.style.comment_name(),
+        scope,
+        target.describe(),
+        numbered
+    )
+}
+
+/// Parse `@@ <line>` entries from the model's answer
+pub fn parse_doc_response(response: &str) -> Vec<(usize, String)> {
+    let mut entries: Vec<(usize, String)> = Vec::new();
+    let mut current: Option<(usize, Vec<String>)> = None;
+
+    for line in response.lines() {
+        if let Some(cap) = DOC_HEADER_RE.captures(line) {
+            if let Some((number, text)) = current.take() {
+                entries.push((number, text.join("\n")));
+            }
+            current = cap[1].parse().ok().map(|n| (n, Vec::new()));
+        } else if line.trim_start().starts_with("```") {
+            continue;
+        } else if let Some((_, text)) = current.as_mut() {
+            text.push(strip_comment_markers(line));
+        }
+    }
+    if let Some((number, text)) = current {
+        entries.push((number, text.join("\n")));
+    }
+
+    entries
+        .into_iter()
+        .map(|(number, text)| (number, text.trim().to_string()))
+        .filter(|(_, text)| !text.is_empty())
+        .collect()
+}
+
+/// Insert documentation entries into `content`
+///
+/// Entries must point at lines that start an item; entries for other lines
+/// or for items that already have documentation are ignored. When `only` is
+/// set, just that line is documented. Returns the new content and the
+/// number of items documented.
+pub fn insert_docs(
+    content: &str,
+    style: DocStyle,
+    entries: &[(usize, String)],
+    only: Option<usize>,
+) -> (String, usize) {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let item_re = style.item_regex();
+
+    let mut targets: Vec<(usize, &str)> = entries
+        .iter()
+        .filter(|(number, _)| only.is_none_or(|line| line == *number))
+        .filter(|(number, _)| *number >= 1 && *number <= lines.len())
+        .map(|(number, text)| (number - 1, text.as_str()))
+        .filter(|(idx, _)| item_re.is_match(&lines[*idx]))
+        .collect();
+    targets.sort_by_key(|(idx, _)| *idx);
+    targets.dedup_by_key(|(idx, _)| *idx);
+
+    // Insert bottom-up so earlier line numbers stay valid
+    let mut inserted = 0;
+    for (idx, text) in targets.into_iter().rev() {
+        let indent: String = lines[idx]
+            .chars()
+            .take_while(|c| c.is_whitespace())
+            .collect();
+
+        let (position, comment) = match style {
+            DocStyle::Python => {
+                let Some(body) = python_body_start(&lines, idx) else {
+                    continue;
+                };
+                let next = lines[body..].iter().find(|l| !l.trim().is_empty());
+                if next.is_some_and(|l| is_docstring_start(l.trim_start())) {
+                    continue;
+                }
+                let body_indent = next
+                    .map(|l| l.chars().take_while(|c| c.is_whitespace()).collect())
+                    .filter(|i: &String| i.len() > indent.len())
+                    .unwrap_or_else(|| format!("{}    ", indent));
+                (body, python_docstring(&body_indent, text))
+            }
+            _ => {
+                let start = attribute_start(&lines, idx, style);
+                if start > 0 && is_documented(lines[start - 1].trim_start(), style) {
+                    continue;
+                }
+                (start, comment_lines(&indent, text, style))
+            }
+        };
+
+        lines.splice(position..position, comment);
+        inserted += 1;
+    }
+
+    let mut result = lines.join("\n");
+    if content.ends_with('\n') {
+        result.push('\n');
+    }
+    (result, inserted)
+}
+
+fn style_for(path: &Path) -> HandlerResult<DocStyle> {
+    DocStyle::from_path(path).ok_or_else(|| {
+        HandlerError::Other(format!(
+            "Documentation is not supported for {}",
+            path.display()
+        ))
+    })
+}
+
+/// Find the 1-based line where `symbol` is defined
+fn find_symbol(content: &str, style: DocStyle, symbol: &str) -> Option<usize> {
+    let symbol = regex::escape(symbol.trim());
+    let definition = Regex::new(&format!(
+        r"\b(?:fn|struct|enum|trait|type|mod|const|static|union|def|class|function|interface|func(?:\s*\([^)]*\))?|let|var)\s+{}\b",
+        symbol
+    ))
+    .ok()?;
+    let item_re = style.item_regex();
+
+    content
+        .lines()
+        .position(|line| item_re.is_match(line) && definition.is_match(line))
+        .map(|idx| idx + 1)
+}
+
+/// First line above the item's attributes/decorators, where docs belong
+fn attribute_start(lines: &[String], idx: usize, style: DocStyle) -> usize {
+    let mut start = idx;
+    while start > 0 {
+        let above = lines[start - 1].trim_start();
+        let is_attribute = match style {
+            DocStyle::Rust => above.starts_with("#[") && !above.starts_with("#[doc"),
+            DocStyle::Block => above.starts_with('@'),
+            _ => false,
+        };
+        if !is_attribute {
+            break;
+        }
+        start -= 1;
+    }
+    start
+}
+
+fn is_documented(above: &str, style: DocStyle) -> bool {
+    match style {
+        DocStyle::Rust => above.starts_with("///") || above.starts_with("#[doc"),
+        DocStyle::Block => above.ends_with("*/"),
+        DocStyle::Go => above.starts_with("//"),
+        DocStyle::Python => false,
+    }
+}
+
+/// Index of the first body line of a Python definition (after the signature)
+fn python_body_start(lines: &[String], idx: usize) -> Option<usize> {
+    lines[idx..]
+        .iter()
+        .position(|line| {
+            let code = line.split('#').next().unwrap_or(line).trim_end();
+            code.ends_with(':')
+        })
+        .map(|offset| idx + offset + 1)
+}
+
+fn is_docstring_start(line: &str) -> bool {
+    let line = line.trim_start_matches(['r', 'R', 'u', 'U']);
+    line.starts_with("\"\"\"") || line.starts_with("'''")
+}
+
+fn comment_lines(indent: &str, text: &str, style: DocStyle) -> Vec<String> {
+    let prefix = match style {
+        DocStyle::Rust => "///",
+        DocStyle::Go => "//",
+        _ => " *",
+    };
+    let mut lines: Vec<String> = text
+        .lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                format!("{}{}", indent, prefix)
+            } else {
+                format!("{}{} {}", indent, prefix, line.trim_end())
+            }
+        })
+        .collect();
+
+    if style == DocStyle::Block {
+        lines.insert(0, format!("{}/**", indent));
+        lines.push(format!("{} */", indent));
+    }
+    lines
+}
+
+fn python_docstring(indent: &str, text: &str) -> Vec<String> {
+    let text_lines: Vec<&str> = text.lines().collect();
+    if text_lines.len() == 1 {
+        return vec![format!("{}\"\"\"{}\"\"\"", indent, text_lines[0].trim())];
+    }
+
+    let mut lines = vec![format!("{}\"\"\"{}", indent, text_lines[0].trim())];
+    lines.extend(text_lines[1..].iter().map(|line| {
+        if line.trim().is_empty() {
+            String::new()
+        } else {
+            format!("{}{}", indent, line.trim_end())
+        }
+    }));
+    lines.push(format!("{}\"\"\"", indent));
+    lines
+}
+
+/// Remove comment markers the model may have added despite the instructions
+fn strip_comment_markers(line: &str) -> String {
+    let trimmed = line.trim();
+    for marker in ["///", "//!", "//", "/**", "*/", "\"\"\"", "'''"] {
+        if trimmed == marker {
+            return String::new();
+        }
+    }
+    let stripped = trimmed
+        .strip_prefix("/// ")
+        .or_else(|| trimmed.strip_prefix("///"))
+        .or_else(|| trimmed.strip_prefix("// "))
+        .or_else(|| trimmed.strip_prefix("* "))
+        .or_else(|| (trimmed == "*").then_some(""));
+    match stripped {
+        Some(text) => text.to_string(),
+        None => line.trim_end().to_string(),
+    }
+}
@@ -0,0 +1,204 @@
+//! Editor handoff
+//!
+//! Finds `file:line` locations in output, such as panics, compiler and
+//! clippy messages, Python tracebacks and search hits, and opens them in
+//! the user's editor.
+
+use crate::config::EditorConfig;
+use crate::handlers::{HandlerError, HandlerResult, safe};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fmt;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// `path:line` or `path:line:column`; the path needs an extension so times
+/// and ports are not taken for locations
+static LOCATION_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"(?:^|[\s(\[<'"`=])((?:[A-Za-z]:)?[\w./\\-]*\w\.\w+):(\d+)(?::(\d+))?"#).unwrap()
+});
+
+/// `File "path", line 12` of Python tracebacks
+static TRACEBACK_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"File "([^"]+)", line (\d+)"#).unwrap());
+
+/// Editors that open a window and return at once
+const GUI_EDITORS: [&str; 6] = ["code", "codium", "cursor", "subl", "zed", "gedit"];
+
+/// A place in a file, lines and columns counted from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLocation {
+    /// The path as written in the output
+    pub path: String,
+    pub line: usize,
+    pub column: Option<usize>,
+}
+
+impl FileLocation {
+    /// Parse `path`, `path:line` or `path:line:column`
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let mut parts = text.rsplitn(3, ':');
+        let numbers: Vec<&str> = parts.by_ref().take(2).collect();
+        let number = |part: &str| part.parse::<usize>().ok().filter(|n| *n > 0);
+        let (path, line, column) = match numbers.as_slice() {
+            [column, line] if number(column).is_some() && number(line).is_some() => {
+                (parts.next()?, number(line), number(column))
+            }
+            [line, ..] if number(line).is_some() => {
+                let end = text.len() - line.len() - 1;
+                (&text[..end], number(line), None)
+            }
+            _ => (text, Some(1), None),
+        };
+        if path.is_empty() {
+            return None;
+        }
+        Some(Self {
+            path: path.to_string(),
+            line: line?,
+            column,
+        })
+    }
+}
+
+impl fmt::Display for FileLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.path, self.line)?;
+        if let Some(column) = self.column {
+            write!(f, ":{}", column)?;
+        }
+        Ok(())
+    }
+}
+
+/// Locations mentioned in `text`, in order and without repeats
+pub fn find_locations(text: &str) -> Vec<FileLocation> {
+    let number = |value: Option<regex::Match>| {
+        value
+            .and_then(|value| value.as_str().parse::<usize>().ok())
+            .filter(|n| *n > 0)
+    };
+    let mut found: Vec<(usize, FileLocation)> = Vec::new();
+    for captures in LOCATION_RE.captures_iter(text) {
+        let path = captures.get(1).unwrap();
+        // Addresses such as `http://host.com:80` are not files
+        if text[..path.start()].ends_with("//") || path.as_str().contains("://") {
+            continue;
+        }
+        if let Some(line) = number(captures.get(2)) {
+            let location = FileLocation {
+                path: path.as_str().to_string(),
+                line,
+                column: number(captures.get(3)),
+            };
+            found.push((path.start(), location));
+        }
+    }
+    for captures in TRACEBACK_RE.captures_iter(text) {
+        let path = captures.get(1).unwrap();
+        if let Some(line) = number(captures.get(2)) {
+            let location = FileLocation {
+                path: path.as_str().to_string(),
+                line,
+                column: None,
+            };
+            found.push((path.start(), location));
+        }
+    }
+    found.sort_by_key(|(start, _)| *start);
+
+    let mut locations: Vec<FileLocation> = Vec::new();
+    for (_, location) in found {
+        if !locations.contains(&location) {
+            locations.push(location);
+        }
+    }
+    locations
+}
+
+/// The command template and whether it runs in the terminal
+///
+/// Without `editor.command` the editor comes from `$VISUAL` or `$EDITOR`
+/// (`vi` if neither is set) with arguments that suit it.
+pub fn resolve(config: &EditorConfig, env_editor: Option<String>) -> (String, bool) {
+    if !config.command.trim().is_empty() {
+        return (config.command.clone(), config.terminal);
+    }
+    let editor = env_editor
+        .filter(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+    let program = shell_words::split(&editor)
+        .ok()
+        .and_then(|words| words.into_iter().next())
+        .unwrap_or_default();
+    let name = Path::new(&program)
+        .file_stem()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let arguments = match name.as_str() {
+        "code" | "codium" | "cursor" => "-g {file}:{line}:{column}",
+        "subl" | "zed" | "hx" | "helix" => "{file}:{line}:{column}",
+        _ => "+{line} {file}",
+    };
+    let terminal = !GUI_EDITORS.contains(&name.as_str());
+    (format!("{} {}", editor, arguments), terminal)
+}
+
+/// The program and arguments of `template` for a location
+///
+/// The file is added at the end when the template does not name it.
+pub fn command_line(
+    template: &str,
+    path: &Path,
+    location: &FileLocation,
+) -> HandlerResult<Vec<String>> {
+    let words = shell_words::split(template)
+        .map_err(|e| HandlerError::Parse(format!("Invalid editor.command: {}", e)))?;
+    if words.is_empty() {
+        return Err(HandlerError::Parse("editor.command is empty".to_string()));
+    }
+    let file = path.to_string_lossy();
+    let mentions_file = words.iter().any(|word| word.contains("{file}"));
+    let mut args: Vec<String> = words
+        .iter()
+        .map(|word| {
+            word.replace("{file}", &file)
+                .replace("{line}", &location.line.to_string())
+                .replace("{column}", &location.column.unwrap_or(1).to_string())
+        })
+        .collect();
+    if !mentions_file {
+        args.push(file.to_string());
+    }
+    Ok(args)
+}
+
+/// Run the editor for a location
+///
+/// A terminal editor is waited for; a window editor is started in the
+/// background.
+pub fn open(args: &[String], terminal: bool, root: &Path) -> HandlerResult<()> {
+    safe::check("Opening an editor")?;
+    let mut command = Command::new(&args[0]);
+    command.args(&args[1..]).current_dir(root);
+    let failed =
+        |e: std::io::Error| HandlerError::Other(format!("Failed to run {}: {}", args[0], e));
+    if terminal {
+        let status = command.status().map_err(failed)?;
+        if !status.success() {
+            return Err(HandlerError::Other(format!(
+                "{} exited with {}",
+                args[0], status
+            )));
+        }
+    } else {
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(failed)?;
+    }
+    Ok(())
+}
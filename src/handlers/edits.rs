@@ -0,0 +1,398 @@
+//! Edits proposed in AI responses
+//!
+//! Besides whole files (see [`files`](crate::handlers::files)), responses can
+//! change files with unified diffs (```` ```diff ```` blocks or bare
+//! `---`/`+++` sections) or with search/replace blocks:
+//!
+//! ```text
+//! src/lib.rs
+//! <<<<<<< SEARCH
+//! fn old() {}
+//! =======
+//! fn new() {}
+//! >>>>>>> REPLACE
+//! ```
+//!
+//! Edits are applied to the files on disk in memory, giving a [`FileBlock`]
+//! with the new content per file, so they are previewed, written and undone
+//! exactly like whole-file blocks. Hunks are located by their content, so
+//! line numbers that are slightly off still apply. Files outside the
+//! workspace are neither read nor edited.
+
+use crate::handlers::files::{FileBlock, looks_like_path, sanitize_path};
+use crate::handlers::workspace;
+use crate::utils::diff::find_unified_diffs;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Start of the search part of a search/replace block
+static SEARCH_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^<{5,9} ?SEARCH\s*$").unwrap());
+
+/// Divider between the search and the replace part
+static DIVIDER_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^={5,9}\s*$").unwrap());
+
+/// End of a search/replace block
+static REPLACE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^>{5,9} ?REPLACE\s*$").unwrap());
+
+/// Hunk header with the old start line
+static HUNK_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^@@ -(\d+)(?:,\d+)? \+\d+(?:,\d+)? @@").unwrap());
+
+/// A line of a hunk
+#[derive(Debug, Clone, PartialEq)]
+pub enum HunkLine {
+    Context(String),
+    Remove(String),
+    Add(String),
+}
+
+/// A hunk of a unified diff
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunk {
+    /// Line the hunk starts at in the old file (1-based, 0 for an empty file)
+    pub old_start: usize,
+    pub lines: Vec<HunkLine>,
+}
+
+impl Hunk {
+    /// Lines the hunk expects in the file
+    fn old_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Remove(text) => Some(text.as_str()),
+                HunkLine::Add(_) => None,
+            })
+            .collect()
+    }
+
+    /// Lines the hunk leaves in the file
+    fn new_lines(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter_map(|line| match line {
+                HunkLine::Context(text) | HunkLine::Add(text) => Some(text.as_str()),
+                HunkLine::Remove(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// What an edit does to its file
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditKind {
+    /// Replace the first occurrence of `search`; an empty search creates the file
+    Replace { search: String, replace: String },
+    /// Apply the hunks of a unified diff
+    Patch(Vec<Hunk>),
+    /// The diff deletes the file, which is not done from responses
+    Delete,
+}
+
+/// An edit of one file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edit {
+    /// Path relative to the working directory
+    pub path: PathBuf,
+    pub kind: EditKind,
+}
+
+/// Whether a code block holds search/replace markers rather than file content
+pub fn has_edit_markers(text: &str) -> bool {
+    text.lines().any(|line| SEARCH_RE.is_match(line))
+}
+
+/// All search/replace blocks and unified diffs in a response, in order
+pub fn extract_edits(text: &str) -> Vec<Edit> {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut edits: Vec<(usize, Edit)> = extract_replacements(&lines);
+    for range in find_unified_diffs(&lines) {
+        edits.extend(
+            parse_unified_diff(&lines[range.clone()])
+                .into_iter()
+                .map(|edit| (range.start, edit)),
+        );
+    }
+    edits.sort_by_key(|(line, _)| *line);
+    edits.into_iter().map(|(_, edit)| edit).collect()
+}
+
+/// Path named on the line before a search/replace block, skipping fences
+fn path_before(lines: &[&str]) -> Option<PathBuf> {
+    for line in lines.iter().rev() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(info) = line.strip_prefix("```") {
+            // ```` ```rust src/lib.rs ```` names the file in the fence
+            match info.split_whitespace().find(|word| looks_like_path(word)) {
+                Some(path) => return sanitize_path(path),
+                None => continue,
+            }
+        }
+        let path = line.trim_matches(|c| matches!(c, '`' | '*' | ':' | '#' | ' '));
+        return looks_like_path(path).then(|| sanitize_path(path)).flatten();
+    }
+    None
+}
+
+/// Search/replace blocks with the line they start at
+fn extract_replacements(lines: &[&str]) -> Vec<(usize, Edit)> {
+    let mut edits = Vec::new();
+    let mut last_path: Option<PathBuf> = None;
+    let mut idx = 0;
+    while idx < lines.len() {
+        if !SEARCH_RE.is_match(lines[idx]) {
+            idx += 1;
+            continue;
+        }
+        let start = idx;
+        let Some(divider) = (start + 1..lines.len()).find(|&i| DIVIDER_RE.is_match(lines[i]))
+        else {
+            break;
+        };
+        let Some(end) = (divider + 1..lines.len()).find(|&i| REPLACE_RE.is_match(lines[i])) else {
+            break;
+        };
+        // Blocks after the first may leave out the path of the same file
+        let path = path_before(&lines[..start]).or_else(|| last_path.clone());
+        if let Some(path) = path {
+            last_path = Some(path.clone());
+            edits.push((
+                start,
+                Edit {
+                    path,
+                    kind: EditKind::Replace {
+                        search: lines[start + 1..divider].join("\n"),
+                        replace: lines[divider + 1..end].join("\n"),
+                    },
+                },
+            ));
+        }
+        idx = end + 1;
+    }
+    edits
+}
+
+/// Path of a `---`/`+++` header, without `a/`/`b/` and timestamps; `None` for `/dev/null`
+fn header_path(header: &str) -> Option<Option<PathBuf>> {
+    let path = header.split('\t').next().unwrap_or(header).trim();
+    if path == "/dev/null" {
+        return Some(None);
+    }
+    let path = path
+        .strip_prefix("a/")
+        .or_else(|| path.strip_prefix("b/"))
+        .unwrap_or(path);
+    sanitize_path(path).map(Some)
+}
+
+/// The file edits of a unified diff; hunks without file headers are skipped
+pub fn parse_unified_diff(lines: &[&str]) -> Vec<Edit> {
+    let mut edits: Vec<Edit> = Vec::new();
+    let mut current: Option<(Option<PathBuf>, Option<PathBuf>, Vec<Hunk>)> = None;
+
+    let finish = |current: Option<(Option<PathBuf>, Option<PathBuf>, Vec<Hunk>)>,
+                  edits: &mut Vec<Edit>| {
+        match current {
+            Some((_, Some(path), hunks)) if !hunks.is_empty() => edits.push(Edit {
+                path,
+                kind: EditKind::Patch(hunks),
+            }),
+            Some((Some(path), None, _)) => edits.push(Edit {
+                path,
+                kind: EditKind::Delete,
+            }),
+            _ => {}
+        }
+    };
+
+    let mut idx = 0;
+    while idx < lines.len() {
+        let line = lines[idx];
+        if let (Some(old), Some(new)) = (
+            line.strip_prefix("--- "),
+            lines
+                .get(idx + 1)
+                .and_then(|next| next.strip_prefix("+++ ")),
+        ) {
+            finish(current.take(), &mut edits);
+            if let (Some(old), Some(new)) = (header_path(old), header_path(new)) {
+                current = Some((old, new, Vec::new()));
+            }
+            idx += 2;
+            continue;
+        }
+        if let Some(captures) = HUNK_RE.captures(line) {
+            if let Some((_, _, hunks)) = &mut current {
+                hunks.push(Hunk {
+                    old_start: captures[1].parse().unwrap_or(0),
+                    lines: Vec::new(),
+                });
+            }
+            idx += 1;
+            continue;
+        }
+        if let Some(hunk) = current.as_mut().and_then(|(_, _, hunks)| hunks.last_mut()) {
+            match line.chars().next() {
+                // Blank lines are context lines that lost their space
+                None => hunk.lines.push(HunkLine::Context(String::new())),
+                Some(' ') => hunk.lines.push(HunkLine::Context(line[1..].to_string())),
+                Some('-') => hunk.lines.push(HunkLine::Remove(line[1..].to_string())),
+                Some('+') => hunk.lines.push(HunkLine::Add(line[1..].to_string())),
+                _ => {}
+            }
+        }
+        idx += 1;
+    }
+    finish(current, &mut edits);
+    edits
+}
+
+/// Positions where `needle` occurs in `haystack` as whole lines
+fn find_lines(haystack: &[String], needle: &[&str], loose: bool) -> Vec<usize> {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return Vec::new();
+    }
+    let same = |a: &str, b: &str| {
+        if loose {
+            a.trim_end() == b.trim_end()
+        } else {
+            a == b
+        }
+    };
+    (0..=haystack.len() - needle.len())
+        .filter(|&start| {
+            needle
+                .iter()
+                .zip(&haystack[start..])
+                .all(|(expected, actual)| same(expected, actual))
+        })
+        .collect()
+}
+
+/// Apply the hunks in order, each near its stated line or else wherever it fits
+fn apply_hunks(content: &str, hunks: &[Hunk]) -> Result<String, String> {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    // Lines added minus lines removed by the hunks applied so far
+    let mut offset: isize = 0;
+    for (number, hunk) in hunks.iter().enumerate() {
+        let old = hunk.old_lines();
+        let new: Vec<String> = hunk.new_lines().into_iter().map(str::to_string).collect();
+        let expected = (hunk.old_start.saturating_sub(1) as isize + offset).max(0) as usize;
+        let start = if old.is_empty() {
+            // Pure additions go where the header says
+            expected.min(lines.len())
+        } else {
+            let mut found = find_lines(&lines, &old, false);
+            if found.is_empty() {
+                found = find_lines(&lines, &old, true);
+            }
+            *found
+                .iter()
+                .min_by_key(|&&start| start.abs_diff(expected))
+                .ok_or_else(|| {
+                    format!(
+                        "hunk {} (at line {}) does not match the file",
+                        number + 1,
+                        hunk.old_start
+                    )
+                })?
+        };
+        lines.splice(start..start + old.len(), new.iter().cloned());
+        offset += new.len() as isize - old.len() as isize;
+    }
+    Ok(with_final_newline(lines.join("\n"), content))
+}
+
+/// Keep the final newline of the original, adding one to new files
+fn with_final_newline(mut text: String, original: &str) -> String {
+    if (original.is_empty() || original.ends_with('\n')) && !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+/// Replace the first occurrence of `search`, or of its lines ignoring trailing spaces
+fn replace_first(content: &str, search: &str, replace: &str) -> Result<String, String> {
+    if let Some(start) = content.find(search) {
+        let mut result = content.to_string();
+        result.replace_range(start..start + search.len(), replace);
+        return Ok(result);
+    }
+    let lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let needle: Vec<&str> = search.lines().collect();
+    let Some(&start) = find_lines(&lines, &needle, true).first() else {
+        return Err("the SEARCH text is not in the file".to_string());
+    };
+    let mut result = lines;
+    result.splice(
+        start..start + needle.len(),
+        replace.lines().map(str::to_string),
+    );
+    Ok(with_final_newline(result.join("\n"), content))
+}
+
+/// The content of a file after an edit; `None` content means the file does not exist
+pub fn apply_edit(content: Option<&str>, kind: &EditKind) -> Result<String, String> {
+    match (kind, content) {
+        (EditKind::Delete, _) => Err("deleting files is not supported".to_string()),
+        (EditKind::Replace { search, replace }, None) if search.trim().is_empty() => {
+            Ok(with_final_newline(replace.clone(), ""))
+        }
+        (EditKind::Replace { search, .. }, Some(_)) if search.trim().is_empty() => {
+            Err("the SEARCH part is empty but the file exists".to_string())
+        }
+        (EditKind::Replace { .. }, None) => Err("the file does not exist".to_string()),
+        (EditKind::Replace { search, replace }, Some(content)) => {
+            replace_first(content, search, replace)
+        }
+        (EditKind::Patch(hunks), content) => apply_hunks(content.unwrap_or_default(), hunks),
+    }
+}
+
+/// Apply the edits to the files under `root`, one block per changed file
+///
+/// Several edits of the same file apply in order. A file with an edit that
+/// does not apply, or outside the workspace, is left out entirely and the
+/// reason is returned instead.
+pub fn resolve(root: &Path, edits: &[Edit]) -> (Vec<FileBlock>, Vec<String>) {
+    let mut files: Vec<(PathBuf, Result<String, String>)> = Vec::new();
+    for edit in edits {
+        let index = match files.iter().position(|(path, _)| *path == edit.path) {
+            Some(index) => index,
+            None => {
+                let original = workspace::check(root, &edit.path)
+                    .map(|path| fs::read_to_string(path).unwrap_or_default())
+                    .map_err(|e| e.to_string());
+                files.push((edit.path.clone(), original));
+                files.len() - 1
+            }
+        };
+        let exists = root.join(&edit.path).is_file();
+        let (_, state) = &mut files[index];
+        if let Ok(content) = state {
+            // A file created by an earlier edit of this response exists from then on
+            let current = (exists || !content.is_empty()).then_some(content.as_str());
+            *state = apply_edit(current, &edit.kind);
+        }
+    }
+
+    let mut blocks = Vec::new();
+    let mut problems = Vec::new();
+    for (path, state) in files {
+        let display = path.to_string_lossy().replace('\\', "/");
+        match state {
+            Ok(content) => blocks.push(FileBlock {
+                path,
+                language: None,
+                content,
+            }),
+            Err(reason) => problems.push(format!("{}: {}", display, reason)),
+        }
+    }
+    (blocks, problems)
+}
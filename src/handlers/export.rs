@@ -0,0 +1,370 @@
+//! Export handler
+//!
+//! This module renders a session transcript as a standalone HTML page using
+//! the current theme palette. Code blocks are syntax highlighted, every
+//! command is a collapsible section, and a header lists the models used and
+//! the session cost so the file can be shared as is.
+
+use crate::config::ThemeConfig;
+use crate::handlers::CommandMode;
+use crate::utils::transcript::{Exchange, Transcript};
+use chrono::{DateTime, Local};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Outputs of bash and app commands longer than this start collapsed
+const COLLAPSE_AFTER_LINES: usize = 20;
+
+/// Keywords highlighted in code blocks, across the common languages
+const KEYWORDS: &[&str] = &[
+    "and",
+    "as",
+    "async",
+    "await",
+    "break",
+    "case",
+    "class",
+    "const",
+    "continue",
+    "crate",
+    "def",
+    "defer",
+    "do",
+    "done",
+    "dyn",
+    "elif",
+    "else",
+    "enum",
+    "except",
+    "export",
+    "extern",
+    "false",
+    "fi",
+    "finally",
+    "fn",
+    "for",
+    "from",
+    "func",
+    "function",
+    "go",
+    "if",
+    "impl",
+    "import",
+    "in",
+    "interface",
+    "is",
+    "lambda",
+    "let",
+    "loop",
+    "match",
+    "mod",
+    "move",
+    "mut",
+    "new",
+    "nil",
+    "None",
+    "not",
+    "null",
+    "or",
+    "package",
+    "pub",
+    "raise",
+    "ref",
+    "return",
+    "select",
+    "self",
+    "Self",
+    "static",
+    "struct",
+    "super",
+    "switch",
+    "then",
+    "this",
+    "trait",
+    "true",
+    "True",
+    "False",
+    "try",
+    "type",
+    "unsafe",
+    "use",
+    "var",
+    "where",
+    "while",
+    "with",
+    "yield",
+];
+
+/// Tokens of code using `//` comments
+static SLASH_TOKEN_RE: Lazy<Regex> = Lazy::new(|| token_regex("//[^\n]*|/\\*[\\s\\S]*?\\*/"));
+/// Tokens of code using `#` comments
+static HASH_TOKEN_RE: Lazy<Regex> = Lazy::new(|| token_regex("#[^\n]*"));
+/// Tokens of code using `--` comments
+static DASH_TOKEN_RE: Lazy<Regex> = Lazy::new(|| token_regex("--[^\n]*"));
+
+/// Build a tokenizer with the given comment syntax
+fn token_regex(comment: &str) -> Regex {
+    Regex::new(&format!(
+        r#"(?P<comment>{})|(?P<string>"(?:[^"\\\n]|\\.)*"|'(?:[^'\\\n]|\\.)*')|(?P<number>\b\d[\d_]*(?:\.\d+)?\b)|(?P<word>[A-Za-z_]\w*)"#,
+        comment
+    ))
+    .unwrap()
+}
+
+/// Session details shown above the transcript
+#[derive(Debug, Clone)]
+pub struct ExportMeta {
+    /// When the session started
+    pub started: DateTime<Local>,
+    /// When the export was made
+    pub exported: DateTime<Local>,
+    /// Working directory of the session
+    pub directory: String,
+    /// Provider configured at export time
+    pub provider: String,
+    /// Models that produced responses
+    pub models: Vec<String>,
+    /// Prompt tokens used
+    pub prompt_tokens: usize,
+    /// Completion tokens used
+    pub completion_tokens: usize,
+    /// Estimated session cost in dollars
+    pub cost: f64,
+    /// When each exchange was entered, if known
+    pub times: Vec<DateTime<Local>>,
+}
+
+/// Escape text for use in HTML
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Highlight a code block as HTML
+pub fn highlight_code(language: &str, code: &str) -> String {
+    let tokens: &Regex = match language.to_lowercase().as_str() {
+        "python" | "py" | "sh" | "bash" | "zsh" | "shell" | "ruby" | "rb" | "yaml" | "yml"
+        | "toml" | "perl" | "r" | "dockerfile" | "make" | "makefile" => &HASH_TOKEN_RE,
+        "sql" | "lua" | "haskell" | "hs" => &DASH_TOKEN_RE,
+        _ => &SLASH_TOKEN_RE,
+    };
+
+    let mut html = String::with_capacity(code.len() * 2);
+    let mut last = 0;
+    for caps in tokens.captures_iter(code) {
+        let token = caps.get(0).unwrap();
+        html.push_str(&escape_html(&code[last..token.start()]));
+        let class = if caps.name("comment").is_some() {
+            Some("cm")
+        } else if caps.name("string").is_some() {
+            Some("st")
+        } else if caps.name("number").is_some() {
+            Some("nu")
+        } else if KEYWORDS.contains(&token.as_str()) {
+            Some("kw")
+        } else {
+            None
+        };
+        match class {
+            Some(class) => html.push_str(&format!(
+                "<span class=\"{}\">{}</span>",
+                class,
+                escape_html(token.as_str())
+            )),
+            None => html.push_str(&escape_html(token.as_str())),
+        }
+        last = token.end();
+    }
+    html.push_str(&escape_html(&code[last..]));
+    html
+}
+
+/// Render output text, highlighting fenced code blocks
+fn render_output(output: &str) -> String {
+    let mut html = String::new();
+    let mut text: Vec<&str> = Vec::new();
+    let mut code: Option<(String, Vec<&str>)> = None;
+
+    let flush_text = |html: &mut String, text: &mut Vec<&str>| {
+        if !text.is_empty() {
+            html.push_str(&format!(
+                "<div class=\"text\">{}</div>\n",
+                escape_html(&text.join("\n"))
+            ));
+            text.clear();
+        }
+    };
+
+    let render_code = |html: &mut String, language: &str, lines: &[&str]| {
+        html.push_str(&format!(
+            "<pre class=\"code\"><span class=\"lang\">{}</span><code>{}</code></pre>\n",
+            escape_html(language),
+            highlight_code(language, &lines.join("\n"))
+        ));
+    };
+
+    for line in output.lines() {
+        let fence = line.trim_start().strip_prefix("```");
+        match (&mut code, fence) {
+            (None, Some(info)) => {
+                flush_text(&mut html, &mut text);
+                let language = info.split_whitespace().next().unwrap_or("").to_string();
+                code = Some((language, Vec::new()));
+            }
+            (Some((language, lines)), Some(_)) => {
+                render_code(&mut html, language, lines);
+                code = None;
+            }
+            (Some((_, lines)), None) => lines.push(line),
+            (None, None) => text.push(line),
+        }
+    }
+
+    // A block cut off before its closing fence is still shown as code
+    if let Some((language, lines)) = code {
+        render_code(&mut html, &language, &lines);
+    }
+    flush_text(&mut html, &mut text);
+    html
+}
+
+/// Render one exchange as a collapsible section
+fn render_exchange(exchange: &Exchange, time: Option<&DateTime<Local>>) -> String {
+    let class = match exchange.mode {
+        CommandMode::AI => "ai",
+        CommandMode::Bash => "bash",
+        CommandMode::Command => "cmd",
+    };
+    let collapsed =
+        exchange.mode != CommandMode::AI && exchange.output.lines().count() > COLLAPSE_AFTER_LINES;
+
+    let time = time
+        .map(|time| {
+            format!(
+                "<span class=\"time\" title=\"{}\">{}</span> ",
+                time.format("%Y-%m-%d %H:%M:%S"),
+                time.format("%H:%M:%S")
+            )
+        })
+        .unwrap_or_default();
+
+    format!(
+        "<details class=\"exchange {}\"{}>\n<summary>{}{} <span class=\"lines\">{} lines</span></summary>\n<div class=\"output\">\n{}</div>\n</details>\n",
+        class,
+        if collapsed { "" } else { " open" },
+        time,
+        escape_html(&exchange.display_input()),
+        exchange.output.lines().count(),
+        render_output(&exchange.output)
+    )
+}
+
+/// Use a theme color if it is a hex color, or the fallback otherwise
+fn css_color(value: &str, fallback: &str) -> String {
+    let hex = value.trim_start_matches('#');
+    if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        format!("#{}", hex)
+    } else {
+        fallback.to_string()
+    }
+}
+
+/// Render the whole session as a standalone HTML page
+pub fn render_html(transcript: &Transcript, meta: &ExportMeta, theme: &ThemeConfig) -> String {
+    let primary = css_color(&theme.primary, "#0087af");
+    let secondary = css_color(&theme.secondary, "#00af87");
+    let accent = css_color(&theme.accent, "#af8700");
+    let background = css_color(&theme.background, "#1e1e1e");
+    let foreground = css_color(&theme.foreground, "#d4d4d4");
+
+    let models = if meta.models.is_empty() {
+        "none".to_string()
+    } else {
+        meta.models.join(", ")
+    };
+    let count = |mode: CommandMode| {
+        transcript
+            .exchanges
+            .iter()
+            .filter(|exchange| exchange.mode == mode)
+            .count()
+    };
+
+    let mut body = String::new();
+    if !transcript.preamble.is_empty() {
+        body.push_str(&format!(
+            "<div class=\"preamble\">{}</div>\n",
+            escape_html(&transcript.preamble)
+        ));
+    }
+    for (idx, exchange) in transcript.exchanges.iter().enumerate() {
+        body.push_str(&render_exchange(exchange, meta.times.get(idx)));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>AI Coder session {started}</title>
+<style>
+:root {{ --primary: {primary}; --secondary: {secondary}; --accent: {accent}; --bg: {background}; --fg: {foreground}; }}
+body {{ background: var(--bg); color: var(--fg); font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; font-size: 14px; margin: 0 auto; max-width: 1100px; padding: 24px; }}
+h1 {{ color: var(--primary); font-size: 20px; }}
+table.meta {{ border-collapse: collapse; margin-bottom: 24px; }}
+table.meta td {{ padding: 2px 16px 2px 0; vertical-align: top; }}
+table.meta td:first-child {{ color: var(--accent); }}
+details.exchange {{ border-left: 3px solid var(--primary); margin: 12px 0; padding-left: 12px; }}
+details.bash {{ border-left-color: var(--secondary); }}
+details.cmd {{ border-left-color: var(--accent); }}
+summary {{ cursor: pointer; font-weight: bold; white-space: pre-wrap; }}
+summary .time {{ font-weight: normal; opacity: 0.6; }}
+summary .lines {{ font-weight: normal; opacity: 0.6; font-size: 12px; }}
+.text, .preamble {{ white-space: pre-wrap; margin: 8px 0; }}
+pre.code {{ background: rgba(127, 127, 127, 0.12); border-radius: 4px; overflow-x: auto; padding: 10px; position: relative; }}
+pre.code .lang {{ color: var(--accent); font-size: 11px; position: absolute; right: 8px; top: 4px; }}
+.kw {{ color: var(--primary); font-weight: bold; }}
+.st {{ color: var(--secondary); }}
+.nu {{ color: var(--accent); }}
+.cm {{ opacity: 0.6; font-style: italic; }}
+</style>
+</head>
+<body>
+<h1>AI Coder session</h1>
+<table class="meta">
+<tr><td>Started</td><td>{started}</td></tr>
+<tr><td>Exported</td><td>{exported}</td></tr>
+<tr><td>Directory</td><td>{directory}</td></tr>
+<tr><td>Provider</td><td>{provider}</td></tr>
+<tr><td>Models used</td><td>{models}</td></tr>
+<tr><td>Exchanges</td><td>{ai} prompts, {bash} bash commands, {cmd} commands</td></tr>
+<tr><td>Tokens</td><td>{prompt_tokens} prompt, {completion_tokens} completion</td></tr>
+<tr><td>Cost</td><td>${cost:.4}</td></tr>
+</table>
+{body}</body>
+</html>
+"#,
+        started = meta.started.format("%Y-%m-%d %H:%M:%S"),
+        exported = meta.exported.format("%Y-%m-%d %H:%M:%S"),
+        directory = escape_html(&meta.directory),
+        provider = escape_html(&meta.provider),
+        models = escape_html(&models),
+        ai = count(CommandMode::AI),
+        bash = count(CommandMode::Bash),
+        cmd = count(CommandMode::Command),
+        prompt_tokens = meta.prompt_tokens,
+        completion_tokens = meta.completion_tokens,
+        cost = meta.cost,
+    )
+}
@@ -0,0 +1,293 @@
+//! Web page fetching
+//!
+//! This module downloads pages for `/fetch <url>` and for ```` ```fetch ````
+//! blocks in chat responses, which is how the model asks for a page. HTML is
+//! reduced to its readable text: scripts, styles, navigation, headers,
+//! footers and forms are dropped, the `<article>` or `<main>` element is
+//! preferred over the whole body, and headings and list items keep a
+//! Markdown-like shape. The text is cut to a token budget before it is
+//! attached. Unlike `curl` and `wget` in bash blocks, nothing is written to
+//! disk or run.
+
+use crate::config::FetchConfig;
+use crate::handlers::{HandlerError, HandlerResult};
+use crate::utils::count_tokens;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::time::Duration;
+
+/// Stands for a blank line while tags are replaced
+const PARAGRAPH_BREAK: &str = "\n\u{1}\n";
+
+/// Elements dropped with everything inside them
+const BOILERPLATE_TAGS: &[&str] = &[
+    "script", "style", "noscript", "template", "svg", "iframe", "nav", "header", "footer", "aside",
+    "form", "button", "select",
+];
+
+static TITLE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?is)<title[^>]*>(.*?)</title\s*>").unwrap());
+static COMMENT_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<!--.*?-->").unwrap());
+static BOILERPLATE_RES: Lazy<Vec<Regex>> = Lazy::new(|| {
+    BOILERPLATE_TAGS
+        .iter()
+        .map(|tag| Regex::new(&format!(r"(?is)<{0}\b[^>]*>.*?</{0}\s*>", tag)).unwrap())
+        .collect()
+});
+static ARTICLE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?is)<article\b[^>]*>(.*)</article\s*>").unwrap());
+static MAIN_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?is)<main\b[^>]*>(.*)</main\s*>").unwrap());
+static BODY_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?is)<body\b[^>]*>(.*)</body\s*>").unwrap());
+static HEADING_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<h([1-6])\b[^>]*>").unwrap());
+static LIST_ITEM_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)<li\b[^>]*>").unwrap());
+static PARAGRAPH_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)</?(?:p|h[1-6]|ul|ol|table|section|blockquote|pre)\b[^>]*>").unwrap()
+});
+static LINE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)</?(?:br|hr|div|li|tr|dt|dd)\b[^>]*/?>").unwrap());
+static PRE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<pre\b[^>]*>.*?</pre\s*>").unwrap());
+static WHITESPACE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").unwrap());
+static TAG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").unwrap());
+static ENTITY_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap());
+static SPACES_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[ \t\u{a0}]+").unwrap());
+static FETCH_BLOCK_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"```fetch\n([\s\S]*?)\n?```").unwrap());
+
+/// A fetched page, reduced to text
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    pub url: String,
+    /// Contents of `<title>`, if any
+    pub title: Option<String>,
+    pub text: String,
+    /// Whether the text was cut to the token budget
+    pub truncated: bool,
+}
+
+impl Page {
+    /// Name of the attachment: the title and the address
+    pub fn name(&self) -> String {
+        match &self.title {
+            Some(title) => format!("{} ({})", title, self.url),
+            None => self.url.clone(),
+        }
+    }
+}
+
+/// Parse the argument of `/fetch`, accepting only http and https addresses
+pub fn parse_url(arg: &str) -> HandlerResult<reqwest::Url> {
+    let arg = arg.trim();
+    if arg.is_empty() {
+        return Err(HandlerError::Parse("Usage: /fetch <url>".to_string()));
+    }
+    let url = if arg.contains("://") {
+        arg.to_string()
+    } else {
+        format!("https://{}", arg)
+    };
+    let url = reqwest::Url::parse(&url)
+        .map_err(|e| HandlerError::Parse(format!("Invalid URL {}: {}", arg, e)))?;
+    match url.scheme() {
+        "http" | "https" => Ok(url),
+        scheme => Err(HandlerError::Parse(format!(
+            "Only http and https pages can be fetched, not {}",
+            scheme
+        ))),
+    }
+}
+
+/// Addresses the model asked for in ```` ```fetch ```` blocks, one per line
+pub fn extract_fetch_requests(text: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for cap in FETCH_BLOCK_RE.captures_iter(text) {
+        for line in cap[1].lines().map(str::trim) {
+            if !line.is_empty() && !urls.iter().any(|url| url == line) {
+                urls.push(line.to_string());
+            }
+        }
+    }
+    urls
+}
+
+/// Download a page and reduce it to text within the configured limits
+pub async fn fetch(url: &reqwest::Url, config: &FetchConfig) -> HandlerResult<Page> {
+    let client = reqwest::Client::builder()
+        .user_agent(concat!("ai-coder/", env!("CARGO_PKG_VERSION")))
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .build()
+        .map_err(|e| HandlerError::Other(format!("Could not create HTTP client: {}", e)))?;
+    let mut response = client
+        .get(url.clone())
+        .header("Accept", "text/html, text/plain;q=0.9, */*;q=0.5")
+        .send()
+        .await
+        .map_err(|e| HandlerError::Other(format!("Could not reach {}: {}", url, e)))?;
+    if !response.status().is_success() {
+        return Err(HandlerError::Other(format!(
+            "{} returned {}",
+            url,
+            response.status()
+        )));
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("text/html")
+        .to_lowercase();
+    let is_html = content_type.contains("html");
+    let is_text = is_html
+        || content_type.starts_with("text/")
+        || content_type.contains("json")
+        || content_type.contains("xml");
+    if !is_text {
+        return Err(HandlerError::Other(format!(
+            "{} is {}, not a text page",
+            url, content_type
+        )));
+    }
+
+    // Read at most `max_bytes`, ignoring the rest of very large pages
+    let mut body = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| HandlerError::Other(format!("Could not download {}: {}", url, e)))?
+    {
+        body.extend_from_slice(&chunk);
+        if body.len() >= config.max_bytes {
+            body.truncate(config.max_bytes);
+            break;
+        }
+    }
+    let body = String::from_utf8_lossy(&body);
+
+    let (title, text) = if is_html {
+        (page_title(&body), readable_text(&body))
+    } else {
+        (None, body.trim().to_string())
+    };
+    let (text, truncated) = truncate_tokens(&text, config.token_budget);
+    Ok(Page {
+        url: url.to_string(),
+        title,
+        text,
+        truncated,
+    })
+}
+
+/// Contents of the `<title>` element
+pub fn page_title(html: &str) -> Option<String> {
+    let title = TITLE_RE.captures(html)?;
+    let title = collapse_spaces(&decode_entities(&title[1]));
+    (!title.is_empty()).then_some(title)
+}
+
+/// The readable text of an HTML page
+pub fn readable_text(html: &str) -> String {
+    let mut html = COMMENT_RE.replace_all(html, "").into_owned();
+    for re in BOILERPLATE_RES.iter() {
+        html = re.replace_all(&html, "").into_owned();
+    }
+
+    // The article or main content, falling back to the body
+    let content = [&*ARTICLE_RE, &*MAIN_RE, &*BODY_RE]
+        .iter()
+        .find_map(|re| re.captures(&html).map(|cap| cap[1].to_string()))
+        .unwrap_or(html);
+
+    // Line breaks in the source only matter inside <pre>
+    let mut flowed = String::with_capacity(content.len());
+    let mut last = 0;
+    for pre in PRE_RE.find_iter(&content) {
+        flowed.push_str(&WHITESPACE_RE.replace_all(&content[last..pre.start()], " "));
+        flowed.push_str(&pre.as_str().replace('\n', "<br>"));
+        last = pre.end();
+    }
+    flowed.push_str(&WHITESPACE_RE.replace_all(&content[last..], " "));
+
+    let text = HEADING_RE.replace_all(&flowed, |cap: &regex::Captures| {
+        let level: usize = cap[1].parse().unwrap_or(1);
+        format!("{}{} ", PARAGRAPH_BREAK, "#".repeat(level))
+    });
+    let text = LIST_ITEM_RE.replace_all(&text, "\n- ");
+    let text = PARAGRAPH_RE.replace_all(&text, PARAGRAPH_BREAK);
+    let text = LINE_RE.replace_all(&text, "\n");
+    let text = TAG_RE.replace_all(&text, "");
+    let text = decode_entities(&text);
+
+    // One space between words, at most one blank line between blocks
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines().map(collapse_spaces) {
+        if line == "\u{1}" {
+            if lines.last().is_some_and(|last| !last.is_empty()) {
+                lines.push(String::new());
+            }
+        } else if !line.is_empty() && line != "-" {
+            lines.push(line);
+        }
+    }
+    lines.join("\n").trim().to_string()
+}
+
+/// Cut text at a line boundary to about `budget` tokens
+///
+/// Returns the text and whether anything was cut.
+pub fn truncate_tokens(text: &str, budget: usize) -> (String, bool) {
+    if count_tokens(text) <= budget {
+        return (text.to_string(), false);
+    }
+    let mut kept = String::new();
+    let mut tokens = 0;
+    for line in text.lines() {
+        let line_tokens = count_tokens(line);
+        if tokens + line_tokens > budget {
+            break;
+        }
+        tokens += line_tokens;
+        kept.push_str(line);
+        kept.push('\n');
+    }
+    kept.push_str(&format!("[… cut to ~{} tokens]", budget));
+    (kept, true)
+}
+
+fn collapse_spaces(text: &str) -> String {
+    SPACES_RE.replace_all(text, " ").trim().to_string()
+}
+
+/// Replace character references and the common named entities
+fn decode_entities(text: &str) -> String {
+    ENTITY_RE
+        .replace_all(text, |cap: &regex::Captures| {
+            let entity = &cap[1];
+            let decoded = if let Some(hex) = entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+            {
+                u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+            } else if let Some(decimal) = entity.strip_prefix('#') {
+                decimal.parse().ok().and_then(char::from_u32)
+            } else {
+                match entity {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some(' '),
+                    "mdash" => Some('—'),
+                    "ndash" => Some('–'),
+                    "hellip" => Some('…'),
+                    "copy" => Some('©'),
+                    _ => None,
+                }
+            };
+            decoded.map_or_else(|| cap[0].to_string(), String::from)
+        })
+        .into_owned()
+}
@@ -0,0 +1,266 @@
+//! File block handler
+//!
+//! This module finds code blocks in AI responses that name a target file,
+//! either in the fence info string (```` ```rust src/lib.rs ````) or in a
+//! leading path comment (`// src/lib.rs`), and writes them into the project.
+
+use crate::handlers::{HandlerError, HandlerResult, edits, safe, workspace};
+use crate::utils::diff::{diff_lines, diff_stats, unified_diff};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Fenced code blocks with their full info string
+static FENCE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"```([^\n`]*)\n([\s\S]*?)\n?```").unwrap());
+
+/// A first line consisting only of a comment naming a file
+static PATH_COMMENT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)^\s*(?://|#|--|;|/\*|<!--)\s*(?:(?:file(?:name)?|path)\s*:\s*)?([\w./-]+\.\w+)\s*(?:\*/|-->)?\s*$",
+    )
+    .unwrap()
+});
+
+/// A code block that names the file it belongs to
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileBlock {
+    /// Path relative to the working directory
+    pub path: PathBuf,
+    /// Language from the fence info string, if any
+    pub language: Option<String>,
+    /// File contents, without the path annotation
+    pub content: String,
+}
+
+impl FileBlock {
+    /// Path as shown to the user
+    pub fn display_path(&self) -> String {
+        self.path.to_string_lossy().replace('\\', "/")
+    }
+
+    /// Unified diff between the file on disk (under `root`) and this block
+    pub fn preview(&self, root: &Path) -> String {
+        let current = fs::read_to_string(root.join(&self.path)).unwrap_or_default();
+        let diff = unified_diff(&self.display_path(), &current, &self.content);
+        if diff.is_empty() {
+            format!("{} is unchanged\n", self.display_path())
+        } else {
+            diff
+        }
+    }
+}
+
+/// Write several blocks as one change: either all files are updated or none
+///
+/// Every block is first written to a temporary file next to its target;
+/// the temporaries are only renamed into place once all writes succeeded.
+/// If a rename fails, files already replaced are restored byte for byte and
+/// files that did not exist are removed again.
+pub fn write_all(root: &Path, blocks: &[FileBlock]) -> HandlerResult<Vec<String>> {
+    safe::check("Writing files")?;
+    // Temporary file, target and the target's previous bytes if it existed
+    let mut staged: Vec<(PathBuf, PathBuf, Option<Vec<u8>>)> = Vec::new();
+
+    let cleanup = |staged: &[(PathBuf, PathBuf, Option<Vec<u8>>)]| {
+        for (tmp, _, _) in staged {
+            let _ = fs::remove_file(tmp);
+        }
+    };
+
+    for block in blocks {
+        if let Err(e) = workspace::check(root, &block.path) {
+            cleanup(&staged);
+            return Err(HandlerError::Other(format!("{}; no files were changed", e)));
+        }
+        let target = root.join(&block.path);
+        let original = if target.exists() {
+            match fs::read(&target) {
+                Ok(bytes) => Some(bytes),
+                Err(e) => {
+                    cleanup(&staged);
+                    return Err(HandlerError::Other(format!(
+                        "Failed to read {}: {}; no files were changed",
+                        block.display_path(),
+                        e
+                    )));
+                }
+            }
+        } else {
+            None
+        };
+        let file_name = target
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let tmp = target.with_file_name(format!(".{}.ai-coder.tmp", file_name));
+
+        let mut content = block.content.clone();
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        let result = target
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&tmp, &content));
+        if let Err(e) = result {
+            cleanup(&staged);
+            return Err(HandlerError::Other(format!(
+                "Failed to stage {}: {}; no files were changed",
+                block.display_path(),
+                e
+            )));
+        }
+        staged.push((tmp, target, original));
+    }
+
+    for (idx, (tmp, target, _)) in staged.iter().enumerate() {
+        if let Err(e) = fs::rename(tmp, target) {
+            // Roll back the files already replaced
+            for (_, done_target, original) in &staged[..idx] {
+                match original {
+                    Some(content) => {
+                        let _ = fs::write(done_target, content);
+                    }
+                    None => {
+                        let _ = fs::remove_file(done_target);
+                    }
+                }
+            }
+            cleanup(&staged[idx..]);
+            return Err(HandlerError::Other(format!(
+                "Failed to replace {}: {}; changes were rolled back",
+                target.display(),
+                e
+            )));
+        }
+    }
+
+    Ok(blocks
+        .iter()
+        .zip(&staged)
+        .map(|(block, (_, _, original))| {
+            let old = original
+                .as_deref()
+                .map(String::from_utf8_lossy)
+                .unwrap_or_default();
+            let (added, removed) = diff_stats(&diff_lines(&old, &block.content));
+            format!(
+                "✅ {} {} (+{} -{})",
+                if original.is_some() {
+                    "Updated"
+                } else {
+                    "Created"
+                },
+                block.display_path(),
+                added,
+                removed
+            )
+        })
+        .collect())
+}
+
+/// Extract all code blocks in `text` that are annotated with a file path
+pub fn extract_file_blocks(text: &str) -> Vec<FileBlock> {
+    let mut blocks: Vec<FileBlock> = Vec::new();
+
+    for cap in FENCE_RE.captures_iter(text) {
+        let info = cap.get(1).map_or("", |m| m.as_str()).trim();
+        let body = cap.get(2).map_or("", |m| m.as_str());
+        let (language, info_path) = parse_info_string(info);
+        // Diffs and search/replace blocks are edits, not file contents
+        if matches!(language.as_deref(), Some("diff" | "patch")) || edits::has_edit_markers(body) {
+            continue;
+        }
+
+        let block = if let Some(path) = info_path {
+            Some(FileBlock {
+                path,
+                language,
+                content: body.to_string(),
+            })
+        } else {
+            // Fall back to a path comment on the first line of the block
+            let (first, rest) = body.split_once('\n').unwrap_or((body, ""));
+            PATH_COMMENT_RE
+                .captures(first)
+                .and_then(|c| sanitize_path(&c[1]))
+                .map(|path| FileBlock {
+                    path,
+                    language,
+                    content: rest.to_string(),
+                })
+        };
+
+        if let Some(block) = block {
+            // A later block for the same file supersedes an earlier one
+            blocks.retain(|b| b.path != block.path);
+            blocks.push(block);
+        }
+    }
+
+    blocks
+}
+
+/// Split a fence info string into its language and an optional file path
+///
+/// Accepts `rust src/lib.rs`, `src/lib.rs`, `rust:src/lib.rs` and
+/// `rust title="src/lib.rs"` (also `file=` and `path=`).
+fn parse_info_string(info: &str) -> (Option<String>, Option<PathBuf>) {
+    let mut language = None;
+    let mut path = None;
+
+    for (idx, token) in info.split_whitespace().enumerate() {
+        let token = match token.split_once('=') {
+            Some(("title" | "file" | "filename" | "path", value)) => {
+                value.trim_matches(|c| c == '"' || c == '\'')
+            }
+            _ => token,
+        };
+
+        let (lang_part, path_part) = match token.split_once(':') {
+            Some((lang, rest)) if idx == 0 && !lang.contains(['/', '.']) => (Some(lang), rest),
+            _ => (None, token),
+        };
+
+        if path.is_none() && looks_like_path(path_part) {
+            path = sanitize_path(path_part);
+            if let Some(lang) = lang_part {
+                language = Some(lang.to_lowercase());
+            }
+        } else if idx == 0 {
+            language = Some(token.to_lowercase());
+        }
+    }
+
+    (language, path)
+}
+
+/// Whether a token is a plausible relative file path rather than a language tag
+pub(crate) fn looks_like_path(token: &str) -> bool {
+    if token.is_empty() || token.contains("://") {
+        return false;
+    }
+    let valid_chars = token
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/'));
+    let file_name = token.rsplit('/').next().unwrap_or(token);
+    valid_chars && (token.contains('/') || file_name.contains('.')) && !file_name.is_empty()
+}
+
+/// Normalize a path and reject ones that would escape the working directory
+pub(crate) fn sanitize_path(raw: &str) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    for component in Path::new(raw).components() {
+        match component {
+            Component::Normal(part) => path.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    if path.as_os_str().is_empty() {
+        None
+    } else {
+        Some(path)
+    }
+}
@@ -0,0 +1,82 @@
+//! Git handler
+//!
+//! This module reads the staged changes of the repository in the working
+//! directory, builds the prompt for commit message generation and runs
+//! `git commit` with the generated message.
+
+use crate::handlers::{HandlerError, HandlerResult, safe};
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+
+/// Largest part of the staged diff sent to the model
+const MAX_DIFF_CHARS: usize = 40_000;
+
+/// Run a git command in `root` and return its stdout
+fn git(root: &Path, args: &[&str]) -> HandlerResult<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .map_err(|e| HandlerError::Other(format!("Failed to run git: {}", e)))?;
+
+    if !output.status.success() {
+        return Err(HandlerError::Other(format!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Build the commit message prompt from the staged changes
+pub fn commit_message_prompt(root: &Path) -> HandlerResult<String> {
+    let stat = git(root, &["diff", "--staged", "--stat"])?;
+    if stat.trim().is_empty() {
+        return Err(HandlerError::Other(
+            "No staged changes. Stage files with !git add first.".to_string(),
+        ));
+    }
+
+    let mut diff = git(root, &["diff", "--staged", "--no-color"])?;
+    if diff.len() > MAX_DIFF_CHARS {
+        let mut cut = MAX_DIFF_CHARS;
+        while !diff.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        diff.truncate(cut);
+        diff.push_str("\n[diff truncated]\n");
+    }
+
+    Ok(format!(
+        "Write a git commit message for the staged changes below using the Conventional \
+         Commits format: `<type>(<optional scope>): <summary>` where type is one of feat, \
+         fix, docs, style, refactor, perf, test, build, ci or chore. Keep the summary under \
+         72 characters in the imperative mood. If the change needs explanation, add a blank \
+         line and a short body wrapped at 72 characters. Reply with the message only, \
+         without code fences or commentary.\n\nFiles changed:\n{}\nDiff:\n{}",
+        stat, diff
+    ))
+}
+
+/// Strip code fences and surrounding chatter from a generated commit message
+pub fn clean_commit_message(response: &str) -> String {
+    let lines: Vec<&str> = response
+        .trim()
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("```"))
+        .collect();
+    lines.join("\n").trim().to_string()
+}
+
+/// Run `git commit -e -m <message>`, letting the user finish it in their editor
+///
+/// The caller must hand over the terminal before calling this.
+pub fn commit_with_editor(root: &Path, message: &str) -> HandlerResult<ExitStatus> {
+    safe::check("Committing")?;
+    Command::new("git")
+        .args(["commit", "-e", "-m", message])
+        .current_dir(root)
+        .status()
+        .map_err(|e| HandlerError::Other(format!("Failed to run git commit: {}", e)))
+}
@@ -0,0 +1,158 @@
+//! Guardrails on outgoing prompts
+//!
+//! Before a prompt is sent, the checks in the `guardrails` config look for
+//! content that must not leave the machine: API keys, tokens and private keys
+//! by default, plus organization rules given as regexes (internal hostnames,
+//! customer id formats) or as commands that read the prompt on stdin and exit
+//! non-zero to object, such as a DLP scanner. A `block` finding stops the
+//! prompt; a `warn` finding shows what was found and asks before sending.
+//!
+//! A rule whose regex does not compile or whose command cannot run counts as
+//! a finding, so a broken check never lets a prompt through unnoticed.
+
+use crate::config::{GuardrailAction, GuardrailRule, GuardrailsConfig};
+use crate::handlers::postprocess::filter;
+use crate::utils::crash::find_secrets;
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Name of the built-in credential check
+pub const SECRETS_RULE: &str = "credentials";
+
+/// Matches shown per rule
+const MAX_MATCHES: usize = 3;
+
+/// Longest excerpt of a match shown
+const MAX_EXCERPT: usize = 40;
+
+/// Private keys in PEM format
+static PRIVATE_KEY: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"-----BEGIN (?:[A-Z]+ )*PRIVATE KEY-----").unwrap());
+
+/// Something a guardrail found in a prompt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    /// Name of the rule
+    pub rule: String,
+    /// Whether the prompt is blocked or needs confirmation
+    pub action: GuardrailAction,
+    /// What matched, masked for credentials
+    pub detail: String,
+}
+
+impl std::fmt::Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.rule, self.detail)
+    }
+}
+
+/// Check `prompt` against the configured guardrails
+///
+/// `local` is whether the prompt goes to a model on this machine, which is
+/// only checked with `check_local`.
+pub fn check(prompt: &str, config: &GuardrailsConfig, local: bool) -> Vec<Finding> {
+    if local && !config.check_local {
+        return Vec::new();
+    }
+    let mut findings = Vec::new();
+    if config.secrets != GuardrailAction::Off {
+        let mut secrets: Vec<String> = find_secrets(prompt).iter().map(|s| mask(s)).collect();
+        secrets.extend(
+            PRIVATE_KEY
+                .find_iter(prompt)
+                .map(|found| found.as_str().trim_matches('-').to_lowercase()),
+        );
+        if let Some(detail) = summarize(secrets) {
+            findings.push(Finding {
+                rule: SECRETS_RULE.to_string(),
+                action: config.secrets,
+                detail,
+            });
+        }
+    }
+    for rule in &config.rules {
+        if rule.action == GuardrailAction::Off {
+            continue;
+        }
+        if let Some(detail) = check_rule(prompt, rule) {
+            findings.push(Finding {
+                rule: rule.name.clone(),
+                action: rule.action,
+                detail,
+            });
+        }
+    }
+    findings
+}
+
+/// Whether any finding stops the prompt
+pub fn is_blocked(findings: &[Finding]) -> bool {
+    findings
+        .iter()
+        .any(|finding| finding.action == GuardrailAction::Block)
+}
+
+/// What `rule` objects to in `prompt`, if anything
+fn check_rule(prompt: &str, rule: &GuardrailRule) -> Option<String> {
+    let mut details = Vec::new();
+    if let Some(pattern) = &rule.pattern {
+        match Regex::new(pattern) {
+            Ok(regex) => {
+                let matches: Vec<String> = regex
+                    .find_iter(prompt)
+                    .map(|found| excerpt(found.as_str()))
+                    .collect();
+                details.extend(summarize(matches));
+            }
+            Err(e) => details.push(format!("invalid pattern ({})", e)),
+        }
+    }
+    if let Some(command) = &rule.command
+        && let Err(e) = filter(command, prompt)
+    {
+        details.push(format!(
+            "`{}` {}",
+            command,
+            e.lines().next().unwrap_or("").trim()
+        ));
+    }
+    (!details.is_empty()).then(|| details.join("; "))
+}
+
+/// Distinct matches, the first few of them quoted
+fn summarize(matches: Vec<String>) -> Option<String> {
+    let mut distinct: Vec<String> = Vec::new();
+    for found in matches {
+        if !distinct.contains(&found) {
+            distinct.push(found);
+        }
+    }
+    if distinct.is_empty() {
+        return None;
+    }
+    let mut detail = distinct
+        .iter()
+        .take(MAX_MATCHES)
+        .map(|found| format!("\"{}\"", found))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if distinct.len() > MAX_MATCHES {
+        detail.push_str(&format!(" and {} more", distinct.len() - MAX_MATCHES));
+    }
+    Some(detail)
+}
+
+/// A match on one line, cut to a readable length
+fn excerpt(text: &str) -> String {
+    let line = text.lines().next().unwrap_or("").trim();
+    if line.chars().count() > MAX_EXCERPT {
+        format!("{}…", line.chars().take(MAX_EXCERPT).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+/// A credential with all but its first characters hidden
+fn mask(secret: &str) -> String {
+    format!("{}…", secret.chars().take(6).collect::<String>())
+}
@@ -0,0 +1,727 @@
+//! Importing from other AI command-line tools
+//!
+//! `/import aider|llm|sgpt` reads the chat history and settings those tools
+//! keep and turns them into stored sessions and provider settings:
+//!
+//! - aider: `.aider.chat.history.md` in the working directory and
+//!   `.aider.conf.yml` in the home and working directories
+//! - llm: the logs printed by `llm logs --json`, `default_model.txt` and
+//!   `keys.json` in its user directory (`LLM_USER_PATH`)
+//! - sgpt: `~/.config/shell_gpt/.sgptrc` and the chats in its chat cache
+//!
+//! Settings only fill in what is not configured yet: API keys are set for
+//! providers without one and the default model is added to its provider.
+
+use crate::ai::conversation::{Message, Role};
+use crate::ai::types::Provider;
+use crate::config::AIConfig;
+use crate::handlers::{HandlerError, HandlerResult};
+use crate::utils::sessions::{self, StoredSession};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+/// Separator printed before every command in the output
+const SEPARATOR: &str = "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━";
+
+/// Tool to import from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tool {
+    Aider,
+    Llm,
+    Sgpt,
+}
+
+impl fmt::Display for Tool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tool::Aider => write!(f, "aider"),
+            Tool::Llm => write!(f, "llm"),
+            Tool::Sgpt => write!(f, "sgpt"),
+        }
+    }
+}
+
+impl FromStr for Tool {
+    type Err = HandlerError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "aider" => Ok(Tool::Aider),
+            "llm" => Ok(Tool::Llm),
+            "sgpt" | "shell_gpt" | "shell-gpt" => Ok(Tool::Sgpt),
+            _ => Err(HandlerError::Parse(
+                "Usage: /import aider|llm|sgpt".to_string(),
+            )),
+        }
+    }
+}
+
+/// A conversation held by another tool
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedConversation {
+    /// Name the tool gave it, if any
+    pub title: Option<String>,
+    pub started: DateTime<Local>,
+    /// Model that answered, if recorded
+    pub model: Option<String>,
+    /// Prompts and answers, oldest first
+    pub messages: Vec<Message>,
+}
+
+/// Provider settings found in another tool's configuration
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedSettings {
+    /// Default model with the provider that serves it
+    pub model: Option<(Provider, String)>,
+    /// API keys per provider
+    pub api_keys: Vec<(Provider, String)>,
+    /// OpenAI-compatible server used in place of OpenAI
+    pub endpoint: Option<String>,
+}
+
+impl ImportedSettings {
+    /// Whether no setting was found
+    pub fn is_empty(&self) -> bool {
+        self.model.is_none() && self.api_keys.is_empty() && self.endpoint.is_none()
+    }
+
+    fn add_key(&mut self, provider: Provider, key: &str) {
+        let key = key.trim();
+        if key.is_empty() || self.api_keys.iter().any(|(known, _)| *known == provider) {
+            return;
+        }
+        self.api_keys.push((provider, key.to_string()));
+    }
+
+    /// With a base URL other than OpenAI's, OpenAI models and keys go to the custom provider
+    fn resolve_endpoint(mut self) -> Self {
+        let Some(endpoint) = self.endpoint.clone() else {
+            return self;
+        };
+        if endpoint.contains("api.openai.com") {
+            self.endpoint = None;
+            return self;
+        }
+        if let Some((provider, _)) = &mut self.model
+            && *provider == Provider::OpenAI
+        {
+            *provider = Provider::Custom;
+        }
+        if let Some(key) = self
+            .api_keys
+            .iter()
+            .find(|(provider, _)| *provider == Provider::OpenAI)
+            .map(|(_, key)| key.clone())
+        {
+            self.add_key(Provider::Custom, &key);
+        }
+        self
+    }
+}
+
+/// Everything found for one tool
+#[derive(Debug, Clone, Default)]
+pub struct Imported {
+    pub conversations: Vec<ImportedConversation>,
+    pub settings: ImportedSettings,
+    /// Files and commands that were read
+    pub sources: Vec<String>,
+    /// Things that could not be read
+    pub warnings: Vec<String>,
+}
+
+/// The provider of a model name and the name it has there
+///
+/// Prefixes such as `anthropic/` or `ollama/` and the short aliases of aider
+/// and llm (`sonnet`, `4o`) are understood; other names are OpenAI models.
+pub fn provider_for_model(model: &str) -> (Provider, String) {
+    let model = model.trim();
+    if let Some((prefix, name)) = model.split_once('/') {
+        let provider = match prefix {
+            "anthropic" => Some(Provider::Anthropic),
+            "openai" => Some(Provider::OpenAI),
+            "ollama" | "ollama_chat" => Some(Provider::Ollama),
+            "openrouter" => Some(Provider::OpenRouter),
+            "groq" => Some(Provider::Groq),
+            "mistral" => Some(Provider::Mistral),
+            "azure" => Some(Provider::AzureOpenAI),
+            _ => None,
+        };
+        if let Some(provider) = provider {
+            return (provider, name.to_string());
+        }
+    }
+    let aliased = match model {
+        "sonnet" => Some((Provider::Anthropic, "claude-3-5-sonnet-latest")),
+        "haiku" => Some((Provider::Anthropic, "claude-3-5-haiku-latest")),
+        "opus" => Some((Provider::Anthropic, "claude-3-opus-latest")),
+        "4o" => Some((Provider::OpenAI, "gpt-4o")),
+        "4o-mini" => Some((Provider::OpenAI, "gpt-4o-mini")),
+        "3.5" => Some((Provider::OpenAI, "gpt-3.5-turbo")),
+        _ => None,
+    };
+    if let Some((provider, name)) = aliased {
+        return (provider, name.to_string());
+    }
+    if model.starts_with("claude") {
+        (Provider::Anthropic, model.to_string())
+    } else if model.starts_with("mistral-")
+        || model.starts_with("open-mistral")
+        || model.starts_with("codestral")
+    {
+        (Provider::Mistral, model.to_string())
+    } else {
+        (Provider::OpenAI, model.to_string())
+    }
+}
+
+/// Provider of a key name used by llm or in aider's `api-key` list
+fn provider_for_key(name: &str) -> Option<Provider> {
+    match name.trim().to_lowercase().as_str() {
+        "openai" => Some(Provider::OpenAI),
+        "anthropic" | "claude" => Some(Provider::Anthropic),
+        "openrouter" => Some(Provider::OpenRouter),
+        "groq" => Some(Provider::Groq),
+        "mistral" => Some(Provider::Mistral),
+        _ => None,
+    }
+}
+
+/// Add the prompt and answer read so far to the conversation
+fn flush(
+    conversation: &mut Option<ImportedConversation>,
+    prompt: &mut Vec<&str>,
+    answer: &mut Vec<&str>,
+) {
+    if let Some(conversation) = conversation
+        && !prompt.is_empty()
+    {
+        conversation.messages.push(Message {
+            role: Role::User,
+            content: prompt.join("\n").trim().to_string(),
+            source: None,
+        });
+        let text = answer.join("\n").trim().to_string();
+        if !text.is_empty() {
+            conversation.messages.push(Message {
+                role: Role::Assistant,
+                content: text,
+                source: None,
+            });
+        }
+    }
+    prompt.clear();
+    answer.clear();
+}
+
+/// Conversations of an aider chat history
+///
+/// Every `# aider chat started at` heading starts a conversation; `####`
+/// lines are prompts, `>` lines are aider's own output and the rest is the
+/// model's answer.
+pub fn parse_aider_history(text: &str) -> Vec<ImportedConversation> {
+    let mut conversations: Vec<ImportedConversation> = Vec::new();
+    let mut current: Option<ImportedConversation> = None;
+    let mut prompt: Vec<&str> = Vec::new();
+    let mut answer: Vec<&str> = Vec::new();
+
+    for line in text.lines() {
+        if let Some(started) = line.strip_prefix("# aider chat started at ") {
+            flush(&mut current, &mut prompt, &mut answer);
+            conversations.extend(current.take());
+            let started = NaiveDateTime::parse_from_str(started.trim(), "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .and_then(|time| Local.from_local_datetime(&time).earliest())
+                .unwrap_or_else(Local::now);
+            current = Some(ImportedConversation {
+                title: None,
+                started,
+                model: None,
+                messages: Vec::new(),
+            });
+        } else if let Some(text) = line.strip_prefix("####") {
+            if !answer.is_empty() {
+                flush(&mut current, &mut prompt, &mut answer);
+            }
+            prompt.push(text.strip_prefix(' ').unwrap_or(text));
+        } else if let Some(note) = line.strip_prefix('>') {
+            // `> Model: gpt-4o with diff edit format` names the model
+            let note = note.trim();
+            if let Some(conversation) = &mut current
+                && conversation.model.is_none()
+                && let Some(model) = note
+                    .strip_prefix("Models: ")
+                    .or_else(|| note.strip_prefix("Model: "))
+                    .or_else(|| note.strip_prefix("Main model: "))
+            {
+                conversation.model = model.split_whitespace().next().map(str::to_string);
+            }
+        } else if !prompt.is_empty() {
+            answer.push(line);
+        }
+    }
+    flush(&mut current, &mut prompt, &mut answer);
+    conversations.extend(current);
+    conversations.retain(|conversation| !conversation.messages.is_empty());
+    conversations
+}
+
+/// Settings of an aider `.aider.conf.yml`
+pub fn parse_aider_config(text: &str) -> HandlerResult<ImportedSettings> {
+    let value: serde_yaml::Value = serde_yaml::from_str(text)
+        .map_err(|e| HandlerError::Parse(format!("Cannot read the aider config: {}", e)))?;
+    let get = |key: &str| value.get(key).and_then(|value| value.as_str());
+
+    let mut settings = ImportedSettings {
+        model: get("model").map(provider_for_model),
+        endpoint: get("openai-api-base").map(str::to_string),
+        ..Default::default()
+    };
+    if let Some(key) = get("openai-api-key") {
+        settings.add_key(Provider::OpenAI, key);
+    }
+    if let Some(key) = get("anthropic-api-key") {
+        settings.add_key(Provider::Anthropic, key);
+    }
+    // `api-key: [openrouter=sk-…, groq=gsk_…]`
+    let keys = match value.get("api-key") {
+        Some(serde_yaml::Value::Sequence(keys)) => keys.iter().filter_map(|v| v.as_str()).collect(),
+        Some(serde_yaml::Value::String(key)) => vec![key.as_str()],
+        _ => Vec::new(),
+    };
+    for entry in keys {
+        if let Some((name, key)) = entry.split_once('=')
+            && let Some(provider) = provider_for_key(name)
+        {
+            settings.add_key(provider, key);
+        }
+    }
+    Ok(settings.resolve_endpoint())
+}
+
+/// One logged response of llm
+#[derive(Debug, Deserialize)]
+struct LlmLog {
+    model: Option<String>,
+    prompt: Option<String>,
+    response: Option<String>,
+    conversation_id: Option<String>,
+    conversation_name: Option<String>,
+    datetime_utc: Option<String>,
+}
+
+/// Conversations of the JSON printed by `llm logs --json`, oldest first
+pub fn parse_llm_logs(json: &str) -> HandlerResult<Vec<ImportedConversation>> {
+    let mut logs: Vec<LlmLog> = serde_json::from_str(json)
+        .map_err(|e| HandlerError::Parse(format!("Cannot read the llm logs: {}", e)))?;
+    let time = |log: &LlmLog| {
+        log.datetime_utc
+            .as_deref()
+            .and_then(|time| NaiveDateTime::parse_from_str(time, "%Y-%m-%dT%H:%M:%S%.f").ok())
+            .map(|time| Utc.from_utc_datetime(&time).with_timezone(&Local))
+    };
+    logs.sort_by_key(|log| time(log));
+
+    let mut conversations: Vec<(Option<String>, ImportedConversation)> = Vec::new();
+    for log in &logs {
+        let prompt = log.prompt.as_deref().unwrap_or_default().trim();
+        if prompt.is_empty() {
+            continue;
+        }
+        let index = match conversations
+            .iter()
+            .position(|(id, _)| id.is_some() && *id == log.conversation_id)
+        {
+            Some(index) => index,
+            None => {
+                conversations.push((
+                    log.conversation_id.clone(),
+                    ImportedConversation {
+                        title: log
+                            .conversation_name
+                            .clone()
+                            .filter(|name| !name.is_empty()),
+                        started: time(log).unwrap_or_else(Local::now),
+                        model: log.model.clone(),
+                        messages: Vec::new(),
+                    },
+                ));
+                conversations.len() - 1
+            }
+        };
+        let messages = &mut conversations[index].1.messages;
+        messages.push(Message {
+            role: Role::User,
+            content: prompt.to_string(),
+            source: None,
+        });
+        let response = log.response.as_deref().unwrap_or_default().trim();
+        if !response.is_empty() {
+            messages.push(Message {
+                role: Role::Assistant,
+                content: response.to_string(),
+                source: None,
+            });
+        }
+    }
+    Ok(conversations
+        .into_iter()
+        .map(|(_, conversation)| conversation)
+        .collect())
+}
+
+/// Settings of llm: its default model and `keys.json`
+pub fn parse_llm_settings(
+    default_model: Option<&str>,
+    keys_json: Option<&str>,
+) -> HandlerResult<ImportedSettings> {
+    let mut settings = ImportedSettings {
+        model: default_model
+            .map(str::trim)
+            .filter(|model| !model.is_empty())
+            .map(provider_for_model),
+        ..Default::default()
+    };
+    if let Some(json) = keys_json {
+        let keys: BTreeMap<String, String> = serde_json::from_str(json)
+            .map_err(|e| HandlerError::Parse(format!("Cannot read the llm keys: {}", e)))?;
+        for (name, key) in &keys {
+            if let Some(provider) = provider_for_key(name) {
+                settings.add_key(provider, key);
+            }
+        }
+    }
+    Ok(settings)
+}
+
+/// Values of an sgpt `.sgptrc` (`KEY=value` lines)
+pub fn parse_sgptrc(text: &str) -> BTreeMap<String, String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// Settings of an sgpt `.sgptrc`
+pub fn sgpt_settings(values: &BTreeMap<String, String>) -> ImportedSettings {
+    let get = |key: &str| {
+        values
+            .get(key)
+            .map(String::as_str)
+            .filter(|value| !value.is_empty())
+    };
+    let mut settings = ImportedSettings {
+        model: get("DEFAULT_MODEL").map(provider_for_model),
+        endpoint: get("API_BASE_URL")
+            .filter(|url| *url != "default")
+            .map(str::to_string),
+        ..Default::default()
+    };
+    if let Some(key) = get("OPENAI_API_KEY") {
+        settings.add_key(Provider::OpenAI, key);
+    }
+    settings.resolve_endpoint()
+}
+
+/// A chat of the sgpt chat cache: a JSON list of messages
+pub fn parse_sgpt_chat(
+    name: &str,
+    json: &str,
+    started: DateTime<Local>,
+) -> HandlerResult<ImportedConversation> {
+    #[derive(Deserialize)]
+    struct SgptMessage {
+        role: String,
+        content: String,
+    }
+    let messages: Vec<SgptMessage> = serde_json::from_str(json)
+        .map_err(|e| HandlerError::Parse(format!("Cannot read the sgpt chat {}: {}", name, e)))?;
+    let messages = messages
+        .into_iter()
+        .filter_map(|message| {
+            let role = match message.role.as_str() {
+                "user" => Role::User,
+                "assistant" => Role::Assistant,
+                _ => return None,
+            };
+            let content = message.content.trim().to_string();
+            (!content.is_empty()).then_some(Message {
+                role,
+                content,
+                source: None,
+            })
+        })
+        .collect();
+    Ok(ImportedConversation {
+        title: Some(name.to_string()),
+        started,
+        model: None,
+        messages,
+    })
+}
+
+/// The conversation as a stored session, its output laid out like a live one
+pub fn to_session(tool: Tool, conversation: &ImportedConversation, id: String) -> StoredSession {
+    let mut output = format!("📥 Imported from {}\n", tool);
+    let mut history = Vec::new();
+    let mut exchanges = 0;
+    for message in &conversation.messages {
+        match message.role {
+            Role::User => {
+                output.push_str(&format!("{}\n❯ {}\n\n", SEPARATOR, message.content));
+                history.push(message.content.clone());
+                exchanges += 1;
+            }
+            Role::Assistant => output.push_str(&format!("{}\n", message.content)),
+        }
+    }
+    let title = conversation
+        .title
+        .clone()
+        .filter(|title| !title.trim().is_empty())
+        .unwrap_or_else(|| sessions::draft_title(history.first().map_or("", String::as_str)));
+    StoredSession {
+        id,
+        title,
+        created: conversation.started,
+        updated: conversation.started,
+        messages: exchanges,
+        cost: 0.0,
+        prompt_tokens: 0,
+        completion_tokens: 0,
+        output,
+        history,
+        conversation: Some(conversation.messages.clone()),
+        command_count: 0,
+        ai_count: exchanges,
+        bash_count: 0,
+        models_used: conversation.model.iter().cloned().collect(),
+    }
+}
+
+/// Session ids for the conversations: the tool and start time, numbered on clashes
+///
+/// The same history gets the same ids, so importing again replaces the sessions.
+pub fn session_ids(tool: Tool, conversations: &[ImportedConversation]) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    for conversation in conversations {
+        let base = format!("{}-{}", tool, conversation.started.format("%Y%m%d-%H%M%S"));
+        let mut id = base.clone();
+        let mut n = 2;
+        while ids.contains(&id) {
+            id = format!("{}-{}", base, n);
+            n += 1;
+        }
+        ids.push(id);
+    }
+    ids
+}
+
+/// The API key setting of a provider, for providers that take one
+fn api_key_mut(ai: &mut AIConfig, provider: Provider) -> Option<&mut String> {
+    match provider {
+        Provider::OpenAI => Some(&mut ai.openai.api_key),
+        Provider::Anthropic => Some(&mut ai.anthropic.api_key),
+        Provider::OpenRouter => Some(&mut ai.openrouter.api_key),
+        Provider::Groq => Some(&mut ai.groq.api_key),
+        Provider::Mistral => Some(&mut ai.mistral.api_key),
+        Provider::AzureOpenAI => Some(&mut ai.azure.api_key),
+        Provider::Custom => Some(&mut ai.custom.api_key),
+        Provider::Ollama | Provider::LMStudio | Provider::LlamaCpp => None,
+    }
+}
+
+/// Fill in the settings ai-coder does not have yet, describing each change
+///
+/// Configured API keys are kept. The model is added to its provider and
+/// selected there, but the active provider stays as it is.
+pub fn apply_settings(ai: &mut AIConfig, settings: &ImportedSettings) -> Vec<String> {
+    let mut notes = Vec::new();
+    for (provider, key) in &settings.api_keys {
+        match api_key_mut(ai, *provider) {
+            Some(slot) if slot.is_empty() => {
+                *slot = key.clone();
+                notes.push(format!("Set the {} API key", provider));
+            }
+            Some(_) => notes.push(format!("Kept the configured {} API key", provider)),
+            None => {}
+        }
+    }
+    if let Some(endpoint) = &settings.endpoint {
+        ai.custom.endpoint = endpoint.trim_end_matches('/').to_string();
+        notes.push(format!("Set the custom provider endpoint to {}", endpoint));
+    }
+    if let Some((provider, model)) = &settings.model {
+        let active = ai.active_provider;
+        ai.active_provider = *provider;
+        ai.select_model(model);
+        ai.active_provider = active;
+        if *provider == active {
+            notes.push(format!("Selected the model {}", model));
+        } else {
+            notes.push(format!(
+                "Selected the model {} for {} (switch to it with /config provider)",
+                model, provider
+            ));
+        }
+    }
+    notes
+}
+
+/// Read what `tool` keeps for the working directory `cwd` and the home directory `home`
+pub fn load(tool: Tool, cwd: &Path, home: &Path) -> Imported {
+    let mut imported = Imported::default();
+    match tool {
+        Tool::Aider => load_aider(&mut imported, cwd, home),
+        Tool::Llm => load_llm(&mut imported),
+        Tool::Sgpt => load_sgpt(&mut imported, home),
+    }
+    imported
+}
+
+/// Read a file that may be missing, noting it when it was read
+fn read_optional(imported: &mut Imported, path: &Path) -> Option<String> {
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            imported.sources.push(path.display().to_string());
+            Some(content)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => {
+            imported
+                .warnings
+                .push(format!("Cannot read {}: {}", path.display(), e));
+            None
+        }
+    }
+}
+
+fn load_aider(imported: &mut Imported, cwd: &Path, home: &Path) {
+    if let Some(history) = read_optional(imported, &cwd.join(".aider.chat.history.md")) {
+        imported.conversations = parse_aider_history(&history);
+    }
+    // The config in the working directory wins over the one in the home directory
+    let mut paths = vec![home.join(".aider.conf.yml")];
+    if cwd != home {
+        paths.push(cwd.join(".aider.conf.yml"));
+    }
+    for path in paths {
+        let Some(text) = read_optional(imported, &path) else {
+            continue;
+        };
+        match parse_aider_config(&text) {
+            Ok(settings) => merge_settings(&mut imported.settings, settings),
+            Err(e) => imported.warnings.push(e.to_string()),
+        }
+    }
+}
+
+/// Settings found later override earlier ones
+fn merge_settings(settings: &mut ImportedSettings, later: ImportedSettings) {
+    if later.model.is_some() {
+        settings.model = later.model;
+    }
+    if later.endpoint.is_some() {
+        settings.endpoint = later.endpoint;
+    }
+    for (provider, key) in later.api_keys {
+        settings.api_keys.retain(|(known, _)| *known != provider);
+        settings.api_keys.push((provider, key));
+    }
+}
+
+/// llm's user directory
+fn llm_dir() -> Option<PathBuf> {
+    match std::env::var_os("LLM_USER_PATH") {
+        Some(path) if !path.is_empty() => Some(PathBuf::from(path)),
+        _ => dirs::config_dir().map(|dir| dir.join("io.datasette.llm")),
+    }
+}
+
+fn load_llm(imported: &mut Imported) {
+    if let Some(dir) = llm_dir() {
+        let default_model = read_optional(imported, &dir.join("default_model.txt"));
+        let keys = read_optional(imported, &dir.join("keys.json"));
+        match parse_llm_settings(default_model.as_deref(), keys.as_deref()) {
+            Ok(settings) => imported.settings = settings,
+            Err(e) => imported.warnings.push(e.to_string()),
+        }
+    }
+
+    // The logs are an SQLite database; llm itself prints them as JSON
+    match Command::new("llm")
+        .args(["logs", "-n", "0", "--json"])
+        .output()
+    {
+        Ok(output) if output.status.success() => {
+            imported.sources.push("llm logs -n 0 --json".to_string());
+            match parse_llm_logs(&String::from_utf8_lossy(&output.stdout)) {
+                Ok(conversations) => imported.conversations = conversations,
+                Err(e) => imported.warnings.push(e.to_string()),
+            }
+        }
+        Ok(output) => imported.warnings.push(format!(
+            "llm logs failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )),
+        Err(_) => imported
+            .warnings
+            .push("llm is not on the PATH, so its logs were not read".to_string()),
+    }
+}
+
+fn load_sgpt(imported: &mut Imported, home: &Path) {
+    let values = read_optional(imported, &home.join(".config/shell_gpt/.sgptrc"))
+        .map(|text| parse_sgptrc(&text))
+        .unwrap_or_default();
+    imported.settings = sgpt_settings(&values);
+
+    let cache = values
+        .get("CHAT_CACHE_PATH")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| std::env::temp_dir().join("chat_cache"));
+    let Ok(entries) = fs::read_dir(&cache) else {
+        return;
+    };
+    imported.sources.push(cache.display().to_string());
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if !path.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let started = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .map(DateTime::<Local>::from)
+            .unwrap_or_else(|_| Local::now());
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| HandlerError::Other(format!("Cannot read {}: {}", path.display(), e)))
+            .and_then(|json| parse_sgpt_chat(&name, &json, started));
+        match parsed {
+            Ok(conversation) if !conversation.messages.is_empty() => {
+                imported.conversations.push(conversation)
+            }
+            Ok(_) => {}
+            Err(e) => imported.warnings.push(e.to_string()),
+        }
+    }
+    imported
+        .conversations
+        .sort_by_key(|conversation| conversation.started);
+}
+
+impl Imported {
+    /// Whether nothing at all was found
+    pub fn is_empty(&self) -> bool {
+        self.conversations.is_empty() && self.settings.is_empty()
+    }
+}
@@ -2,12 +2,49 @@
 //!
 //! This module contains handlers for different command types, including:
 //! - AI command handling
+//! - Attaching files and command output, summarizing large ones
 //! - Bash command execution
+//! - Interactive commands in a pseudo-terminal
+//! - Code block execution through configured interpreters
+//! - Writing path-annotated code blocks into the project
+//! - Preparing multi-file refactoring requests
+//! - Generating and inserting documentation comments
+//! - Post-processing responses before display
+//! - Rewriting responses shorter, as bullet points or translated
+//! - Commit message generation from staged changes
+//! - Exporting the session as HTML
+//! - Checking for and installing new releases
+//! - The working directory of commands, changed with `/cd`
+//! - Safe mode, which blocks side effects
+//! - Workspace boundaries for the files read and written
+//! - Marking and scanning untrusted content sent to the model
+//! - Fetching web pages as readable text
+//! - Opening file locations of the output in an editor
 //! - Application commands
 
 pub mod ai;
+pub mod attach;
 pub mod bash;
+pub mod code;
 pub mod command;
+pub mod doc;
+pub mod editor;
+pub mod edits;
+pub mod export;
+pub mod fetch;
+pub mod files;
+pub mod git;
+pub mod guardrails;
+pub mod import;
+pub mod injection;
+pub mod postprocess;
+pub mod pty;
+pub mod refactor;
+pub mod rewrite;
+pub mod safe;
+pub mod update;
+pub mod workdir;
+pub mod workspace;
 
 use crate::ai::AIError;
 use std::fmt;
@@ -0,0 +1,133 @@
+//! Response post-processing
+//!
+//! The `postprocess` config is an ordered list of steps applied to chat
+//! responses before they are displayed and their code blocks run: dropping
+//! chain-of-thought blocks, masking secrets, formatting code blocks and
+//! piping the response through any filter command. A step that fails leaves
+//! the text as it was and adds a warning below the response.
+
+use crate::config::PostProcessor;
+use crate::handlers::code::CODE_BLOCK;
+use crate::platform;
+use crate::utils::crash::redact_secrets;
+use std::io::Write;
+use std::process::Stdio;
+
+/// Tags that models wrap their reasoning in
+const THINKING_TAGS: [&str; 3] = ["think", "thinking", "reasoning"];
+
+/// Apply the configured steps to a response, in order
+pub fn postprocess(steps: &[PostProcessor], response: &str) -> String {
+    let mut text = response.to_string();
+    let mut warnings = Vec::new();
+    for step in steps {
+        match step {
+            PostProcessor::StripThinking => text = strip_thinking(&text),
+            PostProcessor::RedactSecrets => text = redact_secrets(&text),
+            PostProcessor::FormatCode { language, command } => {
+                text = format_code(&text, language, command, &mut warnings)
+            }
+            PostProcessor::Command { command } => match filter(command, &text) {
+                Ok(filtered) => text = filtered,
+                Err(e) => warnings.push(format!("`{}` {}", command, e)),
+            },
+        }
+    }
+
+    for warning in warnings {
+        text.push_str(&format!("\n⚠️ Post-processor {}", warning));
+    }
+    text
+}
+
+/// Text to show of a response that is still being generated
+///
+/// Reasoning is hidden as in the final answer and secrets are masked when
+/// the steps ask for it. The other steps need the complete response and run
+/// once it has arrived.
+pub fn streaming_preview(steps: &[PostProcessor], partial: &str) -> String {
+    let text = strip_thinking(partial);
+    if steps.contains(&PostProcessor::RedactSecrets) {
+        redact_secrets(&text)
+    } else {
+        text
+    }
+}
+
+/// Remove `<think>` blocks, including an unclosed one at the end
+pub fn strip_thinking(text: &str) -> String {
+    split_thinking(text).1
+}
+
+/// Separate `<think>` blocks from the answer: `(reasoning, answer)`
+///
+/// A closing tag without an opening one ends reasoning that started in the
+/// prompt template, so everything before it is reasoning. An unclosed tag
+/// turns the rest of the text into reasoning.
+pub fn split_thinking(text: &str) -> (Option<String>, String) {
+    let mut text = text.to_string();
+    let mut reasoning = Vec::new();
+    for tag in THINKING_TAGS {
+        let open = format!("<{}>", tag);
+        let close = format!("</{}>", tag);
+        loop {
+            let range = match (text.find(&open), text.find(&close)) {
+                (Some(start), Some(end)) if start < end => start..end + close.len(),
+                (_, Some(end)) => 0..end + close.len(),
+                (Some(start), None) => start..text.len(),
+                (None, None) => break,
+            };
+            let block: String = text.drain(range).collect();
+            let block = block.replace(&open, "").replace(&close, "");
+            if !block.trim().is_empty() {
+                reasoning.push(block.trim().to_string());
+            }
+        }
+    }
+    let reasoning = (!reasoning.is_empty()).then(|| reasoning.join("\n\n"));
+    (reasoning, text.trim().to_string())
+}
+
+/// Pipe the code blocks in `language` through a formatter
+fn format_code(text: &str, language: &str, command: &str, warnings: &mut Vec<String>) -> String {
+    CODE_BLOCK
+        .replace_all(text, |caps: &regex::Captures| {
+            if !caps[1].eq_ignore_ascii_case(language) {
+                return caps[0].to_string();
+            }
+            match filter(command, &format!("{}\n", &caps[2])) {
+                Ok(formatted) => format!("```{}\n{}\n```", &caps[1], formatted.trim_end()),
+                Err(e) => {
+                    warnings.push(format!("`{}` {}", command, e));
+                    caps[0].to_string()
+                }
+            }
+        })
+        .into_owned()
+}
+
+/// Run `command` through the shell with `input` on stdin and return its stdout
+pub fn filter(command: &str, input: &str) -> Result<String, String> {
+    let mut child = platform::shell_command(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("could not start: {}", e))?;
+    // Write from another thread so a filter that streams its output cannot deadlock
+    if let Some(mut stdin) = child.stdin.take() {
+        let input = input.to_string();
+        std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("failed: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "failed ({}): {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
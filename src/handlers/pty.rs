@@ -0,0 +1,253 @@
+//! Interactive commands in a pseudo-terminal
+//!
+//! Commands such as `python` or `ssh` wait for input that a `!` command
+//! cannot give them. `/pty` runs them in a pseudo-terminal instead: keys are
+//! passed to the command and its output is shown as it is printed. The
+//! command sees `TERM=dumb`, so it prints lines rather than drawing on the
+//! screen; full-screen programs such as editors are not supported.
+//!
+//! [`Screen`] turns the raw output into lines, applying carriage returns and
+//! backspaces and leaving out escape sequences.
+
+use crate::handlers::{HandlerError, HandlerResult, bash, safe};
+use crate::platform::{self, PtyProcess};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::path::Path;
+
+/// Lines kept on the screen; older ones are dropped
+const MAX_LINES: usize = 5000;
+
+/// Columns between tab stops
+const TAB_WIDTH: usize = 8;
+
+/// Rightmost column cursor movement in the output can reach, so a command
+/// cannot make a line take arbitrary memory with one escape sequence
+const MAX_COLUMN: usize = 1024;
+
+/// Share of the output area the pane of a `/pty` command covers
+pub const PANE_PERCENT: u16 = 60;
+
+/// Byte typed as Ctrl+D, ending the input of the command
+pub const END_OF_INPUT: u8 = 0x04;
+
+/// Start `command` in `dir` in a pseudo-terminal of `cols` by `rows`
+///
+/// The same checks as for `!` commands apply.
+pub fn spawn(command: &str, dir: &Path, cols: u16, rows: u16) -> HandlerResult<PtyProcess> {
+    safe::check("Running shell commands")?;
+    let mut process = bash::prepare(command, dir)?;
+    // Plain lines, without the cursor movement the pane cannot show
+    process.env("TERM", "dumb");
+    platform::spawn_in_pty(process, cols, rows)
+        .map_err(|e| HandlerError::Bash(format!("Failed to start {}: {}", command.trim(), e)))
+}
+
+/// The bytes a key sends to the command, if it sends any
+pub fn key_bytes(key: &KeyEvent) -> Option<Vec<u8>> {
+    let mut bytes = match key.code {
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            if !c.is_ascii_alphabetic() {
+                return None;
+            }
+            vec![c.to_ascii_lowercase() as u8 & 0x1f]
+        }
+        KeyCode::Char(c) => c.to_string().into_bytes(),
+        KeyCode::Enter => vec![b'\r'],
+        KeyCode::Backspace => vec![0x7f],
+        KeyCode::Tab => vec![b'\t'],
+        KeyCode::BackTab => b"\x1b[Z".to_vec(),
+        KeyCode::Esc => vec![0x1b],
+        KeyCode::Up => b"\x1b[A".to_vec(),
+        KeyCode::Down => b"\x1b[B".to_vec(),
+        KeyCode::Right => b"\x1b[C".to_vec(),
+        KeyCode::Left => b"\x1b[D".to_vec(),
+        KeyCode::Home => b"\x1b[H".to_vec(),
+        KeyCode::End => b"\x1b[F".to_vec(),
+        KeyCode::Delete => b"\x1b[3~".to_vec(),
+        KeyCode::PageUp => b"\x1b[5~".to_vec(),
+        KeyCode::PageDown => b"\x1b[6~".to_vec(),
+        _ => return None,
+    };
+    // Alt sends the key after an escape, as terminals do
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        bytes.insert(0, 0x1b);
+    }
+    Some(bytes)
+}
+
+/// Where the parser is within the output
+#[derive(Debug, Clone, Default, PartialEq)]
+enum Parse {
+    #[default]
+    Text,
+    /// After ESC
+    Escape,
+    /// In a control sequence (`ESC [`), with its parameters so far
+    Control(String),
+    /// In an operating system command (`ESC ]`), e.g. setting the window title
+    Command,
+    /// After ESC in an operating system command, which ends it
+    CommandEscape,
+}
+
+/// The output of an interactive command as lines of text
+#[derive(Debug, Clone, Default)]
+pub struct Screen {
+    /// Finished lines
+    lines: Vec<String>,
+    /// The line being written
+    line: Vec<char>,
+    /// Column the next character is written to
+    column: usize,
+    parse: Parse,
+    /// Bytes of a character split between two reads
+    partial: Vec<u8>,
+}
+
+impl Screen {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add output of the command
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let mut bytes = std::mem::take(&mut self.partial)
+            .into_iter()
+            .chain(bytes.iter().copied())
+            .collect::<Vec<u8>>();
+        loop {
+            match std::str::from_utf8(&bytes) {
+                Ok(text) => {
+                    text.chars().for_each(|ch| self.put(ch));
+                    return;
+                }
+                Err(e) => {
+                    let valid = e.valid_up_to();
+                    let text = std::str::from_utf8(&bytes[..valid]).unwrap_or_default();
+                    text.chars().for_each(|ch| self.put(ch));
+                    match e.error_len() {
+                        Some(len) => {
+                            self.put(char::REPLACEMENT_CHARACTER);
+                            bytes.drain(..valid + len);
+                        }
+                        None => {
+                            // The rest of the character comes with the next read
+                            self.partial = bytes.split_off(valid);
+                            return;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// All lines, the one being written last
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = self.lines.clone();
+        lines.push(self.line.iter().collect());
+        lines
+    }
+
+    /// The output as text, without the trailing empty lines
+    pub fn text(&self) -> String {
+        let lines = self.lines();
+        lines.join("\n").trim_end().to_string()
+    }
+
+    /// Column of the cursor on the last line
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Whether anything was printed
+    pub fn is_empty(&self) -> bool {
+        self.text().is_empty()
+    }
+
+    fn put(&mut self, ch: char) {
+        match std::mem::take(&mut self.parse) {
+            Parse::Text => match ch {
+                '\x1b' => self.parse = Parse::Escape,
+                '\r' => self.column = 0,
+                '\n' => self.new_line(),
+                '\x08' => self.column = self.column.saturating_sub(1),
+                '\t' => {
+                    let stop = (self.column / TAB_WIDTH + 1) * TAB_WIDTH;
+                    while self.column < stop {
+                        self.write(' ');
+                    }
+                }
+                ch if ch.is_control() => {}
+                ch => self.write(ch),
+            },
+            Parse::Escape => {
+                self.parse = match ch {
+                    '[' => Parse::Control(String::new()),
+                    ']' => Parse::Command,
+                    // Two-character sequences such as ESC = are ignored
+                    _ => Parse::Text,
+                }
+            }
+            Parse::Control(mut params) => {
+                if ('\x40'..='\x7e').contains(&ch) {
+                    self.control(ch, &params);
+                } else {
+                    params.push(ch);
+                    self.parse = Parse::Control(params);
+                }
+            }
+            Parse::Command => {
+                self.parse = match ch {
+                    '\x07' => Parse::Text,
+                    '\x1b' => Parse::CommandEscape,
+                    _ => Parse::Command,
+                }
+            }
+            Parse::CommandEscape => {}
+        }
+    }
+
+    /// Apply the control sequences that change the line; others are ignored
+    fn control(&mut self, action: char, params: &str) {
+        let count = params.parse::<usize>().unwrap_or(1).max(1);
+        match action {
+            // Erase to the end of the line, to its start, or all of it
+            'K' => match params {
+                "1" => {
+                    let end = self.column.min(self.line.len());
+                    self.line[..end].fill(' ')
+                }
+                "2" => self.line.clear(),
+                _ => self.line.truncate(self.column),
+            },
+            'C' => self.move_to(self.column.saturating_add(count)),
+            'D' => self.column = self.column.saturating_sub(count),
+            'G' => self.move_to(count - 1),
+            _ => {}
+        }
+    }
+
+    /// Move the cursor to a column, but not past [`MAX_COLUMN`] or the end
+    /// of a longer line
+    fn move_to(&mut self, column: usize) {
+        self.column = column.min(MAX_COLUMN.max(self.line.len()));
+    }
+
+    fn write(&mut self, ch: char) {
+        if self.column < self.line.len() {
+            self.line[self.column] = ch;
+        } else {
+            self.line.resize(self.column, ' ');
+            self.line.push(ch);
+        }
+        self.column += 1;
+    }
+
+    fn new_line(&mut self) {
+        self.lines.push(self.line.drain(..).collect());
+        self.column = 0;
+        if self.lines.len() > MAX_LINES {
+            self.lines.remove(0);
+        }
+    }
+}
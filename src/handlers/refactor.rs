@@ -0,0 +1,138 @@
+//! Refactoring handler
+//!
+//! This module prepares `/refactor` requests: it gathers the files matching
+//! a glob within the configured size and token budgets, builds the prompt
+//! asking the model for coordinated edits, and filters the returned file
+//! blocks down to the files that were part of the request.
+
+use crate::config::get_config;
+use crate::handlers::files::FileBlock;
+use crate::handlers::{HandlerError, HandlerResult, workspace};
+use crate::utils::count_tokens;
+use crate::utils::glob::{Glob, find_files};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A refactoring request sent to the model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RefactorRequest {
+    /// What the user asked to change
+    pub instruction: String,
+    /// Files included in the prompt, relative to the working directory
+    pub files: Vec<PathBuf>,
+    /// Matching files left out, with the reason
+    pub skipped: Vec<String>,
+}
+
+/// Parse `"<instruction>" <glob>` arguments
+pub fn parse_args(args: &str) -> HandlerResult<(String, String)> {
+    let words = shell_words::split(args)
+        .map_err(|e| HandlerError::Parse(format!("Invalid arguments: {}", e)))?;
+
+    match words.as_slice() {
+        [instruction, pattern] if !instruction.trim().is_empty() => {
+            Ok((instruction.trim().to_string(), pattern.clone()))
+        }
+        _ => Err(HandlerError::Parse(
+            "Usage: /refactor \"<instruction>\" <glob>".to_string(),
+        )),
+    }
+}
+
+/// Gather the files matching `pattern` under `root` and build the prompt
+pub fn prepare(
+    root: &Path,
+    instruction: &str,
+    pattern: &str,
+) -> HandlerResult<(RefactorRequest, String)> {
+    let limits = get_config().refactor;
+    let glob = Glob::new(pattern)
+        .map_err(|e| HandlerError::Parse(format!("Invalid glob '{}': {}", pattern, e)))?;
+
+    let mut request = RefactorRequest {
+        instruction: instruction.to_string(),
+        files: Vec::new(),
+        skipped: Vec::new(),
+    };
+    let mut sections = String::new();
+    let mut tokens = 0;
+
+    for path in find_files(root, &glob) {
+        let display = path.to_string_lossy().replace('\\', "/");
+        let full_path = root.join(&path);
+
+        if workspace::check(root, &path).is_err() {
+            request
+                .skipped
+                .push(format!("{} (denied by the workspace)", display));
+            continue;
+        }
+        if request.files.len() >= limits.max_files {
+            request.skipped.push(format!("{} (file limit)", display));
+            continue;
+        }
+        if fs::metadata(&full_path).map_or(true, |m| m.len() > limits.max_file_bytes) {
+            request.skipped.push(format!("{} (too large)", display));
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&full_path) else {
+            request.skipped.push(format!("{} (not text)", display));
+            continue;
+        };
+        let file_tokens = count_tokens(&content);
+        if tokens + file_tokens > limits.token_budget {
+            request.skipped.push(format!("{} (token budget)", display));
+            continue;
+        }
+
+        tokens += file_tokens;
+        let language = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        sections.push_str(&format!(
+            "```{} {}\n{}\n```\n\n",
+            language,
+            display,
+            content.trim_end_matches('\n')
+        ));
+        request.files.push(path);
+    }
+
+    if request.files.is_empty() {
+        return Err(HandlerError::Other(if request.skipped.is_empty() {
+            format!("No files match '{}'", pattern)
+        } else {
+            format!(
+                "No files fit the refactor limits: {}",
+                request.skipped.join(", ")
+            )
+        }));
+    }
+
+    let prompt = format!(
+        "Refactor the following files. Instruction: {}\n\n\
+         Apply the instruction consistently across all files. For every file you change, \
+         reply with its complete new contents in a fenced code block whose info string is \
+         the language followed by the file path, exactly as given below (for example \
+         ```rs src/lib.rs). Leave out unchanged files. Do not use diffs, ellipses or \
+         placeholders.\n\n{}",
+        instruction, sections
+    );
+
+    Ok((request, prompt))
+}
+
+/// Keep only the blocks for files that were part of the request
+///
+/// Returns the accepted blocks and the paths of any ignored blocks.
+pub fn select_edits(
+    request: &RefactorRequest,
+    blocks: Vec<FileBlock>,
+) -> (Vec<FileBlock>, Vec<String>) {
+    let (accepted, ignored): (Vec<FileBlock>, Vec<FileBlock>) = blocks
+        .into_iter()
+        .partition(|block| request.files.contains(&block.path));
+    (
+        accepted,
+        ignored.iter().map(FileBlock::display_path).collect(),
+    )
+}
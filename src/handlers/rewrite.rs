@@ -0,0 +1,90 @@
+//! Rewrite handler
+//!
+//! This module prepares quick actions on a response: making it shorter,
+//! translating it or turning it into bullet points. Each sends the text back
+//! to the model with a canned instruction.
+
+use crate::handlers::{HandlerError, HandlerResult};
+use serde::{Deserialize, Serialize};
+
+/// What to do with the response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RewriteAction {
+    /// Say the same in fewer words
+    Shorter,
+    /// Convert to a bullet point list
+    Bullets,
+    /// Translate into the given language
+    Translate(String),
+}
+
+impl RewriteAction {
+    /// Short description, e.g. "translated to German"
+    pub fn describe(&self) -> String {
+        match self {
+            RewriteAction::Shorter => "shorter".to_string(),
+            RewriteAction::Bullets => "as bullet points".to_string(),
+            RewriteAction::Translate(language) => format!("translated to {}", language),
+        }
+    }
+
+    /// Instruction sent to the model
+    fn instruction(&self) -> String {
+        match self {
+            RewriteAction::Shorter => "Rewrite the following text so it is considerably shorter. \
+                 Keep every fact, command and code block that matters; drop filler and repetition."
+                .to_string(),
+            RewriteAction::Bullets => "Convert the following text into a concise Markdown bullet \
+                 point list. Keep code blocks intact."
+                .to_string(),
+            RewriteAction::Translate(language) => format!(
+                "Translate the following text into {}. Leave code blocks, commands, identifiers \
+                 and file paths untranslated.",
+                language
+            ),
+        }
+    }
+}
+
+/// A quick action on one message of the output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RewriteRequest {
+    /// What to do
+    pub action: RewriteAction,
+    /// Index of the message in the output
+    pub message: usize,
+    /// Output of the message when the request was sent
+    pub original: String,
+    /// Add the result below instead of replacing the message
+    pub append: bool,
+}
+
+/// Parse `shorter|bullets|translate <language> [--append]` arguments
+pub fn parse_args(args: &str) -> HandlerResult<(RewriteAction, bool)> {
+    let mut words: Vec<&str> = args.split_whitespace().collect();
+    let append = words.contains(&"--append");
+    words.retain(|word| *word != "--append");
+
+    let action = match words.as_slice() {
+        ["shorter"] => RewriteAction::Shorter,
+        ["bullets"] => RewriteAction::Bullets,
+        ["translate", language @ ..] if !language.is_empty() => {
+            RewriteAction::Translate(language.join(" "))
+        }
+        _ => {
+            return Err(HandlerError::Parse(
+                "Usage: /rewrite shorter|bullets|translate <language> [--append]".to_string(),
+            ));
+        }
+    };
+    Ok((action, append))
+}
+
+/// Prompt asking the model to rewrite `text`
+pub fn build_prompt(action: &RewriteAction, text: &str) -> String {
+    format!(
+        "{} Reply with the rewritten text only.\n\n---\n\n{}",
+        action.instruction(),
+        text
+    )
+}
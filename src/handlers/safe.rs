@@ -0,0 +1,37 @@
+//! Safe mode
+//!
+//! `ai-coder --safe` blocks everything with side effects: shell commands,
+//! code block execution, file writes, applying patches, commits, undo,
+//! self-update and sync. Chat and read-only commands keep working, which
+//! suits demos and prompts that cannot be trusted.
+
+use crate::handlers::{HandlerError, HandlerResult};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static SAFE_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Turn safe mode on for the rest of the run
+pub fn enable() {
+    SAFE_MODE.store(true, Ordering::SeqCst);
+}
+
+/// Whether the app was started with `--safe`
+pub fn is_enabled() -> bool {
+    SAFE_MODE.load(Ordering::SeqCst)
+}
+
+/// Refuse `action` with an explanation while in safe mode
+pub fn check(action: &str) -> HandlerResult<()> {
+    if is_enabled() {
+        return Err(HandlerError::Other(blocked_message(action)));
+    }
+    Ok(())
+}
+
+/// Why `action` did not happen
+pub fn blocked_message(action: &str) -> String {
+    format!(
+        "🔒 {} is disabled in safe mode (started with --safe); restart without it to allow side effects",
+        action
+    )
+}
@@ -0,0 +1,289 @@
+//! Release checks and self-update
+//!
+//! `/update` lists the GitHub releases newer than the running version with
+//! their release notes. `/update install` downloads the binary built for this
+//! platform and replaces the running executable, unless
+//! `update.allow_self_update` is off (e.g. in managed environments). The
+//! binary is only installed when it matches the `<binary>.sha256` file
+//! published with it.
+
+use crate::handlers::{HandlerError, HandlerResult};
+use crate::utils::crypto::hex;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Time allowed for the release list request
+const CHECK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Time allowed for downloading a binary
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A published release
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    /// Tag such as `v0.2.0`
+    pub tag_name: String,
+    /// Release title
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Release notes in Markdown
+    #[serde(default)]
+    pub body: Option<String>,
+    /// Release page
+    #[serde(default)]
+    pub html_url: String,
+    /// Drafts are never offered
+    #[serde(default)]
+    pub draft: bool,
+    /// Pre-releases are never offered
+    #[serde(default)]
+    pub prerelease: bool,
+    /// Files attached to the release
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+/// A file attached to a release
+#[derive(Debug, Clone, Deserialize)]
+pub struct Asset {
+    /// File name, e.g. `ai-coder-linux-x86_64`
+    pub name: String,
+    /// Direct download URL
+    pub browser_download_url: String,
+    /// Size in bytes
+    #[serde(default)]
+    pub size: u64,
+}
+
+impl Release {
+    /// Version of the release, parsed from its tag
+    pub fn version(&self) -> Option<(u64, u64, u64)> {
+        parse_version(&self.tag_name)
+    }
+
+    /// The binary built for this platform, if the release has one
+    ///
+    /// Archives are skipped: only plain executables can replace the running one.
+    pub fn asset_for(&self, os: &str, arch: &str) -> Option<&Asset> {
+        let os_names: &[&str] = match os {
+            "macos" => &["macos", "darwin", "apple"],
+            "windows" => &["windows", "win64"],
+            other => &[other],
+        };
+        let arch_names: &[&str] = match arch {
+            "aarch64" => &["aarch64", "arm64"],
+            "x86_64" => &["x86_64", "amd64", "x64"],
+            other => &[other],
+        };
+        self.assets.iter().find(|asset| {
+            let name = asset.name.to_lowercase();
+            let archive = [".tar.gz", ".tgz", ".zip", ".tar.xz", ".sha256"]
+                .iter()
+                .any(|ext| name.ends_with(ext));
+            !archive
+                && os_names.iter().any(|os| name.contains(os))
+                && arch_names.iter().any(|arch| name.contains(arch))
+        })
+    }
+
+    /// The `.sha256` file published next to `asset`
+    pub fn checksum_for(&self, asset: &Asset) -> Option<&Asset> {
+        let name = format!("{}.sha256", asset.name);
+        self.assets
+            .iter()
+            .find(|candidate| candidate.name.eq_ignore_ascii_case(&name))
+    }
+}
+
+/// Check `binary` against the contents of a `.sha256` file
+///
+/// The file holds the hex digest, optionally followed by the file name as
+/// `sha256sum` writes it.
+pub fn verify_checksum(binary: &[u8], checksum: &str) -> HandlerResult<()> {
+    let expected = checksum
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    if expected.len() != 64 || !expected.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(HandlerError::Other(
+            "The checksum file holds no SHA-256 digest".to_string(),
+        ));
+    }
+    let actual = hex(&Sha256::digest(binary));
+    if actual != expected {
+        return Err(HandlerError::Other(format!(
+            "Checksum mismatch: expected {}, got {}",
+            expected, actual
+        )));
+    }
+    Ok(())
+}
+
+/// Parse `v1.2.3` or `1.2` into a comparable version
+pub fn parse_version(tag: &str) -> Option<(u64, u64, u64)> {
+    let tag = tag.trim().trim_start_matches(['v', 'V']);
+    // Ignore pre-release and build suffixes such as `-rc.1`
+    let core = tag.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse::<u64>());
+    let major = parts.next()?.ok()?;
+    let minor = parts.next().unwrap_or(Ok(0)).ok()?;
+    let patch = parts.next().unwrap_or(Ok(0)).ok()?;
+    Some((major, minor, patch))
+}
+
+/// Published releases newer than `current`, newest first
+pub fn newer_releases(releases: &[Release], current: &str) -> Vec<Release> {
+    let Some(current) = parse_version(current) else {
+        return Vec::new();
+    };
+    let mut newer: Vec<Release> = releases
+        .iter()
+        .filter(|release| !release.draft && !release.prerelease)
+        .filter(|release| release.version().is_some_and(|version| version > current))
+        .cloned()
+        .collect();
+    newer.sort_by_key(|release| std::cmp::Reverse(release.version()));
+    newer
+}
+
+/// Release notes of the given releases, newest first
+pub fn changelog(releases: &[Release]) -> String {
+    releases
+        .iter()
+        .map(|release| {
+            let title = match &release.name {
+                Some(name) if !name.is_empty() && name != &release.tag_name => {
+                    format!("## {} — {}", release.tag_name, name)
+                }
+                _ => format!("## {}", release.tag_name),
+            };
+            let notes = release
+                .body
+                .as_deref()
+                .map(str::trim)
+                .filter(|body| !body.is_empty())
+                .unwrap_or("(no release notes)");
+            format!("{}\n{}", title, notes)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// HTTP client with the User-Agent GitHub requires
+fn client(timeout: Duration) -> HandlerResult<reqwest::Client> {
+    reqwest::Client::builder()
+        .user_agent(concat!("ai-coder/", env!("CARGO_PKG_VERSION")))
+        .timeout(timeout)
+        .build()
+        .map_err(|e| HandlerError::Other(format!("Could not create HTTP client: {}", e)))
+}
+
+/// Fetch the releases of `repository` (`owner/name`)
+pub async fn fetch_releases(repository: &str) -> HandlerResult<Vec<Release>> {
+    let url = format!("https://api.github.com/repos/{}/releases", repository);
+    let response = client(CHECK_TIMEOUT)?
+        .get(&url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| HandlerError::Other(format!("Could not reach GitHub: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(HandlerError::Other(format!(
+            "GitHub returned {} for {}",
+            response.status(),
+            url
+        )));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| HandlerError::Other(format!("Invalid release list: {}", e)))
+}
+
+/// Download a release asset
+pub async fn download(asset: &Asset) -> HandlerResult<Vec<u8>> {
+    let response = client(DOWNLOAD_TIMEOUT)?
+        .get(&asset.browser_download_url)
+        .header("Accept", "application/octet-stream")
+        .send()
+        .await
+        .map_err(|e| HandlerError::Other(format!("Download failed: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(HandlerError::Other(format!(
+            "Download of {} failed with {}",
+            asset.name,
+            response.status()
+        )));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| HandlerError::Other(format!("Download failed: {}", e)))?;
+    if asset.size > 0 && bytes.len() as u64 != asset.size {
+        return Err(HandlerError::Other(format!(
+            "Download of {} is incomplete ({} of {} bytes)",
+            asset.name,
+            bytes.len(),
+            asset.size
+        )));
+    }
+    Ok(bytes.to_vec())
+}
+
+/// Download the binary `asset` of `release` and check it against its `.sha256` file
+///
+/// A binary published without a checksum is refused.
+pub async fn download_verified(release: &Release, asset: &Asset) -> HandlerResult<Vec<u8>> {
+    let checksum = release.checksum_for(asset).ok_or_else(|| {
+        HandlerError::Other(format!(
+            "{} has no {}.sha256 to verify it; download it from {}",
+            release.tag_name, asset.name, release.html_url
+        ))
+    })?;
+    let checksum = download(checksum).await?;
+    let binary = download(asset).await?;
+    verify_checksum(&binary, &String::from_utf8_lossy(&checksum))?;
+    Ok(binary)
+}
+
+/// Replace the executable at `exe` with `binary`
+///
+/// The old executable is kept next to it with an `.old` suffix, which also
+/// works on Windows where a running executable cannot be overwritten.
+pub fn replace_binary(exe: &Path, binary: &[u8]) -> io::Result<PathBuf> {
+    let staged = with_suffix(exe, "new");
+    let backup = with_suffix(exe, "old");
+    fs::write(&staged, binary)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(exe)
+            .map(|meta| meta.permissions().mode())
+            .unwrap_or(0o755);
+        fs::set_permissions(&staged, fs::Permissions::from_mode(mode | 0o111))?;
+    }
+
+    let _ = fs::remove_file(&backup);
+    fs::rename(exe, &backup)?;
+    if let Err(e) = fs::rename(&staged, exe) {
+        // Put the original back so the install keeps working
+        let _ = fs::rename(&backup, exe);
+        let _ = fs::remove_file(&staged);
+        return Err(e);
+    }
+    Ok(backup)
+}
+
+/// `path` with `.suffix` appended to its file name
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
@@ -0,0 +1,60 @@
+//! The working directory of commands
+//!
+//! `!` commands, code blocks and `/pty` run in the directory of the app,
+//! which `/cd` changes. A shell started for `!cd` would forget the change as
+//! soon as it exits, so a plain `!cd <path>` changes the directory of the
+//! app as `/cd` does.
+
+use crate::handlers::{HandlerError, HandlerResult};
+use std::path::{Path, PathBuf};
+
+/// Characters that make `cd` part of a larger shell command
+const SHELL_SYNTAX: [&str; 7] = ["&&", "||", ";", "|", ">", "<", "$("];
+
+/// The arguments of a bash command that is only `cd`, such as `cd src`
+pub fn cd_args(command: &str) -> Option<&str> {
+    let command = command.trim();
+    let args = command.strip_prefix("cd")?;
+    if !args.is_empty() && !args.starts_with(char::is_whitespace) {
+        return None;
+    }
+    // `cd src && make` changes the directory for `make` only
+    if SHELL_SYNTAX.iter().any(|syntax| args.contains(syntax)) || args.contains('`') {
+        return None;
+    }
+    Some(args.trim())
+}
+
+/// The directory `args` of `cd` lead to from `current`
+///
+/// No arguments or `~` lead to the home directory. Quotes are removed as a
+/// shell would; the result is canonical and must be an existing directory.
+pub fn resolve(current: &Path, args: &str) -> HandlerResult<PathBuf> {
+    let words = shell_words::split(args)
+        .map_err(|e| HandlerError::Parse(format!("Failed to parse the path: {}", e)))?;
+    let target = match words.as_slice() {
+        [] => crate::utils::get_home_dir(),
+        [path] => match path.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+                crate::utils::get_home_dir().join(rest.trim_start_matches('/'))
+            }
+            _ => current.join(path),
+        },
+        _ => {
+            return Err(HandlerError::Other(
+                "cd takes a single directory; quote paths with spaces".to_string(),
+            ));
+        }
+    };
+
+    let dir = target
+        .canonicalize()
+        .map_err(|_| HandlerError::Other(format!("No such directory: {}", target.display())))?;
+    if !dir.is_dir() {
+        return Err(HandlerError::Other(format!(
+            "Not a directory: {}",
+            target.display()
+        )));
+    }
+    Ok(dir)
+}
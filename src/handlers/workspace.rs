@@ -0,0 +1,106 @@
+//! Workspace boundaries
+//!
+//! Files read or written on behalf of a command or a response (attachments,
+//! the sources of `/doc` and `/refactor`, and file blocks being saved) must
+//! lie under the workspace root and must not match one of the deny globs of
+//! the `workspace` config section. A response that talks the app into
+//! touching `~/.ssh/id_rsa` or `../../etc/passwd` gets an error instead.
+//!
+//! Paths are resolved before they are compared, including symbolic links of
+//! the part that exists, so a link inside the root cannot lead out of it.
+
+use crate::config::{WorkspaceConfig, get_config};
+use crate::handlers::{HandlerError, HandlerResult};
+use crate::utils::glob::Glob;
+use std::path::{Component, Path, PathBuf};
+
+/// Check a path against the configured workspace
+///
+/// Relative paths are taken relative to `base`, which is also the root while
+/// `workspace.root` is empty. Returns the resolved path.
+pub fn check(base: &Path, path: &Path) -> HandlerResult<PathBuf> {
+    check_with(&get_config().workspace, base, path)
+}
+
+/// Check a path against the given workspace settings
+pub fn check_with(config: &WorkspaceConfig, base: &Path, path: &Path) -> HandlerResult<PathBuf> {
+    let root = root(config, base);
+    let resolved = resolve(&base.join(path));
+    let Ok(relative) = resolved.strip_prefix(&root) else {
+        return Err(HandlerError::Other(format!(
+            "🚫 {} is outside the workspace {}; set workspace.root to allow it",
+            path.display(),
+            root.display()
+        )));
+    };
+
+    for pattern in &config.deny {
+        let expanded = expand_home(pattern);
+        let denied = if Path::new(&expanded).is_absolute() {
+            Glob::new(&expanded).is_ok_and(|glob| glob.is_match(&resolved))
+        } else {
+            Glob::new(&expanded).is_ok_and(|glob| glob.is_match(relative))
+        };
+        if denied {
+            return Err(HandlerError::Other(format!(
+                "🚫 {} matches {} in workspace.deny and is never read or written",
+                path.display(),
+                pattern
+            )));
+        }
+    }
+    Ok(resolved)
+}
+
+/// The workspace root: `workspace.root` or else `base`, resolved
+pub fn root(config: &WorkspaceConfig, base: &Path) -> PathBuf {
+    if config.root.trim().is_empty() {
+        resolve(base)
+    } else {
+        resolve(&base.join(expand_home(config.root.trim())))
+    }
+}
+
+/// Replace a leading `~/` with the home directory
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => {
+            let home = home.canonicalize().unwrap_or(home);
+            format!("{}/{}", home.to_string_lossy().trim_end_matches('/'), rest)
+        }
+        _ => path.to_string(),
+    }
+}
+
+/// Normalize `..` and `.` and resolve symbolic links of the part that exists
+fn resolve(path: &Path) -> PathBuf {
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normal.pop();
+            }
+            Component::CurDir => {}
+            other => normal.push(other),
+        }
+    }
+
+    // Canonicalize the longest existing ancestor and append the rest
+    let mut existing = normal.as_path();
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(mut real) = existing.canonicalize() {
+            for part in rest.iter().rev() {
+                real.push(part);
+            }
+            return real;
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return normal,
+        }
+    }
+}
@@ -21,9 +21,11 @@
 //! The application is organized into several key modules:
 //! - `ai` - AI client implementations with thread-safe interfaces
 //! - `app` - Core application state and concurrent task management
+//! - `cli` - Command-line options such as `--safe`
 //! - `config` - Configuration management with runtime updates
 //! - `event` - Event handling with abort signal support
 //! - `handlers` - Command execution in background tasks
+//! - `platform` - Shell, `PATH` lookup and terminal differences between Unix and Windows
 //! - `tui` - Terminal interface with non-blocking rendering
 //! - `ui` - UI rendering with progress indicators
 //! - `utils` - Utility functions and logging
@@ -43,9 +45,11 @@
 
 pub mod ai;
 pub mod app;
+pub mod cli;
 pub mod config;
 pub mod event;
 pub mod handlers;
+pub mod platform;
 pub mod tui;
 pub mod ui;
 pub mod utils;
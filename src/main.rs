@@ -3,15 +3,66 @@
 //! This is the main entry point for the AI Coder Interface application.
 //! It initializes the application and runs the main event loop.
 
-use ai_coder_interface_rs::utils::{log_error, log_info};
-use ai_coder_interface_rs::{App, Tui, cleanup, init};
+use ai_coder_interface_rs::utils::{crash, log_error, log_info};
+use ai_coder_interface_rs::utils::tasks::TaskId;
+use ai_coder_interface_rs::handlers::safe;
+use ai_coder_interface_rs::{App, Event, Tui, cleanup, cli, init};
 use anyhow::Result;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
+/// What woke the main loop
+enum Wake {
+    /// Terminal input or a tick
+    Event(Event),
+    /// A background task asked for a repaint
+    Update,
+    /// A tracked task changed
+    Task(TaskId),
+    /// An animation step or the clock is due
+    Timer,
+}
+
 /// Main entry point
 #[tokio::main]
 async fn main() -> Result<()> {
+    let options = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    if options.version {
+        println!("ai-coder {}", env!("CARGO_PKG_VERSION"));
+        return Ok(());
+    }
+    if options.safe {
+        safe::enable();
+    }
+
+    let result = run(&options).await;
+
+    // Fatal errors get the same diagnostic report as panics
+    if let Err(e) = &result {
+        log_error(&format!("Fatal error: {:#}", e)).ok();
+        match crash::write_report(&format!("Fatal error: {:#}", e)) {
+            Ok(path) => eprintln!("A diagnostic report was written to {}", path.display()),
+            Err(report_error) => eprintln!("Failed to write crash report: {}", report_error),
+        }
+    }
+    result
+}
+
+/// Run the application until the user exits
+async fn run(options: &cli::Options) -> Result<()> {
+    // Write a diagnostic report if the app panics
+    crash::install_panic_hook();
+
     // Initialize application
     if let Err(e) = init() {
         eprintln!("Failed to initialize application: {}", e);
@@ -34,13 +85,26 @@ async fn main() -> Result<()> {
     // Create application instance with abort flag
     let mut app = App::new();
     app.set_global_abort(global_abort);
+    crash::watch_tasks(app.task_manager.clone());
 
-    // Initialize terminal with 250ms tick rate
-    let mut tui = Tui::new(250)?;
+    // Initialize terminal; the main loop wakes itself for animations, so ticks are rare
+    let mut tui = Tui::new(1000)?;
 
     // Create a channel for UI updates
     let (ui_tx, mut ui_rx) = tokio::sync::mpsc::channel::<()>(32);
     app.ui_notifier = Some(ui_tx);
+
+    // Watch connectivity so cloud prompts can be rerouted or queued while offline
+    app.start_connectivity_monitor();
+    app.start_indexer(); // Keep the project index fresh while index.enabled is on
+
+    // Offer to resume prompts left unfinished by the previous session
+    app.offer_pending_resume();
+
+    // `ai-coder share` serves the session from the start
+    if options.share {
+        app.start_share().await;
+    }
     
     // Create a task update channel
     let mut task_rx = app.task_manager.get_update_receiver();
@@ -50,37 +114,54 @@ async fn main() -> Result<()> {
         "🚀 AI Coder Interface\nCurrent directory: {}\n",
         std::env::current_dir()?.display()
     ));
+    if safe::is_enabled() {
+        app.add_output(
+            "🔒 Safe mode: shell commands, code execution, file writes, commits, updates and sync are blocked".to_string(),
+        );
+    }
+    app.show_usage_summary(); // Yesterday's and this week's usage per provider
+    app.check_encryption(); // Warn when stored data cannot be encrypted
+    app.check_quiet_hours(); // Warn about maintenance windows that do not parse
     
     // Start the main loop
     while app.running {
-        // Render UI
-        tui.draw(|f| {
-            ai_coder_interface_rs::ui::render(f, &mut app);
-        })?;
-
-        // Set up concurrent handling of events, UI updates and background tasks
-        tokio::select! {
-            // Handle user input events with a timeout to keep UI responsive
-            event_result = tokio::time::timeout(tokio::time::Duration::from_millis(50), app.handle_events(&mut tui)) => {
-                match event_result {
-                    Ok(result) => {
-                        if let Err(e) = result {
-                            log_error(&format!("Error handling events: {}", e)).ok();
-                        }
-                    },
-                    Err(_) => {
-                        // Timeout is expected and helps keep the UI responsive
-                    }
+        // Render UI only when something changed, less often on slow terminals
+        let now = std::time::Instant::now();
+        if app.needs_redraw && app.frame_pacer.should_draw(now) {
+            app.needs_redraw = false;
+            // Time the rendering apart from the flush that follows it
+            let mut rendering = std::time::Duration::ZERO;
+            tui.draw(|f| {
+                let started = std::time::Instant::now();
+                ai_coder_interface_rs::ui::render(f, &mut app);
+                rendering = started.elapsed();
+            })?;
+            app.record_frame(now, rendering);
+        }
+
+        // Sleep until there is input, an update, or an animation step is due
+        let wakeup = app.next_wakeup(now);
+        let wake = tokio::select! {
+            Ok(event) = tui.next_event() => Wake::Event(event),
+            Some(()) = ui_rx.recv() => Wake::Update,
+            Some(task_id) = task_rx.recv() => Wake::Task(task_id),
+            _ = tokio::time::sleep(wakeup) => Wake::Timer,
+        };
+
+        match wake {
+            Wake::Event(event) => {
+                let tick = matches!(event, Event::Tick);
+                if let Err(e) = app.handle_event(event, &mut tui).await {
+                    log_error(&format!("Error handling events: {}", e)).ok();
+                }
+                // Ticks only redraw when an animation or the clock moved
+                if !tick {
+                    app.request_redraw();
                 }
-            },
-            
-            // Process any UI update messages
-            _ = ui_rx.recv() => {
-                // UI update requested, nothing specific to do as we'll redraw at the start of the loop
-            },
-            
-            // Process task updates
-            Some(task_id) = task_rx.recv() => {
+            }
+            Wake::Update => app.request_redraw(),
+            Wake::Task(task_id) => {
+                app.request_redraw();
                 // Task update received, check for status changes
                 if let Some(task) = app.task_manager.get_task(task_id) {
                     // For completed tasks, add a notification to the output
@@ -105,64 +186,79 @@ async fn main() -> Result<()> {
                                 if let Some(mut rx) = app.task_manager.take_response_channel(task_id) {
                                     // Try to receive the response (non-blocking)
                                     if let Ok(Some(response_content)) = rx.try_recv() {
-                                        // Find and remove all spinner characters from the output
-                                        for spinner_char in ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"] {
-                                            // Replace spinner in output lines
-                                            for i in 0..app.output_lines.len() {
-                                                if app.output_lines[i].trim() == spinner_char {
-                                                    app.output_lines[i] = "".to_string();
-                                                }
-                                            }
-                                            
-                                            // Also replace in the main output string
-                                            app.output = app.output.replace(spinner_char, "");
-                                        }
-                                        
-                                        // Replace any double newlines that might have been created
-                                        app.output = app.output.replace("\n\n\n", "\n\n");
-                                        
-                                        // Add the response with a single newline
-                                        app.add_output(format!("{}", response_content));
+                                        app.handle_ai_response(task_id, response_content);
                                     }
                                 }
+                            } else {
+                                // Failed or cancelled tasks have no response to handle
+                                app.forget_task(task_id);
+                                app.stop_chain("the AI prompt did not finish");
                             }
                         }
                     }
                 }
-                // Redraw will happen at the start of the next loop
-            },
-            
-            // Add an explicit small delay to prevent CPU hogging
-            _ = tokio::time::sleep(tokio::time::Duration::from_millis(16)) => {
-                // This represents roughly 60fps and gives other tasks time to run
-                app.update_cursor_blink(); // Update cursor blinking state
-                
-                // Cleanup any completed background tasks
-                app.background_tasks.retain(|task| !task.is_finished());
-                
-                // Clean up old tasks from task manager periodically
-                // Initialize a timer if it doesn't exist yet
-                if !app.has_cleanup_timer() {
-                    app.init_cleanup_timer();
-                }
-                
-                // Check if we need to perform cleanup (every 60 seconds)
-                if app.should_perform_cleanup() {
-                    // Clean up tasks older than 30 minutes
-                    app.task_manager.cleanup_old_tasks();
-                    // Reset the timer
-                    app.reset_cleanup_timer();
-                }
             }
+            Wake::Timer => app.tick(), // Cursor blink, spinner frames and the clock
+        }
+
+        app.check_connectivity(); // Send queued prompts once back online
+        app.collect_stream_chunks(); // Show chat responses as they are generated
+        app.collect_summaries(); // Attach summaries of large attachments once ready
+        app.collect_fetches(); // Attach fetched pages once downloaded
+        app.collect_images(&mut tui); // Save and show generated images
+        app.collect_updates(); // Report release checks and installed updates
+        app.collect_catalogs(); // List the OpenRouter models once fetched
+        app.collect_index_searches(); // Attach chunks found by /index search
+        app.collect_lsp_results(); // Attach definitions, references and hover docs
+        app.collect_logins(); // Show login codes and results
+        app.collect_transcripts(); // Insert push-to-talk transcripts into the input
+        app.collect_speech_errors(); // Report responses that could not be read aloud
+        app.collect_titles(); // Title the session once the model has named it
+        app.collect_sync(); // Report pushes and apply pulled sessions
+        app.collect_template_updates(); // Report pulls of the team templates
+        app.collect_evals(); // Show the comparison tables of /eval
+        app.start_waiting_writes(); // Write accepted file changes once a task slot is free
+        app.collect_bash_queue(); // Show the output of bash blocks chosen from a response
+        app.collect_bash_output(); // Show the output of `!` commands as it is printed
+        app.collect_pty_output(); // Show the output of the `/pty` command in its pane
+        tui.events().pass_keys_through(app.interactive.is_some()); // Ctrl+C and Ctrl+D go to it
+        app.run_chain(&mut tui).await; // Continue chained commands after AI responses
+        app.update_terminal_status(); // Busy marker in the title, progress in the taskbar
+        app.publish_share(); // Keep the viewers of a shared session up to date
+
+        // Cleanup any completed background tasks
+        app.background_tasks.retain(|task| !task.is_finished());
+
+        // Clean up old tasks from task manager periodically
+        // Initialize a timer if it doesn't exist yet
+        if !app.has_cleanup_timer() {
+            app.init_cleanup_timer();
+        }
+
+        // Check if we need to perform cleanup (every 60 seconds)
+        if app.should_perform_cleanup() {
+            // Clean up tasks older than 30 minutes
+            app.task_manager.cleanup_old_tasks();
+            // Reset the timer
+            app.reset_cleanup_timer();
         }
     }
 
+    // Keep the conversation for /sessions
+    app.save_session();
+
     // Log application exit
     log_info("Application exiting normally").ok();
 
     // Exit the terminal interface
+    app.restore_terminal_status();
     tui.exit()?;
 
+    // Output requested with /print goes to the normal screen
+    if let Some(text) = app.print_on_exit.take() {
+        print!("{}", text);
+    }
+
     // Clean up resources
     cleanup()?;
 
@@ -0,0 +1,305 @@
+//! Platform differences
+//!
+//! Everything that behaves differently on Windows lives here so the rest of
+//! the crate stays platform independent:
+//!
+//! - Shell commands run through `sh -c` on Unix and PowerShell on Windows,
+//!   where common commands such as `ls` or `cat` are shell aliases rather
+//!   than programs.
+//! - Programs are looked up on `PATH` directly, trying the `PATHEXT`
+//!   extensions on Windows, instead of spawning `which` or `where`.
+//! - Windows terminals report key releases as well as presses.
+//! - Files holding secrets are made readable by their owner only on Unix;
+//!   on Windows the user profile directory is already private.
+//! - Recording from the microphone uses `arecord` on Linux and `sox`
+//!   elsewhere. Recorders are stopped with SIGINT on Unix so they can finish
+//!   the file; Windows has no such signal and they are killed.
+//! - Responses are read aloud by `say` on macOS, `espeak` on Linux and the
+//!   .NET speech synthesizer on Windows; synthesized audio is played with
+//!   `afplay`, `aplay` or a .NET sound player.
+//! - Interactive commands run in a pseudo-terminal, which is a ConPTY on
+//!   Windows.
+//! - `!` commands run in their own process group on Unix so aborting them
+//!   also stops what they started; on Windows `taskkill /T` stops the tree.
+
+use crossterm::event::{KeyEvent, KeyEventKind};
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+
+/// Whether the app was built for Windows
+pub const IS_WINDOWS: bool = cfg!(windows);
+
+/// Shell program and the arguments that make it run a command string
+pub fn shell() -> (String, Vec<&'static str>) {
+    if IS_WINDOWS {
+        // PowerShell 7 when installed, Windows PowerShell otherwise
+        let program = if find_in_path("pwsh").is_some() {
+            "pwsh"
+        } else {
+            "powershell"
+        };
+        (
+            program.to_string(),
+            vec!["-NoLogo", "-NoProfile", "-NonInteractive", "-Command"],
+        )
+    } else {
+        ("sh".to_string(), vec!["-c"])
+    }
+}
+
+/// A command that runs `command` through the platform shell
+pub fn shell_command(command: &str) -> Command {
+    let (program, args) = shell();
+    let mut cmd = Command::new(program);
+    cmd.args(args).arg(command);
+    cmd
+}
+
+/// Whether `command` has to go through the shell instead of being run directly
+///
+/// On Unix only glob patterns need the shell; on Windows every command does,
+/// since many everyday commands are PowerShell aliases.
+pub fn needs_shell(command: &str) -> bool {
+    IS_WINDOWS || command.contains('*') || command.contains('?') || command.contains('[')
+}
+
+/// Name of the user's shell, for display
+pub fn shell_name() -> String {
+    if IS_WINDOWS {
+        return shell().0;
+    }
+    env::var("SHELL").unwrap_or_else(|_| String::from("unknown"))
+}
+
+/// Full path of `program` if it is found on `PATH`
+pub fn find_in_path(program: &str) -> Option<PathBuf> {
+    let candidate = Path::new(program);
+    if candidate.components().count() > 1 {
+        return is_executable(candidate).then(|| candidate.to_path_buf());
+    }
+
+    let extensions = executable_extensions();
+    env::split_paths(&env::var_os("PATH")?).find_map(|dir| {
+        extensions.iter().find_map(|ext| {
+            let path = dir.join(format!("{}{}", program, ext));
+            is_executable(&path).then_some(path)
+        })
+    })
+}
+
+/// Suffixes tried when looking up a program: `PATHEXT` on Windows
+fn executable_extensions() -> Vec<String> {
+    let mut extensions = vec![String::new()];
+    if IS_WINDOWS {
+        let pathext = env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+        extensions.extend(
+            pathext
+                .split(';')
+                .filter(|ext| !ext.is_empty())
+                .map(|ext| ext.to_lowercase()),
+        );
+    }
+    extensions
+}
+
+/// Whether `path` is a file the current user can run
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata()
+            .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.is_file()
+    }
+}
+
+/// Write a file only the current user can read, replacing it atomically
+pub fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = path.with_extension("tmp");
+    // A leftover file would keep its permissions
+    let _ = fs::remove_file(&tmp);
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(&tmp)?.write_all(contents)?;
+    fs::rename(&tmp, path)
+}
+
+/// Default command of the Python interpreter
+pub fn python_command() -> &'static str {
+    if IS_WINDOWS { "python" } else { "python3" }
+}
+
+/// Default command recording 16 kHz mono WAV from the microphone into `{file}`
+pub fn record_command() -> &'static str {
+    if cfg!(target_os = "linux") {
+        "arecord -q -f S16_LE -r 16000 -c 1 {file}"
+    } else if IS_WINDOWS {
+        "sox -q -t waveaudio default -r 16000 -c 1 -b 16 {file}"
+    } else {
+        "sox -q -d -r 16000 -c 1 -b 16 {file}"
+    }
+}
+
+/// Default command reading the text on its stdin aloud
+pub fn speak_command() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "say"
+    } else if IS_WINDOWS {
+        "powershell -NoProfile -Command \"Add-Type -AssemblyName System.Speech; (New-Object System.Speech.Synthesis.SpeechSynthesizer).Speak([Console]::In.ReadToEnd())\""
+    } else {
+        "espeak --stdin"
+    }
+}
+
+/// Default command playing the WAV file `{file}`
+pub fn play_command() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "afplay {file}"
+    } else if IS_WINDOWS {
+        "powershell -NoProfile -Command \"(New-Object Media.SoundPlayer '{file}').PlaySync()\""
+    } else {
+        "aplay -q {file}"
+    }
+}
+
+/// Ask a child process to stop as if Ctrl+C was pressed in its terminal
+pub fn interrupt(child: &mut Child) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        let pid = libc::pid_t::try_from(child.id())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "process id out of range"))?;
+        // SAFETY: kill only sends a signal to the process we spawned
+        if unsafe { libc::kill(pid, libc::SIGINT) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        child.kill()
+    }
+}
+
+/// Start `command` in its own process group, so [`kill_tree`] reaches the
+/// processes it starts as well
+pub fn own_process_group(command: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = command;
+    }
+}
+
+/// Kill the process `pid` started with [`own_process_group`] and every
+/// process it started
+pub fn kill_tree(pid: u32) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        let pid = libc::pid_t::try_from(pid)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "process id out of range"))?;
+        // SAFETY: a negative pid sends the signal to the process group we created
+        if unsafe { libc::kill(-pid, libc::SIGKILL) } == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid.to_string()])
+            .output()
+            .map(|_| ())
+    }
+}
+
+/// A command running in a pseudo-terminal
+pub struct PtyProcess {
+    /// The command
+    pub child: Box<dyn portable_pty::Child + Send + Sync>,
+    /// What the command prints
+    pub output: Box<dyn io::Read + Send>,
+    /// Where keys for the command are written
+    pub input: Box<dyn io::Write + Send>,
+    /// Controlling side of the pty, open as long as the command runs
+    pub master: Box<dyn portable_pty::MasterPty + Send>,
+}
+
+impl std::fmt::Debug for PtyProcess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PtyProcess")
+            .field("child", &self.child)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Start `command` in a new pseudo-terminal of `cols` by `rows`
+///
+/// The pty is the command's controlling terminal, so Ctrl+C typed into it
+/// reaches the command as on a real terminal. On Windows it is a ConPTY.
+pub fn spawn_in_pty(command: Command, cols: u16, rows: u16) -> io::Result<PtyProcess> {
+    let pair = portable_pty::native_pty_system()
+        .openpty(portable_pty::PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(io::Error::other)?;
+
+    let mut builder = portable_pty::CommandBuilder::new(command.get_program());
+    builder.args(command.get_args());
+    for (key, value) in command.get_envs() {
+        match value {
+            Some(value) => builder.env(key, value),
+            None => builder.env_remove(key),
+        }
+    }
+    if let Some(dir) = command.get_current_dir() {
+        builder.cwd(dir);
+    }
+
+    let child = pair
+        .slave
+        .spawn_command(builder)
+        .map_err(io::Error::other)?;
+    // Without our copy of the terminal side, reading the pty ends once the
+    // command exits
+    drop(pair.slave);
+    let output = pair.master.try_clone_reader().map_err(io::Error::other)?;
+    let input = pair.master.take_writer().map_err(io::Error::other)?;
+    Ok(PtyProcess {
+        child,
+        output,
+        input,
+        master: pair.master,
+    })
+}
+
+/// Whether a key event should be handled
+///
+/// Windows terminals send an event when a key is released as well; handling
+/// both would type every character twice.
+pub fn is_key_press(key: &KeyEvent) -> bool {
+    key.kind != KeyEventKind::Release
+}
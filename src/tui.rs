@@ -64,6 +64,11 @@ impl Tui {
         &self.events
     }
 
+    /// Wait for the next terminal event
+    pub async fn next_event(&mut self) -> Result<crate::event::Event> {
+        self.events.next().await
+    }
+
     pub fn toggle_raw_mode(&mut self) -> io::Result<()> {
         if self.raw_mode_enabled {
             disable_raw_mode()?;
@@ -102,6 +107,33 @@ impl Tui {
         Ok(())
     }
 
+    /// Hand the terminal over to another program (e.g. an editor)
+    ///
+    /// Event polling is paused and the normal screen is restored until
+    /// [`Tui::resume`] is called.
+    pub fn suspend(&mut self) -> Result<()> {
+        self.events.pause();
+        disable_raw_mode()?;
+        crossterm::execute!(
+            io::stdout(),
+            LeaveAlternateScreen,
+            DisableMouseCapture,
+            cursor::Show
+        )?;
+        self.raw_mode_enabled = false;
+        Ok(())
+    }
+
+    /// Take the terminal back after [`Tui::suspend`]
+    pub fn resume(&mut self) -> Result<()> {
+        enable_raw_mode()?;
+        crossterm::execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        self.raw_mode_enabled = true;
+        self.terminal.clear()?;
+        self.events.resume();
+        Ok(())
+    }
+
     pub fn draw<F>(&mut self, f: F) -> Result<()>
     where
         F: FnOnce(&mut Frame),
@@ -9,6 +9,7 @@ use ratatui::{
     text::Text,
     widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap},
 };
+use unicode_width::UnicodeWidthStr;
 
 /// Renders a popup message box
 #[allow(dead_code)]
@@ -105,10 +106,19 @@ pub fn render_help_overlay(f: &mut Frame, background_color: Color, text_color: C
     ------------------
     Up/Down: Navigate history
     Shift+Up/Down: Select text
+    Ctrl+Up/Down: Resize input box
     Ctrl+C: Copy selection or exit
     PageUp/Down: Scroll output
     Ctrl+K: Show context menu
-    Ctrl+T: Show active tasks
+    Ctrl+T: Show active tasks (Tab filters by label)
+    Ctrl+Q: Quote selection into input
+    Alt+Up/Down: Select output section
+    Enter/Space: Expand/collapse section
+    j/k: Previous/next message
+    y/r/q: Copy/re-ask/quote message
+    d/p: Drop from context/pin message
+    { / }: Previous/next prompt
+    :<n> / '': Jump to line / jump back
     Esc: Cancel selection / Close popup
     
     COMMAND PREFIXES
@@ -116,6 +126,7 @@ pub fn render_help_overlay(f: &mut Frame, background_color: Color, text_color: C
     No prefix: AI mode
     !: Bash command
     /: Application command
+    &&: Chain commands (/config ... && prompt)
     
     Press ESC to close help
     ";
@@ -167,6 +178,8 @@ pub fn render_tasks_popup(
     background_color: Color,
 ) {
     use crate::ai::types::TaskStatus;
+    use crate::ui::progress;
+    use crate::utils::tasks::TaskPriority;
     use ratatui::layout::{Constraint, Direction, Layout};
 
     // Get tasks
@@ -248,10 +261,20 @@ pub fn render_tasks_popup(
             // Format progress
             let progress_text = if let Some(progress) = &task.progress {
                 if let Some(percent) = progress.completion_percent {
-                    if task.status == TaskStatus::Running {
+                    if task.status == TaskStatus::Running && app.spinner.progress_bar {
                         format!(
-                            " {:.1}% ({}/s) ",
-                            percent, progress.tokens_per_second as u32
+                            " {} {:.1}% ({}/s, ETA {}) ",
+                            progress::bar(percent, 10),
+                            percent,
+                            progress.tokens_per_second as u32,
+                            progress.format_remaining_time()
+                        )
+                    } else if task.status == TaskStatus::Running {
+                        format!(
+                            " {:.1}% ({}/s, ETA {}) ",
+                            percent,
+                            progress.tokens_per_second as u32,
+                            progress.format_remaining_time()
                         )
                     } else {
                         format!(" {:.1}% ", percent)
@@ -263,8 +286,29 @@ pub fn render_tasks_popup(
                 "   -   ".to_string()
             };
 
-            // Format task name with id
-            let task_text = format!("  {} ({})", task.name, task.id.short());
+            // Format task name with id, priority and labels
+            let priority_text = match task.priority {
+                TaskPriority::High => "↑ ",
+                TaskPriority::Normal => "",
+                TaskPriority::Low => "↓ ",
+            };
+            let task_text = format!(
+                "  {}{} ({}) [{}]",
+                priority_text,
+                task.name,
+                task.id.short(),
+                task.labels.join(", ")
+            );
+
+            // Transient state such as a rate limit wait, or the tasks this one waits on
+            let note_text = match &task.note {
+                Some(note) => format!(" — {}", note),
+                None if task.status == TaskStatus::Pending && !task.depends_on.is_empty() => {
+                    let after: Vec<String> = task.depends_on.iter().map(|id| id.short()).collect();
+                    format!(" ⇠ after {}", after.join(", "))
+                }
+                None => String::new(),
+            };
 
             active_task_lines.push(ratatui::text::Line::from(vec![
                 ratatui::text::Span::styled(status_text, status_style),
@@ -274,6 +318,7 @@ pub fn render_tasks_popup(
                 ratatui::text::Span::raw(progress_text),
                 ratatui::text::Span::raw(" │ "),
                 ratatui::text::Span::raw(task_text),
+                ratatui::text::Span::styled(note_text, Style::default().fg(Color::Yellow)),
             ]));
         }
     }
@@ -305,8 +350,13 @@ pub fn render_tasks_popup(
             // Format duration
             let duration_text = format!(" {} ", task.format_duration());
 
-            // Format task name with id
-            let task_text = format!("  {} ({})", task.name, task.id.short());
+            // Format task name with id and labels
+            let task_text = format!(
+                "  {} ({}) [{}]",
+                task.name,
+                task.id.short(),
+                task.labels.join(", ")
+            );
 
             recent_task_lines.push(ratatui::text::Line::from(vec![
                 ratatui::text::Span::styled(status_text, status_style),
@@ -331,7 +381,15 @@ pub fn render_tasks_popup(
             " Ctrl+C ",
             Style::default().bg(accent_color).fg(background_color),
         ),
-        ratatui::text::Span::raw(" Cancel task"),
+        ratatui::text::Span::raw(" Cancel task  "),
+        ratatui::text::Span::styled(
+            " Tab ",
+            Style::default().bg(accent_color).fg(background_color),
+        ),
+        ratatui::text::Span::raw(format!(
+            " Label: {}",
+            app.task_label_filter.as_deref().unwrap_or("all")
+        )),
     ]);
 
     // Render popup
@@ -372,3 +430,458 @@ pub fn render_tasks_popup(
     // Render footer
     f.render_widget(Paragraph::new(footer), chunks[4]);
 }
+
+/// Renders a confirmation popup with a scrollable, diff-colored preview
+pub fn render_confirmation_popup(
+    f: &mut Frame,
+    confirmation: &crate::app::Confirmation,
+    primary_color: Color,
+    accent_color: Color,
+    background_color: Color,
+) {
+    use ratatui::layout::{Constraint, Direction, Layout};
+    use ratatui::text::{Line, Span};
+
+    // Use most of the screen so diffs stay readable
+    let size = f.size();
+    let width = size.width.saturating_sub(4).max(20).min(size.width);
+    let height = size.height.saturating_sub(4).max(6).min(size.height);
+    let popup_area = Rect::new(
+        (size.width.saturating_sub(width)) / 2,
+        (size.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    );
+
+    let popup_block = Block::default()
+        .title(format!(" {} ", confirmation.title))
+        .title_alignment(ratatui::layout::Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(primary_color))
+        .style(Style::default().bg(background_color));
+
+    let inner_area = popup_block.inner(popup_area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner_area);
+
+    // Color diff lines like `git diff`
+    let lines: Vec<Line> = confirmation
+        .body
+        .lines()
+        .map(|line| {
+            let style = if line.starts_with("+++") || line.starts_with("---") {
+                Style::default().fg(accent_color)
+            } else if line.starts_with('+') {
+                Style::default().fg(Color::Green)
+            } else if line.starts_with('-') {
+                Style::default().fg(Color::Red)
+            } else if line.starts_with("@@") {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default()
+            };
+            Line::from(Span::styled(line.to_string(), style))
+        })
+        .collect();
+
+    let footer = Line::from(vec![
+        Span::styled(
+            " Enter/y ",
+            Style::default().bg(accent_color).fg(background_color),
+        ),
+        Span::raw(" Confirm  "),
+        Span::styled(
+            " Esc/n ",
+            Style::default().bg(accent_color).fg(background_color),
+        ),
+        Span::raw(" Cancel  "),
+        Span::styled(
+            " ↑/↓ ",
+            Style::default().bg(accent_color).fg(background_color),
+        ),
+        Span::raw(" Scroll"),
+    ]);
+
+    // Clear whatever is underneath before drawing the popup
+    f.render_widget(ratatui::widgets::Clear, popup_area);
+    f.render_widget(popup_block, popup_area);
+    f.render_widget(
+        Paragraph::new(lines).scroll((confirmation.scroll, 0)),
+        chunks[0],
+    );
+    f.render_widget(Paragraph::new(footer), chunks[1]);
+}
+
+/// Renders the debug panel with render statistics and the frame-time histogram
+pub fn render_debug_popup(
+    f: &mut Frame,
+    app: &crate::app::App,
+    primary_color: Color,
+    accent_color: Color,
+    background_color: Color,
+) {
+    use ratatui::layout::{Constraint, Direction, Layout};
+    use ratatui::text::{Line, Span};
+
+    let histogram = &app.frame_times;
+    let buckets = histogram.buckets();
+
+    let size = f.size();
+    let width = 60.min(size.width.saturating_sub(4));
+    let height = (buckets.len() as u16 + 9).min(size.height.saturating_sub(4));
+    let popup_area = Rect::new(
+        (size.width.saturating_sub(width)) / 2,
+        (size.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    );
+
+    let popup_block = Block::default()
+        .title(" Debug ")
+        .title_alignment(ratatui::layout::Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(primary_color))
+        .style(Style::default().bg(background_color));
+
+    let inner_area = popup_block.inner(popup_area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(1), Constraint::Length(1)])
+        .split(inner_area);
+
+    let millis =
+        |duration: std::time::Duration| format!("{:.1} ms", duration.as_secs_f64() * 1000.0);
+    let mut lines = vec![
+        Line::from(format!(
+            "Frames: {}   Output lines: {}   Low bandwidth: {}",
+            histogram.count(),
+            app.output_lines.len(),
+            if app.frame_pacer.is_low_bandwidth() {
+                "on"
+            } else {
+                "off"
+            }
+        )),
+        Line::from(format!(
+            "Mean {}   p50 ≤{}   p95 ≤{}   p99 ≤{}   max {}",
+            millis(histogram.mean()),
+            millis(histogram.percentile(50.0)),
+            millis(histogram.percentile(95.0)),
+            millis(histogram.percentile(99.0)),
+            millis(histogram.max())
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Frame time",
+            Style::default().fg(accent_color),
+        )),
+    ];
+
+    // One bar per bucket, scaled to the fullest bucket
+    let fullest = buckets
+        .iter()
+        .map(|(_, count)| *count)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let bar_width = (chunks[0].width as usize).saturating_sub(22);
+    for (label, count) in &buckets {
+        let bar = (*count as usize * bar_width).div_ceil(fullest as usize);
+        lines.push(Line::from(vec![
+            Span::raw(format!("{:>9} ", label)),
+            Span::styled("█".repeat(bar), Style::default().fg(accent_color)),
+            Span::raw(format!(" {}", count)),
+        ]));
+    }
+
+    let footer = Line::from(vec![
+        Span::styled(
+            " Esc ",
+            Style::default().bg(accent_color).fg(background_color),
+        ),
+        Span::raw(" Close  "),
+        Span::raw("/debug reset clears the histogram"),
+    ]);
+
+    f.render_widget(ratatui::widgets::Clear, popup_area);
+    f.render_widget(popup_block, popup_area);
+    f.render_widget(Paragraph::new(lines), chunks[0]);
+    f.render_widget(Paragraph::new(footer), chunks[1]);
+}
+
+/// Renders the one-line prompt asking what to do with the selected text
+pub fn render_selection_prompt(
+    f: &mut Frame,
+    prompt: &crate::app::SelectionPrompt,
+    cursor_visible: bool,
+    accent_color: Color,
+    background_color: Color,
+) {
+    use ratatui::text::{Line, Span};
+
+    let size = f.size();
+    let width = 70.min(size.width.saturating_sub(4));
+    let height = 5.min(size.height);
+    let popup_area = Rect::new(
+        (size.width.saturating_sub(width)) / 2,
+        (size.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    );
+
+    let popup_block = Block::default()
+        .title(" What do you want to do with this? ")
+        .title_alignment(ratatui::layout::Alignment::Center)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent_color))
+        .style(Style::default().bg(background_color));
+
+    // First line of the selection as a reminder of what is being asked about
+    let lines = prompt.selection.lines().count();
+    let first = prompt.selection.lines().next().unwrap_or_default().trim();
+    let summary = if lines > 1 {
+        format!("> {} (+{} lines)", first, lines - 1)
+    } else {
+        format!("> {}", first)
+    };
+
+    let cursor = if cursor_visible { "█" } else { " " };
+    let text = vec![
+        Line::from(Span::styled(summary, Style::default().fg(Color::DarkGray))),
+        Line::from(vec![
+            Span::styled("❯ ", Style::default().fg(accent_color)),
+            Span::raw(prompt.question.clone()),
+            Span::raw(cursor),
+        ]),
+        Line::from(Span::styled(
+            "Enter sends (empty: \"Explain this\") · Esc cancels",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+
+    f.render_widget(ratatui::widgets::Clear, popup_area);
+    f.render_widget(Paragraph::new(text).block(popup_block), popup_area);
+}
+
+/// Renders a one-line notice in the top-right corner of `area`
+pub fn render_toast(
+    f: &mut Frame,
+    message: &str,
+    area: Rect,
+    accent_color: Color,
+    background_color: Color,
+) {
+    let width = (UnicodeWidthStr::width(message) as u16 + 4).min(area.width);
+    let height = 3.min(area.height);
+    let toast_area = Rect::new(
+        area.x + area.width.saturating_sub(width + 1),
+        area.y + 1.min(area.height.saturating_sub(height)),
+        width,
+        height,
+    );
+
+    let toast_block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent_color))
+        .style(Style::default().bg(background_color));
+
+    f.render_widget(ratatui::widgets::Clear, toast_area);
+    f.render_widget(
+        Paragraph::new(format!(" {}", message)).block(toast_block),
+        toast_area,
+    );
+}
+
+/// Renders the banner of an empty or cut-off response along the bottom of `area`
+pub fn render_incomplete_banner(
+    f: &mut Frame,
+    banner: &crate::app::IncompleteBanner,
+    area: Rect,
+    accent_color: Color,
+    background_color: Color,
+) {
+    let height = 3.min(area.height);
+    let banner_area = Rect::new(
+        area.x,
+        area.y + area.height.saturating_sub(height),
+        area.width,
+        height,
+    );
+
+    let banner_block = Block::default()
+        .title(format!(" {} ", banner.title()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent_color))
+        .style(Style::default().bg(background_color));
+
+    f.render_widget(ratatui::widgets::Clear, banner_area);
+    f.render_widget(
+        Paragraph::new(format!(" {}", banner.keys())).block(banner_block),
+        banner_area,
+    );
+}
+
+/// Renders the pane of a `/pty` command over the lower part of `area`
+pub fn render_pty_pane(
+    f: &mut Frame,
+    shell: &crate::app::InteractiveShell,
+    area: Rect,
+    accent_color: Color,
+    background_color: Color,
+) {
+    let height = (area.height * crate::handlers::pty::PANE_PERCENT / 100)
+        .max(3)
+        .min(area.height);
+    let pane_area = Rect::new(
+        area.x,
+        area.y + area.height.saturating_sub(height),
+        area.width,
+        height,
+    );
+
+    let title = if shell.is_closing() {
+        format!(" ⌨️ {} — closing… ", shell.command)
+    } else {
+        format!(" ⌨️ {} — Ctrl+D closes ", shell.command)
+    };
+    let pane_block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(accent_color))
+        .style(Style::default().bg(background_color));
+
+    // The latest lines, as a terminal scrolled to its end shows them
+    let lines = shell.screen.lines();
+    let rows = height.saturating_sub(2) as usize;
+    let visible = &lines[lines.len().saturating_sub(rows)..];
+
+    f.render_widget(ratatui::widgets::Clear, pane_area);
+    f.render_widget(
+        Paragraph::new(visible.join("\n")).block(pane_block),
+        pane_area,
+    );
+
+    if !shell.is_closing() && rows > 0 {
+        let max_column = pane_area.width.saturating_sub(3) as usize;
+        f.set_cursor(
+            pane_area.x + 1 + shell.screen.column().min(max_column) as u16,
+            pane_area.y + visible.len() as u16,
+        );
+    }
+}
+
+/// Renders the bash blocks of a response with their checkboxes
+pub fn render_bash_queue(
+    f: &mut Frame,
+    queue: &crate::app::BashQueue,
+    accent_color: Color,
+    background_color: Color,
+) {
+    let size = f.size();
+    let width = 100.min(size.width.saturating_sub(4));
+    let height = (queue.blocks.len() as u16 + 2)
+        .min(size.height.saturating_sub(4))
+        .max(3.min(size.height));
+    let mut state = ListState::default();
+    state.select(Some(queue.selected));
+
+    f.render_widget(
+        ratatui::widgets::Clear,
+        Rect::new(
+            (size.width.saturating_sub(width)) / 2,
+            (size.height.saturating_sub(height)) / 2,
+            width,
+            height,
+        ),
+    );
+    render_list_popup(
+        f,
+        " Bash blocks — Space checks, Shift+↑/↓ reorders, e edits, Enter runs, Esc closes ",
+        &queue.lines(),
+        &mut state,
+        width,
+        height,
+        accent_color,
+        background_color,
+    );
+}
+
+/// Renders the `/tree` popup with the highlighted entry
+pub fn render_file_tree(
+    f: &mut Frame,
+    tree: &crate::utils::tree::FileTree,
+    accent_color: Color,
+    background_color: Color,
+) {
+    let size = f.size();
+    let width = 60.min(size.width.saturating_sub(4));
+    let height = (tree.entries.len() as u16 + 2)
+        .min(size.height.saturating_sub(4))
+        .max(3.min(size.height));
+    let mut state = ListState::default();
+    state.select(Some(tree.selected));
+    let name = tree
+        .root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| tree.root.display().to_string());
+
+    f.render_widget(
+        ratatui::widgets::Clear,
+        Rect::new(
+            (size.width.saturating_sub(width)) / 2,
+            (size.height.saturating_sub(height)) / 2,
+            width,
+            height,
+        ),
+    );
+    render_list_popup(
+        f,
+        &format!(" 📁 {} — Enter opens, ← collapses, Esc closes ", name),
+        &tree.lines(),
+        &mut state,
+        width,
+        height,
+        accent_color,
+        background_color,
+    );
+}
+
+/// Renders the `/sessions` list with the highlighted session
+pub fn render_session_browser(
+    f: &mut Frame,
+    browser: &crate::app::SessionBrowser,
+    current: &str,
+    accent_color: Color,
+    background_color: Color,
+) {
+    let size = f.size();
+    let width = 100.min(size.width.saturating_sub(4));
+    let height = (browser.sessions.len() as u16 + 2)
+        .min(size.height.saturating_sub(4))
+        .max(3.min(size.height));
+    let mut state = ListState::default();
+    state.select(Some(browser.selected));
+
+    f.render_widget(
+        ratatui::widgets::Clear,
+        Rect::new(
+            (size.width.saturating_sub(width)) / 2,
+            (size.height.saturating_sub(height)) / 2,
+            width,
+            height,
+        ),
+    );
+    render_list_popup(
+        f,
+        " Sessions — Enter opens, Esc closes ",
+        &browser.lines(current),
+        &mut state,
+        width,
+        height,
+        accent_color,
+        background_color,
+    );
+}
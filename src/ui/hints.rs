@@ -0,0 +1,69 @@
+//! Placeholder and hints in the empty input
+//!
+//! The empty input shows a dimmed placeholder naming the prefixes, so a first
+//! run shows how to reach the shell and the commands. While something is
+//! going on the placeholder gives way to a hint for it, such as how to abort
+//! a running generation or what the keys of a selected message do. Typing
+//! hides it; `ui.input_hints: false` turns both off. Once something is
+//! typed, the input title says how it will run instead.
+
+use crate::handlers::CommandMode;
+
+/// Placeholder while plain input goes to the AI
+pub const AI_PLACEHOLDER: &str = "Ask anything… ! for shell, / for commands";
+
+/// Placeholder while plain input runs as bash
+pub const BASH_PLACEHOLDER: &str = "Run a command… ? to ask the AI, / for commands";
+
+/// What the hint in the empty input depends on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HintContext {
+    /// An AI prompt is being answered
+    pub generating: bool,
+    /// A `!` command is running
+    pub running_bash: bool,
+    /// Text is selected with the mouse
+    pub selecting_text: bool,
+    /// A message is selected with Alt+Up/Down
+    pub message_selected: bool,
+    /// Messages are marked with Space for a bulk action
+    pub messages_marked: bool,
+    /// The output is searched with `/search`
+    pub searching: bool,
+    /// The last response wrote files that are not saved yet
+    pub files_to_save: bool,
+    /// Plain input runs as bash
+    pub bash_default: bool,
+}
+
+/// The hint for the empty input, the most pressing first
+pub fn input_hint(context: &HintContext) -> &'static str {
+    if context.selecting_text {
+        "Ctrl+C copies the selection"
+    } else if context.generating {
+        "Esc aborts the running generation"
+    } else if context.messages_marked {
+        "Marked: y copies, q quotes, e exports, d drops from context, a asks the AI, Esc unmarks"
+    } else if context.message_selected {
+        "y copies, r reruns, p pins, d drops from context, Space marks, Enter folds, Esc deselects"
+    } else if context.searching {
+        "n/N move between matches, Ctrl+F changes the search, Esc ends it"
+    } else if context.running_bash {
+        "Esc kills the running command"
+    } else if context.files_to_save {
+        "Ctrl+S previews and saves the files of the response"
+    } else if context.bash_default {
+        BASH_PLACEHOLDER
+    } else {
+        AI_PLACEHOLDER
+    }
+}
+
+/// The input title hint for typed input that runs in `mode`
+pub fn mode_hint(mode: &CommandMode) -> &'static str {
+    match mode {
+        CommandMode::Bash => "will run as BASH",
+        CommandMode::Command => "will run as COMMAND",
+        CommandMode::AI => "will be sent to AI",
+    }
+}
@@ -6,22 +6,39 @@
 //! - Status bar
 //! - Context menus
 //! - Help overlay
+//! - Spinners and progress bars
 
 use chrono::Local;
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span, Text},
     widgets::{Block, Borders, Paragraph, Wrap},
 };
 use std::path::Path;
 
-use crate::app::App;
-use crate::config::{ThemeConfig, get_config};
+use crate::ai::quiet_hours;
+use crate::app::{App, CONTEXT_MENU};
+use crate::config::{CostLevel, ThemeConfig, get_config};
+use crate::handlers::CommandMode;
+use crate::ui::status::{StatusAction, StatusHits};
+use crate::utils::diff::{self, DiffLineKind, SideBySideRow};
+use crate::utils::format_relative_time;
+use crate::utils::transcript;
+use unicode_width::UnicodeWidthChar;
 
 mod components;
+pub mod hints;
+pub mod pacing;
+pub mod profile;
+pub mod progress;
+pub mod search;
+pub mod status;
 mod theme;
+pub mod title;
+pub use pacing::FramePacer;
+pub use profile::FrameHistogram;
 pub use theme::Theme;
 
 /// Convert hex color to ratatui Color
@@ -64,7 +81,7 @@ pub fn render(f: &mut Frame, app: &mut App) {
 
     // Get theme from config
     let config = get_config();
-    let (primary, _secondary, accent, background, foreground) = get_theme_colors(&config.theme);
+    let (primary, secondary, accent, background, foreground) = get_theme_colors(&config.theme);
 
     // Calculate input area height accounting for both explicit newlines and wrapping
     // First count explicit newlines
@@ -95,9 +112,10 @@ pub fn render(f: &mut Frame, app: &mut App) {
     // Use the larger of explicit newlines or wrapped lines calculation
     let estimated_lines = explicit_lines.max(wrapped_lines);
 
-    // Ensure input box is at least 3 lines tall and doesn't exceed 10 lines
-    // (adjust the max as needed based on your preferences)
-    let input_height = (estimated_lines as u16).clamp(1, 10) + 2; // Add 2 for border
+    // Fit the input box to its content, up to 10 lines. A height set with
+    // Ctrl+Up/Down is the minimum and raises the cap, leaving room for the output.
+    let manual = config.ui.input_height.min(size.height.saturating_sub(6));
+    let input_height = (estimated_lines as u16).clamp(manual.max(1), manual.max(10)) + 2; // Add 2 for border
 
     // Create the layout
     let chunks = Layout::default()
@@ -110,9 +128,10 @@ pub fn render(f: &mut Frame, app: &mut App) {
         .split(size);
 
     // Render each component
-    render_output_area(f, app, chunks[0], background, foreground);
-    render_input_area(f, app, chunks[1], background, foreground);
-    render_status_bar(f, app, chunks[2], primary, accent, background);
+    app.follow_scroll_anchor();
+    render_output_area(f, app, chunks[0], accent, background, foreground);
+    render_input_area(f, app, chunks[1], secondary, accent, background, foreground);
+    app.status_hits = render_status_bar(f, app, chunks[2], primary, accent, background);
 
     // Store output area height for mouse handling
     app.output_area_height = chunks[0].height;
@@ -122,16 +141,61 @@ pub fn render(f: &mut Frame, app: &mut App) {
         render_context_menu(f, app, accent, background, foreground);
     }
 
+    // Render the "Send to AI" mini prompt if open
+    if let Some(prompt) = &app.selection_prompt {
+        components::render_selection_prompt(f, prompt, app.cursor_visible, accent, background);
+    }
+
     // Render tasks popup if active
     if app.show_tasks_popup {
         components::render_tasks_popup(f, app, primary, accent, background);
     }
+
+    // Render the session browser if open
+    if let Some(browser) = &app.session_browser {
+        components::render_session_browser(f, browser, &app.session_id, accent, background);
+    }
+
+    // Render the file tree if open
+    if let Some(tree) = &app.file_tree {
+        components::render_file_tree(f, tree, accent, background);
+    }
+
+    // Render the bash block popup if open
+    if let Some(queue) = &app.bash_queue {
+        components::render_bash_queue(f, queue, accent, background);
+    }
+
+    // Render the banner of an empty or cut-off response above the input
+    if let Some(banner) = &app.incomplete_banner {
+        components::render_incomplete_banner(f, banner, chunks[0], accent, background);
+    }
+
+    // Render the pane of a /pty command over the lower part of the output
+    if let Some(shell) = &app.interactive {
+        components::render_pty_pane(f, shell, chunks[0], accent, background);
+    }
+
+    // Render the debug panel if active
+    if app.show_debug_popup {
+        components::render_debug_popup(f, app, primary, accent, background);
+    }
+
+    // Render a toast in the top-right corner of the output
+    if let Some(toast) = &app.toast {
+        components::render_toast(f, &toast.message, chunks[0], accent, background);
+    }
+
+    // Render confirmation popup on top of everything else
+    if let Some(confirmation) = &app.pending_confirmation {
+        components::render_confirmation_popup(f, confirmation, primary, accent, background);
+    }
 }
 
 /// Render the context menu
 fn render_context_menu(f: &mut Frame, app: &App, accent: Color, bg_color: Color, fg_color: Color) {
     let menu_width = 20;
-    let menu_height = 3;
+    let menu_height = CONTEXT_MENU.len() as u16 + 2;
     let menu_x = app
         .context_menu_x
         .min(f.size().width.saturating_sub(menu_width));
@@ -147,11 +211,20 @@ fn render_context_menu(f: &mut Frame, app: &App, accent: Color, bg_color: Color,
         .border_style(Style::default().fg(accent))
         .style(Style::default().bg(bg_color));
 
-    let menu_text = vec![
-        Line::from("Copy"),
-        Line::from("Select All"),
-        Line::from("Clear"),
-    ];
+    let menu_text: Vec<Line> = CONTEXT_MENU
+        .iter()
+        .enumerate()
+        .map(|(idx, (label, _))| {
+            if idx == app.context_menu_selected {
+                Line::from(Span::styled(
+                    label.to_string(),
+                    Style::default().bg(accent).fg(bg_color),
+                ))
+            } else {
+                Line::from(label.to_string())
+            }
+        })
+        .collect();
 
     let menu_widget = Paragraph::new(menu_text)
         .block(menu_block)
@@ -161,7 +234,14 @@ fn render_context_menu(f: &mut Frame, app: &App, accent: Color, bg_color: Color,
 }
 
 /// Render the output area
-fn render_output_area(f: &mut Frame, app: &App, area: Rect, bg_color: Color, fg_color: Color) {
+fn render_output_area(
+    f: &mut Frame,
+    app: &App,
+    area: Rect,
+    accent: Color,
+    bg_color: Color,
+    fg_color: Color,
+) {
     // No border for output area as requested
     let output_block = Block::default().style(Style::default().bg(bg_color).fg(fg_color));
 
@@ -186,16 +266,33 @@ fn render_output_area(f: &mut Frame, app: &App, area: Rect, bg_color: Color, fg_
             }
         }
     } else {
-        // Regular rendering without selection
-        // First ensure the output_lines are up to date with the output
-        // by converting the output string to lines
-        let output_text = app.output.clone();
-        // Convert to Text object
-        let text = Text::from(output_text);
-        // Get the lines
-        let lines = text.lines.to_vec();
-        // Assign to styled_lines
-        styled_lines = lines;
+        // Regular rendering, with long sections collapsed to a summary
+        styled_lines = section_lines(app, accent, area.width);
+
+        // Matches of /search, the one jumped to in the accent color
+        if let Some(output_search) = &app.output_search {
+            let matched = Style::default()
+                .fg(Color::Black)
+                .bg(parse_hex_color(&get_config().theme.search_match));
+            let current = Style::default().fg(Color::Black).bg(accent);
+            let current_row = app.current_match_row();
+            styled_lines = styled_lines
+                .into_iter()
+                .enumerate()
+                .map(|(row, line)| {
+                    let style = if Some(row) == current_row {
+                        current
+                    } else {
+                        matched
+                    };
+                    search::highlight(line, &output_search.term, style)
+                })
+                .collect();
+        }
+    }
+
+    if get_config().ui.line_numbers {
+        styled_lines = number_lines(styled_lines);
     }
 
     let text = Text::from(styled_lines);
@@ -208,10 +305,273 @@ fn render_output_area(f: &mut Frame, app: &App, area: Rect, bg_color: Color, fg_
     f.render_widget(output_widget, area);
 }
 
+/// Prefix each line with its number in a dimmed gutter
+fn number_lines(lines: Vec<Line<'static>>) -> Vec<Line<'static>> {
+    let width = lines.len().max(1).to_string().len();
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(idx, line)| {
+            let mut spans = vec![Span::styled(
+                format!("{:>width$} ", idx + 1),
+                Style::default().fg(Color::DarkGray),
+            )];
+            spans.extend(line.spans);
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Output lines with collapsed sections replaced by their summary
+fn section_lines(app: &App, accent: Color, width: u16) -> Vec<Line<'static>> {
+    let lines: Vec<&str> = app.output.lines().collect();
+    let raw = |range: std::ops::Range<usize>| {
+        lines[range]
+            .iter()
+            .map(|line| Line::from(line.to_string()))
+            .collect::<Vec<_>>()
+    };
+
+    let ui_config = get_config().ui;
+    let timestamp_format = ui_config.valid_timestamp_format();
+    let now = Local::now();
+
+    let mut styled = Vec::with_capacity(lines.len());
+    let mut next = 0;
+    for (idx, span) in app.output_sections().iter().enumerate() {
+        if !app.is_section_visible(span) {
+            // Hidden by /filter, separator included
+            styled.extend(raw(next..span.header));
+            next = span.body.end;
+            continue;
+        }
+        styled.extend(raw(next..span.header + 1));
+
+        let selected = app.selected_section == Some(idx);
+        let collapsed = app.is_section_collapsed(idx, span);
+        let input_style = if selected {
+            Style::default().fg(Color::Black).bg(accent)
+        } else {
+            Style::default()
+        };
+        let time = app.section_times.get(idx);
+        let mut input = Vec::new();
+        if let Some(time) = time.filter(|_| ui_config.timestamps) {
+            input.push(Span::styled(
+                format!("{} ", time.format(&timestamp_format)),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        input.push(Span::styled(
+            lines[span.header + 1].to_string(),
+            input_style,
+        ));
+        // The selected section shows how long ago it was entered
+        if let Some(time) = time.filter(|_| selected) {
+            let elapsed = (now - *time).num_seconds().max(0) as u64;
+            input.push(Span::styled(
+                format!("  {}", format_relative_time(elapsed)),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        if app.marked_sections.contains(&idx) {
+            input.push(Span::styled("  ☑ marked", Style::default().fg(accent)));
+        }
+        let flags = app.message_flags(idx);
+        if flags.pinned {
+            input.push(Span::styled("  📌 pinned", Style::default().fg(accent)));
+        }
+        if flags.excluded {
+            input.push(Span::styled(
+                "  ⊘ not in context",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        styled.push(Line::from(input));
+
+        if collapsed {
+            styled.push(Line::from(Span::styled(
+                span.summary(&lines),
+                Style::default().fg(accent),
+            )));
+        } else {
+            styled.extend(body_lines(
+                &lines[span.body.clone()],
+                app.show_reasoning,
+                app.diff_side_by_side.then_some(width),
+                accent,
+            ));
+        }
+        next = span.body.end;
+    }
+    styled.extend(raw(next..lines.len()));
+    styled
+}
+
+/// Lines of an expanded section, with reasoning blocks folded to a summary or dimmed
+///
+/// Diffs are shown in two columns when `side_by_side` holds the pane width.
+fn body_lines(
+    body: &[&str],
+    show_reasoning: bool,
+    side_by_side: Option<u16>,
+    accent: Color,
+) -> Vec<Line<'static>> {
+    let mut styled = Vec::with_capacity(body.len());
+    let mut next = 0;
+    for block in transcript::reasoning_blocks(body) {
+        styled.extend(diff_colored_lines(&body[next..block.start], side_by_side));
+        if show_reasoning {
+            styled.push(Line::from(Span::styled(
+                "▾ 💭 Reasoning (Ctrl+O to fold)",
+                Style::default().fg(accent),
+            )));
+            styled.extend(body[block.clone()].iter().map(|line| {
+                Line::from(Span::styled(
+                    line.to_string(),
+                    Style::default()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::ITALIC),
+                ))
+            }));
+        } else {
+            styled.push(Line::from(Span::styled(
+                format!("▸ 💭 Reasoning — {} lines (Ctrl+O to show)", block.len()),
+                Style::default().fg(accent),
+            )));
+        }
+        next = block.end;
+    }
+    styled.extend(diff_colored_lines(&body[next..], side_by_side));
+    styled
+}
+
+/// Lines with unified diffs colored: additions green, deletions red, hunk headers cyan
+fn diff_colored_lines(lines: &[&str], side_by_side: Option<u16>) -> Vec<Line<'static>> {
+    let mut styled = Vec::with_capacity(lines.len());
+    let mut next = 0;
+    for range in diff::find_unified_diffs(lines) {
+        styled.extend(
+            lines[next..range.start]
+                .iter()
+                .map(|line| Line::from(line.to_string())),
+        );
+        let diff_lines = &lines[range.clone()];
+        match side_by_side {
+            Some(width) => styled.extend(
+                diff::side_by_side(diff_lines)
+                    .into_iter()
+                    .map(|row| side_by_side_line(row, width)),
+            ),
+            None => styled.extend(diff_lines.iter().map(|line| {
+                Line::from(Span::styled(
+                    line.to_string(),
+                    diff_style(diff::classify_diff_line(line)),
+                ))
+            })),
+        }
+        next = range.end;
+    }
+    styled.extend(
+        lines[next..]
+            .iter()
+            .map(|line| Line::from(line.to_string())),
+    );
+    styled
+}
+
+fn diff_style(kind: DiffLineKind) -> Style {
+    match kind {
+        DiffLineKind::Header => Style::default().add_modifier(Modifier::BOLD),
+        DiffLineKind::Hunk => Style::default().fg(Color::Cyan),
+        DiffLineKind::Added => Style::default().fg(Color::Green),
+        DiffLineKind::Removed => Style::default().fg(Color::Red),
+        DiffLineKind::Context => Style::default(),
+    }
+}
+
+/// One row of a side-by-side diff, old text left of the divider and new text right
+fn side_by_side_line(row: SideBySideRow, width: u16) -> Line<'static> {
+    let (left, right, changed) = match row {
+        SideBySideRow::Header(text, kind) => {
+            return Line::from(Span::styled(text, diff_style(kind)));
+        }
+        SideBySideRow::Pair {
+            left,
+            right,
+            changed,
+        } => (left, right, changed),
+    };
+    let column = (width as usize).saturating_sub(3) / 2;
+    let (left_style, right_style) = if changed {
+        (
+            diff_style(DiffLineKind::Removed),
+            diff_style(DiffLineKind::Added),
+        )
+    } else {
+        (Style::default(), Style::default())
+    };
+    Line::from(vec![
+        Span::styled(
+            fit_column(left.as_deref().unwrap_or(""), column),
+            left_style,
+        ),
+        Span::styled(" │ ", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            fit_column(right.as_deref().unwrap_or(""), column)
+                .trim_end()
+                .to_string(),
+            right_style,
+        ),
+    ])
+}
+
+/// Cut or pad text to exactly `width` columns
+fn fit_column(text: &str, width: usize) -> String {
+    let mut fitted = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let c = if c == '\t' { ' ' } else { c };
+        let char_width = c.width().unwrap_or(0);
+        if used + char_width > width {
+            break;
+        }
+        fitted.push(c);
+        used += char_width;
+    }
+    fitted.push_str(&" ".repeat(width - used));
+    fitted
+}
+
 /// Render the input area
-fn render_input_area(f: &mut Frame, app: &App, area: Rect, bg_color: Color, fg_color: Color) {
+///
+/// The input is colored by the mode it will run in (bash in the secondary
+/// color, commands in the accent color) and the title says which one.
+fn render_input_area(
+    f: &mut Frame,
+    app: &App,
+    area: Rect,
+    bash_color: Color,
+    command_color: Color,
+    bg_color: Color,
+    fg_color: Color,
+) {
+    let (mode, _) = app.detect_mode(&app.input);
+    let mode_color = match mode {
+        CommandMode::Bash => bash_color,
+        CommandMode::Command => command_color,
+        CommandMode::AI => fg_color,
+    };
+    let mut title = vec![Span::raw("Input")];
+    if !app.input.trim().is_empty() {
+        title.push(Span::styled(
+            format!(" — {} ", hints::mode_hint(&mode)),
+            Style::default().fg(mode_color),
+        ));
+    }
+
     let input_block = Block::default()
-        .title("Input")
+        .title(Line::from(title))
         .borders(Borders::ALL)
         .style(Style::default().bg(bg_color).fg(fg_color));
 
@@ -293,8 +653,20 @@ fn render_input_area(f: &mut Frame, app: &App, area: Rect, bg_color: Color, fg_c
         current_pos += part_len;
     }
 
+    // The empty input shows the placeholder or a hint after the cursor
+    if app.input.is_empty() && get_config().ui.input_hints {
+        let mut spans = vec![Span::raw("> ")];
+        if app.cursor_visible {
+            spans.push(Span::styled("│", cursor_style));
+        }
+        spans.push(Span::styled(
+            hints::input_hint(&app.hint_context()),
+            Style::default().fg(Color::DarkGray),
+        ));
+        text.lines = vec![Line::from(spans)];
+    }
     // If cursor is at the very end and there's no newline at the end
-    if app.cursor_position == app.input.len()
+    else if app.cursor_position == app.input.len()
         && (app.input.is_empty() || !app.input.ends_with('\n'))
     {
         // If the text is empty or we haven't added any lines yet
@@ -319,6 +691,13 @@ fn render_input_area(f: &mut Frame, app: &App, area: Rect, bg_color: Color, fg_c
         }
     }
 
+    // Color the input by mode, keeping the cursor style
+    for line in &mut text.lines {
+        for span in &mut line.spans {
+            span.style = Style::default().fg(mode_color).patch(span.style);
+        }
+    }
+
     let input_widget = Paragraph::new(text)
         .block(input_block)
         .style(Style::default().fg(fg_color))
@@ -328,7 +707,7 @@ fn render_input_area(f: &mut Frame, app: &App, area: Rect, bg_color: Color, fg_c
     f.render_widget(input_widget, area);
 }
 
-/// Render the status bar
+/// Render the status bar, returning where its clickable segments are
 fn render_status_bar(
     f: &mut Frame,
     app: &App,
@@ -336,7 +715,7 @@ fn render_status_bar(
     primary_color: Color,
     accent_color: Color,
     bg_color: Color,
-) {
+) -> StatusHits {
     let elapsed = Local::now() - app.stats.start_time;
     let hours = elapsed.num_hours();
     let minutes = elapsed.num_minutes() % 60;
@@ -353,18 +732,42 @@ fn render_status_bar(
     // Style for mode indicator
     let mode_style = Style::default().bg(accent_color).fg(bg_color);
 
-    // Create status bar spans
-    let mut spans = vec![
-        Span::styled(format!(" {} ", app.current_mode), mode_style),
-        Span::raw(" "),
-        Span::raw(format!("📁 {} ", dir_name)),
-        Span::raw(" "),
-        Span::raw(format!("⏱️ {} ", elapsed_str)),
-        Span::raw(" "),
-        Span::raw(format!("💰 ${:.4} ", app.stats.cost)),
-        Span::raw(" "),
-        Span::raw(format!("🧮 {} cmds ", app.stats.command_count)),
+    // Session cost colored by the usage thresholds
+    let cost_style = match get_config().usage.cost_level(app.stats.cost) {
+        CostLevel::Normal => Style::default().fg(Color::Green),
+        CostLevel::Warning => Style::default().fg(Color::Yellow),
+        CostLevel::Alert => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+    };
+
+    // Create status bar spans; the mode, directory and cost react to clicks
+    let segments = [
+        (
+            Span::styled(format!(" {} ", app.current_mode), mode_style),
+            Some(StatusAction::ToggleMode),
+        ),
+        (Span::raw(" "), None),
+        (
+            Span::raw(format!("📁 {} ", dir_name)),
+            Some(StatusAction::FileTree),
+        ),
+        (Span::raw(" "), None),
+        (Span::raw(format!("⏱️ {} ", elapsed_str)), None),
+        (Span::raw(" "), None),
+        (
+            Span::styled(format!("💰 ${:.4} ", app.stats.cost), cost_style),
+            Some(StatusAction::Cost),
+        ),
     ];
+    let hits = StatusHits::layout(
+        area,
+        &segments
+            .iter()
+            .map(|(span, action)| (span.width(), *action))
+            .collect::<Vec<_>>(),
+    );
+    let mut spans: Vec<Span> = segments.into_iter().map(|(span, _)| span).collect();
+    spans.push(Span::raw(" "));
+    spans.push(Span::raw(format!("🧮 {} cmds ", app.stats.command_count)));
 
     // Add active tasks indicator if any
     let active_tasks = app.get_active_tasks();
@@ -375,23 +778,99 @@ fn render_status_bar(
             Style::default().bg(Color::Blue).fg(Color::White),
         ));
 
-        // If there's a running task with progress, show it
-        if let Some(task) = active_tasks
+        // Live gauge of the running generation that is furthest along
+        if let Some(gauge) = active_tasks
             .iter()
-            .find(|t| t.status == crate::ai::types::TaskStatus::Running)
+            .filter(|t| t.status == crate::ai::types::TaskStatus::Running)
+            .filter_map(|t| t.progress.as_ref())
+            .max_by_key(|progress| progress.tokens_generated)
+            .and_then(|progress| progress::gauge(progress, get_config().theme.progress_bar))
         {
-            if let Some(progress) = &task.progress {
-                if let Some(percent) = progress.completion_percent {
-                    spans.push(Span::raw(" "));
-                    spans.push(Span::styled(
-                        format!(" {:.1}% ", percent),
-                        Style::default().bg(Color::Green).fg(Color::Black),
-                    ));
-                }
-            }
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(
+                format!(" {} ", gauge),
+                Style::default().bg(Color::Green).fg(Color::Black),
+            ));
         }
     }
 
+    if let Some(index) = app.index_label() {
+        spans.push(Span::raw(" "));
+        spans.push(Span::raw(format!("{} ", index)));
+    }
+
+    if let Some(persona) = &app.active_persona {
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            format!(" 🎭 {} ", persona),
+            Style::default().bg(Color::Magenta).fg(Color::White),
+        ));
+    }
+
+    if let Some(tag) = &app.usage_tag {
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            format!(" 🏷️ {} ", tag),
+            Style::default().bg(Color::Cyan).fg(Color::Black),
+        ));
+    }
+
+    if crate::handlers::safe::is_enabled() {
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            " SAFE ",
+            Style::default().bg(Color::Yellow).fg(Color::Black),
+        ));
+    }
+
+    // Show offline state and prompts waiting for the network
+    if !app.connectivity.is_online() {
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            " OFFLINE ",
+            Style::default().bg(Color::Red).fg(Color::White),
+        ));
+    }
+    let ai = get_config().ai;
+    let now = quiet_hours::now();
+    if let Some(until) = quiet_hours::provider_quiet_until(&ai, ai.active_provider, now) {
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            format!(" 🌙 QUIET until {} ", quiet_hours::format_until(until, now)),
+            Style::default().bg(Color::DarkGray).fg(Color::White),
+        ));
+    }
+    if !app.offline_queue.is_empty() {
+        spans.push(Span::raw(" "));
+        spans.push(Span::raw(format!("📥 {} queued ", app.offline_queue.len())));
+    }
+    if let Some(filter) = app.filter_label() {
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            format!(" FILTER: {} ", filter),
+            Style::default().bg(Color::Blue).fg(Color::White),
+        ));
+    }
+
+    // Push-to-talk recording with its length
+    if let Some(elapsed) = app.recording_elapsed() {
+        let seconds = elapsed.as_secs();
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            format!(" ● REC {}:{:02} ", seconds / 60, seconds % 60),
+            Style::default().bg(Color::Red).fg(Color::White),
+        ));
+    }
+
+    // A response being read aloud
+    if app.is_speaking() {
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            " 🔊 Ctrl+X to stop ",
+            Style::default().bg(Color::Magenta).fg(Color::White),
+        ));
+    }
+
     // Add text selection indicator if applicable
     if app.is_selecting_text {
         spans.push(Span::raw(" "));
@@ -407,4 +886,5 @@ fn render_status_bar(
         Paragraph::new(status_text).style(Style::default().bg(primary_color).fg(bg_color));
 
     f.render_widget(status_widget, area);
+    hits
 }
@@ -0,0 +1,101 @@
+//! Frame pacing for slow terminals
+//!
+//! Every frame is timed from drawing to flushing. Over a slow SSH link the
+//! flush blocks until the bytes are written, so consistently slow frames
+//! mean a slow connection. In low-bandwidth mode frames are drawn at most a
+//! few times per second and nothing animates (cursor blink, spinner), so
+//! only cells that actually changed are sent.
+
+use crate::config::LowBandwidth;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Shortest time between two frames normally (about 60 fps)
+const NORMAL_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Shortest time between two frames in low-bandwidth mode
+const LOW_BANDWIDTH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Frames considered when deciding whether the terminal is slow
+const SAMPLE_SIZE: usize = 30;
+
+/// Median frame time above which the terminal counts as slow
+const SLOW_FRAME: Duration = Duration::from_millis(40);
+
+/// Decides when to draw and whether to switch to low-bandwidth mode
+#[derive(Debug, Clone)]
+pub struct FramePacer {
+    mode: LowBandwidth,
+    /// Switched on automatically after slow frames
+    detected: bool,
+    samples: VecDeque<Duration>,
+    last_frame: Option<Instant>,
+}
+
+impl FramePacer {
+    /// Create a pacer for the configured mode
+    pub fn new(mode: LowBandwidth) -> Self {
+        Self {
+            mode,
+            detected: false,
+            samples: VecDeque::with_capacity(SAMPLE_SIZE),
+            last_frame: None,
+        }
+    }
+
+    /// Change the mode; `auto` starts measuring again
+    pub fn set_mode(&mut self, mode: LowBandwidth) {
+        self.mode = mode;
+        self.detected = false;
+        self.samples.clear();
+    }
+
+    /// Whether low-bandwidth rendering is in effect
+    pub fn is_low_bandwidth(&self) -> bool {
+        match self.mode {
+            LowBandwidth::On => true,
+            LowBandwidth::Off => false,
+            LowBandwidth::Auto => self.detected,
+        }
+    }
+
+    /// Whether enough time has passed since the last frame to draw another
+    pub fn should_draw(&self, now: Instant) -> bool {
+        self.next_draw(now).is_zero()
+    }
+
+    /// How long until another frame may be drawn
+    pub fn next_draw(&self, now: Instant) -> Duration {
+        let interval = if self.is_low_bandwidth() {
+            LOW_BANDWIDTH_INTERVAL
+        } else {
+            NORMAL_INTERVAL
+        };
+        self.last_frame.map_or(Duration::ZERO, |last| {
+            interval.saturating_sub(now.duration_since(last))
+        })
+    }
+
+    /// Record a drawn frame and how long it took to flush
+    ///
+    /// Returns true when this frame switched low-bandwidth mode on.
+    pub fn record(&mut self, now: Instant, took: Duration) -> bool {
+        self.last_frame = Some(now);
+        if self.mode != LowBandwidth::Auto || self.detected {
+            return false;
+        }
+
+        if self.samples.len() == SAMPLE_SIZE {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(took);
+        if self.samples.len() < SAMPLE_SIZE {
+            return false;
+        }
+
+        let mut sorted: Vec<Duration> = self.samples.iter().copied().collect();
+        sorted.sort();
+        self.detected = sorted[sorted.len() / 2] >= SLOW_FRAME;
+        self.detected
+    }
+}
@@ -0,0 +1,104 @@
+//! Frame-time histogram for the debug panel
+//!
+//! Every drawn frame is counted in a bucket by how long it took from the
+//! start of rendering to the flush. Buckets double in width, so the spread
+//! from a fast local terminal to a slow link fits in a handful of rows.
+
+use std::time::Duration;
+
+/// Upper bounds of the buckets; the last bucket holds everything slower
+pub const BUCKET_BOUNDS: [Duration; 7] = [
+    Duration::from_millis(1),
+    Duration::from_millis(2),
+    Duration::from_millis(4),
+    Duration::from_millis(8),
+    Duration::from_millis(16),
+    Duration::from_millis(33),
+    Duration::from_millis(66),
+];
+
+/// Counts of frame times per bucket
+#[derive(Debug, Clone, Default)]
+pub struct FrameHistogram {
+    counts: [u64; BUCKET_BOUNDS.len() + 1],
+    total: Duration,
+    max: Duration,
+}
+
+impl FrameHistogram {
+    /// Create an empty histogram
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a frame that took `took`
+    pub fn record(&mut self, took: Duration) {
+        let bucket = BUCKET_BOUNDS
+            .iter()
+            .position(|bound| took < *bound)
+            .unwrap_or(BUCKET_BOUNDS.len());
+        self.counts[bucket] += 1;
+        self.total += took;
+        self.max = self.max.max(took);
+    }
+
+    /// Number of frames recorded
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Average frame time
+    pub fn mean(&self) -> Duration {
+        match self.count() {
+            0 => Duration::ZERO,
+            count => self.total / count as u32,
+        }
+    }
+
+    /// Slowest frame
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Upper bound of the bucket holding the given percentile (0–100)
+    ///
+    /// Frames in the last bucket report the slowest frame instead.
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        let count = self.count();
+        if count == 0 {
+            return Duration::ZERO;
+        }
+        let rank = ((percentile / 100.0) * count as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (idx, bucket) in self.counts.iter().enumerate() {
+            seen += bucket;
+            if seen >= rank {
+                return BUCKET_BOUNDS.get(idx).copied().unwrap_or(self.max);
+            }
+        }
+        self.max
+    }
+
+    /// Label and count of every bucket, fastest first
+    pub fn buckets(&self) -> Vec<(String, u64)> {
+        let mut lower = Duration::ZERO;
+        let mut buckets = Vec::new();
+        for (bound, count) in BUCKET_BOUNDS.iter().zip(self.counts) {
+            buckets.push((
+                format!("{}–{} ms", lower.as_millis(), bound.as_millis()),
+                count,
+            ));
+            lower = *bound;
+        }
+        buckets.push((
+            format!("≥{} ms", lower.as_millis()),
+            self.counts[BUCKET_BOUNDS.len()],
+        ));
+        buckets
+    }
+
+    /// Forget all recorded frames
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
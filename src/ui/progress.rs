@@ -0,0 +1,208 @@
+//! Spinners and progress bars
+//!
+//! Everything that animates while a prompt is answered lives here: the
+//! frames of each [`SpinnerStyle`], the task that sends them to the app, the
+//! stage of the generation next to the spinner and the determinate bar added
+//! once the remaining time is known, as well as the gauge of the running
+//! generation in the status bar.
+
+use crate::ai::types::{GenerationStage, ProgressStats};
+use crate::config::{SpinnerStyle, ThemeConfig};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+const BRAILLE: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+const DOTS: [&str; 6] = ["∙  ", "∙∙ ", "∙∙∙", " ∙∙", "  ∙", "   "];
+const LINE: [&str; 4] = ["─", "╲", "│", "╱"];
+
+/// Static indicator for slow links, only resent to notice when it is gone
+const LOW_BANDWIDTH: [&str; 1] = ["⋯"];
+
+/// Frame of the `none` style; the line stays empty unless a bar is shown
+const NO_SPINNER: [&str; 1] = [""];
+
+/// Delay between frames in low-bandwidth mode and without a spinner
+const SLOW_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Width of the progress bar next to the spinner
+const BAR_WIDTH: usize = 20;
+
+/// Width of the gauge in the status bar
+const GAUGE_WIDTH: usize = 10;
+
+/// A stage shown next to the spinner, with the bar that may follow it
+static STAGE_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^(connecting \(\d+\.\ds\)|waiting for first token \(\d+\.\ds\)|streaming \(\d+ tok, \d+/s\))( [█░]+ \d+% · ETA .+)?$",
+    )
+    .unwrap()
+});
+
+/// Every frame of every style, to recognize indicators left in the output
+const ALL_FRAMES: [&[&str]; 4] = [&BRAILLE, &DOTS, &LINE, &LOW_BANDWIDTH];
+
+/// How work in progress is animated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spinner {
+    /// Frames shown in turn
+    pub frames: &'static [&'static str],
+    /// Delay between frames
+    pub interval: Duration,
+    /// Show a progress bar once the remaining time is known
+    pub progress_bar: bool,
+}
+
+impl Spinner {
+    /// The spinner of the theme; slow links get a static indicator
+    pub fn new(theme: &ThemeConfig, low_bandwidth: bool) -> Self {
+        let (frames, interval): (&'static [&'static str], Duration) = match theme.spinner {
+            SpinnerStyle::None => (&NO_SPINNER, SLOW_INTERVAL),
+            _ if low_bandwidth => (&LOW_BANDWIDTH, SLOW_INTERVAL),
+            SpinnerStyle::Braille => (&BRAILLE, Duration::from_millis(80)),
+            SpinnerStyle::Dots => (&DOTS, Duration::from_millis(120)),
+            SpinnerStyle::Line => (&LINE, Duration::from_millis(100)),
+        };
+        Self {
+            frames,
+            interval,
+            progress_bar: theme.progress_bar,
+        }
+    }
+
+    /// Send the frames with the output line they belong on until `abort`
+    /// is set or the receiver is dropped
+    pub fn spawn(
+        self,
+        line_index: usize,
+        abort: Option<Arc<AtomicBool>>,
+    ) -> (mpsc::Receiver<(String, usize)>, JoinHandle<()>) {
+        let (tx, rx) = mpsc::channel();
+        let task = tokio::spawn(async move {
+            for frame in self.frames.iter().cycle() {
+                if abort
+                    .as_ref()
+                    .is_some_and(|flag| flag.load(Ordering::SeqCst))
+                {
+                    break;
+                }
+                if tx.send((frame.to_string(), line_index)).is_err() {
+                    break;
+                }
+                tokio::time::sleep(self.interval).await;
+            }
+        });
+        (rx, task)
+    }
+
+    /// The spinner line for a frame: the frame with the stage of the
+    /// generation, followed by a progress bar and the remaining time once
+    /// they are known
+    ///
+    /// `waited` is the time since the prompt was sent.
+    pub fn status_line(
+        &self,
+        frame: &str,
+        progress: Option<&ProgressStats>,
+        waited: Duration,
+    ) -> String {
+        let mut line = format!("{} {}", frame.trim(), stage(progress, waited));
+        if let Some(progress) = progress
+            .filter(|progress| self.progress_bar && progress.estimate_remaining_seconds().is_some())
+        {
+            let percent = progress.completion_percent.unwrap_or_default();
+            line.push_str(&format!(
+                " {} {:.0}% · ETA {}",
+                bar(percent, BAR_WIDTH),
+                percent,
+                progress.format_remaining_time()
+            ));
+        }
+        line.trim_start().to_string()
+    }
+}
+
+/// What a generation is doing, so a slow model can be told from a dead
+/// connection: how long it has been connecting or waiting for the first
+/// token, then the tokens received and the rate
+pub fn stage(progress: Option<&ProgressStats>, waited: Duration) -> String {
+    let stage = progress.map_or(GenerationStage::Connecting, ProgressStats::stage);
+    let seconds = waited.as_secs_f64();
+    match (stage, progress) {
+        (GenerationStage::Streaming, Some(progress)) => format!(
+            "streaming ({} tok, {:.0}/s)",
+            progress.tokens_generated, progress.tokens_per_second
+        ),
+        (GenerationStage::WaitingForFirstToken, _) => {
+            format!("waiting for first token ({:.1}s)", seconds)
+        }
+        _ => format!("connecting ({:.1}s)", seconds),
+    }
+}
+
+/// A determinate bar of `width` cells for a percentage
+pub fn bar(percent: f64, width: usize) -> String {
+    let permille = (percent.clamp(0.0, 100.0) * 10.0).round() as usize;
+    crate::utils::bar(permille, 1000, width)
+}
+
+/// The status bar gauge of a generation: a bar with the completion percent
+/// once the expected length is known, the rate and the remaining time
+///
+/// Without `with_bar` only the numbers are shown. Returns `None` until the
+/// first token arrives.
+pub fn gauge(progress: &ProgressStats, with_bar: bool) -> Option<String> {
+    if progress.tokens_generated == 0 {
+        return None;
+    }
+    let mut parts = Vec::new();
+    match progress.completion_percent {
+        Some(percent) if with_bar => {
+            parts.push(format!("{} {:.0}%", bar(percent, GAUGE_WIDTH), percent))
+        }
+        Some(percent) => parts.push(format!("{:.0}%", percent)),
+        None => parts.push(format!("{} tok", progress.tokens_generated)),
+    }
+    if progress.tokens_per_second > 0.0 {
+        parts.push(format!("{:.0} tok/s", progress.tokens_per_second));
+    }
+    if progress.estimate_remaining_seconds().is_some() {
+        parts.push(format!("ETA {}", progress.format_remaining_time()));
+    }
+    Some(parts.join(" · "))
+}
+
+/// Whether an output line holds a spinner frame or progress line and nothing else
+pub fn is_indicator(line: &str) -> bool {
+    let line = line.trim();
+    if line.is_empty() {
+        return false;
+    }
+    if ALL_FRAMES
+        .iter()
+        .flat_map(|frames| frames.iter())
+        .any(|frame| frame.trim() == line)
+    {
+        return true;
+    }
+    let bar = line.trim_start_matches(|c: char| {
+        c == ' '
+            || ALL_FRAMES
+                .iter()
+                .flat_map(|frames| frames.iter())
+                .any(|frame| frame.contains(c))
+    });
+    STAGE_LINE.is_match(bar) || (bar.starts_with(['█', '░']) && bar.contains("% · ETA "))
+}
+
+/// Clear the spinner frames and progress lines left in the output
+pub fn clear_indicators(text: &str) -> String {
+    let cleared: Vec<&str> = text
+        .split('\n')
+        .map(|line| if is_indicator(line) { "" } else { line })
+        .collect();
+    cleared.join("\n")
+}
@@ -0,0 +1,54 @@
+//! Highlighting search matches in styled output lines
+
+use crate::utils::search::match_ranges;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+
+/// `line` with every occurrence of `term` drawn in `style`
+///
+/// Matches can span several spans, e.g. a word half in a diff color; each
+/// piece keeps the rest of its own style.
+pub fn highlight(line: Line<'static>, term: &str, style: Style) -> Line<'static> {
+    let text: String = line
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect();
+    let ranges = match_ranges(&text, term);
+    if ranges.is_empty() {
+        return line;
+    }
+
+    let mut spans = Vec::new();
+    let mut start = 0;
+    for span in line.spans {
+        let end = start + span.content.len();
+        let mut cut = start;
+        for range in ranges
+            .iter()
+            .filter(|range| range.start < end && range.end > start)
+        {
+            let from = range.start.max(start);
+            let to = range.end.min(end);
+            if from > cut {
+                spans.push(Span::styled(
+                    span.content[cut - start..from - start].to_string(),
+                    span.style,
+                ));
+            }
+            spans.push(Span::styled(
+                span.content[from - start..to - start].to_string(),
+                span.style.patch(style),
+            ));
+            cut = to;
+        }
+        if cut < end {
+            spans.push(Span::styled(
+                span.content[cut - start..].to_string(),
+                span.style,
+            ));
+        }
+        start = end;
+    }
+    Line { spans, ..line }
+}
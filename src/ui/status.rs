@@ -0,0 +1,56 @@
+//! Clickable status bar segments
+//!
+//! While the status bar is drawn, the place of every segment that reacts
+//! to the mouse is remembered, so a click can be mapped back to its
+//! action: the mode toggles between AI and bash for plain input, the cost
+//! shows `/cost` and the directory opens the file tree.
+
+use ratatui::layout::Rect;
+
+/// What a click on a status bar segment does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusAction {
+    /// Switch plain input between AI prompts and bash commands
+    ToggleMode,
+    /// Show the session cost like `/cost`
+    Cost,
+    /// Open the file tree of the current directory
+    FileTree,
+}
+
+/// Where the clickable segments were drawn
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatusHits {
+    segments: Vec<(Rect, StatusAction)>,
+}
+
+impl StatusHits {
+    /// Lay out segments of the given widths from the left of `area` and
+    /// remember the ones with an action; those past the edge are cut off
+    pub fn layout(area: Rect, segments: &[(usize, Option<StatusAction>)]) -> Self {
+        let mut hits = Vec::new();
+        let mut x = area.x;
+        let right = area.x.saturating_add(area.width);
+        for &(width, action) in segments {
+            let width = (width.min(u16::MAX as usize) as u16).min(right.saturating_sub(x));
+            if let Some(action) = action
+                && width > 0
+            {
+                hits.push((Rect::new(x, area.y, width, area.height), action));
+            }
+            x = x.saturating_add(width);
+        }
+        Self { segments: hits }
+    }
+
+    /// The action of the segment at a terminal cell
+    pub fn action_at(&self, column: u16, row: u16) -> Option<StatusAction> {
+        self.segments
+            .iter()
+            .find(|(rect, _)| {
+                (rect.x..rect.x + rect.width).contains(&column)
+                    && (rect.y..rect.y + rect.height).contains(&row)
+            })
+            .map(|(_, action)| *action)
+    }
+}
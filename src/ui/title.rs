@@ -0,0 +1,119 @@
+//! The terminal's title and taskbar progress
+//!
+//! The title names the app and the current directory and is marked while a
+//! generation runs. Progress goes out as the OSC 9;4 sequence that Windows
+//! Terminal and ConEmu show in the tab and the taskbar: a percentage once
+//! one is known, an indeterminate state before. Other terminals may show
+//! OSC 9 as a notification, so `auto` only sends it to those two.
+
+use crate::ai::types::TaskStatus;
+use crate::config::TaskbarProgress;
+use crate::utils::tasks::{Task, TaskType};
+use std::path::Path;
+
+/// Name at the start of the title
+pub const APP_NAME: &str = "AI Coder";
+
+/// Marker before the title while the AI is busy
+const BUSY_MARKER: &str = "⏳";
+
+/// Save the title of the shell before changing it (XTWINOPS)
+pub const PUSH_TITLE: &str = "\x1b[22;0t";
+
+/// Bring back the title saved with [`PUSH_TITLE`]
+pub const POP_TITLE: &str = "\x1b[23;0t";
+
+/// Progress shown by the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskbarState {
+    /// Nothing running
+    Hidden,
+    /// Running without a known completion
+    Indeterminate,
+    /// Percent done
+    Progress(u8),
+    /// The last run failed
+    Error,
+}
+
+impl TaskbarState {
+    /// The OSC 9;4 sequence setting this state
+    pub fn sequence(self) -> String {
+        let (state, value) = match self {
+            TaskbarState::Hidden => (0, 0),
+            TaskbarState::Progress(percent) => (1, percent.min(100)),
+            TaskbarState::Error => (2, 100),
+            TaskbarState::Indeterminate => (3, 0),
+        };
+        format!("\x1b]9;4;{};{}\x07", state, value)
+    }
+}
+
+/// Progress of the AI generations among `tasks`: the least advanced running
+/// one, or an error while the most recent one has failed
+pub fn taskbar_state(tasks: &[Task]) -> TaskbarState {
+    let generations: Vec<&Task> = tasks
+        .iter()
+        .filter(|task| task.task_type == TaskType::AIGeneration)
+        .collect();
+    let running: Vec<&Task> = generations
+        .iter()
+        .copied()
+        .filter(|task| matches!(task.status, TaskStatus::Running | TaskStatus::Pending))
+        .collect();
+    if !running.is_empty() {
+        let percents: Vec<f64> = running
+            .iter()
+            .map(|task| {
+                task.progress
+                    .as_ref()
+                    .and_then(|progress| progress.completion_percent)
+            })
+            .collect::<Option<_>>()
+            .unwrap_or_default();
+        return match percents.into_iter().reduce(f64::min) {
+            Some(percent) => TaskbarState::Progress(percent.clamp(0.0, 100.0).round() as u8),
+            None => TaskbarState::Indeterminate,
+        };
+    }
+    let last = generations
+        .iter()
+        .filter(|task| task.completed_at.is_some())
+        .max_by_key(|task| task.completed_at);
+    match last {
+        Some(task) if task.status == TaskStatus::Failed => TaskbarState::Error,
+        _ => TaskbarState::Hidden,
+    }
+}
+
+/// Title for the directory, marked while busy
+pub fn title(dir: &Path, busy: bool) -> String {
+    let dir = dir
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| dir.display().to_string());
+    if busy {
+        format!("{} {} — {}", BUSY_MARKER, APP_NAME, dir)
+    } else {
+        format!("{} — {}", APP_NAME, dir)
+    }
+}
+
+/// The OSC 0 sequence setting the window and tab title
+pub fn title_sequence(title: &str) -> String {
+    let title: String = title.chars().filter(|c| !c.is_control()).collect();
+    format!("\x1b]0;{}\x07", title)
+}
+
+/// Whether progress sequences are sent, looking up environment variables
+/// with `var` to recognize the terminal for `auto`
+pub fn progress_enabled(setting: TaskbarProgress, var: impl Fn(&str) -> Option<String>) -> bool {
+    match setting {
+        TaskbarProgress::On => true,
+        TaskbarProgress::Off => false,
+        TaskbarProgress::Auto => {
+            var("WT_SESSION").is_some_and(|session| !session.is_empty())
+                || var("ConEmuANSI").is_some_and(|ansi| ansi.eq_ignore_ascii_case("on"))
+        }
+    }
+}
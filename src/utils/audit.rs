@@ -0,0 +1,86 @@
+//! Audit log of edited bash blocks and moderation verdicts
+//!
+//! A bash block from a response that was loaded into the input with `e` and
+//! then run is appended to `~/.ai-coder/audit.jsonl`, one JSON object per
+//! line, with the block as the model wrote it, the command that actually ran
+//! and the response it came from. With moderation on, the verdict on every
+//! checked response is appended as well, marked `"kind": "moderation"`.
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// The audit log in the config directory
+pub fn default_path() -> PathBuf {
+    crate::config::get_config_dir().join("audit.jsonl")
+}
+
+/// A bash block from a response, run after editing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// When the command ran
+    pub timestamp: DateTime<Local>,
+    /// Stored session the response belongs to
+    pub session_id: String,
+    /// Index of the response among the messages of the session
+    pub response: usize,
+    /// Prompt the response answered
+    pub prompt: String,
+    /// The block as it appeared in the response
+    pub original: String,
+    /// The command that ran
+    pub command: String,
+}
+
+impl AuditEntry {
+    /// Whether the command differs from the block
+    pub fn edited(&self) -> bool {
+        self.original.trim() != self.command.trim()
+    }
+}
+
+/// What moderation decided about a response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModerationEntry {
+    /// Always `moderation`, telling the entry apart from edited blocks
+    pub kind: String,
+    /// When the response was checked
+    pub timestamp: DateTime<Local>,
+    /// Stored session the response belongs to
+    pub session_id: String,
+    /// Provider and model that wrote the response
+    pub model: String,
+    /// Classifier used: `openai` or `command`
+    pub backend: String,
+    /// `passed`, `flagged`, `blocked` or `error`
+    pub verdict: String,
+    /// Categories the classifier flagged
+    pub categories: Vec<String>,
+    /// Why the check failed, for the `error` verdict
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Append an entry to the audit log at `path`
+pub fn append<T: Serialize>(path: &Path, entry: &T) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let line = serde_json::to_string(entry).map_err(io::Error::other)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+/// Read the edited blocks of the audit log at `path`, skipping other lines
+pub fn load(path: &Path) -> io::Result<Vec<AuditEntry>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
@@ -0,0 +1,207 @@
+//! Configuration bundles
+//!
+//! `/config export` writes the configuration, with its themes, personas,
+//! aliases and post-processors, and the prompt library into one JSON file
+//! that a team can share. Secrets such as API keys are left out, or kept
+//! encrypted with a passphrase when asked for. Importing a bundle replaces
+//! the configuration but keeps the local secrets, sync and encryption
+//! settings, and merges the prompt library.
+
+use crate::config::{AppConfig, EncryptionConfig, SyncConfig};
+use crate::utils::crypto::{Cipher, CryptoError};
+use crate::utils::prompts::PromptLibrary;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Marks a file as a configuration bundle
+pub const FORMAT: &str = "ai-coder-bundle";
+
+/// Bundle layout written by this version
+pub const VERSION: u32 = 1;
+
+/// Fields whose values are secrets wherever they appear in the configuration
+pub const SECRET_FIELDS: &[&str] = &["api_key", "password", "passphrase", "client_secret"];
+
+/// Secrets by their JSON pointer in the configuration, such as `/ai/openai/api_key`
+pub type Secrets = BTreeMap<String, String>;
+
+/// Errors reading or applying a bundle
+#[derive(Debug, Error)]
+pub enum BundleError {
+    #[error("not a configuration bundle: {0}")]
+    Format(String),
+
+    #[error("bundle version {0} is newer than this version of ai-coder supports")]
+    Version(u32),
+
+    #[error("invalid configuration in the bundle: {0}")]
+    Config(String),
+
+    #[error("API keys: {0}")]
+    Crypto(#[from] CryptoError),
+}
+
+/// A shareable configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bundle {
+    pub format: String,
+    pub version: u32,
+    pub created: DateTime<Local>,
+    /// The configuration with secrets blanked and without sync and encryption settings
+    pub config: Value,
+    /// Prompts sent before, merged into the library on import
+    #[serde(default)]
+    pub prompts: Option<PromptLibrary>,
+    /// Secrets encrypted with the bundle passphrase, base64
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keys: Option<String>,
+}
+
+/// What importing a bundle changes
+#[derive(Debug)]
+pub struct Applied {
+    pub config: AppConfig,
+    /// Secrets taken from the bundle
+    pub imported_keys: usize,
+    /// Whether the bundle has encrypted keys that were not opened
+    pub locked_keys: bool,
+}
+
+/// Blank every secret in `value`, returning them by pointer
+pub fn take_secrets(value: &mut Value) -> Secrets {
+    let mut secrets = Secrets::new();
+    collect_secrets(value, String::new(), &mut secrets);
+    secrets
+}
+
+fn collect_secrets(value: &mut Value, pointer: String, secrets: &mut Secrets) {
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                let child_pointer =
+                    format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+                match child {
+                    Value::String(secret) if SECRET_FIELDS.contains(&key.as_str()) => {
+                        if !secret.is_empty() {
+                            secrets.insert(child_pointer, std::mem::take(secret));
+                        }
+                    }
+                    _ => collect_secrets(child, child_pointer, secrets),
+                }
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter_mut().enumerate() {
+                collect_secrets(item, format!("{}/{}", pointer, index), secrets);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Put secrets back where they were taken from; ones whose place is gone are dropped
+fn restore_secrets(value: &mut Value, secrets: &Secrets) -> usize {
+    let mut restored = 0;
+    for (pointer, secret) in secrets {
+        if let Some(Value::String(slot)) = value.pointer_mut(pointer) {
+            *slot = secret.clone();
+            restored += 1;
+        }
+    }
+    restored
+}
+
+/// Bundle `config` and `prompts`, encrypting the secrets with `cipher` or leaving them out
+pub fn export(
+    config: &AppConfig,
+    prompts: Option<PromptLibrary>,
+    cipher: Option<&Cipher>,
+    now: DateTime<Local>,
+) -> Result<Bundle, BundleError> {
+    // Sync and encryption settings belong to the machine
+    let mut config = config.clone();
+    config.sync = SyncConfig::default();
+    config.encryption = EncryptionConfig::default();
+
+    let mut value =
+        serde_json::to_value(&config).map_err(|e| BundleError::Config(e.to_string()))?;
+    let secrets = take_secrets(&mut value);
+    let keys = match cipher {
+        Some(cipher) if !secrets.is_empty() => {
+            let plain =
+                serde_json::to_vec(&secrets).map_err(|e| BundleError::Config(e.to_string()))?;
+            Some(STANDARD.encode(cipher.encrypt(&plain)?))
+        }
+        _ => None,
+    };
+    Ok(Bundle {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        created: now,
+        config: value,
+        prompts,
+        keys,
+    })
+}
+
+/// Read a bundle written by [`export`]
+pub fn parse(data: &[u8]) -> Result<Bundle, BundleError> {
+    let bundle: Bundle =
+        serde_json::from_slice(data).map_err(|e| BundleError::Format(e.to_string()))?;
+    if bundle.format != FORMAT {
+        return Err(BundleError::Format(format!(
+            "unknown format '{}'",
+            bundle.format
+        )));
+    }
+    if bundle.version > VERSION {
+        return Err(BundleError::Version(bundle.version));
+    }
+    Ok(bundle)
+}
+
+/// The configuration a bundle gives on this machine
+///
+/// Secrets come from `local` unless the bundle carries encrypted ones and
+/// `cipher` opens them; the local sync and encryption settings stay.
+pub fn apply(
+    bundle: &Bundle,
+    local: &AppConfig,
+    cipher: Option<&Cipher>,
+) -> Result<Applied, BundleError> {
+    let mut value = bundle.config.clone();
+    let mut local_value =
+        serde_json::to_value(local).map_err(|e| BundleError::Config(e.to_string()))?;
+    restore_secrets(&mut value, &take_secrets(&mut local_value));
+
+    let mut imported_keys = 0;
+    let mut locked_keys = false;
+    if let Some(keys) = &bundle.keys {
+        match cipher {
+            Some(cipher) => {
+                let blob = STANDARD
+                    .decode(keys.trim())
+                    .map_err(|e| BundleError::Format(e.to_string()))?;
+                let secrets: Secrets = serde_json::from_slice(&cipher.decrypt(&blob)?)
+                    .map_err(|e| BundleError::Format(e.to_string()))?;
+                imported_keys = restore_secrets(&mut value, &secrets);
+            }
+            None => locked_keys = true,
+        }
+    }
+
+    let mut config: AppConfig =
+        serde_json::from_value(value).map_err(|e| BundleError::Config(e.to_string()))?;
+    config.sync = local.sync.clone();
+    config.encryption = local.encryption.clone();
+    Ok(Applied {
+        config,
+        imported_keys,
+        locked_keys,
+    })
+}
@@ -0,0 +1,256 @@
+//! Crash reports
+//!
+//! On a panic or fatal error a diagnostic bundle is written to
+//! `~/.ai-coder/crash/`: version information, the configuration with secrets
+//! masked, the last lines of the log file and the recent task list. The path
+//! is printed so it can be attached to a bug report.
+//!
+//! The report is built from a panic hook, so it never waits for a lock: a
+//! panic may happen while the configuration or the task list is locked.
+
+use crate::config::{self, AppConfig, try_get_config};
+use crate::utils::TaskManager;
+use chrono::Local;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_yaml::Value;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, TryLockError};
+
+/// Log lines included in a report
+const LOG_LINES: usize = 200;
+
+/// Placeholder for masked secrets
+const MASK: &str = "***";
+
+/// Task manager whose tasks are listed in reports
+static TASKS: Lazy<Mutex<Option<TaskManager>>> = Lazy::new(|| Mutex::new(None));
+
+/// API keys, access tokens and bearer tokens that may show up in logs
+static SECRET_PATTERN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(sk-[A-Za-z0-9_-]{8,}|gh[pousr]_[A-Za-z0-9]{20,}|AKIA[0-9A-Z]{16}|xox[abpr]-[A-Za-z0-9-]{10,}|(?i:bearer)\s+[A-Za-z0-9._~+/=-]{8,})",
+    )
+    .unwrap()
+});
+
+/// List the tasks of this manager in crash reports
+pub fn watch_tasks(task_manager: TaskManager) {
+    *TASKS.lock().unwrap_or_else(|e| e.into_inner()) = Some(task_manager);
+}
+
+/// Write a report when the app panics
+///
+/// Panics on the main thread restore the terminal and print the report path;
+/// panics in background tasks only log it, since the app keeps running. The
+/// previous hook runs afterwards either way, so the panic message is kept.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let reason = format!("Panic: {}", info);
+        let on_main_thread = std::thread::current().name() == Some("main");
+        match write_report(&reason) {
+            Ok(path) if on_main_thread => {
+                restore_terminal();
+                eprintln!(
+                    "💥 AI Coder crashed. A diagnostic report was written to {}\n   Please attach it to your bug report.",
+                    path.display()
+                );
+            }
+            Ok(path) => {
+                super::log_error(&format!(
+                    "{} — report written to {}",
+                    reason,
+                    path.display()
+                ))
+                .ok();
+            }
+            Err(e) => {
+                if on_main_thread {
+                    restore_terminal();
+                }
+                eprintln!("Failed to write crash report: {}", e);
+            }
+        }
+        default_hook(info);
+    }));
+}
+
+/// Leave the alternate screen so the report path is visible
+fn restore_terminal() {
+    use crossterm::{
+        ExecutableCommand,
+        event::DisableMouseCapture,
+        terminal::{LeaveAlternateScreen, disable_raw_mode},
+    };
+    let _ = disable_raw_mode();
+    let mut stdout = io::stdout();
+    let _ = stdout.execute(DisableMouseCapture);
+    let _ = stdout.execute(LeaveAlternateScreen);
+}
+
+/// Write a report to the crash directory and return its path
+pub fn write_report(reason: &str) -> io::Result<PathBuf> {
+    write_report_to(&config::get_config_dir().join("crash"), reason)
+}
+
+/// Write a report to `dir` and return its path
+///
+/// Reports are never overwritten: the name holds the process id, and a
+/// counter when the same process crashes twice within a second.
+pub fn write_report_to(dir: &Path, reason: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+    let stem = format!(
+        "crash-{}-{}",
+        Local::now().format("%Y%m%d-%H%M%S"),
+        std::process::id()
+    );
+    let report = build_report(reason);
+    let mut path = dir.join(format!("{}.txt", stem));
+    let mut attempt = 1;
+    loop {
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(report.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+                path = dir.join(format!("{}-{}.txt", stem, attempt));
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// The text of a report
+pub fn build_report(reason: &str) -> String {
+    let config = try_get_config();
+    let mut report = String::new();
+
+    report.push_str("== AI Coder crash report ==\n");
+    report.push_str(&format!("time: {}\n", Local::now().to_rfc3339()));
+    report.push_str(&format!("version: {}\n", env!("CARGO_PKG_VERSION")));
+    report.push_str(&format!(
+        "platform: {} {}\n",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    ));
+    report.push_str(&format!("\n== Reason ==\n{}\n", redact_secrets(reason)));
+
+    report.push_str("\n== Configuration ==\n");
+    match &config {
+        Some(config) => report.push_str(&redact_config(config)),
+        None => report.push_str("(not available: the configuration was locked)\n"),
+    }
+
+    report.push_str("\n== Tasks ==\n");
+    report.push_str(&task_summary());
+
+    report.push_str(&format!("\n== Log (last {} lines) ==\n", LOG_LINES));
+    match &config {
+        Some(config) => report.push_str(&redact_secrets(&last_log_lines(config, LOG_LINES))),
+        None => report.push_str("(not available: the configuration was locked)\n"),
+    }
+    report
+}
+
+/// The configuration as YAML with API keys, tokens and passwords masked
+pub fn redact_config(config: &AppConfig) -> String {
+    let mut value = match serde_yaml::to_value(config) {
+        Ok(value) => value,
+        Err(e) => return format!("(could not serialize configuration: {})\n", e),
+    };
+    mask_secrets(&mut value);
+    serde_yaml::to_string(&value).unwrap_or_default()
+}
+
+/// Whether a configuration key holds a secret
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_lowercase();
+    ["key", "token", "secret", "password", "authorization"]
+        .iter()
+        .any(|word| key.contains(word))
+}
+
+/// Mask the non-empty string values of secret keys, recursively
+fn mask_secrets(value: &mut Value) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping.iter_mut() {
+                let secret = key.as_str().is_some_and(is_secret_key);
+                match value {
+                    Value::String(text) if secret && !text.is_empty() => {
+                        *text = MASK.to_string();
+                    }
+                    _ => mask_secrets(value),
+                }
+            }
+        }
+        Value::Sequence(items) => items.iter_mut().for_each(mask_secrets),
+        _ => {}
+    }
+}
+
+/// Mask API keys and bearer tokens in free text
+pub fn redact_secrets(text: &str) -> String {
+    SECRET_PATTERN.replace_all(text, MASK).to_string()
+}
+
+/// API keys and bearer tokens in free text, in order of appearance
+pub fn find_secrets(text: &str) -> Vec<String> {
+    SECRET_PATTERN
+        .find_iter(text)
+        .map(|found| found.as_str().to_string())
+        .collect()
+}
+
+/// The recent task list, one task per line
+fn task_summary() -> String {
+    let tasks = match TASKS.try_lock() {
+        Ok(tasks) => tasks,
+        Err(TryLockError::Poisoned(e)) => e.into_inner(),
+        Err(TryLockError::WouldBlock) => return "(not available)\n".to_string(),
+    };
+    let Some(mut tasks) = tasks.as_ref().and_then(TaskManager::try_all_tasks) else {
+        return "(not available)\n".to_string();
+    };
+    if tasks.is_empty() {
+        return "(none)\n".to_string();
+    }
+    tasks.sort_by_key(|task| task.created_at);
+    tasks
+        .iter()
+        .map(|task| {
+            format!(
+                "{} {} [{}] {} ({}) {}\n",
+                task.created_at.format("%H:%M:%S"),
+                task.status,
+                task.labels.join(", "),
+                redact_secrets(&task.name),
+                task.id.short(),
+                task.format_duration()
+            )
+        })
+        .collect()
+}
+
+/// The last `count` lines of the log file
+fn last_log_lines(config: &AppConfig, count: usize) -> String {
+    let Some(file) = &config.log_file else {
+        return "(logging has no log file)\n".to_string();
+    };
+    let path = config::get_config_dir().join(file);
+    match fs::read_to_string(&path) {
+        Ok(log) => {
+            let lines: Vec<&str> = log.lines().collect();
+            let mut tail = lines[lines.len().saturating_sub(count)..].join("\n");
+            tail.push('\n');
+            tail
+        }
+        Err(_) if !config.logging_enabled => "(logging is disabled)\n".to_string(),
+        Err(e) => format!("(could not read {}: {})\n", path.display(), e),
+    }
+}
@@ -0,0 +1,213 @@
+//! Encryption of synced and stored data
+//!
+//! Data is sealed with XChaCha20-Poly1305, either under a key derived from a
+//! passphrase with Argon2id or under a random key kept in the OS keyring.
+//! Every blob carries its own salt and nonce, so passphrase-sealed data can
+//! be opened on any machine that knows the passphrase.
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use std::fs;
+use std::io;
+use std::path::Path;
+use thiserror::Error;
+
+/// Start of every encrypted blob, with the format version
+const MAGIC: &[u8; 8] = b"AICODER\x01";
+
+/// Bytes of salt in every blob
+pub const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Keyring entry holding the storage key
+const KEYRING_SERVICE: &str = "ai-coder";
+const KEYRING_USER: &str = "storage-key";
+
+/// Errors when sealing or opening data
+#[derive(Debug, Error)]
+pub enum CryptoError {
+    /// No passphrase is configured
+    #[error("no passphrase set")]
+    NoPassphrase,
+
+    /// Data that was not written by [`Cipher::encrypt`]
+    #[error("not encrypted by AI Coder")]
+    Format,
+
+    /// Wrong passphrase or tampered data
+    #[error("wrong passphrase or corrupted data")]
+    Decrypt,
+
+    /// The data could not be sealed
+    #[error("encryption failed")]
+    Encrypt,
+
+    /// Key derivation failed
+    #[error("key derivation failed: {0}")]
+    Kdf(String),
+
+    /// The OS keyring could not be used
+    #[error("keyring: {0}")]
+    Keyring(String),
+}
+
+/// Seals and opens data with a passphrase or a raw key
+///
+/// A passphrase key is derived once per cipher; blobs written by other
+/// ciphers, which use a different salt, cost one extra derivation to open.
+pub struct Cipher {
+    /// `None` for raw keys, which ignore the salt
+    passphrase: Option<String>,
+    salt: [u8; SALT_LEN],
+    key: Key,
+}
+
+impl std::fmt::Debug for Cipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cipher").finish_non_exhaustive()
+    }
+}
+
+impl Cipher {
+    /// Cipher with a fresh salt
+    pub fn new(passphrase: &str) -> Result<Self, CryptoError> {
+        Self::with_salt(passphrase, new_salt())
+    }
+
+    /// Cipher with a given salt, so blobs it wrote earlier open without
+    /// another key derivation
+    pub fn with_salt(passphrase: &str, salt: [u8; SALT_LEN]) -> Result<Self, CryptoError> {
+        if passphrase.is_empty() {
+            return Err(CryptoError::NoPassphrase);
+        }
+        Ok(Self {
+            passphrase: Some(passphrase.to_string()),
+            salt,
+            key: derive_key(passphrase, &salt)?,
+        })
+    }
+
+    /// Cipher using a 32-byte key as it is
+    pub fn from_key(key: [u8; 32]) -> Self {
+        Self {
+            passphrase: None,
+            salt: [0; SALT_LEN],
+            key: key.into(),
+        }
+    }
+
+    /// Encrypt `plaintext` into a self-contained blob
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let sealed = XChaCha20Poly1305::new(&self.key)
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| CryptoError::Encrypt)?;
+        let mut blob = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + sealed.len());
+        blob.extend_from_slice(MAGIC);
+        blob.extend_from_slice(&self.salt);
+        blob.extend_from_slice(&nonce);
+        blob.extend_from_slice(&sealed);
+        Ok(blob)
+    }
+
+    /// Decrypt a blob written by [`Cipher::encrypt`] with the same passphrase or key
+    pub fn decrypt(&self, blob: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        if !is_encrypted(blob) || blob.len() < MAGIC.len() + SALT_LEN + NONCE_LEN {
+            return Err(CryptoError::Format);
+        }
+        let (salt, rest) = blob[MAGIC.len()..].split_at(SALT_LEN);
+        let (nonce, sealed) = rest.split_at(NONCE_LEN);
+        let key = match &self.passphrase {
+            Some(passphrase) if salt != self.salt => derive_key(passphrase, salt)?,
+            _ => self.key,
+        };
+        XChaCha20Poly1305::new(&key)
+            .decrypt(XNonce::from_slice(nonce), sealed)
+            .map_err(|_| CryptoError::Decrypt)
+    }
+}
+
+/// A random salt for [`Cipher::with_salt`]
+pub fn new_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    salt
+}
+
+/// Whether `data` starts like a blob written by [`Cipher::encrypt`]
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// Read a file, decrypting it when it was written encrypted
+///
+/// Plain files are returned as they are, so data written before encryption
+/// was turned on stays readable.
+pub fn read_file(path: &Path, cipher: Option<&Cipher>) -> io::Result<Vec<u8>> {
+    let data = fs::read(path)?;
+    if !is_encrypted(&data) {
+        return Ok(data);
+    }
+    let Some(cipher) = cipher else {
+        return Err(io::Error::other(format!(
+            "{} is encrypted; turn on encryption to read it",
+            path.display()
+        )));
+    };
+    cipher.decrypt(&data).map_err(io::Error::other)
+}
+
+/// Write a file, encrypted when a cipher is given
+pub fn write_file(path: &Path, data: &[u8], cipher: Option<&Cipher>) -> io::Result<()> {
+    match cipher {
+        Some(cipher) => fs::write(path, cipher.encrypt(data).map_err(io::Error::other)?),
+        None => fs::write(path, data),
+    }
+}
+
+/// The storage key in the OS keyring, created on first use
+pub fn keyring_key() -> Result<[u8; 32], CryptoError> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)
+        .map_err(|e| CryptoError::Keyring(e.to_string()))?;
+    match entry.get_password() {
+        Ok(stored) => parse_key(&stored)
+            .ok_or_else(|| CryptoError::Keyring("the stored key is not valid".to_string())),
+        Err(keyring::Error::NoEntry) => {
+            let mut key = [0u8; 32];
+            rand::thread_rng().fill_bytes(&mut key);
+            entry
+                .set_password(&hex(&key))
+                .map_err(|e| CryptoError::Keyring(e.to_string()))?;
+            Ok(key)
+        }
+        Err(e) => Err(CryptoError::Keyring(e.to_string())),
+    }
+}
+
+/// Lowercase hex of `bytes`
+pub fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A 32-byte key written with [`hex`]
+pub fn parse_key(text: &str) -> Option<[u8; 32]> {
+    let text = text.trim();
+    if text.len() != 64 || !text.is_ascii() {
+        return None;
+    }
+    let mut key = [0u8; 32];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&text[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(key)
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key, CryptoError> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| CryptoError::Kdf(e.to_string()))?;
+    Ok(key)
+}
@@ -0,0 +1,383 @@
+//! Line-based diff utilities
+//!
+//! This module computes line diffs between two texts and renders them
+//! in unified diff format for previews before files are modified. It also
+//! finds unified diffs in displayed output, such as `git diff` output or a
+//! ```` ```diff ```` block of a response, so they can be colored or shown
+//! side by side.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::ops::Range;
+
+/// Number of unchanged lines shown around each change
+const CONTEXT_LINES: usize = 3;
+
+/// Largest LCS table computed before falling back to a full replacement
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// A single line-level diff operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffOp {
+    /// Line present in both texts
+    Equal(String),
+    /// Line only present in the new text
+    Insert(String),
+    /// Line only present in the old text
+    Delete(String),
+}
+
+/// Compute the line operations that turn `old` into `new`
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffOp> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+
+    // Strip the common prefix and suffix so the LCS only covers the changed middle
+    let prefix = a.iter().zip(&b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+
+    let a_mid = &a[prefix..a.len() - suffix];
+    let b_mid = &b[prefix..b.len() - suffix];
+
+    let mut ops: Vec<DiffOp> = a[..prefix]
+        .iter()
+        .map(|line| DiffOp::Equal(line.to_string()))
+        .collect();
+
+    if a_mid.len().saturating_mul(b_mid.len()) > MAX_DIFF_CELLS {
+        // Too large to diff precisely; show it as a full replacement
+        ops.extend(a_mid.iter().map(|line| DiffOp::Delete(line.to_string())));
+        ops.extend(b_mid.iter().map(|line| DiffOp::Insert(line.to_string())));
+    } else {
+        // lcs[i][j] holds the LCS length of a_mid[i..] and b_mid[j..]
+        let (n, m) = (a_mid.len(), b_mid.len());
+        let mut lcs = vec![vec![0u32; m + 1]; n + 1];
+        for i in (0..n).rev() {
+            for j in (0..m).rev() {
+                lcs[i][j] = if a_mid[i] == b_mid[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+
+        let (mut i, mut j) = (0, 0);
+        while i < n && j < m {
+            if a_mid[i] == b_mid[j] {
+                ops.push(DiffOp::Equal(a_mid[i].to_string()));
+                i += 1;
+                j += 1;
+            } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+                ops.push(DiffOp::Delete(a_mid[i].to_string()));
+                i += 1;
+            } else {
+                ops.push(DiffOp::Insert(b_mid[j].to_string()));
+                j += 1;
+            }
+        }
+        ops.extend(
+            a_mid[i..]
+                .iter()
+                .map(|line| DiffOp::Delete(line.to_string())),
+        );
+        ops.extend(
+            b_mid[j..]
+                .iter()
+                .map(|line| DiffOp::Insert(line.to_string())),
+        );
+    }
+
+    ops.extend(
+        a[a.len() - suffix..]
+            .iter()
+            .map(|line| DiffOp::Equal(line.to_string())),
+    );
+    ops
+}
+
+/// Count added and removed lines in a diff
+pub fn diff_stats(ops: &[DiffOp]) -> (usize, usize) {
+    ops.iter().fold((0, 0), |(added, removed), op| match op {
+        DiffOp::Insert(_) => (added + 1, removed),
+        DiffOp::Delete(_) => (added, removed + 1),
+        DiffOp::Equal(_) => (added, removed),
+    })
+}
+
+/// Render the changes between `old` and `new` as a unified diff
+///
+/// Returns an empty string when both texts have the same lines. An empty
+/// `old` text is labelled `/dev/null` so new files read naturally.
+pub fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    let ops = diff_lines(old, new);
+    if ops.iter().all(|op| matches!(op, DiffOp::Equal(_))) {
+        return String::new();
+    }
+
+    let old_label = if old.is_empty() {
+        "/dev/null".to_string()
+    } else {
+        format!("a/{}", path)
+    };
+    let mut result = format!("--- {}\n+++ b/{}\n", old_label, path);
+
+    // Line numbers (0-based) in the old and new text before each operation
+    let mut old_line = Vec::with_capacity(ops.len() + 1);
+    let mut new_line = Vec::with_capacity(ops.len() + 1);
+    let (mut o, mut n) = (0, 0);
+    for op in &ops {
+        old_line.push(o);
+        new_line.push(n);
+        match op {
+            DiffOp::Equal(_) => {
+                o += 1;
+                n += 1;
+            }
+            DiffOp::Delete(_) => o += 1,
+            DiffOp::Insert(_) => n += 1,
+        }
+    }
+
+    // Group changes into hunks, merging those whose context overlaps
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (idx, op) in ops.iter().enumerate() {
+        if matches!(op, DiffOp::Equal(_)) {
+            continue;
+        }
+        let start = idx.saturating_sub(CONTEXT_LINES);
+        let end = (idx + CONTEXT_LINES + 1).min(ops.len());
+        match hunks.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    for (start, end) in hunks {
+        let hunk = &ops[start..end];
+        let old_count = hunk
+            .iter()
+            .filter(|op| !matches!(op, DiffOp::Insert(_)))
+            .count();
+        let new_count = hunk
+            .iter()
+            .filter(|op| !matches!(op, DiffOp::Delete(_)))
+            .count();
+        // Unified diff numbers lines from 1, except for empty ranges
+        let old_start = old_line[start] + usize::from(old_count > 0);
+        let new_start = new_line[start] + usize::from(new_count > 0);
+
+        result.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start, old_count, new_start, new_count
+        ));
+        for op in hunk {
+            let (marker, line) = match op {
+                DiffOp::Equal(line) => (' ', line),
+                DiffOp::Insert(line) => ('+', line),
+                DiffOp::Delete(line) => ('-', line),
+            };
+            result.push(marker);
+            result.push_str(line);
+            result.push('\n');
+        }
+    }
+
+    result
+}
+
+/// Header lines of `git diff` output that come before the hunks
+const HEADER_PREFIXES: &[&str] = &[
+    "diff --git ",
+    "index ",
+    "--- ",
+    "+++ ",
+    "new file mode",
+    "deleted file mode",
+    "old mode",
+    "new mode",
+    "similarity index",
+    "dissimilarity index",
+    "rename from",
+    "rename to",
+    "copy from",
+    "copy to",
+    "Binary files",
+];
+
+static HUNK_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^@@ -\d+(?:,(\d+))? \+\d+(?:,(\d+))? @@").unwrap());
+
+/// What a line of a unified diff is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLineKind {
+    /// File header such as `diff --git` or `+++ b/path`
+    Header,
+    /// Hunk header `@@ -1,3 +1,4 @@`
+    Hunk,
+    Added,
+    Removed,
+    Context,
+}
+
+/// Classify a line of a unified diff
+pub fn classify_diff_line(line: &str) -> DiffLineKind {
+    if HEADER_PREFIXES
+        .iter()
+        .any(|prefix| line.starts_with(prefix))
+    {
+        DiffLineKind::Header
+    } else if line.starts_with("@@") {
+        DiffLineKind::Hunk
+    } else if line.starts_with('+') {
+        DiffLineKind::Added
+    } else if line.starts_with('-') {
+        DiffLineKind::Removed
+    } else {
+        DiffLineKind::Context
+    }
+}
+
+/// Line counts of the old and new side announced by a hunk header
+fn hunk_counts(line: &str) -> Option<(usize, usize)> {
+    let cap = HUNK_RE.captures(line)?;
+    let count = |group: usize| cap.get(group).map_or(Some(1), |m| m.as_str().parse().ok());
+    Some((count(1)?, count(2)?))
+}
+
+/// Ranges of lines that form unified diffs
+///
+/// ```` ```diff ```` and ```` ```patch ```` blocks count as diffs throughout.
+/// Elsewhere a diff starts at `diff --git`, at a `---`/`+++` pair or at a
+/// hunk header, and each hunk is as long as its header says, so prose after
+/// a diff is not mistaken for part of it.
+pub fn find_unified_diffs<S: AsRef<str>>(lines: &[S]) -> Vec<Range<usize>> {
+    let mut diffs = Vec::new();
+    let mut idx = 0;
+    while idx < lines.len() {
+        let line = lines[idx].as_ref();
+        let fence = line.trim_start().strip_prefix("```").map(str::trim);
+        if matches!(fence, Some("diff" | "patch")) {
+            let start = idx + 1;
+            let end = (start..lines.len())
+                .find(|&i| lines[i].as_ref().trim_start().starts_with("```"))
+                .unwrap_or(lines.len());
+            if end > start {
+                diffs.push(start..end);
+            }
+            idx = end + 1;
+            continue;
+        }
+
+        let starts = line.starts_with("diff --git ")
+            || (line.starts_with("--- ")
+                && lines
+                    .get(idx + 1)
+                    .is_some_and(|next| next.as_ref().starts_with("+++ ")))
+            || hunk_counts(line).is_some();
+        if starts {
+            let end = diff_end(lines, idx);
+            diffs.push(idx..end);
+            idx = end;
+        } else {
+            idx += 1;
+        }
+    }
+    diffs
+}
+
+/// Index after the last line of the diff starting at `start`
+fn diff_end<S: AsRef<str>>(lines: &[S], start: usize) -> usize {
+    let (mut old, mut new) = (0usize, 0usize);
+    let mut idx = start;
+    while idx < lines.len() {
+        let line = lines[idx].as_ref();
+        if old > 0 || new > 0 {
+            // Inside a hunk; blank lines are context lines that lost their space
+            match line.chars().next() {
+                None | Some(' ') => {
+                    old = old.saturating_sub(1);
+                    new = new.saturating_sub(1);
+                }
+                Some('-') => old = old.saturating_sub(1),
+                Some('+') => new = new.saturating_sub(1),
+                Some('\\') => {}
+                _ => break,
+            }
+        } else if let Some((old_count, new_count)) = hunk_counts(line) {
+            (old, new) = (old_count, new_count);
+        } else if !line.starts_with('\\')
+            && !HEADER_PREFIXES
+                .iter()
+                .any(|prefix| line.starts_with(prefix))
+        {
+            break;
+        }
+        idx += 1;
+    }
+    idx
+}
+
+/// A row of a side-by-side view of a unified diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SideBySideRow {
+    /// A file or hunk header, across both columns
+    Header(String, DiffLineKind),
+    /// The old line on the left and the new one on the right
+    Pair {
+        left: Option<String>,
+        right: Option<String>,
+        /// Whether the lines differ, false for context lines
+        changed: bool,
+    },
+}
+
+/// Arrange the lines of a unified diff in two columns
+///
+/// Runs of removed lines are paired with the added lines that follow them,
+/// so a changed line sits next to its replacement.
+pub fn side_by_side<S: AsRef<str>>(lines: &[S]) -> Vec<SideBySideRow> {
+    fn flush(rows: &mut Vec<SideBySideRow>, removed: &mut Vec<String>, added: &mut Vec<String>) {
+        let count = removed.len().max(added.len());
+        let mut removed = removed.drain(..);
+        let mut added = added.drain(..);
+        for _ in 0..count {
+            rows.push(SideBySideRow::Pair {
+                left: removed.next(),
+                right: added.next(),
+                changed: true,
+            });
+        }
+    }
+
+    let mut rows = Vec::new();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    for line in lines {
+        let line = line.as_ref();
+        match classify_diff_line(line) {
+            DiffLineKind::Removed => removed.push(line[1..].to_string()),
+            DiffLineKind::Added => added.push(line[1..].to_string()),
+            DiffLineKind::Context => {
+                flush(&mut rows, &mut removed, &mut added);
+                let text = line.strip_prefix(' ').unwrap_or(line).to_string();
+                rows.push(SideBySideRow::Pair {
+                    left: Some(text.clone()),
+                    right: Some(text),
+                    changed: false,
+                });
+            }
+            kind => {
+                flush(&mut rows, &mut removed, &mut added);
+                rows.push(SideBySideRow::Header(line.to_string(), kind));
+            }
+        }
+    }
+    flush(&mut rows, &mut removed, &mut added);
+    rows
+}
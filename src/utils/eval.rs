@@ -0,0 +1,301 @@
+//! Evaluation of prompts across models
+//!
+//! `/eval <template|file> [text] --models m1,m2` sends the same prompts to
+//! several models and compares the answers. A template gives one prompt; a
+//! YAML or JSON file gives a list of cases, each a prompt with keywords the
+//! response must contain (`expect`) or must not contain (`forbid`). Responses
+//! are scored by those assertions and, with `--judge <model>`, by a model
+//! grading each answer from 0 to 10. The results are shown as one table row
+//! per model with the assertions passed, the judge's mean score, the mean
+//! latency and the cost.
+
+use crate::ai::types::Provider;
+use crate::utils::format_money;
+use crate::utils::latency::format_latency;
+use serde::Deserialize;
+use std::fmt;
+use std::time::Duration;
+
+/// Highest score the judge gives
+pub const MAX_SCORE: u8 = 10;
+
+/// A prompt to evaluate
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct EvalCase {
+    pub prompt: String,
+    /// Keywords the response must contain, ignoring case
+    #[serde(default)]
+    pub expect: Vec<String>,
+    /// Keywords the response must not contain, ignoring case
+    #[serde(default)]
+    pub forbid: Vec<String>,
+}
+
+impl EvalCase {
+    /// Number of assertions checked for the case
+    pub fn assertions(&self) -> usize {
+        self.expect.len() + self.forbid.len()
+    }
+
+    /// The assertions `response` fails, described for the report
+    pub fn failures(&self, response: &str) -> Vec<String> {
+        let response = response.to_lowercase();
+        let missing = self
+            .expect
+            .iter()
+            .filter(|keyword| !response.contains(&keyword.to_lowercase()))
+            .map(|keyword| format!("missing \"{}\"", keyword));
+        let present = self
+            .forbid
+            .iter()
+            .filter(|keyword| response.contains(&keyword.to_lowercase()))
+            .map(|keyword| format!("contains \"{}\"", keyword));
+        missing.chain(present).collect()
+    }
+}
+
+/// A model to evaluate
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalTarget {
+    pub provider: Provider,
+    pub model: String,
+}
+
+impl fmt::Display for EvalTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.provider, self.model)
+    }
+}
+
+/// A model given as `provider/model`, or as a model of the `active` provider
+pub fn parse_target(spec: &str, active: Provider) -> Result<EvalTarget, String> {
+    let spec = spec.trim();
+    let (provider, model) = match spec.split_once('/') {
+        Some((prefix, model)) => match prefix.parse::<Provider>() {
+            Ok(provider) => (provider, model),
+            // Model names such as `meta-llama/llama-3` belong to the active provider
+            Err(_) => (active, spec),
+        },
+        None => (active, spec),
+    };
+    if model.is_empty() {
+        return Err(format!("no model in '{}'", spec));
+    }
+    Ok(EvalTarget {
+        provider,
+        model: model.to_string(),
+    })
+}
+
+/// The arguments of `/eval`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EvalArgs {
+    /// Template name or cases file
+    pub source: String,
+    /// Text filling the template
+    pub text: String,
+    pub models: Vec<String>,
+    /// Keywords expected in every response
+    pub expect: Vec<String>,
+    /// Model grading the responses
+    pub judge: Option<String>,
+}
+
+/// Parse `<template|file> [text] --models m1,m2 [--expect k1,k2] [--judge model]`
+pub fn parse_args(args: &str) -> Result<EvalArgs, String> {
+    let list = |value: &str| -> Vec<String> {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let mut parsed = EvalArgs::default();
+    let mut text = Vec::new();
+    let mut words = args.split_whitespace();
+    while let Some(word) = words.next() {
+        let mut value = || {
+            words
+                .next()
+                .ok_or_else(|| format!("{} needs a value", word))
+        };
+        match word {
+            "--models" | "-m" => parsed.models.extend(list(value()?)),
+            "--expect" => parsed.expect.extend(list(value()?)),
+            "--judge" => parsed.judge = Some(value()?.to_string()),
+            flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+            _ if parsed.source.is_empty() => parsed.source = word.to_string(),
+            _ => text.push(word),
+        }
+    }
+    parsed.text = text.join(" ");
+    if parsed.source.is_empty() {
+        return Err("name a template or a file of cases".to_string());
+    }
+    if parsed.models.is_empty() {
+        return Err("list the models to compare with --models".to_string());
+    }
+    Ok(parsed)
+}
+
+/// Whether `source` names a file of cases rather than a template
+pub fn is_cases_file(source: &str) -> bool {
+    [".yaml", ".yml", ".json"]
+        .iter()
+        .any(|ext| source.to_lowercase().ends_with(ext))
+}
+
+/// The cases of a YAML or JSON file: a list of `{prompt, expect, forbid}`
+pub fn parse_cases(content: &str) -> Result<Vec<EvalCase>, String> {
+    let cases: Vec<EvalCase> =
+        serde_yaml::from_str(content).map_err(|e| format!("invalid cases: {}", e))?;
+    if cases.iter().any(|case| case.prompt.trim().is_empty()) {
+        return Err("every case needs a prompt".to_string());
+    }
+    if cases.is_empty() {
+        return Err("the file has no cases".to_string());
+    }
+    Ok(cases)
+}
+
+/// Prompt asking the judge to grade a response
+pub fn judge_prompt(prompt: &str, response: &str) -> String {
+    format!(
+        "You are grading an AI assistant's answer. Rate how correct, complete and \
+         helpful the answer is for the request, from 0 (useless) to {} (excellent). \
+         Reply with the number only.\n\n<request>\n{}\n</request>\n\n<answer>\n{}\n</answer>",
+        MAX_SCORE,
+        prompt.trim(),
+        response.trim()
+    )
+}
+
+/// The score in the judge's reply, such as `8` or `Score: 8/10`
+pub fn parse_score(reply: &str) -> Option<u8> {
+    reply
+        .split(|c: char| !c.is_ascii_digit())
+        .find(|number| !number.is_empty())
+        .and_then(|number| number.parse::<u8>().ok())
+        .filter(|score| *score <= MAX_SCORE)
+}
+
+/// How one model answered one case
+#[derive(Debug, Clone, PartialEq)]
+pub struct CaseResult {
+    /// The response, or why there is none
+    pub response: Result<String, String>,
+    pub latency: Duration,
+    /// Cost of the response, without the judge
+    pub cost: f64,
+    /// Assertions the response failed
+    pub failures: Vec<String>,
+    /// The judge's score
+    pub score: Option<u8>,
+}
+
+/// How one model answered all cases
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelResult {
+    pub target: EvalTarget,
+    /// In the order of the cases
+    pub cases: Vec<CaseResult>,
+}
+
+impl ModelResult {
+    /// Assertions passed, counting those of failed requests as failed
+    pub fn passed(&self, cases: &[EvalCase]) -> usize {
+        self.cases
+            .iter()
+            .zip(cases)
+            .filter(|(result, _)| result.response.is_ok())
+            .map(|(result, case)| case.assertions() - result.failures.len())
+            .sum()
+    }
+
+    /// Mean score of the judged responses
+    pub fn mean_score(&self) -> Option<f64> {
+        let scores: Vec<u8> = self.cases.iter().filter_map(|case| case.score).collect();
+        if scores.is_empty() {
+            return None;
+        }
+        Some(scores.iter().map(|&score| f64::from(score)).sum::<f64>() / scores.len() as f64)
+    }
+
+    /// Mean latency of the answered cases
+    pub fn mean_latency(&self) -> Option<Duration> {
+        let answered: Vec<Duration> = self
+            .cases
+            .iter()
+            .filter(|case| case.response.is_ok())
+            .map(|case| case.latency)
+            .collect();
+        if answered.is_empty() {
+            return None;
+        }
+        Some(answered.iter().sum::<Duration>() / answered.len() as u32)
+    }
+
+    pub fn cost(&self) -> f64 {
+        self.cases.iter().map(|case| case.cost).sum()
+    }
+
+    pub fn errors(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| case.response.is_err())
+            .count()
+    }
+}
+
+/// The comparison table, one row per model
+pub fn render_table(cases: &[EvalCase], results: &[ModelResult], judged: bool) -> String {
+    let assertions: usize = cases.iter().map(EvalCase::assertions).sum();
+    let names: Vec<String> = results
+        .iter()
+        .map(|result| result.target.to_string())
+        .collect();
+    let width = names
+        .iter()
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("model".len());
+
+    let mut header = format!("  {:<width$}", "model", width = width);
+    if assertions > 0 {
+        header.push_str(&format!("  {:>9}", "passed"));
+    }
+    if judged {
+        header.push_str(&format!("  {:>6}", "judge"));
+    }
+    header.push_str(&format!("  {:>8}  {:>9}  {}", "latency", "cost", "errors"));
+
+    let mut lines = vec![header];
+    for (result, name) in results.iter().zip(&names) {
+        let mut row = format!("  {:<width$}", name, width = width);
+        if assertions > 0 {
+            let passed = format!("{}/{}", result.passed(cases), assertions);
+            row.push_str(&format!("  {:>9}", passed));
+        }
+        if judged {
+            let score = result
+                .mean_score()
+                .map(|score| format!("{:.1}", score))
+                .unwrap_or_else(|| "–".to_string());
+            row.push_str(&format!("  {:>6}", score));
+        }
+        let latency = result
+            .mean_latency()
+            .map(format_latency)
+            .unwrap_or_else(|| "–".to_string());
+        row.push_str(&format!(
+            "  {:>8}  {:>9}  {}",
+            latency,
+            format_money(result.cost()),
+            result.errors()
+        ));
+        lines.push(row);
+    }
+    lines.join("\n")
+}
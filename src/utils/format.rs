@@ -43,6 +43,17 @@ pub fn format_duration(seconds: u64) -> String {
     }
 }
 
+/// Format the time since an event, e.g. `2m ago`
+pub fn format_relative_time(seconds: u64) -> String {
+    match seconds {
+        0..=9 => "just now".to_string(),
+        10..=59 => format!("{}s ago", seconds),
+        60..=3599 => format!("{}m ago", seconds / 60),
+        3600..=86399 => format!("{}h ago", seconds / 3600),
+        _ => format!("{}d ago", seconds / 86400),
+    }
+}
+
 /// Format a number with commas as thousands separators
 pub fn format_number(num: usize) -> String {
     let mut result = String::new();
@@ -64,6 +75,17 @@ pub fn format_float(num: f64, precision: usize) -> String {
     format!("{:.1$}", num, precision)
 }
 
+/// A bar of `width` cells, `█` in proportion to `value` of `max` and `░`
+/// for the rest; any value above zero fills at least one cell
+pub fn bar(value: usize, max: usize, width: usize) -> String {
+    let filled = if max == 0 {
+        0
+    } else {
+        (value.min(max) * width).div_ceil(max)
+    };
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
 /// Format money value
 pub fn format_money(amount: f64) -> String {
     format!("${:.4}", amount)
@@ -0,0 +1,112 @@
+//! Glob pattern matching for project paths
+//!
+//! Supports `*`, `**`, `?`, `[...]` and `{a,b}`. Patterns without a `/`
+//! match the file name at any depth, like `.gitignore` entries.
+
+use regex::Regex;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Directories never descended into when searching the project
+pub(crate) const SKIPPED_DIRS: &[&str] = &[".git", "target", "node_modules"];
+
+/// A compiled glob pattern
+#[derive(Debug, Clone)]
+pub struct Glob {
+    regex: Regex,
+    match_file_name: bool,
+}
+
+impl Glob {
+    /// Compile a glob pattern
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        let pattern = pattern.trim_start_matches("./");
+        let match_file_name = !pattern.contains('/');
+
+        let mut regex = String::from("^");
+        let mut chars = pattern.chars().peekable();
+        let mut in_braces = false;
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+                    // `**/` also matches zero directories
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        regex.push_str("(?:.*/)?");
+                    } else {
+                        regex.push_str(".*");
+                    }
+                }
+                '*' => regex.push_str("[^/]*"),
+                '?' => regex.push_str("[^/]"),
+                '[' => {
+                    regex.push('[');
+                    if chars.peek() == Some(&'!') {
+                        chars.next();
+                        regex.push('^');
+                    }
+                    for c in chars.by_ref() {
+                        if c == ']' {
+                            break;
+                        }
+                        if c == '\\' || c == '[' {
+                            regex.push('\\');
+                        }
+                        regex.push(c);
+                    }
+                    regex.push(']');
+                }
+                '{' => {
+                    in_braces = true;
+                    regex.push_str("(?:");
+                }
+                '}' if in_braces => {
+                    in_braces = false;
+                    regex.push(')');
+                }
+                ',' if in_braces => regex.push('|'),
+                c => regex.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+        regex.push('$');
+
+        Ok(Self {
+            regex: Regex::new(&regex)?,
+            match_file_name,
+        })
+    }
+
+    /// Check whether a relative path matches the pattern
+    pub fn is_match(&self, path: &Path) -> bool {
+        let path = path.to_string_lossy().replace('\\', "/");
+        if self.match_file_name {
+            let file_name = path.rsplit('/').next().unwrap_or(&path);
+            self.regex.is_match(file_name)
+        } else {
+            self.regex.is_match(&path)
+        }
+    }
+}
+
+/// Find files under `root` matching the glob, as sorted paths relative to `root`
+///
+/// Hidden directories and build output (`.git`, `target`, `node_modules`)
+/// are skipped.
+pub fn find_files(root: &Path, glob: &Glob) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0
+                || !entry.file_type().is_dir()
+                || !(name.starts_with('.') || SKIPPED_DIRS.contains(&name.as_ref()))
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| entry.path().strip_prefix(root).ok().map(Path::to_path_buf))
+        .filter(|path| glob.is_match(path))
+        .collect();
+    files.sort();
+    files
+}
@@ -0,0 +1,359 @@
+//! Image generation for `/image`
+//!
+//! Images come from the OpenAI images API or the txt2img API of a local
+//! Stable Diffusion web UI and are saved as PNG files. Terminals that speak
+//! the kitty graphics protocol get the image inline; sixel terminals get it
+//! through `img2sixel`. Everywhere else only the path is printed.
+
+use crate::ai::{AIError, TokenUsage};
+use crate::config::{ImageBackend, ImageConfig, ImageDisplay, get_config_dir};
+use crate::platform;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// Longest time an image may take to generate
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// Base64 bytes per kitty graphics escape sequence
+const KITTY_CHUNK: usize = 4096;
+
+/// An image returned by the backend
+#[derive(Debug, Clone)]
+pub struct GeneratedImage {
+    /// PNG data
+    pub png: Vec<u8>,
+    /// Model that made it
+    pub model: String,
+    /// The prompt as the model rewrote it, if it did
+    pub revised_prompt: Option<String>,
+    /// Tokens the API reported, zero when it bills per image
+    pub usage: TokenUsage,
+}
+
+/// Ways of showing an image in the terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Graphics {
+    Kitty,
+    Sixel,
+    /// Print the path only
+    None,
+}
+
+/// Generate an image with the configured backend
+pub async fn generate(config: &ImageConfig, prompt: &str) -> Result<GeneratedImage, AIError> {
+    match config.backend {
+        ImageBackend::OpenAI => generate_openai(config, prompt).await,
+        ImageBackend::StableDiffusion => generate_stable_diffusion(config, prompt).await,
+    }
+}
+
+async fn generate_openai(config: &ImageConfig, prompt: &str) -> Result<GeneratedImage, AIError> {
+    #[derive(Deserialize)]
+    struct Images {
+        data: Vec<Image>,
+        #[serde(default)]
+        usage: Option<Usage>,
+    }
+    #[derive(Deserialize)]
+    struct Image {
+        b64_json: Option<String>,
+        url: Option<String>,
+        revised_prompt: Option<String>,
+    }
+    #[derive(Deserialize)]
+    struct Usage {
+        input_tokens: usize,
+        output_tokens: usize,
+    }
+
+    let mut body = serde_json::json!({
+        "model": config.model,
+        "prompt": prompt,
+        "n": 1,
+        "size": config.size,
+    });
+    // The DALL·E models return links unless asked for the data
+    if config.model.starts_with("dall-e") {
+        body["response_format"] = "b64_json".into();
+    }
+    let response = reqwest::Client::new()
+        .post(format!(
+            "{}/images/generations",
+            config.endpoint.trim_end_matches('/')
+        ))
+        .bearer_auth(&config.api_key)
+        .json(&body)
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| AIError::NetworkError(format!("Image request failed: {}", e)))?;
+    let images: Images = read_json(response, "The images API").await?;
+
+    let image =
+        images.data.into_iter().next().ok_or_else(|| {
+            AIError::InvalidResponse("The images API returned no image".to_string())
+        })?;
+    let png = match (image.b64_json, image.url) {
+        (Some(data), _) => decode(&data)?,
+        (None, Some(url)) => download(&url).await?,
+        (None, None) => {
+            return Err(AIError::InvalidResponse(
+                "The images API returned neither data nor a link".to_string(),
+            ));
+        }
+    };
+    let usage = images.usage.map_or(
+        TokenUsage {
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            total_tokens: 0,
+        },
+        |usage| TokenUsage {
+            prompt_tokens: usage.input_tokens,
+            completion_tokens: usage.output_tokens,
+            total_tokens: usage.input_tokens + usage.output_tokens,
+        },
+    );
+    Ok(GeneratedImage {
+        png,
+        model: config.model.clone(),
+        revised_prompt: image.revised_prompt,
+        usage,
+    })
+}
+
+async fn generate_stable_diffusion(
+    config: &ImageConfig,
+    prompt: &str,
+) -> Result<GeneratedImage, AIError> {
+    #[derive(Deserialize)]
+    struct Txt2Img {
+        images: Vec<String>,
+    }
+
+    let (width, height) = parse_size(&config.size).ok_or_else(|| {
+        AIError::ConfigError(format!(
+            "Invalid image.size {}; use WIDTHxHEIGHT such as 512x512",
+            config.size
+        ))
+    })?;
+    let response = reqwest::Client::new()
+        .post(format!(
+            "{}/sdapi/v1/txt2img",
+            config.endpoint.trim_end_matches('/')
+        ))
+        .json(&serde_json::json!({
+            "prompt": prompt,
+            "width": width,
+            "height": height,
+            "steps": config.steps,
+        }))
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| {
+            AIError::NetworkError(format!(
+                "Cannot reach Stable Diffusion at {}: {}",
+                config.endpoint, e
+            ))
+        })?;
+    let result: Txt2Img = read_json(response, "Stable Diffusion").await?;
+    let data = result.images.into_iter().next().ok_or_else(|| {
+        AIError::InvalidResponse("Stable Diffusion returned no image".to_string())
+    })?;
+    Ok(GeneratedImage {
+        png: decode(&data)?,
+        model: "stable-diffusion".to_string(),
+        revised_prompt: None,
+        usage: TokenUsage {
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            total_tokens: 0,
+        },
+    })
+}
+
+/// Check the status of a response and parse its body
+async fn read_json<T: serde::de::DeserializeOwned>(
+    response: reqwest::Response,
+    service: &str,
+) -> Result<T, AIError> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(match status {
+            reqwest::StatusCode::UNAUTHORIZED => {
+                AIError::Authentication(format!("{} rejected the key", service))
+            }
+            _ => AIError::APIError(format!("{} returned {}: {}", service, status, body.trim())),
+        });
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| AIError::InvalidResponse(format!("Unexpected answer of {}: {}", service, e)))
+}
+
+async fn download(url: &str) -> Result<Vec<u8>, AIError> {
+    let response = reqwest::Client::new()
+        .get(url)
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| AIError::NetworkError(format!("Cannot download the image: {}", e)))?;
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| AIError::NetworkError(format!("Cannot download the image: {}", e)))?;
+    Ok(bytes.to_vec())
+}
+
+fn decode(data: &str) -> Result<Vec<u8>, AIError> {
+    // Some servers send a data URL instead of bare base64
+    let data = data.split_once("base64,").map_or(data, |(_, rest)| rest);
+    STANDARD
+        .decode(data.trim())
+        .map_err(|e| AIError::InvalidResponse(format!("The image data is not base64: {}", e)))
+}
+
+/// Width and height of a `WIDTHxHEIGHT` size
+pub fn parse_size(size: &str) -> Option<(u32, u32)> {
+    let (width, height) = size.trim().split_once(['x', 'X'])?;
+    let width = width.trim().parse().ok()?;
+    let height = height.trim().parse().ok()?;
+    (width > 0 && height > 0).then_some((width, height))
+}
+
+/// Directory images are saved to
+pub fn output_dir(config: &ImageConfig) -> PathBuf {
+    if config.output_dir.is_empty() {
+        get_config_dir().join("images")
+    } else {
+        PathBuf::from(&config.output_dir)
+    }
+}
+
+/// File name of an image: the time and the first words of the prompt
+pub fn file_name(prompt: &str, time: DateTime<Local>) -> String {
+    let mut slug = String::new();
+    for word in prompt
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        if slug.len() + word.len() >= 40 {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(&word.to_lowercase());
+    }
+    if slug.is_empty() {
+        slug.push_str("image");
+    }
+    format!("{}-{}.png", time.format("%Y%m%d-%H%M%S"), slug)
+}
+
+/// Save an image under the output directory and return its path
+pub fn save(dir: &Path, name: &str, png: &[u8]) -> io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(name);
+    std::fs::write(&path, png)?;
+    Ok(path)
+}
+
+/// The graphics the current terminal supports, as configured
+pub fn detect_graphics(display: ImageDisplay) -> Graphics {
+    graphics_from_env(
+        display,
+        |name| std::env::var(name).ok(),
+        platform::find_in_path("img2sixel").is_some(),
+    )
+}
+
+/// The graphics supported according to the terminal's environment variables
+///
+/// Sixel needs `img2sixel` from libsixel, since images are not decoded here.
+pub fn graphics_from_env(
+    display: ImageDisplay,
+    var: impl Fn(&str) -> Option<String>,
+    has_img2sixel: bool,
+) -> Graphics {
+    match display {
+        ImageDisplay::Off => return Graphics::None,
+        ImageDisplay::Kitty => return Graphics::Kitty,
+        ImageDisplay::Sixel if has_img2sixel => return Graphics::Sixel,
+        ImageDisplay::Sixel => return Graphics::None,
+        ImageDisplay::Auto => {}
+    }
+
+    let term = var("TERM").unwrap_or_default().to_lowercase();
+    let program = var("TERM_PROGRAM").unwrap_or_default().to_lowercase();
+    if var("KITTY_WINDOW_ID").is_some()
+        || term.contains("kitty")
+        || term.contains("ghostty")
+        || ["wezterm", "ghostty"].contains(&program.as_str())
+    {
+        return Graphics::Kitty;
+    }
+    let sixel_terminal = ["foot", "mlterm", "contour", "yaft"]
+        .iter()
+        .any(|name| term.starts_with(name))
+        || term.contains("sixel")
+        || program == "iterm.app";
+    if sixel_terminal && has_img2sixel {
+        Graphics::Sixel
+    } else {
+        Graphics::None
+    }
+}
+
+/// Escape sequences that draw a PNG with the kitty graphics protocol
+pub fn kitty_sequence(png: &[u8]) -> String {
+    let data = STANDARD.encode(png);
+    let chunks: Vec<&str> = data
+        .as_bytes()
+        .chunks(KITTY_CHUNK)
+        .map(|chunk| std::str::from_utf8(chunk).unwrap_or_default())
+        .collect();
+    let mut sequence = String::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let more = u8::from(index + 1 < chunks.len());
+        if index == 0 {
+            sequence.push_str(&format!("\x1b_Ga=T,f=100,m={};{}\x1b\\", more, chunk));
+        } else {
+            sequence.push_str(&format!("\x1b_Gm={};{}\x1b\\", more, chunk));
+        }
+    }
+    sequence
+}
+
+/// Draw an image at the cursor of the normal screen
+pub fn show(graphics: Graphics, path: &Path) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    match graphics {
+        Graphics::Kitty => {
+            let png = std::fs::read(path)?;
+            write!(stdout, "{}", kitty_sequence(&png))?;
+        }
+        Graphics::Sixel => {
+            let status = Command::new("img2sixel")
+                .arg(path)
+                .stdin(Stdio::null())
+                .status()?;
+            if !status.success() {
+                return Err(io::Error::other(format!("img2sixel failed ({})", status)));
+            }
+        }
+        Graphics::None => {}
+    }
+    writeln!(stdout)?;
+    stdout.flush()
+}
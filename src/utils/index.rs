@@ -0,0 +1,353 @@
+//! Project index for retrieving relevant code
+//!
+//! Text files of the workspace are cut into chunks of lines and each chunk
+//! is embedded. The index remembers the modification time and size of every
+//! file so that only files changed since the last pass are embedded again.
+//! It is kept per workspace root under `~/.ai-coder/index/`.
+
+use crate::config::{IndexConfig, WorkspaceConfig, get_config_dir};
+use crate::handlers::workspace;
+use crate::utils::glob::{Glob, find_files};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+/// Modification time and size of a file when it was indexed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    /// Milliseconds since the Unix epoch
+    pub modified: i64,
+    pub size: u64,
+}
+
+impl FileStamp {
+    /// Stamp of the file at `path`
+    pub fn of(path: &Path) -> io::Result<Self> {
+        let metadata = fs::metadata(path)?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis() as i64);
+        Ok(Self {
+            modified,
+            size: metadata.len(),
+        })
+    }
+}
+
+/// Embedded lines of a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Chunk {
+    /// First line, counted from 1
+    pub start_line: usize,
+    /// Last line, inclusive
+    pub end_line: usize,
+    pub text: String,
+    pub vector: Vec<f32>,
+}
+
+/// Indexed state of one file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub stamp: FileStamp,
+    pub chunks: Vec<Chunk>,
+}
+
+/// Files that differ from the index
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Changes {
+    /// New or modified files
+    pub changed: Vec<PathBuf>,
+    /// Indexed files that no longer exist or are no longer indexed
+    pub removed: Vec<PathBuf>,
+}
+
+impl Changes {
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// A chunk found by a search
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    pub path: PathBuf,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub text: String,
+    /// Cosine similarity to the query
+    pub score: f32,
+}
+
+/// Embedded chunks of the files of a workspace
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectIndex {
+    /// Embedding model the vectors come from
+    pub model: String,
+    /// Files by path relative to the workspace root
+    pub files: BTreeMap<PathBuf, FileEntry>,
+    /// When the last pass finished
+    pub updated: Option<DateTime<Local>>,
+}
+
+impl ProjectIndex {
+    /// An empty index for vectors of `model`
+    pub fn new(model: &str) -> Self {
+        Self {
+            model: model.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Files to embed again and files to drop, given the current stamps
+    pub fn changes(&self, scanned: &BTreeMap<PathBuf, FileStamp>) -> Changes {
+        let changed = scanned
+            .iter()
+            .filter(|(path, stamp)| {
+                self.files
+                    .get(*path)
+                    .is_none_or(|entry| entry.stamp != **stamp)
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        let removed = self
+            .files
+            .keys()
+            .filter(|path| !scanned.contains_key(*path))
+            .cloned()
+            .collect();
+        Changes { changed, removed }
+    }
+
+    /// Number of embedded chunks
+    pub fn chunk_count(&self) -> usize {
+        self.files.values().map(|entry| entry.chunks.len()).sum()
+    }
+
+    /// The `k` chunks most similar to the query vector, best first
+    pub fn search(&self, query: &[f32], k: usize) -> Vec<Match> {
+        let mut matches: Vec<Match> = self
+            .files
+            .iter()
+            .flat_map(|(path, entry)| {
+                entry.chunks.iter().map(move |chunk| Match {
+                    path: path.clone(),
+                    start_line: chunk.start_line,
+                    end_line: chunk.end_line,
+                    text: chunk.text.clone(),
+                    score: cosine(query, &chunk.vector),
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+        matches.truncate(k);
+        matches
+    }
+
+    /// Read an index from `path`, returning none if it does not exist
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map(Some)
+                .map_err(io::Error::other),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write the index to `path`
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string(self).map_err(io::Error::other)?;
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, path)
+    }
+}
+
+/// File holding the index of the workspace at `root`
+pub fn index_file(root: &Path) -> PathBuf {
+    let digest = Sha256::digest(root.to_string_lossy().as_bytes());
+    let name: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+    get_config_dir()
+        .join("index")
+        .join(format!("{}.json", name))
+}
+
+/// Stamps of the files under `root` that should be indexed
+///
+/// Files must have one of the configured extensions, fit the size limit and
+/// pass the workspace deny globs.
+pub fn scan(
+    root: &Path,
+    config: &IndexConfig,
+    workspace_config: &WorkspaceConfig,
+) -> BTreeMap<PathBuf, FileStamp> {
+    let Ok(all) = Glob::new("*") else {
+        return BTreeMap::new();
+    };
+    find_files(root, &all)
+        .into_iter()
+        .filter(|path| {
+            config.extensions.is_empty()
+                || path.extension().is_some_and(|extension| {
+                    config
+                        .extensions
+                        .iter()
+                        .any(|wanted| extension.eq_ignore_ascii_case(wanted.as_str()))
+                })
+        })
+        .filter(|path| workspace::check_with(workspace_config, root, path).is_ok())
+        .filter_map(|path| {
+            let stamp = FileStamp::of(&root.join(&path)).ok()?;
+            (stamp.size <= config.max_file_bytes).then_some((path, stamp))
+        })
+        .collect()
+}
+
+/// Cut a text into chunks of `lines` lines: first line, last line and text
+///
+/// Chunks holding only whitespace are skipped.
+pub fn chunk_text(text: &str, lines: usize) -> Vec<(usize, usize, String)> {
+    let all: Vec<&str> = text.lines().collect();
+    all.chunks(lines.max(1))
+        .enumerate()
+        .filter(|(_, chunk)| chunk.iter().any(|line| !line.trim().is_empty()))
+        .map(|(idx, chunk)| {
+            let start = idx * lines.max(1) + 1;
+            (start, start + chunk.len() - 1, chunk.join("\n"))
+        })
+        .collect()
+}
+
+/// Cosine similarity of two vectors, 0 when either is empty or zero
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Attachment text of search results: each chunk under its path and lines
+pub fn format_matches(matches: &[Match]) -> String {
+    matches
+        .iter()
+        .map(|found| {
+            format!(
+                "{}:{}-{}\n```\n{}\n```",
+                found.path.display(),
+                found.start_line,
+                found.end_line,
+                found.text
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// What the background indexer is doing
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum IndexActivity {
+    /// Up to date, or not started yet
+    #[default]
+    Idle,
+    /// Embedding changed files
+    Indexing { done: usize, total: usize },
+    /// Waiting for AI generations to finish
+    Paused,
+    /// The last pass failed; it is retried later
+    Failed(String),
+}
+
+/// State of the index shown in the status bar and by `/index`
+#[derive(Debug, Clone, Default)]
+pub struct IndexStatus {
+    pub activity: IndexActivity,
+    /// When the index last caught up with the files
+    pub updated: Option<DateTime<Local>>,
+    pub files: usize,
+    pub chunks: usize,
+}
+
+impl IndexStatus {
+    /// Short status bar text, e.g. `📇 2m ago` or `📇 3/40`
+    pub fn label(&self, now: DateTime<Local>) -> String {
+        match &self.activity {
+            IndexActivity::Indexing { done, total } => format!("📇 {}/{}", done, total),
+            IndexActivity::Paused => "📇 paused".to_string(),
+            IndexActivity::Failed(_) => "📇 failed".to_string(),
+            IndexActivity::Idle => match self.updated {
+                Some(updated) => format!(
+                    "📇 {}",
+                    crate::utils::format_relative_time((now - updated).num_seconds().max(0) as u64)
+                ),
+                None => "📇 pending".to_string(),
+            },
+        }
+    }
+}
+
+/// Handle on the index shared with the background indexer
+#[derive(Debug, Clone, Default)]
+pub struct Indexer {
+    index: Arc<Mutex<ProjectIndex>>,
+    status: Arc<Mutex<IndexStatus>>,
+    rebuild: Arc<AtomicBool>,
+}
+
+impl Indexer {
+    /// Current status
+    pub fn status(&self) -> IndexStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    pub fn set_activity(&self, activity: IndexActivity) {
+        self.status.lock().unwrap().activity = activity;
+    }
+
+    /// Run `f` on the index, e.g. to search it
+    pub fn with_index<T>(&self, f: impl FnOnce(&mut ProjectIndex) -> T) -> T {
+        f(&mut self.index.lock().unwrap())
+    }
+
+    /// Copy the size and age of the index into the status
+    pub fn publish(&self) {
+        let (files, chunks, updated) =
+            self.with_index(|index| (index.files.len(), index.chunk_count(), index.updated));
+        let mut status = self.status.lock().unwrap();
+        status.files = files;
+        status.chunks = chunks;
+        status.updated = updated;
+    }
+
+    /// Ask the indexer to embed every file again
+    pub fn request_rebuild(&self) {
+        self.rebuild.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether a rebuild was asked for and has not started yet
+    pub fn is_rebuild_requested(&self) -> bool {
+        self.rebuild.load(Ordering::SeqCst)
+    }
+
+    /// Whether a rebuild was asked for, clearing the request
+    pub fn take_rebuild(&self) -> bool {
+        self.rebuild.swap(false, Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,117 @@
+//! Response latency per provider and model
+//!
+//! Every answered prompt records how long the first token took and how long
+//! the whole generation took. `/stats` summarizes them as percentiles per
+//! provider and model so backends can be compared.
+
+use crate::ai::types::{ProgressStats, Provider};
+use std::time::Duration;
+
+/// Timing of one generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Latency {
+    /// Time until the first token; unknown for providers that do not stream
+    pub first_token: Option<Duration>,
+    /// Time until the generation completed
+    pub total: Duration,
+}
+
+impl Latency {
+    /// Timing recorded in the progress of a response
+    pub fn of(progress: &ProgressStats) -> Self {
+        Self {
+            first_token: progress.first_token_latency(),
+            total: progress.elapsed(),
+        }
+    }
+}
+
+/// Timing of an answered prompt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExchangeLatency {
+    pub provider: Provider,
+    pub model: String,
+    pub latency: Latency,
+}
+
+/// Median and 95th percentile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Percentiles {
+    pub p50: Duration,
+    pub p95: Duration,
+}
+
+impl Percentiles {
+    /// Percentiles of the durations, none without any
+    pub fn of(durations: &[Duration]) -> Option<Self> {
+        let mut sorted = durations.to_vec();
+        sorted.sort();
+        Some(Self {
+            p50: percentile(&sorted, 50.0)?,
+            p95: percentile(&sorted, 95.0)?,
+        })
+    }
+}
+
+/// Latency of one provider and model
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencySummary {
+    pub provider: Provider,
+    pub model: String,
+    pub exchanges: usize,
+    pub first_token: Option<Percentiles>,
+    pub total: Percentiles,
+}
+
+/// Nearest-rank percentile of sorted durations
+pub fn percentile(sorted: &[Duration], percent: f64) -> Option<Duration> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// Summaries per provider and model, in order of first use
+pub fn summarize(exchanges: &[ExchangeLatency]) -> Vec<LatencySummary> {
+    let mut keys: Vec<(Provider, &str)> = Vec::new();
+    for exchange in exchanges {
+        let key = (exchange.provider, exchange.model.as_str());
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys.into_iter()
+        .filter_map(|(provider, model)| {
+            let own: Vec<&Latency> = exchanges
+                .iter()
+                .filter(|exchange| exchange.provider == provider && exchange.model == model)
+                .map(|exchange| &exchange.latency)
+                .collect();
+            let first_tokens: Vec<Duration> = own
+                .iter()
+                .filter_map(|latency| latency.first_token)
+                .collect();
+            let totals: Vec<Duration> = own.iter().map(|latency| latency.total).collect();
+            Some(LatencySummary {
+                provider,
+                model: model.to_string(),
+                exchanges: own.len(),
+                first_token: Percentiles::of(&first_tokens),
+                total: Percentiles::of(&totals)?,
+            })
+        })
+        .collect()
+}
+
+/// A duration as `850ms`, `4.2s` or `1m 12s`
+pub fn format_latency(duration: Duration) -> String {
+    let millis = duration.as_millis();
+    if millis < 1000 {
+        format!("{}ms", millis)
+    } else if millis < 60_000 {
+        format!("{:.1}s", duration.as_secs_f64())
+    } else {
+        format!("{}m {}s", millis / 60_000, (millis % 60_000) / 1000)
+    }
+}
@@ -0,0 +1,239 @@
+//! Persistent usage ledger
+//!
+//! The tokens and estimated cost of every finished generation are appended
+//! to `~/.ai-coder/usage.jsonl`, one JSON object per line, so usage can be
+//! totalled across sessions, e.g. for the summary shown on startup.
+//! Entries recorded while a `/tag` is set carry it, so spend can be split
+//! by client or ticket with `/usage by-tag`.
+
+use crate::ai::types::Provider;
+use crate::utils::{format_money, format_number};
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::ops::Range;
+use std::path::Path;
+
+/// Usage of one generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    /// When the response arrived
+    pub timestamp: DateTime<Local>,
+    /// Provider that served the request
+    pub provider: Provider,
+    /// Model that produced the response
+    pub model: String,
+    pub prompt_tokens: usize,
+    pub completion_tokens: usize,
+    /// Estimated cost in dollars
+    pub cost: f64,
+    /// Tag set with `/tag` when the response arrived
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+}
+
+/// Usage of one provider over a period
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProviderTotal {
+    pub provider: Provider,
+    pub tokens: usize,
+    pub cost: f64,
+}
+
+/// Usage of one tag, or of untagged work
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagTotal {
+    pub tag: Option<String>,
+    /// Generations recorded
+    pub responses: usize,
+    pub tokens: usize,
+    pub cost: f64,
+}
+
+/// Append an entry to the ledger at `path`
+pub fn append(path: &Path, entry: &LedgerEntry) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let line = serde_json::to_string(entry).map_err(io::Error::other)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+/// Read the ledger at `path`, skipping lines that cannot be parsed
+pub fn load(path: &Path) -> io::Result<Vec<LedgerEntry>> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Usage per provider of the entries within `period`, most expensive first
+pub fn totals(entries: &[LedgerEntry], period: &Range<DateTime<Local>>) -> Vec<ProviderTotal> {
+    let mut totals: Vec<ProviderTotal> = Vec::new();
+    for entry in entries.iter().filter(|e| period.contains(&e.timestamp)) {
+        let tokens = entry.prompt_tokens + entry.completion_tokens;
+        match totals.iter_mut().find(|t| t.provider == entry.provider) {
+            Some(total) => {
+                total.tokens += tokens;
+                total.cost += entry.cost;
+            }
+            None => totals.push(ProviderTotal {
+                provider: entry.provider,
+                tokens,
+                cost: entry.cost,
+            }),
+        }
+    }
+    totals.sort_by(|a, b| b.cost.total_cmp(&a.cost).then(b.tokens.cmp(&a.tokens)));
+    totals
+}
+
+/// Usage per tag of the entries within `period` (all of them without one),
+/// most expensive first and untagged work last
+pub fn tag_totals(
+    entries: &[LedgerEntry],
+    period: Option<&Range<DateTime<Local>>>,
+) -> Vec<TagTotal> {
+    let mut totals: Vec<TagTotal> = Vec::new();
+    let within =
+        |entry: &&LedgerEntry| period.is_none_or(|period| period.contains(&entry.timestamp));
+    for entry in entries.iter().filter(within) {
+        let tokens = entry.prompt_tokens + entry.completion_tokens;
+        match totals.iter_mut().find(|t| t.tag == entry.tag) {
+            Some(total) => {
+                total.responses += 1;
+                total.tokens += tokens;
+                total.cost += entry.cost;
+            }
+            None => totals.push(TagTotal {
+                tag: entry.tag.clone(),
+                responses: 1,
+                tokens,
+                cost: entry.cost,
+            }),
+        }
+    }
+    totals.sort_by(|a, b| {
+        a.tag
+            .is_none()
+            .cmp(&b.tag.is_none())
+            .then(b.cost.total_cmp(&a.cost))
+            .then(b.tokens.cmp(&a.tokens))
+    });
+    totals
+}
+
+/// Local midnight at the start of `date`, or `fallback` if it does not exist
+fn midnight(date: NaiveDate, fallback: DateTime<Local>) -> DateTime<Local> {
+    Local
+        .from_local_datetime(&date.and_time(NaiveTime::MIN))
+        .earliest()
+        .unwrap_or(fallback)
+}
+
+/// A period named `today`, `week` (from Monday) or `month`, until tonight
+pub fn named_period(name: &str, now: DateTime<Local>) -> Option<Range<DateTime<Local>>> {
+    let today = now.date_naive();
+    let start = match name {
+        "today" => today,
+        "week" => today - Duration::days(today.weekday().num_days_from_monday() as i64),
+        "month" => today.with_day(1)?,
+        _ => return None,
+    };
+    Some(midnight(start, now)..midnight(today + Duration::days(1), now))
+}
+
+/// Yesterday and this week (from Monday until tonight), as of `now`
+pub fn yesterday_and_this_week(
+    now: DateTime<Local>,
+) -> (Range<DateTime<Local>>, Range<DateTime<Local>>) {
+    let midnight = |date: NaiveDate| midnight(date, now);
+    let today = now.date_naive();
+    let yesterday = midnight(today - Duration::days(1))..midnight(today);
+    let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    (
+        yesterday,
+        midnight(monday)..midnight(today + Duration::days(1)),
+    )
+}
+
+/// Compact summary of yesterday's and this week's usage, `None` without any
+pub fn usage_summary(entries: &[LedgerEntry], now: DateTime<Local>) -> Option<String> {
+    let (yesterday, week) = yesterday_and_this_week(now);
+    let yesterday = totals(entries, &yesterday);
+    let week = totals(entries, &week);
+    if yesterday.is_empty() && week.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "📊 Yesterday: {}\n   This week: {}",
+        period_line(&yesterday),
+        period_line(&week)
+    ))
+}
+
+/// `OpenAI 12,345 tokens $0.0123 · Ollama 800 tokens free`
+fn period_line(totals: &[ProviderTotal]) -> String {
+    if totals.is_empty() {
+        return "no usage".to_string();
+    }
+    totals
+        .iter()
+        .map(|total| {
+            let cost = if total.cost > 0.0 {
+                format_money(total.cost)
+            } else {
+                "free".to_string()
+            };
+            format!(
+                "{} {} tokens {}",
+                total.provider,
+                format_number(total.tokens),
+                cost
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+/// Spend per tag as aligned rows with a total, for `/usage by-tag`
+pub fn tag_report(totals: &[TagTotal]) -> String {
+    let names: Vec<&str> = totals
+        .iter()
+        .map(|total| total.tag.as_deref().unwrap_or("(untagged)"))
+        .collect();
+    let width = names
+        .iter()
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max("Total".len());
+    let row = |name: &str, responses: usize, tokens: usize, cost: f64| {
+        format!(
+            "  {:<width$}  {:>5} responses  {:>12} tokens  {}",
+            name,
+            responses,
+            format_number(tokens),
+            format_money(cost),
+            width = width
+        )
+    };
+    let mut lines: Vec<String> = totals
+        .iter()
+        .zip(&names)
+        .map(|(total, name)| row(name, total.responses, total.tokens, total.cost))
+        .collect();
+    lines.push(row(
+        "Total",
+        totals.iter().map(|total| total.responses).sum(),
+        totals.iter().map(|total| total.tokens).sum(),
+        totals.iter().map(|total| total.cost).sum(),
+    ));
+    lines.join("\n")
+}
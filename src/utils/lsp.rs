@@ -0,0 +1,557 @@
+//! Language server client
+//!
+//! Speaks JSON-RPC over the stdin and stdout of a language server process,
+//! enough to look up workspace symbols, definitions, references and hover
+//! documentation. Requests the server sends back, such as configuration or
+//! progress tokens, are answered with empty results.
+
+use crate::config::LanguageServer;
+use serde_json::{Value, json};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::oneshot;
+
+/// Longest time a request may take once the server is up
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Frame a message with its `Content-Length` header
+pub fn encode(message: &Value) -> Vec<u8> {
+    let body = message.to_string();
+    let mut framed = format!("Content-Length: {}\r\n\r\n", body.len()).into_bytes();
+    framed.extend_from_slice(body.as_bytes());
+    framed
+}
+
+/// Read the next framed message, none at the end of the stream
+pub async fn read_message<R: AsyncBufRead + Unpin>(reader: &mut R) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = line.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let mut body = vec![0; length.unwrap_or_default()];
+    reader.read_exact(&mut body).await?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(io::Error::other)
+}
+
+/// `file://` URI of a path
+pub fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from(if path.starts_with('/') {
+        "file://"
+    } else {
+        "file:///"
+    });
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/:".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+/// Path of a `file://` URI
+pub fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut rest = encoded.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        let decoded = (byte == b'%')
+            .then(|| std::str::from_utf8(tail.get(..2)?).ok())
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match decoded {
+            Some(decoded) => {
+                bytes.push(decoded);
+                rest = &tail[2..];
+            }
+            None => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+    let path = String::from_utf8(bytes).ok()?;
+    // `/C:/src` is `C:/src` on Windows
+    let path = match path.as_bytes() {
+        [b'/', drive, b':', ..] if drive.is_ascii_alphabetic() => path[1..].to_string(),
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+/// Column of a byte offset in UTF-16 code units, as LSP counts characters
+pub fn utf16_column(line: &str, byte_index: usize) -> usize {
+    line[..byte_index.min(line.len())].encode_utf16().count()
+}
+
+/// A range of lines in a file, counted from 0 as in LSP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+    pub start_line: usize,
+    /// UTF-16 column of the start
+    pub start_character: usize,
+    pub end_line: usize,
+}
+
+fn parse_location(value: &Value) -> Option<Location> {
+    // A `LocationLink` names its target, a `Location` the place itself
+    let (uri, range) = match value.get("targetUri") {
+        Some(uri) => (uri, value.get("targetRange")),
+        None => (value.get("uri")?, value.get("range")),
+    };
+    let line = |point: &str, field: &str| {
+        range
+            .and_then(|range| range[point][field].as_u64())
+            .unwrap_or_default() as usize
+    };
+    Some(Location {
+        path: uri_to_path(uri.as_str()?)?,
+        start_line: line("start", "line"),
+        start_character: line("start", "character"),
+        end_line: line("end", "line"),
+    })
+}
+
+/// Locations of a definition or references answer
+///
+/// Servers answer with nothing, one location or a list of locations or
+/// location links.
+pub fn parse_locations(value: &Value) -> Vec<Location> {
+    match value {
+        Value::Array(items) => items.iter().filter_map(parse_location).collect(),
+        Value::Object(_) => parse_location(value).into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A symbol found by `workspace/symbol`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    /// Type, module or namespace the symbol belongs to
+    pub container: Option<String>,
+    pub location: Location,
+}
+
+/// Symbols of a `workspace/symbol` answer
+pub fn parse_symbols(value: &Value) -> Vec<Symbol> {
+    let Some(items) = value.as_array() else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| {
+            Some(Symbol {
+                name: item["name"].as_str()?.to_string(),
+                container: item["containerName"]
+                    .as_str()
+                    .filter(|container| !container.is_empty())
+                    .map(str::to_string),
+                location: parse_location(item.get("location")?)?,
+            })
+        })
+        .collect()
+}
+
+/// Split `Type::method` or `Type.method` into the container and the name
+pub fn split_symbol(query: &str) -> (Option<&str>, &str) {
+    let split = query
+        .rfind("::")
+        .map(|idx| (idx, 2))
+        .or_else(|| query.rfind('.').map(|idx| (idx, 1)));
+    match split {
+        Some((idx, len)) if idx > 0 && idx + len < query.len() => {
+            (Some(&query[..idx]), &query[idx + len..])
+        }
+        _ => (None, query),
+    }
+}
+
+/// The symbols named exactly like the query, ignoring case only when
+/// nothing matches exactly
+pub fn matching_symbols(symbols: Vec<Symbol>, query: &str) -> Vec<Symbol> {
+    let (container, name) = split_symbol(query);
+    let in_container = |symbol: &Symbol| {
+        container.is_none_or(|wanted| {
+            symbol
+                .container
+                .as_deref()
+                .is_some_and(|container| container.ends_with(wanted) || wanted.ends_with(container))
+        })
+    };
+    let exact: Vec<Symbol> = symbols
+        .iter()
+        .filter(|symbol| symbol.name == name && in_container(symbol))
+        .cloned()
+        .collect();
+    if !exact.is_empty() {
+        return exact;
+    }
+    symbols
+        .into_iter()
+        .filter(|symbol| symbol.name.eq_ignore_ascii_case(name) && in_container(symbol))
+        .collect()
+}
+
+/// Text of a hover answer
+///
+/// The contents are markup, a marked string with a language or a list of
+/// marked strings; code in a language is put in a fenced block.
+pub fn parse_hover(value: &Value) -> Option<String> {
+    fn marked(value: &Value) -> Option<String> {
+        match value {
+            Value::String(text) => Some(text.clone()),
+            Value::Object(_) => {
+                let text = value["value"].as_str()?;
+                Some(match value["language"].as_str() {
+                    Some(language) => format!("```{}\n{}\n```", language, text),
+                    None => text.to_string(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    let contents = value.get("contents")?;
+    let text = match contents {
+        Value::Array(items) => items
+            .iter()
+            .filter_map(marked)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        _ => marked(contents)?,
+    };
+    let text = text.trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+/// Line and UTF-16 column of `name` in the lines of a location
+///
+/// Symbol ranges often start at the keyword or attributes of an item,
+/// where hover and references find nothing.
+pub fn name_position(text: &str, location: &Location, name: &str) -> (usize, usize) {
+    text.lines()
+        .enumerate()
+        .skip(location.start_line)
+        .take(location.end_line.saturating_sub(location.start_line) + 1)
+        .find_map(|(idx, line)| {
+            let column = line.match_indices(name).find_map(|(column, _)| {
+                let before = line[..column].chars().next_back();
+                let after = line[column + name.len()..].chars().next();
+                let word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+                (!word(before) && !word(after)).then_some(column)
+            })?;
+            Some((idx, utf16_column(line, column)))
+        })
+        .unwrap_or((location.start_line, location.start_character))
+}
+
+/// Lines `first..=last` of a file, counted from 0, under their path and
+/// line numbers counted from 1
+pub fn format_excerpt(path: &Path, text: &str, first: usize, last: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let last = last.min(lines.len().saturating_sub(1));
+    let first = first.min(last);
+    format!(
+        "{}:{}-{}\n```\n{}\n```",
+        path.display(),
+        first + 1,
+        last + 1,
+        lines.get(first..=last).unwrap_or_default().join("\n")
+    )
+}
+
+/// Responses awaited by id
+type Pending = Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>;
+
+/// A running language server
+#[derive(Debug)]
+pub struct LspClient {
+    /// LSP language id of the documents it is sent
+    language: String,
+    stdin: Arc<tokio::sync::Mutex<ChildStdin>>,
+    pending: Pending,
+    next_id: AtomicI64,
+    running: Arc<AtomicBool>,
+    opened: Mutex<HashSet<PathBuf>>,
+    /// When the server was started, to allow for its first indexing
+    pub started: Instant,
+    _child: Child,
+}
+
+impl LspClient {
+    /// Start a server for the workspace at `root` and initialize it
+    pub async fn start(
+        language: &str,
+        server: &LanguageServer,
+        root: &Path,
+        timeout: Duration,
+    ) -> Result<Self, String> {
+        let mut child = Command::new(&server.command)
+            .args(&server.args)
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("cannot start {}: {}", server.command, e))?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(format!("cannot talk to {}", server.command));
+        };
+
+        let client = Self {
+            language: language.to_string(),
+            stdin: Arc::new(tokio::sync::Mutex::new(stdin)),
+            pending: Pending::default(),
+            next_id: AtomicI64::new(1),
+            running: Arc::new(AtomicBool::new(true)),
+            opened: Mutex::new(HashSet::new()),
+            started: Instant::now(),
+            _child: child,
+        };
+        tokio::spawn(read_loop(
+            BufReader::new(stdout),
+            client.stdin.clone(),
+            client.pending.clone(),
+            client.running.clone(),
+        ));
+
+        let root_uri = path_to_uri(root);
+        let params = json!({
+            "processId": std::process::id(),
+            "rootUri": root_uri,
+            "workspaceFolders": [{ "uri": root_uri, "name": root.file_name().map(|name| name.to_string_lossy()).unwrap_or_default() }],
+            "capabilities": {
+                "textDocument": {
+                    "hover": { "contentFormat": ["markdown", "plaintext"] },
+                    "definition": { "linkSupport": true },
+                    "references": {},
+                },
+                "workspace": { "symbol": {}, "workspaceFolders": true, "configuration": true },
+                "window": { "workDoneProgress": true },
+            },
+        });
+        client
+            .request_within("initialize", params, timeout)
+            .await
+            .map_err(|e| format!("{} did not initialize: {}", server.command, e))?;
+        client.notify("initialized", json!({})).await?;
+        Ok(client)
+    }
+
+    /// Whether the server process is still answering
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    async fn send(&self, message: &Value) -> Result<(), String> {
+        let mut stdin = self.stdin.lock().await;
+        stdin
+            .write_all(&encode(message))
+            .await
+            .map_err(|e| format!("language server is gone: {}", e))?;
+        stdin.flush().await.map_err(|e| e.to_string())
+    }
+
+    /// Send a notification
+    pub async fn notify(&self, method: &str, params: Value) -> Result<(), String> {
+        self.send(&json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+            .await
+    }
+
+    /// Send a request and wait for its result
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        self.request_within(method, params, REQUEST_TIMEOUT).await
+    }
+
+    async fn request_within(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if let Err(e) = self.send(&message).await {
+            self.pending.lock().unwrap().remove(&id);
+            return Err(e);
+        }
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err("the language server exited".to_string()),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                Err(format!("{} timed out", method))
+            }
+        }
+    }
+
+    /// Tell the server about a file before asking about positions in it
+    async fn open(&self, path: &Path, text: &str) -> Result<(), String> {
+        if !self.opened.lock().unwrap().insert(path.to_path_buf()) {
+            return Ok(());
+        }
+        self.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {
+                    "uri": path_to_uri(path),
+                    "languageId": self.language,
+                    "version": 1,
+                    "text": text,
+                }
+            }),
+        )
+        .await
+    }
+
+    async fn position_request(
+        &self,
+        method: &str,
+        path: &Path,
+        text: &str,
+        (line, character): (usize, usize),
+        extra: Value,
+    ) -> Result<Value, String> {
+        self.open(path, text).await?;
+        let mut params = json!({
+            "textDocument": { "uri": path_to_uri(path) },
+            "position": { "line": line, "character": character },
+        });
+        if let (Some(params), Value::Object(extra)) = (params.as_object_mut(), extra) {
+            params.extend(extra);
+        }
+        self.request(method, params).await
+    }
+
+    /// Symbols of the workspace matching a query
+    pub async fn symbols(&self, query: &str) -> Result<Vec<Symbol>, String> {
+        let result = self
+            .request("workspace/symbol", json!({ "query": query }))
+            .await?;
+        Ok(parse_symbols(&result))
+    }
+
+    /// Where the symbol at a position is defined
+    pub async fn definition(
+        &self,
+        path: &Path,
+        text: &str,
+        position: (usize, usize),
+    ) -> Result<Vec<Location>, String> {
+        let result = self
+            .position_request("textDocument/definition", path, text, position, json!({}))
+            .await?;
+        Ok(parse_locations(&result))
+    }
+
+    /// Where the symbol at a position is used, without its declaration
+    pub async fn references(
+        &self,
+        path: &Path,
+        text: &str,
+        position: (usize, usize),
+    ) -> Result<Vec<Location>, String> {
+        let result = self
+            .position_request(
+                "textDocument/references",
+                path,
+                text,
+                position,
+                json!({ "context": { "includeDeclaration": false } }),
+            )
+            .await?;
+        Ok(parse_locations(&result))
+    }
+
+    /// Documentation of the symbol at a position
+    pub async fn hover(
+        &self,
+        path: &Path,
+        text: &str,
+        position: (usize, usize),
+    ) -> Result<Option<String>, String> {
+        let result = self
+            .position_request("textDocument/hover", path, text, position, json!({}))
+            .await?;
+        Ok(parse_hover(&result))
+    }
+}
+
+/// Route responses to their requests and answer requests of the server
+async fn read_loop<R: AsyncBufRead + Unpin>(
+    mut reader: R,
+    stdin: Arc<tokio::sync::Mutex<ChildStdin>>,
+    pending: Pending,
+    running: Arc<AtomicBool>,
+) {
+    while let Ok(Some(message)) = read_message(&mut reader).await {
+        let id = message.get("id").cloned();
+        match (message.get("method").and_then(Value::as_str), id) {
+            // A request of the server: settings, progress tokens, registrations
+            (Some(method), Some(id)) => {
+                let result = match method {
+                    "workspace/configuration" => {
+                        let items = message["params"]["items"].as_array().map_or(0, Vec::len);
+                        Value::Array(vec![Value::Null; items])
+                    }
+                    _ => Value::Null,
+                };
+                let reply = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                let _ = stdin.lock().await.write_all(&encode(&reply)).await;
+            }
+            // A notification such as diagnostics or progress
+            (Some(_), None) => {}
+            (None, Some(id)) => {
+                let Some(tx) = id
+                    .as_i64()
+                    .and_then(|id| pending.lock().unwrap().remove(&id))
+                else {
+                    continue;
+                };
+                let result = match message.get("error") {
+                    Some(error) => Err(error["message"]
+                        .as_str()
+                        .unwrap_or("request failed")
+                        .to_string()),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = tx.send(result);
+            }
+            (None, None) => {}
+        }
+    }
+    running.store(false, Ordering::SeqCst);
+    // Dropping the senders fails the requests still waiting
+    pending.lock().unwrap().clear();
+}
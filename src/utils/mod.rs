@@ -2,13 +2,38 @@
 //!
 //! This module provides common utilities for the application
 
+pub mod audit;
+pub mod bundle;
+pub mod crash;
+pub mod crypto;
+pub mod diff;
+pub mod eval;
 mod format;
+pub mod glob;
+pub mod image;
+pub mod index;
+pub mod latency;
+pub mod ledger;
 mod logging;
+pub mod lsp;
+pub mod network;
+pub mod prompts;
+pub mod search;
+pub mod session_diff;
+pub mod sessions;
+pub mod share;
+pub mod speech;
+pub mod sync;
 pub mod tasks;
+pub mod templates;
+pub mod transcript;
+pub mod tree;
+pub mod tts;
+pub mod undo;
 
 pub use format::*;
 pub use logging::*;
-pub use tasks::{Task, TaskId, TaskManager, TaskType};
+pub use tasks::{Task, TaskId, TaskManager, TaskPriority, TaskType, TaskUsage};
 
 use chrono::Local;
 use ratatui::style::Color;
@@ -80,24 +105,23 @@ pub fn get_username() -> String {
         .unwrap_or_else(|_| String::from("user"))
 }
 
+/// Get the name of this machine
+pub fn get_hostname() -> String {
+    env::var("HOSTNAME")
+        .or_else(|_| env::var("COMPUTERNAME"))
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| String::from("unknown host"))
+}
+
 /// Get the current shell
 pub fn get_shell() -> String {
-    env::var("SHELL").unwrap_or_else(|_| String::from("unknown"))
+    crate::platform::shell_name()
 }
 
 /// Check if a command exists in PATH
 pub fn command_exists(command: &str) -> bool {
-    use std::process::Command;
-
-    // Try to execute the command with --version flag
-    // which usually exists for most commands and doesn't do anything harmful
-    #[cfg(target_os = "windows")]
-    let result = Command::new("where").arg(command).output();
-
-    #[cfg(not(target_os = "windows"))]
-    let result = Command::new("which").arg(command).output();
-
-    result
-        .map(|output| output.status.success())
-        .unwrap_or(false)
+    crate::platform::find_in_path(command).is_some()
 }
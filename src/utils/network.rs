@@ -0,0 +1,132 @@
+//! Network connectivity detection
+//!
+//! A background task periodically resolves and connects to a probe host to
+//! detect whether the internet is reachable. Cloud providers are avoided
+//! while the probe fails, and the servers of local providers are probed
+//! too so that offline prompts only go to one that answers.
+
+use crate::ai::Provider;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::{TcpStream, lookup_host};
+
+/// Local providers tried, in order, for prompts sent while offline
+pub const LOCAL_PROVIDERS: [Provider; 3] =
+    [Provider::Ollama, Provider::LMStudio, Provider::LlamaCpp];
+
+/// Check whether `host` (`name:port`) can be resolved and connected to in time
+pub async fn probe(host: &str, timeout: Duration) -> bool {
+    let attempt = async {
+        let addr = lookup_host(host).await.ok()?.next()?;
+        TcpStream::connect(addr).await.ok()
+    };
+    matches!(tokio::time::timeout(timeout, attempt).await, Ok(Some(_)))
+}
+
+/// `host:port` an endpoint URL connects to
+pub fn endpoint_address(endpoint: &str) -> Option<String> {
+    let url = reqwest::Url::parse(endpoint).ok()?;
+    Some(format!(
+        "{}:{}",
+        url.host_str()?,
+        url.port_or_known_default()?
+    ))
+}
+
+/// Local providers whose server accepts a connection within `timeout`
+pub async fn reachable_local(servers: &[(Provider, String)], timeout: Duration) -> Vec<Provider> {
+    let mut reachable = Vec::new();
+    for (provider, host) in servers {
+        if probe(host, timeout).await {
+            reachable.push(*provider);
+        }
+    }
+    reachable
+}
+
+/// Local providers to try, in order, for a prompt sent while offline
+///
+/// llama.cpp runs in-process and needs no server. An empty list means the
+/// prompt is queued until the network returns.
+pub fn offline_candidates(prefer_local: bool, reachable: &[Provider]) -> Vec<Provider> {
+    if !prefer_local {
+        return Vec::new();
+    }
+    LOCAL_PROVIDERS
+        .into_iter()
+        .filter(|provider| *provider == Provider::LlamaCpp || reachable.contains(provider))
+        .collect()
+}
+
+/// Shared view of the current connectivity state
+#[derive(Debug, Clone)]
+pub struct Connectivity {
+    online: Arc<AtomicBool>,
+    local: Arc<Mutex<Vec<Provider>>>,
+}
+
+impl Default for Connectivity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Connectivity {
+    /// Create a handle that assumes the network is available until probed
+    pub fn new() -> Self {
+        Self {
+            online: Arc::new(AtomicBool::new(true)),
+            local: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Whether the last probe succeeded
+    pub fn is_online(&self) -> bool {
+        self.online.load(Ordering::SeqCst)
+    }
+
+    /// Local providers whose server answered the last probe while offline
+    pub fn reachable_local(&self) -> Vec<Provider> {
+        self.local
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Probe the network periodically, notifying `on_change` when the state flips
+    ///
+    /// While the network is down the `local` servers are probed as well,
+    /// before the state changes, so an offline prompt never picks a local
+    /// provider that is not running.
+    pub fn spawn_monitor(
+        &self,
+        host: String,
+        local: Vec<(Provider, String)>,
+        interval: Duration,
+        timeout: Duration,
+        on_change: Option<tokio::sync::mpsc::Sender<()>>,
+    ) -> tokio::task::JoinHandle<()> {
+        let online = self.online.clone();
+        let reachable_local = self.local.clone();
+        tokio::spawn(async move {
+            loop {
+                let reachable = probe(&host, timeout).await;
+                let servers = if reachable {
+                    Vec::new()
+                } else {
+                    self::reachable_local(&local, timeout).await
+                };
+                *reachable_local
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner()) = servers;
+                if online.swap(reachable, Ordering::SeqCst) != reachable
+                    && let Some(tx) = &on_change
+                {
+                    let _ = tx.send(()).await;
+                }
+                tokio::time::sleep(interval).await;
+            }
+        })
+    }
+}
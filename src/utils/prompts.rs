@@ -0,0 +1,165 @@
+//! Library of past prompts
+//!
+//! Every chat prompt is recorded in `~/.ai-coder/prompts.json` with how often
+//! and when it was last sent, so prompts from earlier sessions can be found
+//! again with `/prompts`, ranked by frequency and recency and narrowed down
+//! with a fuzzy search.
+
+use crate::utils::crypto::{self, Cipher};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Most prompts kept; the least used and oldest are dropped first
+pub const MAX_PROMPTS: usize = 500;
+
+/// A prompt that was sent at least once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptEntry {
+    /// Prompt text as typed
+    pub text: String,
+    /// How many times it was sent
+    pub count: u32,
+    /// When it was last sent
+    pub last_used: DateTime<Local>,
+}
+
+impl PromptEntry {
+    /// Ranking weight: the use count, halved for every week since the last use
+    pub fn frecency(&self, now: DateTime<Local>) -> f64 {
+        let weeks = (now - self.last_used).num_minutes().max(0) as f64 / (60.0 * 24.0 * 7.0);
+        self.count as f64 / 2f64.powf(weeks)
+    }
+}
+
+/// Prompts sent in this and earlier sessions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PromptLibrary {
+    pub prompts: Vec<PromptEntry>,
+}
+
+impl PromptLibrary {
+    /// Read the library from `path`, empty if it does not exist
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::load_with(path, None)
+    }
+
+    /// Read the library from `path`, decrypting it if it was saved encrypted
+    pub fn load_with(path: &Path, cipher: Option<&Cipher>) -> io::Result<Self> {
+        match crypto::read_file(path, cipher) {
+            Ok(content) => serde_json::from_slice(&content).map_err(io::Error::other),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write the library to `path`
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        self.save_with(path, None)
+    }
+
+    /// Write the library to `path`, encrypted when a cipher is given
+    pub fn save_with(&self, path: &Path, cipher: Option<&Cipher>) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        let tmp = path.with_extension("json.tmp");
+        crypto::write_file(&tmp, &content, cipher)?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Count a use of `text`, adding it when it is new
+    pub fn record(&mut self, text: &str, now: DateTime<Local>) {
+        let text = text.trim();
+        if text.is_empty() {
+            return;
+        }
+        match self.prompts.iter_mut().find(|entry| entry.text == text) {
+            Some(entry) => {
+                entry.count += 1;
+                entry.last_used = now;
+            }
+            None => self.prompts.push(PromptEntry {
+                text: text.to_string(),
+                count: 1,
+                last_used: now,
+            }),
+        }
+
+        if self.prompts.len() > MAX_PROMPTS {
+            self.prompts
+                .sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+            self.prompts.truncate(MAX_PROMPTS);
+        }
+    }
+
+    /// Add the prompts of another library, e.g. one synced from another machine
+    ///
+    /// Prompts in both keep the higher count and the later use.
+    pub fn merge(&mut self, other: PromptLibrary, now: DateTime<Local>) {
+        for entry in other.prompts {
+            match self.prompts.iter_mut().find(|own| own.text == entry.text) {
+                Some(own) => {
+                    own.count = own.count.max(entry.count);
+                    own.last_used = own.last_used.max(entry.last_used);
+                }
+                None => self.prompts.push(entry),
+            }
+        }
+        if self.prompts.len() > MAX_PROMPTS {
+            self.prompts
+                .sort_by(|a, b| b.frecency(now).total_cmp(&a.frecency(now)));
+            self.prompts.truncate(MAX_PROMPTS);
+        }
+    }
+
+    /// Prompts matching `query`, best first
+    ///
+    /// Without a query prompts are ordered by [`PromptEntry::frecency`]; with
+    /// one, by how closely they match and then by frecency.
+    pub fn search(&self, query: &str, now: DateTime<Local>) -> Vec<&PromptEntry> {
+        let mut matches: Vec<(usize, f64, &PromptEntry)> = self
+            .prompts
+            .iter()
+            .filter_map(|entry| {
+                fuzzy_score(query, &entry.text).map(|score| (score, entry.frecency(now), entry))
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then(b.1.total_cmp(&a.1))
+                .then(b.2.last_used.cmp(&a.2.last_used))
+        });
+        matches.into_iter().map(|(_, _, entry)| entry).collect()
+    }
+}
+
+/// How loosely `text` matches `query`, lower is closer; `None` if it does not
+///
+/// Every character of the query must appear in the text in order, ignoring
+/// case and spaces. The score is the number of characters skipped between
+/// them, so a substring scores 0 and scattered matches score higher.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<usize> {
+    let mut needle = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .peekable();
+    let mut score = 0;
+    let mut started = false;
+    for c in text.chars().flat_map(char::to_lowercase) {
+        let Some(&wanted) = needle.peek() else {
+            break;
+        };
+        if c == wanted {
+            needle.next();
+            started = true;
+        } else if started && !c.is_whitespace() {
+            score += 1;
+        }
+    }
+    needle.peek().is_none().then_some(score)
+}
@@ -0,0 +1,67 @@
+//! Finding a term in the output
+//!
+//! Matching is smart-case: a term in lowercase matches regardless of case,
+//! a term with an uppercase letter only matches exactly.
+
+use std::ops::Range;
+
+/// Byte ranges of the occurrences of `term` in `text`, left to right
+pub fn match_ranges(text: &str, term: &str) -> Vec<Range<usize>> {
+    if term.is_empty() {
+        return Vec::new();
+    }
+    if term.chars().any(char::is_uppercase) {
+        return text
+            .match_indices(term)
+            .map(|(start, found)| start..start + found.len())
+            .collect();
+    }
+
+    // Compare character by character, as lowercasing can change byte lengths
+    let needle: Vec<char> = term.chars().collect();
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i + needle.len() <= chars.len() {
+        let matches = chars[i..i + needle.len()]
+            .iter()
+            .zip(&needle)
+            .all(|(&(_, c), &n)| c.to_lowercase().eq(n.to_lowercase()));
+        if matches {
+            let start = chars[i].0;
+            let end = chars
+                .get(i + needle.len())
+                .map_or(text.len(), |&(index, _)| index);
+            ranges.push(start..end);
+            i += needle.len();
+        } else {
+            i += 1;
+        }
+    }
+    ranges
+}
+
+/// Whether `text` contains `term`
+pub fn contains(text: &str, term: &str) -> bool {
+    !match_ranges(text, term).is_empty()
+}
+
+/// The next (`forward`) or previous line after `current` holding a match,
+/// wrapping around the ends
+///
+/// Without a current line the search starts before the first line, or after
+/// the last one going backwards.
+pub fn next_match(matches: &[usize], current: Option<usize>, forward: bool) -> Option<usize> {
+    let found = match (current, forward) {
+        (Some(current), true) => matches.iter().find(|&&line| line > current),
+        (Some(current), false) => matches.iter().rev().find(|&&line| line < current),
+        (None, _) => None,
+    };
+    found
+        .or(if forward {
+            matches.first()
+        } else {
+            matches.last()
+        })
+        .copied()
+}
@@ -0,0 +1,110 @@
+//! Conversation diff between two sessions
+//!
+//! `/diff-sessions <a> <b>` lines up the prompts of two stored sessions by
+//! their position and shows, as unified diffs, how each prompt and its
+//! response changed. This is how a workflow re-run against another model or
+//! with reworded prompts is compared. Commands and shell output are left out;
+//! only the conversation is compared.
+
+use crate::handlers::CommandMode;
+use crate::utils::diff::{diff_lines, diff_stats, unified_diff};
+use crate::utils::transcript::{Exchange, Transcript};
+
+/// One prompt of either session and what became of it in the other
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExchangeDiff {
+    /// Position of the prompt, from 1
+    pub number: usize,
+    /// The prompt and response in the first session
+    pub old: Option<Exchange>,
+    /// The prompt and response in the second session
+    pub new: Option<Exchange>,
+}
+
+impl ExchangeDiff {
+    /// Whether the prompt was worded differently
+    pub fn prompt_changed(&self) -> bool {
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => old.input.trim() != new.input.trim(),
+            _ => true,
+        }
+    }
+
+    /// Whether the response differs
+    pub fn response_changed(&self) -> bool {
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => old.output.trim() != new.output.trim(),
+            _ => true,
+        }
+    }
+
+    /// Whether both sessions have the same prompt and response here
+    pub fn is_same(&self) -> bool {
+        !self.prompt_changed() && !self.response_changed()
+    }
+
+    /// Lines added and removed between the two texts
+    pub fn stats(&self) -> (usize, usize) {
+        diff_stats(&diff_lines(&side_text(&self.old), &side_text(&self.new)))
+    }
+
+    /// The change as a unified diff labelled with the session names
+    pub fn render(&self, old_name: &str, new_name: &str) -> String {
+        let diff = unified_diff("", &side_text(&self.old), &side_text(&self.new));
+        // Replace the file header with one naming the sessions
+        let hunks: Vec<&str> = diff.lines().skip(2).collect();
+        format!(
+            "--- {} #{}\n+++ {} #{}\n{}",
+            old_name,
+            self.number,
+            new_name,
+            self.number,
+            hunks.join("\n")
+        )
+    }
+}
+
+/// The text compared for one side: the prompt, a blank line and the response
+fn side_text(exchange: &Option<Exchange>) -> String {
+    match exchange {
+        Some(exchange) => format!("{}\n\n{}", exchange.display_input(), exchange.output.trim()),
+        None => String::new(),
+    }
+}
+
+/// The prompts and responses of a session's output, in order
+pub fn conversation(output: &str) -> Vec<Exchange> {
+    Transcript::parse(output)
+        .exchanges
+        .into_iter()
+        .filter(|exchange| exchange.mode == CommandMode::AI)
+        .collect()
+}
+
+/// Pair the prompts of two sessions by position
+pub fn compare(old: &[Exchange], new: &[Exchange]) -> Vec<ExchangeDiff> {
+    (0..old.len().max(new.len()))
+        .map(|idx| ExchangeDiff {
+            number: idx + 1,
+            old: old.get(idx).cloned(),
+            new: new.get(idx).cloned(),
+        })
+        .collect()
+}
+
+/// One-line summary such as `4 prompt(s): 2 same, 1 changed, 1 only in b`
+pub fn summary(diffs: &[ExchangeDiff], old_name: &str, new_name: &str) -> String {
+    let same = diffs.iter().filter(|diff| diff.is_same()).count();
+    let only_old = diffs.iter().filter(|diff| diff.new.is_none()).count();
+    let only_new = diffs.iter().filter(|diff| diff.old.is_none()).count();
+    let changed = diffs.len() - same - only_old - only_new;
+
+    let mut parts = vec![format!("{} same", same), format!("{} changed", changed)];
+    if only_old > 0 {
+        parts.push(format!("{} only in {}", only_old, old_name));
+    }
+    if only_new > 0 {
+        parts.push(format!("{} only in {}", only_new, new_name));
+    }
+    format!("{} prompt(s): {}", diffs.len(), parts.join(", "))
+}
@@ -0,0 +1,183 @@
+//! Stored conversations
+//!
+//! Every session with at least one chat prompt is saved to
+//! `~/.ai-coder/sessions/<id>.json` with its output, a short title and its
+//! usage, so `/sessions` can list earlier conversations and reopen them.
+//! `/session save <name>` stores the session as `<name>.json` instead.
+//! With a cipher the files are written encrypted.
+
+use crate::ai::conversation::Message;
+use crate::utils::crypto::{self, Cipher};
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Longest title kept, in characters
+const MAX_TITLE_CHARS: usize = 60;
+
+/// Words of the first prompt used as a title until a generated one arrives
+const DRAFT_TITLE_WORDS: usize = 8;
+
+/// A conversation saved to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredSession {
+    /// File name without extension, the time the session started
+    pub id: String,
+    pub title: String,
+    pub created: DateTime<Local>,
+    pub updated: DateTime<Local>,
+    /// Number of exchanges in the output
+    pub messages: usize,
+    /// Estimated cost in dollars
+    pub cost: f64,
+    #[serde(default)]
+    pub prompt_tokens: usize,
+    #[serde(default)]
+    pub completion_tokens: usize,
+    /// The output pane as it was shown
+    pub output: String,
+    /// Commands and prompts entered, oldest first
+    #[serde(default)]
+    pub history: Vec<String>,
+    /// Messages sent as context with chat prompts; rebuilt from the output
+    /// for sessions saved without them
+    #[serde(default)]
+    pub conversation: Option<Vec<Message>>,
+    #[serde(default)]
+    pub command_count: usize,
+    #[serde(default)]
+    pub ai_count: usize,
+    #[serde(default)]
+    pub bash_count: usize,
+    /// Models that produced responses, in order of first use
+    #[serde(default)]
+    pub models_used: Vec<String>,
+}
+
+/// Directory of stored sessions
+#[derive(Debug, Clone)]
+pub struct SessionStore {
+    dir: PathBuf,
+    cipher: Option<Arc<Cipher>>,
+}
+
+impl SessionStore {
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            cipher: None,
+        }
+    }
+
+    /// Encrypt saved sessions; encrypted and plain ones are read either way
+    pub fn with_cipher(mut self, cipher: Option<Arc<Cipher>>) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
+    fn path(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    /// Write a session, replacing an earlier save of it
+    pub fn save(&self, session: &StoredSession) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(&session.id);
+        let content = serde_json::to_vec(session).map_err(io::Error::other)?;
+        let tmp = path.with_extension("json.tmp");
+        crypto::write_file(&tmp, &content, self.cipher.as_deref())?;
+        fs::rename(&tmp, path)
+    }
+
+    /// Read the session with the given id
+    pub fn load(&self, id: &str) -> io::Result<StoredSession> {
+        let content = crypto::read_file(&self.path(id), self.cipher.as_deref())?;
+        serde_json::from_slice(&content).map_err(io::Error::other)
+    }
+
+    /// Remove a stored session
+    pub fn remove(&self, id: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(id)) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// All stored sessions, most recently updated first
+    ///
+    /// Files that cannot be read, e.g. encrypted ones without the key, are
+    /// skipped.
+    pub fn list(&self) -> io::Result<Vec<StoredSession>> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        let mut sessions: Vec<StoredSession> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .filter_map(|path| crypto::read_file(&path, self.cipher.as_deref()).ok())
+            .filter_map(|content| serde_json::from_slice(&content).ok())
+            .collect();
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.updated));
+        Ok(sessions)
+    }
+}
+
+/// Whether `name` can name a session file: letters, digits, `-`, `_` and
+/// `.`, not starting with a dot
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Title made from the first words of the first prompt
+pub fn draft_title(prompt: &str) -> String {
+    let words: Vec<&str> = prompt.split_whitespace().collect();
+    let mut title = words[..words.len().min(DRAFT_TITLE_WORDS)].join(" ");
+    if words.len() > DRAFT_TITLE_WORDS {
+        title.push('…');
+    }
+    truncate_title(&title)
+}
+
+/// Prompt asking the model to title a conversation by its first prompt
+pub fn title_prompt(first_prompt: &str) -> String {
+    let excerpt: String = first_prompt.chars().take(2000).collect();
+    format!(
+        "Write a title of at most six words for a conversation that starts with the message \
+         below. Reply with the title only, without quotes.\n\n{}",
+        excerpt
+    )
+}
+
+/// The title in a model's reply: first line, without quotes, label or final period
+pub fn clean_title(reply: &str) -> Option<String> {
+    let line = reply.lines().map(str::trim).find(|line| !line.is_empty())?;
+    let line = line
+        .strip_prefix("Title:")
+        .or_else(|| line.strip_prefix("title:"))
+        .unwrap_or(line);
+    let title = line
+        .trim()
+        .trim_matches(|c| matches!(c, '"' | '\'' | '*' | '#' | '`'))
+        .trim_end_matches('.')
+        .trim();
+    (!title.is_empty()).then(|| truncate_title(title))
+}
+
+fn truncate_title(title: &str) -> String {
+    if title.chars().count() <= MAX_TITLE_CHARS {
+        return title.to_string();
+    }
+    let mut short: String = title.chars().take(MAX_TITLE_CHARS - 1).collect();
+    short.push('…');
+    short
+}
@@ -0,0 +1,245 @@
+//! Read-only sharing of the live session
+//!
+//! `ai-coder share` or `/share` serves the session over HTTP so teammates can
+//! follow the conversation and command output in a browser. The page at
+//! `http://<address>/<token>` keeps itself up to date with Server-Sent
+//! Events from `/<token>/events`; `/<token>/snapshot` returns the current
+//! state as JSON. The random token in every path keeps the session from
+//! anyone who was not given the link, and only `GET` is answered, so
+//! viewers cannot change anything.
+
+use crate::utils::crypto::hex;
+use rand::RngCore;
+use serde::Serialize;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// Largest request head read before the request is refused
+const MAX_REQUEST: usize = 8 * 1024;
+
+/// Time a client gets to send its request head
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What viewers see
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Snapshot {
+    /// Session title or id
+    pub title: String,
+    /// The output pane as plain text
+    pub output: String,
+    /// Whether a prompt or command is still running
+    pub busy: bool,
+}
+
+/// A running share server
+pub struct ShareServer {
+    addr: SocketAddr,
+    token: String,
+    snapshots: watch::Sender<Snapshot>,
+    task: JoinHandle<()>,
+}
+
+impl ShareServer {
+    /// Listen on `address` (such as `127.0.0.1:8765`; port 0 picks a free one)
+    pub async fn start(address: &str, initial: Snapshot) -> io::Result<Self> {
+        let listener = TcpListener::bind(address).await?;
+        let addr = listener.local_addr()?;
+        let mut bytes = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        let token = hex(&bytes);
+        let (snapshots, _) = watch::channel(initial);
+
+        let receiver = snapshots.subscribe();
+        let server_token = token.clone();
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let token = server_token.clone();
+                let snapshots = receiver.clone();
+                tokio::spawn(async move {
+                    let _ = serve(stream, &token, snapshots).await;
+                });
+            }
+        });
+        Ok(Self {
+            addr,
+            token,
+            snapshots,
+            task,
+        })
+    }
+
+    /// Link to the viewer page
+    pub fn url(&self) -> String {
+        let host = if self.addr.ip().is_unspecified() {
+            crate::utils::get_hostname()
+        } else {
+            self.addr.ip().to_string()
+        };
+        format!("http://{}:{}/{}", host, self.addr.port(), self.token)
+    }
+
+    /// Address the server listens on
+    pub fn local_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Number of browsers following the session
+    pub fn viewers(&self) -> usize {
+        // The server keeps one receiver to hand out to connections
+        self.snapshots.receiver_count().saturating_sub(1)
+    }
+
+    /// Change the snapshot in place; viewers are sent it when `update`
+    /// returns true
+    pub fn update(&self, update: impl FnOnce(&mut Snapshot) -> bool) {
+        self.snapshots.send_if_modified(update);
+    }
+
+    /// Show `snapshot` to every viewer, if it changed
+    pub fn publish(&self, snapshot: Snapshot) {
+        self.snapshots.send_if_modified(|current| {
+            if *current == snapshot {
+                return false;
+            }
+            *current = snapshot;
+            true
+        });
+    }
+}
+
+impl Drop for ShareServer {
+    /// Stop serving; open event streams end with the last sender
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// The path of a `GET` request, `Err` with the status line for anything else
+pub fn request_path(head: &str) -> Result<&str, &'static str> {
+    let mut parts = head.lines().next().unwrap_or("").split_whitespace();
+    match (parts.next(), parts.next()) {
+        (Some("GET"), Some(path)) => Ok(path.split('?').next().unwrap_or(path)),
+        (Some(_), Some(_)) => Err("405 Method Not Allowed"),
+        _ => Err("400 Bad Request"),
+    }
+}
+
+/// Answer one connection
+async fn serve(
+    mut stream: TcpStream,
+    token: &str,
+    mut snapshots: watch::Receiver<Snapshot>,
+) -> io::Result<()> {
+    // A client that never finishes its request would hold the connection
+    let head = match tokio::time::timeout(REQUEST_TIMEOUT, read_head(&mut stream)).await {
+        Ok(Ok(Some(head))) => head,
+        Ok(Ok(None)) => return Ok(()),
+        Ok(Err(e)) => return Err(e),
+        Err(_) => {
+            let status = "408 Request Timeout";
+            return respond(&mut stream, status, "text/plain", status).await;
+        }
+    };
+    let head = String::from_utf8_lossy(&head);
+    let path = match request_path(&head) {
+        Ok(path) => path,
+        Err(status) => return respond(&mut stream, status, "text/plain", status).await,
+    };
+
+    let base = format!("/{}", token);
+    if path == base || path == format!("{}/", base) {
+        respond(&mut stream, "200 OK", "text/html; charset=utf-8", VIEWER).await
+    } else if path == format!("{}/snapshot", base) {
+        let json = serde_json::to_string(&*snapshots.borrow()).map_err(io::Error::other)?;
+        respond(&mut stream, "200 OK", "application/json", &json).await
+    } else if path == format!("{}/events", base) {
+        stream
+            .write_all(
+                b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n",
+            )
+            .await?;
+        loop {
+            let json =
+                serde_json::to_string(&*snapshots.borrow_and_update()).map_err(io::Error::other)?;
+            stream
+                .write_all(format!("data: {}\n\n", json).as_bytes())
+                .await?;
+            if snapshots.changed().await.is_err() {
+                return Ok(());
+            }
+        }
+    } else {
+        respond(&mut stream, "404 Not Found", "text/plain", "Not found").await
+    }
+}
+
+/// Read the request head, `None` when the client closed or sent too much
+async fn read_head(stream: &mut TcpStream) -> io::Result<Option<Vec<u8>>> {
+    let mut head = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buf).await?;
+        if read == 0 || head.len() + read > MAX_REQUEST {
+            return Ok(None);
+        }
+        head.extend_from_slice(&buf[..read]);
+    }
+    Ok(Some(head))
+}
+
+async fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Page that follows the event stream
+const VIEWER: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>ai-coder session</title>
+<style>
+  body { margin: 0; background: #1e1e1e; color: #d4d4d4; font: 14px/1.4 ui-monospace, monospace; }
+  header { position: sticky; top: 0; padding: 8px 16px; background: #252526; border-bottom: 1px solid #3c3c3c; }
+  #state { float: right; color: #888; }
+  pre { margin: 0; padding: 16px; white-space: pre-wrap; word-wrap: break-word; }
+</style>
+</head>
+<body>
+<header><span id="title">ai-coder</span> <span id="state">connecting…</span></header>
+<pre id="output"></pre>
+<script>
+  const output = document.getElementById("output");
+  const state = document.getElementById("state");
+  const events = new EventSource(location.pathname.replace(/\/$/, "") + "/events");
+  events.onmessage = (event) => {
+    const snapshot = JSON.parse(event.data);
+    const follow = window.innerHeight + window.scrollY >= document.body.scrollHeight - 40;
+    document.getElementById("title").textContent = snapshot.title;
+    document.title = snapshot.title + " — ai-coder";
+    output.textContent = snapshot.output;
+    state.textContent = snapshot.busy ? "● working" : "● live (read-only)";
+    if (follow) window.scrollTo(0, document.body.scrollHeight);
+  };
+  events.onerror = () => { state.textContent = "disconnected"; };
+</script>
+</body>
+</html>
+"#;
@@ -0,0 +1,225 @@
+//! Speech-to-text for push-to-talk input
+//!
+//! Recording runs an external recorder (`arecord`, `sox`) that writes a WAV
+//! file until it is interrupted. The file is then transcribed by whisper.cpp
+//! or an OpenAI-compatible `/audio/transcriptions` API.
+
+use crate::ai::AIError;
+use crate::config::{SpeechBackend, SpeechConfig, get_config_dir};
+use crate::platform;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long a recorder may take to finish its file after being interrupted
+const STOP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A recorder writing to a WAV file
+pub struct Recording {
+    child: Child,
+    path: PathBuf,
+    started: Instant,
+}
+
+impl Recording {
+    /// Start recording into `path`
+    pub fn start(config: &SpeechConfig, path: PathBuf) -> Result<Self, String> {
+        let args = record_args(&config.record_command, &path)?;
+        let child = Command::new(&args[0])
+            .args(&args[1..])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("Cannot start `{}`: {}", args[0], e))?;
+        Ok(Self {
+            child,
+            path,
+            started: Instant::now(),
+        })
+    }
+
+    /// How long the recording has been running
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Stop recording and return the finished file
+    pub fn stop(mut self) -> Result<PathBuf, String> {
+        if let Ok(Some(status)) = self.child.try_wait() {
+            return Err(format!("The recorder exited early ({})", status));
+        }
+        let _ = platform::interrupt(&mut self.child);
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        while matches!(self.child.try_wait(), Ok(None)) && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+
+        let recorded = std::fs::metadata(&self.path)
+            .map(|meta| meta.len())
+            .unwrap_or(0);
+        // A WAV header alone is 44 bytes
+        if recorded <= 44 {
+            let _ = std::fs::remove_file(&self.path);
+            return Err("Nothing was recorded; check the microphone".to_string());
+        }
+        Ok(self.path.clone())
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        // Never leave a recorder running, e.g. when quitting mid-recording
+        if matches!(self.child.try_wait(), Ok(None)) {
+            let _ = self.child.kill();
+            let _ = self.child.wait();
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Program and arguments of the record command, with `{file}` filled in
+pub fn record_args(template: &str, file: &Path) -> Result<Vec<String>, String> {
+    file_command_args("record_command", template, file)
+}
+
+/// Program and arguments of the command configured as `setting`, with `{file}` filled in
+pub fn file_command_args(
+    setting: &str,
+    template: &str,
+    file: &Path,
+) -> Result<Vec<String>, String> {
+    let words = shell_words::split(template).map_err(|e| format!("Invalid {}: {}", setting, e))?;
+    if words.is_empty() {
+        return Err(format!("{} is empty", setting));
+    }
+    if !words.iter().any(|word| word.contains("{file}")) {
+        return Err(format!("{} must contain {{file}}", setting));
+    }
+    let file = file.display().to_string();
+    Ok(words
+        .into_iter()
+        .map(|word| word.replace("{file}", &file))
+        .collect())
+}
+
+/// The whisper.cpp model file
+pub fn whisper_model(config: &SpeechConfig) -> PathBuf {
+    if config.whisper_model.is_empty() {
+        get_config_dir().join("models").join("ggml-base.en.bin")
+    } else {
+        PathBuf::from(&config.whisper_model)
+    }
+}
+
+/// Arguments for whisper.cpp: plain text on stdout, no timestamps or logs
+pub fn whisper_args(config: &SpeechConfig, file: &Path) -> Vec<String> {
+    let mut args = vec![
+        "-m".to_string(),
+        whisper_model(config).display().to_string(),
+        "-f".to_string(),
+        file.display().to_string(),
+        "-nt".to_string(),
+        "-np".to_string(),
+    ];
+    if !config.language.is_empty() {
+        args.push("-l".to_string());
+        args.push(config.language.clone());
+    }
+    args
+}
+
+/// The spoken text without markers such as `[BLANK_AUDIO]`, on one line
+pub fn clean_transcript(text: &str) -> String {
+    let mut cleaned = String::new();
+    let mut depth = 0;
+    for c in text.chars() {
+        match c {
+            '[' => depth += 1,
+            ']' if depth > 0 => depth -= 1,
+            _ if depth == 0 => cleaned.push(c),
+            _ => {}
+        }
+    }
+    cleaned.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Transcribe a recording with the configured backend
+pub async fn transcribe(config: &SpeechConfig, file: &Path) -> Result<String, AIError> {
+    let text = match config.backend {
+        SpeechBackend::Whisper => {
+            let output = tokio::process::Command::new(&config.whisper_command)
+                .args(whisper_args(config, file))
+                .stdin(Stdio::null())
+                .kill_on_drop(true)
+                .output()
+                .await
+                .map_err(|e| {
+                    AIError::ConfigError(format!("Cannot run `{}`: {}", config.whisper_command, e))
+                })?;
+            if !output.status.success() {
+                return Err(AIError::APIError(format!(
+                    "whisper.cpp failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+            String::from_utf8_lossy(&output.stdout).into_owned()
+        }
+        SpeechBackend::OpenAI => transcribe_api(config, file).await?,
+    };
+    Ok(clean_transcript(&text))
+}
+
+async fn transcribe_api(config: &SpeechConfig, file: &Path) -> Result<String, AIError> {
+    #[derive(serde::Deserialize)]
+    struct Transcription {
+        text: String,
+    }
+
+    let audio = tokio::fs::read(file)
+        .await
+        .map_err(|e| AIError::ConfigError(format!("Cannot read the recording: {}", e)))?;
+    let part = reqwest::multipart::Part::bytes(audio)
+        .file_name("recording.wav")
+        .mime_str("audio/wav")
+        .map_err(|e| AIError::ConfigError(e.to_string()))?;
+    let mut form = reqwest::multipart::Form::new()
+        .part("file", part)
+        .text("model", config.model.clone());
+    if !config.language.is_empty() {
+        form = form.text("language", config.language.clone());
+    }
+
+    let response = reqwest::Client::new()
+        .post(format!(
+            "{}/audio/transcriptions",
+            config.endpoint.trim_end_matches('/')
+        ))
+        .bearer_auth(&config.api_key)
+        .multipart(form)
+        .timeout(Duration::from_secs(60))
+        .send()
+        .await
+        .map_err(|e| AIError::NetworkError(format!("Transcription request failed: {}", e)))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(match status {
+            reqwest::StatusCode::UNAUTHORIZED => {
+                AIError::Authentication("The transcription API rejected the key".to_string())
+            }
+            _ => AIError::APIError(format!(
+                "Transcription API returned {}: {}",
+                status,
+                body.trim()
+            )),
+        });
+    }
+    let transcription: Transcription = response
+        .json()
+        .await
+        .map_err(|e| AIError::InvalidResponse(format!("Unexpected transcription: {}", e)))?;
+    Ok(transcription.text)
+}
@@ -0,0 +1,489 @@
+//! Syncing sessions between machines
+//!
+//! `/sync push` uploads the stored sessions, the prompt library and, when
+//! enabled, the configuration to a git repository, a WebDAV folder or an S3
+//! bucket, every file encrypted with the sync passphrase. An encrypted index
+//! of content hashes limits pushes and pulls to the files that changed, so
+//! `/sync pull` on another machine brings down only what is new there.
+
+use crate::config::{SyncBackend, SyncConfig};
+use crate::utils::crypto::{Cipher, CryptoError, hex};
+use crate::utils::get_hostname;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::process::Command;
+
+/// Remote file listing the synced files and their content hashes
+pub const INDEX_FILE: &str = "index.enc";
+
+/// Time allowed for one HTTP request
+const HTTP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A synced file, as plaintext
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncFile {
+    /// Flat file name such as `session-20240101-120000.json`
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+impl SyncFile {
+    pub fn new(name: impl Into<String>, data: Vec<u8>) -> Self {
+        Self {
+            name: name.into(),
+            data,
+        }
+    }
+}
+
+/// File names mapped to the hash of their content
+pub type SyncIndex = BTreeMap<String, String>;
+
+/// Errors while syncing
+#[derive(Debug, Error)]
+pub enum SyncError {
+    /// The `sync` section is incomplete
+    #[error("{0}")]
+    Config(String),
+
+    /// A git command failed
+    #[error("git: {0}")]
+    Git(String),
+
+    /// A WebDAV or S3 request failed
+    #[error("{0}")]
+    Http(String),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("{0}")]
+    Crypto(#[from] CryptoError),
+
+    /// The remote index could not be read
+    #[error("invalid sync index: {0}")]
+    Index(String),
+
+    /// The sync task was cancelled
+    #[error("cancelled")]
+    Cancelled,
+}
+
+/// Where synced files are kept
+#[derive(Debug, Clone)]
+pub enum Remote {
+    /// A git repository, worked on in a local clone at `dir`
+    Git { url: String, dir: PathBuf },
+    /// A WebDAV folder
+    WebDav {
+        url: String,
+        username: String,
+        password: String,
+    },
+    /// An S3 bucket or S3-compatible store, optionally under a folder
+    S3 {
+        endpoint: String,
+        bucket: String,
+        prefix: String,
+        region: String,
+        access_key: String,
+        secret_key: String,
+    },
+}
+
+impl Remote {
+    /// Remote described by the `sync` section; git clones go to `dir`
+    pub fn from_config(config: &SyncConfig, dir: &Path) -> Result<Self, SyncError> {
+        let url = config.url.trim().trim_end_matches('/').to_string();
+        if config.backend != SyncBackend::Off && url.is_empty() {
+            return Err(SyncError::Config("sync.url is not set".to_string()));
+        }
+        match config.backend {
+            SyncBackend::Off => Err(SyncError::Config(
+                "sync is off; set sync.backend to git, webdav or s3".to_string(),
+            )),
+            SyncBackend::Git => Ok(Remote::Git {
+                url,
+                dir: dir.to_path_buf(),
+            }),
+            SyncBackend::WebDav => Ok(Remote::WebDav {
+                url,
+                username: config.username.clone(),
+                password: config.password.clone(),
+            }),
+            SyncBackend::S3 => {
+                let (bucket, prefix) = config
+                    .bucket
+                    .trim_matches('/')
+                    .split_once('/')
+                    .map(|(bucket, prefix)| (bucket.to_string(), format!("{}/", prefix)))
+                    .unwrap_or_else(|| {
+                        (config.bucket.trim_matches('/').to_string(), String::new())
+                    });
+                if bucket.is_empty() {
+                    return Err(SyncError::Config("sync.bucket is not set".to_string()));
+                }
+                Ok(Remote::S3 {
+                    endpoint: url,
+                    bucket,
+                    prefix,
+                    region: if config.region.is_empty() {
+                        "us-east-1".to_string()
+                    } else {
+                        config.region.clone()
+                    },
+                    access_key: config.username.clone(),
+                    secret_key: config.password.clone(),
+                })
+            }
+        }
+    }
+
+    /// Short description for `/sync status`
+    pub fn describe(&self) -> String {
+        match self {
+            Remote::Git { url, .. } => format!("git {}", url),
+            Remote::WebDav { url, .. } => format!("WebDAV {}", url),
+            Remote::S3 {
+                endpoint,
+                bucket,
+                prefix,
+                ..
+            } => format!("S3 {}/{}/{}", endpoint, bucket, prefix),
+        }
+    }
+
+    /// Bring the local clone up to date; nothing to do for HTTP stores
+    async fn prepare(&self) -> Result<(), SyncError> {
+        let Remote::Git { url, dir } = self else {
+            return Ok(());
+        };
+        if !dir.join(".git").exists() {
+            if let Some(parent) = dir.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let dir = dir.to_string_lossy();
+            git(None, &["clone", "--quiet", url, &dir]).await?;
+            return Ok(());
+        }
+        git(Some(dir), &["remote", "set-url", "origin", url]).await?;
+        git(Some(dir), &["fetch", "--quiet", "origin"]).await?;
+        // The clone only mirrors the remote, so it follows it exactly; an
+        // empty remote has no upstream yet
+        if git(Some(dir), &["rev-parse", "--verify", "--quiet", "@{u}"])
+            .await
+            .is_ok()
+        {
+            git(Some(dir), &["reset", "--quiet", "--hard", "@{u}"]).await?;
+        }
+        Ok(())
+    }
+
+    /// Read a file, `None` if it does not exist
+    async fn get(&self, name: &str) -> Result<Option<Vec<u8>>, SyncError> {
+        match self {
+            Remote::Git { dir, .. } => match fs::read(dir.join(name)) {
+                Ok(data) => Ok(Some(data)),
+                Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(e.into()),
+            },
+            Remote::WebDav { .. } | Remote::S3 { .. } => {
+                let response = self
+                    .request(reqwest::Method::GET, name, Vec::new())?
+                    .send()
+                    .await
+                    .map_err(|e| SyncError::Http(format!("Could not reach {}: {}", name, e)))?;
+                let status = response.status();
+                if status == reqwest::StatusCode::NOT_FOUND {
+                    return Ok(None);
+                }
+                if !status.is_success() {
+                    return Err(SyncError::Http(format!("GET {} returned {}", name, status)));
+                }
+                let data = response
+                    .bytes()
+                    .await
+                    .map_err(|e| SyncError::Http(format!("Download of {} failed: {}", name, e)))?;
+                Ok(Some(data.to_vec()))
+            }
+        }
+    }
+
+    /// Write a file
+    async fn put(&self, name: &str, data: Vec<u8>) -> Result<(), SyncError> {
+        match self {
+            Remote::Git { dir, .. } => Ok(fs::write(dir.join(name), data)?),
+            Remote::WebDav { .. } | Remote::S3 { .. } => {
+                let response = self
+                    .request(reqwest::Method::PUT, name, data)?
+                    .send()
+                    .await
+                    .map_err(|e| SyncError::Http(format!("Upload of {} failed: {}", name, e)))?;
+                if !response.status().is_success() {
+                    return Err(SyncError::Http(format!(
+                        "PUT {} returned {}",
+                        name,
+                        response.status()
+                    )));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Make the written files visible to other machines
+    async fn publish(&self, message: &str) -> Result<(), SyncError> {
+        let Remote::Git { dir, .. } = self else {
+            return Ok(());
+        };
+        git(Some(dir), &["add", "--all"]).await?;
+        git(
+            Some(dir),
+            &[
+                "-c",
+                "user.name=AI Coder",
+                "-c",
+                "user.email=ai-coder@localhost",
+                "commit",
+                "--quiet",
+                "-m",
+                message,
+            ],
+        )
+        .await?;
+        git(Some(dir), &["push", "--quiet", "-u", "origin", "HEAD"]).await?;
+        Ok(())
+    }
+
+    /// Authenticated request for a file in a WebDAV folder or S3 bucket
+    fn request(
+        &self,
+        method: reqwest::Method,
+        name: &str,
+        body: Vec<u8>,
+    ) -> Result<reqwest::RequestBuilder, SyncError> {
+        let client = reqwest::Client::builder()
+            .timeout(HTTP_TIMEOUT)
+            .build()
+            .map_err(|e| SyncError::Http(e.to_string()))?;
+        match self {
+            Remote::WebDav {
+                url,
+                username,
+                password,
+            } => {
+                let request = client.request(method, format!("{}/{}", url, name));
+                let request = if username.is_empty() {
+                    request
+                } else {
+                    request.basic_auth(username, Some(password))
+                };
+                Ok(request.body(body))
+            }
+            Remote::S3 {
+                endpoint,
+                bucket,
+                prefix,
+                region,
+                access_key,
+                secret_key,
+            } => {
+                let url =
+                    reqwest::Url::parse(&format!("{}/{}/{}{}", endpoint, bucket, prefix, name))
+                        .map_err(|e| SyncError::Config(format!("Invalid S3 endpoint: {}", e)))?;
+                let amz_date = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+                let payload_hash = hex(&Sha256::digest(&body));
+                let authorization = s3_authorization(
+                    method.as_str(),
+                    &url,
+                    region,
+                    access_key,
+                    secret_key,
+                    &payload_hash,
+                    &amz_date,
+                );
+                Ok(client
+                    .request(method, url)
+                    .header("x-amz-date", amz_date)
+                    .header("x-amz-content-sha256", payload_hash)
+                    .header("Authorization", authorization)
+                    .body(body))
+            }
+            Remote::Git { .. } => unreachable!("git remotes are read from the local clone"),
+        }
+    }
+}
+
+/// Upload the files that differ from the remote copy, returning how many
+pub async fn push(
+    remote: &Remote,
+    cipher: &Cipher,
+    files: &[SyncFile],
+) -> Result<usize, SyncError> {
+    remote.prepare().await?;
+    let mut index = remote_index(remote, cipher).await?;
+    let mut pushed = 0;
+    for file in files {
+        let hash = fingerprint(&file.data);
+        if index.get(&file.name) == Some(&hash) {
+            continue;
+        }
+        remote
+            .put(&encrypted_name(&file.name), cipher.encrypt(&file.data)?)
+            .await?;
+        index.insert(file.name.clone(), hash);
+        pushed += 1;
+    }
+    if pushed > 0 {
+        let index = serde_json::to_vec(&index).map_err(|e| SyncError::Index(e.to_string()))?;
+        remote.put(INDEX_FILE, cipher.encrypt(&index)?).await?;
+        let host = get_hostname();
+        remote
+            .publish(&format!("Sync {} file(s) from {}", pushed, host))
+            .await?;
+    }
+    Ok(pushed)
+}
+
+/// Download the remote files whose content differs from `local`
+pub async fn pull(
+    remote: &Remote,
+    cipher: &Cipher,
+    local: &SyncIndex,
+) -> Result<Vec<SyncFile>, SyncError> {
+    remote.prepare().await?;
+    let index = remote_index(remote, cipher).await?;
+    let mut files = Vec::new();
+    for (name, hash) in index {
+        if !valid_name(&name) || local.get(&name) == Some(&hash) {
+            continue;
+        }
+        let Some(blob) = remote.get(&encrypted_name(&name)).await? else {
+            continue;
+        };
+        files.push(SyncFile::new(name, cipher.decrypt(&blob)?));
+    }
+    Ok(files)
+}
+
+/// Index of the files as they are on this machine
+pub fn local_index(files: &[SyncFile]) -> SyncIndex {
+    files
+        .iter()
+        .map(|file| (file.name.clone(), fingerprint(&file.data)))
+        .collect()
+}
+
+/// Hex SHA-256 of a file's content
+pub fn fingerprint(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+/// Whether a name from the remote index is a plain file name
+pub fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+async fn remote_index(remote: &Remote, cipher: &Cipher) -> Result<SyncIndex, SyncError> {
+    match remote.get(INDEX_FILE).await? {
+        Some(blob) => serde_json::from_slice(&cipher.decrypt(&blob)?)
+            .map_err(|e| SyncError::Index(e.to_string())),
+        None => Ok(SyncIndex::new()),
+    }
+}
+
+fn encrypted_name(name: &str) -> String {
+    format!("{}.enc", name)
+}
+
+/// Run git, returning its output
+async fn git(dir: Option<&Path>, args: &[&str]) -> Result<String, SyncError> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    // Never wait for credentials on the terminal the TUI is drawn on
+    let output = command
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| SyncError::Git(format!("could not run git: {}", e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(SyncError::Git(format!(
+            "{} failed: {}",
+            args.first().unwrap_or(&""),
+            stderr.trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// `Authorization` header of an AWS Signature Version 4 request
+pub fn s3_authorization(
+    method: &str,
+    url: &reqwest::Url,
+    region: &str,
+    access_key: &str,
+    secret_key: &str,
+    payload_hash: &str,
+    amz_date: &str,
+) -> String {
+    let host = match url.port() {
+        Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+        None => url.host_str().unwrap_or_default().to_string(),
+    };
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "{}\n{}\n{}\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        method,
+        url.path(),
+        url.query().unwrap_or_default(),
+        host,
+        payload_hash,
+        amz_date,
+        signed_headers,
+        payload_hash
+    );
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/s3/aws4_request", date, region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex(&Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = [date, region, "s3", "aws4_request"]
+        .iter()
+        .fold(format!("AWS4{}", secret_key).into_bytes(), |key, part| {
+            hmac_sha256(&key, part.as_bytes())
+        });
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        access_key,
+        scope,
+        signed_headers,
+        hex(&hmac_sha256(&key, string_to_sign.as_bytes()))
+    )
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
@@ -1,12 +1,24 @@
 //! Task management module
 //!
-//! This module provides a task management system for tracking background tasks
+//! This module provides a task management system for tracking background tasks.
+//! Tasks carry labels (e.g. "ai", "bash", "index") and a priority. At most
+//! `tasks.max_concurrent` of them run at once; a waiting task starts only when
+//! no waiting task has a higher priority, so AI generations run before
+//! background work.
+//!
+//! A task can depend on others, e.g. "run tests" after "apply patch". It
+//! starts only once all of them have completed; when one fails or is
+//! cancelled, the tasks waiting on it are cancelled as well.
 
-use crate::ai::types::{ProgressStats, TaskStatus};
+use crate::ai::continuation::Incomplete;
+use crate::ai::types::{ProgressStats, Provider, TaskStatus, TokenUsage};
+use crate::utils::latency::Latency;
 use chrono::{DateTime, Utc};
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use tokio::sync::{broadcast, mpsc};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, TryLockError};
+use std::time::Duration;
+use tokio::sync::{Notify, broadcast, mpsc};
 use uuid::Uuid;
 
 /// Unique identifier for a task
@@ -69,6 +81,63 @@ impl std::fmt::Display for TaskType {
     }
 }
 
+impl TaskType {
+    /// Label given to tasks of this type, used to filter the tasks popup
+    pub fn label(&self) -> Option<&'static str> {
+        match self {
+            TaskType::AIGeneration => Some("ai"),
+            TaskType::BashCommand => Some("bash"),
+            TaskType::FileOperation => Some("file"),
+            TaskType::NetworkRequest => Some("network"),
+            TaskType::Other => None,
+        }
+    }
+
+    /// Priority of tasks of this type unless set explicitly
+    pub fn default_priority(&self) -> TaskPriority {
+        match self {
+            TaskType::AIGeneration => TaskPriority::High,
+            _ => TaskPriority::Normal,
+        }
+    }
+}
+
+/// Scheduling priority of a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TaskPriority {
+    /// Background work such as indexing
+    Low,
+    /// Regular tasks
+    Normal,
+    /// Interactive work the user is waiting for
+    High,
+}
+
+impl std::fmt::Display for TaskPriority {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TaskPriority::Low => write!(f, "low"),
+            TaskPriority::Normal => write!(f, "normal"),
+            TaskPriority::High => write!(f, "high"),
+        }
+    }
+}
+
+/// Model usage reported by a finished AI task
+#[derive(Debug, Clone)]
+pub struct TaskUsage {
+    /// Provider that served the request
+    pub provider: Provider,
+    /// Model that produced the response
+    pub model: String,
+    /// Tokens consumed
+    pub tokens: TokenUsage,
+    /// Estimated cost in dollars
+    pub cost: f64,
+    /// Timing of a prompt the user waited on
+    pub latency: Option<Latency>,
+}
+
 /// A background task with metadata
 #[derive(Debug, Clone)]
 pub struct Task {
@@ -90,6 +159,18 @@ pub struct Task {
     pub progress: Option<ProgressStats>,
     /// Task description (optional)
     pub description: Option<String>,
+    /// Transient state shown next to the task, e.g. while waiting on a rate limit
+    pub note: Option<String>,
+    /// Model usage of a completed AI task
+    pub usage: Option<TaskUsage>,
+    /// The response of an AI task came back empty or cut off
+    pub incomplete: Option<Incomplete>,
+    /// Scheduling priority
+    pub priority: TaskPriority,
+    /// Labels used to filter the tasks popup
+    pub labels: Vec<String>,
+    /// Tasks that must complete before this one starts
+    pub depends_on: Vec<TaskId>,
 }
 
 impl Task {
@@ -105,9 +186,20 @@ impl Task {
             completed_at: None,
             progress: None,
             description: None,
+            note: None,
+            usage: None,
+            incomplete: None,
+            priority: task_type.default_priority(),
+            labels: task_type.label().map(String::from).into_iter().collect(),
+            depends_on: Vec::new(),
         }
     }
 
+    /// Whether the task carries the label
+    pub fn has_label(&self, label: &str) -> bool {
+        self.labels.iter().any(|own| own == label)
+    }
+
     /// Mark the task as running
     pub fn mark_running(&mut self) {
         self.status = TaskStatus::Running;
@@ -141,14 +233,21 @@ impl Task {
     }
 
     /// Update the task's progress
-    pub fn update_progress(&mut self, tokens_generated: usize) {
-        if let Some(progress) = &mut self.progress {
-            progress.update(tokens_generated);
-        } else {
-            let mut progress = ProgressStats::new();
-            progress.update(tokens_generated);
-            self.progress = Some(progress);
+    ///
+    /// `estimated_total` replaces the expected number of tokens when known.
+    pub fn update_progress(&mut self, tokens_generated: usize, estimated_total: Option<usize>) {
+        let progress = self.progress.get_or_insert_with(ProgressStats::new);
+        if estimated_total.is_some() {
+            progress.estimated_total_tokens = estimated_total;
         }
+        progress.update(tokens_generated);
+    }
+
+    /// Note that the server accepted the request of the task
+    pub fn mark_connected(&mut self) {
+        self.progress
+            .get_or_insert_with(ProgressStats::new)
+            .connected();
     }
 
     /// Get the task's duration in seconds
@@ -184,6 +283,10 @@ pub struct TaskManager {
     tx: broadcast::Sender<TaskId>,
     // Store response channels for tasks that return content
     response_channels: Arc<Mutex<HashMap<TaskId, mpsc::Receiver<Option<String>>>>>,
+    // Most tasks running at once, 0 for no limit
+    max_concurrent: Arc<AtomicUsize>,
+    // Woken whenever a running slot may have become free
+    slot_freed: Arc<Notify>,
 }
 
 impl Default for TaskManager {
@@ -200,15 +303,23 @@ impl TaskManager {
             tasks: Arc::new(Mutex::new(HashMap::new())),
             tx,
             response_channels: Arc::new(Mutex::new(HashMap::new())),
+            max_concurrent: Arc::new(AtomicUsize::new(0)),
+            slot_freed: Arc::new(Notify::new()),
         }
     }
-    
+
+    /// Limit how many tasks run at once (0 for no limit)
+    pub fn set_max_concurrent(&self, max: usize) {
+        self.max_concurrent.store(max, Ordering::SeqCst);
+        self.slot_freed.notify_waiters();
+    }
+
     /// Store a response channel for a task
     pub fn set_response_channel(&self, id: TaskId, rx: mpsc::Receiver<Option<String>>) {
         let mut channels = self.response_channels.lock().unwrap();
         channels.insert(id, rx);
     }
-    
+
     /// Get a response channel for a task
     pub fn take_response_channel(&self, id: TaskId) -> Option<mpsc::Receiver<Option<String>>> {
         let mut channels = self.response_channels.lock().unwrap();
@@ -217,7 +328,24 @@ impl TaskManager {
 
     /// Create and register a new task
     pub fn create_task(&self, name: impl Into<String>, task_type: TaskType) -> TaskId {
-        let task = Task::new(name, task_type);
+        self.create_task_with(name, task_type, task_type.default_priority(), &[])
+    }
+
+    /// Create and register a new task with a priority and extra labels
+    pub fn create_task_with(
+        &self,
+        name: impl Into<String>,
+        task_type: TaskType,
+        priority: TaskPriority,
+        labels: &[&str],
+    ) -> TaskId {
+        let mut task = Task::new(name, task_type);
+        task.priority = priority;
+        for label in labels {
+            if !task.has_label(label) {
+                task.labels.push(label.to_string());
+            }
+        }
         let id = task.id;
 
         let mut tasks = self.tasks.lock().unwrap();
@@ -256,6 +384,12 @@ impl TaskManager {
                     task.mark_cancelled();
                 }
             }
+            if task.completed_at.is_some() {
+                self.slot_freed.notify_waiters();
+            }
+            if matches!(status, TaskStatus::Failed | TaskStatus::Cancelled) {
+                Self::cancel_dependents(&mut tasks, id, &self.tx);
+            }
 
             // Notify listeners with broadcast
             let _ = self.tx.send(id);
@@ -267,10 +401,45 @@ impl TaskManager {
     }
 
     /// Update a task's progress
-    pub fn update_task_progress(&self, id: TaskId, tokens_generated: usize) -> bool {
+    pub fn update_task_progress(
+        &self,
+        id: TaskId,
+        tokens_generated: usize,
+        estimated_total: Option<usize>,
+    ) -> bool {
+        let mut tasks = self.tasks.lock().unwrap();
+        if let Some(task) = tasks.get_mut(&id) {
+            task.update_progress(tokens_generated, estimated_total);
+
+            // Notify listeners with broadcast
+            let _ = self.tx.send(id);
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Note that the server accepted the request of a task
+    pub fn mark_task_connected(&self, id: TaskId) -> bool {
+        let mut tasks = self.tasks.lock().unwrap();
+        if let Some(task) = tasks.get_mut(&id) {
+            task.mark_connected();
+
+            // Notify listeners with broadcast
+            let _ = self.tx.send(id);
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Set or clear the note shown next to a task
+    pub fn set_task_note(&self, id: TaskId, note: Option<String>) -> bool {
         let mut tasks = self.tasks.lock().unwrap();
         if let Some(task) = tasks.get_mut(&id) {
-            task.update_progress(tokens_generated);
+            task.note = note;
 
             // Notify listeners with broadcast
             let _ = self.tx.send(id);
@@ -281,11 +450,35 @@ impl TaskManager {
         }
     }
 
+    /// Record the model usage of a task
+    pub fn set_task_usage(&self, id: TaskId, usage: TaskUsage) -> bool {
+        let mut tasks = self.tasks.lock().unwrap();
+        if let Some(task) = tasks.get_mut(&id) {
+            task.usage = Some(usage);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Record that the response of a task came back empty or cut off
+    pub fn set_task_incomplete(&self, id: TaskId, incomplete: Incomplete) -> bool {
+        let mut tasks = self.tasks.lock().unwrap();
+        if let Some(task) = tasks.get_mut(&id) {
+            task.incomplete = Some(incomplete);
+            true
+        } else {
+            false
+        }
+    }
+
     /// Cancel a task by ID
     pub fn cancel_task(&self, id: TaskId) -> bool {
         let mut tasks = self.tasks.lock().unwrap();
         if let Some(task) = tasks.get_mut(&id) {
             task.mark_cancelled();
+            self.slot_freed.notify_waiters();
+            Self::cancel_dependents(&mut tasks, id, &self.tx);
 
             // Notify listeners with broadcast
             let _ = self.tx.send(id);
@@ -296,6 +489,163 @@ impl TaskManager {
         }
     }
 
+    /// Let `task` start only after `dependency` has completed successfully
+    ///
+    /// Returns false if either task is unknown, `task` is no longer pending,
+    /// or the dependency would form a cycle. A dependency that already failed
+    /// cancels the task right away.
+    pub fn add_dependency(&self, task: TaskId, dependency: TaskId) -> bool {
+        let mut tasks = self.tasks.lock().unwrap();
+        if !tasks.contains_key(&dependency)
+            || tasks
+                .get(&task)
+                .is_none_or(|task| task.status != TaskStatus::Pending)
+            || Self::depends_on(&tasks, dependency, task)
+        {
+            return false;
+        }
+
+        if let Some(entry) = tasks.get_mut(&task)
+            && !entry.depends_on.contains(&dependency)
+        {
+            entry.depends_on.push(dependency);
+        }
+        let dependency_status = tasks.get(&dependency).map(|dep| dep.status);
+        if matches!(
+            dependency_status,
+            Some(TaskStatus::Failed | TaskStatus::Cancelled)
+        ) {
+            Self::cancel_dependents(&mut tasks, dependency, &self.tx);
+        }
+        let _ = self.tx.send(task);
+        true
+    }
+
+    /// Whether `task` waits on `dependency`, directly or through other tasks
+    fn depends_on(tasks: &HashMap<TaskId, Task>, task: TaskId, dependency: TaskId) -> bool {
+        if task == dependency {
+            return true;
+        }
+        tasks.get(&task).is_some_and(|entry| {
+            entry
+                .depends_on
+                .iter()
+                .any(|&next| Self::depends_on(tasks, next, dependency))
+        })
+    }
+
+    /// Cancel the unfinished tasks that wait on a failed or cancelled task
+    fn cancel_dependents(
+        tasks: &mut HashMap<TaskId, Task>,
+        failed: TaskId,
+        tx: &broadcast::Sender<TaskId>,
+    ) {
+        let dependents: Vec<TaskId> = tasks
+            .values()
+            .filter(|task| task.completed_at.is_none() && task.depends_on.contains(&failed))
+            .map(|task| task.id)
+            .collect();
+        for id in dependents {
+            if let Some(task) = tasks.get_mut(&id) {
+                task.mark_cancelled();
+                task.note = Some(format!("dependency {} did not complete", failed.short()));
+            }
+            let _ = tx.send(id);
+            Self::cancel_dependents(tasks, id, tx);
+        }
+    }
+
+    /// Whether all dependencies of the task have completed
+    fn is_ready(tasks: &HashMap<TaskId, Task>, task: &Task) -> bool {
+        task.depends_on.iter().all(|dependency| {
+            tasks
+                .get(dependency)
+                .is_some_and(|dep| dep.status == TaskStatus::Completed)
+        })
+    }
+
+    /// Start a pending task if a slot is free and it is next in line
+    ///
+    /// The next task is the ready waiting one with the highest priority, the
+    /// oldest first among equals; a task is ready once all its dependencies
+    /// have completed. Without a limit every ready task starts right away.
+    /// Returns whether the task is running.
+    pub fn try_start(&self, id: TaskId) -> bool {
+        let mut tasks = self.tasks.lock().unwrap();
+        let Some(task) = tasks.get(&id) else {
+            return false;
+        };
+        if task.status != TaskStatus::Pending {
+            return task.status == TaskStatus::Running;
+        }
+        if !Self::is_ready(&tasks, task) {
+            return false;
+        }
+
+        let max = self.max_concurrent.load(Ordering::SeqCst);
+        let running = tasks
+            .values()
+            .filter(|task| task.status == TaskStatus::Running)
+            .count();
+        if max == 0 {
+            // Without a limit every ready task starts at once
+            if let Some(task) = tasks.get_mut(&id) {
+                task.mark_running();
+            }
+            let _ = self.tx.send(id);
+            return true;
+        }
+        if running >= max {
+            return false;
+        }
+        let next = tasks
+            .values()
+            .filter(|task| task.status == TaskStatus::Pending && Self::is_ready(&tasks, task))
+            .max_by(|a, b| {
+                a.priority
+                    .cmp(&b.priority)
+                    .then(b.created_at.cmp(&a.created_at))
+            })
+            .map(|task| task.id);
+        if next != Some(id) {
+            return false;
+        }
+
+        if let Some(task) = tasks.get_mut(&id) {
+            task.mark_running();
+        }
+        let _ = self.tx.send(id);
+        true
+    }
+
+    /// Wait until a running slot may have become free
+    ///
+    /// Also returns after a short timeout so callers can check for aborts.
+    pub async fn wait_for_slot(&self) {
+        let _ = tokio::time::timeout(Duration::from_millis(250), self.slot_freed.notified()).await;
+    }
+
+    /// Number of tasks currently running
+    pub fn running_count(&self) -> usize {
+        let tasks = self.tasks.lock().unwrap();
+        tasks
+            .values()
+            .filter(|task| task.status == TaskStatus::Running)
+            .count()
+    }
+
+    /// Labels of all known tasks, sorted
+    pub fn labels(&self) -> Vec<String> {
+        let tasks = self.tasks.lock().unwrap();
+        let mut labels: Vec<String> = tasks
+            .values()
+            .flat_map(|task| task.labels.iter().cloned())
+            .collect();
+        labels.sort();
+        labels.dedup();
+        labels
+    }
+
     /// Get a list of all active tasks
     pub fn active_tasks(&self) -> Vec<Task> {
         let tasks = self.tasks.lock().unwrap();
@@ -312,6 +662,16 @@ impl TaskManager {
         tasks.values().cloned().collect()
     }
 
+    /// All tasks, or `None` if the task list is locked, e.g. by a thread that panicked
+    pub fn try_all_tasks(&self) -> Option<Vec<Task>> {
+        let tasks = match self.tasks.try_lock() {
+            Ok(tasks) => tasks,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return None,
+        };
+        Some(tasks.values().cloned().collect())
+    }
+
     /// Get a list of recently completed tasks (last 10 minutes)
     pub fn recent_tasks(&self) -> Vec<Task> {
         let tasks = self.tasks.lock().unwrap();
@@ -335,7 +695,7 @@ impl TaskManager {
 
         // Create a new broadcast receiver from the main channel
         let mut broadcast_rx = self.tx.subscribe();
-        
+
         // Spawn a task to forward broadcast messages to the mpsc channel
         tokio::spawn(async move {
             while let Ok(task_id) = broadcast_rx.recv().await {
@@ -363,4 +723,4 @@ impl TaskManager {
             }
         });
     }
-}
\ No newline at end of file
+}
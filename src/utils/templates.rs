@@ -0,0 +1,205 @@
+//! Prompt templates
+//!
+//! Templates are Markdown or text files under `~/.ai-coder/templates`, named
+//! by their path without the extension (`review.md` is `review`). A first
+//! line starting with `# ` describes the template and is not sent. The
+//! prompt gets the text after the template name in place of `{{input}}`, or
+//! after it when there is no placeholder.
+//!
+//! A team repository configured in `templates.team_url` is cloned into
+//! `templates/team` and read as the `team/` namespace. It is reset to the
+//! remote on every `/template update`, so local changes there do not
+//! survive. Its `commands.yaml`, mapping names to commands, adds shortcuts
+//! that work like aliases as `/team/<name>`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+use walkdir::WalkDir;
+
+/// Namespace and directory of the team repository
+pub const TEAM: &str = "team";
+
+/// File of the team repository with its shared commands
+pub const COMMANDS_FILE: &str = "commands.yaml";
+
+/// Placeholder replaced by the text given with the template
+pub const INPUT_PLACEHOLDER: &str = "{{input}}";
+
+/// Extensions of template files
+const EXTENSIONS: &[&str] = &["md", "txt"];
+
+/// A prompt template
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    /// Name such as `review` or `team/review`
+    pub name: String,
+    /// The `# ` line the file starts with, if any
+    pub description: Option<String>,
+    /// Prompt text
+    pub body: String,
+}
+
+impl Template {
+    /// Read a template from its file content
+    pub fn parse(name: String, content: &str) -> Self {
+        let content = content.trim_start_matches('\u{feff}');
+        let (description, body) = match content.split_once('\n') {
+            Some((first, rest)) if first.starts_with("# ") => {
+                (Some(first[2..].trim().to_string()), rest)
+            }
+            None if content.starts_with("# ") => (Some(content[2..].trim().to_string()), ""),
+            _ => (None, content),
+        };
+        Self {
+            name,
+            description,
+            body: body.trim().to_string(),
+        }
+    }
+
+    /// Whether the template comes from the team repository
+    pub fn is_team(&self) -> bool {
+        self.name.starts_with(&format!("{}/", TEAM))
+    }
+
+    /// The prompt for `input`
+    pub fn render(&self, input: &str) -> String {
+        let input = input.trim();
+        if self.body.contains(INPUT_PLACEHOLDER) {
+            self.body.replace(INPUT_PLACEHOLDER, input)
+        } else if input.is_empty() {
+            self.body.clone()
+        } else {
+            format!("{}\n\n{}", self.body, input)
+        }
+    }
+}
+
+/// Directory of the team repository clone under `dir`
+pub fn team_dir(dir: &Path) -> PathBuf {
+    dir.join(TEAM)
+}
+
+/// All templates under `dir`, personal ones first, each group sorted by name
+pub fn load(dir: &Path) -> Vec<Template> {
+    let mut templates: Vec<Template> = WalkDir::new(dir)
+        .follow_links(true)
+        .into_iter()
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file())
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| EXTENSIONS.contains(&ext))
+        })
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(dir).ok()?.with_extension("");
+            let name = relative.to_string_lossy().replace('\\', "/");
+            let content = fs::read_to_string(entry.path()).ok()?;
+            Some(Template::parse(name, &content))
+        })
+        .collect();
+    templates.sort_by(|a, b| (a.is_team(), &a.name).cmp(&(b.is_team(), &b.name)));
+    templates
+}
+
+/// The template called `name`; a name without namespace also finds a team template
+pub fn find<'a>(templates: &'a [Template], name: &str) -> Option<&'a Template> {
+    let name = name.trim_start_matches('/');
+    templates
+        .iter()
+        .find(|template| template.name == name)
+        .or_else(|| {
+            let team_name = format!("{}/{}", TEAM, name);
+            templates.iter().find(|template| template.name == team_name)
+        })
+}
+
+/// Shared commands of the team repository, named `team/<name>`
+pub fn team_commands(dir: &Path) -> BTreeMap<String, String> {
+    let Ok(content) = fs::read_to_string(team_dir(dir).join(COMMANDS_FILE)) else {
+        return BTreeMap::new();
+    };
+    parse_commands(&content)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(name, command)| (format!("{}/{}", TEAM, name), command))
+        .collect()
+}
+
+/// The name-to-command map of a `commands.yaml`
+pub fn parse_commands(content: &str) -> Result<BTreeMap<String, String>, String> {
+    if content.trim().is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    let commands: BTreeMap<String, String> =
+        serde_yaml::from_str(content).map_err(|e| format!("invalid {}: {}", COMMANDS_FILE, e))?;
+    Ok(commands
+        .into_iter()
+        .map(|(name, command)| (name.trim_start_matches('/').to_lowercase(), command))
+        .filter(|(name, command)| {
+            !name.is_empty() && !name.contains(char::is_whitespace) && !command.trim().is_empty()
+        })
+        .collect())
+}
+
+/// Clone the team repository into `dest` or reset it to the remote
+///
+/// Returns the short hash of the commit now checked out.
+pub async fn update_team(url: &str, branch: &str, dest: &Path) -> Result<String, String> {
+    if dest.join(".git").is_dir() {
+        git(Some(dest), &["remote", "set-url", "origin", url]).await?;
+        let refspec = if branch.is_empty() { "HEAD" } else { branch };
+        git(Some(dest), &["fetch", "--depth", "1", "origin", refspec]).await?;
+        // The namespace is read-only: whatever changed locally is dropped
+        git(Some(dest), &["reset", "--hard", "FETCH_HEAD"]).await?;
+        git(Some(dest), &["clean", "-fdx"]).await?;
+    } else {
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        if dest.exists() {
+            return Err(format!("{} exists and is not a git clone", dest.display()));
+        }
+        let dest_arg = dest.to_string_lossy();
+        let mut args = vec!["clone", "--depth", "1"];
+        if !branch.is_empty() {
+            args.extend(["--branch", branch]);
+        }
+        args.extend([url, dest_arg.as_ref()]);
+        git(None, &args).await?;
+    }
+    Ok(git(Some(dest), &["rev-parse", "--short", "HEAD"])
+        .await?
+        .trim()
+        .to_string())
+}
+
+async fn git(dir: Option<&Path>, args: &[&str]) -> Result<String, String> {
+    let mut command = Command::new("git");
+    if let Some(dir) = dir {
+        command.arg("-C").arg(dir);
+    }
+    // Never wait for credentials on the terminal the TUI is drawn on
+    let output = command
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("could not run git: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
@@ -0,0 +1,323 @@
+//! Session transcript parsing
+//!
+//! The output pane is a flat text buffer in which every command starts with
+//! a separator line followed by the echoed input (`❯` for AI prompts, `$`
+//! for bash, `/` for commands). This module splits it back into exchanges
+//! for exporting and printing.
+//!
+//! Reasoning that a model produced before its answer is kept in the buffer
+//! as lines starting with `┊`. Those lines are shown folded and are not part
+//! of an exchange's output, so they are never quoted, printed or sent back
+//! to the model as context.
+
+use crate::handlers::CommandMode;
+use crate::ui::progress;
+use std::ops::Range;
+
+/// Separator character printed before every command
+const SEPARATOR_CHAR: char = '━';
+
+/// Prefix of reasoning lines in the output
+pub const REASONING_PREFIX: char = '┊';
+
+/// Whether a line of the output belongs to a reasoning block
+pub fn is_reasoning_line(line: &str) -> bool {
+    line.starts_with(REASONING_PREFIX)
+}
+
+/// Reasoning formatted for the output buffer, one prefixed line per line
+pub fn format_reasoning(reasoning: &str) -> String {
+    reasoning
+        .trim()
+        .lines()
+        .map(|line| {
+            format!("{} {}", REASONING_PREFIX, line)
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A response without its reasoning lines
+pub fn strip_reasoning(text: &str) -> String {
+    text.lines()
+        .filter(|line| !is_reasoning_line(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_start_matches('\n')
+        .to_string()
+}
+
+/// Ranges of consecutive reasoning lines
+pub fn reasoning_blocks<S: AsRef<str>>(lines: &[S]) -> Vec<Range<usize>> {
+    let mut blocks: Vec<Range<usize>> = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        if !is_reasoning_line(line.as_ref()) {
+            continue;
+        }
+        match blocks.last_mut() {
+            Some(block) if block.end == idx => block.end = idx + 1,
+            _ => blocks.push(idx..idx + 1),
+        }
+    }
+    blocks
+}
+
+/// Whether a character is an emoji or pictograph dropped from plain text
+fn is_decoration(c: char) -> bool {
+    matches!(
+        c as u32,
+        0x1F000..=0x1FAFF // Emoji and pictographs
+            | 0x2190..=0x21FF // Arrows
+            | 0x2300..=0x23FF // Technical symbols (⏱)
+            | 0x25A0..=0x27BF // Shapes, symbols and dingbats
+            | 0x2B00..=0x2BFF // Miscellaneous symbols and arrows
+            | 0xFE00..=0xFE0F // Variation selectors
+            | 0x200D // Zero width joiner
+    )
+}
+
+/// Remove emoji from a line, along with the spacing they leave behind
+fn strip_decorations(line: &str) -> String {
+    let stripped: String = line.chars().filter(|&c| !is_decoration(c)).collect();
+    if stripped.len() == line.len() {
+        return stripped;
+    }
+    let indent = &line[..line.len() - line.trim_start().len()];
+    let words: Vec<&str> = stripped.split_whitespace().collect();
+    format!("{}{}", indent, words.join(" "))
+}
+
+/// Whether a line is the execution summary printed after a command (`[⏱️ 0.12s | ✓ | 📊 3]`)
+fn is_status_line(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with("[⏱") && line.ends_with(']')
+}
+
+/// One command and the output that followed it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exchange {
+    /// Kind of input
+    pub mode: CommandMode,
+    /// Input without the `!` or `/` prefix
+    pub input: String,
+    /// Output printed for the input
+    pub output: String,
+}
+
+impl Exchange {
+    /// The input as shown in a terminal: `❯ prompt`, `$ command` or `/command`
+    pub fn display_input(&self) -> String {
+        match self.mode {
+            CommandMode::AI => format!("❯ {}", self.input),
+            CommandMode::Bash => format!("$ {}", self.input),
+            CommandMode::Command => format!("/{}", self.input),
+        }
+    }
+
+    /// The input as typed, so it can be run again
+    pub fn command(&self) -> String {
+        match self.mode {
+            CommandMode::AI => self.input.clone(),
+            CommandMode::Bash => format!("!{}", self.input),
+            CommandMode::Command => format!("/{}", self.input),
+        }
+    }
+}
+
+/// Location of one command in the output lines
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionSpan {
+    /// Kind of input
+    pub mode: CommandMode,
+    /// Index of the separator line; the echoed input follows it
+    pub header: usize,
+    /// Lines printed for the command
+    pub body: Range<usize>,
+}
+
+impl SectionSpan {
+    /// The command and output of the section
+    pub fn exchange<S: AsRef<str>>(&self, lines: &[S]) -> Exchange {
+        let (mode, input) = lines
+            .get(self.header + 1)
+            .and_then(|line| parse_input(line.as_ref()))
+            .unwrap_or_else(|| (self.mode.clone(), String::new()));
+        let body: Vec<&str> = lines[self.body.clone()]
+            .iter()
+            .map(|line| line.as_ref())
+            .collect();
+        Exchange {
+            mode,
+            input,
+            output: clean_output(&body),
+        }
+    }
+
+    /// One-line summary shown while the section is collapsed,
+    /// e.g. `▸ $ cargo build output — 212 lines, exit 0`
+    pub fn summary<S: AsRef<str>>(&self, lines: &[S]) -> String {
+        let input = lines
+            .get(self.header + 1)
+            .map(|line| line.as_ref().trim())
+            .unwrap_or_default();
+        let body = &lines[self.body.clone()];
+        let mut summary = format!("▸ {} output — {} lines", input, body.len());
+
+        // Bash results start with `[⏱️ 0.12s | ✓ | 📊 <exit code>]`
+        let exit_code = body.iter().find_map(|line| {
+            let line = line.as_ref().trim();
+            if !is_status_line(line) {
+                return None;
+            }
+            line.trim_end_matches(']')
+                .rsplit(' ')
+                .next()?
+                .parse::<i32>()
+                .ok()
+        });
+        if let Some(code) = exit_code {
+            summary.push_str(&format!(", exit {}", code));
+        }
+        summary
+    }
+}
+
+/// Find the commands in the output lines
+pub fn section_spans<S: AsRef<str>>(lines: &[S]) -> Vec<SectionSpan> {
+    let mut spans: Vec<SectionSpan> = Vec::new();
+    for idx in 0..lines.len() {
+        let Some(next) = lines.get(idx + 1) else {
+            break;
+        };
+        if !is_separator(lines[idx].as_ref()) {
+            continue;
+        }
+        if let Some((mode, _)) = parse_input(next.as_ref()) {
+            if let Some(last) = spans.last_mut() {
+                last.body.end = idx;
+            }
+            spans.push(SectionSpan {
+                mode,
+                header: idx,
+                body: idx + 2..lines.len(),
+            });
+        }
+    }
+    spans
+}
+
+/// A parsed session
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Transcript {
+    /// Output before the first command (welcome message)
+    pub preamble: String,
+    /// Commands in the order they were entered
+    pub exchanges: Vec<Exchange>,
+}
+
+/// Whether a line is the separator printed before each command
+fn is_separator(line: &str) -> bool {
+    let line = line.trim();
+    !line.is_empty() && line.chars().all(|c| c == SEPARATOR_CHAR)
+}
+
+/// Parse the echoed input line following a separator
+fn parse_input(line: &str) -> Option<(CommandMode, String)> {
+    if let Some(input) = line.strip_prefix("❯ ") {
+        Some((CommandMode::AI, input.to_string()))
+    } else if let Some(input) = line.strip_prefix("$ ") {
+        let input = input.strip_prefix('!').unwrap_or(input);
+        Some((CommandMode::Bash, input.trim().to_string()))
+    } else {
+        let input = line.strip_prefix("/ ")?;
+        let input = input.strip_prefix('/').unwrap_or(input);
+        Some((CommandMode::Command, input.trim().to_string()))
+    }
+}
+
+/// Trim blank lines around a block of output and drop spinner frames and reasoning
+fn clean_output(lines: &[&str]) -> String {
+    let kept: Vec<&str> = lines
+        .iter()
+        .copied()
+        .filter(|line| !progress::is_indicator(line) && !is_reasoning_line(line))
+        .collect();
+    kept.join("\n").trim_matches('\n').trim_end().to_string()
+}
+
+impl Transcript {
+    /// Split the output buffer into exchanges
+    pub fn parse(output: &str) -> Self {
+        let lines: Vec<&str> = output.lines().collect();
+        let mut transcript = Transcript::default();
+        let mut current: Option<(CommandMode, String)> = None;
+        let mut block: Vec<&str> = Vec::new();
+
+        let mut idx = 0;
+        while idx < lines.len() {
+            let line = lines[idx];
+            let input = lines.get(idx + 1).and_then(|next| parse_input(next));
+            if let (true, Some(input)) = (is_separator(line), input) {
+                transcript.push(current.take(), &block);
+                block.clear();
+                current = Some(input);
+                idx += 2;
+                continue;
+            }
+            block.push(line);
+            idx += 1;
+        }
+        transcript.push(current, &block);
+        transcript
+    }
+
+    /// Add the block collected for `input`, or the preamble when there is none
+    fn push(&mut self, input: Option<(CommandMode, String)>, block: &[&str]) {
+        let output = clean_output(block);
+        match input {
+            Some((mode, input)) => self.exchanges.push(Exchange {
+                mode,
+                input,
+                output,
+            }),
+            None => self.preamble = output,
+        }
+    }
+
+    /// The last `count` exchanges
+    pub fn last(&self, count: usize) -> &[Exchange] {
+        &self.exchanges[self.exchanges.len().saturating_sub(count)..]
+    }
+}
+
+/// Render exchanges as plain text without separators, spinners or emoji
+///
+/// Each exchange is its input (`> prompt`, `$ command` or `/command`)
+/// followed by its output, with a blank line in between.
+pub fn to_plain_text(exchanges: &[Exchange]) -> String {
+    let mut sections = Vec::with_capacity(exchanges.len());
+    for exchange in exchanges {
+        let input = match exchange.mode {
+            CommandMode::AI => format!("> {}", exchange.input),
+            _ => exchange.display_input(),
+        };
+        let output: Vec<String> = exchange
+            .output
+            .lines()
+            .filter(|line| !is_status_line(line))
+            .map(strip_decorations)
+            .collect();
+        let output = output.join("\n").trim().to_string();
+
+        if output.is_empty() {
+            sections.push(input);
+        } else {
+            sections.push(format!("{}\n\n{}", input, output));
+        }
+    }
+    let mut text = sections.join("\n\n");
+    text.push('\n');
+    text
+}
@@ -0,0 +1,174 @@
+//! The file tree of a directory
+//!
+//! Directories come before files, each sorted by name, and the children of
+//! every expanded directory follow it one level deeper. The directories
+//! skipped when searching the project (`.git`, `target`, `node_modules`)
+//! are left out.
+
+use super::glob::SKIPPED_DIRS;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A file or directory in the tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeEntry {
+    /// Path relative to the root of the tree
+    pub path: PathBuf,
+    /// Number of directories between the root and the entry
+    pub depth: usize,
+    pub is_dir: bool,
+}
+
+impl TreeEntry {
+    /// Name of the file or directory
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Entries of `root` and of its expanded subdirectories, in display order
+pub fn list(root: &Path, expanded: &HashSet<PathBuf>) -> Vec<TreeEntry> {
+    let mut entries = Vec::new();
+    push_children(root, Path::new(""), 0, expanded, &mut entries);
+    entries
+}
+
+fn push_children(
+    root: &Path,
+    dir: &Path,
+    depth: usize,
+    expanded: &HashSet<PathBuf>,
+    entries: &mut Vec<TreeEntry>,
+) {
+    let Ok(read) = fs::read_dir(root.join(dir)) else {
+        return;
+    };
+    let mut children: Vec<TreeEntry> = read
+        .flatten()
+        .filter_map(|child| {
+            let is_dir = child.file_type().ok()?.is_dir();
+            let name = child.file_name().to_string_lossy().to_string();
+            if is_dir && SKIPPED_DIRS.contains(&name.as_str()) {
+                return None;
+            }
+            Some(TreeEntry {
+                path: dir.join(name),
+                depth,
+                is_dir,
+            })
+        })
+        .collect();
+    children.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.path.cmp(&b.path)));
+    for child in children {
+        let open = child.is_dir && expanded.contains(&child.path);
+        let path = child.path.clone();
+        entries.push(child);
+        if open {
+            push_children(root, &path, depth + 1, expanded, entries);
+        }
+    }
+}
+
+/// The file tree popup: a directory with some subdirectories expanded
+#[derive(Debug, Clone)]
+pub struct FileTree {
+    pub root: PathBuf,
+    /// Expanded directories, relative to the root
+    pub expanded: HashSet<PathBuf>,
+    /// Entries shown, in display order
+    pub entries: Vec<TreeEntry>,
+    /// Index of the highlighted entry
+    pub selected: usize,
+}
+
+impl FileTree {
+    /// The tree of `root` with every directory collapsed
+    pub fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            expanded: HashSet::new(),
+            entries: list(root, &HashSet::new()),
+            selected: 0,
+        }
+    }
+
+    /// The highlighted entry, none in an empty directory
+    pub fn selected_entry(&self) -> Option<&TreeEntry> {
+        self.entries.get(self.selected)
+    }
+
+    /// Move the highlight by `delta` entries
+    pub fn move_by(&mut self, delta: isize) {
+        let last = self.entries.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    /// Expand the highlighted directory, or collapse it when it is expanded
+    pub fn toggle(&mut self) {
+        let Some(entry) = self.selected_entry().filter(|entry| entry.is_dir) else {
+            return;
+        };
+        let path = entry.path.clone();
+        if !self.expanded.remove(&path) {
+            self.expanded.insert(path);
+        }
+        self.refresh();
+    }
+
+    /// Collapse the highlighted directory, or the one the entry is in
+    pub fn collapse(&mut self) {
+        let Some(entry) = self.selected_entry() else {
+            return;
+        };
+        let path = if entry.is_dir && self.expanded.contains(&entry.path) {
+            entry.path.clone()
+        } else {
+            match entry.path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                _ => return,
+            }
+        };
+        self.expanded
+            .retain(|expanded| !expanded.starts_with(&path));
+        self.refresh();
+        if let Some(idx) = self.entries.iter().position(|entry| entry.path == path) {
+            self.selected = idx;
+        }
+    }
+
+    /// Read the directories again, keeping the highlighted entry
+    pub fn refresh(&mut self) {
+        let selected = self.selected_entry().map(|entry| entry.path.clone());
+        self.entries = list(&self.root, &self.expanded);
+        self.selected = selected
+            .and_then(|path| self.entries.iter().position(|entry| entry.path == path))
+            .unwrap_or(0)
+            .min(self.entries.len().saturating_sub(1));
+    }
+
+    /// One indented line per entry, with ▸/▾ before directories
+    pub fn lines(&self) -> Vec<String> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let marker = match (entry.is_dir, self.expanded.contains(&entry.path)) {
+                    (true, true) => "▾ ",
+                    (true, false) => "▸ ",
+                    (false, _) => "  ",
+                };
+                let slash = if entry.is_dir { "/" } else { "" };
+                format!(
+                    "{}{}{}{}",
+                    "  ".repeat(entry.depth),
+                    marker,
+                    entry.name(),
+                    slash
+                )
+            })
+            .collect()
+    }
+}
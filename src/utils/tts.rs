@@ -0,0 +1,246 @@
+//! Text-to-speech for AI responses
+//!
+//! Responses are read by a speech engine on this machine (`say`, `espeak`)
+//! that gets the text on stdin, or synthesized by an OpenAI-compatible
+//! `/audio/speech` API and played with an audio player. Code blocks are left
+//! out; source code read aloud is of no use. Only one text is spoken at a
+//! time: speaking another one or stopping cuts the current one off.
+
+use crate::ai::AIError;
+use crate::config::{TtsConfig, TtsEngine};
+use crate::utils::speech::file_command_args;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// The prose of a Markdown response, as it should be read aloud
+///
+/// Fenced code blocks are dropped, inline code keeps its text, links keep
+/// their label and heading, list, quote and emphasis markers are removed.
+pub fn speakable_text(markdown: &str) -> String {
+    let mut lines = Vec::new();
+    let mut fence: Option<&str> = None;
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+            }
+            continue;
+        }
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            fence = Some(marker);
+            continue;
+        }
+
+        let mut text = trimmed.trim_start_matches(['#', '>']).trim_start();
+        for bullet in ["- ", "* ", "+ "] {
+            if let Some(rest) = text.strip_prefix(bullet) {
+                text = rest;
+            }
+        }
+        let text = strip_links(text).replace(['`', '*'], "");
+        let text = text.trim();
+        if !text.is_empty() {
+            lines.push(text.to_string());
+        }
+    }
+    lines.join("\n")
+}
+
+/// Replace `[label](url)` with `label`
+fn strip_links(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+    while let Some(open) = rest.find('[') {
+        let after = &rest[open + 1..];
+        let link = after.find("](").and_then(|close| {
+            let url = &after[close + 2..];
+            url.find(')').map(|end| (close, close + 2 + end + 1))
+        });
+        match link {
+            Some((close, end)) => {
+                result.push_str(&rest[..open]);
+                result.push_str(&after[..close]);
+                rest = &after[end..];
+            }
+            None => {
+                result.push_str(&rest[..=open]);
+                rest = after;
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// The process speaking right now
+#[derive(Default)]
+struct Playback {
+    child: Option<Child>,
+    /// Synthesized audio, removed once it has been played
+    file: Option<PathBuf>,
+    /// Bumped by every new text, so late audio of a replaced one is dropped
+    generation: u64,
+    /// Audio for the current generation is being synthesized
+    synthesizing: bool,
+}
+
+impl Playback {
+    fn stop(&mut self) -> bool {
+        let mut stopped = std::mem::take(&mut self.synthesizing);
+        if let Some(mut child) = self.child.take() {
+            stopped |= matches!(child.try_wait(), Ok(None));
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        if let Some(file) = self.file.take() {
+            let _ = std::fs::remove_file(file);
+        }
+        self.generation += 1;
+        stopped
+    }
+}
+
+impl Drop for Playback {
+    fn drop(&mut self) {
+        // Never keep talking after the app has quit
+        self.stop();
+    }
+}
+
+/// Reads texts aloud, one at a time
+#[derive(Clone, Default)]
+pub struct Speaker {
+    playback: Arc<Mutex<Playback>>,
+}
+
+impl Speaker {
+    fn lock(&self) -> MutexGuard<'_, Playback> {
+        self.playback.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether a text is being synthesized or spoken
+    pub fn is_speaking(&self) -> bool {
+        let mut playback = self.lock();
+        if playback.synthesizing {
+            return true;
+        }
+        let running = playback
+            .child
+            .as_mut()
+            .is_some_and(|child| matches!(child.try_wait(), Ok(None)));
+        if !running && playback.child.is_some() {
+            playback.stop();
+        }
+        running
+    }
+
+    /// Stop speaking; returns whether anything was being spoken
+    pub fn stop(&self) -> bool {
+        self.lock().stop()
+    }
+
+    /// Read `text` aloud with the configured engine, replacing any text being spoken
+    pub async fn speak(&self, config: &TtsConfig, text: &str) -> Result<(), AIError> {
+        match config.engine {
+            TtsEngine::System => self.speak_locally(config, text),
+            TtsEngine::OpenAI => self.speak_synthesized(config, text).await,
+        }
+    }
+
+    fn speak_locally(&self, config: &TtsConfig, text: &str) -> Result<(), AIError> {
+        let words = shell_words::split(&config.command)
+            .map_err(|e| AIError::ConfigError(format!("Invalid tts.command: {}", e)))?;
+        let Some((program, args)) = words.split_first() else {
+            return Err(AIError::ConfigError("tts.command is empty".to_string()));
+        };
+
+        let mut playback = self.lock();
+        playback.stop();
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| AIError::ConfigError(format!("Cannot run `{}`: {}", program, e)))?;
+        // Engines read as they speak, so a long text would block on the pipe
+        if let Some(mut stdin) = child.stdin.take() {
+            let text = text.to_string();
+            std::thread::spawn(move || stdin.write_all(text.as_bytes()));
+        }
+        playback.child = Some(child);
+        Ok(())
+    }
+
+    async fn speak_synthesized(&self, config: &TtsConfig, text: &str) -> Result<(), AIError> {
+        let generation = {
+            let mut playback = self.lock();
+            playback.stop();
+            playback.synthesizing = true;
+            playback.generation
+        };
+        let audio = synthesize(config, text).await;
+
+        let mut playback = self.lock();
+        if playback.generation != generation {
+            // Stopped or replaced while the audio was on its way
+            return Ok(());
+        }
+        playback.synthesizing = false;
+        let path = std::env::temp_dir().join(format!("ai-coder-{}.wav", uuid::Uuid::new_v4()));
+        std::fs::write(&path, audio?)
+            .map_err(|e| AIError::ConfigError(format!("Cannot write the audio: {}", e)))?;
+        playback.file = Some(path.clone());
+
+        let args = file_command_args("tts.player_command", &config.player_command, &path)
+            .map_err(AIError::ConfigError)?;
+        let child = Command::new(&args[0])
+            .args(&args[1..])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| AIError::ConfigError(format!("Cannot run `{}`: {}", args[0], e)))?;
+        playback.child = Some(child);
+        Ok(())
+    }
+}
+
+/// Synthesize `text` as WAV audio with the speech API
+pub async fn synthesize(config: &TtsConfig, text: &str) -> Result<Vec<u8>, AIError> {
+    let response = reqwest::Client::new()
+        .post(format!(
+            "{}/audio/speech",
+            config.endpoint.trim_end_matches('/')
+        ))
+        .bearer_auth(&config.api_key)
+        .json(&serde_json::json!({
+            "model": config.model,
+            "voice": config.voice,
+            "input": text,
+            "response_format": "wav",
+        }))
+        .timeout(Duration::from_secs(120))
+        .send()
+        .await
+        .map_err(|e| AIError::NetworkError(format!("Speech request failed: {}", e)))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(match status {
+            reqwest::StatusCode::UNAUTHORIZED => {
+                AIError::Authentication("The speech API rejected the key".to_string())
+            }
+            _ => AIError::APIError(format!("Speech API returned {}: {}", status, body.trim())),
+        });
+    }
+    let audio = response
+        .bytes()
+        .await
+        .map_err(|e| AIError::NetworkError(format!("Speech download failed: {}", e)))?;
+    Ok(audio.to_vec())
+}
@@ -0,0 +1,177 @@
+//! Undo history for file modifications
+//!
+//! Before a feature writes files it records an undo point: the original
+//! contents of every file about to change are copied into
+//! `~/.ai-coder/undo/<session>/<n>/` together with a small manifest.
+//! Undoing restores those copies and deletes files that did not exist.
+//!
+//! The directory is the session's journal: reopening a session reads its
+//! points back, so changes made before a restart can still be undone.
+
+use crate::utils::tasks::TaskId;
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// A file captured by an undo point
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoFile {
+    /// Absolute path of the file
+    pub path: PathBuf,
+    /// Whether the file existed before the change
+    pub existed: bool,
+}
+
+/// A set of file changes that can be reverted together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoPoint {
+    /// Sequence number within the session
+    pub id: usize,
+    /// Description of the change
+    pub label: String,
+    /// When the change was made
+    pub timestamp: DateTime<Local>,
+    /// Files changed
+    pub files: Vec<UndoFile>,
+    /// Task that tracked the change in this run
+    #[serde(skip)]
+    pub task: Option<TaskId>,
+}
+
+/// Stack of undo points for one session
+#[derive(Debug, Clone)]
+pub struct UndoStore {
+    dir: PathBuf,
+    points: Vec<UndoPoint>,
+    next_id: usize,
+}
+
+impl UndoStore {
+    /// Create the store for a session, stored under `base/<session>`
+    pub fn new(base: &Path, session: &str) -> Self {
+        Self {
+            dir: base.join(session),
+            points: Vec::new(),
+            next_id: 1,
+        }
+    }
+
+    /// Open the journal of a session, with the points recorded earlier
+    ///
+    /// Points whose manifest cannot be read are skipped.
+    pub fn open(base: &Path, session: &str) -> Self {
+        let mut store = Self::new(base, session);
+        let Ok(entries) = fs::read_dir(&store.dir) else {
+            return store;
+        };
+        store.points = entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| fs::read_to_string(entry.path().join("manifest.json")).ok())
+            .filter_map(|manifest| serde_json::from_str::<UndoPoint>(&manifest).ok())
+            .collect();
+        store.points.sort_by_key(|point| point.id);
+        store.next_id = store.points.last().map_or(1, |point| point.id + 1);
+        store
+    }
+
+    /// Move the journal to another session, as when the session is renamed
+    pub fn rename(&mut self, session: &str) -> io::Result<()> {
+        let Some(base) = self.dir.parent() else {
+            return Ok(());
+        };
+        let dir = base.join(session);
+        if self.dir.exists() {
+            fs::rename(&self.dir, &dir)?;
+        }
+        self.dir = dir;
+        Ok(())
+    }
+
+    /// Tie the most recent point to the task that tracked it
+    pub fn attach_task(&mut self, task: TaskId) {
+        if let Some(point) = self.points.last_mut() {
+            point.task = Some(task);
+        }
+    }
+
+    /// Undo points, oldest first
+    pub fn points(&self) -> &[UndoPoint] {
+        &self.points
+    }
+
+    /// Snapshot the given files before they are modified, returning the id
+    /// of the new point
+    pub fn record(&mut self, label: &str, paths: &[PathBuf]) -> io::Result<usize> {
+        let id = self.next_id;
+        let point_dir = self.dir.join(id.to_string());
+        fs::create_dir_all(&point_dir)?;
+
+        let mut files = Vec::with_capacity(paths.len());
+        for (idx, path) in paths.iter().enumerate() {
+            let existed = path.is_file();
+            if existed {
+                fs::copy(path, point_dir.join(idx.to_string()))?;
+            }
+            files.push(UndoFile {
+                path: path.clone(),
+                existed,
+            });
+        }
+
+        let point = UndoPoint {
+            id,
+            label: label.to_string(),
+            timestamp: Local::now(),
+            files,
+            task: None,
+        };
+        let manifest = serde_json::to_string_pretty(&point).map_err(io::Error::other)?;
+        fs::write(point_dir.join("manifest.json"), manifest)?;
+
+        self.next_id += 1;
+        self.points.push(point);
+        Ok(id)
+    }
+
+    /// Drop the most recent undo point without restoring it
+    ///
+    /// Used when the change it was recorded for did not happen.
+    pub fn discard_last(&mut self) {
+        if let Some(point) = self.points.pop() {
+            let _ = fs::remove_dir_all(self.dir.join(point.id.to_string()));
+        }
+    }
+
+    /// Revert the most recent `count` undo points, newest first
+    ///
+    /// Returns the reverted points. Stops at the first point that fails to
+    /// restore, leaving it and older points on the stack.
+    pub fn undo(&mut self, count: usize) -> io::Result<Vec<UndoPoint>> {
+        let mut reverted = Vec::new();
+
+        for _ in 0..count {
+            let Some(point) = self.points.last() else {
+                break;
+            };
+            let point_dir = self.dir.join(point.id.to_string());
+
+            for (idx, file) in point.files.iter().enumerate() {
+                if file.existed {
+                    if let Some(parent) = file.path.parent() {
+                        fs::create_dir_all(parent)?;
+                    }
+                    fs::copy(point_dir.join(idx.to_string()), &file.path)?;
+                } else if file.path.exists() {
+                    fs::remove_file(&file.path)?;
+                }
+            }
+
+            let _ = fs::remove_dir_all(&point_dir);
+            reverted.extend(self.points.pop());
+        }
+
+        Ok(reverted)
+    }
+}
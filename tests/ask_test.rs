@@ -0,0 +1,17 @@
+use ai_coder_interface_rs::app::SelectionPrompt;
+
+#[test]
+fn test_compose_quotes_the_selection_after_the_question() {
+    let mut prompt = SelectionPrompt::new("error[E0308]: mismatched types\n  --> src/main.rs:3:5");
+    prompt.question = "why does this fail?".to_string();
+    assert_eq!(
+        prompt.compose(),
+        "why does this fail?\n\n> error[E0308]: mismatched types\n>   --> src/main.rs:3:5"
+    );
+}
+
+#[test]
+fn test_empty_question_asks_for_an_explanation() {
+    let prompt = SelectionPrompt::new("let x = 1;");
+    assert_eq!(prompt.compose(), "Explain this\n\n> let x = 1;");
+}
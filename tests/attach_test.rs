@@ -0,0 +1,140 @@
+use ai_coder_interface_rs::handlers::attach::{
+    self, AttachRequest, Attachment, Reference, Source, fit_references, merge_groups,
+    reference_paths, split_chunks,
+};
+use ai_coder_interface_rs::utils::count_tokens;
+
+#[test]
+fn test_parses_files_commands_and_raw() {
+    assert_eq!(
+        attach::parse_args("src/main.rs").unwrap(),
+        AttachRequest {
+            source: Source::File("src/main.rs".to_string()),
+            raw: false,
+        }
+    );
+    assert_eq!(
+        attach::parse_args("--raw !cargo test").unwrap(),
+        AttachRequest {
+            source: Source::Command("cargo test".to_string()),
+            raw: true,
+        }
+    );
+    assert!(attach::parse_args("").is_err());
+    assert!(attach::parse_args("!").is_err());
+    assert!(attach::parse_args("--raw").is_err());
+}
+
+#[test]
+fn test_chunks_stay_within_budget_and_keep_all_text() {
+    let text: String = (0..200)
+        .map(|i| format!("line {} with a few more words\n", i))
+        .collect();
+    let chunks = split_chunks(&text, 100);
+
+    assert!(chunks.len() > 1);
+    assert!(chunks.iter().all(|chunk| count_tokens(chunk) <= 100));
+    assert!(chunks.iter().all(|chunk| chunk.ends_with('\n')));
+    assert_eq!(chunks.concat(), text);
+
+    // A single long line is split between words
+    let long_line = "word ".repeat(250);
+    let chunks = split_chunks(&long_line, 100);
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(chunks.concat(), long_line);
+}
+
+#[test]
+fn test_merge_groups_always_shrink() {
+    let summaries: Vec<String> = (0..5).map(|_| "word ".repeat(40)).collect();
+
+    // Two summaries fit the budget
+    let groups = merge_groups(summaries.clone(), 80);
+    assert_eq!(groups.iter().map(Vec::len).collect::<Vec<_>>(), vec![2, 3]);
+
+    // Summaries larger than the budget are still merged in pairs
+    let groups = merge_groups(summaries, 10);
+    assert!(groups.len() < 5);
+    assert!(groups.iter().all(|group| group.len() >= 2));
+}
+
+#[test]
+fn test_context_lists_every_attachment() {
+    assert_eq!(attach::context(&[]), None);
+    let context = attach::context(&[
+        Attachment::new("notes.txt", "remember this\n"),
+        Attachment::new("$ date", "Mon"),
+    ])
+    .unwrap();
+    assert!(context.contains("### notes.txt\n```\nremember this\n```"));
+    assert!(context.contains("### $ date\n```\nMon\n```"));
+}
+
+#[test]
+fn test_prompts_name_the_part() {
+    let prompt = attach::chunk_prompt("big.log", 1, 4, "ERROR boom\n");
+    assert!(prompt.contains("part 2 of 4 of `big.log`"));
+    assert!(prompt.contains("ERROR boom"));
+
+    let prompt = attach::merge_prompt("big.log", &["first".to_string(), "second".to_string()]);
+    assert!(prompt.contains("### Part 1\nfirst"));
+    assert!(prompt.contains("### Part 2\nsecond"));
+}
+
+#[test]
+fn test_references_are_words_starting_with_at() {
+    assert_eq!(
+        reference_paths("explain @src/app.rs, then compare it with (@src/main.rs) and @src/app.rs"),
+        vec!["src/app.rs", "src/main.rs"]
+    );
+    assert!(reference_paths("mail me@example.com or write @ alone").is_empty());
+}
+
+#[test]
+fn test_references_share_the_budget() {
+    let small = "fn main() {}\n".to_string();
+    let large: String = (0..100)
+        .map(|i| format!("line {} of the file\n", i))
+        .collect();
+    let references = fit_references(
+        vec![
+            ("src/main.rs".to_string(), Ok(small.clone())),
+            ("target".to_string(), Err("it is a directory".to_string())),
+            ("big.rs".to_string(), Ok(large.clone())),
+            ("after.rs".to_string(), Ok(small.clone())),
+        ],
+        100,
+    );
+
+    assert_eq!(
+        references[0],
+        Reference::Attached(Attachment::new("src/main.rs", small))
+    );
+    assert_eq!(
+        references[1].describe(),
+        "target skipped: it is a directory"
+    );
+    let Reference::Truncated(head, total) = &references[2] else {
+        panic!("expected a truncated file, got {:?}", references[2]);
+    };
+    assert_eq!(*total, count_tokens(&large));
+    assert!(large.starts_with(&head.content));
+    assert!(count_tokens(&head.content) + count_tokens("fn main() {}\n") <= 100);
+    assert!(references[2].describe().starts_with("big.rs (first ~"));
+    assert!(references[3].attachment().is_none());
+}
+
+#[test]
+fn test_only_existing_files_are_references() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("notes.md"), "remember this\n").unwrap();
+    assert_eq!(attach::read_reference(dir.path(), "missing.md", 1024), None);
+    assert_eq!(
+        attach::read_reference(dir.path(), "notes.md", 1024),
+        Some(Ok("remember this\n".to_string()))
+    );
+    assert!(matches!(
+        attach::read_reference(dir.path(), "notes.md", 4),
+        Some(Err(_))
+    ));
+}
@@ -0,0 +1,77 @@
+use ai_coder_interface_rs::ai::azure::{DEFAULT_API_VERSION, deployment_url, endpoint};
+use ai_coder_interface_rs::ai::pricing::model_costs;
+use ai_coder_interface_rs::ai::{AIClientFactory, Provider};
+use ai_coder_interface_rs::config::{AIConfig, AzureOpenAIConfig};
+
+fn settings(resource: &str, endpoint: &str) -> AzureOpenAIConfig {
+    AzureOpenAIConfig {
+        resource: resource.to_string(),
+        endpoint: endpoint.to_string(),
+        api_key: "key".to_string(),
+        ..AzureOpenAIConfig::default()
+    }
+}
+
+#[test]
+fn test_deployments_are_served_from_the_resource() {
+    let settings = settings("my-team-openai", "");
+    assert_eq!(
+        endpoint(&settings).unwrap(),
+        "https://my-team-openai.openai.azure.com"
+    );
+    assert_eq!(
+        deployment_url(&settings, "gpt-4o").unwrap(),
+        "https://my-team-openai.openai.azure.com/openai/deployments/gpt-4o"
+    );
+    assert!(deployment_url(&settings, " ").is_err());
+}
+
+#[test]
+fn test_a_configured_endpoint_replaces_the_resource() {
+    let gateway = settings("ignored", "https://gateway.example.com/azure/");
+    assert_eq!(
+        deployment_url(&gateway, "chat").unwrap(),
+        "https://gateway.example.com/azure/openai/deployments/chat"
+    );
+    assert!(endpoint(&settings("", "")).is_err());
+}
+
+#[test]
+fn test_azure_is_configured_like_the_other_providers() {
+    let defaults = AzureOpenAIConfig::default();
+    assert_eq!(defaults.api_version, DEFAULT_API_VERSION);
+    assert_eq!(defaults.models[0].name, "gpt-4o");
+
+    let config: AIConfig = serde_yaml::from_str(
+        "active_provider: AzureOpenAI
+ollama: { endpoint: 'http://localhost:11434', models: [{ name: llama3, temperature: 0.1, max_tokens: 100 }], current_model_index: 0 }
+openai: { endpoint: '', api_key: '', models: [], current_model_index: 0 }
+anthropic: { endpoint: '', api_key: '', models: [], current_model_index: 0 }
+lmstudio: { endpoint: '', models: [], current_model_index: 0 }
+azure:
+  resource: contoso
+  api_key: secret
+  api_version: 2025-01-01-preview
+  models: [{ name: chat-prod, temperature: 0.2, max_tokens: 2048 }]
+  current_model_index: 0
+",
+    )
+    .unwrap();
+    assert_eq!(config.get_active_model_config().name, "chat-prod");
+    assert_eq!(
+        config.get_active_endpoint(),
+        "https://contoso.openai.azure.com"
+    );
+    assert_eq!(config.get_active_api_key().as_deref(), Some("secret"));
+    assert!(AIClientFactory::create_client_from_config(&config).is_ok());
+}
+
+#[test]
+fn test_providers_parse_by_name() {
+    assert_eq!("azure".parse::<Provider>(), Ok(Provider::AzureOpenAI));
+    assert_eq!("AzureOpenAI".parse::<Provider>(), Ok(Provider::AzureOpenAI));
+    assert_eq!(Provider::AzureOpenAI.to_string(), "Azure OpenAI");
+    assert!(!Provider::AzureOpenAI.is_local());
+    // Deployments named after their model are priced like it
+    assert!(model_costs(Provider::AzureOpenAI, "gpt-4o").prompt_cost_per_1k > 0.0);
+}
@@ -0,0 +1,96 @@
+use ai_coder_interface_rs::app::BashQueue;
+use ai_coder_interface_rs::handlers::bash::extract_bash_blocks;
+use ai_coder_interface_rs::utils::audit::{self, AuditEntry};
+use chrono::Local;
+
+const RESPONSE: &str = "First install:\n\
+    ```bash\nnpm install\n```\n\
+    Then build:\n\
+    ```bash\nnpm run build\nnpm test\n```\n\
+    ```rust\nfn main() {}\n```\n\
+    ```bash\n\n```\n\
+    ```bash\nnpm start\n```\n";
+
+#[test]
+fn test_bash_blocks_are_extracted_in_order() {
+    assert_eq!(
+        extract_bash_blocks(RESPONSE),
+        vec!["npm install", "npm run build\nnpm test", "npm start"]
+    );
+    assert!(extract_bash_blocks("no code here").is_empty());
+}
+
+#[test]
+fn test_blocks_are_checked_and_reordered() {
+    let mut queue = BashQueue::new(extract_bash_blocks(RESPONSE));
+    assert_eq!(
+        queue.lines(),
+        vec![
+            "[x] 1. npm install",
+            "[x] 2. npm run build (+1 line(s))",
+            "[x] 3. npm start",
+        ]
+    );
+
+    // Run the build last and skip the install
+    queue.selected = 1;
+    queue.move_down();
+    assert_eq!(queue.selected, 2);
+    queue.move_down();
+    assert_eq!(queue.selected, 2);
+    queue.selected = 0;
+    queue.toggle();
+    assert_eq!(
+        queue.checked(),
+        vec!["npm start", "npm run build\nnpm test"]
+    );
+
+    queue.toggle_all();
+    assert_eq!(queue.checked().len(), 3);
+    queue.toggle_all();
+    assert!(queue.checked().is_empty());
+
+    queue.move_up();
+    assert_eq!(queue.selected, 0);
+}
+
+#[test]
+fn test_edited_blocks_leave_the_rest_queued() {
+    let mut queue = BashQueue::new(extract_bash_blocks(RESPONSE)).with_source(4, "build it");
+    queue.selected = 2;
+    let block = queue.take_selected().unwrap();
+    assert_eq!(block.command, "npm start");
+    assert_eq!(queue.selected, 1);
+    assert_eq!(queue.blocks.len(), 2);
+    assert_eq!((queue.response, queue.prompt.as_str()), (4, "build it"));
+
+    let mut empty = BashQueue::new(Vec::new());
+    assert!(empty.take_selected().is_none());
+}
+
+#[test]
+fn test_audit_entries_are_appended_and_read_back() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    assert!(audit::load(&path).unwrap().is_empty());
+
+    let entry = AuditEntry {
+        timestamp: Local::now(),
+        session_id: "20240501-090000".to_string(),
+        response: 3,
+        prompt: "set up the project".to_string(),
+        original: "npm install".to_string(),
+        command: "npm ci".to_string(),
+    };
+    audit::append(&path, &entry).unwrap();
+    let unchanged = AuditEntry {
+        command: "npm install ".to_string(),
+        ..entry.clone()
+    };
+    audit::append(&path, &unchanged).unwrap();
+
+    let entries = audit::load(&path).unwrap();
+    assert_eq!(entries, vec![entry, unchanged]);
+    assert!(entries[0].edited());
+    assert!(!entries[1].edited());
+}
@@ -0,0 +1,116 @@
+use ai_coder_interface_rs::handlers::bash::run_streaming;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_output_arrives_line_by_line() {
+    let mut lines = Vec::new();
+    let exit = run_streaming(
+        "printf 'one\\ntwo\\n*'",
+        Path::new("."),
+        Arc::default(),
+        |line| lines.push(line),
+    )
+    .await
+    .unwrap();
+    assert_eq!(lines, vec!["one", "two", "*"]);
+    assert_eq!(exit.code, 0);
+    assert!(exit.printed);
+    assert!(exit.summary().starts_with("[⏱️ "));
+    assert!(exit.summary().ends_with("| ✓ | 📊 0]\n"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stderr_lines_are_marked_and_failures_reported() {
+    let mut lines = Vec::new();
+    let exit = run_streaming("ls /no/such/dir?", Path::new("."), Arc::default(), |line| {
+        lines.push(line)
+    })
+    .await
+    .unwrap();
+    assert_ne!(exit.code, 0);
+    assert!(lines.iter().all(|line| line.starts_with("⚠️ ")));
+    assert!(exit.summary().contains('✗'));
+
+    let quiet = run_streaming("true", Path::new("."), Arc::default(), |_| {})
+        .await
+        .unwrap();
+    assert!(quiet.summary().ends_with("(no output)\n"));
+    assert!(
+        run_streaming("rm -rf /", Path::new("."), Arc::default(), |_| {})
+            .await
+            .is_err()
+    );
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_aborting_kills_the_command() {
+    let abort = Arc::new(AtomicBool::new(false));
+    let flag = abort.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        flag.store(true, Ordering::SeqCst);
+    });
+    let start = Instant::now();
+    let error = run_streaming("sleep 30", Path::new("."), abort, |_| {})
+        .await
+        .unwrap_err();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert!(error.to_string().contains("Killed after"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_output_that_is_not_utf8_is_kept() {
+    let mut lines = Vec::new();
+    let exit = run_streaming(
+        "printf 'a\\377b\\nafter\\n'",
+        Path::new("."),
+        Arc::default(),
+        |line| lines.push(line),
+    )
+    .await
+    .unwrap();
+    assert_eq!(lines, vec!["a\u{FFFD}b", "after"]);
+    assert_eq!(exit.code, 0);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_aborting_kills_what_the_command_started() {
+    let abort = Arc::new(AtomicBool::new(false));
+    let flag = abort.clone();
+    let mut lines = Vec::new();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        flag.store(true, Ordering::SeqCst);
+    });
+    run_streaming(
+        "sh -c 'sleep 37 & echo $!; wait'",
+        Path::new("."),
+        abort,
+        |line| lines.push(line),
+    )
+    .await
+    .unwrap_err();
+
+    // The background sleep is gone once it has been reaped
+    let pid = lines[0].clone();
+    let start = Instant::now();
+    let alive = || {
+        std::process::Command::new("kill")
+            .args(["-0", &pid])
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    };
+    while alive() && start.elapsed() < Duration::from_secs(5) {
+        std::thread::sleep(Duration::from_millis(50));
+    }
+    assert!(!alive());
+}
@@ -0,0 +1,79 @@
+use ai_coder_interface_rs::config::AppConfig;
+use ai_coder_interface_rs::utils::bundle::{self, BundleError};
+use ai_coder_interface_rs::utils::crypto::Cipher;
+use chrono::Local;
+
+fn team_config() -> AppConfig {
+    let mut config = AppConfig::default();
+    config.ai.openai.api_key = "sk-team".to_string();
+    config.sync.password = "webdav-secret".to_string();
+    config.sync.url = "https://dav.example.com".to_string();
+    config
+        .aliases
+        .insert("t".to_string(), "!cargo test".to_string());
+    config.theme.primary = "#FF0000".to_string();
+    config
+}
+
+#[test]
+fn test_exported_bundles_hold_no_secrets() {
+    let bundle = bundle::export(&team_config(), None, None, Local::now()).unwrap();
+    let json = serde_json::to_string(&bundle).unwrap();
+    assert!(!json.contains("sk-team"));
+    assert!(!json.contains("webdav-secret"));
+    assert!(!json.contains("dav.example.com"));
+    assert!(json.contains("!cargo test"));
+    assert!(bundle.keys.is_none());
+}
+
+#[test]
+fn test_importing_keeps_local_secrets_and_sync() {
+    let bundle = bundle::export(&team_config(), None, None, Local::now()).unwrap();
+    let data = serde_json::to_vec(&bundle).unwrap();
+
+    let mut local = AppConfig::default();
+    local.ai.openai.api_key = "sk-mine".to_string();
+    local.sync.url = "https://mine.example.com".to_string();
+    let applied = bundle::apply(&bundle::parse(&data).unwrap(), &local, None).unwrap();
+
+    assert_eq!(applied.config.theme.primary, "#FF0000");
+    assert_eq!(applied.config.aliases["t"], "!cargo test");
+    assert_eq!(applied.config.ai.openai.api_key, "sk-mine");
+    assert_eq!(applied.config.sync.url, "https://mine.example.com");
+    assert_eq!(applied.imported_keys, 0);
+    assert!(!applied.locked_keys);
+}
+
+#[test]
+fn test_keys_travel_encrypted_with_the_passphrase() {
+    let cipher = Cipher::new("team passphrase").unwrap();
+    let bundle = bundle::export(&team_config(), None, Some(&cipher), Local::now()).unwrap();
+    assert!(!serde_json::to_string(&bundle).unwrap().contains("sk-team"));
+
+    let locked = bundle::apply(&bundle, &AppConfig::default(), None).unwrap();
+    assert!(locked.locked_keys);
+    assert_eq!(locked.config.ai.openai.api_key, "");
+
+    let opened = bundle::apply(&bundle, &AppConfig::default(), Some(&cipher)).unwrap();
+    assert_eq!(opened.config.ai.openai.api_key, "sk-team");
+    // The sync password stays behind even when keys are bundled
+    assert_eq!(opened.imported_keys, 1);
+
+    let wrong = Cipher::new("guess").unwrap();
+    assert!(matches!(
+        bundle::apply(&bundle, &AppConfig::default(), Some(&wrong)),
+        Err(BundleError::Crypto(_))
+    ));
+}
+
+#[test]
+fn test_other_files_are_rejected() {
+    assert!(matches!(
+        bundle::parse(b"{\"theme\": {}}"),
+        Err(BundleError::Format(_))
+    ));
+    let mut bundle = bundle::export(&AppConfig::default(), None, None, Local::now()).unwrap();
+    bundle.version = bundle::VERSION + 1;
+    let data = serde_json::to_vec(&bundle).unwrap();
+    assert!(matches!(bundle::parse(&data), Err(BundleError::Version(_))));
+}
@@ -0,0 +1,53 @@
+use ai_coder_interface_rs::app::split_chain;
+use std::collections::BTreeMap;
+
+#[test]
+fn test_split_chain() {
+    let aliases = BTreeMap::new();
+    assert_eq!(
+        split_chain("/config provider openai && explain this error", &aliases),
+        vec!["/config provider openai", "explain this error"]
+    );
+    assert_eq!(
+        split_chain("!cargo build && cargo test", &aliases),
+        vec!["!cargo build && cargo test"]
+    );
+    assert_eq!(
+        split_chain("!cargo build && !cargo test", &aliases),
+        vec!["!cargo build", "!cargo test"]
+    );
+    assert_eq!(
+        split_chain("what does a && b do?", &aliases),
+        vec!["what does a && b do?"]
+    );
+    assert_eq!(
+        split_chain("/alias add ok \"!make && make install\"", &aliases),
+        vec!["/alias add ok \"!make && make install\""]
+    );
+
+    // Lines starting with / or ! start a new command; other lines join the prompt
+    let input = "/filter ai\n!git status\nreview this:\n// not a command\n```\n/usr/bin/env\n```";
+    assert_eq!(
+        split_chain(input, &aliases),
+        vec![
+            "/filter ai",
+            "!git status",
+            "review this:\n// not a command\n```\n/usr/bin/env\n```",
+        ]
+    );
+
+    // Pasted paths are not commands, and neither are unknown names
+    let input = "why does this fail?\n/usr/bin/ld: cannot find -lxcb\n/tmp/build.log";
+    assert_eq!(split_chain(input, &aliases), vec![input]);
+    assert_eq!(
+        split_chain("explain && /usr/bin/ld", &aliases),
+        vec!["explain && /usr/bin/ld"]
+    );
+
+    // Aliases start a command like the built-in ones
+    let aliases = BTreeMap::from([("gs".to_string(), "!git status".to_string())]);
+    assert_eq!(
+        split_chain("/gs\nsummarize the changes", &aliases),
+        vec!["/gs", "summarize the changes"]
+    );
+}
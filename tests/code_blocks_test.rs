@@ -0,0 +1,64 @@
+use ai_coder_interface_rs::config::{CodeExecutionConfig, InterpreterConfig};
+use ai_coder_interface_rs::handlers::code::{
+    CodeBlock, extract_code_blocks_with, handle_code_block, run_with,
+};
+use std::fs;
+
+#[test]
+fn test_language_tags_map_to_interpreters() {
+    assert_eq!(CodeExecutionConfig::canonical_language("py"), "python");
+    assert_eq!(
+        CodeExecutionConfig::canonical_language("JavaScript"),
+        "node"
+    );
+    assert_eq!(CodeExecutionConfig::canonical_language("sqlite3"), "sql");
+    assert_eq!(CodeExecutionConfig::canonical_language("ruby"), "ruby");
+
+    let config = CodeExecutionConfig::default();
+    assert!(config.interpreters.contains_key("python"));
+    // Interpreters stay off until enabled
+    assert!(config.enabled_interpreter("python").is_none());
+    assert!(config.enabled_interpreter("ruby").is_none());
+}
+
+#[test]
+fn test_disabled_languages_are_not_run() {
+    let error = handle_code_block("ruby", "puts 1", &std::env::current_dir().unwrap())
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("/config exec ruby on"));
+}
+
+#[test]
+fn test_only_blocks_of_enabled_interpreters_are_offered() {
+    let mut config = CodeExecutionConfig::default();
+    config.interpreters.get_mut("python").unwrap().enabled = true;
+    let answer = "```py\nprint(1)\n```\n```bash\nls\n```\n```node\nconsole.log(1)\n```\n```python\n\n```";
+
+    // Bash runs inline, node is disabled and empty blocks are skipped
+    assert_eq!(
+        extract_code_blocks_with(&config, answer),
+        vec![CodeBlock {
+            language: "py".to_string(),
+            code: "print(1)".to_string(),
+        }]
+    );
+    assert!(extract_code_blocks_with(&CodeExecutionConfig::default(), answer).is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_blocks_are_fed_to_the_interpreter_in_the_working_dir() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(dir.path().join("marker.txt"), "found").unwrap();
+    let shell = InterpreterConfig {
+        enabled: true,
+        command: "sh".to_string(),
+        args: Vec::new(),
+    };
+
+    let output = run_with(&shell, "cat marker.txt", dir.path()).unwrap();
+    assert!(output.contains("| ✓ | 📊 0]"), "{}", output);
+    assert!(output.contains("found"));
+    assert!(run_with(&shell, "  \n", dir.path()).is_err());
+}
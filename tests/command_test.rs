@@ -0,0 +1,43 @@
+use ai_coder_interface_rs::handlers::command::CommandHandler;
+use std::collections::BTreeMap;
+
+#[test]
+fn test_command_suggestions() {
+    assert_eq!(CommandHandler::suggest("confg"), vec!["config"]);
+    assert_eq!(CommandHandler::suggest("hlep"), vec!["help"]);
+    assert_eq!(CommandHandler::suggest("undp")[0], "undo");
+    assert!(CommandHandler::suggest("frobnicate").is_empty());
+
+    assert_eq!(
+        CommandHandler::correct("confg provider openai").as_deref(),
+        Some("config provider openai")
+    );
+    assert_eq!(CommandHandler::correct("config provider openai"), None);
+
+    let error = CommandHandler::handle_command("confg")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("Unknown command /confg — did you mean /config?"));
+}
+
+#[test]
+fn test_expand_alias() {
+    let aliases = BTreeMap::from([
+        ("gs".to_string(), "!git status".to_string()),
+        ("ex".to_string(), "/export html".to_string()),
+    ]);
+    assert_eq!(
+        CommandHandler::expand_alias("/gs", &aliases).as_deref(),
+        Some("!git status")
+    );
+    assert_eq!(
+        CommandHandler::expand_alias("/gs --short", &aliases).as_deref(),
+        Some("!git status --short")
+    );
+    assert_eq!(
+        CommandHandler::expand_alias("/ex out.html", &aliases).as_deref(),
+        Some("/export html out.html")
+    );
+    assert_eq!(CommandHandler::expand_alias("gs", &aliases), None);
+    assert_eq!(CommandHandler::expand_alias("/help", &aliases), None);
+}
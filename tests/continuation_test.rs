@@ -0,0 +1,144 @@
+use ai_coder_interface_rs::ai::continuation::{
+    AnswerUsage, Incomplete, continuation_prompt, detect, join_into, new_text, raised_max_tokens,
+    stitch,
+};
+use ai_coder_interface_rs::ai::conversation::Conversation;
+use ai_coder_interface_rs::ai::openai_compat::parse_stream_line;
+use ai_coder_interface_rs::ai::{AIResponse, TokenUsage};
+use ai_coder_interface_rs::app::{AIPurpose, PendingPrompt};
+
+fn response(content: &str, truncated: bool) -> AIResponse {
+    AIResponse {
+        content: content.to_string(),
+        model: "test".to_string(),
+        usage: TokenUsage {
+            prompt_tokens: 10,
+            completion_tokens: 4096,
+            total_tokens: 4106,
+        },
+        progress: None,
+        cost: None,
+        reasoning: None,
+        truncated,
+    }
+}
+
+#[test]
+fn test_empty_and_cut_off_responses_are_detected() {
+    assert_eq!(detect(&response("All done.", false)), None);
+    assert_eq!(detect(&response(" \n", false)), Some(Incomplete::Empty));
+    // Nothing to continue when the whole budget went to reasoning
+    assert_eq!(detect(&response("", true)), Some(Incomplete::Empty));
+    assert_eq!(
+        detect(&response("fn main() {\n    println!(", true)),
+        Some(Incomplete::Truncated {
+            partial: "fn main() {\n    println!(".to_string(),
+            tokens: 4096,
+        })
+    );
+}
+
+#[test]
+fn test_finish_reasons_are_read_from_the_stream() {
+    let last =
+        parse_stream_line(r#"data: {"choices":[{"index":0,"delta":{},"finish_reason":"length"}]}"#)
+            .unwrap();
+    assert_eq!(last.finish_reason.as_deref(), Some("length"));
+
+    let chunk =
+        parse_stream_line(r#"data: {"choices":[{"index":0,"delta":{"content":"Hi"}}]}"#).unwrap();
+    assert_eq!(chunk.finish_reason, None);
+}
+
+#[test]
+fn test_repeated_text_is_left_out_of_continuations() {
+    let partial = "The steps are:\n1. Build the crate\n2. Run the tes";
+    assert_eq!(new_text(partial, "ts\n3. Ship it"), "ts\n3. Ship it");
+    assert_eq!(
+        new_text(partial, "2. Run the tests\n3. Ship it"),
+        "ts\n3. Ship it"
+    );
+    // A few matching characters are not taken for a repeat
+    assert_eq!(new_text("Run the", "e end"), "e end");
+    assert_eq!(
+        stitch(partial, "2. Run the tests\n3. Ship it"),
+        "The steps are:\n1. Build the crate\n2. Run the tests\n3. Ship it"
+    );
+}
+
+#[test]
+fn test_continuations_are_joined_into_the_message() {
+    let partial = "Steps:\n1. Build\n2. Run the tes";
+    let message = "Steps:\n1. Build\n2. Run the tes\n↪️ Continuing the response";
+    assert_eq!(
+        join_into(message, partial, "ts\n3. Ship").as_deref(),
+        Some("Steps:\n1. Build\n2. Run the tests\n3. Ship\n↪️ Continuing the response")
+    );
+    // A cut at the end of a line keeps the line break
+    assert_eq!(
+        join_into("1. Build\n", "1. Build\n", "2. Test").as_deref(),
+        Some("1. Build\n2. Test\n")
+    );
+    assert_eq!(join_into("Something else", partial, "ts"), None);
+
+    let mut conversation = Conversation::new();
+    conversation.record("List the steps", partial);
+    conversation.record("Thanks", "You're welcome");
+    assert!(conversation.continue_answer(partial, "2. Run the tests\n3. Ship"));
+    assert_eq!(
+        conversation.messages()[1].content,
+        "Steps:\n1. Build\n2. Run the tests\n3. Ship"
+    );
+    assert!(!conversation.continue_answer("Not said", "at all"));
+}
+
+#[test]
+fn test_usage_is_added_up_over_the_parts() {
+    let mut usage = AnswerUsage::default();
+    usage.add(&response("", true).usage, 0.01);
+    usage.add(&response("", false).usage, 0.02);
+    assert_eq!(usage.parts, 2);
+    assert_eq!(usage.prompt_tokens, 20);
+    assert_eq!(usage.completion_tokens, 8192);
+    assert!((usage.cost - 0.03).abs() < 1e-9);
+    assert_eq!(
+        usage.summary(),
+        "↪️ Answer joined from 2 responses · 8212 tokens (20 prompt, 8192 completion) · $0.0300"
+    );
+}
+
+#[test]
+fn test_continuations_carry_the_prompt_and_the_answer() {
+    let request = continuation_prompt("List the steps", "1. Build");
+    assert!(request.starts_with("List the steps\n"));
+    assert!(request.contains("1. Build"));
+    assert!(request.contains("Continue exactly where"));
+
+    let pending = PendingPrompt::new("List the steps".to_string(), AIPurpose::Chat).with_history(
+        None,
+        "List the steps",
+        None,
+    );
+    assert_eq!(pending.request(), "List the steps");
+    let continued = pending.continuing("1. Build".to_string());
+    assert_eq!(continued.request(), request);
+    // The continuation is not a new message of the conversation
+    assert_eq!(continued.message, None);
+    // Continuing again wraps the original prompt, not the previous request
+    let again = continued.continuing("1. Build\n2. Test".to_string());
+    assert!(again.request().starts_with("List the steps\n"));
+    assert_eq!(again.request().matches("cut off").count(), 1);
+
+    // Pending prompts stored before continuations counted usage still load
+    let stored = r#"{"prompt":"hi","purpose":"Chat","created":"2026-10-16T09:00:00+02:00"}"#;
+    let loaded: PendingPrompt = serde_json::from_str(stored).unwrap();
+    assert_eq!(loaded.answer_usage, AnswerUsage::default());
+}
+
+#[test]
+fn test_retries_double_the_token_limit() {
+    assert_eq!(raised_max_tokens(4096), 8192);
+    assert_eq!(raised_max_tokens(0), 2);
+    let pending = PendingPrompt::new("hi".to_string(), AIPurpose::Chat).with_max_tokens(8192);
+    assert_eq!(pending.max_tokens, Some(8192));
+}
@@ -0,0 +1,160 @@
+use ai_coder_interface_rs::ai::conversation::{Conversation, Role, exchange_tokens};
+use ai_coder_interface_rs::config::{ContextConfig, Truncation};
+
+/// A message of roughly `words * 1.3` tokens
+fn words(word: &str, count: usize) -> String {
+    vec![word; count].join(" ")
+}
+
+fn conversation() -> Conversation {
+    let mut conversation = Conversation::new();
+    conversation.record(&words("first", 10), &words("one", 10));
+    conversation.record(&words("second", 10), &words("two", 10));
+    conversation.record(&words("third", 10), &words("three", 10));
+    conversation
+}
+
+fn config(max_tokens: usize, truncation: Truncation) -> ContextConfig {
+    ContextConfig {
+        enabled: true,
+        max_tokens,
+        truncation,
+    }
+}
+
+fn first_words(conversation: &Conversation, config: &ContextConfig) -> Vec<String> {
+    conversation
+        .window(config)
+        .iter()
+        .map(|message| message.content.split(' ').next().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn test_prompts_and_answers_are_remembered_in_order() {
+    let mut conversation = Conversation::new();
+    conversation.record("What is a trait?", "  An interface.\n");
+    conversation.record("", "ignored question");
+    let messages = conversation.messages();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[0].role, Role::User);
+    assert_eq!(messages[1].content, "An interface.");
+    assert_eq!(messages[2].role, Role::Assistant);
+
+    conversation.clear();
+    assert!(conversation.is_empty());
+    assert_eq!(conversation.context(&ContextConfig::default()), None);
+}
+
+#[test]
+fn test_context_lists_the_messages_by_role() {
+    let mut conversation = Conversation::new();
+    conversation.record("Name a sorting algorithm", "Merge sort");
+    assert_eq!(
+        conversation.context(&ContextConfig::default()).unwrap(),
+        "Earlier messages of this conversation:\n\nUser: Name a sorting algorithm\n\nAssistant: Merge sort"
+    );
+
+    let off = ContextConfig {
+        enabled: false,
+        ..ContextConfig::default()
+    };
+    assert_eq!(conversation.context(&off), None);
+}
+
+#[test]
+fn test_the_oldest_exchanges_are_dropped_first() {
+    let conversation = conversation();
+    // Each exchange is 26 tokens
+    assert_eq!(conversation.tokens(), 78);
+    assert_eq!(
+        first_words(&conversation, &config(100, Truncation::DropOldest)),
+        vec!["first", "one", "second", "two", "third", "three"]
+    );
+    assert_eq!(
+        first_words(&conversation, &config(60, Truncation::DropOldest)),
+        vec!["second", "two", "third", "three"]
+    );
+    assert!(first_words(&conversation, &config(10, Truncation::DropOldest)).is_empty());
+}
+
+#[test]
+fn test_keep_first_drops_the_exchanges_after_the_first() {
+    let conversation = conversation();
+    assert_eq!(
+        first_words(&conversation, &config(60, Truncation::KeepFirst)),
+        vec!["first", "one", "third", "three"]
+    );
+    assert_eq!(
+        first_words(&conversation, &config(30, Truncation::KeepFirst)),
+        vec!["first", "one"]
+    );
+}
+
+#[test]
+fn test_truncation_is_read_from_the_config() {
+    let config: ContextConfig =
+        serde_yaml::from_str("enabled: true\nmax_tokens: 2000\ntruncation: keep_first\n").unwrap();
+    assert_eq!(config.truncation, Truncation::KeepFirst);
+    let config: ContextConfig = serde_yaml::from_str("enabled: false\nmax_tokens: 2000\n").unwrap();
+    assert_eq!(config.truncation, Truncation::DropOldest);
+}
+
+#[test]
+fn test_exchanges_outside_the_budget_are_marked_as_not_sent() {
+    let conversation = conversation();
+    let exchanges = conversation.exchanges();
+    assert_eq!(exchanges.len(), 3);
+    assert_eq!(exchange_tokens(exchanges[0]), 26);
+    assert_eq!(
+        conversation.sent_exchanges(&config(60, Truncation::DropOldest)),
+        vec![false, true, true]
+    );
+    assert_eq!(
+        conversation.sent_exchanges(&config(60, Truncation::KeepFirst)),
+        vec![true, false, true]
+    );
+    let off = ContextConfig {
+        enabled: false,
+        ..ContextConfig::default()
+    };
+    assert_eq!(conversation.sent_exchanges(&off), vec![false; 3]);
+}
+
+#[test]
+fn test_single_exchanges_can_be_forgotten() {
+    let mut conversation = conversation();
+    assert!(conversation.remove_exchange(1));
+    assert!(!conversation.remove_exchange(2));
+    assert_eq!(
+        first_words(&conversation, &config(100, Truncation::DropOldest)),
+        vec!["first", "one", "third", "three"]
+    );
+}
+
+#[test]
+fn test_dropped_messages_leave_their_exchanges_out() {
+    let mut conversation = Conversation::new();
+    conversation.record_from(Some(4), &words("first", 10), &words("one", 10));
+    conversation.record_from(Some(7), &words("second", 10), &words("two", 10));
+    conversation.record(&words("third", 10), &words("three", 10));
+    let budget = config(60, Truncation::DropOldest);
+    assert_eq!(
+        first_words(&conversation, &budget),
+        ["second", "two", "third", "three"]
+    );
+
+    // The dropped exchange frees its budget for an older one
+    conversation.set_excluded(7, true);
+    assert_eq!(
+        first_words(&conversation, &budget),
+        ["first", "one", "third", "three"]
+    );
+    assert_eq!(conversation.sent_exchanges(&budget), [true, false, true]);
+
+    conversation.set_excluded(7, false);
+    assert_eq!(
+        first_words(&conversation, &budget),
+        ["second", "two", "third", "three"]
+    );
+}
@@ -0,0 +1,53 @@
+use ai_coder_interface_rs::config::AppConfig;
+use ai_coder_interface_rs::utils::crash::{redact_config, redact_secrets, write_report_to};
+use std::fs;
+
+#[test]
+fn test_config_secrets_are_masked() {
+    let mut config = AppConfig::default();
+    config.ai.openai.api_key = "sk-live-abcdefghijklmnop".to_string();
+
+    let yaml = redact_config(&config);
+    assert!(!yaml.contains("sk-live-abcdefghijklmnop"));
+    assert!(yaml.contains("api_key: '***'") || yaml.contains("api_key: \"***\""));
+    // Unset keys stay empty so the report shows they were never configured
+    assert!(yaml.contains("api_key: ''"));
+}
+
+#[test]
+fn test_secrets_in_text_are_masked() {
+    let text = "request failed: Authorization: Bearer abc123def456ghi with key sk-proj-0123456789";
+    let redacted = redact_secrets(text);
+    assert!(!redacted.contains("abc123def456ghi"));
+    assert!(!redacted.contains("sk-proj-0123456789"));
+    assert!(redacted.starts_with("request failed"));
+}
+
+#[test]
+fn test_report_is_written_with_version_and_reason() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = write_report_to(dir.path(), "Panic: index out of bounds").unwrap();
+
+    let report = fs::read_to_string(&path).unwrap();
+    assert!(report.contains(env!("CARGO_PKG_VERSION")));
+    assert!(report.contains("Panic: index out of bounds"));
+    assert!(report.contains("== Configuration =="));
+    assert!(report.contains("== Tasks =="));
+}
+
+#[test]
+fn test_reports_in_the_same_second_are_kept_apart() {
+    let dir = tempfile::tempdir().unwrap();
+    let first = write_report_to(dir.path(), "Panic: first").unwrap();
+    let second = write_report_to(dir.path(), "Panic: second").unwrap();
+
+    assert_ne!(first, second);
+    let name = first.file_name().unwrap().to_string_lossy().into_owned();
+    assert!(name.contains(&std::process::id().to_string()));
+    assert!(fs::read_to_string(&first).unwrap().contains("Panic: first"));
+    assert!(
+        fs::read_to_string(&second)
+            .unwrap()
+            .contains("Panic: second")
+    );
+}
@@ -0,0 +1,63 @@
+use ai_coder_interface_rs::ai::custom::key_header;
+use ai_coder_interface_rs::ai::{AIClientFactory, Provider};
+use ai_coder_interface_rs::config::{AIConfig, CustomConfig};
+
+#[test]
+fn test_custom_parses_by_name() {
+    assert_eq!("custom".parse::<Provider>(), Ok(Provider::Custom));
+    assert_eq!(
+        "openai-compatible".parse::<Provider>(),
+        Ok(Provider::Custom)
+    );
+    assert_eq!(Provider::Custom.to_string(), "Custom");
+}
+
+#[test]
+fn test_the_api_key_goes_in_the_configured_header() {
+    let mut settings = CustomConfig::default();
+    assert_eq!(key_header(&settings).unwrap(), None);
+    settings.api_key_header = Some(" ".to_string());
+    assert_eq!(key_header(&settings).unwrap(), None);
+    settings.api_key_header = Some("X-API-Key".to_string());
+    assert_eq!(
+        key_header(&settings).unwrap().unwrap().as_str(),
+        "x-api-key"
+    );
+    settings.api_key_header = Some("not a header".to_string());
+    assert!(key_header(&settings).is_err());
+}
+
+#[test]
+fn test_the_endpoint_and_models_come_from_the_config() {
+    let mut config: AIConfig = serde_yaml::from_str(
+        "active_provider: Custom
+ollama: { endpoint: 'http://localhost:11434', models: [{ name: llama3, temperature: 0.1, max_tokens: 100 }], current_model_index: 0 }
+openai: { endpoint: '', api_key: '', models: [], current_model_index: 0 }
+anthropic: { endpoint: '', api_key: '', models: [], current_model_index: 0 }
+lmstudio: { endpoint: '', models: [], current_model_index: 0 }
+custom:
+  endpoint: http://gpu-box:8000/v1
+  models: [{ name: qwen2.5-coder, temperature: 0.2, max_tokens: 2048 }]
+  current_model_index: 0
+  extra_headers: { X-Team: infra }
+",
+    )
+    .unwrap();
+    assert!(config.custom.stream_usage);
+    assert!(!config.custom.discover_models);
+    assert_eq!(config.get_active_endpoint(), "http://gpu-box:8000/v1");
+    assert_eq!(config.get_active_model_config().name, "qwen2.5-coder");
+    assert!(AIClientFactory::create_client_from_config(&config).is_ok());
+
+    // Selecting an unknown model adds it with the settings of the current one
+    config.select_model("deepseek-coder-v2");
+    let model = config.get_active_model_config();
+    assert_eq!(model.name, "deepseek-coder-v2");
+    assert_eq!(model.max_tokens, 2048);
+
+    config.custom.endpoint = String::new();
+    assert!(AIClientFactory::create_client_from_config(&config).is_err());
+    config.custom.endpoint = "http://gpu-box:8000/v1".to_string();
+    config.custom.api_key_header = Some("bad header".to_string());
+    assert!(AIClientFactory::create_client_from_config(&config).is_err());
+}
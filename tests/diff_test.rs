@@ -0,0 +1,108 @@
+use ai_coder_interface_rs::utils::diff::*;
+
+#[test]
+fn test_diff_lines() {
+    let ops = diff_lines("a\nb\nc", "a\nx\nc");
+    assert_eq!(
+        ops,
+        vec![
+            DiffOp::Equal("a".to_string()),
+            DiffOp::Delete("b".to_string()),
+            DiffOp::Insert("x".to_string()),
+            DiffOp::Equal("c".to_string()),
+        ]
+    );
+    assert_eq!(diff_stats(&ops), (1, 1));
+}
+
+#[test]
+fn test_unified_diff() {
+    assert_eq!(unified_diff("f.txt", "same\n", "same\n"), "");
+
+    let diff = unified_diff(
+        "f.txt",
+        "1\n2\n3\n4\n5\n6\n7\n8\n",
+        "1\n2\n3\n4\nfive\n6\n7\n8\n",
+    );
+    assert_eq!(
+        diff,
+        "--- a/f.txt\n+++ b/f.txt\n@@ -2,7 +2,7 @@\n 2\n 3\n 4\n-5\n+five\n 6\n 7\n 8\n"
+    );
+
+    let new_file = unified_diff("new.rs", "", "fn main() {}\n");
+    assert_eq!(
+        new_file,
+        "--- /dev/null\n+++ b/new.rs\n@@ -0,0 +1,1 @@\n+fn main() {}\n"
+    );
+}
+
+#[test]
+fn test_unified_diffs_are_found_in_output() {
+    let output = [
+        "Here is the change:",
+        "diff --git a/src/lib.rs b/src/lib.rs",
+        "index 3b18e51..a1b2c3d 100644",
+        "--- a/src/lib.rs",
+        "+++ b/src/lib.rs",
+        "@@ -1,3 +1,3 @@",
+        " fn main() {",
+        "-    old();",
+        "+    new();",
+        "",
+        "- this list item is prose, not part of the diff",
+    ];
+    assert_eq!(find_unified_diffs(&output), vec![1..10]);
+    assert_eq!(classify_diff_line(output[3]), DiffLineKind::Header);
+    assert_eq!(classify_diff_line(output[5]), DiffLineKind::Hunk);
+    assert_eq!(classify_diff_line(output[7]), DiffLineKind::Removed);
+    assert_eq!(classify_diff_line(output[8]), DiffLineKind::Added);
+    assert_eq!(classify_diff_line(output[6]), DiffLineKind::Context);
+
+    // Fenced diff blocks of a response count as a whole
+    let response = ["```diff", "-a", "+b", "```", "- not a diff"];
+    assert_eq!(find_unified_diffs(&response), vec![1..3]);
+
+    // Our own previews are found too
+    let preview = unified_diff("f.txt", "1\n2\n", "1\ntwo\n");
+    let lines: Vec<&str> = preview.lines().collect();
+    assert_eq!(find_unified_diffs(&lines), vec![0..lines.len()]);
+    assert!(find_unified_diffs(&["--- just a rule", "text"]).is_empty());
+}
+
+#[test]
+fn test_changed_lines_sit_next_to_their_replacements() {
+    let diff = [
+        "@@ -1,4 +1,3 @@",
+        " keep",
+        "-old one",
+        "-old two",
+        "+new one",
+        " end",
+    ];
+    assert_eq!(
+        side_by_side(&diff),
+        vec![
+            SideBySideRow::Header("@@ -1,4 +1,3 @@".to_string(), DiffLineKind::Hunk),
+            SideBySideRow::Pair {
+                left: Some("keep".to_string()),
+                right: Some("keep".to_string()),
+                changed: false,
+            },
+            SideBySideRow::Pair {
+                left: Some("old one".to_string()),
+                right: Some("new one".to_string()),
+                changed: true,
+            },
+            SideBySideRow::Pair {
+                left: Some("old two".to_string()),
+                right: None,
+                changed: true,
+            },
+            SideBySideRow::Pair {
+                left: Some("end".to_string()),
+                right: Some("end".to_string()),
+                changed: false,
+            },
+        ]
+    );
+}
@@ -0,0 +1,41 @@
+use ai_coder_interface_rs::handlers::doc::{DocStyle, insert_docs, parse_doc_response};
+
+#[test]
+fn test_parse_doc_response() {
+    let response = "```\n@@ 3\n/// Adds two numbers.\n\n@@ 7\nThe entry point.\n```";
+    assert_eq!(
+        parse_doc_response(response),
+        vec![
+            (3, "Adds two numbers.".to_string()),
+            (7, "The entry point.".to_string())
+        ]
+    );
+}
+
+#[test]
+fn test_insert_rust_docs() {
+    let source = "use std::io;\n\n#[inline]\npub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n\n/// Already documented\nfn main() {}\n";
+    let entries = vec![
+        (4, "Adds two numbers.".to_string()),
+        (9, "Entry point.".to_string()),
+        (1, "Not an item.".to_string()),
+    ];
+    let (result, count) = insert_docs(source, DocStyle::Rust, &entries, None);
+    assert_eq!(count, 1);
+    assert_eq!(
+        result,
+        "use std::io;\n\n/// Adds two numbers.\n#[inline]\npub fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n\n/// Already documented\nfn main() {}\n"
+    );
+}
+
+#[test]
+fn test_insert_python_docstring() {
+    let source = "class Greeter:\n    def greet(self, name):\n        return f\"hi {name}\"\n";
+    let entries = vec![(2, "Greet someone.\n\nReturns the greeting.".to_string())];
+    let (result, count) = insert_docs(source, DocStyle::Python, &entries, Some(2));
+    assert_eq!(count, 1);
+    assert_eq!(
+        result,
+        "class Greeter:\n    def greet(self, name):\n        \"\"\"Greet someone.\n\n        Returns the greeting.\n        \"\"\"\n        return f\"hi {name}\"\n"
+    );
+}
@@ -0,0 +1,107 @@
+use ai_coder_interface_rs::config::EditorConfig;
+use ai_coder_interface_rs::handlers::editor::{
+    FileLocation, command_line, find_locations, resolve,
+};
+use std::path::Path;
+
+fn location(path: &str, line: usize, column: Option<usize>) -> FileLocation {
+    FileLocation {
+        path: path.to_string(),
+        line,
+        column,
+    }
+}
+
+#[test]
+fn test_locations_are_found_in_compiler_and_tool_output() {
+    let output = "\
+thread 'main' panicked at src/main.rs:42:9:
+called `Option::unwrap()` on a `None` value
+warning: this `if` statement can be collapsed
+  --> src/app/lsp.rs:177:21
+src/app/lsp.rs:177:21: duplicate
+  File \"scripts/build.py\", line 12, in <module>
+tests/lsp_test.rs:40:    let range =
+";
+    assert_eq!(
+        find_locations(output),
+        vec![
+            location("src/main.rs", 42, Some(9)),
+            location("src/app/lsp.rs", 177, Some(21)),
+            location("scripts/build.py", 12, None),
+            location("tests/lsp_test.rs", 40, None),
+        ]
+    );
+}
+
+#[test]
+fn test_times_and_addresses_are_not_locations() {
+    let output = "Started at 10:42:07, listening on http://localhost.dev:8080 and https://example.com:443/a.rs:3";
+    assert!(find_locations(output).is_empty());
+}
+
+#[test]
+fn test_typed_locations_are_parsed() {
+    assert_eq!(
+        FileLocation::parse("src/main.rs:42:9"),
+        Some(location("src/main.rs", 42, Some(9)))
+    );
+    assert_eq!(
+        FileLocation::parse("src/main.rs:42"),
+        Some(location("src/main.rs", 42, None))
+    );
+    assert_eq!(
+        FileLocation::parse("README.md"),
+        Some(location("README.md", 1, None))
+    );
+    assert_eq!(
+        FileLocation::parse(r"C:\src\lib.rs:3"),
+        Some(location(r"C:\src\lib.rs", 3, None))
+    );
+    assert_eq!(FileLocation::parse(":3"), None);
+    assert_eq!(location("a.rs", 3, Some(4)).to_string(), "a.rs:3:4");
+}
+
+#[test]
+fn test_the_editor_comes_from_the_config_or_the_environment() {
+    let configured = EditorConfig {
+        command: "idea --line {line} {file}".to_string(),
+        terminal: false,
+    };
+    assert_eq!(
+        resolve(&configured, Some("vim".to_string())),
+        ("idea --line {line} {file}".to_string(), false)
+    );
+
+    let config = EditorConfig::default();
+    assert_eq!(
+        resolve(&config, Some("nvim".to_string())),
+        ("nvim +{line} {file}".to_string(), true)
+    );
+    assert_eq!(
+        resolve(&config, Some("/usr/bin/code --wait".to_string())),
+        (
+            "/usr/bin/code --wait -g {file}:{line}:{column}".to_string(),
+            false
+        )
+    );
+    assert_eq!(
+        resolve(&config, None),
+        ("vi +{line} {file}".to_string(), true)
+    );
+}
+
+#[test]
+fn test_placeholders_are_filled_in() {
+    let path = Path::new("/work/my project/src/main.rs");
+    let at = location("src/main.rs", 42, None);
+    assert_eq!(
+        command_line("code -g {file}:{line}:{column}", path, &at).unwrap(),
+        vec!["code", "-g", "/work/my project/src/main.rs:42:1"]
+    );
+    assert_eq!(
+        command_line("nano", path, &at).unwrap(),
+        vec!["nano", "/work/my project/src/main.rs"]
+    );
+    assert!(command_line("", path, &at).is_err());
+}
@@ -0,0 +1,110 @@
+use ai_coder_interface_rs::handlers::edits::{self, EditKind, apply_edit, extract_edits};
+use ai_coder_interface_rs::handlers::files::extract_file_blocks;
+use std::fs;
+use std::path::PathBuf;
+
+const RESPONSE: &str = "Rename the helper:
+
+src/lib.rs
+```rust
+<<<<<<< SEARCH
+fn helper() {}
+=======
+fn assist() {}
+>>>>>>> REPLACE
+```
+
+And bump the version:
+
+```diff
+--- a/Cargo.toml
++++ b/Cargo.toml
+@@ -2,3 +2,3 @@
+ name = \"demo\"
+-version = \"0.1.0\"
++version = \"0.2.0\"
+ edition = \"2021\"
+```
+";
+
+#[test]
+fn test_edits_are_found_in_order_and_are_not_file_blocks() {
+    let found = extract_edits(RESPONSE);
+    let paths: Vec<PathBuf> = found.iter().map(|edit| edit.path.clone()).collect();
+    assert_eq!(
+        paths,
+        [PathBuf::from("src/lib.rs"), PathBuf::from("Cargo.toml")]
+    );
+    assert_eq!(
+        found[0].kind,
+        EditKind::Replace {
+            search: "fn helper() {}".to_string(),
+            replace: "fn assist() {}".to_string(),
+        }
+    );
+    assert!(matches!(&found[1].kind, EditKind::Patch(hunks) if hunks.len() == 1));
+    assert!(extract_file_blocks(RESPONSE).is_empty());
+}
+
+#[test]
+fn test_hunks_apply_by_content_when_line_numbers_are_off() {
+    let diff = "--- a/notes.txt\n+++ b/notes.txt\n@@ -40,2 +40,2 @@\n one\n-two\n+TWO\n";
+    let lines: Vec<&str> = diff.lines().collect();
+    let edit = edits::parse_unified_diff(&lines).remove(0);
+    assert_eq!(
+        apply_edit(Some("zero\none\ntwo\nthree\n"), &edit.kind).unwrap(),
+        "zero\none\nTWO\nthree\n"
+    );
+    assert!(apply_edit(Some("zero\nthree\n"), &edit.kind).is_err());
+}
+
+#[test]
+fn test_new_files_and_deletions() {
+    let diff = "--- /dev/null\n+++ b/src/new.rs\n@@ -0,0 +1,2 @@\n+fn a() {}\n+fn b() {}\n--- a/old.rs\n+++ /dev/null\n@@ -1 +0,0 @@\n-fn old() {}\n";
+    let lines: Vec<&str> = diff.lines().collect();
+    let found = edits::parse_unified_diff(&lines);
+    assert_eq!(found.len(), 2);
+    assert_eq!(
+        apply_edit(None, &found[0].kind).unwrap(),
+        "fn a() {}\nfn b() {}\n"
+    );
+    assert_eq!(found[1].kind, EditKind::Delete);
+    assert!(apply_edit(Some("fn old() {}\n"), &found[1].kind).is_err());
+}
+
+#[test]
+fn test_resolve_applies_edits_to_the_files_on_disk() {
+    let dir = std::env::temp_dir().join(format!("ai-coder-edits-{}", std::process::id()));
+    fs::create_dir_all(dir.join("src")).unwrap();
+    fs::write(dir.join("src/lib.rs"), "fn helper() {}\nfn main() {}\n").unwrap();
+
+    let response = "src/lib.rs\n<<<<<<< SEARCH\nfn helper() {}  \n=======\nfn assist() {}\n>>>>>>> REPLACE\n\
+        <<<<<<< SEARCH\nfn main() {}\n=======\nfn main() { assist() }\n>>>>>>> REPLACE\n\
+        src/missing.rs\n<<<<<<< SEARCH\nfn gone() {}\n=======\n>>>>>>> REPLACE\n";
+    let (blocks, problems) = edits::resolve(&dir, &extract_edits(response));
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(blocks.len(), 1);
+    // The second block reuses the path and trailing spaces do not matter
+    assert_eq!(
+        blocks[0].content,
+        "fn assist() {}\nfn main() { assist() }\n"
+    );
+    assert_eq!(problems, ["src/missing.rs: the file does not exist"]);
+}
+
+#[test]
+fn test_resolve_leaves_denied_files_alone() {
+    let dir = std::env::temp_dir().join(format!("ai-coder-edits-deny-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join(".env"), "TOKEN=secret\n").unwrap();
+
+    // `.env` is in the default workspace.deny
+    let response = ".env\n<<<<<<< SEARCH\nTOKEN=secret\n=======\nTOKEN=leaked\n>>>>>>> REPLACE\n";
+    let (blocks, problems) = edits::resolve(&dir, &extract_edits(response));
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(blocks.is_empty());
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].contains("workspace.deny"));
+}
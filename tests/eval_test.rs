@@ -0,0 +1,139 @@
+use ai_coder_interface_rs::ai::Provider;
+use ai_coder_interface_rs::app::App;
+use ai_coder_interface_rs::utils::eval::{
+    CaseResult, EvalCase, EvalTarget, ModelResult, is_cases_file, parse_args, parse_cases,
+    parse_score, parse_target, render_table,
+};
+use std::time::Duration;
+
+#[test]
+fn test_parse_args() {
+    let args = parse_args("review src/main.rs --models openai/gpt-4o,qwen2.5-coder --expect unwrap --judge gpt-4o-mini").unwrap();
+    assert_eq!(args.source, "review");
+    assert_eq!(args.text, "src/main.rs");
+    assert_eq!(args.models, vec!["openai/gpt-4o", "qwen2.5-coder"]);
+    assert_eq!(args.expect, vec!["unwrap"]);
+    assert_eq!(args.judge.as_deref(), Some("gpt-4o-mini"));
+
+    assert!(parse_args("review").is_err());
+    assert!(parse_args("--models a,b").is_err());
+    assert!(parse_args("review --models").is_err());
+    assert!(parse_args("review --models a --temperature 1").is_err());
+}
+
+#[test]
+fn test_parse_target() {
+    assert_eq!(
+        parse_target("anthropic/claude-3-5-haiku-latest", Provider::Ollama).unwrap(),
+        EvalTarget {
+            provider: Provider::Anthropic,
+            model: "claude-3-5-haiku-latest".to_string(),
+        }
+    );
+    // Slashes that do not follow a provider are part of the model name
+    let target = parse_target("meta-llama/llama-3-70b", Provider::OpenRouter).unwrap();
+    assert_eq!(target.provider, Provider::OpenRouter);
+    assert_eq!(target.model, "meta-llama/llama-3-70b");
+    assert_eq!(target.to_string(), "OpenRouter/meta-llama/llama-3-70b");
+    assert!(parse_target("openai/", Provider::Ollama).is_err());
+}
+
+#[test]
+fn test_cases_and_assertions() {
+    assert!(is_cases_file("evals/strings.YAML"));
+    assert!(!is_cases_file("review"));
+
+    let cases = parse_cases(
+        "- prompt: Reverse a string in Rust\n  expect: [\"chars()\", \"REV\"]\n  forbid: [unsafe]\n- prompt: Say hi\n",
+    )
+    .unwrap();
+    assert_eq!(cases.len(), 2);
+    assert_eq!(cases[0].assertions(), 3);
+    assert!(cases[0].failures("s.chars().rev().collect()").is_empty());
+    assert_eq!(
+        cases[0].failures("unsafe { s.chars() }"),
+        vec!["missing \"REV\"", "contains \"unsafe\""]
+    );
+    assert_eq!(cases[1].assertions(), 0);
+
+    assert!(parse_cases("[]").is_err());
+    assert!(parse_cases("- expect: [a]").is_err());
+}
+
+#[test]
+fn test_parse_score() {
+    assert_eq!(parse_score("8"), Some(8));
+    assert_eq!(parse_score("Score: 7/10"), Some(7));
+    assert_eq!(parse_score("10"), Some(10));
+    assert_eq!(parse_score("42"), None);
+    assert_eq!(parse_score("great answer"), None);
+}
+
+#[test]
+fn test_render_table() {
+    let cases = vec![EvalCase {
+        prompt: "p".to_string(),
+        expect: vec!["a".to_string(), "b".to_string()],
+        forbid: Vec::new(),
+    }];
+    let answered = |failures: Vec<String>, score, millis| CaseResult {
+        response: Ok("text".to_string()),
+        latency: Duration::from_millis(millis),
+        cost: 0.0012,
+        failures,
+        score,
+    };
+    let results = vec![
+        ModelResult {
+            target: parse_target("openai/gpt-4o", Provider::Ollama).unwrap(),
+            cases: vec![answered(Vec::new(), Some(9), 800)],
+        },
+        ModelResult {
+            target: parse_target("llama3", Provider::Ollama).unwrap(),
+            cases: vec![answered(vec!["missing \"b\"".to_string()], Some(5), 2500)],
+        },
+        ModelResult {
+            target: parse_target("groq/mixtral", Provider::Ollama).unwrap(),
+            cases: vec![CaseResult {
+                response: Err("Network error".to_string()),
+                latency: Duration::from_millis(10),
+                cost: 0.0,
+                failures: Vec::new(),
+                score: None,
+            }],
+        },
+    ];
+    assert_eq!(results[1].passed(&cases), 1);
+    assert_eq!(results[2].passed(&cases), 0);
+    assert_eq!(results[2].mean_latency(), None);
+
+    let table = render_table(&cases, &results, true);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].contains("passed") && lines[0].contains("judge"));
+    assert!(lines[1].contains("OpenAI/gpt-4o") && lines[1].contains("2/2"));
+    assert!(lines[1].contains("9.0") && lines[1].contains("800ms"));
+    assert!(lines[2].contains("1/2") && lines[2].contains("2.5s"));
+    assert!(lines[3].contains("0/2") && lines[3].trim_end().ends_with('1'));
+
+    let plain = render_table(&[EvalCase::default()], &results[..1], false);
+    assert!(!plain.contains("passed") && !plain.contains("judge"));
+}
+
+#[test]
+fn test_cases_outside_the_workspace_are_not_read() {
+    let dir = tempfile::tempdir().unwrap();
+    let workspace = dir.path().join("project");
+    std::fs::create_dir(&workspace).unwrap();
+    std::fs::write(
+        dir.path().join("cases.yaml"),
+        "- prompt: secret\n  expect: [ok]\n",
+    )
+    .unwrap();
+
+    let mut app = App::new();
+    app.current_dir = workspace;
+    app.handle_eval("../cases.yaml --models ollama:llama3");
+    assert!(app.output.contains("outside the workspace"));
+    assert!(!app.output.contains("secret"));
+}
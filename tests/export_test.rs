@@ -0,0 +1,110 @@
+use ai_coder_interface_rs::config::ThemeConfig;
+use ai_coder_interface_rs::handlers::CommandMode;
+use ai_coder_interface_rs::handlers::export::{ExportMeta, highlight_code, render_html};
+use ai_coder_interface_rs::utils::transcript::{Transcript, section_spans, to_plain_text};
+use chrono::{Local, TimeZone};
+
+const SESSION: &str = "🚀 AI Coder Interface\nCurrent directory: /tmp\n\n\
+━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
+❯ how do I print?\n\
+⠋\n\
+Use println:\n```rust\nfn main() { println!(\"<hi>\"); }\n```\n\
+━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
+$ !ls\n\
+Cargo.toml\nsrc\n\
+━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
+/ /version\n\
+v0.1.0\n";
+
+#[test]
+fn test_transcript_parse() {
+    let transcript = Transcript::parse(SESSION);
+    assert_eq!(
+        transcript.preamble,
+        "🚀 AI Coder Interface\nCurrent directory: /tmp"
+    );
+    assert_eq!(transcript.exchanges.len(), 3);
+
+    let ai = &transcript.exchanges[0];
+    assert_eq!(ai.mode, CommandMode::AI);
+    assert_eq!(ai.input, "how do I print?");
+    assert!(ai.output.starts_with("Use println:"));
+
+    assert_eq!(transcript.exchanges[1].display_input(), "$ ls");
+    assert_eq!(transcript.exchanges[1].output, "Cargo.toml\nsrc");
+    assert_eq!(transcript.exchanges[2].display_input(), "/version");
+    assert_eq!(transcript.last(1)[0].output, "v0.1.0");
+}
+
+#[test]
+fn test_export_html() {
+    assert_eq!(
+        highlight_code("rust", "let x = 1; // one"),
+        "<span class=\"kw\">let</span> x = <span class=\"nu\">1</span>; <span class=\"cm\">// one</span>"
+    );
+
+    let meta = ExportMeta {
+        started: Local::now(),
+        exported: Local::now(),
+        directory: "/tmp".to_string(),
+        provider: "Ollama".to_string(),
+        models: vec!["llama3".to_string()],
+        prompt_tokens: 10,
+        completion_tokens: 20,
+        cost: 0.0,
+        times: vec![Local.with_ymd_and_hms(2024, 5, 1, 9, 30, 0).unwrap()],
+    };
+    let html = render_html(&Transcript::parse(SESSION), &meta, &ThemeConfig::default());
+    assert!(html.contains("--primary: #0087AF"));
+    assert!(html.contains("<td>Models used</td><td>llama3</td>"));
+    assert!(html.contains("1 prompts, 1 bash commands, 1 commands"));
+    assert!(html.contains("<details class=\"exchange ai\" open>"));
+    assert!(html.contains("&quot;&lt;hi&gt;&quot;"));
+    assert!(html.contains("<span class=\"time\" title=\"2024-05-01 09:30:00\">09:30:00</span>"));
+    assert!(!html.contains("⠋"));
+}
+
+#[test]
+fn test_plain_text() {
+    let session = format!(
+        "{}━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n$ !cargo build\n\
+         ✅ Build finished\n[⏱️ 1.20s | ✓ | 📊 1]\n",
+        SESSION
+    );
+    let transcript = Transcript::parse(&session);
+    let text = to_plain_text(transcript.last(2));
+    assert_eq!(
+        text,
+        "/version\n\nv0.1.0\n\n$ cargo build\n\nBuild finished\n"
+    );
+
+    let text = to_plain_text(&transcript.exchanges[..1]);
+    assert!(text.starts_with("> how do I print?\n\nUse println:\n```rust\n"));
+    assert!(!text.contains('⠋'));
+}
+
+#[test]
+fn test_section_spans() {
+    let session = format!(
+        "{}━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n$ !cargo build\n\
+         [⏱️ 1.20s | ✗ | 📊 101]\nerror[E0308]: mismatched types\n",
+        SESSION
+    );
+    let lines: Vec<&str> = session.lines().collect();
+    let spans = section_spans(&lines);
+    assert_eq!(spans.len(), 4);
+    assert_eq!(spans[0].mode, CommandMode::AI);
+    assert_eq!(lines[spans[1].header + 1], "$ !ls");
+    assert_eq!(spans[1].body.len(), 2);
+    assert_eq!(spans[3].body.end, lines.len());
+    assert_eq!(
+        spans[3].summary(&lines),
+        "▸ $ !cargo build output — 2 lines, exit 101"
+    );
+
+    // Each section is a message that can be run again as typed
+    let message = spans[3].exchange(&lines);
+    assert_eq!(message.command(), "!cargo build");
+    assert!(message.output.ends_with("mismatched types"));
+    assert_eq!(spans[0].exchange(&lines).command(), "how do I print?");
+}
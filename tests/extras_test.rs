@@ -0,0 +1,71 @@
+use ai_coder_interface_rs::ai::Provider;
+use ai_coder_interface_rs::ai::extras::RequestExtras;
+use ai_coder_interface_rs::config::AIConfig;
+use serde_json::json;
+use std::collections::BTreeMap;
+
+#[test]
+fn test_extra_fields_merge_into_nested_objects() {
+    let extras = RequestExtras::new(
+        BTreeMap::new(),
+        BTreeMap::from([
+            ("options".to_string(), json!({ "num_ctx": 8192 })),
+            ("keep_alive".to_string(), json!("10m")),
+            ("stream".to_string(), json!(false)),
+        ]),
+    );
+    let mut body = json!({
+        "model": "llama3",
+        "stream": true,
+        "options": { "num_predict": 2048 },
+    });
+    extras.merge_body(&mut body);
+
+    assert_eq!(
+        body,
+        json!({
+            "model": "llama3",
+            "stream": false,
+            "keep_alive": "10m",
+            "options": { "num_predict": 2048, "num_ctx": 8192 },
+        })
+    );
+}
+
+#[test]
+fn test_invalid_headers_are_config_errors() {
+    let valid = RequestExtras::new(
+        BTreeMap::from([("x-org-id".to_string(), "acme".to_string())]),
+        BTreeMap::new(),
+    );
+    assert_eq!(valid.header_map().unwrap()["x-org-id"], "acme");
+
+    let invalid = RequestExtras::new(
+        BTreeMap::from([("bad header".to_string(), "value".to_string())]),
+        BTreeMap::new(),
+    );
+    assert!(invalid.header_map().is_err());
+}
+
+#[test]
+fn test_provider_configs_read_extras_from_yaml() {
+    let mut config = AIConfig::default();
+    config.ollama = serde_yaml::from_str(
+        r#"
+endpoint: "http://gateway:8080"
+current_model_index: 0
+models: []
+extra_headers:
+  x-routing-tag: coder
+extra_body:
+  options:
+    num_ctx: 8192
+"#,
+    )
+    .unwrap();
+
+    let extras = config.extras(Provider::Ollama);
+    assert_eq!(extras.headers["x-routing-tag"], "coder");
+    assert_eq!(extras.body["options"], json!({ "num_ctx": 8192 }));
+    assert_eq!(config.extras(Provider::OpenAI), RequestExtras::default());
+}
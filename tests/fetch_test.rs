@@ -0,0 +1,97 @@
+use ai_coder_interface_rs::handlers::fetch::{
+    Page, extract_fetch_requests, page_title, parse_url, readable_text, truncate_tokens,
+};
+
+const PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+  <title>Regex &amp; You</title>
+  <style>body { color: red; }</style>
+  <script>var tracking = "<p>not text</p>";</script>
+</head>
+<body>
+  <header><a href="/">Home</a> <a href="/blog">Blog</a></header>
+  <nav><ul><li>Menu item</li></ul></nav>
+  <!-- sidebar starts -->
+  <article>
+    <h1>Matching   text</h1>
+    <p>Regular expressions find <b>patterns</b>&nbsp;in text.</p>
+    <ul>
+      <li>Fast</li>
+      <li>Safe &#8212; no backtracking</li>
+    </ul>
+  </article>
+  <aside>Related posts</aside>
+  <footer>&copy; 2024 Example</footer>
+</body>
+</html>"#;
+
+#[test]
+fn test_boilerplate_is_stripped_to_readable_text() {
+    assert_eq!(
+        readable_text(PAGE),
+        "# Matching text\n\nRegular expressions find patterns in text.\n\n- Fast\n- Safe — no backtracking"
+    );
+    assert_eq!(page_title(PAGE).as_deref(), Some("Regex & You"));
+}
+
+#[test]
+fn test_pages_without_an_article_use_the_body() {
+    let html =
+        "<html><body><div>First</div><div>Second<br>line</div><footer>x</footer></body></html>";
+    assert_eq!(readable_text(html), "First\nSecond\nline");
+    assert_eq!(page_title(html), None);
+}
+
+#[test]
+fn test_text_is_cut_to_the_token_budget() {
+    let text = (1..=200)
+        .map(|n| format!("line number {}", n))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let (cut, truncated) = truncate_tokens(&text, 50);
+    assert!(truncated);
+    assert!(cut.starts_with("line number 1\n"));
+    assert!(cut.ends_with("[… cut to ~50 tokens]"));
+    assert!(cut.lines().count() < 200);
+
+    let (kept, truncated) = truncate_tokens("short page", 50);
+    assert!(!truncated);
+    assert_eq!(kept, "short page");
+}
+
+#[test]
+fn test_only_web_addresses_are_fetched() {
+    assert_eq!(
+        parse_url("docs.rs/regex").unwrap().as_str(),
+        "https://docs.rs/regex"
+    );
+    assert_eq!(
+        parse_url(" http://localhost:8080/a ").unwrap().as_str(),
+        "http://localhost:8080/a"
+    );
+    assert!(parse_url("file:///etc/passwd").is_err());
+    assert!(parse_url("ftp://example.com").is_err());
+    assert!(parse_url("").is_err());
+}
+
+#[test]
+fn test_responses_ask_for_pages_in_fetch_blocks() {
+    let response = "Let me read the docs.\n\n```fetch\nhttps://docs.rs/regex\n\nhttps://example.com\n```\n\n\
+        ```bash\ncurl https://example.com\n```\n\n```fetch\nhttps://docs.rs/regex\n```";
+    assert_eq!(
+        extract_fetch_requests(response),
+        vec!["https://docs.rs/regex", "https://example.com"]
+    );
+}
+
+#[test]
+fn test_attachments_are_named_after_the_page() {
+    let page = Page {
+        url: "https://example.com/".to_string(),
+        title: Some("Example Domain".to_string()),
+        text: "Example".to_string(),
+        truncated: false,
+    };
+    assert_eq!(page.name(), "Example Domain (https://example.com/)");
+}
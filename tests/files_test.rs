@@ -0,0 +1,60 @@
+use ai_coder_interface_rs::handlers::files::{self, FileBlock, extract_file_blocks};
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn test_extract_file_blocks() {
+    let response = "Here you go:\n\
+        ```rust src/utils/retry.rs\npub fn retry() {}\n```\n\
+        ```python\n# scripts/run.py\nprint('hi')\n```\n\
+        ```bash\n# Install dependencies\nnpm install\n```\n\
+        ```toml title=\"Cargo.toml\"\n[package]\n```\n";
+
+    let blocks = extract_file_blocks(response);
+    let paths: Vec<PathBuf> = blocks.iter().map(|b| b.path.clone()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            PathBuf::from("src/utils/retry.rs"),
+            PathBuf::from("scripts/run.py"),
+            PathBuf::from("Cargo.toml"),
+        ]
+    );
+    assert_eq!(blocks[0].language.as_deref(), Some("rust"));
+    assert_eq!(blocks[0].content, "pub fn retry() {}");
+    assert_eq!(blocks[1].content, "print('hi')");
+}
+
+#[test]
+fn test_extract_file_blocks_rejects_escaping_paths() {
+    let response = "```rust ../outside.rs\nfn main() {}\n```\n```sh /etc/profile\nexport X=1\n```";
+    assert!(extract_file_blocks(response).is_empty());
+}
+
+#[test]
+fn test_write_all_keeps_non_utf8_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let binary = [0xff, 0xfe, 0x00, 0x80];
+    fs::write(dir.path().join("data.bin"), binary).unwrap();
+    fs::create_dir(dir.path().join("folder")).unwrap();
+
+    // A target that cannot be read aborts the change and leaves the file alone
+    let blocks = vec![
+        FileBlock {
+            path: PathBuf::from("data.bin"),
+            language: None,
+            content: "text".to_string(),
+        },
+        FileBlock {
+            path: PathBuf::from("folder"),
+            language: None,
+            content: "text".to_string(),
+        },
+    ];
+    assert!(files::write_all(dir.path(), &blocks).is_err());
+    assert_eq!(fs::read(dir.path().join("data.bin")).unwrap(), binary);
+
+    // An existing file is reported as updated even when it is not UTF-8
+    let written = files::write_all(dir.path(), &blocks[..1]).unwrap();
+    assert!(written[0].starts_with("✅ Updated data.bin"), "{}", written[0]);
+}
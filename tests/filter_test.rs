@@ -0,0 +1,81 @@
+use ai_coder_interface_rs::app::App;
+use ai_coder_interface_rs::handlers::CommandMode;
+
+/// A session with an AI, a bash and a command section, in that order
+const SESSION: &str = "🚀 AI Coder Interface\n\n\
+━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
+❯ how do I list files?\n\
+Use ls.\n\
+━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
+$ !ls\n\
+Cargo.toml\n\
+━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
+/ /version\n\
+v0.1.0\n";
+
+fn app() -> App {
+    let mut app = App::new();
+    app.output = SESSION.to_string();
+    app
+}
+
+/// Whether each section of the session is shown
+fn visible(app: &App) -> Vec<bool> {
+    app.output_sections()
+        .iter()
+        .map(|span| app.is_section_visible(span))
+        .collect()
+}
+
+#[test]
+fn test_filter_arguments_are_parsed() {
+    let mut app = app();
+    app.handle_filter("ai");
+    assert_eq!(app.output_filter, vec![CommandMode::AI]);
+
+    // Repeated names count once, in the order given
+    app.handle_filter("bash ai bash");
+    assert_eq!(app.output_filter, vec![CommandMode::Bash, CommandMode::AI]);
+    assert_eq!(app.filter_label().as_deref(), Some("bash+ai"));
+
+    // `all` clears the filter, wherever it appears
+    app.handle_filter("cmd all");
+    assert!(app.output_filter.is_empty());
+    assert_eq!(app.filter_label(), None);
+
+    // An unknown name leaves the filter as it was
+    app.handle_filter("cmd");
+    app.handle_filter("ai logs");
+    assert_eq!(app.output_filter, vec![CommandMode::Command]);
+    assert!(app.output.ends_with("Error: Usage: /filter ai|bash|cmd|all\n"));
+}
+
+#[test]
+fn test_filter_hides_other_sections() {
+    let mut app = app();
+    assert_eq!(visible(&app), vec![true, true, true]);
+    app.handle_filter("ai");
+    assert_eq!(visible(&app), vec![true, false, false]);
+    app.handle_filter("bash cmd");
+    assert_eq!(visible(&app), vec![false, true, true]);
+    app.handle_filter("all");
+    assert_eq!(visible(&app), vec![true, true, true]);
+}
+
+#[test]
+fn test_filter_is_confirmed_in_a_toast() {
+    let mut app = app();
+    app.handle_filter("ai");
+    // The confirmation would be a command section, hidden by the filter itself
+    assert_eq!(app.output, SESSION);
+    assert_eq!(
+        app.toast.as_ref().map(|toast| toast.message.as_str()),
+        Some("🔍 Showing only ai output")
+    );
+
+    app.handle_filter("");
+    assert_eq!(
+        app.toast.as_ref().map(|toast| toast.message.as_str()),
+        Some("🔍 Showing ai output. Usage: /filter ai|bash|cmd|all")
+    );
+}
@@ -0,0 +1,82 @@
+use ai_coder_interface_rs::handlers::git::{
+    clean_commit_message, commit_message_prompt, commit_with_editor,
+};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn git(root: &Path, args: &[&str]) -> String {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(root)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {:?} failed", args);
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
+fn repo() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    git(dir.path(), &["init", "-q"]);
+    git(dir.path(), &["config", "user.name", "Test"]);
+    git(dir.path(), &["config", "user.email", "test@example.com"]);
+    dir
+}
+
+#[test]
+fn test_prompt_needs_staged_changes() {
+    let dir = repo();
+    fs::write(dir.path().join("notes.txt"), "unstaged\n").unwrap();
+    let error = commit_message_prompt(dir.path()).unwrap_err().to_string();
+    assert!(error.contains("No staged changes"));
+}
+
+#[test]
+fn test_prompt_carries_the_staged_diff() {
+    let dir = repo();
+    fs::write(dir.path().join("notes.txt"), "first line\n").unwrap();
+    git(dir.path(), &["add", "notes.txt"]);
+
+    let prompt = commit_message_prompt(dir.path()).unwrap();
+    assert!(prompt.contains("Conventional Commits"));
+    assert!(prompt.contains("notes.txt | 1 +"));
+    assert!(prompt.contains("+first line"));
+    assert!(!prompt.contains("[diff truncated]"));
+}
+
+#[test]
+fn test_large_diffs_are_truncated() {
+    let dir = repo();
+    fs::write(dir.path().join("big.txt"), "é line\n".repeat(10_000)).unwrap();
+    git(dir.path(), &["add", "big.txt"]);
+
+    let prompt = commit_message_prompt(dir.path()).unwrap();
+    assert!(prompt.ends_with("[diff truncated]\n"));
+    assert!(prompt.len() < 50_000);
+}
+
+#[test]
+fn test_fences_and_whitespace_are_stripped() {
+    assert_eq!(
+        clean_commit_message("\n```text\nfix(ui): wrap long lines\n\nBody text.\n```\n"),
+        "fix(ui): wrap long lines\n\nBody text."
+    );
+    assert_eq!(clean_commit_message("  feat: add /x  "), "feat: add /x");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_commit_keeps_the_message_when_the_editor_does() {
+    let dir = repo();
+    // An editor that leaves the message as it is
+    git(dir.path(), &["config", "core.editor", "true"]);
+    fs::write(dir.path().join("notes.txt"), "first line\n").unwrap();
+    git(dir.path(), &["add", "notes.txt"]);
+
+    let status = commit_with_editor(dir.path(), "docs: add notes").unwrap();
+    assert!(status.success());
+    assert_eq!(
+        git(dir.path(), &["log", "-1", "--format=%s"]),
+        "docs: add notes\n"
+    );
+}
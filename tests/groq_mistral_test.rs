@@ -0,0 +1,71 @@
+use ai_coder_interface_rs::ai::openai_compat::{ReportedUsage, parse_stream_line};
+use ai_coder_interface_rs::ai::pricing::model_costs;
+use ai_coder_interface_rs::ai::{ModelCosts, Provider};
+use ai_coder_interface_rs::config::AIConfig;
+
+#[test]
+fn test_groq_usage_is_read_from_its_extension() {
+    let last = parse_stream_line(
+        r#"data: {"model":"llama-3.3-70b-versatile","choices":[{"delta":{}}],"x_groq":{"id":"req_1","usage":{"prompt_tokens":20,"completion_tokens":5,"total_tokens":25}}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        last.usage,
+        Some(ReportedUsage {
+            prompt_tokens: 20,
+            completion_tokens: 5,
+            total_tokens: 25,
+            cost: None,
+        })
+    );
+
+    // Other chunks carry the extension without usage
+    let chunk = parse_stream_line(
+        r#"data: {"choices":[{"delta":{"content":"fn"}}],"x_groq":{"id":"req_1"}}"#,
+    )
+    .unwrap();
+    assert_eq!(chunk.content, "fn");
+    assert_eq!(chunk.usage, None);
+}
+
+#[test]
+fn test_hosted_open_models_have_prices() {
+    assert_eq!(
+        model_costs(Provider::Groq, "llama-3.1-8b-instant"),
+        ModelCosts {
+            prompt_cost_per_1k: 0.000_05,
+            completion_cost_per_1k: 0.000_08,
+        }
+    );
+    assert_eq!(
+        model_costs(Provider::Mistral, "codestral-latest"),
+        ModelCosts {
+            prompt_cost_per_1k: 0.000_3,
+            completion_cost_per_1k: 0.000_9,
+        }
+    );
+    assert!(model_costs(Provider::Mistral, "mistral-large-2411").prompt_cost_per_1k > 0.0);
+
+    let defaults = AIConfig::default();
+    let groq = defaults.groq.models.iter().map(|m| (Provider::Groq, m));
+    let mistral = defaults
+        .mistral
+        .models
+        .iter()
+        .map(|m| (Provider::Mistral, m));
+    for (provider, model) in groq.chain(mistral) {
+        assert!(
+            model_costs(provider, &model.name).prompt_cost_per_1k > 0.0,
+            "{} has no price",
+            model.name
+        );
+    }
+}
+
+#[test]
+fn test_providers_parse_by_name() {
+    assert_eq!("groq".parse::<Provider>(), Ok(Provider::Groq));
+    assert_eq!("Mistral".parse::<Provider>(), Ok(Provider::Mistral));
+    assert_eq!(Provider::Groq.to_string(), "Groq");
+    assert!(!Provider::Mistral.is_local());
+}
@@ -0,0 +1,104 @@
+use ai_coder_interface_rs::app::App;
+use ai_coder_interface_rs::config::UiConfig;
+use ai_coder_interface_rs::handlers::CommandMode;
+use ai_coder_interface_rs::ui::hints::{
+    AI_PLACEHOLDER, BASH_PLACEHOLDER, HintContext, input_hint, mode_hint,
+};
+
+#[test]
+fn test_the_placeholder_names_the_prefixes_of_the_mode() {
+    assert_eq!(input_hint(&HintContext::default()), AI_PLACEHOLDER);
+    let bash = HintContext {
+        bash_default: true,
+        ..Default::default()
+    };
+    assert_eq!(input_hint(&bash), BASH_PLACEHOLDER);
+}
+
+#[test]
+fn test_running_work_and_selections_win_over_the_placeholder() {
+    let generating = HintContext {
+        generating: true,
+        running_bash: true,
+        files_to_save: true,
+        ..Default::default()
+    };
+    assert_eq!(input_hint(&generating), "Esc aborts the running generation");
+    let bash = HintContext {
+        running_bash: true,
+        bash_default: true,
+        ..Default::default()
+    };
+    assert_eq!(input_hint(&bash), "Esc kills the running command");
+    let selected = HintContext {
+        message_selected: true,
+        ..bash
+    };
+    assert!(input_hint(&selected).starts_with("y copies"));
+    let marked = HintContext {
+        messages_marked: true,
+        ..selected
+    };
+    assert!(input_hint(&marked).starts_with("Marked:"));
+    let searching = HintContext {
+        searching: true,
+        ..bash
+    };
+    assert!(input_hint(&searching).starts_with("n/N"));
+    // Esc aborts a running generation before it ends a search or unmarks
+    let busy = HintContext {
+        generating: true,
+        ..marked
+    };
+    assert_eq!(input_hint(&busy), "Esc aborts the running generation");
+    let files = HintContext {
+        files_to_save: true,
+        ..Default::default()
+    };
+    assert!(input_hint(&files).starts_with("Ctrl+S"));
+}
+
+#[test]
+fn test_hints_are_on_unless_configured() {
+    assert!(UiConfig::default().input_hints);
+    let config: UiConfig = serde_yaml::from_str("collapse_after_lines: 15\n").unwrap();
+    assert!(config.input_hints);
+}
+
+#[test]
+fn test_the_mode_is_detected_from_the_prefix() {
+    let mut app = App::new();
+    assert_eq!(
+        app.detect_mode("!ls -la"),
+        (CommandMode::Bash, "ls -la".to_string())
+    );
+    assert_eq!(
+        app.detect_mode("/help ai"),
+        (CommandMode::Command, "help ai".to_string())
+    );
+    assert_eq!(
+        app.detect_mode("explain this"),
+        (CommandMode::AI, "explain this".to_string())
+    );
+
+    // With bash as the default, plain input runs and `?` asks the AI
+    app.default_mode = CommandMode::Bash;
+    assert_eq!(app.detect_mode("ls"), (CommandMode::Bash, "ls".to_string()));
+    assert_eq!(
+        app.detect_mode("? why"),
+        (CommandMode::AI, "why".to_string())
+    );
+    assert_eq!(
+        app.detect_mode("/help"),
+        (CommandMode::Command, "help".to_string())
+    );
+}
+
+#[test]
+fn test_the_title_hint_follows_the_mode() {
+    let app = App::new();
+    let hint = |input: &str| mode_hint(&app.detect_mode(input).0);
+    assert_eq!(hint("!git status"), "will run as BASH");
+    assert_eq!(hint("/clear"), "will run as COMMAND");
+    assert_eq!(hint("what does this do?"), "will be sent to AI");
+}
@@ -0,0 +1,103 @@
+use ai_coder_interface_rs::ai::TokenUsage;
+use ai_coder_interface_rs::ai::pricing::image_cost;
+use ai_coder_interface_rs::config::ImageDisplay;
+use ai_coder_interface_rs::utils::image::{
+    Graphics, file_name, graphics_from_env, kitty_sequence, parse_size, save,
+};
+use chrono::{Local, TimeZone};
+
+fn usage(prompt_tokens: usize, completion_tokens: usize) -> TokenUsage {
+    TokenUsage {
+        prompt_tokens,
+        completion_tokens,
+        total_tokens: prompt_tokens + completion_tokens,
+    }
+}
+
+#[test]
+fn test_images_are_priced_per_size_or_by_tokens() {
+    assert_eq!(image_cost("dall-e-3", "1024x1024", &usage(0, 0)), 0.04);
+    assert_eq!(image_cost("dall-e-3", "1792x1024", &usage(0, 0)), 0.08);
+    assert_eq!(image_cost("dall-e-2", "256x256", &usage(0, 0)), 0.016);
+    let tokens = image_cost("gpt-image-1", "1024x1024", &usage(1000, 4000));
+    assert!((tokens - 0.165).abs() < 1e-9);
+    assert_eq!(image_cost("stable-diffusion", "512x512", &usage(0, 0)), 0.0);
+}
+
+#[test]
+fn test_sizes_and_file_names() {
+    assert_eq!(parse_size("1024x768"), Some((1024, 768)));
+    assert_eq!(parse_size(" 512 X 512 "), Some((512, 512)));
+    assert_eq!(parse_size("0x512"), None);
+    assert_eq!(parse_size("large"), None);
+
+    let time = Local.with_ymd_and_hms(2024, 5, 1, 9, 30, 0).unwrap();
+    assert_eq!(
+        file_name("A red fox, in the snow!", time),
+        "20240501-093000-a-red-fox-in-the-snow.png"
+    );
+    assert_eq!(file_name("???", time), "20240501-093000-image.png");
+    let long = file_name(&"word ".repeat(30), time);
+    assert!(long.len() < 64);
+}
+
+#[test]
+fn test_images_are_saved_under_the_output_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = save(&dir.path().join("images"), "fox.png", b"\x89PNG").unwrap();
+    assert_eq!(path, dir.path().join("images/fox.png"));
+    assert_eq!(std::fs::read(path).unwrap(), b"\x89PNG");
+}
+
+#[test]
+fn test_terminal_graphics_are_detected() {
+    let env = |vars: &'static [(&'static str, &'static str)]| {
+        move |name: &str| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        }
+    };
+    let auto = ImageDisplay::Auto;
+    assert_eq!(
+        graphics_from_env(auto, env(&[("TERM", "xterm-kitty")]), false),
+        Graphics::Kitty
+    );
+    assert_eq!(
+        graphics_from_env(auto, env(&[("TERM_PROGRAM", "WezTerm")]), false),
+        Graphics::Kitty
+    );
+    assert_eq!(
+        graphics_from_env(auto, env(&[("TERM", "foot")]), true),
+        Graphics::Sixel
+    );
+    // Sixel needs img2sixel
+    assert_eq!(
+        graphics_from_env(auto, env(&[("TERM", "foot")]), false),
+        Graphics::None
+    );
+    assert_eq!(
+        graphics_from_env(auto, env(&[("TERM", "xterm-256color")]), true),
+        Graphics::None
+    );
+    assert_eq!(
+        graphics_from_env(ImageDisplay::Off, env(&[("TERM", "xterm-kitty")]), true),
+        Graphics::None
+    );
+    assert_eq!(
+        graphics_from_env(ImageDisplay::Kitty, env(&[]), false),
+        Graphics::Kitty
+    );
+}
+
+#[test]
+fn test_kitty_images_are_sent_in_chunks() {
+    let small = kitty_sequence(b"png");
+    assert_eq!(small, "\x1b_Ga=T,f=100,m=0;cG5n\x1b\\");
+
+    let large = kitty_sequence(&vec![0u8; 6000]);
+    let chunks: Vec<&str> = large.split("\x1b\\").filter(|s| !s.is_empty()).collect();
+    assert_eq!(chunks.len(), 2);
+    assert!(chunks[0].starts_with("\x1b_Ga=T,f=100,m=1;"));
+    assert!(chunks[1].starts_with("\x1b_Gm=0;"));
+}
@@ -0,0 +1,217 @@
+use ai_coder_interface_rs::ai::Provider;
+use ai_coder_interface_rs::ai::conversation::{Message, Role};
+use ai_coder_interface_rs::config::AIConfig;
+use ai_coder_interface_rs::handlers::CommandMode;
+use ai_coder_interface_rs::handlers::import::{
+    self, ImportedSettings, Tool, apply_settings, parse_aider_config, parse_aider_history,
+    parse_llm_logs, provider_for_model,
+};
+use ai_coder_interface_rs::utils::transcript::Transcript;
+use chrono::{Local, TimeZone};
+
+fn message(role: Role, content: &str) -> Message {
+    Message {
+        role,
+        content: content.to_string(),
+        source: None,
+    }
+}
+
+const AIDER_HISTORY: &str = "
+# aider chat started at 2024-05-15 10:00:00
+
+> Aider v0.35.0
+> Models: gpt-4o with diff edit format, weak model gpt-4o-mini
+> Git repo: .git with 12 files
+
+#### explain the parser
+#### briefly
+
+It splits the input into tokens.
+
+> Tokens: 1.2k sent, 80 received.
+
+#### and the lexer?
+
+It reads characters.
+
+# aider chat started at 2024-05-16 09:30:00
+
+> Aider v0.35.0
+";
+
+#[test]
+fn test_aider_history_becomes_conversations() {
+    let conversations = parse_aider_history(AIDER_HISTORY);
+    // The second run has no prompts
+    assert_eq!(conversations.len(), 1);
+    let conversation = &conversations[0];
+    assert_eq!(
+        conversation.started,
+        Local.with_ymd_and_hms(2024, 5, 15, 10, 0, 0).unwrap()
+    );
+    assert_eq!(conversation.model.as_deref(), Some("gpt-4o"));
+    assert_eq!(
+        conversation.messages,
+        vec![
+            message(Role::User, "explain the parser\nbriefly"),
+            message(Role::Assistant, "It splits the input into tokens."),
+            message(Role::User, "and the lexer?"),
+            message(Role::Assistant, "It reads characters."),
+        ]
+    );
+}
+
+#[test]
+fn test_imported_sessions_read_like_live_ones() {
+    let conversation = parse_aider_history(AIDER_HISTORY).remove(0);
+    let session = import::to_session(Tool::Aider, &conversation, "aider-1".to_string());
+    assert_eq!(session.title, "explain the parser briefly");
+    assert_eq!(session.messages, 2);
+    assert_eq!(
+        session.history,
+        ["explain the parser\nbriefly", "and the lexer?"]
+    );
+    assert_eq!(session.models_used, ["gpt-4o"]);
+
+    let transcript = Transcript::parse(&session.output);
+    assert_eq!(transcript.exchanges.len(), 2);
+    assert_eq!(transcript.exchanges[1].mode, CommandMode::AI);
+    assert_eq!(transcript.exchanges[1].input, "and the lexer?");
+    assert!(
+        transcript.exchanges[1]
+            .output
+            .contains("It reads characters.")
+    );
+}
+
+#[test]
+fn test_models_are_matched_to_providers() {
+    assert_eq!(
+        provider_for_model("ollama/qwen2.5-coder"),
+        (Provider::Ollama, "qwen2.5-coder".to_string())
+    );
+    assert_eq!(
+        provider_for_model("sonnet"),
+        (Provider::Anthropic, "claude-3-5-sonnet-latest".to_string())
+    );
+    assert_eq!(
+        provider_for_model("claude-3-haiku-20240307").0,
+        Provider::Anthropic
+    );
+    assert_eq!(
+        provider_for_model("gpt-4o-mini"),
+        (Provider::OpenAI, "gpt-4o-mini".to_string())
+    );
+}
+
+#[test]
+fn test_aider_config_with_a_base_url_goes_to_the_custom_provider() {
+    let settings = parse_aider_config(
+        "model: deepseek-coder\nopenai-api-key: sk-local\nopenai-api-base: http://gpu:8000/v1\napi-key:\n  - groq=gsk_123\n",
+    )
+    .unwrap();
+    assert_eq!(
+        settings,
+        ImportedSettings {
+            model: Some((Provider::Custom, "deepseek-coder".to_string())),
+            api_keys: vec![
+                (Provider::OpenAI, "sk-local".to_string()),
+                (Provider::Groq, "gsk_123".to_string()),
+                (Provider::Custom, "sk-local".to_string()),
+            ],
+            endpoint: Some("http://gpu:8000/v1".to_string()),
+        }
+    );
+}
+
+#[test]
+fn test_llm_logs_are_grouped_by_conversation() {
+    let logs = r#"[
+        {"model": "gpt-4o-mini", "prompt": "second", "response": "B", "conversation_id": "c1",
+         "conversation_name": "Greetings", "datetime_utc": "2024-05-15T10:01:00.000000"},
+        {"model": "gpt-4o-mini", "prompt": "first", "response": "A", "conversation_id": "c1",
+         "conversation_name": "Greetings", "datetime_utc": "2024-05-15T10:00:00.000000"},
+        {"model": "claude-3.5-sonnet", "prompt": "other", "response": "C", "conversation_id": "c2",
+         "conversation_name": null, "datetime_utc": "2024-05-15T11:00:00.000000"}
+    ]"#;
+    let conversations = parse_llm_logs(logs).unwrap();
+    assert_eq!(conversations.len(), 2);
+    assert_eq!(conversations[0].title.as_deref(), Some("Greetings"));
+    assert_eq!(
+        conversations[0].messages,
+        vec![
+            message(Role::User, "first"),
+            message(Role::Assistant, "A"),
+            message(Role::User, "second"),
+            message(Role::Assistant, "B"),
+        ]
+    );
+    assert_eq!(conversations[1].model.as_deref(), Some("claude-3.5-sonnet"));
+    assert!(parse_llm_logs("not json").is_err());
+
+    // Sessions started in the same second get their own ids
+    let mut twins = conversations.clone();
+    twins[1].started = twins[0].started;
+    let ids = import::session_ids(Tool::Llm, &twins);
+    assert_ne!(ids[0], ids[1]);
+    assert!(ids[1].ends_with("-2"));
+}
+
+#[test]
+fn test_sgpt_settings_and_chats_are_read() {
+    let values = import::parse_sgptrc(
+        "CHAT_CACHE_PATH=/tmp/chat_cache\nDEFAULT_MODEL=gpt-4o\nOPENAI_API_KEY=sk-abc\nAPI_BASE_URL=default\n",
+    );
+    let settings = import::sgpt_settings(&values);
+    assert_eq!(
+        settings.model,
+        Some((Provider::OpenAI, "gpt-4o".to_string()))
+    );
+    assert_eq!(settings.endpoint, None);
+    assert_eq!(
+        settings.api_keys,
+        [(Provider::OpenAI, "sk-abc".to_string())]
+    );
+
+    let chat = import::parse_sgpt_chat(
+        "python",
+        r#"[{"role": "system", "content": "You are ShellGPT"},
+            {"role": "user", "content": "list files"},
+            {"role": "assistant", "content": "ls -la"}]"#,
+        Local::now(),
+    )
+    .unwrap();
+    assert_eq!(chat.title.as_deref(), Some("python"));
+    assert_eq!(
+        chat.messages,
+        vec![
+            message(Role::User, "list files"),
+            message(Role::Assistant, "ls -la")
+        ]
+    );
+}
+
+#[test]
+fn test_settings_fill_in_only_what_is_missing() {
+    let mut ai = AIConfig::default();
+    ai.anthropic.api_key = "configured".to_string();
+    let active = ai.active_provider;
+    let settings = ImportedSettings {
+        model: Some((Provider::Anthropic, "claude-3-5-haiku-latest".to_string())),
+        api_keys: vec![
+            (Provider::OpenAI, "sk-new".to_string()),
+            (Provider::Anthropic, "sk-ant-new".to_string()),
+        ],
+        endpoint: None,
+    };
+    let notes = apply_settings(&mut ai, &settings);
+
+    assert_eq!(ai.openai.api_key, "sk-new");
+    assert_eq!(ai.anthropic.api_key, "configured");
+    assert_eq!(ai.active_provider, active);
+    let model = &ai.anthropic.models[ai.anthropic.current_model_index];
+    assert_eq!(model.name, "claude-3-5-haiku-latest");
+    assert_eq!(notes.len(), 3);
+    assert!(notes[1].contains("Kept"));
+}
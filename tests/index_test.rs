@@ -0,0 +1,176 @@
+use ai_coder_interface_rs::config::{IndexConfig, WorkspaceConfig};
+use ai_coder_interface_rs::utils::index::{
+    Chunk, FileEntry, FileStamp, IndexActivity, IndexStatus, ProjectIndex, chunk_text, cosine,
+    format_matches, scan,
+};
+use chrono::{Duration, Local};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+fn stamp(modified: i64, size: u64) -> FileStamp {
+    FileStamp { modified, size }
+}
+
+fn entry(stamp: FileStamp, vectors: &[(&str, Vec<f32>)]) -> FileEntry {
+    FileEntry {
+        stamp,
+        chunks: vectors
+            .iter()
+            .enumerate()
+            .map(|(idx, (text, vector))| Chunk {
+                start_line: idx * 10 + 1,
+                end_line: idx * 10 + 10,
+                text: text.to_string(),
+                vector: vector.clone(),
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn test_only_new_and_modified_files_are_embedded_again() {
+    let mut index = ProjectIndex::new("nomic-embed-text");
+    index
+        .files
+        .insert(PathBuf::from("src/a.rs"), entry(stamp(100, 10), &[]));
+    index
+        .files
+        .insert(PathBuf::from("src/b.rs"), entry(stamp(100, 10), &[]));
+    index
+        .files
+        .insert(PathBuf::from("src/gone.rs"), entry(stamp(100, 10), &[]));
+
+    let scanned: BTreeMap<PathBuf, FileStamp> = [
+        ("src/a.rs", stamp(100, 10)),
+        ("src/b.rs", stamp(200, 12)),
+        ("src/new.rs", stamp(300, 5)),
+    ]
+    .into_iter()
+    .map(|(path, stamp)| (PathBuf::from(path), stamp))
+    .collect();
+
+    let changes = index.changes(&scanned);
+    assert_eq!(
+        changes.changed,
+        vec![PathBuf::from("src/b.rs"), PathBuf::from("src/new.rs")]
+    );
+    assert_eq!(changes.removed, vec![PathBuf::from("src/gone.rs")]);
+    assert!(!changes.is_empty());
+}
+
+#[test]
+fn test_files_are_cut_into_chunks_of_lines() {
+    let text = (1..=25)
+        .map(|n| format!("line {}", n))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let chunks = chunk_text(&text, 10);
+    assert_eq!(chunks.len(), 3);
+    assert_eq!((chunks[0].0, chunks[0].1), (1, 10));
+    assert_eq!((chunks[2].0, chunks[2].1), (21, 25));
+    assert!(chunks[2].2.ends_with("line 25"));
+
+    // Blank stretches are not embedded
+    let chunks = chunk_text("a\n\n\n\n\nb", 2);
+    assert_eq!(
+        chunks
+            .iter()
+            .map(|(start, _, _)| *start)
+            .collect::<Vec<_>>(),
+        vec![1, 5]
+    );
+}
+
+#[test]
+fn test_searches_rank_chunks_by_similarity() {
+    assert!((cosine(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+    assert_eq!(cosine(&[1.0, 0.0], &[0.0, 1.0]), 0.0);
+    assert_eq!(cosine(&[0.0, 0.0], &[1.0, 1.0]), 0.0);
+    assert_eq!(cosine(&[1.0], &[1.0, 0.0]), 0.0);
+
+    let mut index = ProjectIndex::new("m");
+    index.files.insert(
+        PathBuf::from("src/retry.rs"),
+        entry(
+            stamp(1, 1),
+            &[
+                ("fn retry()", vec![0.9, 0.1]),
+                ("fn other()", vec![0.0, 1.0]),
+            ],
+        ),
+    );
+    index.files.insert(
+        PathBuf::from("src/net.rs"),
+        entry(stamp(1, 1), &[("fn backoff()", vec![0.7, 0.3])]),
+    );
+    assert_eq!(index.chunk_count(), 3);
+
+    let matches = index.search(&[1.0, 0.0], 2);
+    assert_eq!(matches.len(), 2);
+    assert_eq!(matches[0].text, "fn retry()");
+    assert_eq!(matches[1].text, "fn backoff()");
+    assert_eq!(
+        format_matches(&matches[..1]),
+        "src/retry.rs:1-10\n```\nfn retry()\n```"
+    );
+}
+
+#[test]
+fn test_indexes_are_saved_and_loaded() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("index/abc.json");
+    assert!(ProjectIndex::load(&path).unwrap().is_none());
+
+    let mut index = ProjectIndex::new("m");
+    index.files.insert(
+        PathBuf::from("a.rs"),
+        entry(stamp(5, 6), &[("x", vec![0.5])]),
+    );
+    index.save(&path).unwrap();
+    let loaded = ProjectIndex::load(&path).unwrap().unwrap();
+    assert_eq!(loaded.model, "m");
+    assert_eq!(loaded.files[&PathBuf::from("a.rs")].stamp, stamp(5, 6));
+    assert_eq!(loaded.chunk_count(), 1);
+}
+
+#[test]
+fn test_scans_skip_denied_large_and_other_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    std::fs::create_dir_all(root.join("src")).unwrap();
+    std::fs::create_dir_all(root.join("target")).unwrap();
+    std::fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+    std::fs::write(root.join("src/big.rs"), "x".repeat(500)).unwrap();
+    std::fs::write(root.join("logo.png"), "png").unwrap();
+    std::fs::write(root.join("secret.pem"), "key").unwrap();
+    std::fs::write(root.join("target/out.rs"), "fn built() {}").unwrap();
+
+    let config = IndexConfig {
+        max_file_bytes: 100,
+        extensions: vec!["rs".to_string(), "pem".to_string()],
+        ..IndexConfig::default()
+    };
+    let workspace = WorkspaceConfig {
+        root: String::new(),
+        deny: vec!["*.pem".to_string()],
+    };
+    let scanned = scan(&root, &config, &workspace);
+    assert_eq!(
+        scanned.keys().cloned().collect::<Vec<_>>(),
+        vec![PathBuf::from("src/main.rs")]
+    );
+    assert_eq!(scanned[&PathBuf::from("src/main.rs")].size, 12);
+}
+
+#[test]
+fn test_the_status_bar_shows_freshness_or_progress() {
+    let now = Local::now();
+    let mut status = IndexStatus::default();
+    assert_eq!(status.label(now), "📇 pending");
+    status.updated = Some(now - Duration::minutes(3));
+    assert_eq!(status.label(now), "📇 3m ago");
+    status.activity = IndexActivity::Indexing { done: 4, total: 9 };
+    assert_eq!(status.label(now), "📇 4/9");
+    status.activity = IndexActivity::Paused;
+    assert_eq!(status.label(now), "📇 paused");
+}
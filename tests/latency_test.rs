@@ -0,0 +1,106 @@
+use ai_coder_interface_rs::ai::types::{ProgressStats, Provider};
+use ai_coder_interface_rs::utils::latency::{
+    ExchangeLatency, Latency, Percentiles, format_latency, percentile, summarize,
+};
+use std::time::Duration;
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+fn exchange(
+    provider: Provider,
+    model: &str,
+    first_token: Option<u64>,
+    total: u64,
+) -> ExchangeLatency {
+    ExchangeLatency {
+        provider,
+        model: model.to_string(),
+        latency: Latency {
+            first_token: first_token.map(ms),
+            total: ms(total),
+        },
+    }
+}
+
+#[test]
+fn test_percentiles_use_the_nearest_rank() {
+    let sorted: Vec<Duration> = (1..=20).map(|n| ms(n * 100)).collect();
+    assert_eq!(percentile(&sorted, 50.0), Some(ms(1000)));
+    assert_eq!(percentile(&sorted, 95.0), Some(ms(1900)));
+    assert_eq!(percentile(&sorted, 100.0), Some(ms(2000)));
+    assert_eq!(percentile(&sorted[..1], 95.0), Some(ms(100)));
+    assert_eq!(percentile(&[], 50.0), None);
+
+    // Unsorted input is sorted first
+    assert_eq!(
+        Percentiles::of(&[ms(900), ms(100), ms(300)]),
+        Some(Percentiles {
+            p50: ms(300),
+            p95: ms(900)
+        })
+    );
+}
+
+#[test]
+fn test_latency_is_summarized_per_provider_and_model() {
+    let summaries = summarize(&[
+        exchange(Provider::Ollama, "llama3", Some(200), 3000),
+        exchange(Provider::OpenAI, "gpt-4o", None, 1500),
+        exchange(Provider::Ollama, "llama3", Some(400), 5000),
+        exchange(Provider::Ollama, "qwen2.5-coder", Some(100), 800),
+    ]);
+    let names: Vec<String> = summaries
+        .iter()
+        .map(|summary| format!("{}/{}", summary.provider, summary.model))
+        .collect();
+    assert_eq!(
+        names,
+        ["Ollama/llama3", "OpenAI/gpt-4o", "Ollama/qwen2.5-coder"]
+    );
+
+    assert_eq!(summaries[0].exchanges, 2);
+    assert_eq!(
+        summaries[0].first_token,
+        Some(Percentiles {
+            p50: ms(200),
+            p95: ms(400)
+        })
+    );
+    assert_eq!(summaries[0].total.p95, ms(5000));
+    // Providers that do not stream have no first-token latency
+    assert_eq!(summaries[1].first_token, None);
+    assert_eq!(summaries[1].total.p50, ms(1500));
+}
+
+#[test]
+fn test_progress_records_the_first_token_and_the_end() {
+    let mut progress = ProgressStats::new();
+    let start = progress.start_time;
+    assert_eq!(progress.first_token_latency(), None);
+
+    progress.update(0);
+    assert_eq!(progress.first_token_at, None);
+    progress.update(1);
+    let first = progress.first_token_at.unwrap();
+    progress.update(5);
+    assert_eq!(progress.first_token_at, Some(first));
+
+    progress.complete();
+    let finished = progress.finished_at.unwrap();
+    progress.complete();
+    assert_eq!(progress.finished_at, Some(finished));
+
+    progress.start_time = start - chrono::Duration::milliseconds(250);
+    let latency = Latency::of(&progress);
+    assert!(latency.first_token.unwrap() >= ms(250));
+    assert!(latency.total >= latency.first_token.unwrap());
+}
+
+#[test]
+fn test_latencies_are_shown_in_readable_units() {
+    assert_eq!(format_latency(ms(850)), "850ms");
+    assert_eq!(format_latency(ms(4_230)), "4.2s");
+    assert_eq!(format_latency(ms(72_000)), "1m 12s");
+}
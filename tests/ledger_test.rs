@@ -0,0 +1,122 @@
+use ai_coder_interface_rs::ai::Provider;
+use ai_coder_interface_rs::config::{CostLevel, UsageConfig};
+use ai_coder_interface_rs::utils::ledger::{self, LedgerEntry};
+use chrono::{Duration, Local, TimeZone};
+
+fn entry(timestamp: chrono::DateTime<Local>, provider: Provider, cost: f64) -> LedgerEntry {
+    LedgerEntry {
+        timestamp,
+        provider,
+        model: "model".to_string(),
+        prompt_tokens: 1_000,
+        completion_tokens: 500,
+        cost,
+        tag: None,
+    }
+}
+
+fn tagged(timestamp: chrono::DateTime<Local>, tag: Option<&str>, cost: f64) -> LedgerEntry {
+    LedgerEntry {
+        tag: tag.map(str::to_string),
+        ..entry(timestamp, Provider::OpenAI, cost)
+    }
+}
+
+#[test]
+fn test_ledger_is_appended_across_sessions() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("usage.jsonl");
+    assert!(ledger::load(&path).unwrap().is_empty());
+
+    let now = Local::now();
+    ledger::append(&path, &entry(now, Provider::OpenAI, 0.01)).unwrap();
+    ledger::append(&path, &entry(now, Provider::Ollama, 0.0)).unwrap();
+    let entries = ledger::load(&path).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].provider, Provider::Ollama);
+}
+
+#[test]
+fn test_summary_covers_yesterday_and_this_week() {
+    // A Wednesday, so Monday and Tuesday count towards this week
+    let now = Local.with_ymd_and_hms(2024, 5, 15, 10, 0, 0).unwrap();
+    let yesterday = now - Duration::days(1);
+    let entries = vec![
+        entry(yesterday, Provider::OpenAI, 0.02),
+        entry(yesterday, Provider::OpenAI, 0.03),
+        entry(now - Duration::days(2), Provider::Ollama, 0.0),
+        entry(now, Provider::Anthropic, 0.1),
+        entry(now - Duration::days(7), Provider::OpenAI, 5.0),
+    ];
+
+    let summary = ledger::usage_summary(&entries, now).unwrap();
+    assert_eq!(
+        summary,
+        "📊 Yesterday: OpenAI 3,000 tokens $0.0500\n   \
+         This week: Anthropic 1,500 tokens $0.1000 · OpenAI 3,000 tokens $0.0500 · Ollama 1,500 tokens free"
+    );
+    assert!(ledger::usage_summary(&entries[4..], now).is_none());
+}
+
+#[test]
+fn test_cost_levels_follow_the_thresholds() {
+    let config = UsageConfig::default();
+    assert_eq!(config.cost_level(0.0), CostLevel::Normal);
+    assert_eq!(config.cost_level(config.cost_warning), CostLevel::Warning);
+    assert_eq!(config.cost_level(config.cost_alert + 1.0), CostLevel::Alert);
+
+    let only_alert = UsageConfig {
+        cost_warning: 0.0,
+        cost_alert: 0.5,
+        ..Default::default()
+    };
+    assert_eq!(only_alert.cost_level(0.4), CostLevel::Normal);
+    assert_eq!(only_alert.cost_level(0.5), CostLevel::Alert);
+}
+
+#[test]
+fn test_entries_without_a_tag_still_load() {
+    let line = r#"{"timestamp":"2024-05-15T10:00:00+00:00","provider":"OpenAI","model":"gpt-4o","prompt_tokens":10,"completion_tokens":5,"cost":0.01}"#;
+    let entry: LedgerEntry = serde_json::from_str(line).unwrap();
+    assert_eq!(entry.tag, None);
+    assert!(!serde_json::to_string(&entry).unwrap().contains("tag"));
+
+    let tagged = tagged(Local::now(), Some("acme"), 0.01);
+    assert!(
+        serde_json::to_string(&tagged)
+            .unwrap()
+            .contains(r#""tag":"acme""#)
+    );
+}
+
+#[test]
+fn test_spend_is_split_by_tag() {
+    let now = Local.with_ymd_and_hms(2024, 5, 15, 10, 0, 0).unwrap();
+    let entries = vec![
+        tagged(now, Some("acme"), 0.02),
+        tagged(now, None, 0.5),
+        tagged(now - Duration::days(1), Some("JIRA-42"), 0.1),
+        tagged(now, Some("acme"), 0.03),
+        tagged(now - Duration::days(30), Some("old"), 9.0),
+    ];
+
+    let totals = ledger::tag_totals(&entries, ledger::named_period("month", now).as_ref());
+    let tags: Vec<Option<&str>> = totals.iter().map(|total| total.tag.as_deref()).collect();
+    // Most expensive first, untagged work last
+    assert_eq!(tags, [Some("JIRA-42"), Some("acme"), None]);
+    assert_eq!(totals[1].responses, 2);
+    assert_eq!(totals[1].tokens, 3_000);
+    assert!((totals[1].cost - 0.05).abs() < 1e-9);
+
+    let today = ledger::tag_totals(&entries, ledger::named_period("today", now).as_ref());
+    assert_eq!(today.len(), 2);
+    assert_eq!(ledger::tag_totals(&entries, None).len(), 4);
+    assert!(ledger::named_period("fortnight", now).is_none());
+
+    let report = ledger::tag_report(&totals);
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[0].starts_with("  JIRA-42   "));
+    assert!(lines[2].starts_with("  (untagged)"));
+    assert!(lines[3].starts_with("  Total") && lines[3].ends_with("$0.6500"));
+}
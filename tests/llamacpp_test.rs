@@ -0,0 +1,68 @@
+use ai_coder_interface_rs::ai::llamacpp::{
+    check_available, find_models, model_dir, model_path, thread_count,
+};
+use ai_coder_interface_rs::ai::{AIError, Provider};
+use ai_coder_interface_rs::config::LlamaCppConfig;
+use std::fs;
+use tempfile::TempDir;
+
+#[test]
+fn test_models_are_found_by_name_in_the_model_dir() {
+    let dir = TempDir::new().unwrap();
+    let config = LlamaCppConfig {
+        model_dir: dir.path().display().to_string(),
+        ..Default::default()
+    };
+    assert_eq!(model_dir(&config), dir.path());
+    assert_eq!(
+        model_path(&config, "coder.gguf"),
+        dir.path().join("coder.gguf")
+    );
+    assert_eq!(
+        model_path(&config, "/opt/models/coder.gguf"),
+        std::path::PathBuf::from("/opt/models/coder.gguf")
+    );
+
+    fs::write(dir.path().join("b.gguf"), "").unwrap();
+    fs::write(dir.path().join("a.GGUF"), "").unwrap();
+    fs::write(dir.path().join("notes.txt"), "").unwrap();
+    fs::create_dir(dir.path().join("c.gguf")).unwrap();
+    assert_eq!(find_models(dir.path()), vec!["a.GGUF", "b.gguf"]);
+    assert!(find_models(&dir.path().join("missing")).is_empty());
+}
+
+#[test]
+fn test_zero_threads_means_all_cores() {
+    let config = LlamaCppConfig {
+        threads: 3,
+        ..Default::default()
+    };
+    assert_eq!(thread_count(&config), 3);
+    assert!(thread_count(&LlamaCppConfig::default()) >= 1);
+}
+
+#[test]
+fn test_availability_needs_the_feature_and_the_file() {
+    let dir = TempDir::new().unwrap();
+    let config = LlamaCppConfig {
+        model_dir: dir.path().display().to_string(),
+        ..Default::default()
+    };
+    let model = config.models[0].clone();
+    match check_available(&config, &model) {
+        Err(AIError::ConfigError(message)) if cfg!(feature = "llama") => {
+            assert!(message.contains("not found"), "{}", message)
+        }
+        Err(AIError::ConfigError(message)) => {
+            assert!(message.contains("--features llama"), "{}", message)
+        }
+        other => panic!("expected a config error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_llamacpp_is_a_local_provider() {
+    assert_eq!("llamacpp".parse::<Provider>(), Ok(Provider::LlamaCpp));
+    assert_eq!("llama.cpp".parse::<Provider>(), Ok(Provider::LlamaCpp));
+    assert!(Provider::LlamaCpp.is_local());
+}
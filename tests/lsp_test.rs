@@ -0,0 +1,152 @@
+use ai_coder_interface_rs::config::LspConfig;
+use ai_coder_interface_rs::utils::lsp::{
+    Location, encode, format_excerpt, matching_symbols, name_position, parse_hover,
+    parse_locations, parse_symbols, path_to_uri, read_message, split_symbol, uri_to_path,
+};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+
+#[tokio::test]
+async fn test_messages_are_framed_with_their_length() {
+    let first = json!({ "jsonrpc": "2.0", "id": 1, "result": "é" });
+    let second = json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} });
+    let framed = encode(&first);
+    let body = first.to_string();
+    // The length counts bytes, not characters
+    assert_eq!(body.len(), body.chars().count() + 1);
+    assert!(framed.starts_with(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes()));
+
+    let mut stream = framed;
+    stream.extend_from_slice(b"Content-Type: application/vscode-jsonrpc\r\n");
+    stream.extend_from_slice(&encode(&second)[..]);
+    let mut reader = &stream[..];
+    assert_eq!(read_message(&mut reader).await.unwrap(), Some(first));
+    assert_eq!(read_message(&mut reader).await.unwrap(), Some(second));
+    assert_eq!(read_message(&mut reader).await.unwrap(), None);
+}
+
+#[test]
+fn test_paths_round_trip_through_uris() {
+    let path = Path::new("/home/me/my project/src/main.rs");
+    let uri = path_to_uri(path);
+    assert_eq!(uri, "file:///home/me/my%20project/src/main.rs");
+    assert_eq!(uri_to_path(&uri), Some(path.to_path_buf()));
+    assert_eq!(
+        uri_to_path("file:///C:/src/lib.rs"),
+        Some(PathBuf::from("C:/src/lib.rs"))
+    );
+    assert_eq!(uri_to_path("https://example.com"), None);
+}
+
+#[test]
+fn test_locations_and_links_are_read() {
+    let range =
+        json!({ "start": { "line": 4, "character": 7 }, "end": { "line": 9, "character": 1 } });
+    let expected = Location {
+        path: PathBuf::from("/src/a.rs"),
+        start_line: 4,
+        start_character: 7,
+        end_line: 9,
+    };
+    let location = json!({ "uri": "file:///src/a.rs", "range": range });
+    assert_eq!(parse_locations(&location), vec![expected.clone()]);
+    let links = json!([{ "targetUri": "file:///src/a.rs", "targetRange": range, "targetSelectionRange": range }]);
+    assert_eq!(parse_locations(&links), vec![expected]);
+    assert!(parse_locations(&json!(null)).is_empty());
+}
+
+#[test]
+fn test_symbols_are_matched_by_name_and_container() {
+    let symbol = |name: &str, container: &str, line: u64| {
+        json!({
+            "name": name,
+            "kind": 12,
+            "containerName": container,
+            "location": {
+                "uri": "file:///src/app.rs",
+                "range": { "start": { "line": line, "character": 0 }, "end": { "line": line + 5, "character": 1 } }
+            }
+        })
+    };
+    let symbols = parse_symbols(&json!([
+        symbol("new", "App", 10),
+        symbol("new", "Conversation", 20),
+        symbol("new_session", "App", 30),
+        symbol("App", "", 1),
+    ]));
+    assert_eq!(symbols.len(), 4);
+    assert_eq!(symbols[3].container, None);
+
+    assert_eq!(split_symbol("App::new"), (Some("App"), "new"));
+    assert_eq!(split_symbol("app.new"), (Some("app"), "new"));
+    assert_eq!(split_symbol("new"), (None, "new"));
+
+    let found = matching_symbols(symbols.clone(), "App::new");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].location.start_line, 10);
+    assert_eq!(matching_symbols(symbols.clone(), "new").len(), 2);
+    // Case is only ignored when nothing matches exactly
+    assert_eq!(matching_symbols(symbols.clone(), "app").len(), 1);
+    assert!(matching_symbols(symbols, "Missing").is_empty());
+}
+
+#[test]
+fn test_hover_contents_become_text() {
+    assert_eq!(
+        parse_hover(&json!({ "contents": { "kind": "markdown", "value": "Creates an app" } })),
+        Some("Creates an app".to_string())
+    );
+    assert_eq!(
+        parse_hover(
+            &json!({ "contents": [{ "language": "rust", "value": "fn new() -> App" }, "Creates an app"] })
+        ),
+        Some("```rust\nfn new() -> App\n```\n\nCreates an app".to_string())
+    );
+    assert_eq!(parse_hover(&json!({ "contents": "" })), None);
+    assert_eq!(parse_hover(&json!(null)), None);
+}
+
+#[test]
+fn test_positions_point_at_the_name_of_a_symbol() {
+    let text = "#[derive(Debug)]\npub struct Åpp {\n    renew: bool,\n}\n\nimpl Åpp {\n    pub fn new() -> Self {\n";
+    let location = Location {
+        path: PathBuf::from("/src/app.rs"),
+        start_line: 5,
+        start_character: 0,
+        end_line: 7,
+    };
+    // `renew` is not a match for `new`; columns count UTF-16 units
+    assert_eq!(name_position(text, &location, "new"), (6, 11));
+    let location = Location {
+        start_line: 0,
+        end_line: 3,
+        ..location
+    };
+    assert_eq!(name_position(text, &location, "Åpp"), (1, 11));
+    assert_eq!(name_position(text, &location, "missing"), (0, 0));
+}
+
+#[test]
+fn test_excerpts_are_numbered_from_one_and_clamped() {
+    let text = "a\nb\nc\nd";
+    assert_eq!(
+        format_excerpt(Path::new("src/x.rs"), text, 1, 2),
+        "src/x.rs:2-3\n```\nb\nc\n```"
+    );
+    assert_eq!(
+        format_excerpt(Path::new("src/x.rs"), text, 2, 40),
+        "src/x.rs:3-4\n```\nc\nd\n```"
+    );
+}
+
+#[test]
+fn test_servers_are_configured_by_language() {
+    let config = LspConfig::default();
+    assert_eq!(config.servers["rust"].command, "rust-analyzer");
+    assert_eq!(config.servers["python"].extensions, vec!["py"]);
+
+    let yaml = "servers:\n  zig:\n    command: zls\n    extensions: [zig]\nstartup_timeout_secs: 30\ncontext_lines: 2\nmax_references: 10\n";
+    let config: LspConfig = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(config.servers.len(), 1);
+    assert!(config.servers["zig"].args.is_empty());
+}
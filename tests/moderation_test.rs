@@ -0,0 +1,167 @@
+use ai_coder_interface_rs::ai::AIError;
+use ai_coder_interface_rs::ai::moderation::{
+    Verdict, apply, classify, decide, matched, parse_command_output, parse_openai_response,
+};
+use ai_coder_interface_rs::config::{
+    AIConfig, ModerationAction, ModerationBackend, ModerationConfig,
+};
+use ai_coder_interface_rs::utils::audit::{self, AuditEntry, ModerationEntry};
+use chrono::Local;
+
+fn strings(items: &[&str]) -> Vec<String> {
+    items.iter().map(|item| item.to_string()).collect()
+}
+
+#[test]
+fn test_openai_categories_are_parsed() {
+    let body = serde_json::json!({
+        "id": "modr-1",
+        "results": [{
+            "flagged": true,
+            "categories": { "harassment": false, "violence": true, "violence/graphic": true },
+            "category_scores": { "harassment": 0.01, "violence": 0.92, "violence/graphic": 0.81 }
+        }]
+    });
+    assert_eq!(
+        parse_openai_response(&body).unwrap(),
+        strings(&["violence", "violence/graphic"])
+    );
+    assert!(parse_openai_response(&serde_json::json!({ "error": "nope" })).is_err());
+}
+
+#[test]
+fn test_command_output_is_split_on_commas_and_lines() {
+    assert_eq!(
+        parse_command_output("hate, violence\nviolence\n\n"),
+        strings(&["hate", "violence"])
+    );
+    assert!(parse_command_output("  \n").is_empty());
+}
+
+#[test]
+fn test_configured_categories_cover_subcategories() {
+    let flagged = strings(&["violence/graphic", "self-harm/intent", "hate"]);
+    assert_eq!(matched(&flagged, &[]), flagged);
+    assert_eq!(
+        matched(&flagged, &strings(&["Violence", "self-harm/instructions"])),
+        strings(&["violence/graphic"])
+    );
+    assert!(matched(&flagged, &strings(&["violent"])).is_empty());
+}
+
+#[test]
+fn test_verdicts_follow_the_action() {
+    let mut config = ModerationConfig {
+        enabled: true,
+        categories: strings(&["violence"]),
+        ..Default::default()
+    };
+    assert_eq!(decide(Ok(strings(&["hate"])), &config), Verdict::Passed);
+    let flagged = decide(Ok(strings(&["violence"])), &config);
+    assert_eq!(flagged, Verdict::Flagged(strings(&["violence"])));
+    assert!(!flagged.withholds(&config));
+    assert_eq!(
+        apply("Here you go\n", &flagged, &config),
+        "Here you go\n⚠️ Moderation flagged this response: violence"
+    );
+    assert!(!config.holds_responses());
+
+    config.action = ModerationAction::Block;
+    let blocked = decide(Ok(strings(&["violence"])), &config);
+    assert_eq!(blocked.label(), "blocked");
+    assert!(blocked.withholds(&config));
+    assert_eq!(
+        apply("Here you go", &blocked, &config),
+        "🚫 Response withheld by moderation: violence"
+    );
+    assert!(config.holds_responses());
+}
+
+#[test]
+fn test_unreachable_classifiers_fail_open_unless_configured() {
+    let mut config = ModerationConfig {
+        enabled: true,
+        ..Default::default()
+    };
+    let verdict = decide(Err(AIError::NetworkError("timed out".to_string())), &config);
+    assert_eq!(verdict.label(), "error");
+    assert!(verdict.categories().is_empty());
+    assert!(!verdict.withholds(&config));
+    assert!(apply("answer", &verdict, &config).starts_with("answer\n⚠️ Moderation unavailable"));
+
+    config.fail_closed = true;
+    assert!(verdict.withholds(&config));
+    assert!(apply("answer", &verdict, &config).starts_with("🚫"));
+    assert!(config.holds_responses());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_command_classifiers_read_the_response() {
+    let config = ModerationConfig {
+        enabled: true,
+        backend: ModerationBackend::Command,
+        command: "grep -q rm && echo destructive; true".to_string(),
+        ..Default::default()
+    };
+    let ai = AIConfig::default();
+    assert_eq!(
+        classify("run rm -rf build", &config, &ai).await.unwrap(),
+        strings(&["destructive"])
+    );
+    assert!(
+        classify("cargo build", &config, &ai)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let failing = ModerationConfig {
+        command: "exit 3".to_string(),
+        ..config
+    };
+    assert!(classify("anything", &failing, &ai).await.is_err());
+}
+
+#[test]
+fn test_verdicts_are_recorded_beside_edited_blocks() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("audit.jsonl");
+    let verdict = ModerationEntry {
+        kind: "moderation".to_string(),
+        timestamp: Local::now(),
+        session_id: "20240501-090000".to_string(),
+        model: "OpenAI/gpt-4o".to_string(),
+        backend: "openai".to_string(),
+        verdict: "blocked".to_string(),
+        categories: strings(&["violence"]),
+        error: None,
+    };
+    audit::append(&path, &verdict).unwrap();
+    let block = AuditEntry {
+        timestamp: Local::now(),
+        session_id: "20240501-090000".to_string(),
+        response: 1,
+        prompt: "clean up".to_string(),
+        original: "rm -rf target".to_string(),
+        command: "cargo clean".to_string(),
+    };
+    audit::append(&path, &block).unwrap();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    let first: ModerationEntry = serde_json::from_str(content.lines().next().unwrap()).unwrap();
+    assert_eq!(first, verdict);
+    assert!(!content.contains("\"error\""));
+    assert_eq!(audit::load(&path).unwrap(), vec![block]);
+}
+
+#[test]
+fn test_moderation_is_read_from_yaml() {
+    let config: ModerationConfig = serde_yaml::from_str(
+        "enabled: true\nbackend: command\nmodel: omni-moderation-latest\nendpoint: ''\ncommand: classify\ncategories: [hate]\naction: block\nfail_closed: true\n",
+    )
+    .unwrap();
+    assert_eq!(config.backend, ModerationBackend::Command);
+    assert_eq!(config.action, ModerationAction::Block);
+    assert!(config.fail_closed);
+}
@@ -0,0 +1,69 @@
+use ai_coder_interface_rs::app::{anchor_jump, anchor_target, resized_input_height};
+use ai_coder_interface_rs::config::AppConfig;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[test]
+fn test_braces_jump_only_with_alt() {
+    let key = |c, modifiers| KeyEvent::new(KeyCode::Char(c), modifiers);
+    assert_eq!(anchor_jump(&key('{', KeyModifiers::ALT)), Some(-1));
+    assert_eq!(anchor_jump(&key('}', KeyModifiers::ALT)), Some(1));
+    assert_eq!(
+        anchor_jump(&key('}', KeyModifiers::ALT | KeyModifiers::SHIFT)),
+        Some(1)
+    );
+
+    // Plain braces are typed into the input, e.g. a prompt starting with `{`
+    assert_eq!(anchor_jump(&key('{', KeyModifiers::NONE)), None);
+    assert_eq!(anchor_jump(&key('}', KeyModifiers::SHIFT)), None);
+    assert_eq!(anchor_jump(&key('n', KeyModifiers::ALT)), None);
+}
+
+#[test]
+fn test_anchors_are_found_around_the_reading_position() {
+    let rows = [0, 12, 40];
+    assert_eq!(anchor_target(&rows, 20, 1), Some(40));
+    assert_eq!(anchor_target(&rows, 20, -1), Some(12));
+    // The anchor at the reading position itself is skipped
+    assert_eq!(anchor_target(&rows, 12, 1), Some(40));
+    assert_eq!(anchor_target(&rows, 12, -1), Some(0));
+    // Nothing before the first or after the last anchor
+    assert_eq!(anchor_target(&rows, 0, -1), None);
+    assert_eq!(anchor_target(&rows, 40, 1), None);
+    assert_eq!(anchor_target(&[], 5, 1), None);
+}
+
+#[test]
+fn test_input_height_is_clamped() {
+    // Starts from the height of the input while it is sized automatically
+    assert_eq!(resized_input_height(0, "one", 1), 2);
+    assert_eq!(resized_input_height(0, "one\ntwo\nthree", 1), 4);
+    assert_eq!(resized_input_height(0, &"line\n".repeat(30), 1), 11);
+    // And from the saved height once set
+    assert_eq!(resized_input_height(6, "one", 1), 7);
+    assert_eq!(resized_input_height(6, "one", -1), 5);
+
+    // At most 40 lines
+    assert_eq!(resized_input_height(40, "", 1), 40);
+    assert_eq!(resized_input_height(39, "", 5), 40);
+    // Shrinking to a single line switches back to the automatic height
+    assert_eq!(resized_input_height(2, "", -1), 0);
+    assert_eq!(resized_input_height(0, "", -1), 0);
+    assert_eq!(resized_input_height(3, "", -10), 0);
+}
+
+#[test]
+fn test_input_height_is_kept_when_the_settings_are_reloaded() {
+    let mut config = AppConfig::default();
+    assert_eq!(config.ui.input_height, 0);
+    config.ui.input_height = resized_input_height(0, "one", 4);
+
+    let saved = serde_yaml::to_string(&config).unwrap();
+    let loaded: AppConfig = serde_yaml::from_str(&saved).unwrap();
+    assert_eq!(loaded.ui.input_height, 5);
+
+    // Settings saved before the height existed size the box to the input
+    let mut old: serde_yaml::Value = serde_yaml::from_str(&saved).unwrap();
+    old["ui"].as_mapping_mut().unwrap().remove("input_height");
+    let loaded: AppConfig = serde_yaml::from_value(old).unwrap();
+    assert_eq!(loaded.ui.input_height, 0);
+}
@@ -0,0 +1,151 @@
+use ai_coder_interface_rs::ai::Provider;
+use ai_coder_interface_rs::config::NetworkConfig;
+use ai_coder_interface_rs::utils::network::{
+    Connectivity, endpoint_address, offline_candidates, probe,
+};
+use std::time::Duration;
+use tokio::net::TcpListener;
+
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// An address nothing listens on any more
+async fn closed_port() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_probe_reports_reachable_hosts() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let host = listener.local_addr().unwrap().to_string();
+    assert!(probe(&host, TIMEOUT).await);
+
+    assert!(!probe(&closed_port().await, TIMEOUT).await);
+    assert!(!probe("not a host", TIMEOUT).await);
+}
+
+#[tokio::test]
+async fn test_monitor_notifies_when_the_network_goes_down() {
+    let connectivity = Connectivity::new();
+    // Assumed online until the first probe says otherwise
+    assert!(connectivity.is_online());
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+    let monitor = connectivity.spawn_monitor(
+        closed_port().await,
+        Vec::new(),
+        Duration::from_millis(50),
+        TIMEOUT,
+        Some(tx),
+    );
+    tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!connectivity.is_online());
+    // Clones share the state
+    assert!(!connectivity.clone().is_online());
+    monitor.abort();
+}
+
+#[tokio::test]
+async fn test_monitor_stays_quiet_while_online() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let connectivity = Connectivity::new();
+    let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+    let monitor = connectivity.spawn_monitor(
+        listener.local_addr().unwrap().to_string(),
+        Vec::new(),
+        Duration::from_millis(50),
+        TIMEOUT,
+        Some(tx),
+    );
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert!(rx.try_recv().is_err());
+    assert!(connectivity.is_online());
+    monitor.abort();
+}
+
+#[test]
+fn test_local_providers_work_offline() {
+    assert!(Provider::Ollama.is_local());
+    assert!(Provider::LMStudio.is_local());
+    assert!(Provider::LlamaCpp.is_local());
+    assert!(!Provider::OpenAI.is_local());
+
+    let network = NetworkConfig::default();
+    assert!(network.prefer_local_when_offline);
+    assert!(network.probe_host.ends_with(":443"));
+}
+
+#[tokio::test]
+async fn test_offline_prompt_is_queued_when_no_local_server_answers() {
+    let connectivity = Connectivity::new();
+    let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+    let monitor = connectivity.spawn_monitor(
+        closed_port().await,
+        vec![
+            (Provider::Ollama, closed_port().await),
+            (Provider::LMStudio, closed_port().await),
+        ],
+        Duration::from_millis(50),
+        TIMEOUT,
+        Some(tx),
+    );
+    tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!connectivity.is_online());
+    assert!(connectivity.reachable_local().is_empty());
+
+    // Only the in-process llama.cpp is left to try; with it unavailable the prompt is queued
+    let candidates = offline_candidates(true, &connectivity.reachable_local());
+    assert_eq!(candidates, vec![Provider::LlamaCpp]);
+    assert!(offline_candidates(false, &[Provider::Ollama]).is_empty());
+    monitor.abort();
+}
+
+#[tokio::test]
+async fn test_offline_prompt_goes_to_a_running_local_server() {
+    let server = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let connectivity = Connectivity::new();
+    let (tx, mut rx) = tokio::sync::mpsc::channel(4);
+    let monitor = connectivity.spawn_monitor(
+        closed_port().await,
+        vec![
+            (Provider::Ollama, closed_port().await),
+            (Provider::LMStudio, server.local_addr().unwrap().to_string()),
+        ],
+        Duration::from_millis(50),
+        TIMEOUT,
+        Some(tx),
+    );
+    tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(connectivity.reachable_local(), vec![Provider::LMStudio]);
+    assert_eq!(
+        offline_candidates(true, &connectivity.reachable_local()),
+        vec![Provider::LMStudio, Provider::LlamaCpp]
+    );
+    monitor.abort();
+}
+
+#[test]
+fn test_endpoint_address() {
+    assert_eq!(
+        endpoint_address("http://localhost:11434").as_deref(),
+        Some("localhost:11434")
+    );
+    assert_eq!(
+        endpoint_address("http://localhost:1234/v1").as_deref(),
+        Some("localhost:1234")
+    );
+    assert_eq!(
+        endpoint_address("https://example.com").as_deref(),
+        Some("example.com:443")
+    );
+    assert_eq!(endpoint_address("not a url"), None);
+}
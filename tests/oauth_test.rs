@@ -0,0 +1,88 @@
+use ai_coder_interface_rs::ai::Provider;
+use ai_coder_interface_rs::ai::oauth::{
+    DeviceCode, PollOutcome, StoredToken, TokenStore, poll_outcome,
+};
+use chrono::{Duration, Utc};
+
+#[test]
+fn test_token_responses_are_interpreted() {
+    let now = Utc::now();
+    assert_eq!(
+        poll_outcome(r#"{"error":"authorization_pending"}"#, now),
+        PollOutcome::Pending
+    );
+    assert_eq!(
+        poll_outcome(r#"{"error":"slow_down","interval":10}"#, now),
+        PollOutcome::SlowDown
+    );
+    assert!(matches!(
+        poll_outcome(r#"{"error":"access_denied"}"#, now),
+        PollOutcome::Failed(_)
+    ));
+    assert!(matches!(
+        poll_outcome("<html>", now),
+        PollOutcome::Failed(_)
+    ));
+
+    let body =
+        r#"{"access_token":"abc","refresh_token":"def","expires_in":3600,"token_type":"bearer"}"#;
+    assert_eq!(
+        poll_outcome(body, now),
+        PollOutcome::Token(StoredToken {
+            access_token: "abc".to_string(),
+            refresh_token: Some("def".to_string()),
+            expires_at: Some(now + Duration::seconds(3600)),
+        })
+    );
+}
+
+#[test]
+fn test_tokens_are_refreshed_shortly_before_they_expire() {
+    let now = Utc::now();
+    let token = |expires_at| StoredToken {
+        access_token: "abc".to_string(),
+        refresh_token: None,
+        expires_at,
+    };
+    assert!(!token(None).needs_refresh(now));
+    assert!(!token(Some(now + Duration::minutes(10))).needs_refresh(now));
+    assert!(token(Some(now + Duration::seconds(30))).needs_refresh(now));
+    assert!(token(Some(now - Duration::seconds(1))).needs_refresh(now));
+}
+
+#[test]
+fn test_device_codes_accept_both_verification_spellings() {
+    let code: DeviceCode = serde_json::from_str(
+        r#"{"device_code":"d","user_code":"ABCD-1234","verification_url":"https://example.com/device","expires_in":900}"#,
+    )
+    .unwrap();
+    assert_eq!(code.verification_uri, "https://example.com/device");
+    assert_eq!(code.interval, None);
+}
+
+#[test]
+fn test_stored_tokens_are_private_and_per_provider() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = TokenStore::at(dir.path().join("tokens.json"));
+    let token = StoredToken {
+        access_token: "abc".to_string(),
+        refresh_token: Some("def".to_string()),
+        expires_at: None,
+    };
+
+    assert_eq!(store.load(Provider::Ollama).unwrap(), None);
+    store.save(Provider::Ollama, &token).unwrap();
+    assert_eq!(store.load(Provider::Ollama).unwrap(), Some(token));
+    assert_eq!(store.load(Provider::OpenAI).unwrap(), None);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = store.path().metadata().unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    assert!(store.remove(Provider::Ollama).unwrap());
+    assert!(!store.remove(Provider::Ollama).unwrap());
+    assert!(!store.path().exists());
+}
@@ -0,0 +1,149 @@
+use ai_coder_interface_rs::ai::OllamaClient;
+use ai_coder_interface_rs::ai::types::{AIClient, ProgressCallback, ProgressStats, StreamChunk};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+/// Serve one `/api/generate` request, sending the body in the given pieces
+///
+/// Every piece is its own HTTP chunk, written after a short pause so the
+/// client receives them separately.
+async fn serve(pieces: Vec<&'static str>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        // Read the request headers and the JSON body
+        let mut request = Vec::new();
+        let mut buffer = [0u8; 4096];
+        loop {
+            let read = socket.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+            let text = String::from_utf8_lossy(&request);
+            if let Some(end) = text.find("\r\n\r\n") {
+                let length = text[..end]
+                    .lines()
+                    .find_map(|line| {
+                        let (name, value) = line.split_once(':')?;
+                        name.eq_ignore_ascii_case("content-length")
+                            .then(|| value.trim().parse::<usize>().ok())?
+                    })
+                    .unwrap_or(0);
+                if request.len() >= end + 4 + length {
+                    break;
+                }
+            }
+            if read == 0 {
+                break;
+            }
+        }
+
+        socket
+            .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: application/x-ndjson\r\ntransfer-encoding: chunked\r\n\r\n")
+            .await
+            .unwrap();
+        for piece in pieces {
+            let chunk = format!("{:x}\r\n{}\r\n", piece.len(), piece);
+            socket.write_all(chunk.as_bytes()).await.unwrap();
+            socket.flush().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(30)).await;
+        }
+        socket.write_all(b"0\r\n\r\n").await.unwrap();
+    });
+    url
+}
+
+/// A progress callback and the reports it received
+fn recorder() -> (ProgressCallback, Arc<Mutex<Vec<ProgressStats>>>) {
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let sink = reports.clone();
+    let callback: ProgressCallback = Arc::new(move |stats: &ProgressStats| {
+        sink.lock().unwrap().push(stats.clone());
+    });
+    (callback, reports)
+}
+
+#[tokio::test]
+async fn test_json_line_split_across_chunks() {
+    let url = serve(vec![
+        "{\"model\":\"llama3\",\"response\":\"Hel",
+        "lo\",\"done\":false}\n{\"model\":\"llama3\",\"response\":\" world\",",
+        "\"done\":false}\n",
+        "{\"model\":\"llama3\",\"response\":\"\",\"done\":true,\"prompt_eval_count\":4,\"eval_count\":2}\n",
+    ])
+    .await;
+    let client = OllamaClient::with_base_url(url, "llama3".to_string());
+    let (chunks, mut received) = tokio::sync::mpsc::unbounded_channel();
+    let (callback, _) = recorder();
+
+    let response = client
+        .generate_stream("Say hello", None, chunks, callback)
+        .await
+        .unwrap();
+    assert_eq!(response.content, "Hello world");
+    assert_eq!(response.model, "llama3");
+    assert_eq!(response.usage.prompt_tokens, 4);
+    assert_eq!(response.usage.completion_tokens, 2);
+    assert!(!response.truncated);
+
+    let mut streamed = Vec::new();
+    while let Ok(chunk) = received.try_recv() {
+        streamed.push(chunk);
+    }
+    assert_eq!(
+        streamed,
+        vec![
+            StreamChunk::Content("Hello".to_string()),
+            StreamChunk::Content(" world".to_string()),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_several_json_lines_in_one_chunk() {
+    let url = serve(vec![
+        "{\"model\":\"llama3\",\"response\":\"a\",\"done\":false}\n{\"model\":\"llama3\",\"response\":\"b\",\"done\":false}\n{\"model\":\"llama3\",\"response\":\"c\",\"done\":true,\"done_reason\":\"length\"}",
+    ])
+    .await;
+    let client = OllamaClient::with_base_url(url, "llama3".to_string());
+    let (callback, _) = recorder();
+
+    // The last line has no trailing newline and is still read
+    let response = client
+        .generate_with_progress("Spell it", None, callback)
+        .await
+        .unwrap();
+    assert_eq!(response.content, "abc");
+    assert!(response.truncated);
+}
+
+#[tokio::test]
+async fn test_generate_with_progress_reports_stages() {
+    let url = serve(vec![
+        "{\"model\":\"llama3\",\"response\":\"one\",\"done\":false}\n",
+        "{\"model\":\"llama3\",\"response\":\" two\",\"done\":false}\n",
+        "{\"model\":\"llama3\",\"response\":\"\",\"done\":true,\"eval_count\":2}\n",
+    ])
+    .await;
+    let client = OllamaClient::with_base_url(url, "llama3".to_string());
+    let (callback, reports) = recorder();
+
+    let response = client
+        .generate_with_progress("Count", None, callback)
+        .await
+        .unwrap();
+    assert_eq!(response.content, "one two");
+
+    let reports = reports.lock().unwrap();
+    // Reported once connected, before any token arrived
+    let first = reports.first().unwrap();
+    assert!(first.connected_at.is_some());
+    assert_eq!(first.tokens_generated, 0);
+    // And once complete, with the real token count
+    let last = reports.last().unwrap();
+    assert_eq!(last.completion_percent, Some(100.0));
+    assert!(last.finished_at.is_some());
+    assert_eq!(last.tokens_generated, 2);
+    assert_eq!(response.progress.unwrap().tokens_generated, 2);
+}
@@ -0,0 +1,116 @@
+use ai_coder_interface_rs::ai::openai_compat::{ReportedUsage, parse_stream_line};
+use ai_coder_interface_rs::ai::openrouter::{self, filter_catalog, parse_catalog};
+use ai_coder_interface_rs::ai::pricing::{self, model_costs, response_cost};
+use ai_coder_interface_rs::ai::{AIResponse, ModelCosts, Provider, TokenUsage};
+use ai_coder_interface_rs::config::OpenRouterConfig;
+use serde_json::json;
+
+#[test]
+fn test_stream_lines_carry_content_usage_and_cost() {
+    let chunk = parse_stream_line(
+        r#"data: {"model":"anthropic/claude-3.5-sonnet","choices":[{"delta":{"content":"Hi"}}]}"#,
+    )
+    .unwrap();
+    assert_eq!(chunk.content, "Hi");
+    assert_eq!(chunk.model.as_deref(), Some("anthropic/claude-3.5-sonnet"));
+
+    let last = parse_stream_line(
+        r#"data: {"choices":[],"usage":{"prompt_tokens":12,"completion_tokens":30,"total_tokens":42,"cost":0.00051}}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        last.usage,
+        Some(ReportedUsage {
+            prompt_tokens: 12,
+            completion_tokens: 30,
+            total_tokens: 42,
+            cost: Some(0.00051),
+        })
+    );
+
+    assert!(parse_stream_line("data: [DONE]").unwrap().done);
+    assert_eq!(parse_stream_line(": OPENROUTER PROCESSING"), None);
+    assert_eq!(parse_stream_line(""), None);
+    assert_eq!(
+        parse_stream_line(r#"data: {"error":{"message":"No credits"}}"#)
+            .unwrap()
+            .error
+            .as_deref(),
+        Some("No credits")
+    );
+}
+
+#[test]
+fn test_catalog_prices_are_per_thousand_tokens() {
+    let catalog = parse_catalog(
+        r#"{"data":[
+            {"id":"openai/gpt-4o","name":"OpenAI: GPT-4o","context_length":128000,
+             "pricing":{"prompt":"0.0000025","completion":"0.00001"}},
+            {"id":"openrouter/auto","name":"Auto Router","pricing":{"prompt":"-1","completion":"-1"}},
+            {"id":"meta-llama/llama-3-8b-instruct:free","pricing":{"prompt":"0","completion":"0"}}
+        ]}"#,
+    )
+    .unwrap();
+
+    let costs = catalog[0].costs.clone().unwrap();
+    assert!((costs.prompt_cost_per_1k - 0.0025).abs() < 1e-12);
+    assert!((costs.completion_cost_per_1k - 0.01).abs() < 1e-12);
+    assert_eq!(catalog[1].costs, None);
+    assert_eq!(catalog[2].name, "meta-llama/llama-3-8b-instruct:free");
+
+    let ids: Vec<&str> = filter_catalog(&catalog, "GPT openai")
+        .iter()
+        .map(|model| model.id.as_str())
+        .collect();
+    assert_eq!(ids, ["openai/gpt-4o"]);
+}
+
+#[test]
+fn test_attribution_headers_yield_to_configured_extras() {
+    let mut config = OpenRouterConfig::default();
+    config
+        .extra_headers
+        .insert("X-Title".to_string(), "My Team".to_string());
+
+    let extras = openrouter::request_extras(&config);
+    assert_eq!(extras.headers["X-Title"], "My Team");
+    assert_eq!(extras.headers["HTTP-Referer"], config.app_url);
+    assert_eq!(extras.body["usage"], json!({ "include": true }));
+}
+
+#[test]
+fn test_reported_cost_wins_over_list_prices() {
+    // Vendor prefixes and dotted versions still find the list price
+    let listed = model_costs(Provider::OpenRouter, "anthropic/claude-3.5-sonnet");
+    assert_eq!(
+        listed,
+        model_costs(Provider::Anthropic, "claude-3-5-sonnet")
+    );
+
+    let registered = ModelCosts {
+        prompt_cost_per_1k: 0.5,
+        completion_cost_per_1k: 1.0,
+    };
+    pricing::register(Provider::OpenRouter, "acme/model-x", registered.clone());
+    assert_eq!(
+        model_costs(Provider::OpenRouter, "acme/model-x"),
+        registered
+    );
+
+    let mut response = AIResponse {
+        content: String::new(),
+        model: "acme/model-x".to_string(),
+        usage: TokenUsage {
+            prompt_tokens: 1000,
+            completion_tokens: 1000,
+            total_tokens: 2000,
+        },
+        progress: None,
+        cost: None,
+        reasoning: None,
+        truncated: false,
+    };
+    assert!((response_cost(Provider::OpenRouter, &response) - 1.5).abs() < 1e-12);
+    response.cost = Some(0.02);
+    assert_eq!(response_cost(Provider::OpenRouter, &response), 0.02);
+}
@@ -0,0 +1,63 @@
+use ai_coder_interface_rs::config::LowBandwidth;
+use ai_coder_interface_rs::ui::FramePacer;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_slow_frames_switch_to_low_bandwidth() {
+    let mut pacer = FramePacer::new(LowBandwidth::Auto);
+    let start = Instant::now();
+
+    // A few slow frames are not enough
+    for i in 0..10 {
+        assert!(!pacer.record(start + Duration::from_secs(i), Duration::from_millis(80)));
+    }
+    assert!(!pacer.is_low_bandwidth());
+
+    let switched = (10..30)
+        .map(|i| pacer.record(start + Duration::from_secs(i), Duration::from_millis(80)))
+        .any(|switched| switched);
+    assert!(switched);
+    assert!(pacer.is_low_bandwidth());
+}
+
+#[test]
+fn test_fast_frames_stay_normal() {
+    let mut pacer = FramePacer::new(LowBandwidth::Auto);
+    let start = Instant::now();
+    for i in 0..60 {
+        pacer.record(
+            start + Duration::from_millis(i * 20),
+            Duration::from_millis(2),
+        );
+    }
+    assert!(!pacer.is_low_bandwidth());
+}
+
+#[test]
+fn test_low_bandwidth_draws_less_often() {
+    let start = Instant::now();
+    let mut pacer = FramePacer::new(LowBandwidth::On);
+    assert!(pacer.should_draw(start));
+    pacer.record(start, Duration::from_millis(1));
+    assert!(!pacer.should_draw(start + Duration::from_millis(100)));
+    assert!(pacer.should_draw(start + Duration::from_millis(300)));
+
+    pacer.set_mode(LowBandwidth::Off);
+    assert!(pacer.should_draw(start + Duration::from_millis(20)));
+}
+
+#[test]
+fn test_next_draw_counts_down_the_interval() {
+    let start = Instant::now();
+    let mut pacer = FramePacer::new(LowBandwidth::On);
+    assert_eq!(pacer.next_draw(start), Duration::ZERO);
+    pacer.record(start, Duration::from_millis(1));
+    assert_eq!(
+        pacer.next_draw(start + Duration::from_millis(100)),
+        Duration::from_millis(150)
+    );
+    assert_eq!(
+        pacer.next_draw(start + Duration::from_millis(400)),
+        Duration::ZERO
+    );
+}
@@ -0,0 +1,53 @@
+use ai_coder_interface_rs::app::{AIPurpose, PendingPrompt};
+use ai_coder_interface_rs::utils::crypto::{Cipher, is_encrypted};
+
+#[test]
+fn test_pending_prompts_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pending.json");
+
+    // A missing file means nothing is pending
+    assert!(PendingPrompt::load_all(&path).unwrap().is_empty());
+
+    let prompts = vec![
+        PendingPrompt::new("explain lifetimes".to_string(), AIPurpose::Chat),
+        PendingPrompt::new("staged diff".to_string(), AIPurpose::CommitMessage),
+    ];
+    PendingPrompt::save_all(&path, &prompts).unwrap();
+
+    let loaded = PendingPrompt::load_all(&path).unwrap();
+    assert_eq!(loaded.len(), 2);
+    assert_eq!(loaded[0].prompt, "explain lifetimes");
+    assert!(matches!(loaded[1].purpose, AIPurpose::CommitMessage));
+    assert_eq!(loaded[0].summary(), "[AI] explain lifetimes");
+
+    // Saving an empty set removes the file
+    PendingPrompt::save_all(&path, &[]).unwrap();
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_pending_prompts_are_saved_encrypted() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("pending.json");
+    let cipher = Cipher::from_key([7; 32]);
+
+    let prompts = vec![PendingPrompt::new(
+        "explain the secret parser".to_string(),
+        AIPurpose::Chat,
+    )];
+    PendingPrompt::save_all_with(&path, &prompts, Some(&cipher)).unwrap();
+    let raw = std::fs::read(&path).unwrap();
+    assert!(is_encrypted(&raw));
+    assert!(!String::from_utf8_lossy(&raw).contains("secret parser"));
+
+    let loaded = PendingPrompt::load_all_with(&path, Some(&cipher)).unwrap();
+    assert_eq!(loaded[0].prompt, "explain the secret parser");
+    // Without the key they cannot be read
+    assert!(PendingPrompt::load_all(&path).is_err());
+
+    // Prompts saved before encryption was turned on stay readable
+    PendingPrompt::save_all(&path, &prompts).unwrap();
+    let loaded = PendingPrompt::load_all_with(&path, Some(&cipher)).unwrap();
+    assert_eq!(loaded.len(), 1);
+}
@@ -0,0 +1,86 @@
+use ai_coder_interface_rs::ai::Provider;
+use ai_coder_interface_rs::ai::persona::{apply, find, overrides_model, parse_prefix};
+use ai_coder_interface_rs::config::{AIConfig, Persona};
+use std::collections::BTreeMap;
+
+fn personas() -> BTreeMap<String, Persona> {
+    let mut personas = BTreeMap::new();
+    personas.insert(
+        "reviewer".to_string(),
+        Persona {
+            system_prompt: "You review code.".to_string(),
+            provider: Some(Provider::Anthropic),
+            model: "claude-3-5-sonnet".to_string(),
+            temperature: Some(0.05),
+        },
+    );
+    personas.insert(
+        "tester".to_string(),
+        Persona {
+            system_prompt: "You write tests.".to_string(),
+            ..Persona::default()
+        },
+    );
+    personas
+}
+
+#[test]
+fn test_prefixes_name_a_configured_persona() {
+    let personas = personas();
+    assert_eq!(
+        parse_prefix("@reviewer: is this safe?", &personas),
+        (Some("reviewer".to_string()), "is this safe?")
+    );
+    assert_eq!(
+        parse_prefix("@Tester:cover the parser", &personas),
+        (Some("tester".to_string()), "cover the parser")
+    );
+    // Unknown names and mentions without a colon stay part of the prompt
+    assert_eq!(
+        parse_prefix("@someone: hello", &personas),
+        (None, "@someone: hello")
+    );
+    assert_eq!(
+        parse_prefix("@reviewer please look", &personas),
+        (None, "@reviewer please look")
+    );
+    assert_eq!(
+        parse_prefix("explain this", &personas),
+        (None, "explain this")
+    );
+    assert_eq!(find("REVIEWER", &personas).as_deref(), Some("reviewer"));
+    assert_eq!(find("architect", &personas), None);
+}
+
+#[test]
+fn test_personas_pick_provider_model_and_temperature() {
+    let personas = personas();
+    let mut ai = AIConfig::default();
+    apply(&personas["reviewer"], &mut ai);
+    assert_eq!(ai.active_provider, Provider::Anthropic);
+    let model = ai.get_active_model_config();
+    assert_eq!(model.name, "claude-3-5-sonnet");
+    assert_eq!(model.temperature, 0.05);
+    assert!(overrides_model(&personas["reviewer"]));
+
+    // Only the system prompt: the active model stays as it is
+    let mut ai = AIConfig::default();
+    let before = ai.get_active_model_config();
+    apply(&personas["tester"], &mut ai);
+    assert_eq!(ai.active_provider, AIConfig::default().active_provider);
+    assert_eq!(ai.get_active_model_config().name, before.name);
+    assert_eq!(ai.get_active_model_config().temperature, before.temperature);
+    assert!(!overrides_model(&personas["tester"]));
+}
+
+#[test]
+fn test_personas_are_read_from_the_config() {
+    let yaml =
+        "reviewer:\n  system_prompt: Review.\n  temperature: 0.2\narchitect:\n  model: gpt-4o\n";
+    let personas: BTreeMap<String, Persona> = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(personas["reviewer"].system_prompt, "Review.");
+    assert_eq!(personas["reviewer"].temperature, Some(0.2));
+    assert_eq!(personas["reviewer"].provider, None);
+    assert_eq!(personas["architect"].model, "gpt-4o");
+    assert!(personas["architect"].system_prompt.is_empty());
+}
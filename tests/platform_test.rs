@@ -0,0 +1,34 @@
+use ai_coder_interface_rs::platform;
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyEventState, KeyModifiers};
+
+#[test]
+fn test_shell_runs_commands() {
+    let output = platform::shell_command("echo hello").output().unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("hello"));
+}
+
+#[test]
+fn test_programs_are_found_on_path() {
+    let shell = if platform::IS_WINDOWS { "cmd" } else { "sh" };
+    assert!(platform::find_in_path(shell).is_some());
+    assert!(platform::find_in_path("surely-not-an-installed-program").is_none());
+}
+
+#[test]
+fn test_only_globs_need_the_shell_on_unix() {
+    assert!(platform::needs_shell("ls *.rs"));
+    assert_eq!(platform::needs_shell("ls -la"), platform::IS_WINDOWS);
+}
+
+#[test]
+fn test_key_releases_are_ignored() {
+    let mut key = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE);
+    assert!(platform::is_key_press(&key));
+    key = KeyEvent {
+        kind: KeyEventKind::Release,
+        state: KeyEventState::NONE,
+        ..key
+    };
+    assert!(!platform::is_key_press(&key));
+}
@@ -0,0 +1,67 @@
+use ai_coder_interface_rs::config::PostProcessor;
+use ai_coder_interface_rs::handlers::postprocess::{postprocess, strip_thinking};
+
+#[test]
+fn test_thinking_blocks_are_removed() {
+    assert_eq!(
+        strip_thinking("<think>\nplan it\n</think>\n\nThe answer is 42."),
+        "The answer is 42."
+    );
+    assert_eq!(strip_thinking("reasoning here</think>Done"), "Done");
+    assert_eq!(strip_thinking("Done<thinking>cut off"), "Done");
+    assert_eq!(strip_thinking("No tags"), "No tags");
+}
+
+#[test]
+fn test_steps_run_in_order() {
+    let steps = vec![PostProcessor::StripThinking, PostProcessor::RedactSecrets];
+    assert_eq!(
+        postprocess(&steps, "<think>key?</think>Use sk-abcdef123456 here"),
+        "Use *** here"
+    );
+    assert_eq!(postprocess(&[], "<think>x</think>"), "<think>x</think>");
+}
+
+#[test]
+fn test_steps_are_configured_as_tagged_entries() {
+    let yaml = "- type: strip_thinking\n- type: format_code\n  language: rust\n  command: rustfmt\n- type: command\n  command: cat\n";
+    let steps: Vec<PostProcessor> = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(
+        steps,
+        vec![
+            PostProcessor::StripThinking,
+            PostProcessor::FormatCode {
+                language: "rust".to_string(),
+                command: "rustfmt".to_string()
+            },
+            PostProcessor::Command {
+                command: "cat".to_string()
+            },
+        ]
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_commands_filter_the_response_and_code_blocks() {
+    let steps = vec![
+        PostProcessor::FormatCode {
+            language: "python".to_string(),
+            command: "tr a-z A-Z".to_string(),
+        },
+        PostProcessor::Command {
+            command: "sed 's/Run/Try/'".to_string(),
+        },
+    ];
+    let response = "Run:\n```python\nprint(1)\n```\n```rust\nfn main() {}\n```";
+    assert_eq!(
+        postprocess(&steps, response),
+        "Try:\n```python\nPRINT(1)\n```\n```rust\nfn main() {}\n```"
+    );
+
+    let failing = vec![PostProcessor::Command {
+        command: "exit 3".to_string(),
+    }];
+    let output = postprocess(&failing, "kept");
+    assert!(output.starts_with("kept\n⚠️ Post-processor `exit 3` failed"));
+}
@@ -0,0 +1,79 @@
+use ai_coder_interface_rs::ai::Provider;
+use ai_coder_interface_rs::ai::pricing::{PriceSource, PromptEstimate, lookup, model_costs};
+use ai_coder_interface_rs::config::ModelPrice;
+
+#[test]
+fn test_prompt_estimate() {
+    // More specific model names win over their prefixes
+    let mini = model_costs(Provider::OpenAI, "gpt-4o-mini");
+    let full = model_costs(Provider::OpenAI, "gpt-4o-2024-08-06");
+    assert!(mini.prompt_cost_per_1k < full.prompt_cost_per_1k);
+
+    // Local providers are free
+    let local = PromptEstimate::new(Provider::Ollama, "gpt-4o", "hello world");
+    assert_eq!(local.cost, 0.0);
+
+    let prompt = "word ".repeat(2000);
+    let estimate = PromptEstimate::new(Provider::OpenAI, "gpt-4o", &prompt);
+    assert_eq!(estimate.tokens, 2600);
+    assert_eq!(estimate.to_string(), "~2,600 tokens, ≈$0.007 with gpt-4o");
+}
+
+#[test]
+fn test_configured_prices_win() {
+    let price = |provider, model: &str, prompt| ModelPrice {
+        provider,
+        model: model.to_string(),
+        prompt,
+        completion: prompt * 4.0,
+    };
+    let prices = vec![
+        price(None, "gpt-4o", 0.002),
+        price(None, "gpt-4o-mini", 0.0001),
+        price(Some(Provider::Ollama), "qwen2.5-coder", 0.000_05),
+        price(Some(Provider::Groq), "llama", 0.001),
+    ];
+
+    // The longest configured prefix applies, from any provider without one
+    let (costs, source) = lookup(&prices, Provider::OpenAI, "gpt-4o-mini-2024-07-18");
+    assert_eq!(source, PriceSource::Config);
+    assert_eq!(costs.prompt_cost_per_1k, 0.0001);
+    assert_eq!(
+        lookup(&prices, Provider::OpenRouter, "openai/gpt-4o")
+            .0
+            .completion_cost_per_1k,
+        0.008
+    );
+
+    // Local models cost what is configured for their compute
+    let (costs, source) = lookup(&prices, Provider::Ollama, "qwen2.5-coder:7b");
+    assert_eq!(source, PriceSource::Config);
+    assert_eq!(costs.prompt_cost_per_1k, 0.000_05);
+    assert_eq!(
+        lookup(&prices, Provider::Ollama, "llama3").1,
+        PriceSource::Free
+    );
+
+    // Other models keep the built-in price
+    let (costs, source) = lookup(&prices, Provider::Anthropic, "claude-3-5-haiku-latest");
+    assert_eq!(source, PriceSource::BuiltIn);
+    assert_eq!(costs.prompt_cost_per_1k, 0.000_8);
+    assert_eq!(
+        lookup(&[], Provider::OpenAI, "gpt-4.1-mini")
+            .0
+            .prompt_cost_per_1k,
+        0.000_4
+    );
+    assert_eq!(lookup(&[], Provider::OpenAI, "acme-1").1, PriceSource::Free);
+}
+
+#[test]
+fn test_prices_are_read_from_yaml() {
+    let prices: Vec<ModelPrice> = serde_yaml::from_str(
+        "- model: gpt-4o\n  prompt: 0.0025\n  completion: 0.01\n- provider: Ollama\n  model: qwen2.5-coder\n  prompt: 0.00005\n  completion: 0.0001\n",
+    )
+    .unwrap();
+    assert_eq!(prices[0].provider, None);
+    assert_eq!(prices[1].provider, Some(Provider::Ollama));
+    assert_eq!(prices[1].completion, 0.0001);
+}
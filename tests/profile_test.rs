@@ -0,0 +1,40 @@
+use ai_coder_interface_rs::ui::FrameHistogram;
+use std::time::Duration;
+
+#[test]
+fn test_frames_land_in_doubling_buckets() {
+    let mut histogram = FrameHistogram::new();
+    for millis in [0, 3, 3, 5, 100] {
+        histogram.record(Duration::from_millis(millis));
+    }
+
+    let counts: Vec<u64> = histogram
+        .buckets()
+        .iter()
+        .map(|(_, count)| *count)
+        .collect();
+    assert_eq!(counts, vec![1, 0, 2, 1, 0, 0, 0, 1]);
+    assert_eq!(histogram.buckets()[7].0, "≥66 ms");
+    assert_eq!(histogram.count(), 5);
+    assert_eq!(histogram.max(), Duration::from_millis(100));
+    assert_eq!(histogram.mean(), Duration::from_millis(111) / 5);
+}
+
+#[test]
+fn test_percentiles_report_bucket_bounds() {
+    let mut histogram = FrameHistogram::new();
+    assert_eq!(histogram.percentile(50.0), Duration::ZERO);
+
+    for _ in 0..98 {
+        histogram.record(Duration::from_micros(1500));
+    }
+    histogram.record(Duration::from_millis(20));
+    histogram.record(Duration::from_millis(200));
+
+    assert_eq!(histogram.percentile(50.0), Duration::from_millis(2));
+    assert_eq!(histogram.percentile(99.0), Duration::from_millis(33));
+    assert_eq!(histogram.percentile(100.0), Duration::from_millis(200));
+
+    histogram.reset();
+    assert_eq!(histogram.count(), 0);
+}
@@ -0,0 +1,153 @@
+use ai_coder_interface_rs::ai::types::{GenerationStage, ProgressStats};
+use ai_coder_interface_rs::config::{SpinnerStyle, ThemeConfig};
+use ai_coder_interface_rs::ui::progress::{Spinner, bar, clear_indicators, gauge, is_indicator};
+use std::time::Duration;
+
+fn theme(spinner: SpinnerStyle, progress_bar: bool) -> ThemeConfig {
+    ThemeConfig {
+        spinner,
+        progress_bar,
+        ..ThemeConfig::default()
+    }
+}
+
+/// Progress halfway through 200 tokens at 10 tokens per second
+fn halfway() -> ProgressStats {
+    ProgressStats {
+        tokens_generated: 100,
+        estimated_total_tokens: Some(200),
+        tokens_per_second: 10.0,
+        completion_percent: Some(50.0),
+        ..ProgressStats::default()
+    }
+}
+
+#[test]
+fn test_each_style_has_its_frames() {
+    let braille = Spinner::new(&theme(SpinnerStyle::Braille, true), false);
+    assert_eq!(braille.frames.len(), 10);
+    assert_eq!(braille.interval, Duration::from_millis(80));
+    assert_eq!(
+        Spinner::new(&theme(SpinnerStyle::Line, true), false).frames,
+        ["─", "╲", "│", "╱"]
+    );
+    assert_eq!(
+        Spinner::new(&theme(SpinnerStyle::None, true), false).frames,
+        [""]
+    );
+    // Slow links get a static indicator whatever the style
+    let slow = Spinner::new(&theme(SpinnerStyle::Dots, true), true);
+    assert_eq!(slow.frames, ["⋯"]);
+    assert_eq!(slow.interval, Duration::from_millis(500));
+}
+
+#[test]
+fn test_styles_are_read_from_the_theme() {
+    let theme: ThemeConfig = serde_yaml::from_str(
+        "primary: '#0087AF'\nsecondary: '#00AF87'\naccent: '#AF8700'\nbackground: default\nforeground: default\nspinner: dots\n",
+    )
+    .unwrap();
+    assert_eq!(theme.spinner, SpinnerStyle::Dots);
+    assert!(theme.progress_bar);
+    assert_eq!("line".parse::<SpinnerStyle>(), Ok(SpinnerStyle::Line));
+    assert!("stars".parse::<SpinnerStyle>().is_err());
+}
+
+#[test]
+fn test_the_spinner_shows_the_stage_of_the_generation() {
+    let spinner = Spinner::new(&theme(SpinnerStyle::Braille, true), false);
+    let waited = Duration::from_millis(2100);
+    assert_eq!(
+        spinner.status_line("⠋", None, waited),
+        "⠋ connecting (2.1s)"
+    );
+    let mut connected = ProgressStats::default();
+    assert_eq!(
+        spinner.status_line("⠋", Some(&connected), waited),
+        "⠋ connecting (2.1s)"
+    );
+    connected.connected();
+    assert_eq!(connected.stage(), GenerationStage::WaitingForFirstToken);
+    assert_eq!(
+        spinner.status_line("⠋", Some(&connected), waited),
+        "⠋ waiting for first token (2.1s)"
+    );
+
+    let open_ended = ProgressStats {
+        tokens_generated: 431,
+        tokens_per_second: 38.2,
+        ..ProgressStats::default()
+    };
+    assert_eq!(open_ended.stage(), GenerationStage::Streaming);
+    assert_eq!(
+        spinner.status_line("⠋", Some(&open_ended), waited),
+        "⠋ streaming (431 tok, 38/s)"
+    );
+}
+
+#[test]
+fn test_a_bar_is_shown_once_the_remaining_time_is_known() {
+    let spinner = Spinner::new(&theme(SpinnerStyle::Braille, true), false);
+    let waited = Duration::from_secs(10);
+    assert_eq!(
+        spinner.status_line("⠋", Some(&halfway()), waited),
+        "⠋ streaming (100 tok, 10/s) ██████████░░░░░░░░░░ 50% · ETA 10 sec"
+    );
+
+    let without_spinner = Spinner::new(&theme(SpinnerStyle::None, true), false);
+    assert_eq!(
+        without_spinner.status_line("", Some(&halfway()), waited),
+        "streaming (100 tok, 10/s) ██████████░░░░░░░░░░ 50% · ETA 10 sec"
+    );
+    let without_bar = Spinner::new(&theme(SpinnerStyle::Braille, false), false);
+    assert_eq!(
+        without_bar.status_line("⠙", Some(&halfway()), waited),
+        "⠙ streaming (100 tok, 10/s)"
+    );
+}
+
+#[test]
+fn test_the_status_bar_gauge_shows_rate_and_remaining_time() {
+    assert_eq!(
+        gauge(&halfway(), true).unwrap(),
+        "█████░░░░░ 50% · 10 tok/s · ETA 10 sec"
+    );
+    assert_eq!(
+        gauge(&halfway(), false).unwrap(),
+        "50% · 10 tok/s · ETA 10 sec"
+    );
+    // Without an expected length only the tokens so far and the rate are known
+    let open_ended = ProgressStats {
+        estimated_total_tokens: None,
+        completion_percent: None,
+        ..halfway()
+    };
+    assert_eq!(gauge(&open_ended, true).unwrap(), "100 tok · 10 tok/s");
+    assert_eq!(gauge(&ProgressStats::default(), true), None);
+}
+
+#[test]
+fn test_indicators_are_cleared_from_the_output() {
+    assert!(is_indicator("⠋"));
+    assert!(is_indicator(" ∙∙"));
+    assert!(is_indicator("│"));
+    assert!(is_indicator("⠋ ██████████░░░░░░░░░░ 50% · ETA 10 sec"));
+    assert!(is_indicator("⠋ connecting (0.4s)"));
+    assert!(is_indicator("waiting for first token (12.0s)"));
+    assert!(is_indicator(
+        "⠙ streaming (100 tok, 10/s) ██████████░░░░░░░░░░ 50% · ETA 10 sec"
+    ));
+    assert!(!is_indicator("streaming (with backpressure)"));
+    assert!(!is_indicator("connecting (slowly)"));
+    assert!(!is_indicator(""));
+    assert!(!is_indicator("---"));
+    assert!(!is_indicator("- item"));
+    assert!(!is_indicator("██ 50% done"));
+
+    assert_eq!(
+        clear_indicators("❯ hi\n⠸\n\nHello\n⠋ █░ 5% · ETA 3 sec\n"),
+        "❯ hi\n\n\nHello\n\n"
+    );
+    assert_eq!(bar(25.0, 4), "█░░░");
+    assert_eq!(bar(150.0, 4), "████");
+}
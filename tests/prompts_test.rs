@@ -0,0 +1,93 @@
+use ai_coder_interface_rs::utils::prompts::{MAX_PROMPTS, PromptLibrary, fuzzy_score};
+use chrono::{Duration, Local};
+
+#[test]
+fn test_fuzzy_search_matches_characters_in_order() {
+    assert_eq!(fuzzy_score("", "anything"), Some(0));
+    assert_eq!(
+        fuzzy_score("unit test", "Write unit tests for this"),
+        Some(0)
+    );
+    assert_eq!(fuzzy_score("wut", "Write unit tests"), Some(6));
+    assert_eq!(fuzzy_score("tsu", "Write unit tests"), None);
+}
+
+#[test]
+fn test_prompts_are_ranked_by_use_and_recency() {
+    let now = Local::now();
+    let mut library = PromptLibrary::default();
+    library.record("explain this error", now - Duration::weeks(8));
+    library.record("explain this error", now - Duration::weeks(8));
+    library.record("explain this error", now - Duration::weeks(8));
+    library.record("write tests", now - Duration::days(1));
+    library.record("write tests", now);
+    library.record("  summarize the diff ", now);
+    library.record("", now);
+
+    let ranked: Vec<&str> = library
+        .search("", now)
+        .iter()
+        .map(|entry| entry.text.as_str())
+        .collect();
+    assert_eq!(
+        ranked,
+        ["write tests", "summarize the diff", "explain this error"]
+    );
+    assert_eq!(library.prompts[0].count, 3);
+
+    let found: Vec<&str> = library
+        .search("exerr", now)
+        .iter()
+        .map(|entry| entry.text.as_str())
+        .collect();
+    assert_eq!(found, ["explain this error"]);
+}
+
+#[test]
+fn test_library_survives_a_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("prompts.json");
+    assert!(PromptLibrary::load(&path).unwrap().prompts.is_empty());
+
+    let now = Local::now();
+    let mut library = PromptLibrary::default();
+    for n in 0..=MAX_PROMPTS {
+        library.record(&format!("prompt {}", n), now - Duration::minutes(n as i64));
+    }
+    library.record("prompt 1", now);
+    assert_eq!(library.prompts.len(), MAX_PROMPTS);
+    assert!(
+        !library
+            .prompts
+            .iter()
+            .any(|e| e.text == format!("prompt {}", MAX_PROMPTS))
+    );
+    library.save(&path).unwrap();
+
+    let loaded = PromptLibrary::load(&path).unwrap();
+    assert_eq!(loaded.prompts.len(), MAX_PROMPTS);
+    assert_eq!(loaded.search("prompt 1", now)[0].count, 2);
+}
+
+#[test]
+fn test_merged_libraries_keep_the_higher_count_and_later_use() {
+    let now = Local::now();
+    let mut laptop = PromptLibrary::default();
+    laptop.record("explain this error", now - Duration::days(3));
+    laptop.record("explain this error", now - Duration::days(3));
+    laptop.record("write tests", now - Duration::days(1));
+
+    let mut desktop = PromptLibrary::default();
+    desktop.record("explain this error", now);
+    desktop.record("summarize the diff", now);
+
+    laptop.merge(desktop, now);
+    assert_eq!(laptop.prompts.len(), 3);
+    let explain = laptop
+        .prompts
+        .iter()
+        .find(|entry| entry.text == "explain this error")
+        .unwrap();
+    assert_eq!(explain.count, 2);
+    assert_eq!(explain.last_used, now);
+}
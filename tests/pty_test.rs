@@ -0,0 +1,121 @@
+use ai_coder_interface_rs::handlers::pty::{Screen, key_bytes};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+fn screen(output: &[u8]) -> Screen {
+    let mut screen = Screen::new();
+    screen.feed(output);
+    screen
+}
+
+#[test]
+fn test_output_is_turned_into_lines() {
+    assert_eq!(
+        screen(b">>> 1 + 1\r\n2\r\n>>> ").lines(),
+        vec![">>> 1 + 1", "2", ">>> "]
+    );
+    // Progress redrawn over itself keeps only the last state
+    assert_eq!(screen(b"10%\r50%\r100%\n").text(), "100%");
+    assert_eq!(screen(b"abc\x08\x08X").text(), "aXc");
+    assert_eq!(screen(b"a\tb").text(), "a       b");
+    assert!(screen(b"\r\n\n").is_empty());
+}
+
+#[test]
+fn test_escape_sequences_are_left_out() {
+    assert_eq!(screen(b"\x1b[1;32mok\x1b[0m done").text(), "ok done");
+    assert_eq!(screen(b"\x1b]0;title\x07prompt$ ").text(), "prompt$");
+    assert_eq!(screen(b"\x1b]0;title\x1b\\prompt$ ").text(), "prompt$");
+    // Erasing the line, as readline does when redrawing it
+    assert_eq!(screen(b"old text\r\x1b[Knew").text(), "new");
+    assert_eq!(screen(b"abcdef\x1b[3D\x1b[K").text(), "abc");
+    assert_eq!(screen(b"abcdef\x1b[4G\x1b[1K").text(), "   def");
+}
+
+#[test]
+fn test_cursor_movement_sequences() {
+    assert_eq!(screen(b"ab\x1b[3Cc").text(), "ab   c");
+    assert_eq!(screen(b"abcdef\x1b[2DX").text(), "abcdXf");
+    assert_eq!(screen(b"abcdef\x1b[GX").text(), "Xbcdef");
+    // A count of 0 moves by one, as in a terminal
+    assert_eq!(screen(b"abc\x1b[10D\x1b[0CX").text(), "aXc");
+
+    // Moves far to the right stop at a bounded column
+    let far = screen(b"\x1b[99999999Cx");
+    assert_eq!(far.column(), 1025);
+    assert_eq!(far.text().len(), 1025);
+    assert_eq!(screen(b"\x1b[99999999Gx").column(), 1025);
+    assert_eq!(screen(b"a\x1b[18446744073709551615Cx").column(), 1025);
+}
+
+#[test]
+fn test_characters_split_between_reads_are_joined() {
+    let mut screen = Screen::new();
+    let text = "héllo ✓".as_bytes();
+    screen.feed(&text[..2]);
+    screen.feed(&text[2..9]);
+    screen.feed(&text[9..]);
+    assert_eq!(screen.text(), "héllo ✓");
+    assert_eq!(screen.column(), 7);
+}
+
+#[test]
+fn test_keys_are_sent_as_a_terminal_sends_them() {
+    let key = |code, modifiers| key_bytes(&KeyEvent::new(code, modifiers));
+    assert_eq!(
+        key(KeyCode::Char('x'), KeyModifiers::NONE),
+        Some(b"x".to_vec())
+    );
+    assert_eq!(
+        key(KeyCode::Char('é'), KeyModifiers::NONE),
+        Some("é".as_bytes().to_vec())
+    );
+    assert_eq!(
+        key(KeyCode::Char('c'), KeyModifiers::CONTROL),
+        Some(vec![0x03])
+    );
+    assert_eq!(
+        key(KeyCode::Enter, KeyModifiers::NONE),
+        Some(b"\r".to_vec())
+    );
+    assert_eq!(
+        key(KeyCode::Up, KeyModifiers::NONE),
+        Some(b"\x1b[A".to_vec())
+    );
+    assert_eq!(
+        key(KeyCode::Char('b'), KeyModifiers::ALT),
+        Some(b"\x1bb".to_vec())
+    );
+    assert_eq!(key(KeyCode::F(5), KeyModifiers::NONE), None);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_commands_read_input_from_the_pty() {
+    use ai_coder_interface_rs::handlers::pty::spawn;
+    use std::io::{Read, Write};
+    use std::path::Path;
+
+    let mut process = spawn(
+        "printf 'name? '; read name; echo \"hello $name\"",
+        Path::new("."),
+        80,
+        24,
+    )
+    .unwrap();
+    process.input.write_all(b"pty\r").unwrap();
+    process.input.flush().unwrap();
+
+    let mut output = process.output;
+    let mut screen = Screen::new();
+    let mut buffer = [0; 1024];
+    // Reads fail once the command exited and the pty closed
+    while let Ok(read) = output.read(&mut buffer) {
+        if read == 0 {
+            break;
+        }
+        screen.feed(&buffer[..read]);
+    }
+    assert!(process.child.wait().unwrap().success());
+    assert!(screen.text().contains("name? "));
+    assert!(screen.text().ends_with("hello pty"));
+}
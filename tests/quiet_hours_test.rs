@@ -0,0 +1,115 @@
+use ai_coder_interface_rs::ai::Provider;
+use ai_coder_interface_rs::ai::quiet_hours::{
+    QuietWindow, format_until, invalid, provider_quiet_until, quiet_until,
+};
+use ai_coder_interface_rs::config::{AIConfig, OllamaConfig};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+
+/// 2024-05-03 is a Friday
+fn at(day: u32, time: &str) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 5, day)
+        .unwrap()
+        .and_time(NaiveTime::parse_from_str(time, "%H:%M").unwrap())
+}
+
+fn specs(items: &[&str]) -> Vec<String> {
+    items.iter().map(|item| item.to_string()).collect()
+}
+
+#[test]
+fn test_windows_are_parsed_with_optional_days() {
+    let window = QuietWindow::parse("mon-wed,sat 02:00-03:30").unwrap();
+    assert_eq!(
+        window.days,
+        vec![Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Sat]
+    );
+    assert_eq!(window.start, NaiveTime::from_hms_opt(2, 0, 0).unwrap());
+    assert_eq!(window.end, NaiveTime::from_hms_opt(3, 30, 0).unwrap());
+    // Ranges wrap around the week
+    assert_eq!(
+        QuietWindow::parse("Sat-Mon 00:00-06:00").unwrap().days,
+        vec![Weekday::Sat, Weekday::Sun, Weekday::Mon]
+    );
+    assert!(QuietWindow::parse("01:00-05:00").unwrap().days.is_empty());
+
+    assert_eq!(
+        invalid(&specs(&[
+            "01:00-05:00",
+            "nightly",
+            "fri 25:00-02:00",
+            "someday 01:00-02:00"
+        ])),
+        vec![
+            "'nightly' is not HH:MM-HH:MM",
+            "invalid time '25:00' in 'fri 25:00-02:00'",
+            "unknown day 'someday'"
+        ]
+    );
+}
+
+#[test]
+fn test_windows_cover_their_times() {
+    let nightly = specs(&["01:00-05:00"]);
+    assert_eq!(quiet_until(&nightly, at(3, "01:00")), Some(at(3, "05:00")));
+    assert_eq!(quiet_until(&nightly, at(3, "04:59")), Some(at(3, "05:00")));
+    assert_eq!(quiet_until(&nightly, at(3, "05:00")), None);
+    assert_eq!(quiet_until(&nightly, at(3, "00:59")), None);
+
+    let weekends = specs(&["sat,sun 01:00-05:00"]);
+    assert_eq!(quiet_until(&weekends, at(3, "02:00")), None);
+    assert_eq!(quiet_until(&weekends, at(4, "02:00")), Some(at(4, "05:00")));
+}
+
+#[test]
+fn test_windows_run_past_midnight_from_their_day() {
+    let friday_night = specs(&["fri 22:00-02:00"]);
+    assert_eq!(
+        quiet_until(&friday_night, at(3, "23:00")),
+        Some(at(4, "02:00"))
+    );
+    assert_eq!(
+        quiet_until(&friday_night, at(4, "01:00")),
+        Some(at(4, "02:00"))
+    );
+    // Thursday night is not covered
+    assert_eq!(quiet_until(&friday_night, at(3, "01:00")), None);
+    assert_eq!(quiet_until(&friday_night, at(4, "23:00")), None);
+
+    let whole_day = specs(&["sun 00:00-00:00"]);
+    assert_eq!(
+        quiet_until(&whole_day, at(5, "13:00")),
+        Some(at(6, "00:00"))
+    );
+    assert_eq!(quiet_until(&whole_day, at(6, "00:00")), None);
+}
+
+#[test]
+fn test_providers_have_their_own_windows() {
+    let mut ai = AIConfig::default();
+    ai.ollama.quiet_hours = specs(&["01:00-05:00", "broken"]);
+    assert_eq!(
+        provider_quiet_until(&ai, Provider::Ollama, at(3, "03:00")),
+        Some(at(3, "05:00"))
+    );
+    assert_eq!(
+        provider_quiet_until(&ai, Provider::OpenAI, at(3, "03:00")),
+        None
+    );
+
+    assert_eq!(format_until(at(3, "05:00"), at(3, "03:00")), "05:00");
+    assert_eq!(format_until(at(4, "02:00"), at(3, "23:00")), "Sat 02:00");
+}
+
+#[test]
+fn test_windows_are_read_from_yaml() {
+    let ollama: OllamaConfig = serde_yaml::from_str(
+        "endpoint: http://gpu-cluster:11434\nmodels: []\ncurrent_model_index: 0\nquiet_hours: ['mon-fri 02:00-02:30']\n",
+    )
+    .unwrap();
+    assert_eq!(ollama.quiet_hours, vec!["mon-fri 02:00-02:30"]);
+    let without: OllamaConfig = serde_yaml::from_str(
+        "endpoint: http://localhost:11434\nmodels: []\ncurrent_model_index: 0\n",
+    )
+    .unwrap();
+    assert!(without.quiet_hours.is_empty());
+}
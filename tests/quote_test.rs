@@ -0,0 +1,68 @@
+use ai_coder_interface_rs::app::App;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+const SESSION: &str = "🚀 AI Coder Interface\n\n\
+━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
+❯ how do I list files?\n\
+Use ls.\n\
+━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\
+$ !ls\n\
+Cargo.toml\n\
+src\n";
+
+fn ctrl_q() -> KeyEvent {
+    KeyEvent::new(KeyCode::Char('q'), KeyModifiers::CONTROL)
+}
+
+#[test]
+fn test_selected_lines_are_quoted_at_the_cursor() {
+    let mut app = App::new();
+    app.output_lines = vec!["one".to_string(), "two".to_string(), "three".to_string()];
+    app.is_selecting_text = true;
+    // Selected upwards, from the third line to the second
+    app.selection_start = 2;
+    app.selection_end = 1;
+    app.input = "about this".to_string();
+    app.cursor_position = app.input.len();
+
+    app.quote_selection();
+    // The quote starts on its own line and the cursor ends up after it
+    assert_eq!(app.input, "about this\n> two\n> three\n");
+    assert_eq!(app.cursor_position, app.input.len());
+    assert!(!app.is_selecting_text);
+}
+
+#[test]
+fn test_quote_is_inserted_before_the_rest_of_the_input() {
+    let mut app = App::new();
+    app.output_lines = vec!["error: boom".to_string()];
+    app.is_selecting_text = true;
+    app.input = "why?".to_string();
+    app.cursor_position = 0;
+
+    app.quote_selection();
+    assert_eq!(app.input, "> error: boom\nwhy?");
+    assert_eq!(app.cursor_position, "> error: boom\n".len());
+}
+
+#[test]
+fn test_ctrl_q_quotes_the_selected_message() {
+    let mut app = App::new();
+    app.output = SESSION.to_string();
+    app.selected_section = Some(1);
+
+    app.handle_message_key(ctrl_q());
+    assert_eq!(app.input, "> Cargo.toml\n> src\n");
+    assert_eq!(app.cursor_position, app.input.len());
+    assert_eq!(app.selected_section, None);
+}
+
+#[test]
+fn test_nothing_selected_leaves_the_input_alone() {
+    let mut app = App::new();
+    app.input = "draft".to_string();
+    app.cursor_position = 5;
+    app.quote_selection();
+    assert_eq!(app.input, "draft");
+    assert_eq!(app.cursor_position, 5);
+}
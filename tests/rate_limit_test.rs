@@ -0,0 +1,94 @@
+use ai_coder_interface_rs::ai::rate_limit::RateLimitInfo;
+use chrono::{TimeZone, Utc};
+use reqwest::header::{HeaderMap, HeaderValue};
+use std::time::Duration;
+
+#[test]
+fn test_rate_limit_headers() {
+    let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+
+    // OpenAI style: counts plus Go-style durations
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "x-ratelimit-remaining-requests",
+        HeaderValue::from_static("0"),
+    );
+    headers.insert(
+        "x-ratelimit-remaining-tokens",
+        HeaderValue::from_static("1200"),
+    );
+    headers.insert(
+        "x-ratelimit-reset-requests",
+        HeaderValue::from_static("1m12s"),
+    );
+    headers.insert(
+        "x-ratelimit-reset-tokens",
+        HeaderValue::from_static("250ms"),
+    );
+    let info = RateLimitInfo::from_headers_at(&headers, now);
+    assert_eq!(info.remaining_requests, Some(0));
+    assert_eq!(info.remaining_tokens, Some(1200));
+    assert_eq!(info.wait_time(), Some(Duration::from_secs(72)));
+
+    // Anthropic style: RFC 3339 reset timestamps
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "anthropic-ratelimit-tokens-remaining",
+        HeaderValue::from_static("0"),
+    );
+    headers.insert(
+        "anthropic-ratelimit-tokens-reset",
+        HeaderValue::from_static("2024-05-01T12:00:12Z"),
+    );
+    let info = RateLimitInfo::from_headers_at(&headers, now);
+    assert_eq!(info.wait_time(), Some(Duration::from_secs(12)));
+
+    // Retry-After always applies; limits that are not exhausted never do
+    let mut headers = HeaderMap::new();
+    headers.insert("retry-after", HeaderValue::from_static("5"));
+    headers.insert(
+        "x-ratelimit-remaining-requests",
+        HeaderValue::from_static("3"),
+    );
+    headers.insert(
+        "x-ratelimit-reset-requests",
+        HeaderValue::from_static("30s"),
+    );
+    let info = RateLimitInfo::from_headers_at(&headers, now);
+    assert_eq!(info.wait_time(), Some(Duration::from_secs(5)));
+
+    assert_eq!(
+        RateLimitInfo::from_headers(&HeaderMap::new()).wait_time(),
+        None
+    );
+}
+
+#[test]
+fn test_invalid_reset_values_give_no_hint() {
+    let now = Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap();
+    for value in [
+        "inf",
+        "-inf",
+        "NaN",
+        "-5",
+        "1e300",
+        "99999999999999999999999h",
+        "1.5.5s",
+        "10x",
+    ] {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after", HeaderValue::from_str(value).unwrap());
+        headers.insert(
+            "x-ratelimit-remaining-requests",
+            HeaderValue::from_static("0"),
+        );
+        headers.insert(
+            "x-ratelimit-reset-requests",
+            HeaderValue::from_str(value).unwrap(),
+        );
+        let info = RateLimitInfo::from_headers_at(&headers, now);
+        assert_eq!(info.retry_after, None, "{}", value);
+        assert_eq!(info.reset_requests, None, "{}", value);
+        assert_eq!(info.wait_time(), None, "{}", value);
+    }
+}
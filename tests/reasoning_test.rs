@@ -0,0 +1,58 @@
+use ai_coder_interface_rs::ai::openai_compat::parse_stream_line;
+use ai_coder_interface_rs::handlers::postprocess::split_thinking;
+use ai_coder_interface_rs::utils::transcript::{
+    Transcript, format_reasoning, reasoning_blocks, strip_reasoning,
+};
+
+#[test]
+fn test_think_tags_are_split_from_the_answer() {
+    assert_eq!(
+        split_thinking("<think>\nCheck the edge case.\n</think>\n\nUse `saturating_sub`."),
+        (
+            Some("Check the edge case.".to_string()),
+            "Use `saturating_sub`.".to_string()
+        )
+    );
+    // The opening tag was part of the prompt template
+    assert_eq!(
+        split_thinking("First, the loop.</think>Done"),
+        (Some("First, the loop.".to_string()), "Done".to_string())
+    );
+    assert_eq!(
+        split_thinking("Plain answer"),
+        (None, "Plain answer".to_string())
+    );
+}
+
+#[test]
+fn test_streamed_reasoning_is_kept_apart() {
+    let deepseek = parse_stream_line(
+        r#"data: {"choices":[{"delta":{"reasoning_content":"Let me think","content":null}}]}"#,
+    )
+    .unwrap();
+    assert_eq!(deepseek.reasoning, "Let me think");
+    assert_eq!(deepseek.content, "");
+
+    let openrouter =
+        parse_stream_line(r#"data: {"choices":[{"delta":{"reasoning":"Hmm","content":"Hi"}}]}"#)
+            .unwrap();
+    assert_eq!(openrouter.reasoning, "Hmm");
+    assert_eq!(openrouter.content, "Hi");
+}
+
+#[test]
+fn test_reasoning_is_not_part_of_the_exchange() {
+    let response = format!(
+        "{}\n\nThe answer.",
+        format_reasoning("Step one.\n\nStep two.")
+    );
+    assert_eq!(response, "┊ Step one.\n┊\n┊ Step two.\n\nThe answer.");
+    assert_eq!(strip_reasoning(&response), "The answer.");
+
+    let session = format!("━━━━\n❯ why?\n{}\n━━━━\n❯ next\nok", response);
+    let transcript = Transcript::parse(&session);
+    assert_eq!(transcript.exchanges[0].output, "The answer.");
+
+    let lines: Vec<&str> = session.lines().collect();
+    assert_eq!(reasoning_blocks(&lines), vec![2..5]);
+}
@@ -0,0 +1,35 @@
+use ai_coder_interface_rs::handlers::rewrite::{self, RewriteAction};
+
+#[test]
+fn test_parses_actions_and_append() {
+    assert_eq!(
+        rewrite::parse_args("shorter").unwrap(),
+        (RewriteAction::Shorter, false)
+    );
+    assert_eq!(
+        rewrite::parse_args("--append bullets").unwrap(),
+        (RewriteAction::Bullets, true)
+    );
+    assert_eq!(
+        rewrite::parse_args("translate Brazilian Portuguese --append").unwrap(),
+        (
+            RewriteAction::Translate("Brazilian Portuguese".to_string()),
+            true
+        )
+    );
+    assert!(rewrite::parse_args("").is_err());
+    assert!(rewrite::parse_args("translate").is_err());
+    assert!(rewrite::parse_args("translate --append").is_err());
+    assert!(rewrite::parse_args("longer").is_err());
+}
+
+#[test]
+fn test_prompt_carries_instruction_and_text() {
+    let action = RewriteAction::Translate("German".to_string());
+    let prompt = rewrite::build_prompt(&action, "Run `cargo test` first.");
+
+    assert!(prompt.contains("into German"));
+    assert!(prompt.ends_with("Run `cargo test` first."));
+    assert_eq!(action.describe(), "translated to German");
+    assert_eq!(RewriteAction::Bullets.describe(), "as bullet points");
+}
@@ -0,0 +1,122 @@
+use ai_coder_interface_rs::ai::Provider;
+use ai_coder_interface_rs::ai::routing::{self, PromptClass, parse_override};
+use ai_coder_interface_rs::config::{AIConfig, RouteTarget, RoutingConfig};
+
+fn routing() -> RoutingConfig {
+    RoutingConfig {
+        enabled: true,
+        simple: Some(RouteTarget {
+            provider: Provider::Ollama,
+            model: "qwen2.5-coder:1.5b".to_string(),
+        }),
+        code: Some(RouteTarget {
+            provider: Provider::OpenAI,
+            model: String::new(),
+        }),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_prompts_are_classified_by_kind() {
+    let config = RoutingConfig::default();
+    let classify = |prompt: &str| routing::classify(prompt, false, &config);
+    assert_eq!(classify("what does HTTP 418 mean?"), PromptClass::Simple);
+    assert_eq!(classify("write a parser for INI files"), PromptClass::Code);
+    assert_eq!(
+        classify("why does this fail?\n```\nlet x;\n```"),
+        PromptClass::Code
+    );
+    assert_eq!(classify(&"why ".repeat(40)), PromptClass::Code);
+    assert_eq!(classify("what is in screenshot.PNG?"), PromptClass::Vision);
+    assert_eq!(
+        routing::classify("summarize it", true, &config),
+        PromptClass::Code
+    );
+}
+
+#[test]
+fn test_prefix_overrides_the_tier() {
+    assert_eq!(
+        parse_override("%code  explain monads"),
+        (Some(Some(PromptClass::Code)), "explain monads")
+    );
+    assert_eq!(parse_override("%default hi"), (Some(None), "hi"));
+    assert_eq!(parse_override("%d of the total"), (None, "%d of the total"));
+    assert_eq!(parse_override("plain"), (None, "plain"));
+}
+
+#[test]
+fn test_routes_use_the_configured_tier() {
+    let ai = AIConfig::default();
+    let config = routing();
+
+    let (route, text) = routing::route("what is a monad?", false, &config, &ai);
+    let route = route.unwrap();
+    assert_eq!(text, "what is a monad?");
+    assert_eq!(route.to_string(), "simple → Ollama qwen2.5-coder:1.5b");
+
+    // An empty model is the provider's selected model
+    let (route, _) = routing::route("%code what is a monad?", false, &config, &ai);
+    let mut openai = ai.clone();
+    openai.active_provider = Provider::OpenAI;
+    assert_eq!(route.unwrap().model, openai.get_active_model_config().name);
+
+    // No vision model configured, routing off or %default: the active model
+    assert!(
+        routing::route("describe a.png", false, &config, &ai)
+            .0
+            .is_none()
+    );
+    assert!(
+        routing::route("%default write code", false, &config, &ai)
+            .0
+            .is_none()
+    );
+    let disabled = RoutingConfig {
+        enabled: false,
+        ..routing()
+    };
+    assert!(
+        routing::route("write code", false, &disabled, &ai)
+            .0
+            .is_none()
+    );
+    assert!(
+        routing::route("%simple write code", false, &disabled, &ai)
+            .0
+            .is_some()
+    );
+}
+
+#[test]
+fn test_tiers_in_quiet_hours_are_skipped() {
+    let mut ai = AIConfig::default();
+    // Equal times cover the whole day, whenever the test runs
+    ai.openai.quiet_hours = vec!["00:00-00:00".to_string()];
+    let config = routing();
+    assert!(
+        routing::route("%code refactor this", false, &config, &ai)
+            .0
+            .is_none()
+    );
+    assert!(
+        routing::route("what is a monad?", false, &config, &ai)
+            .0
+            .is_some()
+    );
+}
+
+#[test]
+fn test_selecting_an_unlisted_model_adds_it() {
+    let mut ai = AIConfig::default();
+    let listed = ai.ollama.models.len();
+    ai.select_model("tinyllama");
+    assert_eq!(ai.get_active_model_config().name, "tinyllama");
+    assert_eq!(ai.ollama.models.len(), listed + 1);
+
+    let first = ai.ollama.models[0].name.clone();
+    ai.select_model(&first);
+    assert_eq!(ai.ollama.current_model_index, 0);
+    assert_eq!(ai.ollama.models.len(), listed + 1);
+}
@@ -0,0 +1,37 @@
+use ai_coder_interface_rs::cli::{Options, parse_args};
+use ai_coder_interface_rs::handlers::files::FileBlock;
+use ai_coder_interface_rs::handlers::{bash, files, safe};
+use std::path::Path;
+
+fn args(list: &[&str]) -> Vec<String> {
+    list.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn test_command_line_options_are_parsed() {
+    assert_eq!(parse_args(args(&[])).unwrap(), Options::default());
+    let options = parse_args(args(&["--safe"])).unwrap();
+    assert!(options.safe && !options.help);
+    assert!(parse_args(args(&["-h"])).unwrap().help);
+    assert!(parse_args(args(&["--version"])).unwrap().version);
+    assert!(parse_args(args(&["--unsafe"])).is_err());
+}
+
+#[test]
+fn test_safe_mode_blocks_side_effects() {
+    let dir = tempfile::tempdir().unwrap();
+    safe::enable();
+
+    let error = bash::handle_bash_command("echo hi", Path::new("."))
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("safe mode"), "{}", error);
+
+    let block = FileBlock {
+        path: "notes.txt".into(),
+        language: None,
+        content: "hello".to_string(),
+    };
+    assert!(files::write_all(dir.path(), &[block]).is_err());
+    assert!(!dir.path().join("notes.txt").exists());
+}
@@ -0,0 +1,122 @@
+use ai_coder_interface_rs::ai::sampling::{apply, for_command, preset};
+use ai_coder_interface_rs::app::{AIPurpose, PendingPrompt};
+use ai_coder_interface_rs::config::{AIConfig, SamplingConfig, SamplingPreset};
+use std::collections::BTreeMap;
+
+fn bindings(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(key, name)| (key.to_string(), name.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_configured_presets_replace_built_in_ones() {
+    let mut config = SamplingConfig::default();
+    assert_eq!(
+        preset("deterministic", &config),
+        Some(SamplingPreset {
+            temperature: 0.0,
+            seed: Some(0)
+        })
+    );
+    assert_eq!(preset("creative", &config).unwrap().temperature, 1.0);
+    assert_eq!(preset("wild", &config), None);
+
+    config.presets.insert(
+        "creative".to_string(),
+        SamplingPreset {
+            temperature: 1.3,
+            seed: None,
+        },
+    );
+    assert_eq!(preset("creative", &config).unwrap().temperature, 1.3);
+}
+
+#[test]
+fn test_the_most_specific_key_chooses_the_preset() {
+    let config = SamplingConfig {
+        commands: bindings(&[
+            ("chat", "creative"),
+            ("template", "balanced"),
+            ("changelog", "precise"),
+        ]),
+        ..Default::default()
+    };
+    let name = |keys: &[&str]| for_command(keys, &config).unwrap().map(|(name, _)| name);
+    assert_eq!(
+        name(&["changelog", "template", "chat"]).as_deref(),
+        Some("precise")
+    );
+    assert_eq!(
+        name(&["review", "template", "chat"]).as_deref(),
+        Some("balanced")
+    );
+    assert_eq!(name(&["chat"]).as_deref(), Some("creative"));
+    assert_eq!(name(&["rewrite"]), None);
+}
+
+#[test]
+fn test_unknown_presets_are_reported() {
+    let config = SamplingConfig {
+        commands: bindings(&[("doc", "exact")]),
+        ..Default::default()
+    };
+    let error = for_command(&["doc"], &config).unwrap_err();
+    assert!(error.contains("'exact'"));
+    assert!(error.contains("doc"));
+}
+
+#[test]
+fn test_prompts_know_their_command() {
+    let chat = PendingPrompt::new("hi".to_string(), AIPurpose::Chat);
+    assert_eq!(chat.sampling_keys(), vec!["chat"]);
+    let templated = chat.with_template(Some("changelog".to_string()));
+    assert_eq!(
+        templated.sampling_keys(),
+        vec!["changelog", "template", "chat"]
+    );
+    let commit = PendingPrompt::new("diff".to_string(), AIPurpose::CommitMessage);
+    assert_eq!(commit.sampling_keys(), vec!["commitmsg"]);
+
+    let config = SamplingConfig::default();
+    let (name, _) = for_command(&commit.sampling_keys(), &config)
+        .unwrap()
+        .unwrap();
+    assert_eq!(name, "deterministic");
+}
+
+#[test]
+fn test_presets_set_the_temperature_and_seed() {
+    let mut ai = AIConfig::default();
+    apply(
+        &SamplingPreset {
+            temperature: 0.3,
+            seed: None,
+        },
+        &mut ai,
+    );
+    assert_eq!(ai.get_active_model_config().temperature, 0.3);
+    assert_eq!(ai.seed, None);
+
+    apply(
+        &SamplingPreset {
+            temperature: 0.0,
+            seed: Some(7),
+        },
+        &mut ai,
+    );
+    assert_eq!(ai.get_active_model_config().temperature, 0.0);
+    assert_eq!(ai.request_seed(), Some(7));
+}
+
+#[test]
+fn test_presets_are_read_from_yaml() {
+    let config: SamplingConfig = serde_yaml::from_str(
+        "presets:\n  exact:\n    temperature: 0.0\n    seed: 42\ncommands:\n  chat: creative\n  commitmsg: exact\n",
+    )
+    .unwrap();
+    assert_eq!(config.presets["exact"].seed, Some(42));
+    assert_eq!(config.commands["chat"], "creative");
+    assert!(!config.commands.contains_key("refactor"));
+}
@@ -0,0 +1,43 @@
+use ai_coder_interface_rs::app::ScrollAnchor;
+
+#[test]
+fn test_rows_are_anchored_to_their_section() {
+    let rows = [3, 10, 40];
+    assert_eq!(
+        ScrollAnchor::at(&rows, 12),
+        ScrollAnchor {
+            section: Some(1),
+            offset: 2
+        }
+    );
+    assert_eq!(
+        ScrollAnchor::at(&rows, 1),
+        ScrollAnchor {
+            section: None,
+            offset: 1
+        }
+    );
+    assert_eq!(ScrollAnchor::at(&rows, 45).section, Some(2));
+    assert_eq!(ScrollAnchor::at(&rows, 12).row(&rows), Some(12));
+}
+
+#[test]
+fn test_changes_above_the_anchor_do_not_move_it() {
+    let anchor = ScrollAnchor::at(&[3, 10, 40], 42);
+    // The second section collapsed to its summary
+    assert_eq!(anchor.row(&[3, 10, 13]), Some(15));
+    // New output below changes nothing
+    assert_eq!(anchor.row(&[3, 10, 40, 90]), Some(42));
+    // A longer startup message pushes everything down
+    assert_eq!(anchor.row(&[5, 12, 42]), Some(44));
+}
+
+#[test]
+fn test_shrinking_sections_keep_the_anchor_inside() {
+    let anchor = ScrollAnchor::at(&[0, 10, 40], 30);
+    assert_eq!(anchor.row(&[0, 10, 13]), Some(12));
+    let before = ScrollAnchor::at(&[5, 10], 4);
+    assert_eq!(before.row(&[2, 10]), Some(1));
+    // Cleared output has no sections to anchor to
+    assert_eq!(anchor.row(&[]), None);
+}
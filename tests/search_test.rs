@@ -0,0 +1,56 @@
+use ai_coder_interface_rs::ui::search::highlight;
+use ai_coder_interface_rs::utils::search::{match_ranges, next_match};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+
+#[test]
+fn test_lowercase_terms_match_any_case() {
+    assert_eq!(
+        match_ranges("Error: error ERROR", "error"),
+        vec![0..5, 7..12, 13..18]
+    );
+    assert_eq!(match_ranges("Error: error ERROR", "Error"), vec![0..5]);
+    assert_eq!(match_ranges("aaaa", "aa"), vec![0..2, 2..4]);
+    assert!(match_ranges("anything", "").is_empty());
+    // Byte ranges stay on character boundaries
+    assert_eq!(match_ranges("Ünïcode ünï", "ünï"), vec![0..5, 10..15]);
+}
+
+#[test]
+fn test_matches_wrap_around_the_ends() {
+    let matches = [3, 8, 20];
+    assert_eq!(next_match(&matches, None, true), Some(3));
+    assert_eq!(next_match(&matches, None, false), Some(20));
+    assert_eq!(next_match(&matches, Some(8), true), Some(20));
+    assert_eq!(next_match(&matches, Some(20), true), Some(3));
+    assert_eq!(next_match(&matches, Some(3), false), Some(20));
+    // The line shown last may no longer match
+    assert_eq!(next_match(&matches, Some(10), false), Some(8));
+    assert_eq!(next_match(&[], Some(1), true), None);
+}
+
+#[test]
+fn test_highlights_keep_the_style_around_them() {
+    let green = Style::default().fg(Color::Green);
+    let matched = Style::default().bg(Color::Yellow);
+    let line = Line::from(vec![Span::raw("+ let ma"), Span::styled("tch = 1;", green)]);
+
+    let highlighted = highlight(line, "match", matched);
+    let spans: Vec<(&str, Style)> = highlighted
+        .spans
+        .iter()
+        .map(|span| (span.content.as_ref(), span.style))
+        .collect();
+    assert_eq!(
+        spans,
+        vec![
+            ("+ let ", Style::default()),
+            ("ma", matched),
+            ("tch", green.patch(matched)),
+            (" = 1;", green),
+        ]
+    );
+
+    let plain = Line::from("nothing here");
+    assert_eq!(highlight(plain.clone(), "match", matched), plain);
+}
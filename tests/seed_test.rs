@@ -0,0 +1,33 @@
+use ai_coder_interface_rs::config::AIConfig;
+
+#[test]
+fn test_deterministic_mode_fixes_temperature_and_seed() {
+    let mut config = AIConfig::default();
+    assert_eq!(config.request_seed(), None);
+    assert_eq!(
+        config.request_model_config().temperature,
+        config.get_active_model_config().temperature
+    );
+
+    config.deterministic = true;
+    assert_eq!(config.request_seed(), Some(0));
+    assert_eq!(config.request_model_config().temperature, 0.0);
+
+    config.seed = Some(42);
+    assert_eq!(config.request_seed(), Some(42));
+    config.deterministic = false;
+    assert_eq!(config.request_seed(), Some(42));
+}
+
+#[test]
+fn test_seed_settings_default_when_missing() {
+    let config: AIConfig = serde_yaml::from_str(
+        &serde_yaml::to_string(&AIConfig::default())
+            .unwrap()
+            .replace("seed: null\n", "")
+            .replace("deterministic: false\n", ""),
+    )
+    .unwrap();
+    assert_eq!(config.seed, None);
+    assert!(!config.deterministic);
+}
@@ -0,0 +1,63 @@
+use ai_coder_interface_rs::utils::diff::find_unified_diffs;
+use ai_coder_interface_rs::utils::session_diff::{compare, conversation, summary};
+
+const OLD: &str = "Welcome\n\
+━━━\n❯ explain main.rs\n\nIt starts the app.\n\
+━━━\n$ ls\n[⏱️ 0.01s | ✓ | 📊 0]\nsrc\n\
+━━━\n❯ add a test\n\nUse #[test].\nRun cargo test.\n";
+
+const NEW: &str = "Welcome\n\
+━━━\n❯ explain main.rs\n\nIt starts the app.\n\
+━━━\n❯ add a unit test\n\nUse #[test].\nRun cargo test --lib.\n\
+━━━\n/ /model gpt-4o\n✅ Switched\n\
+━━━\n❯ thanks\n\nYou're welcome.\n";
+
+#[test]
+fn test_conversation_keeps_only_prompts() {
+    let old = conversation(OLD);
+    assert_eq!(old.len(), 2);
+    assert_eq!(old[1].input, "add a test");
+    assert_eq!(old[1].output, "Use #[test].\nRun cargo test.");
+    assert_eq!(conversation(NEW).len(), 3);
+}
+
+#[test]
+fn test_compare_pairs_prompts_by_position() {
+    let diffs = compare(&conversation(OLD), &conversation(NEW));
+    assert_eq!(diffs.len(), 3);
+    assert!(diffs[0].is_same());
+    assert!(diffs[1].prompt_changed());
+    assert!(diffs[1].response_changed());
+    assert!(diffs[2].old.is_none());
+    assert_eq!(
+        summary(&diffs, "a", "b"),
+        "3 prompt(s): 1 same, 1 changed, 1 only in b"
+    );
+
+    let same = compare(&conversation(OLD), &conversation(OLD));
+    assert!(same.iter().all(|diff| diff.is_same()));
+    assert_eq!(summary(&same, "a", "a"), "2 prompt(s): 2 same, 0 changed");
+}
+
+#[test]
+fn test_render_is_a_unified_diff() {
+    let diffs = compare(&conversation(OLD), &conversation(NEW));
+    let rendered = diffs[1].render("a", "b");
+    let lines: Vec<&str> = rendered.lines().collect();
+    assert_eq!(lines[0], "--- a #2");
+    assert_eq!(lines[1], "+++ b #2");
+    assert!(lines.contains(&"-❯ add a test"));
+    assert!(lines.contains(&"+❯ add a unit test"));
+    assert!(lines.contains(&"+Run cargo test --lib."));
+    // The output pane colors it like any other diff
+    assert_eq!(find_unified_diffs(&lines), vec![0..lines.len()]);
+    assert_eq!(diffs[1].stats(), (2, 2));
+
+    let added = diffs[2].render("a", "b");
+    assert!(added.contains("+❯ thanks"));
+    assert!(
+        !added
+            .lines()
+            .any(|line| line.starts_with('-') && line != "--- a #3")
+    );
+}
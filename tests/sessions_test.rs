@@ -0,0 +1,151 @@
+use ai_coder_interface_rs::ai::conversation::{Message, Role};
+use ai_coder_interface_rs::utils::crypto::{Cipher, is_encrypted};
+use ai_coder_interface_rs::utils::sessions::{
+    SessionStore, StoredSession, clean_title, draft_title, is_valid_name,
+};
+use chrono::{Duration, Local};
+use std::sync::Arc;
+
+fn session(id: &str, title: &str, minutes_ago: i64) -> StoredSession {
+    let updated = Local::now() - Duration::minutes(minutes_ago);
+    StoredSession {
+        id: id.to_string(),
+        title: title.to_string(),
+        created: updated,
+        updated,
+        messages: 2,
+        cost: 0.01,
+        prompt_tokens: 100,
+        completion_tokens: 50,
+        output: "━━━\n❯ hello\n\nHi!\n".to_string(),
+        history: vec!["hello".to_string()],
+        conversation: None,
+        command_count: 0,
+        ai_count: 1,
+        bash_count: 0,
+        models_used: Vec::new(),
+    }
+}
+
+#[test]
+fn test_sessions_are_listed_newest_first() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SessionStore::new(&dir.path().join("sessions"));
+    assert!(store.list().unwrap().is_empty());
+
+    store.save(&session("20240501-090000", "Old", 60)).unwrap();
+    store.save(&session("20240502-090000", "New", 1)).unwrap();
+    std::fs::write(dir.path().join("sessions/broken.json"), "{").unwrap();
+
+    let titles: Vec<String> = store.list().unwrap().into_iter().map(|s| s.title).collect();
+    assert_eq!(titles, ["New", "Old"]);
+
+    let mut renamed = session("20240501-090000", "Renamed", 0);
+    renamed.messages = 4;
+    store.save(&renamed).unwrap();
+    let loaded = store.load("20240501-090000").unwrap();
+    assert_eq!(loaded.title, "Renamed");
+    assert_eq!(loaded.messages, 4);
+    assert_eq!(store.list().unwrap().len(), 2);
+}
+
+#[test]
+fn test_titles_are_short_and_clean() {
+    assert_eq!(
+        draft_title("fix  the\nborrow error"),
+        "fix the borrow error"
+    );
+    assert_eq!(
+        draft_title("one two three four five six seven eight nine"),
+        "one two three four five six seven eight…"
+    );
+    assert_eq!(
+        clean_title("\nTitle: \"Fixing a Borrow Error.\"\nMore text"),
+        Some("Fixing a Borrow Error".to_string())
+    );
+    assert_eq!(
+        clean_title("**Async Rust Basics**"),
+        Some("Async Rust Basics".to_string())
+    );
+    assert_eq!(clean_title("  \n\"\""), None);
+    assert_eq!(clean_title(&"x".repeat(100)).unwrap().chars().count(), 60);
+}
+
+#[test]
+fn test_encrypted_sessions_need_the_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let sessions = dir.path().join("sessions");
+    let plain = SessionStore::new(&sessions);
+    plain
+        .save(&session("20240501-090000", "Written before", 60))
+        .unwrap();
+
+    let cipher = Arc::new(Cipher::from_key([7; 32]));
+    let encrypted = SessionStore::new(&sessions).with_cipher(Some(cipher));
+    encrypted
+        .save(&session("20240502-090000", "Secret", 1))
+        .unwrap();
+    let raw = std::fs::read(sessions.join("20240502-090000.json")).unwrap();
+    assert!(is_encrypted(&raw));
+
+    // Both are listed with the key; without it only the plain one is
+    assert_eq!(encrypted.list().unwrap().len(), 2);
+    assert_eq!(encrypted.load("20240502-090000").unwrap().title, "Secret");
+    let titles: Vec<String> = plain.list().unwrap().into_iter().map(|s| s.title).collect();
+    assert_eq!(titles, ["Written before"]);
+    assert!(plain.load("20240502-090000").is_err());
+
+    let other = SessionStore::new(&sessions).with_cipher(Some(Arc::new(Cipher::from_key([8; 32]))));
+    assert!(other.load("20240502-090000").is_err());
+}
+
+#[test]
+fn test_named_sessions_keep_history_and_conversation() {
+    let dir = tempfile::tempdir().unwrap();
+    let store = SessionStore::new(&dir.path().join("sessions"));
+    let mut named = session("refactor-parser", "refactor-parser", 0);
+    named.conversation = Some(vec![
+        Message {
+            role: Role::User,
+            content: "hello".to_string(),
+            source: None,
+        },
+        Message {
+            role: Role::Assistant,
+            content: "Hi!".to_string(),
+            source: None,
+        },
+    ]);
+    named.models_used = vec!["llama3".to_string()];
+    store.save(&named).unwrap();
+    assert!(dir.path().join("sessions/refactor-parser.json").exists());
+
+    let loaded = store.load("refactor-parser").unwrap();
+    assert_eq!(loaded.history, ["hello"]);
+    assert_eq!(loaded.conversation, named.conversation);
+    assert_eq!(loaded.ai_count, 1);
+    assert_eq!(loaded.models_used, ["llama3"]);
+
+    store.remove("refactor-parser").unwrap();
+    store.remove("refactor-parser").unwrap();
+    assert!(store.load("refactor-parser").is_err());
+}
+
+#[test]
+fn test_sessions_saved_before_history_was_kept_still_load() {
+    let json = r#"{"id":"20240501-090000","title":"Old","created":"2024-05-01T09:00:00+00:00",
+        "updated":"2024-05-01T09:30:00+00:00","messages":1,"cost":0.0,"output":"❯ hi\n"}"#;
+    let session: StoredSession = serde_json::from_str(json).unwrap();
+    assert!(session.history.is_empty());
+    assert_eq!(session.conversation, None);
+}
+
+#[test]
+fn test_session_names_are_file_names() {
+    assert!(is_valid_name("refactor-parser"));
+    assert!(is_valid_name("v2.1_fix"));
+    assert!(!is_valid_name(""));
+    assert!(!is_valid_name(".hidden"));
+    assert!(!is_valid_name("../escape"));
+    assert!(!is_valid_name("two words"));
+}
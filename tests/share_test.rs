@@ -0,0 +1,89 @@
+use ai_coder_interface_rs::cli::parse_args;
+use ai_coder_interface_rs::utils::share::{ShareServer, Snapshot, request_path};
+
+fn snapshot(output: &str) -> Snapshot {
+    Snapshot {
+        title: "pairing".to_string(),
+        output: output.to_string(),
+        busy: false,
+    }
+}
+
+#[test]
+fn test_share_is_a_command_line_option() {
+    assert!(parse_args(vec!["share".to_string()]).unwrap().share);
+    assert!(
+        parse_args(vec!["--safe".to_string(), "share".to_string()])
+            .unwrap()
+            .safe
+    );
+}
+
+#[test]
+fn test_only_get_requests_are_answered() {
+    assert_eq!(
+        request_path("GET /abc/events?x=1 HTTP/1.1\r\n"),
+        Ok("/abc/events")
+    );
+    assert_eq!(
+        request_path("POST /abc HTTP/1.1\r\n"),
+        Err("405 Method Not Allowed")
+    );
+    assert_eq!(request_path(""), Err("400 Bad Request"));
+}
+
+#[tokio::test]
+async fn test_viewers_follow_the_session_live() {
+    let server = ShareServer::start("127.0.0.1:0", snapshot("❯ hello\n"))
+        .await
+        .unwrap();
+    let url = server.url();
+    assert!(url.starts_with("http://127.0.0.1:"));
+    let client = reqwest::Client::new();
+
+    let page = client.get(&url).send().await.unwrap();
+    assert!(page.status().is_success());
+    assert!(page.text().await.unwrap().contains("EventSource"));
+
+    let current: serde_json::Value = client
+        .get(format!("{}/snapshot", url))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(current["output"], "❯ hello\n");
+
+    // The link only works with its token, and viewers cannot post
+    let base = url.rsplit_once('/').unwrap().0;
+    let guessed = client.get(format!("{}/0000", base)).send().await.unwrap();
+    assert_eq!(guessed.status().as_u16(), 404);
+    let posted = client.post(&url).body("x").send().await.unwrap();
+    assert_eq!(posted.status().as_u16(), 405);
+
+    let mut events = client.get(format!("{}/events", url)).send().await.unwrap();
+    let first = events.chunk().await.unwrap().unwrap();
+    assert!(String::from_utf8_lossy(&first).contains("hello"));
+
+    server.publish(snapshot("❯ hello\nworld\n"));
+    let next = events.chunk().await.unwrap().unwrap();
+    assert!(String::from_utf8_lossy(&next).contains("world"));
+
+    // Changes in place reach the viewers only when reported
+    server.update(|snapshot| {
+        snapshot.title = "unseen".to_string();
+        false
+    });
+    server.update(|snapshot| {
+        snapshot.busy = true;
+        true
+    });
+    let next = String::from_utf8_lossy(&events.chunk().await.unwrap().unwrap()).to_string();
+    assert!(next.contains("\"busy\":true"));
+    assert!(next.contains("unseen"));
+
+    // Stopping the server ends the stream
+    drop(server);
+    assert!(events.chunk().await.unwrap().is_none());
+}
@@ -0,0 +1,72 @@
+use ai_coder_interface_rs::config::{SpeechBackend, SpeechConfig};
+use ai_coder_interface_rs::utils::speech::{
+    Recording, clean_transcript, record_args, whisper_args,
+};
+use std::path::Path;
+
+#[test]
+fn test_record_command_needs_a_file_placeholder() {
+    let args = record_args("arecord -q -r 16000 {file}", Path::new("/tmp/a b.wav")).unwrap();
+    assert_eq!(args, vec!["arecord", "-q", "-r", "16000", "/tmp/a b.wav"]);
+    assert!(record_args("arecord -q", Path::new("x.wav")).is_err());
+    assert!(record_args("", Path::new("x.wav")).is_err());
+    assert!(record_args("sox 'unterminated {file}", Path::new("x.wav")).is_err());
+}
+
+#[test]
+fn test_whisper_prints_plain_text() {
+    let mut config = SpeechConfig {
+        whisper_model: "/models/ggml-small.bin".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(config.backend, SpeechBackend::Whisper);
+    assert_eq!(
+        whisper_args(&config, Path::new("rec.wav")),
+        vec![
+            "-m",
+            "/models/ggml-small.bin",
+            "-f",
+            "rec.wav",
+            "-nt",
+            "-np"
+        ]
+    );
+    config.language = "de".to_string();
+    assert!(
+        whisper_args(&config, Path::new("rec.wav"))
+            .ends_with(&["-l".to_string(), "de".to_string()])
+    );
+}
+
+#[test]
+fn test_transcripts_drop_markers_and_line_breaks() {
+    assert_eq!(
+        clean_transcript(" [BLANK_AUDIO]\n Why does the build\n fail on CI? [Music]\n"),
+        "Why does the build fail on CI?"
+    );
+    assert_eq!(clean_transcript("[BLANK_AUDIO]"), "");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_recordings_stop_when_interrupted() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let config = SpeechConfig {
+        // Writes some audio, then waits to be interrupted like a real recorder
+        record_command: r#"sh -c 'head -c 4000 /dev/zero > "$0"; exec sleep 30' {file}"#
+            .to_string(),
+        ..Default::default()
+    };
+    let recording = Recording::start(&config, dir.path().join("rec.wav")).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(300));
+    let path = recording.stop().unwrap();
+    assert_eq!(std::fs::metadata(path).unwrap().len(), 4000);
+
+    // A recorder that writes nothing is reported
+    let silent = SpeechConfig {
+        record_command: r#"sh -c 'exec sleep 30' {file}"#.to_string(),
+        ..Default::default()
+    };
+    let recording = Recording::start(&silent, dir.path().join("silent.wav")).unwrap();
+    assert!(recording.stop().is_err());
+}
@@ -0,0 +1,73 @@
+use ai_coder_interface_rs::ui::status::{StatusAction, StatusHits};
+use ai_coder_interface_rs::utils::tree::FileTree;
+use ratatui::layout::Rect;
+use std::fs;
+use std::path::PathBuf;
+
+#[test]
+fn test_clicks_are_mapped_to_the_segment_under_them() {
+    let area = Rect::new(0, 23, 40, 1);
+    let hits = StatusHits::layout(
+        area,
+        &[
+            (4, Some(StatusAction::ToggleMode)),
+            (1, None),
+            (8, Some(StatusAction::FileTree)),
+            (1, None),
+            (12, None),
+            (1, None),
+            (30, Some(StatusAction::Cost)),
+        ],
+    );
+    assert_eq!(hits.action_at(0, 23), Some(StatusAction::ToggleMode));
+    assert_eq!(hits.action_at(3, 23), Some(StatusAction::ToggleMode));
+    assert_eq!(hits.action_at(4, 23), None);
+    assert_eq!(hits.action_at(5, 23), Some(StatusAction::FileTree));
+    assert_eq!(hits.action_at(12, 23), Some(StatusAction::FileTree));
+    assert_eq!(hits.action_at(20, 23), None);
+    // The cost is cut off at the edge of the bar
+    assert_eq!(hits.action_at(39, 23), Some(StatusAction::Cost));
+    assert_eq!(hits.action_at(40, 23), None);
+    // Other rows belong to the output and input
+    assert_eq!(hits.action_at(0, 22), None);
+    assert_eq!(StatusHits::default().action_at(0, 23), None);
+}
+
+#[test]
+fn test_the_file_tree_lists_directories_first_and_expands_them() {
+    let dir = tempfile::tempdir().unwrap();
+    fs::create_dir_all(dir.path().join("src/ui")).unwrap();
+    fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+    fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+    fs::write(dir.path().join("src/main.rs"), "").unwrap();
+    fs::write(dir.path().join("src/ui/mod.rs"), "").unwrap();
+
+    let mut tree = FileTree::new(dir.path());
+    assert_eq!(tree.lines(), ["▸ src/", "  Cargo.toml"]);
+
+    tree.toggle();
+    assert_eq!(
+        tree.lines(),
+        ["▾ src/", "  ▸ ui/", "    main.rs", "  Cargo.toml"]
+    );
+    tree.move_by(1);
+    tree.toggle();
+    tree.move_by(1);
+    assert_eq!(
+        tree.selected_entry().unwrap().path,
+        PathBuf::from("src/ui/mod.rs")
+    );
+
+    // Left on a file collapses the directory it is in
+    tree.collapse();
+    assert_eq!(tree.selected_entry().unwrap().path, PathBuf::from("src/ui"));
+    assert_eq!(
+        tree.lines(),
+        ["▾ src/", "  ▸ ui/", "    main.rs", "  Cargo.toml"]
+    );
+    tree.move_by(-5);
+    tree.collapse();
+    assert_eq!(tree.lines(), ["▸ src/", "  Cargo.toml"]);
+    tree.move_by(isize::MAX);
+    assert_eq!(tree.selected, 1);
+}
@@ -0,0 +1,82 @@
+use ai_coder_interface_rs::ai::{
+    AIClient, AIError, AIResponse, ModelCosts, StreamChunk, TokenUsage,
+};
+use ai_coder_interface_rs::config::{PostProcessor, UiConfig};
+use ai_coder_interface_rs::handlers::postprocess::streaming_preview;
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// Client answering every prompt at once, without streaming
+struct Echo;
+
+#[async_trait]
+impl AIClient for Echo {
+    async fn generate(&self, prompt: &str, _context: Option<&str>) -> Result<AIResponse, AIError> {
+        Ok(AIResponse {
+            content: format!("echo: {}", prompt),
+            model: "echo".to_string(),
+            usage: TokenUsage {
+                prompt_tokens: 1,
+                completion_tokens: 2,
+                total_tokens: 3,
+            },
+            progress: None,
+            cost: None,
+            reasoning: None,
+            truncated: false,
+        })
+    }
+
+    async fn models(&self) -> Result<Vec<String>, AIError> {
+        Ok(vec!["echo".to_string()])
+    }
+
+    fn get_model_costs(&self, _model: &str) -> ModelCosts {
+        ModelCosts {
+            prompt_cost_per_1k: 0.0,
+            completion_cost_per_1k: 0.0,
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_clients_without_streaming_send_the_answer_as_one_chunk() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let response = Echo
+        .generate_stream("hi", None, tx, Arc::new(|_| {}))
+        .await
+        .unwrap();
+    assert_eq!(response.content, "echo: hi");
+    assert_eq!(
+        rx.recv().await,
+        Some(StreamChunk::Content("echo: hi".to_string()))
+    );
+    assert_eq!(rx.recv().await, None);
+}
+
+#[test]
+fn test_previews_hide_reasoning_and_mask_secrets() {
+    assert_eq!(streaming_preview(&[], "<think>still planning"), "");
+    assert_eq!(
+        streaming_preview(&[], "<think>plan</think>\nHere is"),
+        "Here is"
+    );
+    assert_eq!(
+        streaming_preview(&[], "Use sk-abcdef123456"),
+        "Use sk-abcdef123456"
+    );
+    assert_eq!(
+        streaming_preview(&[PostProcessor::RedactSecrets], "Use sk-abcdef123456"),
+        "Use ***"
+    );
+}
+
+#[test]
+fn test_responses_stream_unless_switched_off() {
+    assert!(UiConfig::default().stream_responses);
+    let ui: UiConfig = serde_yaml::from_str("collapse_after_lines: 10\n").unwrap();
+    assert!(ui.stream_responses);
+    let ui: UiConfig =
+        serde_yaml::from_str("collapse_after_lines: 10\nstream_responses: false\n").unwrap();
+    assert!(!ui.stream_responses);
+}
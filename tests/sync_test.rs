@@ -0,0 +1,173 @@
+use ai_coder_interface_rs::config::{SyncBackend, SyncConfig};
+use ai_coder_interface_rs::utils::crypto::{Cipher, CryptoError, hex, is_encrypted, parse_key};
+use ai_coder_interface_rs::utils::sync::{self, Remote, SyncFile, SyncIndex, valid_name};
+use std::process::Command;
+
+#[test]
+fn test_encrypted_data_opens_with_the_same_passphrase_only() {
+    let cipher = Cipher::new("correct horse").unwrap();
+    let blob = cipher.encrypt(b"session output").unwrap();
+    assert!(is_encrypted(&blob));
+    assert!(!blob.windows(7).any(|w| w == b"session"));
+    assert_eq!(cipher.decrypt(&blob).unwrap(), b"session output");
+
+    // Another machine derives the key from the salt in the blob
+    let other = Cipher::new("correct horse").unwrap();
+    assert_eq!(other.decrypt(&blob).unwrap(), b"session output");
+
+    let wrong = Cipher::new("battery staple").unwrap();
+    assert!(matches!(wrong.decrypt(&blob), Err(CryptoError::Decrypt)));
+
+    let mut tampered = blob.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert!(matches!(
+        cipher.decrypt(&tampered),
+        Err(CryptoError::Decrypt)
+    ));
+
+    assert!(matches!(cipher.decrypt(b"{}"), Err(CryptoError::Format)));
+    assert!(matches!(Cipher::new(""), Err(CryptoError::NoPassphrase)));
+}
+
+#[test]
+fn test_raw_keys_round_trip_through_hex() {
+    let key: [u8; 32] = std::array::from_fn(|i| i as u8 * 7);
+    assert_eq!(parse_key(&hex(&key)), Some(key));
+    assert_eq!(parse_key("abc"), None);
+    assert_eq!(parse_key(&"zz".repeat(32)), None);
+
+    let cipher = Cipher::from_key(key);
+    let blob = cipher.encrypt(b"history").unwrap();
+    assert_eq!(Cipher::from_key(key).decrypt(&blob).unwrap(), b"history");
+    assert!(Cipher::new("passphrase").unwrap().decrypt(&blob).is_err());
+}
+
+#[test]
+fn test_remote_names_must_be_plain_file_names() {
+    assert!(valid_name("session-20240501-090000.json"));
+    assert!(valid_name("prompts.json"));
+    assert!(!valid_name("../config.yaml"));
+    assert!(!valid_name("sessions/a.json"));
+    assert!(!valid_name(".git"));
+    assert!(!valid_name(""));
+}
+
+#[test]
+fn test_remotes_are_built_from_the_sync_section() {
+    let dir = std::path::Path::new("/tmp/sync");
+    let mut config = SyncConfig::default();
+    assert!(Remote::from_config(&config, dir).is_err());
+
+    config.backend = SyncBackend::S3;
+    assert!(Remote::from_config(&config, dir).is_err());
+    config.url = "https://s3.example.com/".to_string();
+    config.bucket = "my-bucket/ai-coder".to_string();
+    let remote = Remote::from_config(&config, dir).unwrap();
+    assert_eq!(
+        remote.describe(),
+        "S3 https://s3.example.com/my-bucket/ai-coder/"
+    );
+    let Remote::S3 { region, .. } = remote else {
+        panic!("expected an S3 remote");
+    };
+    assert_eq!(region, "us-east-1");
+
+    config.backend = SyncBackend::WebDav;
+    config.url = "https://dav.example.com/ai-coder".to_string();
+    assert_eq!(
+        Remote::from_config(&config, dir).unwrap().describe(),
+        "WebDAV https://dav.example.com/ai-coder"
+    );
+}
+
+#[test]
+fn test_s3_requests_are_signed_for_the_request_scope() {
+    let url = reqwest::Url::parse("https://s3.example.com/bucket/index.enc").unwrap();
+    let hash = sync::fingerprint(b"");
+    let header = sync::s3_authorization(
+        "GET",
+        &url,
+        "eu-west-1",
+        "AKIDEXAMPLE",
+        "secret",
+        &hash,
+        "20240501T120000Z",
+    );
+    assert!(header.starts_with(
+        "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20240501/eu-west-1/s3/aws4_request, \
+         SignedHeaders=host;x-amz-content-sha256;x-amz-date, Signature="
+    ));
+    let signature = header.rsplit('=').next().unwrap();
+    assert_eq!(signature.len(), 64);
+
+    let other = sync::s3_authorization(
+        "PUT",
+        &url,
+        "eu-west-1",
+        "AKIDEXAMPLE",
+        "secret",
+        &hash,
+        "20240501T120000Z",
+    );
+    assert_ne!(header, other);
+}
+
+fn git_available() -> bool {
+    Command::new("git").arg("--version").output().is_ok()
+}
+
+#[tokio::test]
+async fn test_sessions_pushed_from_one_machine_are_pulled_on_another() {
+    if !git_available() {
+        return;
+    }
+    let root = tempfile::tempdir().unwrap();
+    let bare = root.path().join("remote.git");
+    let status = Command::new("git")
+        .args(["init", "--quiet", "--bare"])
+        .arg(&bare)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let config = SyncConfig {
+        backend: SyncBackend::Git,
+        url: bare.to_string_lossy().into_owned(),
+        ..SyncConfig::default()
+    };
+    let laptop = Remote::from_config(&config, &root.path().join("laptop")).unwrap();
+    let desktop = Remote::from_config(&config, &root.path().join("desktop")).unwrap();
+    let cipher = Cipher::new("passphrase").unwrap();
+
+    let mut files = vec![
+        SyncFile::new("session-1.json", b"{\"id\":\"1\"}".to_vec()),
+        SyncFile::new("prompts.json", b"{\"prompts\":[]}".to_vec()),
+    ];
+    assert_eq!(sync::push(&laptop, &cipher, &files).await.unwrap(), 2);
+    assert_eq!(sync::push(&laptop, &cipher, &files).await.unwrap(), 0);
+
+    // Only ciphertext reaches the remote
+    let stored = std::fs::read(root.path().join("laptop/session-1.json.enc")).unwrap();
+    assert!(is_encrypted(&stored));
+
+    let desktop_cipher = Cipher::new("passphrase").unwrap();
+    let pulled = sync::pull(&desktop, &desktop_cipher, &SyncIndex::new())
+        .await
+        .unwrap();
+    assert_eq!(pulled.len(), 2);
+    assert!(pulled.contains(&files[0]));
+
+    // A later change travels alone
+    files[0].data = b"{\"id\":\"1\",\"messages\":2}".to_vec();
+    assert_eq!(sync::push(&laptop, &cipher, &files).await.unwrap(), 1);
+    let local = sync::local_index(&pulled);
+    let pulled = sync::pull(&desktop, &desktop_cipher, &local).await.unwrap();
+    assert_eq!(pulled, vec![files[0].clone()]);
+
+    let wrong = Cipher::new("other").unwrap();
+    assert!(
+        sync::pull(&desktop, &wrong, &SyncIndex::new())
+            .await
+            .is_err()
+    );
+}
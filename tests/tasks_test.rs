@@ -0,0 +1,199 @@
+use ai_coder_interface_rs::ai::types::TaskStatus;
+use ai_coder_interface_rs::app::App;
+use ai_coder_interface_rs::handlers::files::FileBlock;
+use ai_coder_interface_rs::utils::undo::UndoStore;
+use ai_coder_interface_rs::utils::{TaskId, TaskManager, TaskPriority, TaskType};
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[test]
+fn test_higher_priority_tasks_start_first() {
+    let manager = TaskManager::new();
+    manager.set_max_concurrent(1);
+
+    let index = manager.create_task_with("index", TaskType::Other, TaskPriority::Low, &["index"]);
+    let ai = manager.create_task("explain", TaskType::AIGeneration);
+
+    // The AI task was created later but has a higher priority
+    assert!(!manager.try_start(index));
+    assert!(manager.try_start(ai));
+
+    // The only slot is taken until the AI task finishes
+    assert!(!manager.try_start(index));
+    manager.update_task_status(ai, TaskStatus::Completed);
+    assert!(manager.try_start(index));
+
+    assert_eq!(manager.labels(), vec!["ai", "index"]);
+    assert!(manager.get_task(index).unwrap().has_label("index"));
+}
+
+#[test]
+fn test_no_limit_starts_every_task() {
+    let manager = TaskManager::new();
+    let first = manager.create_task("first", TaskType::BashCommand);
+    let second = manager.create_task("second", TaskType::BashCommand);
+
+    assert!(manager.try_start(first));
+    assert!(manager.try_start(second));
+    assert_eq!(manager.running_count(), 2);
+}
+
+#[test]
+fn test_no_limit_ignores_waiting_tasks_nobody_starts() {
+    let manager = TaskManager::new();
+    let index = manager.create_task_with("index", TaskType::Other, TaskPriority::High, &[]);
+    let bash = manager.create_task("ls", TaskType::BashCommand);
+
+    // The older, higher-priority task is never polled and does not block
+    assert!(manager.try_start(bash));
+    assert_eq!(manager.get_task(index).unwrap().status, TaskStatus::Pending);
+
+    // With a limit it is next in line again
+    manager.set_max_concurrent(2);
+    let other = manager.create_task("pwd", TaskType::BashCommand);
+    assert!(!manager.try_start(other));
+    assert!(manager.try_start(index));
+}
+
+#[test]
+fn test_dependent_task_waits_for_its_dependency() {
+    let manager = TaskManager::new();
+    let patch = manager.create_task("apply patch", TaskType::FileOperation);
+    let tests = manager.create_task("run tests", TaskType::BashCommand);
+
+    assert!(manager.add_dependency(tests, patch));
+    // Cycles are rejected
+    assert!(!manager.add_dependency(patch, tests));
+
+    assert!(!manager.try_start(tests));
+    assert!(manager.try_start(patch));
+    assert!(!manager.try_start(tests));
+
+    manager.update_task_status(patch, TaskStatus::Completed);
+    assert!(manager.try_start(tests));
+}
+
+#[test]
+fn test_failed_dependency_cancels_dependents() {
+    let manager = TaskManager::new();
+    let patch = manager.create_task("apply patch", TaskType::FileOperation);
+    let tests = manager.create_task("run tests", TaskType::BashCommand);
+    let deploy = manager.create_task("deploy", TaskType::BashCommand);
+    assert!(manager.add_dependency(tests, patch));
+    assert!(manager.add_dependency(deploy, tests));
+
+    assert!(manager.try_start(patch));
+    manager.update_task_status(patch, TaskStatus::Failed);
+
+    assert_eq!(
+        manager.get_task(tests).unwrap().status,
+        TaskStatus::Cancelled
+    );
+    assert_eq!(
+        manager.get_task(deploy).unwrap().status,
+        TaskStatus::Cancelled
+    );
+    assert!(!manager.try_start(deploy));
+}
+
+/// Wait until a task has finished, failing the test after a few seconds
+async fn finished_status(manager: &TaskManager, id: TaskId) -> TaskStatus {
+    for _ in 0..100 {
+        let status = manager.get_task(id).unwrap().status;
+        if !matches!(status, TaskStatus::Pending | TaskStatus::Running) {
+            return status;
+        }
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+    panic!("task {} did not finish", id.short());
+}
+
+#[tokio::test]
+async fn test_bash_command_waits_for_a_queued_file_write() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut app = App::new();
+    app.current_dir = dir.path().to_path_buf();
+    app.undo = UndoStore::new(&dir.path().join(".undo"), "test");
+    app.task_manager.set_max_concurrent(1);
+    let generation = app
+        .task_manager
+        .create_task("generation", TaskType::AIGeneration);
+    assert!(app.task_manager.try_start(generation));
+
+    // The only slot is taken, so the patch waits and the tests wait for it
+    let patch = app.queue_file_write(
+        "Save notes.txt".to_string(),
+        vec![FileBlock {
+            path: PathBuf::from("notes.txt"),
+            language: None,
+            content: "notes".to_string(),
+        }],
+    );
+    let tests = app.start_bash("test -f notes.txt".to_string());
+    tokio::time::sleep(Duration::from_millis(300)).await;
+    assert_eq!(
+        app.task_manager.get_task(patch).unwrap().status,
+        TaskStatus::Pending
+    );
+    assert_eq!(
+        app.task_manager.get_task(tests).unwrap().status,
+        TaskStatus::Pending
+    );
+    assert!(!dir.path().join("notes.txt").exists());
+
+    app.task_manager
+        .update_task_status(generation, TaskStatus::Completed);
+    app.start_waiting_writes();
+    assert_eq!(
+        app.task_manager.get_task(patch).unwrap().status,
+        TaskStatus::Completed
+    );
+    // `test -f` only succeeds if the file was written before it ran
+    assert_eq!(
+        finished_status(&app.task_manager, tests).await,
+        TaskStatus::Completed
+    );
+}
+
+#[tokio::test]
+async fn test_cancelled_background_job_stays_cancelled() {
+    let mut app = App::new();
+    let (results, mut received) = tokio::sync::mpsc::unbounded_channel();
+
+    let finished = app.task_manager.create_task("fetch", TaskType::Other);
+    app.task_manager
+        .update_task_status(finished, TaskStatus::Running);
+    let cancelled = app.task_manager.create_task("fetch", TaskType::Other);
+    app.task_manager
+        .update_task_status(cancelled, TaskStatus::Running);
+
+    let job = |value: i32| async move {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        Ok::<i32, String>(value)
+    };
+    let status = |result: &Result<i32, String>| match result {
+        Ok(_) => TaskStatus::Completed,
+        Err(_) => TaskStatus::Failed,
+    };
+    let on_cancel = |_| Some(Err("cancelled".to_string()));
+    app.spawn_tracked(finished, results.clone(), job(1), status, on_cancel);
+    app.spawn_tracked(cancelled, results, job(2), status, on_cancel);
+    assert!(app.cancel_task(cancelled));
+
+    let mut delivered = Vec::new();
+    for _ in 0..2 {
+        delivered.push(received.recv().await.unwrap());
+    }
+    delivered.sort();
+    assert_eq!(delivered, vec![Ok(1), Err("cancelled".to_string())]);
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(
+        app.task_manager.get_task(finished).unwrap().status,
+        TaskStatus::Completed
+    );
+    // The job's result does not turn the cancelled task into a completed one
+    assert_eq!(
+        app.task_manager.get_task(cancelled).unwrap().status,
+        TaskStatus::Cancelled
+    );
+}
@@ -0,0 +1,130 @@
+use ai_coder_interface_rs::utils::templates::{self, Template};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+#[test]
+fn test_templates_render_their_input() {
+    let review = Template::parse(
+        "review".to_string(),
+        "# Review a file\nReview {{input}} for bugs.\n",
+    );
+    assert_eq!(review.description.as_deref(), Some("Review a file"));
+    assert_eq!(review.render(" src/lib.rs "), "Review src/lib.rs for bugs.");
+
+    let plain = Template::parse("explain".to_string(), "Explain this code.");
+    assert_eq!(plain.description, None);
+    assert_eq!(plain.render(""), "Explain this code.");
+    assert_eq!(
+        plain.render("fn main() {}"),
+        "Explain this code.\n\nfn main() {}"
+    );
+}
+
+#[test]
+fn test_personal_templates_come_before_team_ones() {
+    let dir = tempfile::tempdir().unwrap();
+    let write = |path: &str, content: &str| {
+        let path = dir.path().join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    };
+    write("team/review.md", "# Team review\nTeam review of {{input}}");
+    write("team/docs/changelog.txt", "Write a changelog");
+    write("team/.git/HEAD.md", "not a template");
+    write(
+        "team/commands.yaml",
+        "lint: \"!cargo clippy\"\n/Deploy: \"!make deploy\"\n",
+    );
+    write("explain.md", "Explain");
+    write("notes.json", "{}");
+
+    let all = templates::load(dir.path());
+    let names: Vec<&str> = all.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, ["explain", "team/docs/changelog", "team/review"]);
+    assert!(all[2].is_team());
+
+    // A bare name also finds a team template
+    assert_eq!(
+        templates::find(&all, "review").map(|t| t.name.as_str()),
+        Some("team/review")
+    );
+    assert!(templates::find(&all, "missing").is_none());
+
+    let commands = templates::team_commands(dir.path());
+    assert_eq!(commands["team/lint"], "!cargo clippy");
+    assert_eq!(commands["team/deploy"], "!make deploy");
+    assert!(templates::parse_commands("- not a map").is_err());
+}
+
+fn git(dir: &Path, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[tokio::test]
+async fn test_team_repository_is_cloned_and_reset_to_the_remote() {
+    if Command::new("git").arg("--version").output().is_err() {
+        return;
+    }
+    let root = tempfile::tempdir().unwrap();
+    let origin = root.path().join("origin");
+    fs::create_dir_all(&origin).unwrap();
+    git(&origin, &["init", "--quiet"]);
+    fs::write(origin.join("review.md"), "Review {{input}}").unwrap();
+    git(&origin, &["add", "."]);
+    git(
+        &origin,
+        &[
+            "-c",
+            "user.name=t",
+            "-c",
+            "user.email=t@t",
+            "commit",
+            "--quiet",
+            "-m",
+            "first",
+        ],
+    );
+
+    let templates_dir = root.path().join("templates");
+    let dest = templates::team_dir(&templates_dir);
+    let url = origin.to_string_lossy().into_owned();
+    templates::update_team(&url, "", &dest).await.unwrap();
+    assert!(dest.join("review.md").is_file());
+
+    // Local edits in the team namespace are dropped on the next update
+    fs::write(dest.join("review.md"), "changed").unwrap();
+    fs::write(dest.join("extra.md"), "extra").unwrap();
+    fs::write(origin.join("explain.md"), "Explain").unwrap();
+    git(&origin, &["add", "."]);
+    git(
+        &origin,
+        &[
+            "-c",
+            "user.name=t",
+            "-c",
+            "user.email=t@t",
+            "commit",
+            "--quiet",
+            "-m",
+            "second",
+        ],
+    );
+    templates::update_team(&url, "", &dest).await.unwrap();
+
+    let names: Vec<String> = templates::load(&templates_dir)
+        .into_iter()
+        .map(|t| t.name)
+        .collect();
+    assert_eq!(names, ["team/explain", "team/review"]);
+    assert_eq!(
+        fs::read_to_string(dest.join("review.md")).unwrap(),
+        "Review {{input}}"
+    );
+}
@@ -0,0 +1,81 @@
+use ai_coder_interface_rs::ai::types::ProgressStats;
+use ai_coder_interface_rs::config::TaskbarProgress;
+use ai_coder_interface_rs::ui::title::{self, TaskbarState};
+use ai_coder_interface_rs::utils::tasks::{Task, TaskType};
+use std::path::Path;
+
+fn generation(percent: Option<f64>) -> Task {
+    let mut task = Task::new("prompt", TaskType::AIGeneration);
+    task.mark_running();
+    if let Some(percent) = percent {
+        task.set_progress(ProgressStats {
+            completion_percent: Some(percent),
+            ..ProgressStats::new()
+        });
+    }
+    task
+}
+
+#[test]
+fn test_the_title_names_the_directory_and_marks_generations() {
+    let dir = Path::new("/home/dev/ai-coder");
+    assert_eq!(title::title(dir, false), "AI Coder — ai-coder");
+    assert_eq!(title::title(dir, true), "⏳ AI Coder — ai-coder");
+    assert_eq!(title::title(Path::new("/"), false), "AI Coder — /");
+    assert_eq!(
+        title::title_sequence("AI Coder — a\x07b"),
+        "\x1b]0;AI Coder — ab\x07"
+    );
+}
+
+#[test]
+fn test_progress_follows_the_least_advanced_generation() {
+    assert_eq!(title::taskbar_state(&[]), TaskbarState::Hidden);
+
+    let mut bash = Task::new("make", TaskType::BashCommand);
+    bash.mark_running();
+    assert_eq!(title::taskbar_state(&[bash.clone()]), TaskbarState::Hidden);
+
+    assert_eq!(
+        title::taskbar_state(&[generation(Some(80.0)), generation(Some(42.4)), bash]),
+        TaskbarState::Progress(42)
+    );
+    assert_eq!(
+        title::taskbar_state(&[generation(Some(80.0)), generation(None)]),
+        TaskbarState::Indeterminate
+    );
+
+    let mut failed = generation(None);
+    failed.mark_failed();
+    assert_eq!(title::taskbar_state(&[failed.clone()]), TaskbarState::Error);
+    let mut done = generation(None);
+    done.mark_completed();
+    assert_eq!(title::taskbar_state(&[failed, done]), TaskbarState::Hidden);
+}
+
+#[test]
+fn test_progress_is_sent_as_osc_9_4() {
+    assert_eq!(TaskbarState::Hidden.sequence(), "\x1b]9;4;0;0\x07");
+    assert_eq!(TaskbarState::Progress(42).sequence(), "\x1b]9;4;1;42\x07");
+    assert_eq!(TaskbarState::Progress(120).sequence(), "\x1b]9;4;1;100\x07");
+    assert_eq!(TaskbarState::Error.sequence(), "\x1b]9;4;2;100\x07");
+    assert_eq!(TaskbarState::Indeterminate.sequence(), "\x1b]9;4;3;0\x07");
+}
+
+#[test]
+fn test_auto_progress_is_only_sent_to_terminals_that_show_it() {
+    let none = |_: &str| None;
+    let windows_terminal = |name: &str| (name == "WT_SESSION").then(|| "4f1c".to_string());
+    let conemu = |name: &str| (name == "ConEmuANSI").then(|| "ON".to_string());
+    assert!(!title::progress_enabled(TaskbarProgress::Auto, none));
+    assert!(title::progress_enabled(
+        TaskbarProgress::Auto,
+        windows_terminal
+    ));
+    assert!(title::progress_enabled(TaskbarProgress::Auto, conemu));
+    assert!(title::progress_enabled(TaskbarProgress::On, none));
+    assert!(!title::progress_enabled(
+        TaskbarProgress::Off,
+        windows_terminal
+    ));
+}
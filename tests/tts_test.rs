@@ -0,0 +1,47 @@
+use ai_coder_interface_rs::config::{TtsConfig, TtsEngine};
+use ai_coder_interface_rs::utils::tts::{Speaker, speakable_text};
+
+#[test]
+fn test_code_blocks_are_not_read() {
+    let response = "## Fix\n\nCall `parse` first:\n\n```rust\nlet x = parse(input)?;\n```\n\n- See [the docs](https://docs.rs/x) for **details**\n~~~\nmore code\n~~~\n> Done";
+    assert_eq!(
+        speakable_text(response),
+        "Fix\nCall parse first:\nSee the docs for details\nDone"
+    );
+}
+
+#[test]
+fn test_unclosed_fences_hide_the_rest() {
+    assert_eq!(speakable_text("Here:\n```\ncode\nstill code"), "Here:");
+    assert_eq!(speakable_text("[not a link] (x)"), "[not a link] (x)");
+}
+
+#[test]
+fn test_tts_defaults_to_the_system_engine() {
+    let config = TtsConfig::default();
+    assert!(!config.enabled);
+    assert_eq!(config.engine, TtsEngine::System);
+    assert!(config.player_command.contains("{file}"));
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_speaking_can_be_stopped() {
+    let config = TtsConfig {
+        command: "sh -c 'cat > /dev/null; sleep 10'".to_string(),
+        ..Default::default()
+    };
+    let speaker = Speaker::default();
+    assert!(!speaker.is_speaking());
+    speaker.speak(&config, "Hello there").await.unwrap();
+    assert!(speaker.is_speaking());
+    assert!(speaker.stop());
+    assert!(!speaker.is_speaking());
+    assert!(!speaker.stop());
+
+    let missing = TtsConfig {
+        command: "ai-coder-no-such-engine".to_string(),
+        ..Default::default()
+    };
+    assert!(speaker.speak(&missing, "Hello").await.is_err());
+}
@@ -0,0 +1,57 @@
+use ai_coder_interface_rs::utils::undo::UndoStore;
+use std::fs;
+
+#[test]
+fn test_undo_restores_and_removes_files() {
+    let project = tempfile::tempdir().unwrap();
+    let undo_dir = tempfile::tempdir().unwrap();
+    let existing = project.path().join("main.rs");
+    let created = project.path().join("new.rs");
+    fs::write(&existing, "original\n").unwrap();
+
+    let mut store = UndoStore::new(undo_dir.path(), "session");
+    store
+        .record("Edit files", &[existing.clone(), created.clone()])
+        .unwrap();
+    fs::write(&existing, "changed\n").unwrap();
+    fs::write(&created, "new\n").unwrap();
+
+    let reverted = store.undo(1).unwrap();
+    assert_eq!(reverted.len(), 1);
+    assert_eq!(fs::read_to_string(&existing).unwrap(), "original\n");
+    assert!(!created.exists());
+    assert!(store.points().is_empty());
+    assert!(store.undo(1).unwrap().is_empty());
+}
+
+#[test]
+fn test_undo_journal_survives_reopening_the_session() {
+    let project = tempfile::tempdir().unwrap();
+    let undo_dir = tempfile::tempdir().unwrap();
+    let file = project.path().join("lib.rs");
+    fs::write(&file, "v1\n").unwrap();
+
+    let mut store = UndoStore::open(undo_dir.path(), "session");
+    store.record("First edit", &[file.clone()]).unwrap();
+    fs::write(&file, "v2\n").unwrap();
+    store.record("Second edit", &[file.clone()]).unwrap();
+    fs::write(&file, "v3\n").unwrap();
+
+    // A later run reads the journal back and keeps numbering after it
+    let mut reopened = UndoStore::open(undo_dir.path(), "session");
+    let labels: Vec<&str> = reopened.points().iter().map(|p| p.label.as_str()).collect();
+    assert_eq!(labels, ["First edit", "Second edit"]);
+    assert_eq!(reopened.record("Third edit", &[]).unwrap(), 3);
+    reopened.discard_last();
+
+    // Renaming the session takes the journal along
+    reopened.rename("named").unwrap();
+    assert!(
+        UndoStore::open(undo_dir.path(), "session")
+            .points()
+            .is_empty()
+    );
+    let mut renamed = UndoStore::open(undo_dir.path(), "named");
+    assert_eq!(renamed.undo(2).unwrap().len(), 2);
+    assert_eq!(fs::read_to_string(&file).unwrap(), "v1\n");
+}
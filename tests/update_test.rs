@@ -0,0 +1,105 @@
+use ai_coder_interface_rs::handlers::update::{
+    Release, changelog, newer_releases, parse_version, replace_binary, verify_checksum,
+};
+use std::fs;
+
+fn release(tag: &str, assets: &[&str]) -> Release {
+    let assets: Vec<serde_json::Value> = assets
+        .iter()
+        .map(|name| {
+            serde_json::json!({
+                "name": name,
+                "browser_download_url": format!("https://example.com/{}", name),
+            })
+        })
+        .collect();
+    serde_json::from_value(serde_json::json!({
+        "tag_name": tag,
+        "body": format!("Notes for {}", tag),
+        "html_url": format!("https://example.com/{}", tag),
+        "assets": assets,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn test_versions_are_compared_numerically() {
+    assert_eq!(parse_version("v0.10.2"), Some((0, 10, 2)));
+    assert_eq!(parse_version("1.2"), Some((1, 2, 0)));
+    assert_eq!(parse_version("v2.0.0-rc.1"), Some((2, 0, 0)));
+    assert_eq!(parse_version("nightly"), None);
+
+    let releases = vec![
+        release("v0.1.0", &[]),
+        release("v0.2.0", &[]),
+        release("v0.10.0", &[]),
+        release("v0.0.9", &[]),
+    ];
+    let newer = newer_releases(&releases, "0.1.0");
+    let tags: Vec<&str> = newer.iter().map(|r| r.tag_name.as_str()).collect();
+    assert_eq!(tags, vec!["v0.10.0", "v0.2.0"]);
+
+    let notes = changelog(&newer);
+    assert!(notes.starts_with("## v0.10.0\nNotes for v0.10.0"));
+    assert!(notes.contains("## v0.2.0"));
+}
+
+#[test]
+fn test_platform_binary_is_picked_over_archives() {
+    let release = release(
+        "v0.2.0",
+        &[
+            "ai-coder-linux-x86_64.tar.gz",
+            "ai-coder-linux-x86_64",
+            "ai-coder-darwin-arm64",
+        ],
+    );
+    assert_eq!(
+        release
+            .asset_for("linux", "x86_64")
+            .map(|a| a.name.as_str()),
+        Some("ai-coder-linux-x86_64")
+    );
+    assert_eq!(
+        release
+            .asset_for("macos", "aarch64")
+            .map(|a| a.name.as_str()),
+        Some("ai-coder-darwin-arm64")
+    );
+    assert!(release.asset_for("windows", "x86_64").is_none());
+}
+
+#[test]
+fn test_binary_is_replaced_and_backed_up() {
+    let dir = tempfile::tempdir().unwrap();
+    let exe = dir.path().join("ai-coder");
+    fs::write(&exe, b"old").unwrap();
+
+    let backup = replace_binary(&exe, b"new").unwrap();
+    assert_eq!(fs::read(&exe).unwrap(), b"new");
+    assert_eq!(fs::read(&backup).unwrap(), b"old");
+}
+
+#[test]
+fn test_binaries_are_checked_against_their_checksum() {
+    let signed = release(
+        "v0.2.0",
+        &["ai-coder-linux-x86_64", "ai-coder-linux-x86_64.sha256"],
+    );
+    let asset = signed.asset_for("linux", "x86_64").unwrap();
+    assert_eq!(
+        signed.checksum_for(asset).map(|a| a.name.as_str()),
+        Some("ai-coder-linux-x86_64.sha256")
+    );
+    let unsigned = release("v0.2.0", &["ai-coder-linux-x86_64"]);
+    assert!(unsigned.checksum_for(asset).is_none());
+
+    let sha256 = "11507a0e2f5e69d5dfa40a62a1bd7b6ee57e6bcd85c67c9b8431b36fff21c437";
+    assert!(verify_checksum(b"new", sha256).is_ok());
+    // As written by sha256sum, and in uppercase
+    assert!(verify_checksum(b"new", &format!("{}  ai-coder-linux-x86_64\n", sha256)).is_ok());
+    assert!(verify_checksum(b"new", &sha256.to_uppercase()).is_ok());
+    assert!(verify_checksum(b"old", sha256).is_err());
+    assert!(verify_checksum(b"new", "not a digest").is_err());
+    assert!(verify_checksum(b"new", "").is_err());
+}
@@ -28,6 +28,15 @@ fn test_format_duration() {
     assert_eq!(format_duration(3661), "1h 1m 1s");
 }
 
+#[test]
+fn test_format_relative_time() {
+    assert_eq!(format_relative_time(3), "just now");
+    assert_eq!(format_relative_time(42), "42s ago");
+    assert_eq!(format_relative_time(150), "2m ago");
+    assert_eq!(format_relative_time(7200), "2h ago");
+    assert_eq!(format_relative_time(200_000), "2d ago");
+}
+
 #[test]
 fn test_format_number() {
     assert_eq!(format_number(0), "0");
@@ -53,6 +62,15 @@ fn test_format_money() {
     assert_eq!(format_money(0.00001), "$0.0000");
 }
 
+#[test]
+fn test_bar() {
+    assert_eq!(bar(10, 10, 4), "████");
+    assert_eq!(bar(5, 10, 4), "██░░");
+    assert_eq!(bar(1, 100, 4), "█░░░");
+    assert_eq!(bar(0, 100, 4), "░░░░");
+    assert_eq!(bar(0, 0, 3), "░░░");
+}
+
 #[test]
 fn test_count_tokens() {
     assert_eq!(count_tokens(""), 0);
@@ -66,3 +84,18 @@ fn test_count_tokens() {
         token_count
     );
 }
+
+#[test]
+fn test_glob_matching() {
+    use ai_coder_interface_rs::utils::glob::Glob;
+    use std::path::Path;
+
+    let rs = Glob::new("*.rs").unwrap();
+    assert!(rs.is_match(Path::new("src/app/commands.rs")));
+    assert!(!rs.is_match(Path::new("README.md")));
+
+    let nested = Glob::new("src/**/*.{rs,toml}").unwrap();
+    assert!(nested.is_match(Path::new("src/lib.rs")));
+    assert!(nested.is_match(Path::new("src/ui/theme.rs")));
+    assert!(!nested.is_match(Path::new("tests/utils_test.rs")));
+}
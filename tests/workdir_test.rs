@@ -0,0 +1,59 @@
+use ai_coder_interface_rs::handlers::bash::run_streaming;
+use ai_coder_interface_rs::handlers::workdir::{cd_args, resolve};
+use std::fs;
+use std::sync::Arc;
+
+#[test]
+fn test_plain_cd_commands_are_recognized() {
+    assert_eq!(cd_args("cd src"), Some("src"));
+    assert_eq!(cd_args("  cd  "), Some(""));
+    assert_eq!(cd_args("cd 'my dir'"), Some("'my dir'"));
+    assert_eq!(cd_args("cd -"), Some("-"));
+    // Anything more runs in a shell
+    assert_eq!(cd_args("cd src && make"), None);
+    assert_eq!(cd_args("cd src; ls"), None);
+    assert_eq!(cd_args("cd $(git rev-parse --show-toplevel)"), None);
+    assert_eq!(cd_args("cdrecord disc.iso"), None);
+    assert_eq!(cd_args("ls"), None);
+}
+
+#[test]
+fn test_paths_are_resolved_from_the_current_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    fs::create_dir_all(root.join("my dir/src")).unwrap();
+    fs::write(root.join("notes.txt"), "").unwrap();
+
+    assert_eq!(resolve(&root, "'my dir'").unwrap(), root.join("my dir"));
+    assert_eq!(
+        resolve(&root.join("my dir"), "src/..").unwrap(),
+        root.join("my dir")
+    );
+    assert_eq!(resolve(&root, root.to_str().unwrap()).unwrap(), root);
+    assert!(
+        resolve(&root, "missing")
+            .unwrap_err()
+            .to_string()
+            .starts_with("No such directory")
+    );
+    assert!(
+        resolve(&root, "notes.txt")
+            .unwrap_err()
+            .to_string()
+            .starts_with("Not a directory")
+    );
+    assert!(resolve(&root, "a b").is_err());
+    assert_eq!(resolve(&root, "").unwrap(), resolve(&root, "~").unwrap());
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_commands_run_in_the_given_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path().canonicalize().unwrap();
+    let mut lines = Vec::new();
+    run_streaming("pwd", &root, Arc::default(), |line| lines.push(line))
+        .await
+        .unwrap();
+    assert_eq!(lines, vec![root.display().to_string()]);
+}
@@ -0,0 +1,83 @@
+use ai_coder_interface_rs::config::WorkspaceConfig;
+use ai_coder_interface_rs::handlers::files::{self, FileBlock};
+use ai_coder_interface_rs::handlers::workspace::{check_with, root};
+use std::path::{Path, PathBuf};
+
+#[test]
+fn test_paths_must_stay_inside_the_root() {
+    let dir = tempfile::tempdir().unwrap();
+    let base = dir.path();
+    let config = WorkspaceConfig::default();
+
+    let resolved = check_with(&config, base, Path::new("src/main.rs")).unwrap();
+    assert_eq!(resolved, root(&config, base).join("src/main.rs"));
+    assert!(check_with(&config, base, Path::new("src/../lib.rs")).is_ok());
+
+    let error = check_with(&config, base, Path::new("../outside.txt")).unwrap_err();
+    assert!(error.to_string().contains("outside the workspace"));
+    assert!(check_with(&config, base, Path::new("/etc/passwd")).is_err());
+}
+
+#[test]
+fn test_deny_globs_apply_inside_the_root() {
+    let dir = tempfile::tempdir().unwrap();
+    let base = dir.path();
+    let mut config = WorkspaceConfig::default();
+
+    let error = check_with(&config, base, Path::new("config/.env")).unwrap_err();
+    assert!(error.to_string().contains("workspace.deny"));
+    assert!(check_with(&config, base, Path::new("certs/server.pem")).is_err());
+    assert!(check_with(&config, base, Path::new(".env.example")).is_ok());
+
+    // Absolute globs match the resolved path
+    let secrets = root(&config, base).join("secrets");
+    config.deny = vec![format!("{}/**", secrets.display())];
+    assert!(check_with(&config, base, Path::new("secrets/key.txt")).is_err());
+    assert!(check_with(&config, base, Path::new(".env")).is_ok());
+}
+
+#[test]
+fn test_the_configured_root_replaces_the_working_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("project")).unwrap();
+    let config = WorkspaceConfig {
+        root: dir.path().join("project").to_string_lossy().into_owned(),
+        deny: Vec::new(),
+    };
+
+    assert!(check_with(&config, dir.path(), Path::new("project/a.rs")).is_ok());
+    assert!(check_with(&config, dir.path(), Path::new("other/a.rs")).is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_symbolic_links_cannot_lead_out_of_the_root() {
+    let dir = tempfile::tempdir().unwrap();
+    let elsewhere = tempfile::tempdir().unwrap();
+    std::os::unix::fs::symlink(elsewhere.path(), dir.path().join("link")).unwrap();
+    let config = WorkspaceConfig::default();
+
+    assert!(check_with(&config, dir.path(), Path::new("link/file.txt")).is_err());
+}
+
+#[test]
+fn test_denied_file_blocks_are_not_written() {
+    let dir = tempfile::tempdir().unwrap();
+    let blocks = vec![
+        FileBlock {
+            path: PathBuf::from("notes.txt"),
+            language: None,
+            content: "notes".to_string(),
+        },
+        FileBlock {
+            path: PathBuf::from(".env"),
+            language: None,
+            content: "TOKEN=stolen".to_string(),
+        },
+    ];
+
+    let error = files::write_all(dir.path(), &blocks).unwrap_err();
+    assert!(error.to_string().contains("no files were changed"));
+    assert!(!dir.path().join("notes.txt").exists());
+    assert!(!dir.path().join(".env").exists());
+}